@@ -0,0 +1,70 @@
+//! Memory held by one million lab results, owned versus interned.
+//!
+//! Run with `cargo bench --bench intern --features intern`.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+use wellally::catalog;
+use wellally::intern::{Interner, InternedLabResult};
+use wellally::LabResult;
+
+struct Counting;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+const RESULTS: usize = 1_000_000;
+
+/// NDJSON of `RESULTS` results cycling through every catalog analyte.
+fn ndjson() -> String {
+    let analytes: Vec<_> = catalog::PANELS.iter().flat_map(|panel| panel.analytes).collect();
+    let mut out = String::new();
+    for i in 0..RESULTS {
+        let analyte = analytes[i % analytes.len()];
+        let spread = (i % 97) as f64 / 96.0;
+        let result = analyte.result(analyte.min + (analyte.max - analyte.min) * spread);
+        out.push_str(&serde_json::to_string(&result).unwrap());
+        out.push('\n');
+    }
+    out
+}
+
+/// Parses every line into `T` and reports the bytes the parsed values retain.
+fn measure<T: serde::de::DeserializeOwned>(label: &str, input: &str) -> usize {
+    let before = ALLOCATED.load(Ordering::Relaxed);
+    let started = Instant::now();
+    let parsed: Vec<T> = input.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+    let elapsed = started.elapsed();
+    let retained = ALLOCATED.load(Ordering::Relaxed) - before;
+    println!("{:<10} {:>8.1} MiB  {:>6} ms", label, retained as f64 / 1048576.0, elapsed.as_millis());
+    drop(parsed);
+    retained
+}
+
+fn main() {
+    let input = ndjson();
+    println!("{} lab results ({:.1} MiB of NDJSON)", RESULTS, input.len() as f64 / 1048576.0);
+    let owned = measure::<LabResult>("owned", &input);
+    let interned = measure::<InternedLabResult>("interned", &input);
+    println!(
+        "interned results use {:.0}% of the owned memory ({} distinct strings)",
+        interned as f64 * 100.0 / owned as f64,
+        Interner::global().len()
+    );
+}
@@ -0,0 +1,66 @@
+//! Throughput of the bulk NDJSON readers on a large lab report export.
+//!
+//! Run with `cargo bench --bench ndjson --features simd-json`.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use chrono::{TimeZone, Utc};
+use wellally::borrowed::{ndjson, ndjson_simd, LabReportRef};
+use wellally::{catalog, LabReport};
+
+const REPORTS: usize = 100_000;
+const ROUNDS: u32 = 5;
+
+/// NDJSON export of `REPORTS` reports cycling through the catalog panels.
+fn export() -> Vec<u8> {
+    let issued_at = Utc.with_ymd_and_hms(2024, 11, 2, 9, 30, 0).unwrap();
+    let mut out = Vec::new();
+    for i in 0..REPORTS {
+        let panel = catalog::PANELS[i % catalog::PANELS.len()];
+        let spread = (i % 89) as f64 / 88.0;
+        let results = panel
+            .analytes
+            .iter()
+            .map(|analyte| analyte.result(analyte.min + (analyte.max - analyte.min) * spread))
+            .collect();
+        let report = panel.report(format!("lab-{:06}", i), format!("person-{:05}", i % 5000), issued_at, issued_at, results);
+        serde_json::to_writer(&mut out, &report).unwrap();
+        out.push(b'\n');
+    }
+    out
+}
+
+/// Best time of `ROUNDS` runs; `parse` gets a fresh copy of the export each run.
+fn bench(label: &str, export: &[u8], parse: impl Fn(&mut [u8]) -> usize) -> Duration {
+    let mut best = Duration::MAX;
+    for _ in 0..ROUNDS {
+        let mut buffer = export.to_vec();
+        let started = Instant::now();
+        assert_eq!(black_box(parse(&mut buffer)), REPORTS);
+        best = best.min(started.elapsed());
+    }
+    let mib_per_s = export.len() as f64 / 1048576.0 / best.as_secs_f64();
+    println!("{:<28} {:>7.1} ms  {:>7.1} MiB/s", label, best.as_secs_f64() * 1000.0, mib_per_s);
+    best
+}
+
+fn main() {
+    let export = export();
+    println!("{} lab reports ({:.1} MiB of NDJSON)", REPORTS, export.len() as f64 / 1048576.0);
+
+    bench("serde_json  LabReport", &export, |buffer| {
+        let text = std::str::from_utf8(buffer).unwrap();
+        ndjson::<LabReport>(text).map(Result::unwrap).count()
+    });
+    bench("simd-json   LabReport", &export, |buffer| {
+        ndjson_simd::<LabReport>(buffer).map(Result::unwrap).count()
+    });
+    bench("serde_json  LabReportRef", &export, |buffer| {
+        let text = std::str::from_utf8(buffer).unwrap();
+        ndjson::<LabReportRef>(text).map(Result::unwrap).count()
+    });
+    bench("simd-json   LabReportRef", &export, |buffer| {
+        ndjson_simd::<LabReportRef>(buffer).map(Result::unwrap).count()
+    });
+}
@@ -0,0 +1,30 @@
+-- WellAlly reference schema (PostgreSQL).
+--
+-- Every top-level resource is kept as one row in `resources`, keyed by its
+-- resource type and id. The canonical JSON document lives in the `body`
+-- JSONB column; the remaining columns are denormalised from it for indexed
+-- lookups by patient and for incremental sync.
+
+CREATE TABLE IF NOT EXISTS resources (
+    -- Resource type name, e.g. 'Person', 'LabReport', 'MedicationRecord'
+    resource_type TEXT        NOT NULL,
+    -- Resource id (Person.id, LabReport.id, FamilyHealthTree.probandId, ...)
+    id            TEXT        NOT NULL,
+    -- Person.id the resource belongs to
+    patient_id    TEXT        NOT NULL,
+    -- Time of the last write
+    updated_at    TIMESTAMPTZ NOT NULL DEFAULT clock_timestamp(),
+    -- Canonical JSON document
+    body          JSONB       NOT NULL,
+    PRIMARY KEY (resource_type, id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_resources_patient
+    ON resources (patient_id, resource_type);
+
+CREATE INDEX IF NOT EXISTS idx_resources_updated_at
+    ON resources (resource_type, updated_at);
+
+-- Supports containment queries such as body @> '{"results": [{"interpretation": "H"}]}'
+CREATE INDEX IF NOT EXISTS idx_resources_body
+    ON resources USING GIN (body jsonb_path_ops);
@@ -0,0 +1,28 @@
+-- WellAlly local store schema (SQLite).
+--
+-- Every top-level resource is kept as one row in `resources`, keyed by its
+-- resource type and id. The canonical JSON document lives in `body`; the
+-- remaining columns are denormalised from it so the store can be queried by
+-- patient and synced incrementally without parsing documents.
+
+CREATE TABLE IF NOT EXISTS resources (
+    -- Resource type name, e.g. 'Person', 'LabReport', 'MedicationRecord'
+    resource_type TEXT    NOT NULL,
+    -- Resource id (Person.id, LabReport.id, FamilyHealthTree.probandId, ...)
+    id            TEXT    NOT NULL,
+    -- Person.id the resource belongs to
+    patient_id    TEXT    NOT NULL,
+    -- Last write in Unix epoch milliseconds (UTC), strictly increasing per store
+    updated_at    INTEGER NOT NULL,
+    -- 1 when the resource has been deleted (tombstone kept for sync)
+    deleted       INTEGER NOT NULL DEFAULT 0,
+    -- Canonical JSON document; NULL for tombstones
+    body          TEXT,
+    PRIMARY KEY (resource_type, id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_resources_patient
+    ON resources (patient_id, resource_type);
+
+CREATE INDEX IF NOT EXISTS idx_resources_updated_at
+    ON resources (resource_type, updated_at);
@@ -0,0 +1,206 @@
+//! Allergy and intolerance data model.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//! Schema: https://wellall.health/schemas/allergy-intolerance/v0.1.0
+//!
+//! An [`AllergyIntolerance`] records a patient's propensity to react to a
+//! substance (a drug ingredient, food or environmental agent) with the
+//! reactions seen so far. Prescriptions are checked against recorded
+//! allergies with [`safety::allergy_check`](crate::safety::allergy_check).
+
+use alloc::{string::String, vec::Vec};
+use serde::{Deserialize, Serialize};
+
+use crate::common::{Annotation, CodeableConcept, Coding};
+use crate::condition::VerificationStatus;
+use crate::datetime::FlexibleDateTime;
+use crate::extension::Extension;
+
+/// Clinical status of an allergy
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "async-graphql", derive(async_graphql::Enum))]
+#[serde(rename_all = "lowercase")]
+pub enum AllergyClinicalStatus {
+    Active,
+    Inactive,
+    /// Shown to no longer apply, e.g. after desensitization
+    Resolved,
+}
+
+/// Immune-mediated allergy or non-immune intolerance
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "async-graphql", derive(async_graphql::Enum))]
+#[serde(rename_all = "lowercase")]
+pub enum AllergyType {
+    Allergy,
+    Intolerance,
+}
+
+/// Kind of substance
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "async-graphql", derive(async_graphql::Enum))]
+#[serde(rename_all = "lowercase")]
+pub enum AllergyCategory {
+    Food,
+    Medication,
+    Environment,
+    /// Blood products, vaccines and other biologics
+    Biologic,
+}
+
+/// Potential seriousness of a future reaction
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "async-graphql", derive(async_graphql::Enum))]
+#[serde(rename_all = "kebab-case")]
+pub enum Criticality {
+    Low,
+    /// May be life-threatening or cause organ damage
+    High,
+    UnableToAssess,
+}
+
+/// Severity of a reaction that occurred
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "async-graphql", derive(async_graphql::Enum))]
+#[serde(rename_all = "lowercase")]
+pub enum ReactionSeverity {
+    Mild,
+    Moderate,
+    Severe,
+}
+
+/// A reaction to the substance.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "AllergyReactionInput")
+)]
+pub struct AllergyReaction {
+    /// Signs and symptoms (e.g., SNOMED CT urticaria, anaphylaxis)
+    pub manifestation: Vec<CodeableConcept>,
+    /// Severity of the reaction
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub severity: Option<ReactionSeverity>,
+    /// When the reaction began
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "utoipa", schema(value_type = Option<String>))]
+    pub onset: Option<FlexibleDateTime>,
+    /// Free-text description
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+/// An allergy or intolerance of a patient.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "AllergyIntoleranceInput")
+)]
+pub struct AllergyIntolerance {
+    /// Unique allergy identifier
+    pub id: String,
+    /// Reference to Person.id
+    #[serde(rename = "patientId", alias = "patient_id")]
+    pub patient_id: String,
+    /// Clinical status
+    #[serde(rename = "clinicalStatus", alias = "clinical_status", skip_serializing_if = "Option::is_none")]
+    pub clinical_status: Option<AllergyClinicalStatus>,
+    /// Verification status
+    #[serde(rename = "verificationStatus", alias = "verification_status", skip_serializing_if = "Option::is_none")]
+    pub verification_status: Option<VerificationStatus>,
+    /// Allergy or intolerance
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub allergy_type: Option<AllergyType>,
+    /// Kinds of substance
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<Vec<AllergyCategory>>,
+    /// Potential seriousness of a future reaction
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub criticality: Option<Criticality>,
+    /// The substance (RxNorm ingredient, SNOMED CT substance or text)
+    pub code: CodeableConcept,
+    /// When the allergy was first noticed; may be partial (e.g., "2010")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "utoipa", schema(value_type = Option<String>))]
+    pub onset: Option<FlexibleDateTime>,
+    /// When the allergy was recorded
+    #[serde(rename = "recordedDate", alias = "recorded_date", skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "utoipa", schema(value_type = Option<String>))]
+    pub recorded_date: Option<FlexibleDateTime>,
+    /// Reactions that occurred
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reactions: Option<Vec<AllergyReaction>>,
+    /// Free-text notes and comments
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notes: Option<Vec<Annotation>>,
+    /// Schema version the resource was written against (e.g., "0.1.0"); absent means 0.1.0
+    #[serde(rename = "schemaVersion", alias = "schema_version", skip_serializing_if = "Option::is_none")]
+    pub schema_version: Option<String>,
+    /// Confidentiality and sensitivity labels (e.g., v3 ActCode HIV); see [`crate::security`]
+    #[serde(rename = "securityLabels", alias = "security_labels", skip_serializing_if = "Option::is_none")]
+    pub security_labels: Option<Vec<Coding>>,
+    /// Site-specific extensions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extensions: Option<Vec<Extension>>,
+    /// Properties this model version does not define, kept for re-serialization
+    #[cfg(feature = "preserve-unknown")]
+    #[serde(flatten)]
+    #[cfg_attr(feature = "schemars", schemars(skip))]
+    #[cfg_attr(feature = "utoipa", schema(ignore))]
+    #[cfg_attr(feature = "async-graphql", graphql(skip))]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl AllergyIntolerance {
+    /// Active allergy to `code`.
+    pub fn new(id: impl Into<String>, patient_id: impl Into<String>, code: CodeableConcept) -> Self {
+        AllergyIntolerance {
+            id: id.into(),
+            patient_id: patient_id.into(),
+            clinical_status: Some(AllergyClinicalStatus::Active),
+            verification_status: None,
+            allergy_type: None,
+            category: None,
+            criticality: None,
+            code,
+            onset: None,
+            recorded_date: None,
+            reactions: None,
+            notes: None,
+            schema_version: None,
+            security_labels: None,
+            extensions: None,
+            #[cfg(feature = "preserve-unknown")]
+            extra: Default::default(),
+        }
+    }
+
+    /// Whether the allergy still applies: not inactive or resolved, and not
+    /// refuted or entered in error. A missing clinical status counts as active.
+    pub fn is_active(&self) -> bool {
+        !matches!(self.clinical_status, Some(AllergyClinicalStatus::Inactive | AllergyClinicalStatus::Resolved))
+            && !matches!(self.verification_status, Some(VerificationStatus::Refuted | VerificationStatus::EnteredInError))
+    }
+
+    /// Most severe reaction recorded, if any has a severity.
+    pub fn worst_reaction(&self) -> Option<ReactionSeverity> {
+        self.reactions.iter().flatten().filter_map(|reaction| reaction.severity).max()
+    }
+}
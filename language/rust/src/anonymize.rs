@@ -0,0 +1,144 @@
+//! Pseudonymization of resources for sharing outside the care team.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//!
+//! An [`Anonymizer`] replaces every resource id, patient id and reference
+//! target with a salted hash, so records of one patient stay linked to each
+//! other but not to the source system, and strips the direct identifiers of
+//! a person: names, contact points, addresses and identifiers, keeping only
+//! the birth year. The same salt gives the same pseudonyms across runs; keep
+//! it secret, as anyone holding it can test guessed ids.
+//!
+//! This is a baseline for test and analytics extracts. Free text (notes,
+//! conclusions) is kept as is and may still identify a patient. Extracts
+//! that must not hold sensitive categories at all (mental health, HIV, ...)
+//! withhold resources by security label with [`Anonymizer::withholding`];
+//! labels derived from a resource's codes count as well as explicit ones.
+//!
+//! ```
+//! use wellally::anonymize::Anonymizer;
+//! use wellally::io::AnyResource;
+//!
+//! let person: AnyResource = serde_json::from_value(serde_json::json!({
+//!     "id": "p-1", "resourceType": "Person", "birthDate": "1980-04-12",
+//!     "name": [{"family": "Doe", "given": ["Jane"]}],
+//!     "telecom": [{"system": "phone", "value": "+1 555 0100"}]
+//! }))
+//! .unwrap();
+//! let anonymizer = Anonymizer::new("site-secret");
+//! let shared = serde_json::to_value(anonymizer.anonymize(&person)?).unwrap();
+//! assert_eq!(shared["id"], anonymizer.pseudonym("p-1"));
+//! assert_eq!(shared["birthDate"], "1980");
+//! assert!(shared.get("telecom").is_none());
+//! # Ok::<(), wellally::WellAllyError>(())
+//! ```
+
+use serde_json::{Map, Value};
+use sha1::{Digest, Sha1};
+
+use crate::common::Coding;
+use crate::error::WellAllyError;
+use crate::io::AnyResource;
+use crate::security;
+
+/// Family name given to every anonymized person.
+pub const ANONYMOUS: &str = "Anonymous";
+
+/// Fields holding direct identifiers, removed wherever they appear.
+const IDENTIFYING_FIELDS: &[&str] = &["telecom", "address", "identifier"];
+
+/// Replaces ids with salted pseudonyms and strips direct identifiers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Anonymizer {
+    salt: String,
+    /// System and code of the labels whose resources are left out
+    withheld: Vec<(String, String)>,
+}
+
+impl Anonymizer {
+    /// Anonymizer whose pseudonyms are derived with `salt`.
+    pub fn new(salt: impl Into<String>) -> Self {
+        Anonymizer { salt: salt.into(), withheld: Vec::new() }
+    }
+
+    /// Leaves resources labeled with any of `labels` (e.g.,
+    /// [`security::category`]`(`[`security::HIV`]`)`) out of
+    /// [`Anonymizer::anonymize_all`].
+    pub fn withholding(mut self, labels: impl IntoIterator<Item = Coding>) -> Self {
+        self.withheld.extend(labels.into_iter().map(|label| (label.system, label.code)));
+        self
+    }
+
+    /// Whether `resource` carries a withheld label, explicit or derived with
+    /// [`security::sensitivity`].
+    pub fn is_withheld(&self, resource: &AnyResource) -> bool {
+        !self.withheld.is_empty()
+            && security::classify(resource)
+                .iter()
+                .any(|label| self.withheld.iter().any(|(system, code)| *system == label.system && *code == label.code))
+    }
+
+    /// Pseudonym of `id`: the first 16 hex digits of the salted SHA-1.
+    pub fn pseudonym(&self, id: &str) -> String {
+        let digest = Sha1::new().chain_update(self.salt.as_bytes()).chain_update([0]).chain_update(id).finalize();
+        digest.iter().take(8).map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    /// Anonymized copy of `resource`.
+    pub fn anonymize(&self, resource: &AnyResource) -> Result<AnyResource, WellAllyError> {
+        let mut value = serde_json::to_value(resource).map_err(|err| WellAllyError::Conversion {
+            resource_type: resource.resource_type().to_string(),
+            path: String::new(),
+            value: None,
+            message: err.to_string(),
+        })?;
+        if let Value::Object(object) = &mut value {
+            for field in ["id", "patientId"] {
+                if let Some(Value::String(id)) = object.get_mut(field) {
+                    *id = self.pseudonym(id);
+                }
+            }
+            if matches!(resource, AnyResource::Person(_)) {
+                self.strip_person(object);
+            }
+        }
+        self.strip(&mut value);
+        AnyResource::from_json_value(&value)
+    }
+
+    /// Anonymized copies of the resources that are not withheld, in order.
+    pub fn anonymize_all(&self, resources: &[AnyResource]) -> Result<Vec<AnyResource>, WellAllyError> {
+        let shared = resources.iter().filter(|resource| !self.is_withheld(resource));
+        shared.map(|resource| self.anonymize(resource)).collect()
+    }
+
+    fn strip_person(&self, person: &mut Map<String, Value>) {
+        person.insert("name".to_string(), serde_json::json!([{ "family": ANONYMOUS, "given": [] }]));
+        if let Some(Value::String(birth_date)) = person.get_mut("birthDate") {
+            birth_date.truncate(4);
+        }
+    }
+
+    /// Removes identifying fields and pseudonymizes reference targets at any depth.
+    fn strip(&self, value: &mut Value) {
+        match value {
+            Value::Object(object) => {
+                object.retain(|field, _| !IDENTIFYING_FIELDS.contains(&field.as_str()));
+                if let Some(Value::String(reference)) = object.get_mut("reference") {
+                    if let Some((resource_type, id)) = reference.split_once('/') {
+                        *reference = format!("{}/{}", resource_type, self.pseudonym(id));
+                    }
+                }
+                object.values_mut().for_each(|value| self.strip(value));
+            }
+            Value::Array(items) => items.iter_mut().for_each(|item| self.strip(item)),
+            _ => {}
+        }
+    }
+}
+
+/// Anonymized copies of `resources` with pseudonyms derived from `salt`.
+pub fn anonymize(resources: &[AnyResource], salt: &str) -> Result<Vec<AnyResource>, WellAllyError> {
+    Anonymizer::new(salt).anonymize_all(resources)
+}
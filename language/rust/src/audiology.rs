@@ -0,0 +1,379 @@
+//! Audiometry model.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//! Schema: https://wellall.health/schemas/audiology/v0.1.0
+//!
+//! An [`AudiogramReport`] holds one hearing test: per ear, the pure-tone
+//! thresholds in dB HL across the tested frequencies, speech recognition and
+//! the tympanogram type. The helpers cover the two common screening uses:
+//!
+//! - [`AudiogramReport::passes_screening`] for pediatric and school screening
+//!   at a fixed level;
+//! - [`standard_threshold_shift`] compares an occupational audiogram with
+//!   the worker's baseline as OSHA 29 CFR 1910.95 does.
+//!
+//! ```
+//! use wellally::audiology::{AudiogramReport, Ear, EarResult, HearingGrade, Threshold};
+//!
+//! let mut left = EarResult::new(Ear::Left);
+//! for (frequency, level) in [(500, 15.0), (1000, 20.0), (2000, 30.0), (4000, 55.0)] {
+//!     left.thresholds.push(Threshold::air(frequency, level));
+//! }
+//! let mut report = AudiogramReport::new("aud-1", "p-1", "2024-10-08".parse().unwrap());
+//! report.ears.push(left);
+//! assert_eq!(report.ear(Ear::Left).unwrap().pure_tone_average(), Some(30.0));
+//! assert_eq!(report.grade(Ear::Left), Some(HearingGrade::Mild));
+//! assert!(!report.passes_screening(20.0));
+//! ```
+
+use alloc::{string::String, vec::Vec};
+
+use serde::{Deserialize, Serialize};
+
+use crate::common::{Annotation, Coding, DataSource, HasDataSource, Reference};
+use crate::datetime::FlexibleDateTime;
+use crate::extension::Extension;
+
+/// Frequencies of the WHO four-frequency pure-tone average, in Hz
+pub const PTA_FREQUENCIES: [u32; 4] = [500, 1000, 2000, 4000];
+/// Frequencies OSHA averages for a standard threshold shift, in Hz
+pub const STS_FREQUENCIES: [u32; 3] = [2000, 3000, 4000];
+
+/// Ear tested.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "async-graphql", derive(async_graphql::Enum))]
+#[serde(rename_all = "kebab-case")]
+pub enum Ear {
+    Left,
+    Right,
+}
+
+/// Path by which the test tone reaches the cochlea.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "async-graphql", derive(async_graphql::Enum))]
+#[serde(rename_all = "kebab-case")]
+pub enum Conduction {
+    /// Through earphones or insert phones
+    #[default]
+    Air,
+    /// Through a bone vibrator on the mastoid
+    Bone,
+}
+
+impl Conduction {
+    /// Whether the tone was presented by air conduction.
+    pub fn is_air(&self) -> bool {
+        *self == Conduction::Air
+    }
+}
+
+/// Jerger tympanogram type.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "async-graphql", derive(async_graphql::Enum))]
+pub enum TympanogramType {
+    /// Normal middle ear pressure and compliance
+    A,
+    /// Shallow peak, as in otosclerosis
+    As,
+    /// Deep peak, as in ossicular discontinuity
+    Ad,
+    /// Flat, as in middle ear effusion or perforation
+    B,
+    /// Peak at negative pressure, as in Eustachian tube dysfunction
+    C,
+}
+
+impl TympanogramType {
+    /// Whether the tympanogram is normal (type A).
+    pub fn is_normal(&self) -> bool {
+        *self == TympanogramType::A
+    }
+}
+
+/// Hearing threshold at one frequency.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "ThresholdInput")
+)]
+pub struct Threshold {
+    /// Test frequency in Hz (e.g., 1000)
+    #[serde(rename = "frequencyHz", alias = "frequency_hz")]
+    pub frequency_hz: u32,
+    /// Hearing level in dB HL
+    #[serde(rename = "levelDb", alias = "level_db")]
+    pub level_db: f64,
+    /// Air conduction when absent
+    #[serde(default, skip_serializing_if = "Conduction::is_air")]
+    pub conduction: Conduction,
+    /// Whether the other ear was masked
+    #[serde(default, skip_serializing_if = "core::ops::Not::not")]
+    pub masked: bool,
+    /// No response up to the audiometer limit; `levelDb` is then the highest level presented
+    #[serde(rename = "noResponse", alias = "no_response", default, skip_serializing_if = "core::ops::Not::not")]
+    pub no_response: bool,
+}
+
+impl Threshold {
+    /// Unmasked air-conduction threshold.
+    pub fn air(frequency_hz: u32, level_db: f64) -> Self {
+        Threshold { frequency_hz, level_db, conduction: Conduction::Air, masked: false, no_response: false }
+    }
+
+    /// Unmasked bone-conduction threshold.
+    pub fn bone(frequency_hz: u32, level_db: f64) -> Self {
+        Threshold { conduction: Conduction::Bone, ..Threshold::air(frequency_hz, level_db) }
+    }
+}
+
+/// Speech audiometry of one ear.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "SpeechRecognitionInput")
+)]
+pub struct SpeechRecognition {
+    /// Word recognition score, percent of words repeated correctly
+    #[serde(rename = "scorePercent", alias = "score_percent")]
+    pub score_percent: f64,
+    /// Level the word list was presented at, in dB HL
+    #[serde(rename = "presentationLevelDb", alias = "presentation_level_db", skip_serializing_if = "Option::is_none")]
+    pub presentation_level_db: Option<f64>,
+    /// Speech reception threshold in dB HL
+    #[serde(rename = "receptionThresholdDb", alias = "reception_threshold_db", skip_serializing_if = "Option::is_none")]
+    pub reception_threshold_db: Option<f64>,
+}
+
+/// Results of one ear.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "EarResultInput")
+)]
+pub struct EarResult {
+    pub ear: Ear,
+    /// Pure-tone thresholds, air and bone conduction
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub thresholds: Vec<Threshold>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub speech: Option<SpeechRecognition>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tympanometry: Option<TympanogramType>,
+}
+
+impl EarResult {
+    /// Ear not tested yet.
+    pub fn new(ear: Ear) -> Self {
+        EarResult { ear, thresholds: Vec::new(), speech: None, tympanometry: None }
+    }
+
+    /// Threshold at `frequency_hz` by `conduction`.
+    pub fn threshold(&self, frequency_hz: u32, conduction: Conduction) -> Option<&Threshold> {
+        self.thresholds
+            .iter()
+            .find(|threshold| threshold.frequency_hz == frequency_hz && threshold.conduction == conduction)
+    }
+
+    /// Mean air-conduction threshold at `frequencies`; `None` unless all were tested.
+    pub fn average(&self, frequencies: &[u32]) -> Option<f64> {
+        let mut sum = 0.0;
+        for frequency in frequencies {
+            sum += self.threshold(*frequency, Conduction::Air)?.level_db;
+        }
+        (!frequencies.is_empty()).then(|| sum / frequencies.len() as f64)
+    }
+
+    /// WHO four-frequency pure-tone average (0.5, 1, 2 and 4 kHz).
+    pub fn pure_tone_average(&self) -> Option<f64> {
+        self.average(&PTA_FREQUENCIES)
+    }
+
+    /// Air-conduction minus bone-conduction threshold at `frequency_hz`; a
+    /// gap of 10 dB or more points to a conductive loss.
+    pub fn air_bone_gap(&self, frequency_hz: u32) -> Option<f64> {
+        let air = self.threshold(frequency_hz, Conduction::Air)?;
+        let bone = self.threshold(frequency_hz, Conduction::Bone)?;
+        Some(air.level_db - bone.level_db)
+    }
+}
+
+/// WHO grade of hearing loss (World Report on Hearing, 2021), from the
+/// four-frequency pure-tone average of an ear.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum HearingGrade {
+    /// Below 20 dB
+    Normal,
+    /// 20 to below 35 dB
+    Mild,
+    /// 35 to below 50 dB
+    Moderate,
+    /// 50 to below 65 dB
+    ModeratelySevere,
+    /// 65 to below 80 dB
+    Severe,
+    /// 80 to below 95 dB
+    Profound,
+    /// 95 dB and above
+    Complete,
+}
+
+impl HearingGrade {
+    /// WHO grade of a pure-tone average in dB HL.
+    pub fn who(average_db: f64) -> Self {
+        match average_db {
+            a if a < 20.0 => HearingGrade::Normal,
+            a if a < 35.0 => HearingGrade::Mild,
+            a if a < 50.0 => HearingGrade::Moderate,
+            a if a < 65.0 => HearingGrade::ModeratelySevere,
+            a if a < 80.0 => HearingGrade::Severe,
+            a if a < 95.0 => HearingGrade::Profound,
+            _ => HearingGrade::Complete,
+        }
+    }
+}
+
+/// Why the test was done.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "async-graphql", derive(async_graphql::Enum))]
+#[serde(rename_all = "kebab-case")]
+pub enum AudiometryPurpose {
+    Diagnostic,
+    /// Newborn, school or other population screening
+    Screening,
+    /// First audiogram of a hearing conservation program
+    OccupationalBaseline,
+    /// Periodic audiogram of a hearing conservation program
+    OccupationalAnnual,
+}
+
+/// One hearing test.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "AudiogramReportInput")
+)]
+pub struct AudiogramReport {
+    /// Unique report identifier
+    pub id: String,
+    /// Reference to Person.id
+    #[serde(rename = "patientId", alias = "patient_id")]
+    pub patient_id: String,
+    /// When the test was done
+    #[serde(rename = "testedAt", alias = "tested_at")]
+    #[cfg_attr(feature = "utoipa", schema(value_type = String))]
+    pub tested_at: FlexibleDateTime,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub purpose: Option<AudiometryPurpose>,
+    /// Results per ear
+    pub ears: Vec<EarResult>,
+    /// Audiologist or technician who ran the test (e.g., "Practitioner/aud-liu")
+    #[serde(rename = "testedBy", alias = "tested_by", skip_serializing_if = "Option::is_none")]
+    pub tested_by: Option<Reference>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notes: Option<Vec<Annotation>>,
+    /// Audiometer, app and capture method of the record
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<DataSource>,
+    /// Schema version the resource was written against (e.g., "0.1.0"); absent means 0.1.0
+    #[serde(rename = "schemaVersion", alias = "schema_version", skip_serializing_if = "Option::is_none")]
+    pub schema_version: Option<String>,
+    /// Confidentiality and sensitivity labels (e.g., v3 ActCode HIV); see [`crate::security`]
+    #[serde(rename = "securityLabels", alias = "security_labels", skip_serializing_if = "Option::is_none")]
+    pub security_labels: Option<Vec<Coding>>,
+    /// Site-specific extensions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extensions: Option<Vec<Extension>>,
+    /// Properties this model version does not define, kept for re-serialization
+    #[cfg(feature = "preserve-unknown")]
+    #[serde(flatten)]
+    #[cfg_attr(feature = "schemars", schemars(skip))]
+    #[cfg_attr(feature = "utoipa", schema(ignore))]
+    #[cfg_attr(feature = "async-graphql", graphql(skip))]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl AudiogramReport {
+    /// Report without results yet.
+    pub fn new(id: impl Into<String>, patient_id: impl Into<String>, tested_at: FlexibleDateTime) -> Self {
+        AudiogramReport {
+            id: id.into(),
+            patient_id: patient_id.into(),
+            tested_at,
+            purpose: None,
+            ears: Vec::new(),
+            tested_by: None,
+            notes: None,
+            source: None,
+            schema_version: None,
+            security_labels: None,
+            extensions: None,
+            #[cfg(feature = "preserve-unknown")]
+            extra: Default::default(),
+        }
+    }
+
+    /// Results of `ear`, if tested.
+    pub fn ear(&self, ear: Ear) -> Option<&EarResult> {
+        self.ears.iter().find(|result| result.ear == ear)
+    }
+
+    /// WHO grade of `ear`, if its pure-tone average can be taken.
+    pub fn grade(&self, ear: Ear) -> Option<HearingGrade> {
+        self.ear(ear)?.pure_tone_average().map(HearingGrade::who)
+    }
+
+    /// Whether every air-conduction tone was heard at or below `level_db`
+    /// (e.g., 20 dB HL in school screening). A report without air-conduction
+    /// thresholds does not pass.
+    pub fn passes_screening(&self, level_db: f64) -> bool {
+        let mut tones = self
+            .ears
+            .iter()
+            .flat_map(|ear| &ear.thresholds)
+            .filter(|tone| tone.conduction == Conduction::Air)
+            .peekable();
+        tones.peek().is_some() && tones.all(|tone| !tone.no_response && tone.level_db <= level_db)
+    }
+}
+
+impl HasDataSource for AudiogramReport {
+    fn data_source(&self) -> Option<&DataSource> {
+        self.source.as_ref()
+    }
+}
+
+/// Ears of `current` whose average threshold at 2, 3 and 4 kHz rose by 10 dB
+/// or more over `baseline`: an OSHA standard threshold shift. Thresholds are
+/// compared as measured, without the optional age correction; ears missing a
+/// frequency in either audiogram are left out.
+pub fn standard_threshold_shift(baseline: &AudiogramReport, current: &AudiogramReport) -> Vec<Ear> {
+    [Ear::Left, Ear::Right]
+        .into_iter()
+        .filter(|ear| {
+            let before = baseline.ear(*ear).and_then(|result| result.average(&STS_FREQUENCIES));
+            let after = current.ear(*ear).and_then(|result| result.average(&STS_FREQUENCIES));
+            matches!((before, after), (Some(before), Some(after)) if after - before >= 10.0)
+        })
+        .collect()
+}
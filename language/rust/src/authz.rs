@@ -0,0 +1,289 @@
+//! SMART on FHIR scopes and access checks.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//!
+//! A [`Scope`] is one OAuth 2.0 scope in SMART syntax: a context
+//! (`patient`, `user` or `system`), a resource type or `*`, and the actions
+//! it allows, either in SMART v1 form (`read`, `write`, `*`) or as SMART v2
+//! letters (`c`, `r`, `u`, `d`, `s`, in that order). Resource types are the
+//! names of this crate's resources (`LabReport`, `MedicationRecord`, ...).
+//!
+//! `patient` scopes only grant access within the patient the token was
+//! launched for. [`Authorization`] holds the scopes of a token with that
+//! patient and checks whole resources, so services enforce the same rules
+//! where they load and store records:
+//!
+//! ```
+//! use wellally::authz::{Action, Authorization, Scope};
+//!
+//! let scope: Scope = "patient/LabReport.rs".parse()?;
+//! assert!(scope.permits("LabReport", Action::Search, Some("p-1")));
+//! assert!(!scope.permits("LabReport", Action::Update, Some("p-1")));
+//! assert!(!scope.permits("LabReport", Action::Read, None));
+//!
+//! let token = Authorization::parse("openid launch/patient patient/*.read")?.with_patient("p-1");
+//! assert!(token.permits("MedicationRecord", Action::Read, "p-1"));
+//! assert!(!token.permits("MedicationRecord", Action::Read, "p-2"));
+//! # Ok::<(), wellally::WellAllyError>(())
+//! ```
+
+use alloc::{string::{String, ToString}, vec::Vec};
+use core::fmt;
+use core::str::FromStr;
+
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::error::WellAllyError;
+use crate::resource::Resource;
+
+/// Whose data a scope covers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Context {
+    /// The patient in the launch context
+    Patient,
+    /// Whatever the signed-in user may access
+    User,
+    /// A backend service, without a user
+    System,
+}
+
+impl Context {
+    /// Scope prefix (e.g., "patient")
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Context::Patient => "patient",
+            Context::User => "user",
+            Context::System => "system",
+        }
+    }
+}
+
+/// An operation on resources.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    Create,
+    Read,
+    Update,
+    Delete,
+    Search,
+}
+
+impl Action {
+    /// Every action, in SMART v2 letter order
+    pub const ALL: [Action; 5] = [Action::Create, Action::Read, Action::Update, Action::Delete, Action::Search];
+
+    /// SMART v2 letter of the action
+    pub fn letter(&self) -> char {
+        match self {
+            Action::Create => 'c',
+            Action::Read => 'r',
+            Action::Update => 'u',
+            Action::Delete => 'd',
+            Action::Search => 's',
+        }
+    }
+
+    fn bit(&self) -> u8 {
+        1 << Action::ALL.iter().position(|action| action == self).unwrap_or_default()
+    }
+}
+
+/// A SMART resource scope such as `patient/LabReport.read` or `user/*.cruds`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Scope {
+    source: String,
+    context: Context,
+    resource_type: Option<String>,
+    actions: u8,
+}
+
+impl Scope {
+    /// Parses a resource scope. Scopes with parameters (`?category=...`)
+    /// are rejected rather than granted without the restriction.
+    pub fn parse(source: &str) -> Result<Self, WellAllyError> {
+        let invalid = |message: &str| scope_error(source, message);
+        if source.contains('?') {
+            return Err(invalid("scope parameters are not supported"));
+        }
+        let (context, rest) = source.split_once('/').ok_or_else(|| invalid("expected context/Type.actions"))?;
+        let context = match context {
+            "patient" => Context::Patient,
+            "user" => Context::User,
+            "system" => Context::System,
+            _ => return Err(invalid("context must be patient, user or system")),
+        };
+        let (resource_type, actions) = rest.rsplit_once('.').ok_or_else(|| invalid("expected Type.actions"))?;
+        if resource_type.is_empty() || !resource_type.chars().all(|c| c == '*' || c.is_ascii_alphanumeric()) {
+            return Err(invalid("invalid resource type"));
+        }
+        let bits = |actions: &[Action]| actions.iter().fold(0, |bits, action| bits | action.bit());
+        let actions = match actions {
+            "*" => bits(&Action::ALL),
+            "read" => bits(&[Action::Read, Action::Search]),
+            "write" => bits(&[Action::Create, Action::Update, Action::Delete]),
+            letters => {
+                let unknown = || invalid("actions must be read, write, * or letters of cruds in order");
+                let mut remaining = Action::ALL.iter();
+                let mut actions = 0;
+                for letter in letters.chars() {
+                    actions |= remaining.find(|action| action.letter() == letter).ok_or_else(unknown)?.bit();
+                }
+                if actions == 0 {
+                    return Err(invalid("no actions"));
+                }
+                actions
+            }
+        };
+        Ok(Scope {
+            source: source.to_string(),
+            context,
+            resource_type: (resource_type != "*").then(|| resource_type.to_string()),
+            actions,
+        })
+    }
+
+    /// Scope text as parsed
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// Whose data the scope covers
+    pub fn context(&self) -> Context {
+        self.context
+    }
+
+    /// Resource type the scope covers; `None` for `*`
+    pub fn resource_type(&self) -> Option<&str> {
+        self.resource_type.as_deref()
+    }
+
+    /// Whether the scope allows `action`
+    pub fn allows(&self, action: Action) -> bool {
+        self.actions & action.bit() != 0
+    }
+
+    /// Whether the scope allows `action` on resources of `resource_type`.
+    /// `patient_context` is the patient the request is bound to; `patient`
+    /// scopes grant nothing without one.
+    pub fn permits(&self, resource_type: &str, action: Action, patient_context: Option<&str>) -> bool {
+        self.resource_type.as_deref().is_none_or(|covered| covered == resource_type)
+            && self.allows(action)
+            && (self.context != Context::Patient || patient_context.is_some())
+    }
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+impl FromStr for Scope {
+    type Err = WellAllyError;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        Scope::parse(source)
+    }
+}
+
+impl Serialize for Scope {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.source)
+    }
+}
+
+impl<'de> Deserialize<'de> for Scope {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let source = String::deserialize(deserializer)?;
+        Scope::parse(&source).map_err(D::Error::custom)
+    }
+}
+
+#[cfg(feature = "async-graphql")]
+async_graphql::scalar!(Scope, "SmartScope", "SMART resource scope (e.g., patient/LabReport.read)");
+
+#[cfg(feature = "schemars")]
+impl schemars::JsonSchema for Scope {
+    fn inline_schema() -> bool {
+        true
+    }
+
+    fn schema_name() -> alloc::borrow::Cow<'static, str> {
+        "SmartScope".into()
+    }
+
+    fn json_schema(_: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        schemars::json_schema!({
+            "type": "string",
+            "pattern": SCOPE_PATTERN
+        })
+    }
+}
+
+#[cfg(feature = "schemars")]
+const SCOPE_PATTERN: &str = "^(patient|user|system)/[A-Za-z0-9*]+\\.(read|write|\\*|c?r?u?d?s?)$";
+
+/// The resource scopes of an access token and the patient it was launched for.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Authorization {
+    scopes: Vec<Scope>,
+    patient: Option<String>,
+}
+
+impl Authorization {
+    /// Authorization with the given resource scopes and no patient context.
+    pub fn new(scopes: Vec<Scope>) -> Self {
+        Authorization { scopes, patient: None }
+    }
+
+    /// Parses a space-separated OAuth `scope` value, skipping scopes that are
+    /// not resource scopes (`openid`, `launch/patient`, `offline_access`, ...).
+    pub fn parse(scope: &str) -> Result<Self, WellAllyError> {
+        let resource_scopes = scope.split_whitespace().filter(|scope| {
+            scope.split_once('/').is_some_and(|(context, _)| matches!(context, "patient" | "user" | "system"))
+        });
+        Ok(Authorization::new(resource_scopes.map(Scope::parse).collect::<Result<_, _>>()?))
+    }
+
+    /// Sets the patient the token was launched for.
+    pub fn with_patient(mut self, patient_id: impl Into<String>) -> Self {
+        self.patient = Some(patient_id.into());
+        self
+    }
+
+    /// Resource scopes of the token
+    pub fn scopes(&self) -> &[Scope] {
+        &self.scopes
+    }
+
+    /// Patient the token was launched for
+    pub fn patient(&self) -> Option<&str> {
+        self.patient.as_deref()
+    }
+
+    /// Whether any scope allows `action` on a `resource_type` resource of
+    /// patient `patient_id`; `patient` scopes only cover the launch patient.
+    pub fn permits(&self, resource_type: &str, action: Action, patient_id: &str) -> bool {
+        let patient = self.patient.as_deref();
+        self.scopes.iter().any(|scope| {
+            scope.permits(resource_type, action, patient)
+                && (scope.context != Context::Patient || patient == Some(patient_id))
+        })
+    }
+
+    /// Whether any scope allows `action` on `resource`.
+    pub fn permits_resource<R: Resource>(&self, resource: &R, action: Action) -> bool {
+        self.permits(R::RESOURCE_TYPE, action, resource.patient_id())
+    }
+}
+
+fn scope_error(source: &str, message: &str) -> WellAllyError {
+    WellAllyError::Parse {
+        resource_type: "Scope".to_string(),
+        path: String::new(),
+        value: Some(serde_json::Value::from(source).into()),
+        message: message.to_string(),
+    }
+}
@@ -0,0 +1,252 @@
+//! Command-line companion of the wellally crate.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//!
+//! Wraps the library for data-ops pipelines: validating, converting,
+//! anonymizing, mapping and generating resources without writing code.
+//! Inputs are files or standard input (`-`) in any format
+//! [`parse_auto`] reads; outputs go to standard output.
+
+use std::error::Error;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use clap::{Parser, Subcommand, ValueEnum};
+use wellally::anonymize::Anonymizer;
+use wellally::io::{parse_auto, AnyResource, Bundle};
+use wellally::mapping::Mapping;
+use wellally::profiles::Profile;
+use wellally::security;
+use wellally::synthetic::{Cohort, CohortConfig};
+use wellally::validation::Validate;
+use wellally::wire::Wire;
+
+#[derive(Debug, Parser)]
+#[command(name = "wellally", version, about = "Validate, convert, anonymize and generate WellAlly health data")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Checks resources against the schemas and, optionally, a profile
+    Validate {
+        /// Data-quality profile (.toml or .json) to check as well
+        #[arg(long)]
+        profile: Option<PathBuf>,
+        /// Files to check; `-` reads standard input
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+    },
+    /// Rewrites resources in another container format
+    Convert {
+        /// Output format
+        #[arg(long, value_enum)]
+        to: OutputFormat,
+        /// Input file; standard input when absent
+        input: Option<PathBuf>,
+    },
+    /// Replaces ids with salted pseudonyms and strips direct identifiers
+    Anonymize {
+        /// Secret salt of the pseudonyms; equal salts give equal pseudonyms
+        #[arg(long)]
+        salt: String,
+        /// Leaves out resources of a sensitivity category (v3 ActCode, e.g. BH or HIV); repeatable
+        #[arg(long = "withhold", value_name = "CATEGORY")]
+        withheld: Vec<String>,
+        /// Output format
+        #[arg(long, value_enum, default_value = "ndjson")]
+        to: OutputFormat,
+        /// Input file; standard input when absent
+        input: Option<PathBuf>,
+    },
+    /// Maps a partner JSON or CSV file onto resources with a mapping file
+    Map {
+        /// Mapping (.yaml, .yml or .json)
+        #[arg(long)]
+        mapping: PathBuf,
+        /// Output format
+        #[arg(long, value_enum, default_value = "ndjson")]
+        to: OutputFormat,
+        /// Partner file; read as CSV when it ends in .csv
+        input: Option<PathBuf>,
+    },
+    /// Generates a synthetic cohort
+    Synth {
+        /// Number of patients
+        #[arg(long, default_value_t = 10)]
+        patients: usize,
+        /// Seed of the random generator; equal seeds give equal cohorts
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+        /// Output format
+        #[arg(long, value_enum, default_value = "ndjson")]
+        to: OutputFormat,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    /// A JSON array of resources
+    Json,
+    /// One resource per line
+    Ndjson,
+    /// A bundle of type `collection`
+    Bundle,
+    /// NDJSON in the compact wire profile
+    Wire,
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse()) {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("wellally: {}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(cli: Cli) -> Result<ExitCode, Box<dyn Error>> {
+    match cli.command {
+        Command::Validate { profile, files } => {
+            let profile = profile.as_deref().map(read_profile).transpose()?;
+            validate(&files, profile.as_ref())
+        }
+        Command::Convert { to, input } => {
+            write(&read_resources(input.as_deref())?, to)?;
+            Ok(ExitCode::SUCCESS)
+        }
+        Command::Anonymize { salt, withheld, to, input } => {
+            let anonymizer = Anonymizer::new(salt).withholding(withheld.iter().map(|code| security::category(code)));
+            let resources = read_resources(input.as_deref())?;
+            write(&anonymizer.anonymize_all(&resources)?, to)?;
+            Ok(ExitCode::SUCCESS)
+        }
+        Command::Map { mapping, to, input } => {
+            write(&map(&mapping, input.as_deref())?, to)?;
+            Ok(ExitCode::SUCCESS)
+        }
+        Command::Synth { patients, seed, to } => {
+            let mut resources = Vec::new();
+            for records in Cohort::new(CohortConfig { patients, seed, ..CohortConfig::default() }) {
+                resources.push(AnyResource::from(records.person));
+                resources.extend(records.lab_reports.into_iter().map(AnyResource::from));
+                resources.extend(records.medications.into_iter().map(AnyResource::from));
+                resources.push(AnyResource::from(records.family_tree));
+            }
+            write(&resources, to)?;
+            Ok(ExitCode::SUCCESS)
+        }
+    }
+}
+
+/// Prints every issue as `file: Type/id: path: message`; fails if there is one.
+fn validate(files: &[PathBuf], profile: Option<&Profile>) -> Result<ExitCode, Box<dyn Error>> {
+    let (mut checked, mut failed) = (0, 0);
+    for file in files {
+        let resources = match read_resources(Some(file)) {
+            Ok(resources) => resources,
+            Err(err) => {
+                println!("{}: {}", file.display(), err);
+                failed += 1;
+                continue;
+            }
+        };
+        for resource in &resources {
+            let mut issues = resource.validate();
+            issues.extend(profile.map(|profile| profile.validate_any(resource)).unwrap_or_default());
+            for issue in &issues {
+                let (resource_type, id) = (resource.resource_type(), resource.id());
+                println!("{}: {}/{}: {}: {}", file.display(), resource_type, id, issue.path, issue.message);
+            }
+            checked += 1;
+            failed += usize::from(!issues.is_empty());
+        }
+    }
+    eprintln!("{} resources checked, {} with issues", checked, failed);
+    Ok(if failed == 0 { ExitCode::SUCCESS } else { ExitCode::FAILURE })
+}
+
+fn map(mapping: &Path, input: Option<&Path>) -> Result<Vec<AnyResource>, Box<dyn Error>> {
+    let text = fs::read_to_string(mapping)?;
+    let mapping = match extension(mapping).as_deref() {
+        Some("json") => Mapping::from_json(&text)?,
+        _ => Mapping::from_yaml(&text)?,
+    };
+    let source = String::from_utf8(read(input)?)?;
+    let mapped = match input.and_then(extension).as_deref() {
+        Some("csv") => mapping.map_csv_value(&source)?,
+        _ => mapping.map_value(&serde_json::from_str(&source)?)?,
+    };
+    let mut resources = Vec::new();
+    for value in mapped {
+        let resource = AnyResource::from_json_value(&value)?;
+        if let Some(issue) = resource.validate().into_iter().next() {
+            let message = format!("{}/{}: {}: {}", resource.resource_type(), resource.id(), issue.path, issue.message);
+            return Err(message.into());
+        }
+        resources.push(resource);
+    }
+    Ok(resources)
+}
+
+fn read_profile(path: &Path) -> Result<Profile, Box<dyn Error>> {
+    let text = fs::read_to_string(path)?;
+    Ok(match extension(path).as_deref() {
+        Some("json") => Profile::from_json(&text)?,
+        _ => Profile::from_toml(&text)?,
+    })
+}
+
+fn read_resources(path: Option<&Path>) -> Result<Vec<AnyResource>, Box<dyn Error>> {
+    let hint = match path.and_then(extension).as_deref() {
+        Some("ndjson" | "jsonl") => Some("application/x-ndjson"),
+        _ => None,
+    };
+    Ok(parse_auto(&read(path)?, hint)?.resources)
+}
+
+fn read(path: Option<&Path>) -> io::Result<Vec<u8>> {
+    match path {
+        Some(path) if path != Path::new("-") => fs::read(path),
+        _ => {
+            let mut bytes = Vec::new();
+            io::stdin().read_to_end(&mut bytes)?;
+            Ok(bytes)
+        }
+    }
+}
+
+fn extension(path: &Path) -> Option<String> {
+    path.extension().map(|extension| extension.to_string_lossy().to_ascii_lowercase())
+}
+
+fn write(resources: &[AnyResource], format: OutputFormat) -> Result<(), Box<dyn Error>> {
+    let mut out = io::BufWriter::new(io::stdout().lock());
+    match format {
+        OutputFormat::Json => serde_json::to_writer_pretty(&mut out, resources)?,
+        OutputFormat::Bundle => serde_json::to_writer_pretty(&mut out, &Bundle::new(resources.to_vec()))?,
+        OutputFormat::Ndjson => {
+            for resource in resources {
+                serde_json::to_writer(&mut out, resource)?;
+                writeln!(out)?;
+            }
+        }
+        OutputFormat::Wire => {
+            for resource in resources {
+                serde_json::to_writer(&mut out, &Wire(resource))?;
+                writeln!(out)?;
+            }
+        }
+    }
+    if matches!(format, OutputFormat::Json | OutputFormat::Bundle) {
+        writeln!(out)?;
+    }
+    out.flush()?;
+    Ok(())
+}
@@ -0,0 +1,388 @@
+//! Birth record model.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//! Schema: https://wellall.health/schemas/birth/v0.1.0
+//!
+//! A [`BirthRecord`] describes one birth from the newborn's side: the
+//! gestational age, birth weight, length and head circumference, APGAR
+//! scores, how the baby was delivered, complications and the newborn
+//! screening panel. It belongs to the child (`patientId`) and names the
+//! mother (`motherId`), so it can be read from either record; twins get one
+//! record each, told apart by `birthOrder`.
+//!
+//! [`GestationalAge`] lives here rather than in `calc::obstetrics`,
+//! which re-exports it, so that birth records work without `std`.
+//!
+//! ```
+//! use wellally::birth::{BirthRecord, BirthTerm, BirthWeightCategory, DeliveryMethod, GestationalAge};
+//! use wellally::Quantity;
+//!
+//! let born = "2024-10-12T03:40:00+08:00".parse().unwrap();
+//! let mut record = BirthRecord::new("birth-1", "baby-1", "mother-1", born, GestationalAge::new(36, 2));
+//! record.delivery_method = Some(DeliveryMethod::SpontaneousVaginal);
+//! record.birth_weight = Some(Quantity::new(2.35, "kg"));
+//! assert_eq!(record.term(), BirthTerm::ModerateToLatePreterm);
+//! assert_eq!(record.weight_category(), Some(BirthWeightCategory::Low));
+//! ```
+
+use alloc::{string::String, vec::Vec};
+use core::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::common::{Annotation, CodeableConcept, Coding, Quantity, Reference};
+use crate::datetime::FlexibleDateTime;
+use crate::extension::Extension;
+use crate::ucum;
+
+/// Gestational age in completed weeks and days.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "GestationalAgeInput")
+)]
+pub struct GestationalAge {
+    /// Completed weeks
+    pub weeks: u32,
+    /// Days past the completed weeks (0 to 6)
+    pub days: u32,
+}
+
+impl GestationalAge {
+    /// Gestational age of `weeks` weeks and `days` days.
+    pub fn new(weeks: u32, days: u32) -> Self {
+        Self::from_days(i64::from(weeks) * 7 + i64::from(days)).unwrap_or_default()
+    }
+
+    /// Gestational age from a number of days; `None` if negative.
+    pub fn from_days(days: i64) -> Option<Self> {
+        let days = u32::try_from(days).ok()?;
+        Some(Self { weeks: days / 7, days: days % 7 })
+    }
+
+    /// Total number of days.
+    pub fn in_days(&self) -> i64 {
+        i64::from(self.weeks) * 7 + i64::from(self.days)
+    }
+
+    /// Trimester the gestational age falls in.
+    pub fn trimester(&self) -> Trimester {
+        match self.weeks {
+            0..=13 => Trimester::First,
+            14..=27 => Trimester::Second,
+            _ => Trimester::Third,
+        }
+    }
+}
+
+/// Written the clinical way, e.g. `12w3d`.
+impl fmt::Display for GestationalAge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}w{}d", self.weeks, self.days)
+    }
+}
+
+/// Trimester of pregnancy.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Trimester {
+    /// Up to 13w6d
+    First,
+    /// 14w0d to 27w6d
+    Second,
+    /// 28w0d onwards
+    Third,
+}
+
+/// How the baby was delivered.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "async-graphql", derive(async_graphql::Enum))]
+#[serde(rename_all = "kebab-case")]
+pub enum DeliveryMethod {
+    SpontaneousVaginal,
+    /// Vaginal delivery assisted by vacuum extraction
+    Vacuum,
+    /// Vaginal delivery assisted by forceps
+    Forceps,
+    /// Cesarean section scheduled before labor
+    PlannedCesarean,
+    /// Cesarean section decided during labor or for an emergency
+    EmergencyCesarean,
+}
+
+impl DeliveryMethod {
+    /// Whether the baby was delivered by cesarean section.
+    pub fn is_cesarean(&self) -> bool {
+        matches!(self, DeliveryMethod::PlannedCesarean | DeliveryMethod::EmergencyCesarean)
+    }
+}
+
+/// The five APGAR signs, each scored 0 to 2.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "ApgarComponentsInput")
+)]
+pub struct ApgarComponents {
+    /// Skin color
+    pub appearance: u8,
+    /// Heart rate
+    pub pulse: u8,
+    /// Reflex irritability
+    pub grimace: u8,
+    /// Muscle tone
+    pub activity: u8,
+    /// Respiratory effort
+    pub respiration: u8,
+}
+
+impl ApgarComponents {
+    /// Sum of the five signs.
+    pub fn total(&self) -> u8 {
+        [self.appearance, self.pulse, self.grimace, self.activity, self.respiration]
+            .into_iter()
+            .fold(0, u8::saturating_add)
+    }
+}
+
+/// APGAR score at a minute of life.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "ApgarScoreInput")
+)]
+pub struct ApgarScore {
+    /// Minute after birth (1, 5, then every 5 minutes while below 7)
+    pub minute: u8,
+    /// Total score, 0 to 10
+    pub score: u8,
+    /// Scores of the individual signs, when recorded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub components: Option<ApgarComponents>,
+}
+
+/// Gestational age category at birth: WHO preterm subcategories and the
+/// ACOG term definitions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum BirthTerm {
+    /// Before 28 weeks
+    ExtremelyPreterm,
+    /// 28 to before 32 weeks
+    VeryPreterm,
+    /// 32 to before 37 weeks
+    ModerateToLatePreterm,
+    /// 37w0d to 38w6d
+    EarlyTerm,
+    /// 39w0d to 40w6d
+    FullTerm,
+    /// 41w0d to 41w6d
+    LateTerm,
+    /// 42 weeks and beyond
+    PostTerm,
+}
+
+impl BirthTerm {
+    /// Category of a gestational age at birth.
+    pub fn of(age: GestationalAge) -> Self {
+        match age.weeks {
+            0..=27 => BirthTerm::ExtremelyPreterm,
+            28..=31 => BirthTerm::VeryPreterm,
+            32..=36 => BirthTerm::ModerateToLatePreterm,
+            37..=38 => BirthTerm::EarlyTerm,
+            39..=40 => BirthTerm::FullTerm,
+            41 => BirthTerm::LateTerm,
+            _ => BirthTerm::PostTerm,
+        }
+    }
+
+    /// Whether the birth was before 37 weeks.
+    pub fn is_preterm(&self) -> bool {
+        *self <= BirthTerm::ModerateToLatePreterm
+    }
+}
+
+/// WHO birth weight category.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum BirthWeightCategory {
+    /// Below 1000 g
+    ExtremelyLow,
+    /// 1000 g to below 1500 g
+    VeryLow,
+    /// 1500 g to below 2500 g
+    Low,
+    /// 2500 g to below 4000 g
+    Normal,
+    /// 4000 g and above
+    Macrosomia,
+}
+
+impl BirthWeightCategory {
+    /// Category of a birth weight in grams.
+    pub fn who(grams: f64) -> Self {
+        match grams {
+            g if g < 1000.0 => BirthWeightCategory::ExtremelyLow,
+            g if g < 1500.0 => BirthWeightCategory::VeryLow,
+            g if g < 2500.0 => BirthWeightCategory::Low,
+            g if g < 4000.0 => BirthWeightCategory::Normal,
+            _ => BirthWeightCategory::Macrosomia,
+        }
+    }
+}
+
+/// Birth of one baby.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "BirthRecordInput")
+)]
+pub struct BirthRecord {
+    /// Unique record identifier
+    pub id: String,
+    /// Reference to the Person.id of the newborn
+    #[serde(rename = "patientId", alias = "patient_id")]
+    pub patient_id: String,
+    /// Reference to the Person.id of the mother
+    #[serde(rename = "motherId", alias = "mother_id")]
+    pub mother_id: String,
+    /// Time of birth
+    #[serde(rename = "bornAt", alias = "born_at")]
+    #[cfg_attr(feature = "utoipa", schema(value_type = String))]
+    pub born_at: FlexibleDateTime,
+    /// Gestational age at birth
+    #[serde(rename = "gestationalAge", alias = "gestational_age")]
+    pub gestational_age: GestationalAge,
+    /// Position in a multiple birth (1 for the first twin); absent for a singleton
+    #[serde(rename = "birthOrder", alias = "birth_order", skip_serializing_if = "Option::is_none")]
+    pub birth_order: Option<u8>,
+    #[serde(rename = "birthWeight", alias = "birth_weight", skip_serializing_if = "Option::is_none")]
+    pub birth_weight: Option<Quantity>,
+    #[serde(rename = "birthLength", alias = "birth_length", skip_serializing_if = "Option::is_none")]
+    pub birth_length: Option<Quantity>,
+    #[serde(rename = "headCircumference", alias = "head_circumference", skip_serializing_if = "Option::is_none")]
+    pub head_circumference: Option<Quantity>,
+    /// APGAR scores, usually at 1, 5 and 10 minutes
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub apgar: Vec<ApgarScore>,
+    #[serde(rename = "deliveryMethod", alias = "delivery_method", skip_serializing_if = "Option::is_none")]
+    pub delivery_method: Option<DeliveryMethod>,
+    /// Complications of labor, delivery or the newborn (e.g., SNOMED CT shoulder dystocia)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub complications: Vec<CodeableConcept>,
+    /// Newborn screening panel (e.g., "LabReport/nbs-2024-001")
+    #[serde(rename = "newbornScreening", alias = "newborn_screening", skip_serializing_if = "Option::is_none")]
+    pub newborn_screening: Option<Reference>,
+    /// Where the baby was born (e.g., "Location/maternity-ward")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<Reference>,
+    /// Midwife or obstetrician who delivered the baby
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attendant: Option<Reference>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notes: Option<Vec<Annotation>>,
+    /// Schema version the resource was written against (e.g., "0.1.0"); absent means 0.1.0
+    #[serde(rename = "schemaVersion", alias = "schema_version", skip_serializing_if = "Option::is_none")]
+    pub schema_version: Option<String>,
+    /// Confidentiality and sensitivity labels (e.g., v3 ActCode HIV); see [`crate::security`]
+    #[serde(rename = "securityLabels", alias = "security_labels", skip_serializing_if = "Option::is_none")]
+    pub security_labels: Option<Vec<Coding>>,
+    /// Site-specific extensions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extensions: Option<Vec<Extension>>,
+    /// Properties this model version does not define, kept for re-serialization
+    #[cfg(feature = "preserve-unknown")]
+    #[serde(flatten)]
+    #[cfg_attr(feature = "schemars", schemars(skip))]
+    #[cfg_attr(feature = "utoipa", schema(ignore))]
+    #[cfg_attr(feature = "async-graphql", graphql(skip))]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl BirthRecord {
+    /// Birth without measurements yet.
+    pub fn new(
+        id: impl Into<String>,
+        patient_id: impl Into<String>,
+        mother_id: impl Into<String>,
+        born_at: FlexibleDateTime,
+        gestational_age: GestationalAge,
+    ) -> Self {
+        BirthRecord {
+            id: id.into(),
+            patient_id: patient_id.into(),
+            mother_id: mother_id.into(),
+            born_at,
+            gestational_age,
+            birth_order: None,
+            birth_weight: None,
+            birth_length: None,
+            head_circumference: None,
+            apgar: Vec::new(),
+            delivery_method: None,
+            complications: Vec::new(),
+            newborn_screening: None,
+            location: None,
+            attendant: None,
+            notes: None,
+            schema_version: None,
+            security_labels: None,
+            extensions: None,
+            #[cfg(feature = "preserve-unknown")]
+            extra: Default::default(),
+        }
+    }
+
+    /// APGAR score at `minute`, if taken.
+    pub fn apgar_at(&self, minute: u8) -> Option<u8> {
+        self.apgar.iter().find(|apgar| apgar.minute == minute).map(|apgar| apgar.score)
+    }
+
+    /// Whether the 5-minute APGAR score is below 7, the point from which
+    /// scoring continues every 5 minutes.
+    pub fn is_low_apgar(&self) -> bool {
+        self.apgar_at(5).is_some_and(|score| score < 7)
+    }
+
+    /// Gestational age category of the birth.
+    pub fn term(&self) -> BirthTerm {
+        BirthTerm::of(self.gestational_age)
+    }
+
+    /// Whether the baby was born before 37 weeks.
+    pub fn is_preterm(&self) -> bool {
+        self.term().is_preterm()
+    }
+
+    /// Birth weight in grams; `None` when absent or not a mass.
+    pub fn weight_grams(&self) -> Option<f64> {
+        let weight = self.birth_weight.as_ref()?;
+        ucum::convert(weight.value, &weight.unit, "g").ok()
+    }
+
+    /// WHO category of the birth weight.
+    pub fn weight_category(&self) -> Option<BirthWeightCategory> {
+        self.weight_grams().map(BirthWeightCategory::who)
+    }
+}
+
+/// Births to the mother `mother_id` among `records`, oldest first and twins
+/// in birth order: her obstetric history as seen from the children.
+pub fn births_to<'a>(records: &'a [BirthRecord], mother_id: &str) -> Vec<&'a BirthRecord> {
+    let mut births: Vec<&BirthRecord> = records.iter().filter(|record| record.mother_id == mother_id).collect();
+    births.sort_by_key(|record| (record.born_at.to_utc(), record.birth_order));
+    births
+}
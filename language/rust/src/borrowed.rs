@@ -0,0 +1,416 @@
+//! Borrowed mirrors of the lab report models for zero-copy ingestion.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//!
+//! Every string field is a `Cow<'a, str>` that borrows from the input buffer
+//! unless the JSON string contains escapes, so parsing a report only allocates
+//! for its arrays. Call `to_owned()` on a value to convert it into the regular
+//! model type once it needs to outlive the buffer.
+//!
+//! ```
+//! use wellally::borrowed::{ndjson, LabReportRef};
+//!
+//! let input = r#"{"id":"lab-1","patientId":"p-1","issuedAt":"2024-11-02T09:30:00Z","results":[]}"#;
+//! for report in ndjson::<LabReportRef>(input) {
+//!     let report = report?;
+//!     assert_eq!(report.patient_id, "p-1");
+//! }
+//! # Ok::<(), serde_json::Error>(())
+//! ```
+//!
+//! With the `simd-json` feature, [`ndjson_simd`] parses the same input with
+//! SIMD-accelerated scanning; see `benches/ndjson.rs` for the comparison.
+
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::common::{Annotation, CodeableConcept, Coding, Effective, Quantity, Range, Reference, ReferenceRange, ReferenceRangeType};
+use crate::datetime::FlexibleDateTime;
+use crate::extension::Extension;
+use crate::lab_report::{one_or_many, Comparator, Facility, Interpretation, LabReport, LabResult, LabValue, Specimen};
+
+/// Deserializes an optional string, borrowing it when possible.
+///
+/// `#[serde(borrow)]` only borrows a bare `Cow<str>`; inside an `Option` it
+/// would always allocate.
+fn borrow_option<'de: 'a, 'a, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Cow<'a, str>>, D::Error> {
+    #[derive(Deserialize)]
+    struct Borrowed<'a>(#[serde(borrow)] Cow<'a, str>);
+
+    Ok(Option::<Borrowed<'a>>::deserialize(deserializer)?.map(|b| b.0))
+}
+
+fn to_string_option(value: &Option<Cow<'_, str>>) -> Option<alloc::string::String> {
+    value.as_deref().map(Into::into)
+}
+
+/// Borrowed [`Coding`].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CodingRef<'a> {
+    /// URI identifying the terminology system
+    #[serde(borrow)]
+    pub system: Cow<'a, str>,
+    /// The code value from the system
+    #[serde(borrow)]
+    pub code: Cow<'a, str>,
+    /// Optional human-readable display text
+    #[serde(borrow, default, deserialize_with = "borrow_option", skip_serializing_if = "Option::is_none")]
+    pub display: Option<Cow<'a, str>>,
+}
+
+impl CodingRef<'_> {
+    /// Owned copy of the coding.
+    pub fn to_owned(&self) -> Coding {
+        Coding { system: self.system.as_ref().into(), code: self.code.as_ref().into(), display: to_string_option(&self.display) }
+    }
+}
+
+/// Borrowed [`CodeableConcept`].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CodeableConceptRef<'a> {
+    /// List of coded values
+    #[serde(borrow)]
+    pub coding: Vec<CodingRef<'a>>,
+    /// Optional plain text representation
+    #[serde(borrow, default, deserialize_with = "borrow_option", skip_serializing_if = "Option::is_none")]
+    pub text: Option<Cow<'a, str>>,
+}
+
+impl CodeableConceptRef<'_> {
+    /// Owned copy of the concept.
+    pub fn to_owned(&self) -> CodeableConcept {
+        CodeableConcept { coding: self.coding.iter().map(CodingRef::to_owned).collect(), text: to_string_option(&self.text) }
+    }
+}
+
+/// Borrowed [`Quantity`].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct QuantityRef<'a> {
+    /// Numerical value
+    pub value: f64,
+    /// UCUM unit string
+    #[serde(borrow)]
+    pub unit: Cow<'a, str>,
+    /// How to read `value` when it is a limit
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comparator: Option<Comparator>,
+    /// Coded form of the unit
+    #[serde(borrow, default, deserialize_with = "borrow_option", skip_serializing_if = "Option::is_none")]
+    pub code: Option<Cow<'a, str>>,
+    /// System of `code`
+    #[serde(borrow, default, deserialize_with = "borrow_option", skip_serializing_if = "Option::is_none")]
+    pub system: Option<Cow<'a, str>>,
+}
+
+impl QuantityRef<'_> {
+    /// Owned copy of the quantity.
+    pub fn to_owned(&self) -> Quantity {
+        Quantity {
+            comparator: self.comparator,
+            code: to_string_option(&self.code),
+            system: to_string_option(&self.system),
+            ..Quantity::new(self.value, self.unit.as_ref())
+        }
+    }
+}
+
+/// Borrowed [`ReferenceRange`].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ReferenceRangeRef<'a> {
+    /// Lower bound quantity
+    #[serde(borrow, skip_serializing_if = "Option::is_none")]
+    pub low: Option<QuantityRef<'a>>,
+    /// Upper bound quantity
+    #[serde(borrow, skip_serializing_if = "Option::is_none")]
+    pub high: Option<QuantityRef<'a>>,
+    /// Optional textual description
+    #[serde(borrow, default, deserialize_with = "borrow_option", skip_serializing_if = "Option::is_none")]
+    pub text: Option<Cow<'a, str>>,
+    /// Kind of range; absent means normal
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub range_type: Option<ReferenceRangeType>,
+    /// Populations or states the range is for
+    #[serde(borrow, rename = "appliesTo", alias = "applies_to", skip_serializing_if = "Option::is_none")]
+    pub applies_to: Option<Vec<CodeableConceptRef<'a>>>,
+    /// Ages the range is for
+    #[serde(borrow, skip_serializing_if = "Option::is_none")]
+    pub age: Option<RangeRef<'a>>,
+}
+
+impl ReferenceRangeRef<'_> {
+    /// Owned copy of the range.
+    pub fn to_owned(&self) -> ReferenceRange {
+        ReferenceRange {
+            low: self.low.as_ref().map(QuantityRef::to_owned),
+            high: self.high.as_ref().map(QuantityRef::to_owned),
+            text: to_string_option(&self.text),
+            range_type: self.range_type,
+            applies_to: self.applies_to.as_ref().map(|concepts| concepts.iter().map(CodeableConceptRef::to_owned).collect()),
+            age: self.age.as_ref().map(RangeRef::to_owned),
+        }
+    }
+}
+
+/// Borrowed [`Range`].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RangeRef<'a> {
+    /// Lower bound quantity
+    #[serde(borrow, skip_serializing_if = "Option::is_none")]
+    pub low: Option<QuantityRef<'a>>,
+    /// Upper bound quantity
+    #[serde(borrow, skip_serializing_if = "Option::is_none")]
+    pub high: Option<QuantityRef<'a>>,
+}
+
+impl RangeRef<'_> {
+    /// Owned copy of the range.
+    pub fn to_owned(&self) -> Range {
+        Range { low: self.low.as_ref().map(QuantityRef::to_owned), high: self.high.as_ref().map(QuantityRef::to_owned) }
+    }
+}
+
+/// Borrowed [`LabValue`].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum LabValueRef<'a> {
+    Quantity(#[serde(borrow)] QuantityRef<'a>),
+    Ratio { numerator: f64, denominator: f64 },
+    Comparator {
+        comparator: Comparator,
+        #[serde(borrow)]
+        value: QuantityRef<'a>,
+    },
+    Concept(#[serde(borrow)] CodeableConceptRef<'a>),
+    String(#[serde(borrow)] Cow<'a, str>),
+}
+
+impl LabValueRef<'_> {
+    /// Owned copy of the value.
+    pub fn to_owned(&self) -> LabValue {
+        match self {
+            LabValueRef::Quantity(quantity) => LabValue::Quantity(quantity.to_owned()),
+            LabValueRef::Ratio { numerator, denominator } => {
+                LabValue::Ratio { numerator: *numerator, denominator: *denominator }
+            }
+            LabValueRef::Comparator { comparator, value } => {
+                LabValue::Comparator { comparator: *comparator, value: value.to_owned() }
+            }
+            LabValueRef::Concept(concept) => LabValue::Concept(concept.to_owned()),
+            LabValueRef::String(text) => LabValue::String(text.as_ref().into()),
+        }
+    }
+}
+
+/// Borrowed [`LabResult`].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LabResultRef<'a> {
+    /// LOINC code for the test
+    #[serde(borrow)]
+    pub code: CodeableConceptRef<'a>,
+    /// Result value
+    #[serde(borrow)]
+    pub value: LabValueRef<'a>,
+    /// Reference ranges; a single range object is also accepted
+    #[serde(borrow, default, deserialize_with = "one_or_many", skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "referenceRange", alias = "reference_range")]
+    pub reference_range: Option<Vec<ReferenceRangeRef<'a>>>,
+    /// HL7 v3 interpretation code (N, L, H, A, HH, LL, POS, ...)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub interpretation: Option<Interpretation>,
+    /// Test method used
+    #[serde(borrow, skip_serializing_if = "Option::is_none")]
+    pub method: Option<CodeableConceptRef<'a>>,
+    /// Instant or period the result applies to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub effective: Option<Effective>,
+}
+
+impl LabResultRef<'_> {
+    /// Owned copy of the result.
+    pub fn to_owned(&self) -> LabResult {
+        LabResult {
+            code: self.code.to_owned(),
+            value: self.value.to_owned(),
+            reference_range: self
+                .reference_range
+                .as_ref()
+                .map(|ranges| ranges.iter().map(ReferenceRangeRef::to_owned).collect()),
+            interpretation: self.interpretation,
+            method: self.method.as_ref().map(CodeableConceptRef::to_owned),
+            effective: self.effective,
+        }
+    }
+}
+
+/// Borrowed [`Facility`].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FacilityRef<'a> {
+    #[serde(borrow, default, deserialize_with = "borrow_option", skip_serializing_if = "Option::is_none")]
+    pub id: Option<Cow<'a, str>>,
+    #[serde(borrow, default, deserialize_with = "borrow_option", skip_serializing_if = "Option::is_none")]
+    pub name: Option<Cow<'a, str>>,
+    #[serde(borrow, skip_serializing_if = "Option::is_none")]
+    pub location: Option<ReferenceRef<'a>>,
+}
+
+impl FacilityRef<'_> {
+    /// Owned copy of the facility.
+    pub fn to_owned(&self) -> Facility {
+        Facility {
+            id: to_string_option(&self.id),
+            name: to_string_option(&self.name),
+            location: self.location.as_ref().map(ReferenceRef::to_owned),
+        }
+    }
+}
+
+/// Borrowed [`Reference`].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ReferenceRef<'a> {
+    /// Relative reference (e.g., "Location/loc-1")
+    #[serde(borrow)]
+    pub reference: Cow<'a, str>,
+    /// Optional display text
+    #[serde(borrow, default, deserialize_with = "borrow_option", skip_serializing_if = "Option::is_none")]
+    pub display: Option<Cow<'a, str>>,
+}
+
+impl ReferenceRef<'_> {
+    /// Owned copy of the reference.
+    pub fn to_owned(&self) -> Reference {
+        Reference { reference: self.reference.as_ref().into(), display: to_string_option(&self.display) }
+    }
+}
+
+/// Borrowed [`Annotation`].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AnnotationRef<'a> {
+    #[serde(borrow, rename = "authorReference", alias = "author_reference", skip_serializing_if = "Option::is_none")]
+    pub author_reference: Option<ReferenceRef<'a>>,
+    #[serde(
+        borrow,
+        rename = "authorString",
+        alias = "author_string",
+        default,
+        deserialize_with = "borrow_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub author_string: Option<Cow<'a, str>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time: Option<FlexibleDateTime>,
+    #[serde(borrow)]
+    pub text: Cow<'a, str>,
+}
+
+impl AnnotationRef<'_> {
+    /// Owned copy of the note.
+    pub fn to_owned(&self) -> Annotation {
+        Annotation {
+            author_reference: self.author_reference.as_ref().map(ReferenceRef::to_owned),
+            author_string: to_string_option(&self.author_string),
+            time: self.time,
+            text: self.text.as_ref().into(),
+        }
+    }
+}
+
+/// Borrowed [`Specimen`].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SpecimenRef<'a> {
+    #[serde(borrow, rename = "type", skip_serializing_if = "Option::is_none")]
+    pub specimen_type: Option<CodingRef<'a>>,
+    #[serde(rename = "collectedAt", alias = "collected_at", skip_serializing_if = "Option::is_none")]
+    pub collected_at: Option<FlexibleDateTime>,
+}
+
+impl SpecimenRef<'_> {
+    /// Owned copy of the specimen.
+    pub fn to_owned(&self) -> Specimen {
+        Specimen { specimen_type: self.specimen_type.as_ref().map(CodingRef::to_owned), collected_at: self.collected_at }
+    }
+}
+
+/// Borrowed [`LabReport`].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LabReportRef<'a> {
+    /// Unique report identifier
+    #[serde(borrow)]
+    pub id: Cow<'a, str>,
+    /// Reference to Person.id
+    #[serde(borrow, rename = "patientId", alias = "patient_id")]
+    pub patient_id: Cow<'a, str>,
+    /// Report issue timestamp
+    #[serde(rename = "issuedAt", alias = "issued_at")]
+    pub issued_at: FlexibleDateTime,
+    /// List of lab test results
+    #[serde(borrow)]
+    pub results: Vec<LabResultRef<'a>>,
+    /// Lab facility information
+    #[serde(borrow, skip_serializing_if = "Option::is_none")]
+    pub facility: Option<FacilityRef<'a>>,
+    /// Test panel code
+    #[serde(borrow, skip_serializing_if = "Option::is_none")]
+    pub panel: Option<CodeableConceptRef<'a>>,
+    /// Specimen information
+    #[serde(borrow, skip_serializing_if = "Option::is_none")]
+    pub specimen: Option<SpecimenRef<'a>>,
+    /// Free-text notes and comments
+    #[serde(borrow, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<Vec<AnnotationRef<'a>>>,
+    /// Schema version the report was written against
+    #[serde(borrow, default, deserialize_with = "borrow_option", skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "schemaVersion", alias = "schema_version")]
+    pub schema_version: Option<Cow<'a, str>>,
+    /// Confidentiality and sensitivity labels (e.g., v3 ActCode HIV); owned since they are rare
+    #[serde(rename = "securityLabels", alias = "security_labels", skip_serializing_if = "Option::is_none")]
+    pub security_labels: Option<Vec<Coding>>,
+    /// Site-specific extensions, owned since they are rare
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extensions: Option<Vec<Extension>>,
+}
+
+impl LabReportRef<'_> {
+    /// Owned copy of the report.
+    pub fn to_owned(&self) -> LabReport {
+        LabReport {
+            id: self.id.as_ref().into(),
+            patient_id: self.patient_id.as_ref().into(),
+            issued_at: self.issued_at,
+            results: self.results.iter().map(LabResultRef::to_owned).collect(),
+            facility: self.facility.as_ref().map(FacilityRef::to_owned),
+            panel: self.panel.as_ref().map(CodeableConceptRef::to_owned),
+            specimen: self.specimen.as_ref().map(SpecimenRef::to_owned),
+            notes: self.notes.as_ref().map(|notes| notes.iter().map(AnnotationRef::to_owned).collect()),
+            schema_version: to_string_option(&self.schema_version),
+            security_labels: self.security_labels.clone(),
+            extensions: self.extensions.clone(),
+            #[cfg(feature = "preserve-unknown")]
+            extra: Default::default(),
+        }
+    }
+}
+
+/// Parses newline-delimited JSON, one value per non-blank line.
+///
+/// Values borrow from `input`, so `T` can be a `*Ref` type.
+pub fn ndjson<'a, T: Deserialize<'a> + 'a>(input: &'a str) -> impl Iterator<Item = Result<T, serde_json::Error>> + 'a {
+    input.lines().filter(|line| !line.trim().is_empty()).map(serde_json::from_str)
+}
+
+/// Parses newline-delimited JSON with simd-json, one value per non-blank line.
+///
+/// simd-json unescapes strings in place, so it needs the buffer mutably;
+/// values still borrow from it like with [`ndjson`]. Scratch buffers are
+/// reused across lines.
+#[cfg(feature = "simd-json")]
+pub fn ndjson_simd<'a, T: Deserialize<'a> + 'a>(
+    input: &'a mut [u8],
+) -> impl Iterator<Item = Result<T, simd_json::Error>> + 'a {
+    let mut buffers = simd_json::Buffers::default();
+    input
+        .split_mut(|&byte| byte == b'\n')
+        .filter(|line| !line.iter().all(u8::is_ascii_whitespace))
+        .map(move |line| simd_json::serde::from_slice_with_buffers(line, &mut buffers))
+}
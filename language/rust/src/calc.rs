@@ -0,0 +1,307 @@
+//! Clinical calculators deriving results from recorded measurements.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//!
+//! [`BodyMeasurements`] takes body weight and height (as quantities in any
+//! common unit, or straight from coded results) and derives body mass index,
+//! body surface area and ideal/adjusted body weight, each as a plain number or
+//! as a coded [`LabResult`] ready to add to a report. [`obstetrics`] dates
+//! pregnancies and [`kidney`] estimates GFR from serum creatinine.
+//!
+//! ```
+//! use wellally::calc::{BmiCategory, BodyMeasurements, BsaFormula};
+//!
+//! let body = BodyMeasurements { weight_kg: 70.0, height_cm: 175.0 };
+//! assert_eq!(body.bmi_category(), BmiCategory::Normal);
+//! assert_eq!(body.bmi_result().code.coding[0].code, "39156-5");
+//! assert!((body.bsa(BsaFormula::Mosteller) - 1.84).abs() < 0.01);
+//! ```
+
+use crate::catalog::{self, Analyte, LOINC};
+use crate::common::{CodeableConcept, Quantity};
+use crate::error::WellAllyError;
+use crate::family_health::Sex;
+use crate::lab_report::{LabResult, LabValue};
+
+pub mod kidney;
+pub mod obstetrics;
+
+/// LOINC codes of body weight results (body weight, body weight measured)
+pub const BODY_WEIGHT_CODES: &[&str] = &["29463-7", "3141-9"];
+/// LOINC codes of body height results (body height, measured, lying)
+pub const BODY_HEIGHT_CODES: &[&str] = &["8302-2", "3137-7", "8306-3"];
+
+/// Body mass index with the WHO adult normal range
+pub const BMI: Analyte = Analyte {
+    code: "39156-5",
+    display: "Body mass index (BMI) [Ratio]",
+    unit: "kg/m2",
+    low: 18.5,
+    high: 24.9,
+    min: 12.0,
+    max: 70.0,
+};
+
+/// LOINC body surface area
+const BSA: (&str, &str) = ("8277-6", "Body surface area");
+/// LOINC ideal body weight
+const IDEAL_BODY_WEIGHT: (&str, &str) = ("50064-5", "Ideal body weight");
+
+/// Code system of WellAlly-derived values that have no LOINC code
+pub const CALCULATION_SYSTEM: &str = "https://www.wellally.tech/fhir/CodeSystem/calculation";
+/// Adjusted body weight in [`CALCULATION_SYSTEM`]
+const ADJUSTED_BODY_WEIGHT: (&str, &str) = ("adjusted-body-weight", "Adjusted body weight");
+
+/// WHO adult BMI classification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum BmiCategory {
+    /// Below 18.5
+    Underweight,
+    /// 18.5 to below 25
+    Normal,
+    /// 25 to below 30 (pre-obese)
+    Overweight,
+    /// 30 to below 35
+    ObeseClass1,
+    /// 35 to below 40
+    ObeseClass2,
+    /// 40 and above
+    ObeseClass3,
+}
+
+impl BmiCategory {
+    /// WHO category of an adult BMI.
+    pub fn who(bmi: f64) -> Self {
+        match bmi {
+            b if b < 18.5 => BmiCategory::Underweight,
+            b if b < 25.0 => BmiCategory::Normal,
+            b if b < 30.0 => BmiCategory::Overweight,
+            b if b < 35.0 => BmiCategory::ObeseClass1,
+            b if b < 40.0 => BmiCategory::ObeseClass2,
+            _ => BmiCategory::ObeseClass3,
+        }
+    }
+
+    /// WHO label of the category.
+    pub fn display(&self) -> &'static str {
+        match self {
+            BmiCategory::Underweight => "Underweight",
+            BmiCategory::Normal => "Normal weight",
+            BmiCategory::Overweight => "Pre-obesity",
+            BmiCategory::ObeseClass1 => "Obesity class I",
+            BmiCategory::ObeseClass2 => "Obesity class II",
+            BmiCategory::ObeseClass3 => "Obesity class III",
+        }
+    }
+}
+
+/// CDC weight status of children and teens (2 to 19 years).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PediatricBmiCategory {
+    /// Below the 5th percentile
+    Underweight,
+    /// 5th to below the 85th percentile
+    HealthyWeight,
+    /// 85th to below the 95th percentile
+    Overweight,
+    /// 95th percentile and above
+    Obese,
+}
+
+impl PediatricBmiCategory {
+    /// CDC category of a BMI-for-age percentile (0 to 100).
+    pub fn cdc(percentile: f64) -> Self {
+        match percentile {
+            p if p < 5.0 => PediatricBmiCategory::Underweight,
+            p if p < 85.0 => PediatricBmiCategory::HealthyWeight,
+            p if p < 95.0 => PediatricBmiCategory::Overweight,
+            _ => PediatricBmiCategory::Obese,
+        }
+    }
+
+    /// CDC label of the category.
+    pub fn display(&self) -> &'static str {
+        match self {
+            PediatricBmiCategory::Underweight => "Underweight",
+            PediatricBmiCategory::HealthyWeight => "Healthy weight",
+            PediatricBmiCategory::Overweight => "Overweight",
+            PediatricBmiCategory::Obese => "Obesity",
+        }
+    }
+}
+
+/// Body surface area formula.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum BsaFormula {
+    /// Du Bois & Du Bois (1916): 0.007184 × W^0.425 × H^0.725
+    DuBois,
+    /// Mosteller (1987): √(H × W / 3600)
+    #[default]
+    Mosteller,
+}
+
+impl BsaFormula {
+    fn name(&self) -> &'static str {
+        match self {
+            BsaFormula::DuBois => "Du Bois",
+            BsaFormula::Mosteller => "Mosteller",
+        }
+    }
+}
+
+/// Body weight and height of a person.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BodyMeasurements {
+    /// Body weight in kilograms
+    pub weight_kg: f64,
+    /// Body height in centimetres
+    pub height_cm: f64,
+}
+
+const WEIGHT_UNITS: &[(&str, f64)] = &[("kg", 1.0), ("g", 0.001), ("[lb_av]", 0.453_592_37)];
+const HEIGHT_UNITS: &[(&str, f64)] = &[("cm", 1.0), ("m", 100.0), ("[in_i]", 2.54)];
+
+/// Value of `quantity` in the first unit of `units`, failing with the location of the quantity.
+fn convert(
+    quantity: &Quantity,
+    units: &[(&str, f64)],
+    (resource_type, path): (&str, String),
+) -> Result<f64, WellAllyError> {
+    units.iter().find(|(unit, _)| *unit == quantity.unit).map(|(_, factor)| quantity.value * factor).ok_or_else(|| {
+        WellAllyError::Conversion {
+            resource_type: resource_type.to_string(),
+            path,
+            value: Some(Box::new(quantity.unit.as_str().into())),
+            message: format!(
+                "unsupported unit, expected one of {}",
+                units.iter().map(|(unit, _)| *unit).collect::<Vec<_>>().join(", ")
+            ),
+        }
+    })
+}
+
+fn find<'r>(results: &'r [LabResult], codes: &[&str], what: &str) -> Result<(usize, &'r Quantity), WellAllyError> {
+    results
+        .iter()
+        .enumerate()
+        .find_map(|(i, result)| {
+            let coded = result.code.coding.iter().any(|c| c.system == LOINC && codes.contains(&c.code.as_str()));
+            match &result.value {
+                LabValue::Quantity(quantity) if coded => Some((i, quantity)),
+                _ => None,
+            }
+        })
+        .ok_or_else(|| WellAllyError::Conversion {
+            resource_type: "LabReport".to_string(),
+            path: "results".to_string(),
+            value: None,
+            message: format!("no {} result (LOINC {})", what, codes.join(", ")),
+        })
+}
+
+/// Rounds to `decimals` places.
+fn round(value: f64, decimals: i32) -> f64 {
+    let scale = 10f64.powi(decimals);
+    (value * scale).round() / scale
+}
+
+fn quantity_result(code: CodeableConcept, value: f64, unit: &str) -> LabResult {
+    LabResult {
+        code,
+        value: LabValue::Quantity(Quantity::new(value, unit)),
+        reference_range: None,
+        interpretation: None,
+        method: None,
+        effective: None,
+    }
+}
+
+impl BodyMeasurements {
+    /// Measurements from weight (`kg`, `g`, `[lb_av]`) and height (`cm`, `m`, `[in_i]`) quantities.
+    pub fn new(weight: &Quantity, height: &Quantity) -> Result<Self, WellAllyError> {
+        Ok(Self {
+            weight_kg: convert(weight, WEIGHT_UNITS, ("Quantity", "unit".to_string()))?,
+            height_cm: convert(height, HEIGHT_UNITS, ("Quantity", "unit".to_string()))?,
+        })
+    }
+
+    /// Measurements from the first LOINC-coded body weight and height results.
+    pub fn from_results(results: &[LabResult]) -> Result<Self, WellAllyError> {
+        let (weight_at, weight) = find(results, BODY_WEIGHT_CODES, "body weight")?;
+        let (height_at, height) = find(results, BODY_HEIGHT_CODES, "body height")?;
+        let unit_path = |i: usize| ("LabReport", format!("results[{}].value.unit", i));
+        Ok(Self {
+            weight_kg: convert(weight, WEIGHT_UNITS, unit_path(weight_at))?,
+            height_cm: convert(height, HEIGHT_UNITS, unit_path(height_at))?,
+        })
+    }
+
+    fn height_m(&self) -> f64 {
+        self.height_cm / 100.0
+    }
+
+    /// Body mass index in kg/m².
+    pub fn bmi(&self) -> f64 {
+        self.weight_kg / (self.height_m() * self.height_m())
+    }
+
+    /// WHO adult category of the BMI.
+    pub fn bmi_category(&self) -> BmiCategory {
+        BmiCategory::who(self.bmi())
+    }
+
+    /// BMI result with the adult normal range and its interpretation.
+    pub fn bmi_result(&self) -> LabResult {
+        BMI.result(self.bmi())
+    }
+
+    /// Body surface area in m².
+    pub fn bsa(&self, formula: BsaFormula) -> f64 {
+        match formula {
+            BsaFormula::DuBois => 0.007_184 * self.weight_kg.powf(0.425) * self.height_cm.powf(0.725),
+            BsaFormula::Mosteller => (self.height_cm * self.weight_kg / 3600.0).sqrt(),
+        }
+    }
+
+    /// BSA result; the formula is named in the code text.
+    pub fn bsa_result(&self, formula: BsaFormula) -> LabResult {
+        let (code, display) = BSA;
+        let mut concept = catalog::concept(LOINC, code, display);
+        concept.text = Some(format!("{} ({})", display, formula.name()));
+        quantity_result(concept, round(self.bsa(formula), 2), "m2")
+    }
+
+    /// Ideal body weight in kg by the Devine formula (50 kg for men, 45.5 kg
+    /// for women, plus 2.3 kg per inch over 5 feet); `None` unless `sex` is
+    /// male or female.
+    pub fn ideal_body_weight(&self, sex: Sex) -> Option<f64> {
+        let base = match sex {
+            Sex::Male => 50.0,
+            Sex::Female => 45.5,
+            Sex::Other | Sex::Unknown => return None,
+        };
+        Some(base + 2.3 * (self.height_cm / 2.54 - 60.0))
+    }
+
+    /// Ideal body weight result.
+    pub fn ideal_body_weight_result(&self, sex: Sex) -> Option<LabResult> {
+        let (code, display) = IDEAL_BODY_WEIGHT;
+        let value = self.ideal_body_weight(sex)?;
+        Some(quantity_result(catalog::concept(LOINC, code, display), round(value, 1), "kg"))
+    }
+
+    /// Adjusted body weight in kg: ideal weight plus 40% of the excess over it.
+    /// Dosing guidelines usually apply it when weight exceeds 120% of ideal.
+    pub fn adjusted_body_weight(&self, sex: Sex) -> Option<f64> {
+        let ideal = self.ideal_body_weight(sex)?;
+        Some(ideal + 0.4 * (self.weight_kg - ideal))
+    }
+
+    /// Adjusted body weight result, coded in [`CALCULATION_SYSTEM`].
+    pub fn adjusted_body_weight_result(&self, sex: Sex) -> Option<LabResult> {
+        let (code, display) = ADJUSTED_BODY_WEIGHT;
+        let value = self.adjusted_body_weight(sex)?;
+        Some(quantity_result(catalog::concept(CALCULATION_SYSTEM, code, display), round(value, 1), "kg"))
+    }
+}
@@ -0,0 +1,92 @@
+//! Estimated glomerular filtration rate (eGFR) from serum creatinine.
+//!
+//! Uses the race-free CKD-EPI 2021 creatinine equation recommended by the
+//! NKF-ASN task force and KDIGO:
+//!
+//! 142 × min(Scr/κ, 1)^α × max(Scr/κ, 1)^-1.200 × 0.9938^age × 1.012 (female)
+//!
+//! with κ = 0.7 (female) or 0.9 (male) and α = -0.241 (female) or -0.302
+//! (male), Scr in mg/dL. The equation is validated for adults only.
+//!
+//! ```
+//! use wellally::calc::kidney::KidneyFunction;
+//! use wellally::{Quantity, Sex};
+//!
+//! let kidney = KidneyFunction::new(&Quantity::new(106.0, "umol/L"), 60.0, Sex::Male).unwrap();
+//! assert_eq!(kidney.egfr().map(f64::round), Some(69.0));
+//! assert_eq!(kidney.egfr_result().unwrap().code.coding[0].code, "98979-8");
+//! ```
+
+use crate::catalog::{self, LOINC};
+use crate::common::Quantity;
+use crate::error::WellAllyError;
+use crate::family_health::Sex;
+use crate::lab_report::LabResult;
+
+use super::{convert, find, quantity_result, round};
+
+/// LOINC codes of serum or plasma creatinine results (mass and molar concentration)
+pub const SERUM_CREATININE_CODES: &[&str] = &["2160-0", "14682-9"];
+
+/// LOINC eGFR by the CKD-EPI 2021 creatinine equation
+pub const EGFR_CKD_EPI_2021: (&str, &str) = (
+    "98979-8",
+    "Glomerular filtration rate/1.73 sq M.predicted [Volume Rate/Area] by Creatinine-based formula (CKD-EPI 2021)",
+);
+
+/// UCUM unit of eGFR
+pub const EGFR_UNIT: &str = "mL/min/{1.73_m2}";
+
+/// Creatinine units with their factor to mg/dL (molar mass 113.12 g/mol).
+const CREATININE_UNITS: &[(&str, f64)] = &[("mg/dL", 1.0), ("umol/L", 1.0 / 88.42), ("mmol/L", 1000.0 / 88.42)];
+
+/// Serum creatinine, age and sex of an adult.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KidneyFunction {
+    /// Serum creatinine in mg/dL
+    pub creatinine_mg_dl: f64,
+    /// Age in years at the time of the sample
+    pub age_years: f64,
+    /// Sex selecting the CKD-EPI 2021 coefficients
+    pub sex: Sex,
+}
+
+impl KidneyFunction {
+    /// Kidney function from a creatinine quantity in `mg/dL`, `umol/L` or `mmol/L`.
+    pub fn new(creatinine: &Quantity, age_years: f64, sex: Sex) -> Result<Self, WellAllyError> {
+        Ok(Self {
+            creatinine_mg_dl: convert(creatinine, CREATININE_UNITS, ("Quantity", "unit".to_string()))?,
+            age_years,
+            sex,
+        })
+    }
+
+    /// Kidney function from the first LOINC-coded serum creatinine result.
+    pub fn from_results(results: &[LabResult], age_years: f64, sex: Sex) -> Result<Self, WellAllyError> {
+        let (at, creatinine) = find(results, SERUM_CREATININE_CODES, "serum creatinine")?;
+        let path = ("LabReport", format!("results[{}].value.unit", at));
+        Ok(Self { creatinine_mg_dl: convert(creatinine, CREATININE_UNITS, path)?, age_years, sex })
+    }
+
+    /// eGFR in mL/min/1.73 m² by CKD-EPI 2021; `None` unless the sex is
+    /// male or female, the age is at least 18 and the creatinine positive.
+    pub fn egfr(&self) -> Option<f64> {
+        let (kappa, alpha, factor) = match self.sex {
+            Sex::Female => (0.7, -0.241, 1.012),
+            Sex::Male => (0.9, -0.302, 1.0),
+            Sex::Other | Sex::Unknown => return None,
+        };
+        if self.age_years < 18.0 || !(self.creatinine_mg_dl.is_finite() && self.creatinine_mg_dl > 0.0) {
+            return None;
+        }
+        let ratio = self.creatinine_mg_dl / kappa;
+        Some(142.0 * ratio.min(1.0).powf(alpha) * ratio.max(1.0).powf(-1.2) * 0.9938f64.powf(self.age_years) * factor)
+    }
+
+    /// eGFR result coded LOINC 98979-8, rounded to a whole number.
+    pub fn egfr_result(&self) -> Option<LabResult> {
+        let (code, display) = EGFR_CKD_EPI_2021;
+        let value = self.egfr()?;
+        Some(quantity_result(catalog::concept(LOINC, code, display), round(value, 0), EGFR_UNIT))
+    }
+}
@@ -0,0 +1,121 @@
+//! Pregnancy dating: estimated due date, gestational age and trimester.
+//!
+//! The estimated due date (EDD) comes from the last menstrual period (LMP) by
+//! Naegele's rule, from a dating ultrasound, or from both following the ACOG
+//! re-dating rules (Committee Opinion 700): the ultrasound date wins when it
+//! differs from the LMP date by more than the threshold for the gestational
+//! age at which the scan was done.
+
+use chrono::{Days, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+pub use crate::birth::{GestationalAge, Trimester};
+
+/// Length of a pregnancy from the LMP to the due date, in days.
+pub const TERM_DAYS: i64 = 280;
+/// Cycle length assumed by Naegele's rule, in days.
+pub const STANDARD_CYCLE_DAYS: u32 = 28;
+
+/// What the due date is based on.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum DatingBasis {
+    /// Last menstrual period
+    LastMenstrualPeriod,
+    /// Dating ultrasound
+    Ultrasound,
+}
+
+/// A dating ultrasound: scan date and the gestational age it measured.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct UltrasoundDating {
+    /// Date of the scan
+    pub date: NaiveDate,
+    /// Gestational age measured at the scan (e.g., from crown-rump length)
+    #[serde(rename = "gestationalAge", alias = "gestational_age")]
+    pub gestational_age: GestationalAge,
+}
+
+/// Estimated due date of a pregnancy and how it was established.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PregnancyDating {
+    /// Estimated due date
+    #[serde(rename = "estimatedDueDate", alias = "estimated_due_date")]
+    pub estimated_due_date: NaiveDate,
+    /// Source of the estimate
+    pub basis: DatingBasis,
+}
+
+impl PregnancyDating {
+    /// Due date from the first day of the LMP (Naegele's rule), shifted by
+    /// the difference between `cycle_days` and a 28-day cycle. `None` when
+    /// the due date falls past the end of the calendar.
+    pub fn from_lmp(lmp: NaiveDate, cycle_days: Option<u32>) -> Option<Self> {
+        let shift = i64::from(cycle_days.unwrap_or(STANDARD_CYCLE_DAYS)) - i64::from(STANDARD_CYCLE_DAYS);
+        let estimated_due_date = add_days(lmp, TERM_DAYS + shift)?;
+        Some(Self { estimated_due_date, basis: DatingBasis::LastMenstrualPeriod })
+    }
+
+    /// Due date from a dating ultrasound; `None` when it falls outside the calendar.
+    pub fn from_ultrasound(scan: UltrasoundDating) -> Option<Self> {
+        let estimated_due_date = add_days(scan.date, TERM_DAYS - scan.gestational_age.in_days())?;
+        Some(Self { estimated_due_date, basis: DatingBasis::Ultrasound })
+    }
+
+    /// Best obstetric estimate from whatever is known: the LMP date is kept
+    /// unless the first ultrasound differs from it by more than the ACOG
+    /// threshold for the gestational age at the scan. `None` without either,
+    /// or when neither gives a due date within the calendar.
+    pub fn estimate(lmp: Option<NaiveDate>, cycle_days: Option<u32>, scan: Option<UltrasoundDating>) -> Option<Self> {
+        let by_lmp = lmp.and_then(|lmp| Self::from_lmp(lmp, cycle_days));
+        let by_scan = scan.and_then(|scan| Some((scan.gestational_age, Self::from_ultrasound(scan)?)));
+        match (by_lmp, by_scan) {
+            (Some(by_lmp), Some((at_scan, by_scan))) => {
+                let discrepancy = (by_lmp.estimated_due_date - by_scan.estimated_due_date).num_days().abs();
+                if discrepancy > redating_threshold_days(at_scan) {
+                    Some(by_scan)
+                } else {
+                    Some(by_lmp)
+                }
+            }
+            (by_lmp, by_scan) => by_lmp.or(by_scan.map(|(_, by_scan)| by_scan)),
+        }
+    }
+
+    /// First day of the (possibly adjusted) LMP implied by the due date;
+    /// `None` when it falls before the start of the calendar.
+    pub fn pregnancy_start(&self) -> Option<NaiveDate> {
+        add_days(self.estimated_due_date, -TERM_DAYS)
+    }
+
+    /// Gestational age on `date`; `None` before the pregnancy started.
+    pub fn gestational_age(&self, date: NaiveDate) -> Option<GestationalAge> {
+        GestationalAge::from_days((date - self.pregnancy_start()?).num_days())
+    }
+
+    /// Trimester on `date`; `None` before the pregnancy started.
+    pub fn trimester(&self, date: NaiveDate) -> Option<Trimester> {
+        self.gestational_age(date).map(|age| age.trimester())
+    }
+}
+
+/// `date` moved by `days` (back when negative); `None` outside the calendar.
+fn add_days(date: NaiveDate, days: i64) -> Option<NaiveDate> {
+    let offset = Days::new(days.unsigned_abs());
+    if days < 0 {
+        date.checked_sub_days(offset)
+    } else {
+        date.checked_add_days(offset)
+    }
+}
+
+/// Largest LMP/ultrasound discrepancy, in days, that keeps the LMP dating.
+pub fn redating_threshold_days(at_scan: GestationalAge) -> i64 {
+    match at_scan.in_days() {
+        ..63 => 5,
+        63..112 => 7,
+        112..154 => 10,
+        154..196 => 14,
+        _ => 21,
+    }
+}
@@ -0,0 +1,393 @@
+//! Reference terminology used to build realistic records.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//!
+//! A small, curated subset of LOINC, RxNorm, SNOMED CT and DICOM codes with
+//! adult reference ranges and typical value spreads. It backs the data
+//! generators and is handy for demos; it is not a substitute for the full
+//! terminologies.
+
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+
+use crate::common::{CodeableConcept, Coding, Quantity, ReferenceRange, Route};
+use crate::lab_report::{Interpretation, LabReport, LabResult, LabValue, Specimen};
+use crate::medication::{Dosage, MedicationRecord};
+
+/// LOINC code system URI
+pub const LOINC: &str = "http://loinc.org";
+/// SNOMED CT code system URI
+pub const SNOMED_CT: &str = "http://snomed.info/sct";
+/// RxNorm code system URI
+pub const RXNORM: &str = "http://www.nlm.nih.gov/research/umls/rxnorm";
+/// DICOM controlled terminology URI
+pub const DICOM: &str = "http://dicom.nema.org/resources/ontology/DCM";
+/// UCUM units of measure URI
+pub const UCUM: &str = "http://unitsofmeasure.org";
+
+/// HL7 v2 specimen type code system URI (table 0487)
+pub const SPECIMEN_TYPE: &str = "http://terminology.hl7.org/CodeSystem/v2-0487";
+/// HL7 v2 identifier type code system URI (table 0203)
+pub const IDENTIFIER_TYPE: &str = "http://terminology.hl7.org/CodeSystem/v2-0203";
+/// HL7 v3 marital status code system URI
+pub const MARITAL_STATUS: &str = "http://terminology.hl7.org/CodeSystem/v3-MaritalStatus";
+/// HL7 v3 act code system URI (sensitivity categories such as BH or HIV)
+pub const ACT_CODE: &str = "http://terminology.hl7.org/CodeSystem/v3-ActCode";
+/// HL7 v3 confidentiality code system URI (N, R, V, ...)
+pub const CONFIDENTIALITY: &str = "http://terminology.hl7.org/CodeSystem/v3-Confidentiality";
+
+/// Builds a coding with a display text.
+pub fn coding(system: &str, code: &str, display: &str) -> Coding {
+    Coding { system: system.to_string(), code: code.to_string(), display: Some(display.to_string()) }
+}
+
+/// Builds a single-coding concept.
+pub fn concept(system: &str, code: &str, display: &str) -> CodeableConcept {
+    CodeableConcept { coding: vec![coding(system, code, display)], text: None }
+}
+
+/// A quantitative lab test with its adult reference range.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Analyte {
+    /// LOINC code
+    pub code: &'static str,
+    /// LOINC display name
+    pub display: &'static str,
+    /// UCUM unit
+    pub unit: &'static str,
+    /// Lower reference limit
+    pub low: f64,
+    /// Upper reference limit
+    pub high: f64,
+    /// Lowest plausible observed value
+    pub min: f64,
+    /// Highest plausible observed value
+    pub max: f64,
+}
+
+/// A lab panel and the analytes it reports.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Panel {
+    /// LOINC panel code
+    pub code: &'static str,
+    /// LOINC panel display name
+    pub display: &'static str,
+    /// Specimen code (HL7 v2 table 0487)
+    pub specimen: &'static str,
+    /// Analytes reported by the panel
+    pub analytes: &'static [Analyte],
+}
+
+impl Analyte {
+    /// Rounds a raw value to the precision analysers usually report for this range.
+    pub fn round(&self, value: f64) -> f64 {
+        let scale = if self.high >= 100.0 {
+            1.0
+        } else if self.high >= 10.0 {
+            10.0
+        } else {
+            100.0
+        };
+        // `f64::round` needs std; lab values are far within i64 range.
+        let scaled = value * scale;
+        let rounded = if scaled < 0.0 { scaled - 0.5 } else { scaled + 0.5 } as i64;
+        rounded as f64 / scale
+    }
+
+    /// Interpretation of a value against the reference range.
+    pub fn interpret(&self, value: f64) -> Interpretation {
+        if value < self.low {
+            Interpretation::L
+        } else if value > self.high {
+            Interpretation::H
+        } else {
+            Interpretation::N
+        }
+    }
+
+    /// Coded result for a value, with reference range and interpretation.
+    pub fn result(&self, value: f64) -> LabResult {
+        let value = self.round(value);
+        let quantity = |value: f64| Quantity::new(value, self.unit);
+        LabResult {
+            code: concept(LOINC, self.code, self.display),
+            value: LabValue::Quantity(quantity(value)),
+            reference_range: Some(vec![ReferenceRange::new(
+                (self.low > 0.0).then(|| quantity(self.low)),
+                Some(quantity(self.high)),
+            )]),
+            interpretation: Some(self.interpret(value)),
+            method: None,
+            effective: None,
+        }
+    }
+}
+
+const fn analyte(
+    code: &'static str,
+    display: &'static str,
+    unit: &'static str,
+    (low, high): (f64, f64),
+    (min, max): (f64, f64),
+) -> Analyte {
+    Analyte { code, display, unit, low, high, min, max }
+}
+
+impl Panel {
+    /// Report for this panel holding `results`.
+    pub fn report(
+        &self,
+        id: String,
+        patient_id: String,
+        collected_at: DateTime<Utc>,
+        issued_at: DateTime<Utc>,
+        results: Vec<LabResult>,
+    ) -> LabReport {
+        LabReport {
+            id,
+            patient_id,
+            issued_at: issued_at.into(),
+            results,
+            facility: None,
+            panel: Some(concept(LOINC, self.code, self.display)),
+            specimen: Some(Specimen {
+                specimen_type: Some(coding(SPECIMEN_TYPE, self.specimen, self.specimen)),
+                collected_at: Some(collected_at.into()),
+            }),
+            notes: None,
+            schema_version: None,
+            security_labels: None,
+            extensions: None,
+            #[cfg(feature = "preserve-unknown")]
+            extra: Default::default(),
+        }
+    }
+}
+
+/// Complete blood count.
+pub const CBC: Panel = Panel {
+    code: "58410-2",
+    display: "CBC panel - Blood by Automated count",
+    specimen: "BLD",
+    analytes: &[
+        analyte("6690-2", "Leukocytes [#/volume] in Blood by Automated count", "10*3/uL", (4.0, 11.0), (1.5, 25.0)),
+        analyte("789-8", "Erythrocytes [#/volume] in Blood by Automated count", "10*6/uL", (4.2, 5.9), (2.8, 7.0)),
+        analyte("718-7", "Hemoglobin [Mass/volume] in Blood", "g/dL", (12.0, 17.5), (7.0, 19.5)),
+        analyte("4544-3", "Hematocrit [Volume Fraction] of Blood by Automated count", "%", (36.0, 52.0), (22.0, 58.0)),
+        analyte("787-2", "MCV [Entitic volume] by Automated count", "fL", (80.0, 100.0), (65.0, 115.0)),
+        analyte("777-3", "Platelets [#/volume] in Blood by Automated count", "10*3/uL", (150.0, 400.0), (40.0, 650.0)),
+    ],
+};
+
+/// Comprehensive metabolic panel.
+pub const CMP: Panel = Panel {
+    code: "24323-8",
+    display: "Comprehensive metabolic 2000 panel - Serum or Plasma",
+    specimen: "SER",
+    analytes: &[
+        analyte("2345-7", "Glucose [Mass/volume] in Serum or Plasma", "mg/dL", (70.0, 99.0), (50.0, 300.0)),
+        analyte("3094-0", "Urea nitrogen [Mass/volume] in Serum or Plasma", "mg/dL", (7.0, 20.0), (3.0, 60.0)),
+        analyte("2160-0", "Creatinine [Mass/volume] in Serum or Plasma", "mg/dL", (0.6, 1.3), (0.4, 6.0)),
+        analyte("2951-2", "Sodium [Moles/volume] in Serum or Plasma", "mmol/L", (135.0, 145.0), (122.0, 156.0)),
+        analyte("2823-3", "Potassium [Moles/volume] in Serum or Plasma", "mmol/L", (3.5, 5.1), (2.6, 6.6)),
+        analyte("2075-0", "Chloride [Moles/volume] in Serum or Plasma", "mmol/L", (98.0, 107.0), (88.0, 118.0)),
+        analyte("17861-6", "Calcium [Mass/volume] in Serum or Plasma", "mg/dL", (8.6, 10.3), (6.8, 12.5)),
+        analyte("1742-6", "Alanine aminotransferase [Enzymatic activity/volume] in Serum or Plasma", "U/L", (7.0, 56.0), (5.0, 250.0)),
+        analyte("1920-8", "Aspartate aminotransferase [Enzymatic activity/volume] in Serum or Plasma", "U/L", (10.0, 40.0), (6.0, 220.0)),
+        analyte("2885-2", "Protein [Mass/volume] in Serum or Plasma", "g/dL", (6.0, 8.3), (4.5, 9.5)),
+        analyte("1751-7", "Albumin [Mass/volume] in Serum or Plasma", "g/dL", (3.5, 5.0), (2.0, 5.8)),
+    ],
+};
+
+/// Lipid panel.
+pub const LIPID: Panel = Panel {
+    code: "24331-1",
+    display: "Lipid panel - Serum or Plasma",
+    specimen: "SER",
+    analytes: &[
+        analyte("2093-3", "Cholesterol [Mass/volume] in Serum or Plasma", "mg/dL", (0.0, 200.0), (110.0, 320.0)),
+        analyte("2085-9", "Cholesterol in HDL [Mass/volume] in Serum or Plasma", "mg/dL", (40.0, 100.0), (20.0, 95.0)),
+        analyte("13457-7", "Cholesterol in LDL [Mass/volume] in Serum or Plasma by calculation", "mg/dL", (0.0, 100.0), (40.0, 220.0)),
+        analyte("2571-8", "Triglyceride [Mass/volume] in Serum or Plasma", "mg/dL", (0.0, 150.0), (40.0, 500.0)),
+    ],
+};
+
+/// Glycated hemoglobin.
+pub const HBA1C: Panel = Panel {
+    code: "4548-4",
+    display: "Hemoglobin A1c/Hemoglobin.total in Blood",
+    specimen: "BLD",
+    analytes: &[analyte("4548-4", "Hemoglobin A1c/Hemoglobin.total in Blood", "%", (4.0, 5.6), (4.2, 12.5))],
+};
+
+/// Thyroid function.
+pub const THYROID: Panel = Panel {
+    code: "24348-5",
+    display: "Thyroid panel - Serum or Plasma",
+    specimen: "SER",
+    analytes: &[
+        analyte("3016-3", "Thyrotropin [Units/volume] in Serum or Plasma", "mIU/L", (0.4, 4.0), (0.05, 15.0)),
+        analyte("3024-7", "Thyroxine (T4) free [Mass/volume] in Serum or Plasma", "ng/dL", (0.8, 1.8), (0.3, 3.5)),
+    ],
+};
+
+/// All panels in the catalog.
+pub const PANELS: &[Panel] = &[CBC, CMP, LIPID, HBA1C, THYROID];
+
+/// Names and abbreviations printed on lab reports for the catalog analytes,
+/// keyed by LOINC code: English, then Chinese.
+pub const LAB_ALIASES: &[(&str, &[&str])] = &[
+    ("6690-2", &["WBC", "White blood cells", "White blood cell count", "Leukocytes", "白细胞", "白细胞计数"]),
+    ("789-8", &["RBC", "Red blood cells", "Red blood cell count", "Erythrocytes", "红细胞", "红细胞计数"]),
+    ("718-7", &["Hemoglobin", "Haemoglobin", "HGB", "Hb", "血红蛋白"]),
+    ("4544-3", &["Hematocrit", "Haematocrit", "HCT", "红细胞压积"]),
+    ("787-2", &["MCV", "Mean corpuscular volume", "平均红细胞体积"]),
+    ("777-3", &["Platelets", "Platelet count", "PLT", "血小板", "血小板计数"]),
+    ("2345-7", &["Glucose", "Blood glucose", "Fasting glucose", "GLU", "葡萄糖", "血糖", "空腹血糖"]),
+    ("3094-0", &["BUN", "Urea nitrogen", "Blood urea nitrogen", "尿素氮"]),
+    ("2160-0", &["Creatinine", "CREA", "Cr", "肌酐"]),
+    ("2951-2", &["Sodium", "Na", "钠"]),
+    ("2823-3", &["Potassium", "K", "钾"]),
+    ("2075-0", &["Chloride", "Cl", "氯"]),
+    ("17861-6", &["Calcium", "Ca", "钙"]),
+    ("1742-6", &["ALT", "SGPT", "Alanine aminotransferase", "谷丙转氨酶", "丙氨酸氨基转移酶"]),
+    ("1920-8", &["AST", "SGOT", "Aspartate aminotransferase", "谷草转氨酶", "天门冬氨酸氨基转移酶"]),
+    ("2885-2", &["Total protein", "TP", "总蛋白"]),
+    ("1751-7", &["Albumin", "ALB", "白蛋白"]),
+    ("2093-3", &["Total cholesterol", "Cholesterol", "CHOL", "TC", "总胆固醇"]),
+    ("2085-9", &["HDL", "HDL cholesterol", "HDL-C", "高密度脂蛋白胆固醇"]),
+    ("13457-7", &["LDL", "LDL cholesterol", "LDL-C", "低密度脂蛋白胆固醇"]),
+    ("2571-8", &["Triglycerides", "Triglyceride", "TG", "甘油三酯"]),
+    ("4548-4", &["HbA1c", "Hemoglobin A1c", "A1c", "Glycated hemoglobin", "糖化血红蛋白"]),
+    ("3016-3", &["TSH", "Thyrotropin", "Thyroid stimulating hormone", "促甲状腺激素"]),
+    ("3024-7", &["Free T4", "FT4", "Free thyroxine", "游离甲状腺素"]),
+];
+
+/// A coded clinical condition (SNOMED CT).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConditionCode {
+    /// SNOMED CT code
+    pub code: &'static str,
+    /// Display name
+    pub display: &'static str,
+}
+
+impl ConditionCode {
+    /// SNOMED CT concept for the condition.
+    pub fn concept(&self) -> CodeableConcept {
+        concept(SNOMED_CT, self.code, self.display)
+    }
+}
+
+/// Essential hypertension
+pub const HYPERTENSION: ConditionCode = ConditionCode { code: "59621000", display: "Essential hypertension" };
+/// Type 2 diabetes mellitus
+pub const DIABETES_TYPE_2: ConditionCode = ConditionCode { code: "44054006", display: "Diabetes mellitus type 2" };
+/// Hyperlipidemia
+pub const HYPERLIPIDEMIA: ConditionCode = ConditionCode { code: "55822004", display: "Hyperlipidemia" };
+/// Hypothyroidism
+pub const HYPOTHYROIDISM: ConditionCode = ConditionCode { code: "40930008", display: "Hypothyroidism" };
+/// Community-acquired pneumonia
+pub const PNEUMONIA: ConditionCode = ConditionCode { code: "385093006", display: "Community acquired pneumonia" };
+
+/// Conditions commonly recorded in problem lists and family histories.
+pub const CONDITIONS: &[ConditionCode] = &[
+    HYPERTENSION,
+    DIABETES_TYPE_2,
+    HYPERLIPIDEMIA,
+    HYPOTHYROIDISM,
+    ConditionCode { code: "195967001", display: "Asthma" },
+    ConditionCode { code: "254837009", display: "Malignant neoplasm of breast" },
+    ConditionCode { code: "363406005", display: "Malignant neoplasm of colon" },
+    ConditionCode { code: "22298006", display: "Myocardial infarction" },
+    ConditionCode { code: "230690007", display: "Cerebrovascular accident" },
+    ConditionCode { code: "26929004", display: "Alzheimer's disease" },
+];
+
+/// An oral drug product with its usual regimen (RxNorm).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Drug {
+    /// RxNorm code
+    pub code: &'static str,
+    /// RxNorm display name
+    pub display: &'static str,
+    /// Single dose amount
+    pub dose: f64,
+    /// UCUM unit of the dose
+    pub unit: &'static str,
+    /// Usual dosing frequency (QD, BID, TID)
+    pub frequency: &'static str,
+    /// Condition the drug is usually prescribed for
+    pub indication: ConditionCode,
+}
+
+impl Drug {
+    /// Oral regimen of the drug over `days` days starting on `start_date`.
+    pub fn record(&self, id: String, patient_id: String, start_date: NaiveDate, days: i32) -> MedicationRecord {
+        MedicationRecord {
+            id,
+            patient_id,
+            medication: coding(RXNORM, self.code, self.display),
+            dosage: Dosage { value: self.dose, unit: self.unit.to_string() },
+            route: Route::oral(),
+            start_date,
+            form: None,
+            frequency: Some(self.frequency.to_string()),
+            duration_days: Some(days),
+            end_date: Some(start_date + Duration::days(i64::from(days) - 1)),
+            indication: Some(self.indication.concept()),
+            instructions: None,
+            is_prn: false,
+            prn_reason: None,
+            max_dose_per_day: None,
+            dea_schedule: None,
+            notes: None,
+            schema_version: None,
+            security_labels: None,
+            extensions: None,
+            #[cfg(feature = "preserve-unknown")]
+            extra: Default::default(),
+        }
+    }
+}
+
+/// Oral drugs matched to the conditions above.
+pub const DRUGS: &[Drug] = &[
+    Drug { code: "314076", display: "lisinopril 10 MG Oral Tablet", dose: 10.0, unit: "mg", frequency: "QD", indication: HYPERTENSION },
+    Drug { code: "197361", display: "amlodipine 5 MG Oral Tablet", dose: 5.0, unit: "mg", frequency: "QD", indication: HYPERTENSION },
+    Drug { code: "860975", display: "metformin 24 HR 500 MG Extended Release Oral Tablet", dose: 500.0, unit: "mg", frequency: "QD", indication: DIABETES_TYPE_2 },
+    Drug { code: "617310", display: "atorvastatin 20 MG Oral Tablet", dose: 20.0, unit: "mg", frequency: "QD", indication: HYPERLIPIDEMIA },
+    Drug { code: "966247", display: "levothyroxine sodium 0.05 MG Oral Tablet", dose: 50.0, unit: "ug", frequency: "QD", indication: HYPOTHYROIDISM },
+    Drug { code: "308182", display: "amoxicillin 500 MG Oral Capsule", dose: 500.0, unit: "mg", frequency: "TID", indication: PNEUMONIA },
+];
+
+/// SNOMED CT code for the oral route
+pub const ORAL_ROUTE: (&str, &str) = ("26643006", "Oral route");
+/// SNOMED CT code for the tablet dose form
+pub const TABLET_FORM: (&str, &str) = ("385055001", "Tablet");
+
+/// Imaging modality and body site combinations (DICOM code, SNOMED CT body site).
+pub const IMAGING_STUDIES: &[(&str, &str, &str, &str)] = &[
+    ("CT", "Computed Tomography", "51185008", "Structure of chest"),
+    ("CT", "Computed Tomography", "818983003", "Abdomen"),
+    ("MR", "Magnetic Resonance", "12738006", "Brain structure"),
+    ("MR", "Magnetic Resonance", "72696002", "Knee region structure"),
+    ("US", "Ultrasound", "818983003", "Abdomen"),
+    ("US", "Ultrasound", "69748006", "Thyroid structure"),
+    ("XR", "X-Ray", "51185008", "Structure of chest"),
+];
+
+/// Family names used for synthetic people.
+pub const FAMILY_NAMES: &[&str] = &[
+    "Zhang", "Wang", "Li", "Liu", "Chen", "Smith", "Johnson", "Garcia", "Müller", "Nguyen",
+];
+
+/// Given names for synthetic male people.
+pub const MALE_GIVEN_NAMES: &[&str] = &["Wei", "Jun", "Hao", "James", "Daniel", "Lukas", "Minh"];
+
+/// Given names for synthetic female people.
+pub const FEMALE_GIVEN_NAMES: &[&str] = &["Mei", "Xiu", "Ling", "Emma", "Sofia", "Anna", "Lan"];
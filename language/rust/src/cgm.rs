@@ -0,0 +1,309 @@
+//! Continuous glucose monitoring (CGM) data model.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//! Schema: https://wellall.health/schemas/glucose-series/v0.1.0
+//!
+//! A CGM sensor reports glucose every few minutes, around 288 readings a day,
+//! which is a different shape from the discrete results of a lab report. A
+//! [`GlucoseSeries`] stores the readings of one sensor session at a fixed
+//! interval from a start time, run-length encoded: `values` holds each run's
+//! reading (`null` for a missed one) and `counts` how many consecutive
+//! samples it covers. Flat overnight stretches and sensor gaps take one entry
+//! each.
+//!
+//! The aggregates follow the 2019 international consensus on time in range:
+//! [`GlucoseSeries::time_in_range`], [`GlucoseSeries::mean`],
+//! [`GlucoseSeries::gmi`] and, with `std`, [`GlucoseSeries::variability`].
+//! Readings in `mmol/L` are converted at 18 mg/dL per mmol/L.
+//!
+//! ```
+//! use wellally::cgm::GlucoseSeries;
+//!
+//! let samples = [Some(110.0), Some(110.0), Some(190.0), None, Some(60.0)];
+//! let series = GlucoseSeries::from_samples("cgm-1", "p-1", "mg/dL", "2024-10-01T00:00:00Z".parse().unwrap(), 300, &samples);
+//! assert_eq!(series.values, vec![Some(110.0), Some(190.0), None, Some(60.0)]);
+//! assert_eq!(series.counts, Some(vec![2, 1, 1, 1]));
+//!
+//! let tir = series.time_in_range().unwrap();
+//! assert_eq!((tir.low, tir.in_range, tir.high), (25.0, 50.0, 25.0));
+//! assert_eq!(series.mean(), Some(117.5));
+//! ```
+
+use alloc::{string::String, vec, vec::Vec};
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::common::{DataSource, HasDataSource, Reference, UCUMUnit, Coding};
+use crate::datetime::FlexibleDateTime;
+use crate::extension::Extension;
+use crate::ucum;
+
+/// mg/dL of glucose per mmol/L
+pub const MG_DL_PER_MMOL_L: f64 = 18.0;
+
+/// A sensor session of glucose readings at a fixed interval.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "GlucoseSeriesInput")
+)]
+pub struct GlucoseSeries {
+    /// Unique series identifier
+    pub id: String,
+    /// Reference to Person.id
+    #[serde(rename = "patientId", alias = "patient_id")]
+    pub patient_id: String,
+    /// Sensor or receiver that recorded the series (e.g., "Device/libre-3")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device: Option<Reference>,
+    /// Device, app and capture method of the readings
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<DataSource>,
+    /// UCUM unit of the readings: "mg/dL" or "mmol/L"
+    #[cfg_attr(feature = "utoipa", schema(value_type = String))]
+    pub unit: UCUMUnit,
+    /// Time of the first sample
+    #[cfg_attr(feature = "utoipa", schema(value_type = String))]
+    pub start: FlexibleDateTime,
+    /// Seconds between samples (e.g., 300 for a 5-minute sensor)
+    #[serde(rename = "intervalSeconds", alias = "interval_seconds")]
+    pub interval_seconds: u32,
+    /// Reading of each run; `null` for samples the sensor missed
+    pub values: Vec<Option<f64>>,
+    /// Number of consecutive samples of each run; absent when every run is one sample
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub counts: Option<Vec<u32>>,
+    /// Schema version the resource was written against (e.g., "0.1.0"); absent means 0.1.0
+    #[serde(rename = "schemaVersion", alias = "schema_version", skip_serializing_if = "Option::is_none")]
+    pub schema_version: Option<String>,
+    /// Confidentiality and sensitivity labels (e.g., v3 ActCode HIV); see [`crate::security`]
+    #[serde(rename = "securityLabels", alias = "security_labels", skip_serializing_if = "Option::is_none")]
+    pub security_labels: Option<Vec<Coding>>,
+    /// Site-specific extensions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extensions: Option<Vec<Extension>>,
+    /// Properties this model version does not define, kept for re-serialization
+    #[cfg(feature = "preserve-unknown")]
+    #[serde(flatten)]
+    #[cfg_attr(feature = "schemars", schemars(skip))]
+    #[cfg_attr(feature = "utoipa", schema(ignore))]
+    #[cfg_attr(feature = "async-graphql", graphql(skip))]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// Percentages of readings in the consensus glucose ranges.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TimeInRange {
+    /// Below 54 mg/dL (level 2 hypoglycaemia)
+    pub very_low: f64,
+    /// 54–69 mg/dL (level 1 hypoglycaemia)
+    pub low: f64,
+    /// 70–180 mg/dL, the target range
+    pub in_range: f64,
+    /// 181–250 mg/dL
+    pub high: f64,
+    /// Above 250 mg/dL
+    pub very_high: f64,
+}
+
+/// Glycaemic variability of a series.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Variability {
+    /// Sample standard deviation in mg/dL
+    pub standard_deviation: f64,
+    /// Coefficient of variation in percent; 36 or less is considered stable
+    pub coefficient_of_variation: f64,
+}
+
+impl GlucoseSeries {
+    /// Series of `samples` taken every `interval_seconds` from `start`,
+    /// run-length encoded.
+    pub fn from_samples(
+        id: impl Into<String>,
+        patient_id: impl Into<String>,
+        unit: impl Into<UCUMUnit>,
+        start: FlexibleDateTime,
+        interval_seconds: u32,
+        samples: &[Option<f64>],
+    ) -> Self {
+        let (values, counts) = encode(samples.iter().map(|sample| (*sample, 1)));
+        GlucoseSeries {
+            id: id.into(),
+            patient_id: patient_id.into(),
+            device: None,
+            source: None,
+            unit: unit.into(),
+            start,
+            interval_seconds,
+            values,
+            counts,
+            schema_version: None,
+            security_labels: None,
+            extensions: None,
+            #[cfg(feature = "preserve-unknown")]
+            extra: Default::default(),
+        }
+    }
+
+    /// Each run's reading and number of samples; without `counts` every run
+    /// is one sample.
+    pub fn runs(&self) -> impl Iterator<Item = (Option<f64>, u32)> + '_ {
+        let counts = self.counts.as_deref();
+        self.values.iter().enumerate().map(move |(i, value)| (*value, counts.map_or(1, |counts| counts.get(i).copied().unwrap_or(0))))
+    }
+
+    /// Number of samples, missed ones included.
+    pub fn len(&self) -> usize {
+        self.runs().map(|(_, count)| count as usize).sum()
+    }
+
+    /// Whether the series has no samples.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Every sample in order, `None` where the sensor missed one.
+    pub fn samples(&self) -> Vec<Option<f64>> {
+        self.runs().flat_map(|(value, count)| core::iter::repeat_n(value, count as usize)).collect()
+    }
+
+    /// Time and value of each reading, skipping missed samples; `None` when
+    /// a reading falls outside the calendar.
+    pub fn readings(&self) -> Option<Vec<(DateTime<Utc>, f64)>> {
+        self.samples()
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, value)| Some((i, value?)))
+            .map(|(i, value)| Some((self.sample_time(i)?, value)))
+            .collect()
+    }
+
+    /// Time of the last sample; `None` when it falls outside the calendar.
+    pub fn end(&self) -> Option<DateTime<Utc>> {
+        self.sample_time(self.len().saturating_sub(1))
+    }
+
+    /// Time of the `index`th sample.
+    fn sample_time(&self, index: usize) -> Option<DateTime<Utc>> {
+        let seconds = i64::try_from(index).ok()?.checked_mul(self.interval_seconds.into())?;
+        self.start.to_utc().checked_add_signed(TimeDelta::try_seconds(seconds)?)
+    }
+
+    /// Converts a reading to mg/dL; `None` for units other than mass or
+    /// substance concentrations.
+    fn to_mg_dl(&self) -> Option<impl Fn(f64) -> f64> {
+        let factor = if ucum::commensurable(&self.unit, "mg/dL") {
+            ucum::factor(&self.unit, "mg/dL").ok()?
+        } else {
+            ucum::factor(&self.unit, "mmol/L").ok()? * MG_DL_PER_MMOL_L
+        };
+        Some(move |value: f64| value * factor)
+    }
+
+    /// Readings in mg/dL with their sample counts.
+    fn weighted_mg_dl(&self) -> Option<Vec<(f64, f64)>> {
+        let to_mg_dl = self.to_mg_dl()?;
+        Some(self.runs().filter_map(|(value, count)| Some((to_mg_dl(value?), f64::from(count)))).filter(|(_, n)| *n > 0.0).collect())
+    }
+
+    /// Share of readings in each consensus range, in percent; `None` without
+    /// readings or in an unknown unit.
+    pub fn time_in_range(&self) -> Option<TimeInRange> {
+        let readings = self.weighted_mg_dl()?;
+        let total: f64 = readings.iter().map(|(_, n)| n).sum();
+        if total == 0.0 {
+            return None;
+        }
+        let mut tir = TimeInRange::default();
+        for (value, n) in readings {
+            let bin = match value {
+                v if v < 54.0 => &mut tir.very_low,
+                v if v < 70.0 => &mut tir.low,
+                v if v <= 180.0 => &mut tir.in_range,
+                v if v <= 250.0 => &mut tir.high,
+                _ => &mut tir.very_high,
+            };
+            *bin += n;
+        }
+        for bin in [&mut tir.very_low, &mut tir.low, &mut tir.in_range, &mut tir.high, &mut tir.very_high] {
+            *bin = *bin * 100.0 / total;
+        }
+        Some(tir)
+    }
+
+    /// Mean glucose in mg/dL.
+    pub fn mean(&self) -> Option<f64> {
+        let readings = self.weighted_mg_dl()?;
+        let total: f64 = readings.iter().map(|(_, n)| n).sum();
+        (total > 0.0).then(|| readings.iter().map(|(value, n)| value * n).sum::<f64>() / total)
+    }
+
+    /// Glucose management indicator, the HbA1c (%) estimated from the mean:
+    /// 3.31 + 0.02392 × mean mg/dL. Meaningful for 14 days or more of data.
+    pub fn gmi(&self) -> Option<f64> {
+        Some(3.31 + 0.02392 * self.mean()?)
+    }
+
+    /// Standard deviation and coefficient of variation; `None` with fewer
+    /// than two readings.
+    #[cfg(feature = "std")]
+    pub fn variability(&self) -> Option<Variability> {
+        let readings = self.weighted_mg_dl()?;
+        let total: f64 = readings.iter().map(|(_, n)| n).sum();
+        let mean = self.mean()?;
+        if total < 2.0 {
+            return None;
+        }
+        let squares: f64 = readings.iter().map(|(value, n)| (value - mean) * (value - mean) * n).sum();
+        let standard_deviation = (squares / (total - 1.0)).sqrt();
+        Some(Variability { standard_deviation, coefficient_of_variation: standard_deviation / mean * 100.0 })
+    }
+
+    /// The series averaged over `interval_seconds` buckets from the start; a
+    /// bucket without readings is a missed sample. Intervals shorter than the
+    /// series' own keep it.
+    pub fn downsample(&self, interval_seconds: u32) -> GlucoseSeries {
+        let source = u64::from(self.interval_seconds.max(1));
+        let target = u64::from(interval_seconds).max(source);
+        let mut buckets: Vec<(f64, u32)> = Vec::new();
+        for (i, sample) in self.samples().into_iter().enumerate() {
+            let bucket = (i as u64 * source / target) as usize;
+            if buckets.len() <= bucket {
+                buckets.resize(bucket + 1, (0.0, 0));
+            }
+            if let Some(value) = sample {
+                buckets[bucket].0 += value;
+                buckets[bucket].1 += 1;
+            }
+        }
+        let (values, counts) = encode(buckets.into_iter().map(|(sum, n)| ((n > 0).then(|| sum / f64::from(n)), 1)));
+        GlucoseSeries { interval_seconds: target as u32, values, counts, ..self.clone() }
+    }
+}
+
+impl HasDataSource for GlucoseSeries {
+    fn data_source(&self) -> Option<&DataSource> {
+        self.source.as_ref()
+    }
+}
+
+/// Run-length encodes `(value, count)` runs, merging equal neighbours;
+/// counts are left out when every run is one sample.
+fn encode(runs: impl Iterator<Item = (Option<f64>, u32)>) -> (Vec<Option<f64>>, Option<Vec<u32>>) {
+    let (mut values, mut counts): (Vec<Option<f64>>, Vec<u32>) = (vec![], vec![]);
+    for (value, count) in runs {
+        match (values.last(), counts.last_mut()) {
+            (Some(last), Some(n)) if *last == value => *n += count,
+            _ => {
+                values.push(value);
+                counts.push(count);
+            }
+        }
+    }
+    let counts = counts.iter().any(|n| *n != 1).then_some(counts);
+    (values, counts)
+}
@@ -0,0 +1,194 @@
+//! A patient's chart and the clinical summary derived from it.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//!
+//! A [`PatientChart`] holds the records of one patient that a
+//! [`ClinicalSummary`] condenses: conditions, allergies, medications and the
+//! reports vital signs are read from, plus the immunizations a patient
+//! summary document (`wellally::ips`) also lists. Deriving the summary from
+//! the chart instead of keeping it by hand keeps it from drifting out of date:
+//!
+//! - conditions: the active problems, after reconciling the chart's
+//!   conditions as [`problem_list::update`] does;
+//! - allergies: the active allergies (see [`AllergyIntolerance::is_active`]);
+//! - the number of medications current on the day of the summary;
+//! - the latest report with vital signs, as a [`VitalsSnapshot`].
+//!
+//! [`SummaryOptions`] sets the time of the summary, stamped as `derivedAt`,
+//! and which sources it trusts. Blood type and primary care provider are not
+//! in the chart and are left empty.
+//!
+//! ```
+//! use wellally::catalog::{concept, SNOMED_CT};
+//! use wellally::chart::{PatientChart, SummaryOptions, SummarySource};
+//! use wellally::{ClinicalSummary, Condition};
+//!
+//! let mut chart = PatientChart::new("p-1");
+//! chart.conditions.push(Condition::new("c-1", "p-1", concept(SNOMED_CT, "38341003", "Hypertension")));
+//!
+//! let mut options = SummaryOptions::new("2024-06-01T09:00:00Z".parse().unwrap());
+//! options.sources = vec![SummarySource::Conditions];
+//! let summary = ClinicalSummary::derive_with(&chart, &options);
+//! assert_eq!(summary.conditions.unwrap()[0].coding[0].code, "38341003");
+//! assert_eq!(summary.allergies, None);
+//! assert_eq!(summary.derived_at, Some(options.at));
+//! ```
+
+use alloc::{string::String, vec::Vec};
+
+use crate::allergy::AllergyIntolerance;
+use crate::common::CodeableConcept;
+use crate::condition::{Condition, VerificationStatus};
+use crate::datetime::FlexibleDateTime;
+use crate::health::{ClinicalSummary, VitalsSnapshot};
+use crate::immunization::ImmunizationRecord;
+use crate::lab_report::LabReport;
+use crate::medication::MedicationRecord;
+use crate::problem_list;
+use crate::vitals::Vitals;
+
+/// The records of one patient, in the order they were gathered.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PatientChart {
+    /// Reference to Person.id
+    pub patient_id: String,
+    pub conditions: Vec<Condition>,
+    pub allergies: Vec<AllergyIntolerance>,
+    pub medications: Vec<MedicationRecord>,
+    /// Lab and vital sign reports
+    pub lab_reports: Vec<LabReport>,
+    pub immunizations: Vec<ImmunizationRecord>,
+}
+
+impl PatientChart {
+    /// Empty chart of `patient_id`.
+    pub fn new(patient_id: impl Into<String>) -> Self {
+        PatientChart { patient_id: patient_id.into(), ..Default::default() }
+    }
+
+    /// Chart of `patient_id` from the resources of `bundle` that belong to
+    /// that patient, in entry order.
+    #[cfg(feature = "std")]
+    pub fn from_bundle(bundle: &crate::io::Bundle, patient_id: &str) -> Self {
+        use crate::io::AnyResource;
+
+        let mut chart = PatientChart::new(patient_id);
+        for resource in bundle.resources.iter().filter(|resource| resource.patient_id() == patient_id) {
+            match resource {
+                AnyResource::Condition(condition) => chart.conditions.push(condition.clone()),
+                AnyResource::AllergyIntolerance(allergy) => chart.allergies.push(allergy.clone()),
+                AnyResource::MedicationRecord(medication) => chart.medications.push(medication.clone()),
+                AnyResource::LabReport(report) => chart.lab_reports.push(report.clone()),
+                AnyResource::ImmunizationRecord(dose) => chart.immunizations.push(dose.clone()),
+                _ => {}
+            }
+        }
+        chart
+    }
+}
+
+/// Section of a [`ClinicalSummary`] derived from the chart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SummarySource {
+    Conditions,
+    Allergies,
+    Medications,
+    Vitals,
+}
+
+impl SummarySource {
+    /// Every source, in summary order
+    pub const ALL: [SummarySource; 4] =
+        [SummarySource::Conditions, SummarySource::Allergies, SummarySource::Medications, SummarySource::Vitals];
+}
+
+/// When a summary is derived and which of the chart's records it trusts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SummaryOptions {
+    /// Time of the summary: stamped as `derivedAt`; medications must be
+    /// current and reports issued by then
+    pub at: FlexibleDateTime,
+    /// Sections derived from the chart; the others are left empty
+    pub sources: Vec<SummarySource>,
+    /// Only take conditions and allergies whose verification status is confirmed
+    pub confirmed_only: bool,
+    /// Facilities (Facility.id or name) whose reports vital signs are read
+    /// from; `None` trusts every report
+    pub trusted_facilities: Option<Vec<String>>,
+}
+
+impl SummaryOptions {
+    /// Options deriving every section at `at` from all records.
+    pub fn new(at: FlexibleDateTime) -> Self {
+        SummaryOptions { at, sources: SummarySource::ALL.to_vec(), confirmed_only: false, trusted_facilities: None }
+    }
+
+    fn uses(&self, source: SummarySource) -> bool {
+        self.sources.contains(&source)
+    }
+
+    fn trusts_verification(&self, status: Option<VerificationStatus>) -> bool {
+        !self.confirmed_only || status == Some(VerificationStatus::Confirmed)
+    }
+
+    fn trusts_report(&self, report: &LabReport) -> bool {
+        self.trusted_facilities.as_ref().is_none_or(|trusted| {
+            report.facility.as_ref().is_some_and(|facility| {
+                [&facility.id, &facility.name].into_iter().flatten().any(|name| trusted.contains(name))
+            })
+        })
+    }
+}
+
+impl ClinicalSummary {
+    /// Summary of `chart` as of now, from all its records.
+    #[cfg(feature = "std")]
+    pub fn derive(chart: &PatientChart) -> Self {
+        ClinicalSummary::derive_with(chart, &SummaryOptions::new(chrono::Utc::now().into()))
+    }
+
+    /// Summary of `chart` as `options` set it; see the [module docs](crate::chart).
+    pub fn derive_with(chart: &PatientChart, options: &SummaryOptions) -> Self {
+        let day = options.at.to_utc().date_naive();
+        let conditions = options.uses(SummarySource::Conditions).then(|| {
+            let trusted = chart.conditions.iter().filter(|condition| {
+                options.trusts_verification(condition.verification_status)
+            });
+            problem_list::active_problems(&problem_list::update(Vec::new(), trusted.cloned().collect()))
+        });
+        let allergies = options.uses(SummarySource::Allergies).then(|| {
+            chart
+                .allergies
+                .iter()
+                .filter(|allergy| allergy.is_active() && options.trusts_verification(allergy.verification_status))
+                .map(|allergy| allergy.code.clone())
+                .collect::<Vec<CodeableConcept>>()
+        });
+        let current_medication_count = options
+            .uses(SummarySource::Medications)
+            .then(|| chart.medications.iter().filter(|medication| medication.is_active_on(day)).count() as u32);
+        let latest_vitals = if options.uses(SummarySource::Vitals) { latest_vitals(chart, options) } else { None };
+        ClinicalSummary {
+            conditions,
+            allergies,
+            current_medication_count,
+            latest_vitals,
+            derived_at: Some(options.at),
+            ..Default::default()
+        }
+    }
+}
+
+/// Vital signs of the latest trusted report issued by `options.at` that has any.
+fn latest_vitals(chart: &PatientChart, options: &SummaryOptions) -> Option<VitalsSnapshot> {
+    let at = options.at.to_utc();
+    chart
+        .lab_reports
+        .iter()
+        .filter(|report| report.issued_at.to_utc() <= at && options.trusts_report(report))
+        .map(|report| (report, Vitals::from_results(&report.results)))
+        .filter(|(_, vitals)| *vitals != Vitals::default())
+        .max_by_key(|(report, _)| report.issued_at.to_utc())
+        .map(|(report, vitals)| VitalsSnapshot::new(report.id.clone(), report.issued_at, &vitals))
+}
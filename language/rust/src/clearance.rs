@@ -0,0 +1,266 @@
+//! Clearance document model.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//! Schema: https://wellall.health/schemas/clearance/v0.1.0
+//!
+//! A [`ClearanceDocument`] is the form a school, team or employer asks for: a
+//! pre-participation sports physical, a fitness-for-work or a return-to-work
+//! certificate. It states the outcome, the restrictions, how long it is valid
+//! and who signed it, and points at the immunizations and lab results it
+//! relies on instead of copying them. [`ClearanceDocument::link_lab_evidence`]
+//! fills those pointers from the patient's lab reports.
+//!
+//! ```
+//! use wellally::clearance::{ClearanceDocument, ClearanceOutcome, ClearanceType, Restriction};
+//! use wellally::Reference;
+//!
+//! let issued = "2024-08-20".parse().unwrap();
+//! let clinician = Reference { reference: "Practitioner/dr-li".into(), display: None };
+//! let mut form = ClearanceDocument::new("clr-1", "p-1", ClearanceType::ReturnToWork, issued, clinician);
+//! form.outcome = ClearanceOutcome::ClearedWithRestrictions;
+//! form.restrictions.push(Restriction::new("No lifting over 10 kg").with_until("2024-09-20".parse().unwrap()));
+//! assert!(form.is_valid_on("2024-09-01".parse().unwrap()));
+//! assert_eq!(form.restrictions_on("2024-10-01".parse().unwrap()).len(), 0);
+//! ```
+
+use alloc::{string::String, vec::Vec};
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::common::{Annotation, CodeableConcept, Coding, Period, Reference};
+use crate::extension::Extension;
+use crate::lab_report::LabReport;
+
+/// Kind of clearance.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "async-graphql", derive(async_graphql::Enum))]
+#[serde(rename_all = "kebab-case")]
+pub enum ClearanceType {
+    /// Pre-participation physical for school or club sports
+    SportsPhysical,
+    /// Pre-placement or periodic fitness for a job
+    FitnessForWork,
+    /// Return to work after illness or injury
+    ReturnToWork,
+}
+
+/// Decision of the clinician.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "async-graphql", derive(async_graphql::Enum))]
+#[serde(rename_all = "kebab-case")]
+pub enum ClearanceOutcome {
+    Cleared,
+    /// Cleared within the listed restrictions
+    ClearedWithRestrictions,
+    NotCleared,
+    /// Decision pending further evaluation or evidence
+    Deferred,
+}
+
+impl ClearanceOutcome {
+    /// Whether the patient may take part, with or without restrictions.
+    pub fn is_cleared(&self) -> bool {
+        matches!(self, ClearanceOutcome::Cleared | ClearanceOutcome::ClearedWithRestrictions)
+    }
+}
+
+/// A limit on what the patient may do.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "RestrictionInput")
+)]
+pub struct Restriction {
+    /// Restriction as written on the form (e.g., "No lifting over 10 kg")
+    pub description: String,
+    /// Coded restriction, when the program uses a terminology
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<CodeableConcept>,
+    /// Last day the restriction applies; absent while the clearance is valid
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub until: Option<NaiveDate>,
+}
+
+impl Restriction {
+    /// Restriction for as long as the clearance is valid.
+    pub fn new(description: impl Into<String>) -> Self {
+        Restriction { description: description.into(), code: None, until: None }
+    }
+
+    /// The restriction lifted after `until`.
+    pub fn with_until(mut self, until: NaiveDate) -> Self {
+        self.until = Some(until);
+        self
+    }
+
+    /// Whether the restriction still applies on `date`.
+    pub fn applies_on(&self, date: NaiveDate) -> bool {
+        self.until.is_none_or(|until| date <= until)
+    }
+}
+
+/// Evidence the clearance requires, such as a vaccine or a screening test.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "RequiredEvidenceInput")
+)]
+pub struct RequiredEvidence {
+    /// What is required (e.g., CVX-coded vaccine, LOINC-coded test)
+    pub code: CodeableConcept,
+    /// Record that meets the requirement (e.g., "LabReport/lab-1"); absent while missing
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub evidence: Option<Reference>,
+}
+
+impl RequiredEvidence {
+    /// Requirement not met yet.
+    pub fn new(code: CodeableConcept) -> Self {
+        RequiredEvidence { code, evidence: None }
+    }
+
+    /// Whether a record meets the requirement.
+    pub fn is_met(&self) -> bool {
+        self.evidence.is_some()
+    }
+}
+
+/// School, sports or occupational clearance.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "ClearanceDocumentInput")
+)]
+pub struct ClearanceDocument {
+    /// Unique document identifier
+    pub id: String,
+    /// Reference to Person.id
+    #[serde(rename = "patientId", alias = "patient_id")]
+    pub patient_id: String,
+    #[serde(rename = "type")]
+    #[cfg_attr(feature = "async-graphql", graphql(name = "type"))]
+    pub clearance_type: ClearanceType,
+    pub outcome: ClearanceOutcome,
+    /// Day the clinician signed the document
+    #[serde(rename = "issuedOn", alias = "issued_on")]
+    pub issued_on: NaiveDate,
+    /// Days the clearance is valid; an open end means until revoked
+    #[serde(rename = "validPeriod", alias = "valid_period")]
+    pub valid_period: Period,
+    /// Sport, job or role cleared for (e.g., "Football", "Forklift operator")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub activity: Option<String>,
+    /// School, team or employer the document is for (e.g., "Organization/school-12")
+    #[serde(rename = "requestedBy", alias = "requested_by", skip_serializing_if = "Option::is_none")]
+    pub requested_by: Option<Reference>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub restrictions: Vec<Restriction>,
+    /// Immunizations and lab results the clearance relies on
+    #[serde(rename = "requiredEvidence", alias = "required_evidence", default, skip_serializing_if = "Vec::is_empty")]
+    pub required_evidence: Vec<RequiredEvidence>,
+    /// Clinician who signed the document (e.g., "Practitioner/dr-li")
+    pub clinician: Reference,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notes: Option<Vec<Annotation>>,
+    /// Schema version the resource was written against (e.g., "0.1.0"); absent means 0.1.0
+    #[serde(rename = "schemaVersion", alias = "schema_version", skip_serializing_if = "Option::is_none")]
+    pub schema_version: Option<String>,
+    /// Confidentiality and sensitivity labels (e.g., v3 ActCode HIV); see [`crate::security`]
+    #[serde(rename = "securityLabels", alias = "security_labels", skip_serializing_if = "Option::is_none")]
+    pub security_labels: Option<Vec<Coding>>,
+    /// Site-specific extensions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extensions: Option<Vec<Extension>>,
+    /// Properties this model version does not define, kept for re-serialization
+    #[cfg(feature = "preserve-unknown")]
+    #[serde(flatten)]
+    #[cfg_attr(feature = "schemars", schemars(skip))]
+    #[cfg_attr(feature = "utoipa", schema(ignore))]
+    #[cfg_attr(feature = "async-graphql", graphql(skip))]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl ClearanceDocument {
+    /// Unrestricted clearance valid from `issued_on` with an open end.
+    pub fn new(
+        id: impl Into<String>,
+        patient_id: impl Into<String>,
+        clearance_type: ClearanceType,
+        issued_on: NaiveDate,
+        clinician: Reference,
+    ) -> Self {
+        ClearanceDocument {
+            id: id.into(),
+            patient_id: patient_id.into(),
+            clearance_type,
+            outcome: ClearanceOutcome::Cleared,
+            issued_on,
+            valid_period: Period { start: Some(issued_on), end: None },
+            activity: None,
+            requested_by: None,
+            restrictions: Vec::new(),
+            required_evidence: Vec::new(),
+            clinician,
+            notes: None,
+            schema_version: None,
+            security_labels: None,
+            extensions: None,
+            #[cfg(feature = "preserve-unknown")]
+            extra: Default::default(),
+        }
+    }
+
+    /// Whether the document clears the patient on `date`.
+    pub fn is_valid_on(&self, date: NaiveDate) -> bool {
+        self.outcome.is_cleared() && self.valid_period.contains(date)
+    }
+
+    /// Restrictions that still apply on `date`.
+    pub fn restrictions_on(&self, date: NaiveDate) -> Vec<&Restriction> {
+        self.restrictions.iter().filter(|restriction| restriction.applies_on(date)).collect()
+    }
+
+    /// Requirements no record meets yet.
+    pub fn missing_evidence(&self) -> Vec<&RequiredEvidence> {
+        self.required_evidence.iter().filter(|required| !required.is_met()).collect()
+    }
+
+    /// Points each unmet requirement at the latest of the patient's
+    /// `reports` with a result coded like it, and returns how many were met.
+    pub fn link_lab_evidence(&mut self, reports: &[LabReport]) -> usize {
+        let mut linked = 0;
+        for required in self.required_evidence.iter_mut().filter(|required| !required.is_met()) {
+            let latest = reports
+                .iter()
+                .filter(|report| report.patient_id == self.patient_id)
+                .filter(|report| {
+                    let mut codes = report.results.iter().flat_map(|result| &result.code.coding);
+                    codes.any(|coding| {
+                        let wanted = &required.code.coding;
+                        wanted.iter().any(|wanted| wanted.system == coding.system && wanted.code == coding.code)
+                    })
+                })
+                .max_by_key(|report| report.issued_at.to_utc());
+            if let Some(report) = latest {
+                required.evidence = Some(Reference::to(report));
+                linked += 1;
+            }
+        }
+        linked
+    }
+}
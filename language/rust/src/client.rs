@@ -0,0 +1,442 @@
+//! Typed async client for the WellAlly API.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//!
+//! [`Client`] reads and writes resources over the WellAlly REST API with
+//! these models: `GET`, `PUT` and `DELETE` on `{base}/{resourceType}/{id}`,
+//! and paginated listing on `{base}/{resourceType}?patientId=...`, which
+//! returns `{"items": [...], "next": "<cursor>"}`. Uploads are validated
+//! before they are sent. Connection failures, `429` and `5xx` responses are
+//! retried with exponential backoff (honoring `Retry-After`), which is safe
+//! because every request is idempotent. Requests carry a bearer token from
+//! a fixed string or a [`TokenProvider`] such as [`ClientCredentials`].
+//!
+//! ```no_run
+//! use wellally::client::{Client, ClientCredentials};
+//!
+//! # async fn run(report: wellally::LabReport) -> Result<(), wellally::client::ClientError> {
+//! let credentials = ClientCredentials::new("https://auth.wellally.tech/oauth/token", "my-app", "secret");
+//! let client = Client::new("https://api.wellally.tech/v1/")?.with_token_provider(credentials);
+//!
+//! client.upload_lab_report(&report).await?;
+//! let person = client.get_person("patient-123").await?;
+//! let medications = client.list_medications("patient-123").await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use reqwest::{header, Method, RequestBuilder, Response, StatusCode, Url};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{self, WellAllyError};
+use crate::health::Person;
+use crate::lab_report::LabReport;
+use crate::medication::MedicationRecord;
+use crate::resource::Resource;
+use crate::validation::Validate;
+
+/// Retries after the first attempt by default.
+pub const DEFAULT_MAX_RETRIES: u32 = 3;
+
+/// Default wait before the first retry; doubled for each further one.
+pub const DEFAULT_RETRY_BACKOFF_MILLIS: u64 = 200;
+
+/// Default number of resources requested per page.
+pub const DEFAULT_PAGE_SIZE: usize = 100;
+
+/// Tokens are refreshed this long before they expire.
+const TOKEN_LEEWAY: Duration = Duration::from_secs(30);
+
+/// Errors raised by the client.
+#[derive(Debug)]
+pub enum ClientError {
+    /// Transport failure, or a response body that is not JSON
+    Http(reqwest::Error),
+    /// The server answered with an error status
+    Status {
+        /// HTTP status code
+        status: u16,
+        /// Response body, for diagnostics
+        body: String,
+    },
+    /// A resource failed validation before upload or could not be read
+    Data(WellAllyError),
+    /// No access token could be obtained
+    Auth(String),
+    /// The base URL or a path segment is not a valid URL
+    Url(String),
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientError::Http(err) => write!(f, "http error: {}", err),
+            ClientError::Status { status, body } => write!(f, "server returned {}: {}", status, body),
+            ClientError::Data(err) => write!(f, "invalid resource: {}", err),
+            ClientError::Auth(message) => write!(f, "cannot authenticate: {}", message),
+            ClientError::Url(message) => write!(f, "invalid url: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ClientError::Http(err) => Some(err),
+            ClientError::Data(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for ClientError {
+    fn from(err: reqwest::Error) -> Self {
+        ClientError::Http(err)
+    }
+}
+
+impl From<WellAllyError> for ClientError {
+    fn from(err: WellAllyError) -> Self {
+        ClientError::Data(err)
+    }
+}
+
+/// Future returned by [`TokenProvider::token`].
+pub type TokenFuture<'a> = Pin<Box<dyn Future<Output = Result<String, ClientError>> + Send + 'a>>;
+
+/// Source of bearer tokens, asked before every request; implementations
+/// cache tokens until they expire.
+pub trait TokenProvider: Send + Sync {
+    /// A valid access token.
+    fn token(&self) -> TokenFuture<'_>;
+}
+
+/// OAuth 2.0 client-credentials grant against a token endpoint, caching the
+/// token until shortly before it expires.
+pub struct ClientCredentials {
+    http: reqwest::Client,
+    token_url: String,
+    client_id: String,
+    client_secret: String,
+    scope: Option<String>,
+    cached: Mutex<Option<(String, Instant)>>,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    expires_in: Option<u64>,
+}
+
+impl ClientCredentials {
+    /// Credentials of `client_id` for the token endpoint `token_url`.
+    pub fn new(token_url: impl Into<String>, client_id: impl Into<String>, client_secret: impl Into<String>) -> Self {
+        ClientCredentials {
+            http: reqwest::Client::new(),
+            token_url: token_url.into(),
+            client_id: client_id.into(),
+            client_secret: client_secret.into(),
+            scope: None,
+            cached: Mutex::new(None),
+        }
+    }
+
+    /// Requests tokens for `scope` (space-separated scopes).
+    pub fn with_scope(mut self, scope: impl Into<String>) -> Self {
+        self.scope = Some(scope.into());
+        self
+    }
+
+    async fn fetch(&self) -> Result<String, ClientError> {
+        let cached = self.cached.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).clone();
+        if let Some((token, _)) = cached.filter(|(_, expires_at)| Instant::now() < *expires_at) {
+            return Ok(token);
+        }
+        let mut form = vec![("grant_type", "client_credentials")];
+        if let Some(scope) = &self.scope {
+            form.push(("scope", scope));
+        }
+        let response = self
+            .http
+            .post(&self.token_url)
+            .basic_auth(&self.client_id, Some(&self.client_secret))
+            .form(&form)
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            let status = response.status();
+            return Err(ClientError::Auth(format!("token endpoint returned {}: {}", status, response.text().await?)));
+        }
+        let token: TokenResponse = response.json().await?;
+        let lifetime = Duration::from_secs(token.expires_in.unwrap_or(3600)).saturating_sub(TOKEN_LEEWAY);
+        *self.cached.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) =
+            Some((token.access_token.clone(), Instant::now() + lifetime));
+        Ok(token.access_token)
+    }
+}
+
+impl fmt::Debug for ClientCredentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClientCredentials")
+            .field("token_url", &self.token_url)
+            .field("client_id", &self.client_id)
+            .field("scope", &self.scope)
+            .finish_non_exhaustive()
+    }
+}
+
+impl TokenProvider for ClientCredentials {
+    fn token(&self) -> TokenFuture<'_> {
+        Box::pin(self.fetch())
+    }
+}
+
+/// One page of a listing.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(bound(deserialize = "R: DeserializeOwned"))]
+pub struct Page<R> {
+    /// Resources on this page
+    pub items: Vec<R>,
+    /// Cursor of the next page; `None` on the last page
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next: Option<String>,
+}
+
+#[derive(Clone)]
+enum Auth {
+    None,
+    Token(String),
+    Provider(Arc<dyn TokenProvider>),
+}
+
+/// Client for the WellAlly API.
+#[derive(Clone)]
+pub struct Client {
+    http: reqwest::Client,
+    base_url: Url,
+    auth: Auth,
+    max_retries: u32,
+    retry_backoff: Duration,
+    page_size: usize,
+}
+
+impl fmt::Debug for Client {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Client")
+            .field("base_url", &self.base_url.as_str())
+            .field("max_retries", &self.max_retries)
+            .field("retry_backoff", &self.retry_backoff)
+            .field("page_size", &self.page_size)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Client {
+    /// Client of the API at `base_url` (e.g., "https://api.wellally.tech/v1/").
+    pub fn new(base_url: &str) -> Result<Self, ClientError> {
+        let mut base_url = Url::parse(base_url).map_err(|err| ClientError::Url(format!("{}: {}", base_url, err)))?;
+        if !base_url.path().ends_with('/') {
+            base_url.set_path(&format!("{}/", base_url.path()));
+        }
+        Ok(Client {
+            http: reqwest::Client::new(),
+            base_url,
+            auth: Auth::None,
+            max_retries: DEFAULT_MAX_RETRIES,
+            retry_backoff: Duration::from_millis(DEFAULT_RETRY_BACKOFF_MILLIS),
+            page_size: DEFAULT_PAGE_SIZE,
+        })
+    }
+
+    /// Uses a preconfigured reqwest client (proxies, timeouts, TLS roots).
+    pub fn with_http_client(mut self, http: reqwest::Client) -> Self {
+        self.http = http;
+        self
+    }
+
+    /// Sends `token` as the bearer token of every request.
+    pub fn with_token(mut self, token: impl Into<String>) -> Self {
+        self.auth = Auth::Token(token.into());
+        self
+    }
+
+    /// Asks `provider` for the bearer token of every request.
+    pub fn with_token_provider(mut self, provider: impl TokenProvider + 'static) -> Self {
+        self.auth = Auth::Provider(Arc::new(provider));
+        self
+    }
+
+    /// Retries failed requests up to `max_retries` times, waiting `backoff`
+    /// before the first retry and twice as long before each further one.
+    pub fn with_retries(mut self, max_retries: u32, backoff: Duration) -> Self {
+        self.max_retries = max_retries;
+        self.retry_backoff = backoff;
+        self
+    }
+
+    /// Requests `page_size` resources per page.
+    pub fn with_page_size(mut self, page_size: usize) -> Self {
+        self.page_size = page_size.max(1);
+        self
+    }
+
+    /// The resource of type `R` with `id`, or `None` if the server has none.
+    pub async fn get<R: Resource>(&self, id: &str) -> Result<Option<R>, ClientError> {
+        let url = self.url(&[R::RESOURCE_TYPE, id])?;
+        let response = self.send(Method::GET, url, |request| request).await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        Ok(Some(read(R::RESOURCE_TYPE, response).await?))
+    }
+
+    /// Validates `resource` and creates or replaces it; returns the stored version.
+    pub async fn put<R: Resource + Validate>(&self, resource: &R) -> Result<R, ClientError> {
+        if let Some(err) = resource.validation_errors().into_iter().next() {
+            return Err(ClientError::Data(err));
+        }
+        let url = self.url(&[R::RESOURCE_TYPE, resource.id()])?;
+        let body = serde_json::to_vec(resource).map_err(|err| {
+            ClientError::Data(WellAllyError::Conversion {
+                resource_type: R::RESOURCE_TYPE.to_string(),
+                path: String::new(),
+                value: None,
+                message: err.to_string(),
+            })
+        })?;
+        let response = self
+            .send(Method::PUT, url, |request| {
+                request.header(header::CONTENT_TYPE, "application/json").body(body.clone())
+            })
+            .await?;
+        read(R::RESOURCE_TYPE, response).await
+    }
+
+    /// Deletes the resource of type `R` with `id`; returns whether it existed.
+    pub async fn delete<R: Resource>(&self, id: &str) -> Result<bool, ClientError> {
+        let url = self.url(&[R::RESOURCE_TYPE, id])?;
+        let response = self.send(Method::DELETE, url, |request| request).await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(false);
+        }
+        check(response).await?;
+        Ok(true)
+    }
+
+    /// One page of the resources of type `R` of a patient, starting at
+    /// `cursor` (the first page when `None`).
+    pub async fn list_page<R: Resource>(&self, patient_id: &str, cursor: Option<&str>) -> Result<Page<R>, ClientError> {
+        let mut url = self.url(&[R::RESOURCE_TYPE])?;
+        url.query_pairs_mut()
+            .append_pair("patientId", patient_id)
+            .append_pair("limit", &self.page_size.to_string());
+        if let Some(cursor) = cursor {
+            url.query_pairs_mut().append_pair("cursor", cursor);
+        }
+        let response = self.send(Method::GET, url, |request| request).await?;
+        read(R::RESOURCE_TYPE, response).await
+    }
+
+    /// Every resource of type `R` of a patient, following the pages.
+    pub async fn list<R: Resource>(&self, patient_id: &str) -> Result<Vec<R>, ClientError> {
+        let mut resources = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page: Page<R> = self.list_page(patient_id, cursor.as_deref()).await?;
+            resources.extend(page.items);
+            match page.next {
+                Some(next) if Some(&next) != cursor.as_ref() => cursor = Some(next),
+                _ => return Ok(resources),
+            }
+        }
+    }
+
+    /// Validates and uploads a lab report.
+    pub async fn upload_lab_report(&self, report: &LabReport) -> Result<LabReport, ClientError> {
+        self.put(report).await
+    }
+
+    /// The person with `id`, if any.
+    pub async fn get_person(&self, id: &str) -> Result<Option<Person>, ClientError> {
+        self.get(id).await
+    }
+
+    /// Every medication record of a patient.
+    pub async fn list_medications(&self, patient_id: &str) -> Result<Vec<MedicationRecord>, ClientError> {
+        self.list(patient_id).await
+    }
+
+    fn url(&self, segments: &[&str]) -> Result<Url, ClientError> {
+        let mut url = self.base_url.clone();
+        url.path_segments_mut()
+            .map_err(|_| ClientError::Url(format!("{} cannot be a base", self.base_url)))?
+            .pop_if_empty()
+            .extend(segments);
+        Ok(url)
+    }
+
+    /// Sends a request built by `build`, retrying transient failures.
+    async fn send(
+        &self,
+        method: Method,
+        url: Url,
+        build: impl Fn(RequestBuilder) -> RequestBuilder,
+    ) -> Result<Response, ClientError> {
+        let mut attempt = 0;
+        loop {
+            let mut request = build(self.http.request(method.clone(), url.clone()));
+            match &self.auth {
+                Auth::None => {}
+                Auth::Token(token) => request = request.bearer_auth(token),
+                Auth::Provider(provider) => request = request.bearer_auth(provider.token().await?),
+            }
+            let result = request.send().await;
+            let retry_after = match &result {
+                Ok(response) if is_transient(response.status()) => Some(retry_after(response)),
+                Ok(_) => return Ok(result?),
+                Err(err) if err.is_connect() || err.is_timeout() => Some(None),
+                Err(_) => None,
+            };
+            match retry_after {
+                Some(wait) if attempt < self.max_retries => {
+                    tokio::time::sleep(wait.unwrap_or(self.retry_backoff * 2u32.saturating_pow(attempt))).await;
+                    attempt += 1;
+                }
+                _ => return Ok(result?),
+            }
+        }
+    }
+}
+
+fn is_transient(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// Wait requested by a `Retry-After` header in seconds.
+fn retry_after(response: &Response) -> Option<Duration> {
+    let seconds = response.headers().get(header::RETRY_AFTER)?.to_str().ok()?.trim().parse().ok()?;
+    Some(Duration::from_secs(seconds))
+}
+
+/// The response, or a [`ClientError::Status`] for an error status.
+async fn check(response: Response) -> Result<Response, ClientError> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    Err(ClientError::Status { status: status.as_u16(), body: response.text().await.unwrap_or_default() })
+}
+
+/// The body of a successful response read as a `resource_type` document.
+async fn read<T: DeserializeOwned>(resource_type: &str, response: Response) -> Result<T, ClientError> {
+    let bytes = check(response).await?.bytes().await?;
+    Ok(error::from_str(resource_type, &String::from_utf8_lossy(&bytes))?)
+}
@@ -4,14 +4,28 @@
 //! Website: https://www.wellally.tech/
 //! Schema: https://wellall.health/schemas/common/v0.1.0
 
-use serde::{Deserialize, Serialize};
-use chrono::NaiveDate;
+use alloc::{boxed::Box, string::{String, ToString}, vec::Vec};
+use core::cmp::Ordering;
+use core::fmt;
+use core::ops::{Add, Sub};
+use core::str::FromStr;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+
+use crate::catalog;
+use crate::datetime::FlexibleDateTime;
+use crate::error::WellAllyError;
+use crate::resource::Resource;
+use crate::ucum;
 
 /// UCUM unit type
 pub type UCUMUnit = String;
 
 /// Name usage context
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "async-graphql", derive(async_graphql::Enum))]
 #[serde(rename_all = "lowercase")]
 pub enum NameUse {
     Official,
@@ -23,7 +37,10 @@ pub enum NameUse {
 }
 
 /// Contact system type
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "async-graphql", derive(async_graphql::Enum))]
 #[serde(rename_all = "lowercase")]
 pub enum ContactSystem {
     Phone,
@@ -31,7 +48,10 @@ pub enum ContactSystem {
 }
 
 /// Contact use context
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "async-graphql", derive(async_graphql::Enum))]
 #[serde(rename_all = "lowercase")]
 pub enum ContactUse {
     Home,
@@ -39,18 +59,155 @@ pub enum ContactUse {
     Mobile,
 }
 
-/// Imaging modality codes
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
-pub enum ModalityCode {
-    CT,
-    MR,
-    US,
-    XR,
-    PT,
+/// Defines [`ModalityCode`] from `Variant => "description"` entries whose
+/// variant names are the DICOM codes.
+macro_rules! modality_codes {
+    ($($variant:ident => $doc:literal,)*) => {
+        /// Imaging modality codes: the DICOM CID 29 acquisition modalities,
+        /// the legacy XR and OT, and any other code as given.
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        pub enum ModalityCode {
+            $(#[doc = $doc] $variant,)*
+            /// Code outside the known set, kept verbatim
+            Other(String),
+        }
+
+        impl ModalityCode {
+            /// Every known code, in declaration order
+            pub const KNOWN: &'static [ModalityCode] = &[$(ModalityCode::$variant,)*];
+
+            /// DICOM code (e.g., "CT")
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $(ModalityCode::$variant => stringify!($variant),)*
+                    ModalityCode::Other(code) => code,
+                }
+            }
+
+            /// Variant for an exact code; [`ModalityCode::Other`] when unknown.
+            pub fn from_code(code: &str) -> Self {
+                match code {
+                    $(stringify!($variant) => ModalityCode::$variant,)*
+                    _ => ModalityCode::Other(code.into()),
+                }
+            }
+        }
+    };
+}
+
+modality_codes! {
+    CT => "Computed tomography",
+    MR => "Magnetic resonance",
+    US => "Ultrasound",
+    XR => "X-ray (legacy general code; DICOM uses CR or DX)",
+    PT => "Positron emission tomography",
+    AR => "Autorefraction",
+    BDUS => "Bone densitometry (ultrasound)",
+    BI => "Biomagnetic imaging",
+    BMD => "Bone densitometry (X-ray)",
+    CR => "Computed radiography",
+    DG => "Diaphanography",
+    DX => "Digital radiography",
+    EPS => "Cardiac electrophysiology",
+    ES => "Endoscopy",
+    GM => "General microscopy",
+    HD => "Hemodynamic waveform",
+    IO => "Intra-oral radiography",
+    IVOCT => "Intravascular optical coherence tomography",
+    IVUS => "Intravascular ultrasound",
+    KER => "Keratometry",
+    LEN => "Lensometry",
+    LS => "Laser surface scan",
+    MG => "Mammography",
+    NM => "Nuclear medicine",
+    OAM => "Ophthalmic axial measurements",
+    OCT => "Optical coherence tomography",
+    OP => "Ophthalmic photography",
+    OPM => "Ophthalmic mapping",
+    OPT => "Ophthalmic tomography",
+    OPTBSV => "Ophthalmic tomography B-scan volume analysis",
+    OPTENF => "Ophthalmic tomography en face",
+    OPV => "Ophthalmic visual field",
+    OSS => "Optical surface scan",
+    OT => "Other",
+    PX => "Panoramic X-ray",
+    RF => "Radiofluoroscopy",
+    RG => "Radiographic imaging (conventional film/screen)",
+    SM => "Slide microscopy",
+    SRF => "Subjective refraction",
+    VA => "Visual acuity",
+    XA => "X-ray angiography",
+    XC => "External-camera photography",
+}
+
+impl FromStr for ModalityCode {
+    type Err = WellAllyError;
+
+    /// Parses a code case-insensitively, also accepting common names (MRI,
+    /// PET, X-ray, ultrasound, mammography, ...). Unknown codes become
+    /// [`ModalityCode::Other`]; only blank input fails.
+    fn from_str(code: &str) -> Result<Self, Self::Err> {
+        let code = code.trim();
+        if code.is_empty() {
+            return Err(WellAllyError::Terminology {
+                resource_type: "ModalityCode".into(),
+                path: String::new(),
+                system: catalog::DICOM.into(),
+                value: Some(alloc::boxed::Box::new(serde_json::Value::from(code))),
+                message: "must not be empty".into(),
+            });
+        }
+        let upper = code.to_ascii_uppercase();
+        let alias = match upper.as_str() {
+            "MRI" => Some(ModalityCode::MR),
+            "PET" => Some(ModalityCode::PT),
+            "XRAY" | "X-RAY" | "X RAY" => Some(ModalityCode::XR),
+            "ULTRASOUND" | "SONOGRAPHY" => Some(ModalityCode::US),
+            "MAMMOGRAPHY" | "MAMMO" => Some(ModalityCode::MG),
+            "FLUOROSCOPY" => Some(ModalityCode::RF),
+            "ANGIOGRAPHY" => Some(ModalityCode::XA),
+            "DEXA" | "DXA" => Some(ModalityCode::BMD),
+            "ENDOSCOPY" => Some(ModalityCode::ES),
+            _ => None,
+        };
+        Ok(alias.unwrap_or_else(|| match ModalityCode::from_code(&upper) {
+            ModalityCode::Other(_) => ModalityCode::Other(code.into()),
+            known => known,
+        }))
+    }
+}
+
+impl fmt::Display for ModalityCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Serialize for ModalityCode {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
 }
 
+impl<'de> Deserialize<'de> for ModalityCode {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let code = <alloc::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        Ok(ModalityCode::from_code(&code))
+    }
+}
+
+#[cfg(feature = "async-graphql")]
+async_graphql::scalar!(ModalityCode, "ModalityCode", "DICOM modality code (e.g., CT, MR, NM), as a string");
+
 /// Represents a coded value from a terminology system.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "CodingInput")
+)]
 pub struct Coding {
     /// URI identifying the terminology system (e.g., http://loinc.org)
     pub system: String,
@@ -63,6 +220,13 @@ pub struct Coding {
 
 /// A concept that may be defined by one or more codes from formal terminologies.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "CodeableConceptInput")
+)]
 pub struct CodeableConcept {
     /// List of coded values (at least one required)
     pub coding: Vec<Coding>,
@@ -71,17 +235,216 @@ pub struct CodeableConcept {
     pub text: Option<String>,
 }
 
+/// Comparator of a value beyond a detection or reporting limit: the true
+/// value is below or above the stated one.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "async-graphql", derive(async_graphql::Enum))]
+pub enum Comparator {
+    #[serde(rename = "<")]
+    LessThan,
+    #[serde(rename = "<=")]
+    LessOrEqual,
+    #[serde(rename = ">")]
+    GreaterThan,
+    #[serde(rename = ">=")]
+    GreaterOrEqual,
+}
+
+impl Comparator {
+    /// Symbol of the comparator (e.g., "<=")
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Comparator::LessThan => "<",
+            Comparator::LessOrEqual => "<=",
+            Comparator::GreaterThan => ">",
+            Comparator::GreaterOrEqual => ">=",
+        }
+    }
+}
+
 /// A measured or measurable amount with a UCUM unit.
+///
+/// `==` compares fields, so `1 g` and `1000 mg` differ; quantities in
+/// commensurable units order and add after conversion (see
+/// [`ucum`](crate::ucum)), and [`Quantity::equivalent`] tells whether they
+/// are the same amount. Addition and subtraction are checked: they give the
+/// result in the left operand's unit, or an error when the units do not
+/// convert or an operand is a limit (has a comparator). Limits only compare
+/// equal to the same limit.
+///
+/// ```
+/// use wellally::Quantity;
+///
+/// let total = (Quantity::new(500.0, "mg") + Quantity::new(1.0, "g")).unwrap();
+/// assert_eq!(total, Quantity::new(1500.0, "mg"));
+/// assert!(total.equivalent(&Quantity::new(1.5, "g")));
+/// assert!(Quantity::new(2.0, "L") > Quantity::new(1500.0, "mL"));
+/// assert!((Quantity::new(1.0, "L") - Quantity::new(1.0, "kg")).is_err());
+/// ```
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "QuantityInput")
+)]
 pub struct Quantity {
     /// Numerical value
     pub value: f64,
     /// UCUM unit string
+    #[cfg_attr(feature = "utoipa", schema(value_type = String))]
     pub unit: UCUMUnit,
+    /// How to read `value` when it is a limit (e.g., "<" for "< 5")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comparator: Option<Comparator>,
+    /// Coded form of the unit when it differs from `unit` (e.g., "[in_i]" for "in")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    /// System of `code`, normally UCUM
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system: Option<String>,
+}
+
+impl Quantity {
+    /// Exact quantity of `value` in `unit`.
+    pub fn new(value: f64, unit: impl Into<UCUMUnit>) -> Self {
+        Quantity { value, unit: unit.into(), comparator: None, code: None, system: None }
+    }
+
+    /// UCUM code of the unit: `code` when it is a UCUM code, else `unit`.
+    pub fn ucum_code(&self) -> &str {
+        match (&self.code, self.system.as_deref()) {
+            (Some(code), None | Some(catalog::UCUM)) => code,
+            _ => &self.unit,
+        }
+    }
+
+    /// The quantity expressed in `unit`.
+    pub fn convert_to(&self, unit: &str) -> Result<Quantity, WellAllyError> {
+        let value = ucum::convert(self.value, self.ucum_code(), unit)?;
+        Ok(Quantity { comparator: self.comparator, ..Quantity::new(value, unit) })
+    }
+
+    /// Order of the values on a common scale; `None` when the units do not
+    /// convert, and for limits other than equal ones.
+    pub fn compare(&self, other: &Quantity) -> Option<Ordering> {
+        let (this, that) = ucum::common_scale(self.value, self.ucum_code(), other.value, other.ucum_code()).ok()?;
+        let ordering = this.partial_cmp(&that)?;
+        match (self.comparator, other.comparator) {
+            (None, None) => Some(ordering),
+            (ours, theirs) => (ours == theirs && ordering == Ordering::Equal).then_some(ordering),
+        }
+    }
+
+    /// Whether the quantities are the same amount, whatever their units
+    /// (e.g., `1 g` and `1000 mg`).
+    pub fn equivalent(&self, other: &Quantity) -> bool {
+        self.compare(other) == Some(Ordering::Equal)
+    }
+
+    /// `self` plus `sign` times `other`, in the unit of `self`.
+    fn combine(&self, other: &Quantity, sign: f64) -> Result<Quantity, WellAllyError> {
+        if let Some(limit) = [self, other].into_iter().find(|quantity| quantity.comparator.is_some()) {
+            return Err(WellAllyError::Conversion {
+                resource_type: "Quantity".to_string(),
+                path: "comparator".to_string(),
+                value: limit.comparator.map(|comparator| Box::new(comparator.as_str().into())),
+                message: "cannot do arithmetic on a limit".to_string(),
+            });
+        }
+        let other = ucum::convert(other.value, other.ucum_code(), self.ucum_code())?;
+        Ok(Quantity { value: self.value + sign * other, ..self.clone() })
+    }
+}
+
+impl PartialOrd for Quantity {
+    /// [`Quantity::compare`], except that equivalent quantities written
+    /// differently are unordered, to agree with `==`.
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        if self == other {
+            return Some(Ordering::Equal);
+        }
+        self.compare(other).filter(|ordering| *ordering != Ordering::Equal)
+    }
+}
+
+impl Add<&Quantity> for Quantity {
+    type Output = Result<Quantity, WellAllyError>;
+
+    fn add(self, other: &Quantity) -> Self::Output {
+        self.combine(other, 1.0)
+    }
+}
+
+impl Add for Quantity {
+    type Output = Result<Quantity, WellAllyError>;
+
+    fn add(self, other: Quantity) -> Self::Output {
+        self.combine(&other, 1.0)
+    }
+}
+
+impl Sub<&Quantity> for Quantity {
+    type Output = Result<Quantity, WellAllyError>;
+
+    fn sub(self, other: &Quantity) -> Self::Output {
+        self.combine(other, -1.0)
+    }
+}
+
+impl Sub for Quantity {
+    type Output = Result<Quantity, WellAllyError>;
+
+    fn sub(self, other: Quantity) -> Self::Output {
+        self.combine(&other, -1.0)
+    }
+}
+
+/// Set of values bounded by low and high quantities, both inclusive.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "RangeInput")
+)]
+pub struct Range {
+    /// Lower bound quantity
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub low: Option<Quantity>,
+    /// Upper bound quantity
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub high: Option<Quantity>,
+}
+
+/// Kind of reference range
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "async-graphql", derive(async_graphql::Enum))]
+#[serde(rename_all = "lowercase")]
+pub enum ReferenceRangeType {
+    /// Values expected in healthy individuals
+    Normal,
+    /// Values beyond which immediate action is needed
+    Critical,
+    /// Target values of a treatment
+    Therapeutic,
 }
 
 /// Reference range for lab test results.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "ReferenceRangeInput")
+)]
 pub struct ReferenceRange {
     /// Lower bound quantity
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -92,10 +455,34 @@ pub struct ReferenceRange {
     /// Optional textual description
     #[serde(skip_serializing_if = "Option::is_none")]
     pub text: Option<String>,
+    /// Kind of range; absent means normal
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "async-graphql", graphql(name = "type"))]
+    pub range_type: Option<ReferenceRangeType>,
+    /// Populations or states the range is for (e.g., pregnant, fasting)
+    #[serde(rename = "appliesTo", alias = "applies_to", skip_serializing_if = "Option::is_none")]
+    pub applies_to: Option<Vec<CodeableConcept>>,
+    /// Ages the range is for, in UCUM time units (a, mo, wk, d)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub age: Option<Range>,
+}
+
+impl ReferenceRange {
+    /// Normal range between `low` and `high`, without applicability limits.
+    pub fn new(low: Option<Quantity>, high: Option<Quantity>) -> Self {
+        Self { low, high, text: None, range_type: None, applies_to: None, age: None }
+    }
 }
 
 /// An identifier assigned to a resource.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "IdentifierInput")
+)]
 pub struct Identifier {
     /// URI identifying the namespace
     pub system: String,
@@ -109,8 +496,161 @@ pub struct Identifier {
     pub period: Option<Period>,
 }
 
+/// Common identifier types from the HL7 v2 identifier type table (0203).
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum IdentifierType {
+    /// Medical record number
+    #[serde(rename = "MR")]
+    MedicalRecordNumber,
+    /// National unique individual identifier
+    #[serde(rename = "NI")]
+    NationalId,
+    /// Passport number
+    #[serde(rename = "PPN")]
+    Passport,
+    /// Driver's license number
+    #[serde(rename = "DL")]
+    DriversLicense,
+    /// Insurance member number
+    #[serde(rename = "MB")]
+    InsuranceMember,
+}
+
+impl IdentifierType {
+    /// Every identifier type, in declaration order
+    pub const ALL: [IdentifierType; 5] = [
+        IdentifierType::MedicalRecordNumber,
+        IdentifierType::NationalId,
+        IdentifierType::Passport,
+        IdentifierType::DriversLicense,
+        IdentifierType::InsuranceMember,
+    ];
+
+    /// HL7 v2-0203 code (e.g., "MR")
+    pub fn code(self) -> &'static str {
+        match self {
+            IdentifierType::MedicalRecordNumber => "MR",
+            IdentifierType::NationalId => "NI",
+            IdentifierType::Passport => "PPN",
+            IdentifierType::DriversLicense => "DL",
+            IdentifierType::InsuranceMember => "MB",
+        }
+    }
+
+    /// HL7 v2-0203 display text
+    pub fn display(self) -> &'static str {
+        match self {
+            IdentifierType::MedicalRecordNumber => "Medical record number",
+            IdentifierType::NationalId => "National unique individual identifier",
+            IdentifierType::Passport => "Passport number",
+            IdentifierType::DriversLicense => "Driver's license number",
+            IdentifierType::InsuranceMember => "Member Number",
+        }
+    }
+
+    /// Type for a v2-0203 code, matched case-insensitively.
+    pub fn from_code(code: &str) -> Option<Self> {
+        IdentifierType::ALL.into_iter().find(|kind| kind.code().eq_ignore_ascii_case(code.trim()))
+    }
+
+    /// The type as a concept for [`Identifier::type`](Identifier).
+    pub fn concept(self) -> CodeableConcept {
+        CodeableConcept {
+            coding: alloc::vec![Coding {
+                system: catalog::IDENTIFIER_TYPE.into(),
+                code: self.code().into(),
+                display: Some(self.display().into()),
+            }],
+            text: None,
+        }
+    }
+}
+
+impl Identifier {
+    /// Identifier of the given type in `system`.
+    pub fn typed(kind: IdentifierType, system: impl Into<String>, value: impl Into<String>) -> Self {
+        Identifier { system: system.into(), value: value.into(), r#type: Some(kind.concept()), period: None }
+    }
+
+    /// The first v2-0203 coding of `type` as an [`IdentifierType`].
+    pub fn identifier_type(&self) -> Option<IdentifierType> {
+        self.r#type
+            .as_ref()?
+            .coding
+            .iter()
+            .filter(|coding| coding.system == catalog::IDENTIFIER_TYPE)
+            .find_map(|coding| IdentifierType::from_code(&coding.code))
+    }
+}
+
+/// Access to the business identifiers of a resource.
+pub trait HasIdentifiers {
+    /// Identifiers in document order
+    fn identifiers(&self) -> &[Identifier];
+
+    /// The identifier list; `None` when the resource has none.
+    fn identifiers_mut(&mut self) -> &mut Option<Vec<Identifier>>;
+
+    /// First identifier issued by `system`.
+    fn find_identifier(&self, system: &str) -> Option<&Identifier> {
+        self.identifiers().iter().find(|identifier| identifier.system == system)
+    }
+
+    /// Appends `identifier` unless one with the same system and value is
+    /// already present. Returns whether it was added.
+    fn add_identifier_unique(&mut self, identifier: Identifier) -> bool {
+        let identifiers = self.identifiers_mut().get_or_insert_with(Vec::new);
+        if identifiers.iter().any(|existing| existing.system == identifier.system && existing.value == identifier.value) {
+            return false;
+        }
+        identifiers.push(identifier);
+        true
+    }
+}
+
+/// One system+value pair claimed by several records.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdentifierCollision {
+    /// Identifier system
+    pub system: String,
+    /// Identifier value
+    pub value: String,
+    /// Ids of the records carrying it, in input order
+    pub ids: Vec<String>,
+}
+
+/// Identifiers shared by records with different ids, e.g. two persons with
+/// the same MRN after merging sources. Sorted by system, then value.
+pub fn detect_identifier_collisions<T: Resource + HasIdentifiers>(records: &[T]) -> Vec<IdentifierCollision> {
+    let mut owners: alloc::collections::BTreeMap<(&str, &str), Vec<&str>> = alloc::collections::BTreeMap::new();
+    for record in records {
+        for identifier in record.identifiers() {
+            let ids = owners.entry((identifier.system.as_str(), identifier.value.as_str())).or_default();
+            if !ids.contains(&record.id()) {
+                ids.push(record.id());
+            }
+        }
+    }
+    owners
+        .into_iter()
+        .filter(|(_, ids)| ids.len() > 1)
+        .map(|((system, value), ids)| IdentifierCollision {
+            system: system.into(),
+            value: value.into(),
+            ids: ids.into_iter().map(Into::into).collect(),
+        })
+        .collect()
+}
+
 /// A human's name with text, parts and usage information.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "HumanNameInput")
+)]
 pub struct HumanName {
     /// Family/last name
     pub family: String,
@@ -129,6 +669,13 @@ pub struct HumanName {
 
 /// Contact details for a person or organization.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "ContactPointInput")
+)]
 pub struct ContactPoint {
     /// phone | email
     pub system: ContactSystem,
@@ -141,6 +688,13 @@ pub struct ContactPoint {
 
 /// An address for a person or organization.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "AddressInput")
+)]
 pub struct Address {
     /// Street address lines
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -152,7 +706,7 @@ pub struct Address {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub state: Option<String>,
     /// Postal/zip code
-    #[serde(rename = "postalCode", skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "postalCode", alias = "postal_code", skip_serializing_if = "Option::is_none")]
     pub postal_code: Option<String>,
     /// Country name
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -160,7 +714,14 @@ pub struct Address {
 }
 
 /// A time period defined by start and end dates.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "PeriodInput")
+)]
 pub struct Period {
     /// Start date
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -170,8 +731,97 @@ pub struct Period {
     pub end: Option<NaiveDate>,
 }
 
+impl Period {
+    /// Whether `date` falls within the period; open ends are unbounded.
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        self.start.is_none_or(|start| start <= date) && self.end.is_none_or(|end| date <= end)
+    }
+}
+
+/// Time a measurement applies to: an instant, or the days an aggregate
+/// (a daily step count, a weekly average) covers.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[serde(untagged)]
+pub enum Effective {
+    /// Date-time string, as in `"2024-10-01T08:00:00+08:00"`
+    Instant(FlexibleDateTime),
+    /// Period object, as in `{"start":"2024-09-23","end":"2024-09-29"}`
+    Period(Period),
+}
+
+#[cfg(feature = "async-graphql")]
+async_graphql::scalar!(
+    Effective,
+    "Effective",
+    "Time a measurement applies to as JSON: a date-time string or a period {\"start\": ..., \"end\": ...}"
+);
+
+impl Effective {
+    /// First day covered, in the instant's own offset; `None` for a period
+    /// without start.
+    pub fn first_date(&self) -> Option<NaiveDate> {
+        match self {
+            Effective::Instant(instant) => Some(instant.local().date()),
+            Effective::Period(period) => period.start,
+        }
+    }
+
+    /// Last day covered; `None` for a period without end.
+    pub fn last_date(&self) -> Option<NaiveDate> {
+        match self {
+            Effective::Instant(instant) => Some(instant.local().date()),
+            Effective::Period(period) => period.end,
+        }
+    }
+
+    /// Whether any day covered falls within `period`; open ends are unbounded.
+    pub fn overlaps(&self, period: &Period) -> bool {
+        let starts_in_time = match (self.first_date(), period.end) {
+            (Some(first), Some(end)) => first <= end,
+            _ => true,
+        };
+        let ends_in_time = match (self.last_date(), period.start) {
+            (Some(last), Some(start)) => last >= start,
+            _ => true,
+        };
+        starts_in_time && ends_in_time
+    }
+
+    /// Moment to order measurements by: the instant, or the start of the
+    /// period's first day in UTC (the earliest time for an open start).
+    pub fn sort_key(&self) -> DateTime<Utc> {
+        match self {
+            Effective::Instant(instant) => instant.to_utc(),
+            Effective::Period(period) => {
+                period.start.map_or(DateTime::<Utc>::MIN_UTC, |day| day.and_time(NaiveTime::MIN).and_utc())
+            }
+        }
+    }
+}
+
+impl From<FlexibleDateTime> for Effective {
+    fn from(instant: FlexibleDateTime) -> Self {
+        Effective::Instant(instant)
+    }
+}
+
+impl From<Period> for Effective {
+    fn from(period: Period) -> Self {
+        Effective::Period(period)
+    }
+}
+
 /// Imaging modality code (CT, MR, US, XR, PT).
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "ModalityInput")
+)]
 pub struct Modality {
     /// Terminology system URI
     pub system: String,
@@ -182,14 +832,578 @@ pub struct Modality {
     pub display: Option<String>,
 }
 
+/// Side of the body a site is on.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "async-graphql", derive(async_graphql::Enum))]
+#[serde(rename_all = "lowercase")]
+pub enum Laterality {
+    Left,
+    Right,
+    Bilateral,
+}
+
+impl Laterality {
+    /// SNOMED CT qualifier value.
+    pub fn snomed_code(self) -> &'static str {
+        match self {
+            Laterality::Left => "7771000",
+            Laterality::Right => "24028007",
+            Laterality::Bilateral => "51440002",
+        }
+    }
+
+    /// Lowercase name, as serialized.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Laterality::Left => "left",
+            Laterality::Right => "right",
+            Laterality::Bilateral => "bilateral",
+        }
+    }
+}
+
+/// Anatomical site with its laterality and qualifiers. A bare [`Coding`],
+/// the shape `bodySite` had before, is still read as a site without either.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "BodySiteInput")
+)]
+#[serde(from = "BodySiteShape")]
+pub struct BodySite {
+    /// Body structure (SNOMED CT, e.g., 72696002 knee region)
+    pub site: Coding,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub laterality: Option<Laterality>,
+    /// Further qualifiers (SNOMED CT, e.g., 40415009 proximal, 46053002 distal)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub qualifiers: Vec<Coding>,
+}
+
+/// The shapes `bodySite` is read from.
+#[derive(Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(untagged)]
+enum BodySiteShape {
+    Structured {
+        site: Coding,
+        #[serde(default)]
+        laterality: Option<Laterality>,
+        #[serde(default)]
+        qualifiers: Vec<Coding>,
+    },
+    Legacy(Coding),
+}
+
+impl From<BodySiteShape> for BodySite {
+    fn from(shape: BodySiteShape) -> Self {
+        match shape {
+            BodySiteShape::Structured { site, laterality, qualifiers } => BodySite { site, laterality, qualifiers },
+            BodySiteShape::Legacy(site) => BodySite::new(site),
+        }
+    }
+}
+
+impl From<Coding> for BodySite {
+    fn from(site: Coding) -> Self {
+        BodySite::new(site)
+    }
+}
+
+impl BodySite {
+    /// Site with no laterality or qualifier.
+    pub fn new(site: Coding) -> Self {
+        BodySite { site, laterality: None, qualifiers: Vec::new() }
+    }
+
+    /// The site on `laterality`.
+    pub fn with_laterality(mut self, laterality: Laterality) -> Self {
+        self.laterality = Some(laterality);
+        self
+    }
+
+    /// Whether `other` is the same structure on the same side; qualifiers
+    /// are not compared.
+    pub fn is_same_site(&self, other: &BodySite) -> bool {
+        self.site.system == other.site.system
+            && self.site.code == other.site.code
+            && self.laterality == other.laterality
+    }
+
+    /// Readable label: laterality, qualifiers and site display, falling back
+    /// to codes (e.g., "left distal Femur").
+    pub fn label(&self) -> String {
+        let name = |coding: &Coding| coding.display.clone().unwrap_or_else(|| coding.code.clone());
+        self.laterality
+            .map(|laterality| laterality.as_str().to_string())
+            .into_iter()
+            .chain(self.qualifiers.iter().map(name))
+            .chain([name(&self.site)])
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
 /// Medication administration route.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "RouteInput")
+)]
 pub struct Route {
     /// Terminology system URI
     pub system: String,
-    /// Route code (e.g., PO, IV, IM, SC, INH, SL, PR or SNOMED CT codes)
+    /// Route code (e.g., PO, IV, IM, SC, INH, SL, PR or SNOMED CT codes; see [`RouteCode`])
     pub code: String,
     /// Optional display text
     #[serde(skip_serializing_if = "Option::is_none")]
     pub display: Option<String>,
 }
+
+/// Defines [`RouteCode`] from `Variant => ("abbreviation", "snomed", "display")`
+/// entries.
+macro_rules! route_codes {
+    ($($variant:ident => ($abbr:literal, $snomed:literal, $display:literal),)*) => {
+        /// Common routes of administration with their SNOMED CT codes, and
+        /// any other code as given.
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        pub enum RouteCode {
+            $(#[doc = $display] $variant,)*
+            /// Route outside the known set, kept verbatim
+            Other(String),
+        }
+
+        impl RouteCode {
+            /// Every known route, in declaration order
+            pub const KNOWN: &'static [RouteCode] = &[$(RouteCode::$variant,)*];
+
+            /// Clinical abbreviation (e.g., "PO"); the code itself for [`RouteCode::Other`].
+            pub fn abbreviation(&self) -> &str {
+                match self {
+                    $(RouteCode::$variant => $abbr,)*
+                    RouteCode::Other(code) => code,
+                }
+            }
+
+            /// SNOMED CT code of a known route (e.g., "26643006" for oral).
+            pub fn snomed_code(&self) -> Option<&'static str> {
+                match self {
+                    $(RouteCode::$variant => Some($snomed),)*
+                    RouteCode::Other(_) => None,
+                }
+            }
+
+            /// SNOMED CT display of a known route (e.g., "Oral route").
+            pub fn display(&self) -> Option<&'static str> {
+                match self {
+                    $(RouteCode::$variant => Some($display),)*
+                    RouteCode::Other(_) => None,
+                }
+            }
+
+            /// Variant for an exact abbreviation; [`RouteCode::Other`] when unknown.
+            pub fn from_abbreviation(code: &str) -> Self {
+                match code {
+                    $($abbr => RouteCode::$variant,)*
+                    _ => RouteCode::Other(code.into()),
+                }
+            }
+
+            /// Variant for a SNOMED CT code; [`RouteCode::Other`] when unknown.
+            pub fn from_snomed(code: &str) -> Self {
+                match code {
+                    $($snomed => RouteCode::$variant,)*
+                    _ => RouteCode::Other(code.into()),
+                }
+            }
+        }
+    };
+}
+
+route_codes! {
+    Oral => ("PO", "26643006", "Oral route"),
+    Intravenous => ("IV", "47625008", "Intravenous route"),
+    Intramuscular => ("IM", "78421000", "Intramuscular route"),
+    Subcutaneous => ("SC", "34206005", "Subcutaneous route"),
+    Sublingual => ("SL", "37839007", "Sublingual route"),
+    Rectal => ("PR", "37161004", "Rectal route"),
+    Inhalation => ("INH", "447694001", "Respiratory tract route"),
+    Topical => ("TOP", "6064005", "Topical route"),
+    Ophthalmic => ("OPH", "54485002", "Ophthalmic route"),
+    Otic => ("OTIC", "10547007", "Otic route"),
+    Nasal => ("NAS", "46713006", "Nasal route"),
+}
+
+impl FromStr for RouteCode {
+    type Err = WellAllyError;
+
+    /// Parses an abbreviation case-insensitively, a SNOMED CT code, or a
+    /// common name (oral, SQ, inhaled, ...). Unknown codes become
+    /// [`RouteCode::Other`]; only blank input fails.
+    fn from_str(code: &str) -> Result<Self, Self::Err> {
+        let code = code.trim();
+        if code.is_empty() {
+            return Err(WellAllyError::Terminology {
+                resource_type: "RouteCode".into(),
+                path: String::new(),
+                system: catalog::SNOMED_CT.into(),
+                value: Some(alloc::boxed::Box::new(serde_json::Value::from(code))),
+                message: "must not be empty".into(),
+            });
+        }
+        let upper = code.to_ascii_uppercase();
+        let alias = match upper.as_str() {
+            "ORAL" | "PER OS" => Some(RouteCode::Oral),
+            "INTRAVENOUS" => Some(RouteCode::Intravenous),
+            "INTRAMUSCULAR" => Some(RouteCode::Intramuscular),
+            "SUBCUTANEOUS" | "SQ" | "SUBQ" | "SUBCUT" => Some(RouteCode::Subcutaneous),
+            "SUBLINGUAL" => Some(RouteCode::Sublingual),
+            "RECTAL" => Some(RouteCode::Rectal),
+            "INHALATION" | "INHALED" | "IH" => Some(RouteCode::Inhalation),
+            "TOPICAL" => Some(RouteCode::Topical),
+            "OPHTHALMIC" => Some(RouteCode::Ophthalmic),
+            "NASAL" | "INTRANASAL" => Some(RouteCode::Nasal),
+            _ => None,
+        };
+        if let Some(route) = alias {
+            return Ok(route);
+        }
+        Ok(match RouteCode::from_abbreviation(&upper) {
+            RouteCode::Other(_) => RouteCode::from_snomed(code),
+            known => known,
+        })
+    }
+}
+
+impl fmt::Display for RouteCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.abbreviation())
+    }
+}
+
+impl Route {
+    /// SNOMED CT route for `code`. [`RouteCode::Other`] codes are kept as
+    /// given, without a display.
+    pub fn new(code: RouteCode) -> Self {
+        Route {
+            system: catalog::SNOMED_CT.into(),
+            code: code.snomed_code().map_or_else(|| code.abbreviation().into(), Into::into),
+            display: code.display().map(Into::into),
+        }
+    }
+
+    /// Oral route (PO)
+    pub fn oral() -> Self {
+        Route::new(RouteCode::Oral)
+    }
+
+    /// Intravenous route (IV)
+    pub fn intravenous() -> Self {
+        Route::new(RouteCode::Intravenous)
+    }
+
+    /// Intramuscular route (IM)
+    pub fn intramuscular() -> Self {
+        Route::new(RouteCode::Intramuscular)
+    }
+
+    /// Subcutaneous route (SC)
+    pub fn subcutaneous() -> Self {
+        Route::new(RouteCode::Subcutaneous)
+    }
+
+    /// Sublingual route (SL)
+    pub fn sublingual() -> Self {
+        Route::new(RouteCode::Sublingual)
+    }
+
+    /// Rectal route (PR)
+    pub fn rectal() -> Self {
+        Route::new(RouteCode::Rectal)
+    }
+
+    /// Inhaled, respiratory tract route (INH)
+    pub fn inhalation() -> Self {
+        Route::new(RouteCode::Inhalation)
+    }
+
+    /// Topical route (TOP)
+    pub fn topical() -> Self {
+        Route::new(RouteCode::Topical)
+    }
+
+    /// Ophthalmic route (OPH)
+    pub fn ophthalmic() -> Self {
+        Route::new(RouteCode::Ophthalmic)
+    }
+
+    /// Otic route (OTIC)
+    pub fn otic() -> Self {
+        Route::new(RouteCode::Otic)
+    }
+
+    /// Nasal route (NAS)
+    pub fn nasal() -> Self {
+        Route::new(RouteCode::Nasal)
+    }
+
+    /// The route as a [`RouteCode`], read from either a SNOMED CT code or an
+    /// abbreviation whatever the declared system.
+    pub fn route_code(&self) -> RouteCode {
+        self.code.parse().unwrap_or_else(|_| RouteCode::Other(self.code.clone()))
+    }
+}
+
+/// Urgency of a request (referral, task)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "async-graphql", derive(async_graphql::Enum))]
+#[serde(rename_all = "lowercase")]
+pub enum RequestPriority {
+    Routine,
+    Urgent,
+    /// As soon as possible
+    Asap,
+    /// Immediately
+    Stat,
+}
+
+/// Reference from one resource to another, as `"<ResourceType>/<id>"`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "ReferenceInput")
+)]
+pub struct Reference {
+    /// Relative reference (e.g., "LabReport/lab-1")
+    pub reference: String,
+    /// Optional display text
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display: Option<String>,
+}
+
+impl Reference {
+    /// Reference to a resource.
+    pub fn to<R: crate::resource::Resource>(resource: &R) -> Self {
+        Self { reference: alloc::format!("{}/{}", R::RESOURCE_TYPE, resource.id()), display: None }
+    }
+
+    /// Resource type and id of the target; `None` unless the reference has the `Type/id` form.
+    pub fn target(&self) -> Option<(&str, &str)> {
+        self.reference.split_once('/').filter(|(resource_type, id)| {
+            !resource_type.is_empty() && !id.is_empty() && !id.contains('/')
+        })
+    }
+
+    /// Whether the reference points to the given resource.
+    pub fn points_to<R: crate::resource::Resource>(&self, resource: &R) -> bool {
+        self.target() == Some((R::RESOURCE_TYPE, resource.id()))
+    }
+}
+
+/// Free-text note with its author and time.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "AnnotationInput")
+)]
+pub struct Annotation {
+    /// Author as a resource (e.g., "Practitioner/dr-li")
+    #[serde(rename = "authorReference", alias = "author_reference", skip_serializing_if = "Option::is_none")]
+    pub author_reference: Option<Reference>,
+    /// Author as plain text, when there is no resource for them
+    #[serde(rename = "authorString", alias = "author_string", skip_serializing_if = "Option::is_none")]
+    pub author_string: Option<String>,
+    /// When the note was made
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "utoipa", schema(value_type = Option<String>))]
+    pub time: Option<FlexibleDateTime>,
+    /// Note text (Markdown)
+    pub text: String,
+}
+
+impl Annotation {
+    /// Note with the given text and no author or time.
+    pub fn new(text: impl Into<String>) -> Self {
+        Self { author_reference: None, author_string: None, time: None, text: text.into() }
+    }
+
+    /// Display name of the author: the reference display, the reference
+    /// itself, or the plain-text author.
+    pub fn author(&self) -> Option<&str> {
+        match &self.author_reference {
+            Some(reference) => Some(reference.display.as_deref().unwrap_or(&reference.reference)),
+            None => self.author_string.as_deref(),
+        }
+    }
+}
+
+/// How a measurement was captured
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "async-graphql", derive(async_graphql::Enum))]
+#[serde(rename_all = "lowercase")]
+pub enum CollectionMethod {
+    /// Recorded by a sensor without user input
+    Automatic,
+    /// Typed in by the user or a caregiver
+    Manual,
+}
+
+/// How far a measurement can be trusted, as judged by the source
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "async-graphql", derive(async_graphql::Enum))]
+#[serde(rename_all = "lowercase")]
+pub enum Reliability {
+    Low,
+    Medium,
+    High,
+}
+
+/// Known problem with a sensor measurement
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "async-graphql", derive(async_graphql::Enum))]
+#[serde(rename_all = "kebab-case")]
+pub enum QualityFlag {
+    /// The wearer was moving
+    MotionArtifact,
+    /// The sensor had poor skin contact
+    PoorContact,
+    /// The sensor had not finished warming up
+    WarmUp,
+    /// Taken during or shortly after calibration
+    Calibrating,
+    LowBattery,
+}
+
+/// Where a measurement came from: the device and app that recorded it, and
+/// whether a sensor or a person captured it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "DataSourceInput")
+)]
+pub struct DataSource {
+    /// Identifier of the recording device (e.g., a serial number)
+    #[serde(rename = "deviceId", alias = "device_id", skip_serializing_if = "Option::is_none")]
+    pub device_id: Option<String>,
+    /// App or platform the data was synced through (e.g., "Apple Health")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub app: Option<String>,
+    /// Captured by a sensor or entered by hand
+    #[serde(rename = "collectionMethod", alias = "collection_method", skip_serializing_if = "Option::is_none")]
+    pub collection_method: Option<CollectionMethod>,
+    /// Trust the source places in the data
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reliability: Option<Reliability>,
+    /// Problems the source detected
+    #[serde(rename = "qualityFlags", alias = "quality_flags", skip_serializing_if = "Option::is_none")]
+    pub quality_flags: Option<Vec<QualityFlag>>,
+}
+
+impl DataSource {
+    /// Whether the data was entered by hand.
+    pub fn is_manual(&self) -> bool {
+        self.collection_method == Some(CollectionMethod::Manual)
+    }
+
+    /// Whether the source flagged a quality problem.
+    pub fn is_flagged(&self) -> bool {
+        self.quality_flags.as_ref().is_some_and(|flags| !flags.is_empty())
+    }
+}
+
+/// Access to where a record's data came from.
+pub trait HasDataSource {
+    /// The source; `None` when it is unknown
+    fn data_source(&self) -> Option<&DataSource>;
+
+    /// Whether the record was entered by hand.
+    fn is_manual(&self) -> bool {
+        self.data_source().is_some_and(DataSource::is_manual)
+    }
+}
+
+/// Records not entered by hand, in input order. Records of unknown source
+/// are kept.
+pub fn exclude_manual<T: HasDataSource>(records: &[T]) -> Vec<&T> {
+    records.iter().filter(|record| !record.is_manual()).collect()
+}
+
+/// Records whose source rates them at least `minimum` and flagged no quality
+/// problem, in input order. Records of unknown source or reliability are kept.
+pub fn exclude_unreliable<T: HasDataSource>(records: &[T], minimum: Reliability) -> Vec<&T> {
+    records
+        .iter()
+        .filter(|record| {
+            record.data_source().is_none_or(|source| {
+                !source.is_flagged() && source.reliability.is_none_or(|reliability| reliability >= minimum)
+            })
+        })
+        .collect()
+}
+
+#[cfg(feature = "schemars")]
+mod json_schema {
+    use alloc::borrow::Cow;
+
+    use schemars::{json_schema, JsonSchema, Schema, SchemaGenerator};
+
+    impl JsonSchema for super::ModalityCode {
+        fn inline_schema() -> bool {
+            true
+        }
+
+        fn schema_name() -> Cow<'static, str> {
+            "ModalityCode".into()
+        }
+
+        fn json_schema(_: &mut SchemaGenerator) -> Schema {
+            let known: alloc::vec::Vec<&str> = super::ModalityCode::KNOWN.iter().map(super::ModalityCode::as_str).collect();
+            json_schema!({
+                "type": "string",
+                "minLength": 1,
+                "examples": known
+            })
+        }
+    }
+}
+
+/// String schema for the open set of modality codes.
+#[cfg(feature = "utoipa")]
+mod openapi_schema {
+    use utoipa::openapi::schema::{ObjectBuilder, Schema, Type};
+    use utoipa::openapi::RefOr;
+    use utoipa::{PartialSchema, ToSchema};
+
+    impl PartialSchema for super::ModalityCode {
+        fn schema() -> RefOr<Schema> {
+            RefOr::T(Schema::Object(ObjectBuilder::new().schema_type(Type::String).min_length(Some(1)).build()))
+        }
+    }
+
+    impl ToSchema for super::ModalityCode {}
+}
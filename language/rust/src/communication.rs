@@ -0,0 +1,162 @@
+//! Communication (secure message) data model.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//! Schema: https://wellall.health/schemas/communication/v0.1.0
+//!
+//! A [`CommunicationRecord`] is one message between a patient and their
+//! providers. Replies point at the message they answer through
+//! `inResponseTo`; [`message_threads`] rebuilds the conversations from a mailbox.
+
+use alloc::{string::String, vec, vec::Vec};
+use serde::{Deserialize, Serialize};
+
+use crate::common::{CodeableConcept, Reference, Coding};
+use crate::datetime::FlexibleDateTime;
+use crate::imaging_report::Attachment;
+use crate::extension::Extension;
+
+/// Delivery status of a message
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "async-graphql", derive(async_graphql::Enum))]
+#[serde(rename_all = "kebab-case")]
+pub enum CommunicationStatus {
+    /// Being written or not yet delivered
+    InProgress,
+    /// Delivered
+    Completed,
+    /// Could not be delivered
+    NotDone,
+    /// Created by mistake
+    EnteredInError,
+}
+
+/// Content of a message: text, an attachment or a reference to a record
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub enum MessagePayload {
+    #[serde(rename = "contentString", alias = "content_string")]
+    Text(String),
+    #[serde(rename = "contentAttachment", alias = "content_attachment")]
+    Attachment(Attachment),
+    #[serde(rename = "contentReference", alias = "content_reference")]
+    Reference(Reference),
+}
+
+#[cfg(feature = "async-graphql")]
+async_graphql::scalar!(
+    MessagePayload,
+    "MessagePayload",
+    "Message content as JSON: {\"contentString\": ...}, {\"contentAttachment\": {...}} or {\"contentReference\": {...}}"
+);
+
+/// Message between a patient and providers.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "CommunicationRecordInput")
+)]
+pub struct CommunicationRecord {
+    /// Unique message identifier
+    pub id: String,
+    /// Reference to Person.id of the patient the message is about
+    #[serde(rename = "patientId", alias = "patient_id")]
+    pub patient_id: String,
+    /// Delivery status
+    pub status: CommunicationStatus,
+    /// Author of the message (patient or practitioner)
+    pub sender: Reference,
+    /// Addressees
+    pub recipient: Vec<Reference>,
+    /// When the message was sent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "utoipa", schema(value_type = Option<String>))]
+    pub sent: Option<FlexibleDateTime>,
+    /// When the message was received
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "utoipa", schema(value_type = Option<String>))]
+    pub received: Option<FlexibleDateTime>,
+    /// Subject of the conversation (e.g., prescription refill, result question)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub topic: Option<CodeableConcept>,
+    /// Message contents
+    pub payload: Vec<MessagePayload>,
+    /// Message this one replies to
+    #[serde(rename = "inResponseTo", alias = "in_response_to", skip_serializing_if = "Option::is_none")]
+    pub in_response_to: Option<Reference>,
+    /// Schema version the resource was written against (e.g., "0.1.0"); absent means 0.1.0
+    #[serde(rename = "schemaVersion", alias = "schema_version", skip_serializing_if = "Option::is_none")]
+    pub schema_version: Option<String>,
+    /// Confidentiality and sensitivity labels (e.g., v3 ActCode HIV); see [`crate::security`]
+    #[serde(rename = "securityLabels", alias = "security_labels", skip_serializing_if = "Option::is_none")]
+    pub security_labels: Option<Vec<Coding>>,
+    /// Site-specific extensions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extensions: Option<Vec<Extension>>,
+    /// Properties this model version does not define, kept for re-serialization
+    #[cfg(feature = "preserve-unknown")]
+    #[serde(flatten)]
+    #[cfg_attr(feature = "schemars", schemars(skip))]
+    #[cfg_attr(feature = "utoipa", schema(ignore))]
+    #[cfg_attr(feature = "async-graphql", graphql(skip))]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl CommunicationRecord {
+    /// Text parts of the message joined by blank lines.
+    pub fn text(&self) -> String {
+        let parts: Vec<&str> = self
+            .payload
+            .iter()
+            .filter_map(|payload| match payload {
+                MessagePayload::Text(text) => Some(text.as_str()),
+                _ => None,
+            })
+            .collect();
+        parts.join("\n\n")
+    }
+
+    /// Id of the message this one replies to.
+    pub fn reply_to(&self) -> Option<&str> {
+        match self.in_response_to.as_ref()?.target()? {
+            ("CommunicationRecord", id) => Some(id),
+            _ => None,
+        }
+    }
+}
+
+/// Groups messages into conversation threads. Each thread starts with a
+/// message that replies to nothing in `records` and holds all its replies,
+/// direct or indirect, in the order they were sent (unsent last).
+pub fn message_threads(records: &[CommunicationRecord]) -> Vec<Vec<&CommunicationRecord>> {
+    let is_root = |record: &CommunicationRecord| match record.reply_to() {
+        Some(parent) => !records.iter().any(|other| other.id == parent),
+        None => true,
+    };
+    records
+        .iter()
+        .filter(|record| is_root(record))
+        .map(|root| {
+            let mut thread = vec![root];
+            let mut next = 0;
+            while next < thread.len() {
+                let parent = thread[next].id.as_str();
+                let replies: Vec<_> = records
+                    .iter()
+                    .filter(|record| record.reply_to() == Some(parent))
+                    .filter(|record| !thread.iter().any(|seen| core::ptr::eq(*seen, *record)))
+                    .collect();
+                thread.extend(replies);
+                next += 1;
+            }
+            thread[1..].sort_by_key(|record| (record.sent.is_none(), record.sent.map(|sent| sent.to_utc())));
+            thread
+        })
+        .collect()
+}
@@ -0,0 +1,208 @@
+//! Lab test compendium model.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//! Schema: https://wellall.health/schemas/compendium/v0.1.0
+//!
+//! A [`Compendium`] is a laboratory's orderable test catalog. Each
+//! [`TestDefinition`] carries the LOINC code, the specimen it needs with the
+//! container and minimum volume, the turnaround time and the reference lab
+//! that performs it. Ordering UIs look tests up by code or name; validation
+//! uses the same catalog to find results a lab report should not contain.
+//!
+//! ```
+//! use wellally::catalog::LOINC;
+//! use wellally::compendium::{Compendium, TestDefinition};
+//! use wellally::Coding;
+//!
+//! let mut compendium = Compendium::new("lab-east", "East District Lab");
+//! let code = Coding { system: LOINC.into(), code: "4548-4".into(), display: None };
+//! let mut hba1c = TestDefinition::new(code, "Hemoglobin A1c");
+//! hba1c.aliases.push("HbA1c".into());
+//! compendium.tests.push(hba1c);
+//! assert_eq!(compendium.by_name("hba1c").unwrap().code.code, "4548-4");
+//! assert!(compendium.by_code("4548-4").is_some());
+//! ```
+
+use alloc::{string::String, vec::Vec};
+
+use serde::{Deserialize, Serialize};
+
+use crate::catalog::LOINC;
+use crate::common::{CodeableConcept, Coding, Quantity, Reference};
+use crate::extension::Extension;
+use crate::lab_report::{LabReport, LabResult};
+
+/// Specimen a test can be run on.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "SpecimenRequirementInput")
+)]
+pub struct SpecimenRequirement {
+    /// Specimen type (HL7 v2 table 0487, e.g., "SER" serum)
+    #[serde(rename = "type")]
+    #[cfg_attr(feature = "async-graphql", graphql(name = "type"))]
+    pub specimen_type: Coding,
+    /// Collection container (e.g., SNOMED CT gold-top serum separator tube)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub container: Option<CodeableConcept>,
+    /// Least volume the lab accepts (e.g., 0.5 mL)
+    #[serde(rename = "minimumVolume", alias = "minimum_volume", skip_serializing_if = "Option::is_none")]
+    pub minimum_volume: Option<Quantity>,
+    /// Collection, transport and storage instructions (e.g., "Keep refrigerated")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub handling: Option<String>,
+}
+
+/// Orderable test.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "TestDefinitionInput")
+)]
+pub struct TestDefinition {
+    /// Test or panel code (LOINC)
+    pub code: Coding,
+    /// Name shown to orderers (e.g., "Hemoglobin A1c")
+    pub name: String,
+    /// Other names and abbreviations orderers search for (e.g., "HbA1c")
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+    /// Acceptable specimens, preferred first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub specimens: Vec<SpecimenRequirement>,
+    /// Results a panel reports (LOINC); empty for a single test
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub components: Vec<Coding>,
+    /// Usual time from receipt to result (e.g., 24 h, 3 d)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub turnaround: Option<Quantity>,
+    /// Lab the test is sent out to (e.g., "Organization/ref-lab-1"); absent when run in house
+    #[serde(rename = "referenceLab", alias = "reference_lab", skip_serializing_if = "Option::is_none")]
+    pub reference_lab: Option<Reference>,
+}
+
+impl TestDefinition {
+    /// Test with no specimen requirements yet.
+    pub fn new(code: Coding, name: impl Into<String>) -> Self {
+        TestDefinition {
+            code,
+            name: name.into(),
+            aliases: Vec::new(),
+            specimens: Vec::new(),
+            components: Vec::new(),
+            turnaround: None,
+            reference_lab: None,
+        }
+    }
+
+    /// Whether the name or an alias is `name`, ignoring ASCII case.
+    pub fn is_named(&self, name: &str) -> bool {
+        let name = name.trim();
+        self.name.eq_ignore_ascii_case(name) || self.aliases.iter().any(|alias| alias.eq_ignore_ascii_case(name))
+    }
+
+    /// Whether the test or one of its components is coded `coding`.
+    pub fn reports(&self, coding: &Coding) -> bool {
+        let same = |code: &Coding| code.system == coding.system && code.code == coding.code;
+        same(&self.code) || self.components.iter().any(same)
+    }
+
+    /// Whether a reference lab performs the test.
+    pub fn is_send_out(&self) -> bool {
+        self.reference_lab.is_some()
+    }
+}
+
+/// A laboratory's catalog of orderable tests.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "CompendiumInput")
+)]
+pub struct Compendium {
+    /// Unique compendium identifier
+    pub id: String,
+    /// Catalog title (e.g., "East District Lab test menu")
+    pub name: String,
+    /// Edition of the catalog (e.g., "2024-Q3")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// Laboratory publishing the catalog (e.g., "Organization/lab-east")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub publisher: Option<Reference>,
+    /// Orderable tests and panels
+    pub tests: Vec<TestDefinition>,
+    /// Schema version the resource was written against (e.g., "0.1.0"); absent means 0.1.0
+    #[serde(rename = "schemaVersion", alias = "schema_version", skip_serializing_if = "Option::is_none")]
+    pub schema_version: Option<String>,
+    /// Confidentiality and sensitivity labels (e.g., v3 ActCode HIV); see [`crate::security`]
+    #[serde(rename = "securityLabels", alias = "security_labels", skip_serializing_if = "Option::is_none")]
+    pub security_labels: Option<Vec<Coding>>,
+    /// Site-specific extensions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extensions: Option<Vec<Extension>>,
+    /// Properties this model version does not define, kept for re-serialization
+    #[cfg(feature = "preserve-unknown")]
+    #[serde(flatten)]
+    #[cfg_attr(feature = "schemars", schemars(skip))]
+    #[cfg_attr(feature = "utoipa", schema(ignore))]
+    #[cfg_attr(feature = "async-graphql", graphql(skip))]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Compendium {
+    /// Empty catalog.
+    pub fn new(id: impl Into<String>, name: impl Into<String>) -> Self {
+        Compendium {
+            id: id.into(),
+            name: name.into(),
+            version: None,
+            publisher: None,
+            tests: Vec::new(),
+            schema_version: None,
+            security_labels: None,
+            extensions: None,
+            #[cfg(feature = "preserve-unknown")]
+            extra: Default::default(),
+        }
+    }
+
+    /// Test with LOINC code `code`.
+    pub fn by_code(&self, code: &str) -> Option<&TestDefinition> {
+        self.tests.iter().find(|test| test.code.system == LOINC && test.code.code == code)
+    }
+
+    /// Test named or aliased `name`, ignoring ASCII case.
+    pub fn by_name(&self, name: &str) -> Option<&TestDefinition> {
+        self.tests.iter().find(|test| test.is_named(name))
+    }
+
+    /// Tests whose name or an alias contains `query`, ignoring ASCII case,
+    /// for type-ahead in ordering screens.
+    pub fn search(&self, query: &str) -> Vec<&TestDefinition> {
+        let query = query.trim().to_ascii_lowercase();
+        let matches = |name: &String| name.to_ascii_lowercase().contains(&query);
+        self.tests.iter().filter(|test| matches(&test.name) || test.aliases.iter().any(matches)).collect()
+    }
+
+    /// Whether a test or panel component of the catalog is coded `coding`.
+    pub fn covers(&self, coding: &Coding) -> bool {
+        self.tests.iter().any(|test| test.reports(coding))
+    }
+
+    /// Results of `report` with no code the catalog covers.
+    pub fn uncatalogued<'a>(&self, report: &'a LabReport) -> Vec<&'a LabResult> {
+        report.results.iter().filter(|result| !result.code.coding.iter().any(|coding| self.covers(coding))).collect()
+    }
+}
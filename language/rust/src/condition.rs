@@ -0,0 +1,180 @@
+//! Condition (problem / diagnosis) data model.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//! Schema: https://wellall.health/schemas/condition/v0.1.0
+//!
+//! A [`Condition`] records a problem, diagnosis or health concern of a
+//! patient with its clinical and verification status, onset and abatement.
+//! The patient's problem list is maintained with
+//! [`problem_list`](crate::problem_list).
+
+use alloc::{string::String, vec::Vec};
+use serde::{Deserialize, Serialize};
+
+use crate::common::{Annotation, CodeableConcept, Coding};
+use crate::datetime::FlexibleDateTime;
+use crate::extension::Extension;
+
+/// Clinical status of a condition
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "async-graphql", derive(async_graphql::Enum))]
+#[serde(rename_all = "lowercase")]
+pub enum ClinicalStatus {
+    Active,
+    /// Active again after having been resolved
+    Recurrence,
+    /// Active again after a remission
+    Relapse,
+    Inactive,
+    /// No longer symptomatic, but may return
+    Remission,
+    Resolved,
+}
+
+impl ClinicalStatus {
+    /// Whether the condition currently affects the patient.
+    pub fn is_active(&self) -> bool {
+        matches!(self, ClinicalStatus::Active | ClinicalStatus::Recurrence | ClinicalStatus::Relapse)
+    }
+}
+
+/// Certainty of a condition
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "async-graphql", derive(async_graphql::Enum))]
+#[serde(rename_all = "kebab-case")]
+pub enum VerificationStatus {
+    Unconfirmed,
+    Provisional,
+    /// One of several possible diagnoses
+    Differential,
+    Confirmed,
+    /// Ruled out
+    Refuted,
+    /// Recorded by mistake
+    EnteredInError,
+}
+
+/// Where a condition was recorded
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "async-graphql", derive(async_graphql::Enum))]
+#[serde(rename_all = "kebab-case")]
+pub enum ConditionCategory {
+    /// Kept on the patient's problem list
+    ProblemListItem,
+    /// Diagnosis made during an encounter
+    EncounterDiagnosis,
+}
+
+/// A problem, diagnosis or health concern of a patient.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "ConditionInput")
+)]
+pub struct Condition {
+    /// Unique condition identifier
+    pub id: String,
+    /// Reference to Person.id
+    #[serde(rename = "patientId", alias = "patient_id")]
+    pub patient_id: String,
+    /// Clinical status
+    #[serde(rename = "clinicalStatus", alias = "clinical_status")]
+    pub clinical_status: ClinicalStatus,
+    /// Verification status
+    #[serde(rename = "verificationStatus", alias = "verification_status", skip_serializing_if = "Option::is_none")]
+    pub verification_status: Option<VerificationStatus>,
+    /// Problem list item or encounter diagnosis
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<ConditionCategory>,
+    /// The condition (SNOMED CT or ICD-10)
+    pub code: CodeableConcept,
+    /// Severity (e.g., SNOMED CT mild/moderate/severe)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub severity: Option<CodeableConcept>,
+    /// When the condition began; may be partial (e.g., "2019")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "utoipa", schema(value_type = Option<String>))]
+    pub onset: Option<FlexibleDateTime>,
+    /// When the condition resolved or went into remission
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "utoipa", schema(value_type = Option<String>))]
+    pub abatement: Option<FlexibleDateTime>,
+    /// When the condition was recorded
+    #[serde(rename = "recordedDate", alias = "recorded_date", skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "utoipa", schema(value_type = Option<String>))]
+    pub recorded_date: Option<FlexibleDateTime>,
+    /// Free-text notes and comments
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notes: Option<Vec<Annotation>>,
+    /// Schema version the resource was written against (e.g., "0.1.0"); absent means 0.1.0
+    #[serde(rename = "schemaVersion", alias = "schema_version", skip_serializing_if = "Option::is_none")]
+    pub schema_version: Option<String>,
+    /// Confidentiality and sensitivity labels (e.g., v3 ActCode HIV); see [`crate::security`]
+    #[serde(rename = "securityLabels", alias = "security_labels", skip_serializing_if = "Option::is_none")]
+    pub security_labels: Option<Vec<Coding>>,
+    /// Site-specific extensions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extensions: Option<Vec<Extension>>,
+    /// Properties this model version does not define, kept for re-serialization
+    #[cfg(feature = "preserve-unknown")]
+    #[serde(flatten)]
+    #[cfg_attr(feature = "schemars", schemars(skip))]
+    #[cfg_attr(feature = "utoipa", schema(ignore))]
+    #[cfg_attr(feature = "async-graphql", graphql(skip))]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Condition {
+    /// Active condition with `code`.
+    pub fn new(id: impl Into<String>, patient_id: impl Into<String>, code: CodeableConcept) -> Self {
+        Condition {
+            id: id.into(),
+            patient_id: patient_id.into(),
+            clinical_status: ClinicalStatus::Active,
+            verification_status: None,
+            category: None,
+            code,
+            severity: None,
+            onset: None,
+            abatement: None,
+            recorded_date: None,
+            notes: None,
+            schema_version: None,
+            security_labels: None,
+            extensions: None,
+            #[cfg(feature = "preserve-unknown")]
+            extra: Default::default(),
+        }
+    }
+
+    /// Whether the condition is clinically active and not refuted or
+    /// entered in error.
+    pub fn is_active(&self) -> bool {
+        self.clinical_status.is_active()
+            && !matches!(self.verification_status, Some(VerificationStatus::Refuted | VerificationStatus::EnteredInError))
+    }
+
+    /// Whether `other` codes the same condition: they share a coding
+    /// (system and code), or, without codings, have the same text.
+    pub fn same_code(&self, other: &Condition) -> bool {
+        let (ours, theirs) = (&self.code, &other.code);
+        if ours.coding.is_empty() || theirs.coding.is_empty() {
+            return ours.coding.is_empty()
+                && theirs.coding.is_empty()
+                && ours.text.as_deref().map(str::trim).is_some_and(|text| {
+                    theirs.text.as_deref().is_some_and(|other| other.trim().eq_ignore_ascii_case(text))
+                });
+        }
+        ours.coding.iter().any(|coding| theirs.coding.iter().any(|other| other.system == coding.system && other.code == coding.code))
+    }
+}
@@ -0,0 +1,339 @@
+//! Golden-file conformance suite for resource payloads.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//!
+//! Every fixture is deserialized into its resource type, serialized again and
+//! compared with the original in canonical form (sorted keys, no insignificant
+//! whitespace, integral numbers without a fraction). A payload passes only if
+//! nothing is lost or altered on the way through the Rust models, so services
+//! can point [`assert_dir`] at their own payloads to check they stay within
+//! what the schemas describe.
+//!
+//! The resource type of a fixture is taken from its `resourceType` field, else
+//! from the file name prefix (`lab-report.cbc.json`, `medication.json`), else
+//! from the name of an enclosing directory (`family-health/examples/...`).
+//!
+//! ```no_run
+//! wellally::conformance::assert_dir("../../infrastructure/schemas");
+//! ```
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde_json::{Map, Number, Value};
+
+use crate::error::WellAllyError;
+use crate::family_health::FamilyHealthTree;
+use crate::health::Person;
+use crate::imaging_report::ImagingReport;
+use crate::lab_report::LabReport;
+use crate::medication::MedicationRecord;
+use crate::questionnaire::{Questionnaire, QuestionnaireResponse};
+use crate::referral::Referral;
+use crate::task::Task;
+use crate::communication::CommunicationRecord;
+use crate::location::Location;
+use crate::condition::Condition;
+use crate::allergy::AllergyIntolerance;
+use crate::flag::Flag;
+use crate::cgm::GlucoseSeries;
+use crate::ecg::EcgRecord;
+use crate::consent::ConsentRecord;
+use crate::nephrology::DialysisSession;
+use crate::rehab::RehabSession;
+use crate::wound::WoundAssessment;
+use crate::infusion::InfusionRecord;
+use crate::pathology::PathologyReport;
+use crate::audiology::AudiogramReport;
+use crate::birth::BirthRecord;
+use crate::clearance::ClearanceDocument;
+use crate::travel::TravelHealthRecord;
+use crate::compendium::Compendium;
+use crate::dispense::DispenseRecord;
+use crate::immunization::ImmunizationRecord;
+use crate::relationships::RelatedPerson;
+use crate::resource::Resource;
+
+/// Why a fixture failed the conformance check.
+#[derive(Debug)]
+pub enum ConformanceError {
+    /// The fixture could not be read
+    Io(io::Error),
+    /// The fixture is not valid JSON
+    Json(serde_json::Error),
+    /// The fixture does not match its resource type
+    Resource(WellAllyError),
+    /// The resource type of the fixture could not be determined
+    UnknownResourceType,
+    /// The re-serialized resource differs from the fixture
+    Mismatch {
+        /// JSON Pointer of the first difference
+        pointer: String,
+        /// Canonical fixture value at the pointer (empty if missing)
+        expected: String,
+        /// Canonical re-serialized value at the pointer (empty if missing)
+        actual: String,
+    },
+}
+
+impl fmt::Display for ConformanceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConformanceError::Io(err) => write!(f, "cannot read fixture: {}", err),
+            ConformanceError::Json(err) => write!(f, "invalid fixture: {}", err),
+            ConformanceError::Resource(err) => write!(f, "invalid fixture: {}", err),
+            ConformanceError::UnknownResourceType => write!(f, "cannot determine resource type"),
+            ConformanceError::Mismatch { pointer, expected, actual } => write!(
+                f,
+                "round-trip mismatch at '{}': expected {}, got {}",
+                pointer,
+                if expected.is_empty() { "nothing" } else { expected },
+                if actual.is_empty() { "nothing" } else { actual },
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ConformanceError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ConformanceError::Io(err) => Some(err),
+            ConformanceError::Json(err) => Some(err),
+            ConformanceError::Resource(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ConformanceError {
+    fn from(err: io::Error) -> Self {
+        ConformanceError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for ConformanceError {
+    fn from(err: serde_json::Error) -> Self {
+        ConformanceError::Json(err)
+    }
+}
+
+impl From<WellAllyError> for ConformanceError {
+    fn from(err: WellAllyError) -> Self {
+        ConformanceError::Resource(err)
+    }
+}
+
+/// Outcome of checking a set of fixtures.
+#[derive(Debug, Default)]
+pub struct ConformanceReport {
+    /// Fixtures that round-tripped, with their resource type
+    pub passed: Vec<(PathBuf, &'static str)>,
+    /// Fixtures that failed, with the reason
+    pub failures: Vec<(PathBuf, ConformanceError)>,
+}
+
+impl ConformanceReport {
+    /// Whether every fixture passed.
+    pub fn is_ok(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    /// Panics with every failure if any fixture failed.
+    pub fn assert_ok(&self) {
+        if self.is_ok() {
+            return;
+        }
+        let failures: Vec<String> =
+            self.failures.iter().map(|(path, err)| format!("  {}: {}", path.display(), err)).collect();
+        panic!(
+            "{} of {} fixtures failed conformance:\n{}",
+            self.failures.len(),
+            self.failures.len() + self.passed.len(),
+            failures.join("\n")
+        );
+    }
+}
+
+/// Canonical form of a JSON value: sorted keys, compact, integral numbers without fraction.
+pub fn canonical(value: &Value) -> String {
+    normalize(value).to_string()
+}
+
+fn normalize(value: &Value) -> Value {
+    match value {
+        Value::Number(n) => match n.as_f64() {
+            Some(f) if n.is_f64() && f.fract() == 0.0 && f.abs() < 9_007_199_254_740_992.0 => {
+                Value::Number(Number::from(f as i64))
+            }
+            _ => value.clone(),
+        },
+        Value::Array(items) => Value::Array(items.iter().map(normalize).collect()),
+        Value::Object(map) => {
+            Value::Object(map.iter().map(|(k, v)| (k.clone(), normalize(v))).collect::<Map<_, _>>())
+        }
+        _ => value.clone(),
+    }
+}
+
+/// First difference between two normalized values as (pointer, expected, actual).
+fn first_difference(pointer: String, expected: Option<&Value>, actual: Option<&Value>) -> Option<(String, String, String)> {
+    let text = |value: Option<&Value>| value.map(Value::to_string).unwrap_or_default();
+    match (expected, actual) {
+        (Some(Value::Object(e)), Some(Value::Object(a))) => {
+            let mut keys: Vec<&String> = e.keys().chain(a.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            keys.into_iter().find_map(|key| {
+                let escaped = key.replace('~', "~0").replace('/', "~1");
+                first_difference(format!("{}/{}", pointer, escaped), e.get(key), a.get(key))
+            })
+        }
+        (Some(Value::Array(e)), Some(Value::Array(a))) => (0..e.len().max(a.len()))
+            .find_map(|i| first_difference(format!("{}/{}", pointer, i), e.get(i), a.get(i))),
+        (e, a) if e == a => None,
+        (e, a) => Some((pointer, text(e), text(a))),
+    }
+}
+
+/// Checks that `json` round-trips through `R` without changes. Properties
+/// kept by the `preserve-unknown` feature do not count as round-tripped.
+pub fn check<R: Resource>(json: &str) -> Result<(), ConformanceError> {
+    let original: Value = serde_json::from_str(json)?;
+    let mut resource = R::from_json_value(&original)?;
+    resource.take_unknown_fields();
+    let expected = normalize(&original);
+    let actual = normalize(&serde_json::to_value(&resource)?);
+    match first_difference(String::new(), Some(&expected), Some(&actual)) {
+        None => Ok(()),
+        Some((pointer, expected, actual)) => Err(ConformanceError::Mismatch { pointer, expected, actual }),
+    }
+}
+
+struct Kind {
+    resource_type: &'static str,
+    /// File name prefixes and directory names that identify the type
+    names: [&'static str; 2],
+    check: fn(&str) -> Result<(), ConformanceError>,
+}
+
+fn kind<R: Resource>(alias: &'static str) -> Kind {
+    // Schema ids end in "/<module>/v<version>".
+    let module = R::SCHEMA_ID.rsplit('/').nth(1).unwrap_or(alias);
+    Kind { resource_type: R::RESOURCE_TYPE, names: [module, alias], check: check::<R> }
+}
+
+fn kinds() -> [Kind; 30] {
+    [
+        kind::<Person>("person"),
+        kind::<LabReport>("lab-report"),
+        kind::<ImagingReport>("imaging-report"),
+        kind::<MedicationRecord>("medication-record"),
+        kind::<FamilyHealthTree>("family-tree"),
+        kind::<Questionnaire>("questionnaire-definition"),
+        kind::<QuestionnaireResponse>("questionnaire-response"),
+        kind::<Referral>("referrals"),
+        kind::<Task>("tasks"),
+        kind::<CommunicationRecord>("messages"),
+        kind::<Location>("locations"),
+        kind::<Condition>("condition"),
+        kind::<AllergyIntolerance>("allergies"),
+        kind::<Flag>("flags"),
+        kind::<GlucoseSeries>("glucose-series"),
+        kind::<EcgRecord>("ecg"),
+        kind::<ConsentRecord>("consents"),
+        kind::<DialysisSession>("dialysis-sessions"),
+        kind::<RehabSession>("rehab-sessions"),
+        kind::<WoundAssessment>("wound-assessments"),
+        kind::<InfusionRecord>("infusions"),
+        kind::<PathologyReport>("pathology-report"),
+        kind::<AudiogramReport>("audiogram"),
+        kind::<BirthRecord>("birth-record"),
+        kind::<ClearanceDocument>("clearances"),
+        kind::<TravelHealthRecord>("travel"),
+        kind::<Compendium>("lab-compendium"),
+        kind::<DispenseRecord>("dispense"),
+        kind::<ImmunizationRecord>("immunization"),
+        kind::<RelatedPerson>("related-person"),
+    ]
+}
+
+fn detect<'a>(kinds: &'a [Kind], path: &Path, document: &Value) -> Option<&'a Kind> {
+    if let Some(Value::String(name)) = document.get("resourceType") {
+        return kinds.iter().find(|k| k.resource_type == name);
+    }
+    let file_name = path.file_name()?.to_str()?;
+    let prefix = file_name.split('.').next().unwrap_or(file_name);
+    kinds.iter().find(|k| k.names.contains(&prefix)).or_else(|| {
+        path.ancestors()
+            .skip(1)
+            .filter_map(|dir| dir.file_name()?.to_str())
+            .find_map(|dir| kinds.iter().find(|k| k.names.contains(&dir)))
+    })
+}
+
+fn check_text(path: &Path, json: &str) -> Result<&'static str, ConformanceError> {
+    let document: Value = serde_json::from_str(json)?;
+    let kinds = kinds();
+    let kind = detect(&kinds, path, &document).ok_or(ConformanceError::UnknownResourceType)?;
+    (kind.check)(json)?;
+    Ok(kind.resource_type)
+}
+
+/// Checks one fixture file and returns its resource type.
+pub fn check_file(path: impl AsRef<Path>) -> Result<&'static str, ConformanceError> {
+    let path = path.as_ref();
+    check_text(path, &fs::read_to_string(path)?)
+}
+
+fn json_files(dir: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            json_files(&path, files)?;
+        } else if path.extension().is_some_and(|ext| ext == "json") {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// JSON Schema documents (with a `$schema` keyword) may sit next to fixtures.
+fn is_schema(json: &str) -> bool {
+    serde_json::from_str::<Value>(json).is_ok_and(|document| document.get("$schema").is_some())
+}
+
+/// Checks every `*.json` fixture under `dir` recursively, skipping JSON Schema documents.
+pub fn check_dir(dir: impl AsRef<Path>) -> io::Result<ConformanceReport> {
+    let mut files = Vec::new();
+    json_files(dir.as_ref(), &mut files)?;
+    files.sort();
+    let mut report = ConformanceReport::default();
+    for path in files {
+        let result = match fs::read_to_string(&path) {
+            Ok(json) if is_schema(&json) => continue,
+            Ok(json) => check_text(&path, &json),
+            Err(err) => Err(err.into()),
+        };
+        match result {
+            Ok(resource_type) => report.passed.push((path, resource_type)),
+            Err(err) => report.failures.push((path, err)),
+        }
+    }
+    Ok(report)
+}
+
+/// Runs [`check_dir`] and panics unless at least one fixture was found and all passed.
+pub fn assert_dir(dir: impl AsRef<Path>) -> ConformanceReport {
+    let dir = dir.as_ref();
+    let report = check_dir(dir).unwrap_or_else(|err| panic!("cannot read {}: {}", dir.display(), err));
+    assert!(
+        !report.passed.is_empty() || !report.failures.is_empty(),
+        "no fixtures found under {}",
+        dir.display()
+    );
+    report.assert_ok();
+    report
+}
@@ -0,0 +1,237 @@
+//! Patient consent data model.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//! Schema: https://wellall.health/schemas/consent/v0.1.0
+//!
+//! A [`ConsentRecord`] states whether a patient agrees to share their
+//! records: a base decision (permit or deny) and provisions that make
+//! exceptions to it for particular purposes of use, recipients, sensitivity
+//! categories (mental health, HIV, genetics, ...) or resource types. With
+//! the `std` feature, [`filter`] applies a patient's consents to a bundle
+//! before it is shared, redacting or excluding what they did not agree to
+//! and logging each decision. The categories of a resource are its
+//! security labels plus those derived from its codes (see
+//! [`crate::security`]).
+
+use alloc::{string::String, vec::Vec};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::common::{Coding, Period, Reference};
+use crate::extension::Extension;
+use crate::security::same_label;
+
+#[cfg(feature = "std")]
+mod filter;
+#[cfg(feature = "std")]
+pub use filter::{filter, AccessDecision, AccessOutcome, ConsentFilter, Filtered};
+pub use crate::security::{sensitivity, BEHAVIORAL_HEALTH, GENETIC, HIV, SEXUAL_HEALTH, SUBSTANCE_USE};
+
+/// Whether a consent is in force
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "async-graphql", derive(async_graphql::Enum))]
+#[serde(rename_all = "kebab-case")]
+pub enum ConsentStatus {
+    /// Being prepared; not yet in force
+    Draft,
+    Active,
+    /// Revoked or superseded
+    Inactive,
+    /// Recorded by mistake
+    EnteredInError,
+}
+
+/// Whether sharing is allowed
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "async-graphql", derive(async_graphql::Enum))]
+#[serde(rename_all = "lowercase")]
+pub enum ConsentDecision {
+    Permit,
+    Deny,
+}
+
+/// Why data is requested (HL7 v3 PurposeOfUse codes)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "async-graphql", derive(async_graphql::Enum))]
+pub enum Purpose {
+    /// Treatment
+    #[serde(rename = "TREAT")]
+    Treatment,
+    /// Emergency treatment
+    #[serde(rename = "ETREAT")]
+    EmergencyTreatment,
+    /// Payment
+    #[serde(rename = "HPAYMT")]
+    Payment,
+    /// Healthcare operations
+    #[serde(rename = "HOPERAT")]
+    Operations,
+    /// Research
+    #[serde(rename = "HRESCH")]
+    Research,
+    /// Public health
+    #[serde(rename = "PUBHLTH")]
+    PublicHealth,
+    /// Requested by the patient
+    #[serde(rename = "PATRQT")]
+    PatientRequest,
+}
+
+/// An exception to a consent's base decision. Each listed criterion narrows
+/// the provision; an absent one matches everything.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "ConsentProvisionInput")
+)]
+pub struct ConsentProvision {
+    /// Decision for requests the provision matches
+    pub decision: ConsentDecision,
+    /// Purposes of use covered
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub purposes: Option<Vec<Purpose>>,
+    /// Recipients covered (e.g., "Organization/research-1")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actors: Option<Vec<Reference>>,
+    /// Sensitivity categories covered (e.g., v3 ActCode BH)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub categories: Option<Vec<Coding>>,
+    /// Resource types covered (e.g., "LabReport")
+    #[serde(rename = "resourceTypes", alias = "resource_types", skip_serializing_if = "Option::is_none")]
+    pub resource_types: Option<Vec<String>>,
+    /// When the provision applies
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub period: Option<Period>,
+}
+
+/// A patient's decision on sharing their records.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "ConsentRecordInput")
+)]
+pub struct ConsentRecord {
+    /// Unique consent identifier
+    pub id: String,
+    /// Reference to Person.id
+    #[serde(rename = "patientId", alias = "patient_id")]
+    pub patient_id: String,
+    /// Whether the consent is in force
+    pub status: ConsentStatus,
+    /// Decision unless a provision says otherwise
+    pub decision: ConsentDecision,
+    /// When the patient gave the consent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub date: Option<NaiveDate>,
+    /// When the consent applies
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub period: Option<Period>,
+    /// Exceptions to the base decision
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provisions: Option<Vec<ConsentProvision>>,
+    /// Schema version the resource was written against (e.g., "0.1.0"); absent means 0.1.0
+    #[serde(rename = "schemaVersion", alias = "schema_version", skip_serializing_if = "Option::is_none")]
+    pub schema_version: Option<String>,
+    /// Confidentiality and sensitivity labels (e.g., v3 ActCode HIV); see [`crate::security`]
+    #[serde(rename = "securityLabels", alias = "security_labels", skip_serializing_if = "Option::is_none")]
+    pub security_labels: Option<Vec<Coding>>,
+    /// Site-specific extensions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extensions: Option<Vec<Extension>>,
+    /// Properties this model version does not define, kept for re-serialization
+    #[cfg(feature = "preserve-unknown")]
+    #[serde(flatten)]
+    #[cfg_attr(feature = "schemars", schemars(skip))]
+    #[cfg_attr(feature = "utoipa", schema(ignore))]
+    #[cfg_attr(feature = "async-graphql", graphql(skip))]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// What a request asks to share, for matching against consents.
+#[derive(Debug, Clone, Copy)]
+pub struct ConsentRequest<'a> {
+    /// Why the data is requested
+    pub purpose: Purpose,
+    /// Who requests it (e.g., "Organization/research-1")
+    pub requester: &'a str,
+    /// Type of the resource
+    pub resource_type: &'a str,
+    /// Sensitivity categories of the resource
+    pub categories: &'a [Coding],
+    /// Day of the request
+    pub date: NaiveDate,
+}
+
+impl ConsentRecord {
+    /// Active consent with base decision `decision`.
+    pub fn new(id: impl Into<String>, patient_id: impl Into<String>, decision: ConsentDecision) -> Self {
+        ConsentRecord {
+            id: id.into(),
+            patient_id: patient_id.into(),
+            status: ConsentStatus::Active,
+            decision,
+            date: None,
+            period: None,
+            provisions: None,
+            schema_version: None,
+            security_labels: None,
+            extensions: None,
+            #[cfg(feature = "preserve-unknown")]
+            extra: Default::default(),
+        }
+    }
+
+    /// Whether the consent is active and its period includes `date`.
+    pub fn is_in_force(&self, date: NaiveDate) -> bool {
+        self.status == ConsentStatus::Active && self.period.is_none_or(|period| period.contains(date))
+    }
+
+    /// Decision for `request`: `None` when the consent is not in force,
+    /// otherwise the decision of the matching provisions (a deny wins over
+    /// a permit), or the base decision when none match.
+    pub fn decide(&self, request: &ConsentRequest<'_>) -> Option<ConsentDecision> {
+        if !self.is_in_force(request.date) {
+            return None;
+        }
+        let matching: Vec<_> = self
+            .provisions
+            .iter()
+            .flatten()
+            .filter(|provision| provision.matches(request))
+            .map(|provision| provision.decision)
+            .collect();
+        if matching.contains(&ConsentDecision::Deny) {
+            Some(ConsentDecision::Deny)
+        } else if matching.is_empty() {
+            Some(self.decision)
+        } else {
+            Some(ConsentDecision::Permit)
+        }
+    }
+}
+
+impl ConsentProvision {
+    /// Whether every criterion of the provision covers `request`.
+    pub fn matches(&self, request: &ConsentRequest<'_>) -> bool {
+        self.purposes.as_ref().is_none_or(|purposes| purposes.contains(&request.purpose))
+            && self.actors.as_ref().is_none_or(|actors| actors.iter().any(|actor| actor.reference == request.requester))
+            && self.categories.as_ref().is_none_or(|categories| {
+                categories.iter().any(|category| request.categories.iter().any(|label| same_label(label, category)))
+            })
+            && self.resource_types.as_ref().is_none_or(|types| types.iter().any(|t| t == request.resource_type))
+            && self.period.is_none_or(|period| period.contains(request.date))
+    }
+}
@@ -0,0 +1,216 @@
+//! Consent-aware filtering of bundles before they are shared.
+
+use chrono::{NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{ConsentDecision, ConsentRecord, ConsentRequest, Purpose};
+use crate::common::Coding;
+use crate::io::{AnyResource, Bundle};
+use crate::security::{self, Classifier, SecurityLabeled};
+use crate::validation::index;
+
+/// What the filter did with a resource.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "outcome")]
+pub enum AccessOutcome {
+    /// Shared as is
+    Permitted,
+    /// Shared without the parts at `paths` (e.g., "results[2]")
+    Redacted { paths: Vec<String> },
+    /// Not shared
+    Excluded,
+}
+
+/// Log entry of the decision on one resource.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessDecision {
+    /// Resource type name (e.g., "LabReport")
+    pub resource_type: String,
+    /// Resource id
+    pub id: String,
+    /// Patient the resource belongs to
+    pub patient_id: String,
+    /// What was done with the resource
+    #[serde(flatten)]
+    pub outcome: AccessOutcome,
+    /// Sensitivity categories found in the resource
+    pub categories: Vec<Coding>,
+    /// Consents in force that decided; empty when the default applied
+    pub consents: Vec<String>,
+}
+
+/// The shared bundle and the decision on every input resource.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Filtered {
+    /// Resources that may be shared, redacted where needed
+    pub bundle: Bundle,
+    /// One entry per input resource, in input order
+    pub decisions: Vec<AccessDecision>,
+}
+
+/// Applies consents to bundles. A resource's categories are its security
+/// labels plus those derived from its codes. Lab results are decided one by
+/// one, so a report loses only the results the patient did not agree to
+/// share; other resources are shared or excluded whole.
+pub struct ConsentFilter<'a> {
+    consents: &'a [ConsentRecord],
+    default: ConsentDecision,
+    date: NaiveDate,
+    classifier: Classifier<'a>,
+}
+
+impl<'a> ConsentFilter<'a> {
+    /// Filter applying `consents` today, excluding resources of patients
+    /// without a consent in force, and classifying codes with
+    /// [`security::sensitivity`].
+    pub fn new(consents: &'a [ConsentRecord]) -> Self {
+        ConsentFilter {
+            consents,
+            default: ConsentDecision::Deny,
+            date: Utc::now().date_naive(),
+            classifier: Classifier::default(),
+        }
+    }
+
+    /// Sets the decision for patients without a consent in force.
+    pub fn with_default(mut self, default: ConsentDecision) -> Self {
+        self.default = default;
+        self
+    }
+
+    /// Sets the day consents and provisions are evaluated for.
+    pub fn with_date(mut self, date: NaiveDate) -> Self {
+        self.date = date;
+        self
+    }
+
+    /// Sets the sensitivity category of a code.
+    pub fn with_classifier(mut self, classifier: impl Fn(&Coding) -> Option<Coding> + 'a) -> Self {
+        self.classifier = Classifier::new(classifier);
+        self
+    }
+
+    /// Resources of `bundle` that may be shared with `requester` for `purpose`.
+    pub fn apply(&self, bundle: &Bundle, purpose: Purpose, requester: &str) -> Filtered {
+        let mut shared = Vec::new();
+        let mut decisions = Vec::new();
+        for resource in &bundle.resources {
+            let (kept, decision) = self.decide_resource(resource, purpose, requester);
+            shared.extend(kept);
+            decisions.push(decision);
+        }
+        Filtered { bundle: Bundle::new(shared), decisions }
+    }
+
+    fn decide_resource(
+        &self,
+        resource: &AnyResource,
+        purpose: Purpose,
+        requester: &str,
+    ) -> (Option<AnyResource>, AccessDecision) {
+        let mut value = serde_json::to_value(resource).unwrap_or_default();
+        let results = match (resource, &mut value) {
+            (AnyResource::LabReport(_), Value::Object(report)) => report.remove("results"),
+            _ => None,
+        };
+        let mut categories = resource.all_security_labels().to_vec();
+        security::merge(&mut categories, self.classifier.categories(&value));
+        let (decision, mut consents) = self.decide(resource, &categories, purpose, requester);
+        let mut log = AccessDecision {
+            resource_type: resource.resource_type().to_string(),
+            id: resource.id().to_string(),
+            patient_id: resource.patient_id().to_string(),
+            outcome: AccessOutcome::Excluded,
+            categories,
+            consents: Vec::new(),
+        };
+        let (AnyResource::LabReport(report), ConsentDecision::Permit) = (resource, decision) else {
+            log.outcome = outcome(decision);
+            log.consents = consents;
+            return ((decision == ConsentDecision::Permit).then(|| resource.clone()), log);
+        };
+        let mut denied = Vec::new();
+        for result in results.as_ref().and_then(Value::as_array).into_iter().flatten() {
+            let mut categories = resource.all_security_labels().to_vec();
+            security::merge(&mut categories, self.classifier.categories(result));
+            let (decision, deciding) = self.decide(resource, &categories, purpose, requester);
+            security::merge(&mut log.categories, categories);
+            merge(&mut consents, deciding);
+            denied.push(decision == ConsentDecision::Deny);
+        }
+        log.consents = consents;
+        let paths: Vec<_> = (0..denied.len()).filter(|i| denied[*i]).map(|i| index("results", i)).collect();
+        if paths.is_empty() {
+            log.outcome = AccessOutcome::Permitted;
+            return (Some(resource.clone()), log);
+        }
+        let mut kept = report.clone();
+        let mut deny = denied.into_iter();
+        kept.results.retain(|_| !deny.next().unwrap_or(false));
+        if kept.results.is_empty() {
+            log.outcome = AccessOutcome::Excluded;
+            (None, log)
+        } else {
+            log.outcome = AccessOutcome::Redacted { paths };
+            (Some(AnyResource::LabReport(kept)), log)
+        }
+    }
+
+    /// Decision across the patient's consents in force (a deny wins), with
+    /// the ids of the consents that decided.
+    fn decide(
+        &self,
+        resource: &AnyResource,
+        categories: &[Coding],
+        purpose: Purpose,
+        requester: &str,
+    ) -> (ConsentDecision, Vec<String>) {
+        let request = ConsentRequest {
+            purpose,
+            requester,
+            resource_type: resource.resource_type(),
+            categories,
+            date: self.date,
+        };
+        let decided: Vec<_> = self
+            .consents
+            .iter()
+            .filter(|consent| consent.patient_id == resource.patient_id())
+            .filter_map(|consent| consent.decide(&request).map(|decision| (decision, consent.id.clone())))
+            .collect();
+        let decision = if decided.is_empty() {
+            self.default
+        } else if decided.iter().any(|(decision, _)| *decision == ConsentDecision::Deny) {
+            ConsentDecision::Deny
+        } else {
+            ConsentDecision::Permit
+        };
+        let ids = decided.into_iter().filter(|(d, _)| *d == decision).map(|(_, id)| id).collect();
+        (decision, ids)
+    }
+}
+
+/// Appends the items not in `items` yet.
+fn merge<T: PartialEq>(items: &mut Vec<T>, new: impl IntoIterator<Item = T>) {
+    for item in new {
+        if !items.contains(&item) {
+            items.push(item);
+        }
+    }
+}
+
+fn outcome(decision: ConsentDecision) -> AccessOutcome {
+    match decision {
+        ConsentDecision::Permit => AccessOutcome::Permitted,
+        ConsentDecision::Deny => AccessOutcome::Excluded,
+    }
+}
+
+/// Resources of `bundle` that `consents` allow sharing with `requester` for
+/// `purpose` today, with the decision log. Patients without a consent in
+/// force are excluded.
+pub fn filter(bundle: &Bundle, consents: &[ConsentRecord], purpose: Purpose, requester: &str) -> Filtered {
+    ConsentFilter::new(consents).apply(bundle, purpose, requester)
+}
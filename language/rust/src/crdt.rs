@@ -0,0 +1,318 @@
+//! Conflict-free merging of records edited offline on several devices.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//!
+//! A [`Mergeable`] record keeps, next to each top-level field, the [`Stamp`]
+//! of its last edit. Replicas edited independently merge field by field and
+//! reach the same record in any merge order, without a server arbitrating:
+//! a plain field is a last-writer-wins [`LwwRegister`], and a list field
+//! declared as a set (notes, adherence log entries, vital readings) is an
+//! observed-remove [`OrSet`], so entries added on different devices are all
+//! kept and an entry removed on one device stays removed.
+//!
+//! Every edit needs a distinct stamp: the wall-clock time of the edit and the
+//! id of the device making it, which breaks ties.
+//!
+//! ```
+//! use wellally::crdt::{Mergeable, Stamp};
+//! use wellally::Task;
+//!
+//! let stamp = |at: &str, device: &str| Stamp::new(at.parse().unwrap(), device);
+//! let task: Task = serde_json::from_value(serde_json::json!({
+//!     "id": "task-1", "patientId": "p-1", "status": "requested", "description": "Repeat CBC"
+//! }))
+//! .unwrap();
+//! let base = Mergeable::new(&task, &stamp("2024-11-02T09:00:00Z", "server"), &[]).unwrap();
+//!
+//! let edited = |description: &str| Task { description: description.into(), ..task.clone() };
+//! let mut phone = base.clone();
+//! phone.update(&edited("Repeat CBC fasting"), &stamp("2024-11-02T10:00:00Z", "phone")).unwrap();
+//! let mut tablet = base.clone();
+//! tablet.update(&edited("Cancel"), &stamp("2024-11-02T09:30:00Z", "tablet")).unwrap();
+//!
+//! phone.merge(&tablet);
+//! tablet.merge(&phone);
+//! assert_eq!(phone, tablet);
+//! assert_eq!(phone.value().unwrap().description, "Repeat CBC fasting");
+//! ```
+
+use alloc::{collections::{BTreeMap, BTreeSet}, string::{String, ToString}, vec::Vec};
+use core::marker::PhantomData;
+
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::error::{self, type_name, WellAllyError};
+
+/// When and where an edit was made. Stamps order by time, then device id.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct Stamp {
+    /// Wall-clock time of the edit
+    pub at: DateTime<Utc>,
+    /// Id of the device that made the edit
+    pub device: String,
+}
+
+impl Stamp {
+    /// Stamp of an edit made at `at` on `device`.
+    pub fn new(at: DateTime<Utc>, device: impl Into<String>) -> Self {
+        Stamp { at, device: device.into() }
+    }
+}
+
+/// Last-writer-wins register: the value of the edit with the latest stamp.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LwwRegister<T> {
+    /// Current value
+    pub value: T,
+    /// Stamp of the edit that wrote it
+    pub stamp: Stamp,
+}
+
+impl<T: Clone> LwwRegister<T> {
+    /// Register holding `value` written at `stamp`.
+    pub fn new(value: T, stamp: Stamp) -> Self {
+        LwwRegister { value, stamp }
+    }
+
+    /// Writes `value` unless the register holds a later edit.
+    pub fn set(&mut self, value: T, stamp: Stamp) {
+        if stamp > self.stamp {
+            *self = LwwRegister { value, stamp };
+        }
+    }
+
+    /// Takes the other replica's value if it was written later.
+    pub fn merge(&mut self, other: &Self) {
+        self.set(other.value.clone(), other.stamp.clone());
+    }
+}
+
+/// Identity of one addition to an [`OrSet`]: the edit's stamp and the
+/// position among the values it added.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct Tag {
+    /// Stamp of the edit that added the value
+    pub stamp: Stamp,
+    /// Position among the values added by that edit
+    pub seq: u32,
+}
+
+/// Observed-remove set: a removal removes the additions of a value the
+/// replica has seen, so a concurrent addition elsewhere survives the merge.
+/// Values are listed in the order they were added.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrSet<T> {
+    added: Vec<(Tag, T)>,
+    removed: BTreeSet<Tag>,
+}
+
+impl<T> Default for OrSet<T> {
+    fn default() -> Self {
+        OrSet { added: Vec::new(), removed: BTreeSet::new() }
+    }
+}
+
+impl<T: Clone + PartialEq> OrSet<T> {
+    /// Empty set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `value` at `stamp`.
+    pub fn add(&mut self, value: T, stamp: &Stamp) {
+        let added = self.added.iter().map(|(tag, _)| tag).chain(&self.removed);
+        let seq = added.filter(|tag| tag.stamp == *stamp).count() as u32;
+        let tag = Tag { stamp: stamp.clone(), seq };
+        let at = self.added.partition_point(|(existing, _)| *existing < tag);
+        self.added.insert(at, (tag, value));
+    }
+
+    /// Removes every addition of `value` this replica has seen; returns
+    /// whether there was one.
+    pub fn remove(&mut self, value: &T) -> bool {
+        let mut removed = false;
+        self.added.retain(|(tag, added)| {
+            let remove = added == value;
+            if remove {
+                self.removed.insert(tag.clone());
+                removed = true;
+            }
+            !remove
+        });
+        removed
+    }
+
+    /// Whether the set holds `value`.
+    pub fn contains(&self, value: &T) -> bool {
+        self.added.iter().any(|(_, added)| added == value)
+    }
+
+    /// Values in the order they were added; a value added more than once is
+    /// listed once, at its first addition.
+    pub fn values(&self) -> Vec<&T> {
+        let mut values: Vec<&T> = Vec::new();
+        for (_, value) in &self.added {
+            if !values.contains(&value) {
+                values.push(value);
+            }
+        }
+        values
+    }
+
+    /// Unites the additions and removals of both replicas.
+    pub fn merge(&mut self, other: &Self) {
+        self.removed.extend(other.removed.iter().cloned());
+        for (tag, value) in &other.added {
+            if let Err(at) = self.added.binary_search_by(|(existing, _)| existing.cmp(tag)) {
+                self.added.insert(at, (tag.clone(), value.clone()));
+            }
+        }
+        let removed = &self.removed;
+        self.added.retain(|(tag, _)| !removed.contains(tag));
+    }
+}
+
+/// State of one top-level field of a [`Mergeable`] record.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldState {
+    /// Last-writer-wins value; `null` when the field was cleared
+    Register(LwwRegister<Value>),
+    /// List field merged as an observed-remove set of its items
+    Set(OrSet<Value>),
+}
+
+/// A record whose top-level fields carry the stamp of their last edit, so
+/// replicas merge deterministically. Serializes with its stamps for storing
+/// and exchanging replicas.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct Mergeable<T> {
+    fields: BTreeMap<String, FieldState>,
+    #[serde(skip)]
+    record: PhantomData<fn() -> T>,
+}
+
+impl<T> Clone for Mergeable<T> {
+    fn clone(&self) -> Self {
+        Mergeable { fields: self.fields.clone(), record: PhantomData }
+    }
+}
+
+impl<T> PartialEq for Mergeable<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.fields == other.fields
+    }
+}
+
+impl<T: Serialize> Mergeable<T> {
+    /// Replica of `record` with every field stamped `stamp`. List fields
+    /// named in `set_fields` merge as sets of their items.
+    pub fn new(record: &T, stamp: &Stamp, set_fields: &[&str]) -> Result<Self, WellAllyError> {
+        let mut fields = BTreeMap::new();
+        for name in set_fields {
+            fields.insert(name.to_string(), FieldState::Set(OrSet::new()));
+        }
+        let mut mergeable = Mergeable { fields, record: PhantomData };
+        mergeable.update(record, stamp)?;
+        Ok(mergeable)
+    }
+
+    /// Records the edits that turn the current record into `record`: each
+    /// changed field is written at `stamp`, and set fields add and remove
+    /// the items that differ.
+    pub fn update(&mut self, record: &T, stamp: &Stamp) -> Result<(), WellAllyError> {
+        let mut object = to_object(record)?;
+        for (name, state) in &mut self.fields {
+            let value = object.remove(name).unwrap_or(Value::Null);
+            match state {
+                FieldState::Register(register) if register.value != value => register.set(value, stamp.clone()),
+                FieldState::Register(_) => {}
+                FieldState::Set(set) => {
+                    let items = match value {
+                        Value::Array(items) => items,
+                        _ => Vec::new(),
+                    };
+                    let stale: Vec<Value> =
+                        set.values().into_iter().filter(|item| !items.contains(item)).cloned().collect();
+                    for item in &stale {
+                        set.remove(item);
+                    }
+                    for item in items {
+                        if !set.contains(&item) {
+                            set.add(item, stamp);
+                        }
+                    }
+                }
+            }
+        }
+        for (name, value) in object {
+            self.fields.insert(name, FieldState::Register(LwwRegister::new(value, stamp.clone())));
+        }
+        Ok(())
+    }
+}
+
+impl<T> Mergeable<T> {
+    /// Merges another replica of the same record into this one.
+    pub fn merge(&mut self, other: &Self) {
+        for (name, theirs) in &other.fields {
+            match (self.fields.get_mut(name), theirs) {
+                (Some(FieldState::Register(ours)), FieldState::Register(theirs)) => ours.merge(theirs),
+                (Some(FieldState::Set(ours)), FieldState::Set(theirs)) => ours.merge(theirs),
+                (Some(_), _) => {}
+                (None, theirs) => {
+                    self.fields.insert(name.clone(), theirs.clone());
+                }
+            }
+        }
+    }
+
+    /// Stamp of the last edit of a plain field.
+    pub fn stamp(&self, field: &str) -> Option<&Stamp> {
+        match self.fields.get(field)? {
+            FieldState::Register(register) => Some(&register.stamp),
+            FieldState::Set(_) => None,
+        }
+    }
+
+    /// State of a field.
+    pub fn field(&self, field: &str) -> Option<&FieldState> {
+        self.fields.get(field)
+    }
+}
+
+impl<T: DeserializeOwned> Mergeable<T> {
+    /// The merged record.
+    pub fn value(&self) -> Result<T, WellAllyError> {
+        let mut object = Map::new();
+        for (name, state) in &self.fields {
+            let value = match state {
+                FieldState::Register(register) => register.value.clone(),
+                FieldState::Set(set) if set.values().is_empty() => Value::Null,
+                FieldState::Set(set) => Value::Array(set.values().into_iter().cloned().collect()),
+            };
+            if !value.is_null() {
+                object.insert(name.clone(), value);
+            }
+        }
+        error::from_value(&type_name::<T>(), &Value::Object(object))
+    }
+}
+
+fn to_object<T: Serialize>(record: &T) -> Result<Map<String, Value>, WellAllyError> {
+    let conversion = |message: String| WellAllyError::Conversion {
+        resource_type: type_name::<T>(),
+        path: String::new(),
+        value: None,
+        message,
+    };
+    match serde_json::to_value(record).map_err(|err| conversion(err.to_string()))? {
+        Value::Object(object) => Ok(object),
+        _ => Err(conversion("a mergeable record must serialize to an object".to_string())),
+    }
+}
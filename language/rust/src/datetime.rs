@@ -0,0 +1,457 @@
+//! Partial and lenient dates and date-times.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//!
+//! Source systems send `"2023"`, `"2023-05"`, `"2023/05/12"`, date-times
+//! without a timezone or with a space instead of `T`. [`FlexibleDate`] and
+//! [`FlexibleDateTime`] accept all of these, remember how precise the value
+//! was and serialize it back in the canonical ISO 8601 form of that precision
+//! (`"2023-05"`, `"2023-05-12T08:30:00+08:00"`, `"2023-05-12T08:30:00"`).
+//!
+//! Date-times keep the offset the facility reported them in. Services that
+//! prefer normalized timestamps call [`set_timestamp_style`] once at startup
+//! to serialize every date-time with a known offset in UTC instead.
+
+use core::fmt;
+use core::str::FromStr;
+use core::sync::atomic::{AtomicU8, Ordering};
+
+use chrono::{DateTime, Datelike, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, Offset, TimeZone, Timelike, Utc};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// How much of a date is known.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DatePrecision {
+    /// Only the year (`"2023"`)
+    Year,
+    /// Year and month (`"2023-05"`)
+    Month,
+    /// Full calendar date (`"2023-05-12"`)
+    Day,
+    /// Date and time of day (date-times only)
+    Time,
+}
+
+/// Error returned when a date string cannot be read even leniently.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseDateError(&'static str);
+
+impl fmt::Display for ParseDateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseDateError {}
+
+/// How [`FlexibleDateTime`] values are serialized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimestampStyle {
+    /// Keep the offset the value was reported in (`"2023-05-12T08:30:00+08:00"`)
+    #[default]
+    Original,
+    /// Convert values with a known offset to UTC (`"2023-05-12T00:30:00Z"`)
+    Utc,
+}
+
+static TIMESTAMP_STYLE: AtomicU8 = AtomicU8::new(0);
+
+/// Sets how every [`FlexibleDateTime`] in the process is serialized.
+pub fn set_timestamp_style(style: TimestampStyle) {
+    TIMESTAMP_STYLE.store(style as u8, Ordering::Relaxed);
+}
+
+/// Current process-wide [`TimestampStyle`].
+pub fn timestamp_style() -> TimestampStyle {
+    match TIMESTAMP_STYLE.load(Ordering::Relaxed) {
+        0 => TimestampStyle::Original,
+        _ => TimestampStyle::Utc,
+    }
+}
+
+/// A calendar date that may be known only to the year or month.
+///
+/// The date is stored as the first day of the period it covers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FlexibleDate {
+    date: NaiveDate,
+    precision: DatePrecision,
+}
+
+impl FlexibleDate {
+    /// A year-only date.
+    pub fn from_year(year: i32) -> Option<Self> {
+        Some(Self { date: NaiveDate::from_ymd_opt(year, 1, 1)?, precision: DatePrecision::Year })
+    }
+
+    /// A year-and-month date.
+    pub fn from_year_month(year: i32, month: u32) -> Option<Self> {
+        Some(Self { date: NaiveDate::from_ymd_opt(year, month, 1)?, precision: DatePrecision::Month })
+    }
+
+    /// First day of the period the value covers.
+    pub fn date(&self) -> NaiveDate {
+        self.date
+    }
+
+    /// Last day of the period the value covers.
+    pub fn last_date(&self) -> NaiveDate {
+        let next = match self.precision {
+            DatePrecision::Year => self.date.with_year(self.date.year() + 1),
+            DatePrecision::Month => self.date.checked_add_months(chrono::Months::new(1)),
+            DatePrecision::Day | DatePrecision::Time => return self.date,
+        };
+        next.and_then(|next| next.pred_opt()).unwrap_or(self.date)
+    }
+
+    /// How much of the date is known.
+    pub fn precision(&self) -> DatePrecision {
+        self.precision
+    }
+
+    /// Year of the date.
+    pub fn year(&self) -> i32 {
+        self.date.year()
+    }
+}
+
+impl From<NaiveDate> for FlexibleDate {
+    fn from(date: NaiveDate) -> Self {
+        Self { date, precision: DatePrecision::Day }
+    }
+}
+
+impl fmt::Display for FlexibleDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_date(f, self.date, self.precision)
+    }
+}
+
+impl FromStr for FlexibleDate {
+    type Err = ParseDateError;
+
+    /// Accepts `YYYY`, `YYYY-MM`, `YYYY-MM-DD` and `YYYYMMDD` with `-`, `/` or
+    /// `.` separators; a trailing time of day is ignored.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        let date_part = value.split(['T', 't', ' ']).next().unwrap_or(value);
+        let (date, precision) = parse_date(date_part)?;
+        Ok(Self { date, precision })
+    }
+}
+
+/// A date-time that keeps the offset it was reported in, or none if the
+/// source sent local time without a timezone, and may be only a date.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FlexibleDateTime {
+    local: NaiveDateTime,
+    offset: Option<FixedOffset>,
+    precision: DatePrecision,
+}
+
+impl FlexibleDateTime {
+    /// Wall-clock time as reported by the source (start of the period for partial values).
+    pub fn local(&self) -> NaiveDateTime {
+        self.local
+    }
+
+    /// UTC offset the value was reported in; `None` for floating local times.
+    pub fn offset(&self) -> Option<FixedOffset> {
+        self.offset
+    }
+
+    /// How much of the date-time is known.
+    pub fn precision(&self) -> DatePrecision {
+        self.precision
+    }
+
+    /// Instant in UTC, treating a value without offset as UTC.
+    pub fn to_utc(&self) -> DateTime<Utc> {
+        let offset = self.offset.unwrap_or(Utc.fix());
+        Utc.from_utc_datetime(&(self.local - offset))
+    }
+
+    /// The same instant expressed in UTC; values without offset or time of day are unchanged.
+    pub fn in_utc(&self) -> Self {
+        match self.offset {
+            Some(offset) if self.precision == DatePrecision::Time && offset.local_minus_utc() != 0 => {
+                Self::from(self.to_utc())
+            }
+            _ => *self,
+        }
+    }
+
+    /// The value with its original offset, treating a value without offset as UTC.
+    pub fn to_fixed_offset(&self) -> DateTime<FixedOffset> {
+        self.to_utc().with_timezone(&self.offset.unwrap_or(Utc.fix()))
+    }
+}
+
+impl From<DateTime<Utc>> for FlexibleDateTime {
+    fn from(value: DateTime<Utc>) -> Self {
+        Self { local: value.naive_utc(), offset: Some(Utc.fix()), precision: DatePrecision::Time }
+    }
+}
+
+impl From<DateTime<FixedOffset>> for FlexibleDateTime {
+    fn from(value: DateTime<FixedOffset>) -> Self {
+        Self { local: value.naive_local(), offset: Some(*value.offset()), precision: DatePrecision::Time }
+    }
+}
+
+impl From<FlexibleDateTime> for DateTime<Utc> {
+    fn from(value: FlexibleDateTime) -> Self {
+        value.to_utc()
+    }
+}
+
+impl PartialEq<DateTime<Utc>> for FlexibleDateTime {
+    fn eq(&self, other: &DateTime<Utc>) -> bool {
+        self.to_utc() == *other
+    }
+}
+
+impl fmt::Display for FlexibleDateTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_date(f, self.local.date(), self.precision)?;
+        if self.precision != DatePrecision::Time {
+            return Ok(());
+        }
+        let time = self.local.time();
+        write!(f, "T{:02}:{:02}:{:02}", time.hour(), time.minute(), time.second())?;
+        let nanos = time.nanosecond() % 1_000_000_000;
+        if nanos > 0 && nanos.is_multiple_of(1_000_000) {
+            write!(f, ".{:03}", nanos / 1_000_000)?;
+        } else if nanos > 0 && nanos.is_multiple_of(1_000) {
+            write!(f, ".{:06}", nanos / 1_000)?;
+        } else if nanos > 0 {
+            write!(f, ".{:09}", nanos)?;
+        }
+        match self.offset.map(|offset| offset.local_minus_utc()) {
+            None => Ok(()),
+            Some(0) => f.write_str("Z"),
+            Some(seconds) => {
+                let sign = if seconds < 0 { '-' } else { '+' };
+                let minutes = seconds.abs() / 60;
+                write!(f, "{}{:02}:{:02}", sign, minutes / 60, minutes % 60)
+            }
+        }
+    }
+}
+
+impl FromStr for FlexibleDateTime {
+    type Err = ParseDateError;
+
+    /// Accepts anything [`FlexibleDate`] does, optionally followed by `T` or a
+    /// space, `HH:MM[:SS[.fraction]]` and `Z` or an offset (`+08:00`, `+0800`, `+08`).
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        let (date_part, time_part) = match value.find(['T', 't', ' ']) {
+            Some(split) => (&value[..split], Some(value[split + 1..].trim())),
+            None => (value, None),
+        };
+        let (date, precision) = parse_date(date_part)?;
+        let Some(time_part) = time_part else {
+            return Ok(Self { local: date.and_time(NaiveTime::MIN), offset: None, precision });
+        };
+        if precision != DatePrecision::Day {
+            return Err(ParseDateError("a time of day needs a full date"));
+        }
+        let (time, offset) = split_offset(time_part)?;
+        let time = NaiveTime::parse_from_str(time, "%H:%M:%S%.f")
+            .or_else(|_| NaiveTime::parse_from_str(time, "%H:%M"))
+            .map_err(|_| ParseDateError("invalid time of day"))?;
+        Ok(Self { local: date.and_time(time), offset, precision: DatePrecision::Time })
+    }
+}
+
+fn write_date(f: &mut fmt::Formatter<'_>, date: NaiveDate, precision: DatePrecision) -> fmt::Result {
+    match precision {
+        DatePrecision::Year => write!(f, "{:04}", date.year()),
+        DatePrecision::Month => write!(f, "{:04}-{:02}", date.year(), date.month()),
+        DatePrecision::Day | DatePrecision::Time => {
+            write!(f, "{:04}-{:02}-{:02}", date.year(), date.month(), date.day())
+        }
+    }
+}
+
+fn number(part: &str) -> Result<u32, ParseDateError> {
+    if part.is_empty() || !part.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(ParseDateError("invalid date"));
+    }
+    part.parse().map_err(|_| ParseDateError("invalid date"))
+}
+
+fn parse_date(value: &str) -> Result<(NaiveDate, DatePrecision), ParseDateError> {
+    let invalid = ParseDateError("invalid date");
+    if value.len() == 8 && value.bytes().all(|b| b.is_ascii_digit()) {
+        let (year, month, day) = (number(&value[..4])?, number(&value[4..6])?, number(&value[6..])?);
+        let date = NaiveDate::from_ymd_opt(year as i32, month, day).ok_or(invalid)?;
+        return Ok((date, DatePrecision::Day));
+    }
+    let mut parts = value.split(['-', '/', '.']);
+    let year = parts.next().filter(|y| y.len() == 4).ok_or(invalid)?;
+    let year = number(year)? as i32;
+    let month = parts.next().map(number).transpose()?;
+    let day = parts.next().map(number).transpose()?;
+    if parts.next().is_some() {
+        return Err(invalid);
+    }
+    let (date, precision) = match (month, day) {
+        (None, _) => (NaiveDate::from_ymd_opt(year, 1, 1), DatePrecision::Year),
+        (Some(month), None) => (NaiveDate::from_ymd_opt(year, month, 1), DatePrecision::Month),
+        (Some(month), Some(day)) => (NaiveDate::from_ymd_opt(year, month, day), DatePrecision::Day),
+    };
+    Ok((date.ok_or(invalid)?, precision))
+}
+
+/// Splits `08:30:00+08:00` into the time and its offset.
+fn split_offset(value: &str) -> Result<(&str, Option<FixedOffset>), ParseDateError> {
+    let invalid = ParseDateError("invalid UTC offset");
+    if let Some(time) = value.strip_suffix(['Z', 'z']) {
+        return Ok((time, Some(Utc.fix())));
+    }
+    let Some(split) = value.rfind(['+', '-']) else {
+        return Ok((value, None));
+    };
+    let (time, offset) = value.split_at(split);
+    let sign = if offset.starts_with('-') { -1 } else { 1 };
+    let digits: &str = &offset[1..];
+    if !digits.bytes().all(|b| b.is_ascii_digit() || b == b':') {
+        return Err(invalid);
+    }
+    let (hours, minutes) = match digits.len() {
+        2 => (number(digits)?, 0),
+        4 => (number(&digits[..2])?, number(&digits[2..])?),
+        5 if digits.as_bytes()[2] == b':' => (number(&digits[..2])?, number(&digits[3..])?),
+        _ => return Err(invalid),
+    };
+    let seconds = sign * (hours * 3600 + minutes * 60) as i32;
+    Ok((time.trim_end(), Some(FixedOffset::east_opt(seconds).ok_or(invalid)?)))
+}
+
+impl Serialize for FlexibleDate {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Follows the process-wide [`TimestampStyle`].
+impl Serialize for FlexibleDateTime {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match timestamp_style() {
+            TimestampStyle::Original => serializer.collect_str(self),
+            TimestampStyle::Utc => serializer.collect_str(&self.in_utc()),
+        }
+    }
+}
+
+macro_rules! lenient_deserialize {
+    ($($ty:ty => $expecting:literal),* $(,)?) => {$(
+        impl<'de> Deserialize<'de> for $ty {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                struct Visitor;
+
+                impl serde::de::Visitor<'_> for Visitor {
+                    type Value = $ty;
+
+                    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                        f.write_str($expecting)
+                    }
+
+                    fn visit_str<E: serde::de::Error>(self, value: &str) -> Result<$ty, E> {
+                        value.parse().map_err(E::custom)
+                    }
+                }
+
+                deserializer.deserialize_str(Visitor)
+            }
+        }
+    )*};
+}
+
+lenient_deserialize!(
+    FlexibleDate => "a full or partial date such as \"2023\", \"2023-05\" or \"2023-05-12\"",
+    FlexibleDateTime => "a date or date-time such as \"2023-05-12T08:30:00Z\"",
+);
+
+#[cfg(feature = "async-graphql")]
+async_graphql::scalar!(FlexibleDate, "FlexibleDate", "Full or partial ISO 8601 date (YYYY, YYYY-MM or YYYY-MM-DD)");
+
+#[cfg(feature = "async-graphql")]
+async_graphql::scalar!(
+    FlexibleDateTime,
+    "FlexibleDateTime",
+    "ISO 8601 date-time, possibly without offset, or a partial date"
+);
+
+#[cfg(feature = "schemars")]
+mod json_schema {
+    use alloc::borrow::Cow;
+
+    use schemars::{json_schema, JsonSchema, Schema, SchemaGenerator};
+
+    impl JsonSchema for super::FlexibleDate {
+        fn inline_schema() -> bool {
+            true
+        }
+
+        fn schema_name() -> Cow<'static, str> {
+            "FlexibleDate".into()
+        }
+
+        fn json_schema(_: &mut SchemaGenerator) -> Schema {
+            json_schema!({
+                "type": "string",
+                "pattern": "^\\d{4}(-\\d{2}(-\\d{2})?)?$"
+            })
+        }
+    }
+
+    impl JsonSchema for super::FlexibleDateTime {
+        fn inline_schema() -> bool {
+            true
+        }
+
+        fn schema_name() -> Cow<'static, str> {
+            "FlexibleDateTime".into()
+        }
+
+        fn json_schema(_: &mut SchemaGenerator) -> Schema {
+            json_schema!({
+                "type": "string",
+                "pattern": "^\\d{4}(-\\d{2}(-\\d{2}(T\\d{2}:\\d{2}:\\d{2}(\\.\\d+)?(Z|[+-]\\d{2}:\\d{2})?)?)?)?$"
+            })
+        }
+    }
+}
+
+/// String schemas so that enums holding dates can derive `ToSchema`.
+#[cfg(feature = "utoipa")]
+mod openapi_schema {
+    use utoipa::openapi::schema::{ObjectBuilder, Schema, Type};
+    use utoipa::openapi::RefOr;
+    use utoipa::{PartialSchema, ToSchema};
+
+    fn string(pattern: &str) -> RefOr<Schema> {
+        RefOr::T(Schema::Object(ObjectBuilder::new().schema_type(Type::String).pattern(Some(pattern)).build()))
+    }
+
+    impl PartialSchema for super::FlexibleDate {
+        fn schema() -> RefOr<Schema> {
+            string("^\\d{4}(-\\d{2}(-\\d{2})?)?$")
+        }
+    }
+
+    impl ToSchema for super::FlexibleDate {}
+
+    impl PartialSchema for super::FlexibleDateTime {
+        fn schema() -> RefOr<Schema> {
+            string("^\\d{4}(-\\d{2}(-\\d{2}(T\\d{2}:\\d{2}:\\d{2}(\\.\\d+)?(Z|[+-]\\d{2}:\\d{2})?)?)?)?$")
+        }
+    }
+
+    impl ToSchema for super::FlexibleDateTime {}
+}
@@ -0,0 +1,156 @@
+//! Pharmacy dispense model.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//! Schema: https://wellall.health/schemas/dispense/v0.1.0
+//!
+//! A [`MedicationRecord`](crate::MedicationRecord) is what the clinician
+//! prescribed; a [`DispenseRecord`] is one fill of it at a pharmacy: how much
+//! was handed out, for how many days, which fill it was and how many refills
+//! remain. Medication-management apps project refill reminders from it with
+//! [`DispenseRecord::refill_due_dates`].
+//!
+//! ```
+//! use wellally::dispense::DispenseRecord;
+//! use wellally::{Quantity, Reference};
+//!
+//! let prescription = Reference { reference: "MedicationRecord/med-1".into(), display: None };
+//! let tablets = Quantity::new(30.0, "{tbl}");
+//! let mut fill = DispenseRecord::new("rx-1", "p-1", prescription, "2024-03-01".parse().unwrap(), tablets, 30);
+//! fill.refills_remaining = 2;
+//! let due: Vec<String> = fill.refill_due_dates().iter().map(|date| date.to_string()).collect();
+//! assert_eq!(due, ["2024-03-31", "2024-04-30"]);
+//! ```
+
+use alloc::{string::String, vec::Vec};
+
+use chrono::{Days, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::common::{Annotation, Coding, Quantity, Reference};
+use crate::extension::Extension;
+
+/// Largest days supply a fill may cover (the three-digit NCPDP field)
+pub const MAX_DAYS_SUPPLY: u32 = 999;
+/// Most refills a prescription may authorize (the two-digit NCPDP field)
+pub const MAX_REFILLS: u32 = 99;
+
+/// One fill of a prescription.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "DispenseRecordInput")
+)]
+pub struct DispenseRecord {
+    /// Unique record identifier
+    pub id: String,
+    /// Reference to Person.id
+    #[serde(rename = "patientId", alias = "patient_id")]
+    pub patient_id: String,
+    /// Prescription filled (e.g., "MedicationRecord/med-1")
+    pub prescription: Reference,
+    /// Product handed out when it differs from the prescription, as after a
+    /// generic substitution (RxNorm)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub medication: Option<Coding>,
+    /// Day the medication was handed out
+    #[serde(rename = "dispensedOn", alias = "dispensed_on")]
+    pub dispensed_on: NaiveDate,
+    /// Amount handed out (e.g., 30 {tbl}, 150 mL)
+    pub quantity: Quantity,
+    /// Days the amount lasts at the prescribed dose
+    #[serde(rename = "daysSupply", alias = "days_supply")]
+    pub days_supply: u32,
+    /// Pharmacy that filled it (e.g., "Organization/pharmacy-3")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pharmacy: Option<Reference>,
+    /// 1 for the original fill, 2 for the first refill, and so on
+    #[serde(rename = "fillNumber", alias = "fill_number")]
+    pub fill_number: u32,
+    /// Refills still authorized after this fill
+    #[serde(rename = "refillsRemaining", alias = "refills_remaining")]
+    pub refills_remaining: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notes: Option<Vec<Annotation>>,
+    /// Schema version the resource was written against (e.g., "0.1.0"); absent means 0.1.0
+    #[serde(rename = "schemaVersion", alias = "schema_version", skip_serializing_if = "Option::is_none")]
+    pub schema_version: Option<String>,
+    /// Confidentiality and sensitivity labels (e.g., v3 ActCode HIV); see [`crate::security`]
+    #[serde(rename = "securityLabels", alias = "security_labels", skip_serializing_if = "Option::is_none")]
+    pub security_labels: Option<Vec<Coding>>,
+    /// Site-specific extensions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extensions: Option<Vec<Extension>>,
+    /// Properties this model version does not define, kept for re-serialization
+    #[cfg(feature = "preserve-unknown")]
+    #[serde(flatten)]
+    #[cfg_attr(feature = "schemars", schemars(skip))]
+    #[cfg_attr(feature = "utoipa", schema(ignore))]
+    #[cfg_attr(feature = "async-graphql", graphql(skip))]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl DispenseRecord {
+    /// Original fill with no refills authorized.
+    pub fn new(
+        id: impl Into<String>,
+        patient_id: impl Into<String>,
+        prescription: Reference,
+        dispensed_on: NaiveDate,
+        quantity: Quantity,
+        days_supply: u32,
+    ) -> Self {
+        DispenseRecord {
+            id: id.into(),
+            patient_id: patient_id.into(),
+            prescription,
+            medication: None,
+            dispensed_on,
+            quantity,
+            days_supply,
+            pharmacy: None,
+            fill_number: 1,
+            refills_remaining: 0,
+            notes: None,
+            schema_version: None,
+            security_labels: None,
+            extensions: None,
+            #[cfg(feature = "preserve-unknown")]
+            extra: Default::default(),
+        }
+    }
+
+    /// Last day covered by this fill; `None` past the end of the calendar.
+    pub fn supply_end(&self) -> Option<NaiveDate> {
+        self.dispensed_on.checked_add_days(Days::new(u64::from(self.days_supply.saturating_sub(1))))
+    }
+
+    /// Day the next fill is due; `None` when no refills remain.
+    pub fn next_refill_due(&self) -> Option<NaiveDate> {
+        self.refill_due_dates().into_iter().next()
+    }
+
+    /// Due dates of the remaining refills, each the day after the previous
+    /// supply runs out. Stops at the first date past the end of the calendar.
+    pub fn refill_due_dates(&self) -> Vec<NaiveDate> {
+        (1..=u64::from(self.refills_remaining))
+            .map_while(|refill| self.dispensed_on.checked_add_days(Days::new(refill * u64::from(self.days_supply))))
+            .collect()
+    }
+
+    /// Whether this fill uses up the prescription.
+    pub fn is_final_fill(&self) -> bool {
+        self.refills_remaining == 0
+    }
+}
+
+/// Fills of `prescription` among `records`, in fill order.
+pub fn fills_of<'a>(records: &'a [DispenseRecord], prescription: &Reference) -> Vec<&'a DispenseRecord> {
+    let mut fills: Vec<_> =
+        records.iter().filter(|record| record.prescription.reference == prescription.reference).collect();
+    fills.sort_by_key(|record| (record.fill_number, record.dispensed_on));
+    fills
+}
@@ -0,0 +1,233 @@
+//! Electrocardiogram (ECG) data model.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//! Schema: https://wellall.health/schemas/ecg/v0.1.0
+//!
+//! An [`EcgRecord`] holds one recording, from a single-lead smartwatch strip
+//! to a clinical 12-lead ECG: the sampling rate, each lead's waveform, the
+//! measured intervals and the rhythm interpretation. A lead's samples are
+//! stored as a compact array of integers scaled by `factor` microvolts, or
+//! referenced as an attachment (e.g., a DICOM or HL7 aECG file) when the
+//! waveform lives elsewhere.
+
+use alloc::{string::String, vec::Vec};
+use serde::{Deserialize, Serialize};
+
+use crate::catalog::SNOMED_CT;
+use crate::common::{CodeableConcept, DataSource, HasDataSource, Reference, Coding};
+use crate::datetime::FlexibleDateTime;
+use crate::extension::Extension;
+use crate::imaging_report::Attachment;
+
+/// SNOMED CT sinus rhythm
+pub const SINUS_RHYTHM: &str = "426783006";
+/// SNOMED CT atrial fibrillation
+pub const ATRIAL_FIBRILLATION: &str = "49436004";
+
+/// ECG lead
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "async-graphql", derive(async_graphql::Enum))]
+pub enum EcgLeadName {
+    I,
+    II,
+    III,
+    #[serde(rename = "aVR")]
+    AVR,
+    #[serde(rename = "aVL")]
+    AVL,
+    #[serde(rename = "aVF")]
+    AVF,
+    V1,
+    V2,
+    V3,
+    V4,
+    V5,
+    V6,
+}
+
+/// Waveform of one lead.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "EcgLeadInput")
+)]
+pub struct EcgLead {
+    /// Which lead
+    pub lead: EcgLeadName,
+    /// Samples at the record's sampling rate, in units of `factor`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<Vec<i32>>,
+    /// Microvolts per unit of `data`; absent means 1
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub factor: Option<f64>,
+    /// Waveform file when the samples are not inline
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attachment: Option<Attachment>,
+}
+
+impl EcgLead {
+    /// Lead with inline samples in microvolts.
+    pub fn microvolts(lead: EcgLeadName, data: Vec<i32>) -> Self {
+        EcgLead { lead, data: Some(data), factor: None, attachment: None }
+    }
+
+    /// Inline samples in millivolts.
+    pub fn millivolts(&self) -> Option<Vec<f64>> {
+        let factor = self.factor.unwrap_or(1.0) / 1000.0;
+        Some(self.data.as_ref()?.iter().map(|sample| f64::from(*sample) * factor).collect())
+    }
+}
+
+/// Measured heart rate and intervals.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "EcgIntervalsInput")
+)]
+pub struct EcgIntervals {
+    /// Ventricular rate in beats per minute
+    #[serde(rename = "heartRate", alias = "heart_rate", skip_serializing_if = "Option::is_none")]
+    pub heart_rate: Option<f64>,
+    /// PR interval in ms
+    #[serde(rename = "prInterval", alias = "pr_interval", skip_serializing_if = "Option::is_none")]
+    pub pr_interval: Option<f64>,
+    /// QRS duration in ms
+    #[serde(rename = "qrsDuration", alias = "qrs_duration", skip_serializing_if = "Option::is_none")]
+    pub qrs_duration: Option<f64>,
+    /// QT interval in ms
+    #[serde(rename = "qtInterval", alias = "qt_interval", skip_serializing_if = "Option::is_none")]
+    pub qt_interval: Option<f64>,
+    /// Heart-rate corrected QT interval in ms, as reported by the device
+    #[serde(rename = "qtcInterval", alias = "qtc_interval", skip_serializing_if = "Option::is_none")]
+    pub qtc_interval: Option<f64>,
+}
+
+impl EcgIntervals {
+    /// RR interval in seconds, from the heart rate.
+    pub fn rr_seconds(&self) -> Option<f64> {
+        self.heart_rate.filter(|rate| *rate > 0.0).map(|rate| 60.0 / rate)
+    }
+
+    /// QTc by Bazett (QT / √RR) in ms.
+    #[cfg(feature = "std")]
+    pub fn qtc_bazett(&self) -> Option<f64> {
+        Some(self.qt_interval? / self.rr_seconds()?.sqrt())
+    }
+
+    /// QTc by Fridericia (QT / ∛RR) in ms, more accurate at high and low rates.
+    #[cfg(feature = "std")]
+    pub fn qtc_fridericia(&self) -> Option<f64> {
+        Some(self.qt_interval? / self.rr_seconds()?.cbrt())
+    }
+}
+
+/// An ECG recording with its measurements and interpretation.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "EcgRecordInput")
+)]
+pub struct EcgRecord {
+    /// Unique recording identifier
+    pub id: String,
+    /// Reference to Person.id
+    #[serde(rename = "patientId", alias = "patient_id")]
+    pub patient_id: String,
+    /// When the recording started
+    #[serde(rename = "recordedAt", alias = "recorded_at")]
+    #[cfg_attr(feature = "utoipa", schema(value_type = String))]
+    pub recorded_at: FlexibleDateTime,
+    /// Recording device (e.g., "Device/watch-1", "Device/ecg-cart-3")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device: Option<Reference>,
+    /// Device, app and capture method of the recording
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<DataSource>,
+    /// Samples per second of every lead
+    #[serde(rename = "samplingRate", alias = "sampling_rate")]
+    pub sampling_rate: f64,
+    /// Recorded leads: one for a smartwatch, twelve for a clinical ECG
+    pub leads: Vec<EcgLead>,
+    /// Measured heart rate and intervals
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub intervals: Option<EcgIntervals>,
+    /// Rhythm and findings (e.g., SNOMED CT sinus rhythm, atrial fibrillation)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub interpretation: Option<Vec<CodeableConcept>>,
+    /// Free-text conclusion of the reader
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conclusion: Option<String>,
+    /// Schema version the resource was written against (e.g., "0.1.0"); absent means 0.1.0
+    #[serde(rename = "schemaVersion", alias = "schema_version", skip_serializing_if = "Option::is_none")]
+    pub schema_version: Option<String>,
+    /// Confidentiality and sensitivity labels (e.g., v3 ActCode HIV); see [`crate::security`]
+    #[serde(rename = "securityLabels", alias = "security_labels", skip_serializing_if = "Option::is_none")]
+    pub security_labels: Option<Vec<Coding>>,
+    /// Site-specific extensions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extensions: Option<Vec<Extension>>,
+    /// Properties this model version does not define, kept for re-serialization
+    #[cfg(feature = "preserve-unknown")]
+    #[serde(flatten)]
+    #[cfg_attr(feature = "schemars", schemars(skip))]
+    #[cfg_attr(feature = "utoipa", schema(ignore))]
+    #[cfg_attr(feature = "async-graphql", graphql(skip))]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl EcgRecord {
+    /// Recording without leads yet.
+    pub fn new(id: impl Into<String>, patient_id: impl Into<String>, recorded_at: FlexibleDateTime, sampling_rate: f64) -> Self {
+        EcgRecord {
+            id: id.into(),
+            patient_id: patient_id.into(),
+            recorded_at,
+            device: None,
+            source: None,
+            sampling_rate,
+            leads: Vec::new(),
+            intervals: None,
+            interpretation: None,
+            conclusion: None,
+            schema_version: None,
+            security_labels: None,
+            extensions: None,
+            #[cfg(feature = "preserve-unknown")]
+            extra: Default::default(),
+        }
+    }
+
+    /// The recording of `lead`, if present.
+    pub fn lead(&self, lead: EcgLeadName) -> Option<&EcgLead> {
+        self.leads.iter().find(|recorded| recorded.lead == lead)
+    }
+
+    /// Length of the longest inline lead in seconds.
+    pub fn duration_seconds(&self) -> Option<f64> {
+        let samples = self.leads.iter().filter_map(|lead| lead.data.as_ref().map(Vec::len)).max()?;
+        (self.sampling_rate > 0.0).then(|| samples as f64 / self.sampling_rate)
+    }
+
+    /// Whether the interpretation includes the SNOMED CT finding `code`.
+    pub fn has_finding(&self, code: &str) -> bool {
+        self.interpretation.iter().flatten().flat_map(|concept| &concept.coding).any(|coding| coding.system == SNOMED_CT && coding.code == code)
+    }
+}
+
+impl HasDataSource for EcgRecord {
+    fn data_source(&self) -> Option<&DataSource> {
+        self.source.as_ref()
+    }
+}
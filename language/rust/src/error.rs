@@ -0,0 +1,319 @@
+//! Crate-wide error type.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//!
+//! Every [`WellAllyError`] names the resource (or type) involved, the path of
+//! the offending field in JSON names (e.g. `results[1].value.unit`, the same
+//! form as [`ValidationIssue::path`]) and, when there is one, the offending
+//! value, so the message can be acted on by whoever has to fix the data.
+//!
+//! ```
+//! use wellally::{LabReport, Resource, WellAllyError};
+//!
+//! let json = r#"{"id":"lab-1","patientId":"p-1","issuedAt":"2024-11-02T09:30:00Z",
+//!     "results":[{"code":{"coding":[]},"value":{"value":5.1,"unit":"mmol/L"},"interpretation":"X"}]}"#;
+//! let err = LabReport::from_json(json).unwrap_err();
+//! assert!(matches!(err, WellAllyError::Parse { .. }));
+//! assert_eq!(err.path(), "results[0].interpretation");
+//! assert_eq!(err.value(), Some(&serde_json::json!("X")));
+//! ```
+
+use alloc::{boxed::Box, format, string::{String, ToString}, vec::Vec};
+use core::str::FromStr;
+
+use serde::de::{DeserializeOwned, IntoDeserializer};
+use serde_json::Value;
+use serde_path_to_error::Segment;
+use thiserror::Error;
+
+use crate::common::{ContactSystem, ContactUse, NameUse};
+use crate::family_health::{FamilyHealthTree, RelationToProband, Sex};
+use crate::health::Gender;
+use crate::lab_report::Interpretation;
+use crate::resource::Resource;
+use crate::validation::ValidationIssue;
+
+/// Result type using [`WellAllyError`].
+pub type Result<T, E = WellAllyError> = core::result::Result<T, E>;
+
+/// Error raised while reading, checking or converting WellAlly data.
+#[derive(Debug, Clone, PartialEq, Error)]
+#[non_exhaustive]
+pub enum WellAllyError {
+    /// The input is not JSON or does not have the shape of the type.
+    #[error("{resource_type}{}: {message}", at(.path))]
+    Parse {
+        /// Resource or type being read (e.g., "LabReport")
+        resource_type: String,
+        /// Path of the offending field; empty for the document itself
+        path: String,
+        /// Offending JSON value, if the document could be read
+        value: Option<Box<Value>>,
+        /// Description of the problem
+        message: String,
+    },
+    /// The value breaks a constraint of its schema.
+    #[error("{resource_type}{}: {message}{}", at(.path), got(.value))]
+    Validation {
+        /// Resource type being validated
+        resource_type: String,
+        /// Path of the offending field
+        path: String,
+        /// Offending JSON value
+        value: Option<Box<Value>>,
+        /// Description of the problem
+        message: String,
+    },
+    /// A value cannot be converted between representations.
+    #[error("{resource_type}{}: {message}{}", at(.path), got(.value))]
+    Conversion {
+        /// Resource or type being converted
+        resource_type: String,
+        /// Path of the offending field
+        path: String,
+        /// Offending JSON value
+        value: Option<Box<Value>>,
+        /// Description of the problem
+        message: String,
+    },
+    /// A code is not part of the terminology the field is bound to.
+    #[error("{resource_type}{}: {message} in {system}{}", at(.path), got(.value))]
+    Terminology {
+        /// Resource or type holding the code
+        resource_type: String,
+        /// Path of the offending field
+        path: String,
+        /// URI of the code system or value set
+        system: String,
+        /// Offending code
+        value: Option<Box<Value>>,
+        /// Description of the problem
+        message: String,
+    },
+}
+
+fn at(path: &str) -> String {
+    if path.is_empty() {
+        String::new()
+    } else {
+        format!(" at {}", path)
+    }
+}
+
+/// Longest rendering of an offending value kept in messages.
+const MAX_SHOWN: usize = 80;
+
+fn got(value: &Option<Box<Value>>) -> String {
+    let Some(value) = value else {
+        return String::new();
+    };
+    let shown = value.to_string();
+    if shown.chars().count() <= MAX_SHOWN {
+        format!(" (got {})", shown)
+    } else {
+        format!(" (got {}...)", shown.chars().take(MAX_SHOWN - 3).collect::<String>())
+    }
+}
+
+impl WellAllyError {
+    /// Error for a validation issue of `resource`, whose JSON form supplies the offending value.
+    pub fn validation(resource_type: &str, issue: &ValidationIssue, resource: Option<&Value>) -> Self {
+        WellAllyError::Validation {
+            resource_type: resource_type.to_string(),
+            path: issue.path.clone(),
+            value: resource.and_then(|resource| lookup(resource, &issue.path)).cloned().map(Box::new),
+            message: issue.message.clone(),
+        }
+    }
+
+    /// Resource or type the error is about.
+    pub fn resource_type(&self) -> &str {
+        match self {
+            WellAllyError::Parse { resource_type, .. }
+            | WellAllyError::Validation { resource_type, .. }
+            | WellAllyError::Conversion { resource_type, .. }
+            | WellAllyError::Terminology { resource_type, .. } => resource_type,
+        }
+    }
+
+    /// Path of the offending field in JSON names; empty for the whole value.
+    pub fn path(&self) -> &str {
+        match self {
+            WellAllyError::Parse { path, .. }
+            | WellAllyError::Validation { path, .. }
+            | WellAllyError::Conversion { path, .. }
+            | WellAllyError::Terminology { path, .. } => path,
+        }
+    }
+
+    /// Offending value, when known.
+    pub fn value(&self) -> Option<&Value> {
+        match self {
+            WellAllyError::Parse { value, .. }
+            | WellAllyError::Validation { value, .. }
+            | WellAllyError::Conversion { value, .. }
+            | WellAllyError::Terminology { value, .. } => value.as_deref(),
+        }
+    }
+
+    /// Description of the problem, without resource type, path or value.
+    pub fn message(&self) -> &str {
+        match self {
+            WellAllyError::Parse { message, .. }
+            | WellAllyError::Validation { message, .. }
+            | WellAllyError::Conversion { message, .. }
+            | WellAllyError::Terminology { message, .. } => message,
+        }
+    }
+}
+
+/// Value at a path such as `results[0].code.coding` inside `root`.
+pub(crate) fn lookup<'v>(root: &'v Value, path: &str) -> Option<&'v Value> {
+    let mut current = root;
+    for part in path.split('.').filter(|part| !part.is_empty()) {
+        let (field, indices) = match part.split_once('[') {
+            Some((field, indices)) => (field, Some(indices)),
+            None => (part, None),
+        };
+        if !field.is_empty() {
+            current = current.get(field)?;
+        }
+        for index in indices.into_iter().flat_map(|indices| indices.split('[')) {
+            current = current.get(index.trim_end_matches(']').parse::<usize>().ok()?)?;
+        }
+    }
+    Some(current)
+}
+
+/// Type name without module paths (`Vec<LabReport>` rather than `alloc::vec::Vec<wellally::...>`).
+pub(crate) fn type_name<T: ?Sized>() -> String {
+    let mut name = String::new();
+    let mut segment_start = 0;
+    let mut rest = core::any::type_name::<T>();
+    while let Some(c) = rest.chars().next() {
+        if rest.starts_with("::") {
+            name.truncate(segment_start);
+            rest = &rest[2..];
+            continue;
+        }
+        name.push(c);
+        if !(c.is_alphanumeric() || c == '_') {
+            segment_start = name.len();
+        }
+        rest = &rest[c.len_utf8()..];
+    }
+    name
+}
+
+fn parse_error(
+    resource_type: &str,
+    document: Option<&Value>,
+    err: serde_path_to_error::Error<serde_json::Error>,
+) -> WellAllyError {
+    let mut path = String::new();
+    for segment in err.path().iter() {
+        match segment {
+            Segment::Seq { index } => path.push_str(&format!("[{}]", index)),
+            Segment::Map { key } | Segment::Enum { variant: key } => {
+                if !path.is_empty() {
+                    path.push('.');
+                }
+                path.push_str(key);
+            }
+            Segment::Unknown => break,
+        }
+    }
+    let err = err.into_inner();
+    let message = err.to_string();
+    let missing = message
+        .strip_prefix("missing field `")
+        .and_then(|rest| rest.split_once('`'))
+        .map(|(field, _)| field);
+    let value = match missing {
+        Some(field) => {
+            path = if path.is_empty() { field.to_string() } else { format!("{}.{}", path, field) };
+            None
+        }
+        None if err.is_syntax() || err.is_eof() => None,
+        None => document.and_then(|document| lookup(document, &path)).cloned().map(Box::new),
+    };
+    WellAllyError::Parse { resource_type: resource_type.to_string(), path, value, message }
+}
+
+/// Parses JSON text, reporting failures with the path of the offending field.
+pub(crate) fn from_str<T: DeserializeOwned>(resource_type: &str, json: &str) -> Result<T> {
+    let mut deserializer = serde_json::Deserializer::from_str(json);
+    let value = serde_path_to_error::deserialize(&mut deserializer).map_err(|err| {
+        let document = serde_json::from_str::<Value>(json).ok();
+        parse_error(resource_type, document.as_ref(), err)
+    })?;
+    deserializer.end().map_err(|err| WellAllyError::Parse {
+        resource_type: resource_type.to_string(),
+        path: String::new(),
+        value: None,
+        message: err.to_string(),
+    })?;
+    Ok(value)
+}
+
+/// Deserializes a JSON value, reporting failures with the path of the offending field.
+pub(crate) fn from_value<T: DeserializeOwned>(resource_type: &str, value: &Value) -> Result<T> {
+    serde_path_to_error::deserialize(value).map_err(|err| parse_error(resource_type, Some(value), err))
+}
+
+/// Every validation issue of `resource` as a [`WellAllyError::Validation`].
+pub(crate) fn validation_errors<R: Resource>(resource: &R, issues: Vec<ValidationIssue>) -> Vec<WellAllyError> {
+    if issues.is_empty() {
+        return Vec::new();
+    }
+    let document = serde_json::to_value(resource).ok();
+    issues.iter().map(|issue| WellAllyError::validation(R::RESOURCE_TYPE, issue, document.as_ref())).collect()
+}
+
+/// A status change the resource lifecycle does not allow, reported against `status`.
+pub(crate) fn transition_error<S: serde::Serialize>(resource_type: &str, from: S, to: S) -> WellAllyError {
+    let code = |status: &S| serde_json::to_value(status).unwrap_or_default();
+    WellAllyError::Validation {
+        resource_type: resource_type.to_string(),
+        path: "status".to_string(),
+        value: Some(Box::new(code(&to))),
+        message: format!("cannot change status from {} to {}", code(&from), code(&to)),
+    }
+}
+
+fn parse_code<T: DeserializeOwned>(code: &str, system: &str) -> Result<T> {
+    let deserializer: serde::de::value::StrDeserializer<'_, serde::de::value::Error> = code.into_deserializer();
+    T::deserialize(deserializer).map_err(|err| WellAllyError::Terminology {
+        resource_type: type_name::<T>(),
+        path: String::new(),
+        system: system.to_string(),
+        value: Some(Box::new(Value::from(code))),
+        message: err.to_string().replace("unknown variant", "unknown code"),
+    })
+}
+
+/// `FromStr` for coded enums, parsing their JSON code.
+macro_rules! code_from_str {
+    ($($ty:ty => $system:expr,)*) => {
+        $(
+            impl FromStr for $ty {
+                type Err = WellAllyError;
+
+                fn from_str(code: &str) -> Result<Self> {
+                    parse_code(code, $system)
+                }
+            }
+        )*
+    };
+}
+
+code_from_str! {
+    Interpretation => "http://terminology.hl7.org/CodeSystem/v3-ObservationInterpretation",
+    Gender => "http://hl7.org/fhir/administrative-gender",
+    Sex => "http://hl7.org/fhir/administrative-gender",
+    NameUse => "http://hl7.org/fhir/name-use",
+    ContactSystem => "http://hl7.org/fhir/contact-point-system",
+    ContactUse => "http://hl7.org/fhir/contact-point-use",
+    RelationToProband => FamilyHealthTree::SCHEMA_ID,
+}
@@ -0,0 +1,197 @@
+//! Event envelope for streaming record changes.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//!
+//! A [`RecordEvent`] announces that a resource was created, updated or
+//! deleted, for pipelines that move records between systems over Kafka,
+//! NATS or similar brokers. Producers serialize it with
+//! [`RecordEvent::to_canonical_json`], so equal events give equal bytes, and
+//! key messages with [`RecordEvent::key`], so the changes of one record stay
+//! in order on one partition.
+//!
+//! The envelope is versioned on its own, independently of the resource
+//! schemas: `eventVersion` follows [`EVENT_VERSION`] and is registered as
+//! [`SUBJECT`] with the schema at [`SCHEMA_ID`]. Minor versions only add
+//! optional fields, so consumers ignore unknown fields and
+//! [`RecordEvent::from_json`] rejects only newer major versions.
+//!
+//! ```
+//! use wellally::events::{EventType, RecordEvent};
+//! use wellally::io::AnyResource;
+//!
+//! let task: AnyResource = serde_json::from_value(serde_json::json!({
+//!     "id": "task-1", "patientId": "p-1", "status": "requested", "description": "Repeat CBC"
+//! }))
+//! .unwrap();
+//! let event = RecordEvent::created(task).with_trace_id("4bf92f3577b34da6a3ce929d0e0e4736");
+//! assert_eq!(event.key(), "Task/task-1");
+//!
+//! let received = RecordEvent::from_json(&event.to_canonical_json()?)?;
+//! assert_eq!(received.event_type, EventType::Created);
+//! assert_eq!(received, event);
+//! # Ok::<(), wellally::WellAllyError>(())
+//! ```
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::conformance;
+use crate::error::{self, WellAllyError};
+use crate::io::{self, AnyResource};
+use crate::migrations::SchemaVersion;
+
+/// Version of the envelope this crate writes
+pub const EVENT_VERSION: SchemaVersion = SchemaVersion::new(1, 0, 0);
+
+/// URI of the published JSON Schema of the envelope
+pub const SCHEMA_ID: &str = "https://wellall.health/schemas/record-event/v1.0.0";
+
+/// Schema-registry subject of the envelope
+pub const SUBJECT: &str = "tech.wellally.RecordEvent";
+
+/// What happened to the record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EventType {
+    Created,
+    Updated,
+    Deleted,
+}
+
+/// A change of one resource.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordEvent {
+    /// Version of the envelope
+    pub event_version: SchemaVersion,
+    /// What happened to the record
+    pub event_type: EventType,
+    /// Resource type name (e.g., "LabReport")
+    pub resource_type: String,
+    /// Resource id
+    pub id: String,
+    /// The resource after the change; `None` for a delete
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resource: Option<AnyResource>,
+    /// When the change happened
+    pub occurred_at: DateTime<Utc>,
+    /// Trace the change belongs to (e.g., a W3C trace id), for correlating
+    /// it across services
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trace_id: Option<String>,
+}
+
+/// Envelope as read, before its resource is parsed with the declared type.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawEvent {
+    event_version: SchemaVersion,
+    event_type: EventType,
+    resource_type: String,
+    id: String,
+    #[serde(default)]
+    resource: Option<Value>,
+    occurred_at: DateTime<Utc>,
+    #[serde(default)]
+    trace_id: Option<String>,
+}
+
+impl RecordEvent {
+    /// Event announcing that `resource` was created, occurring now.
+    pub fn created(resource: AnyResource) -> Self {
+        Self::changed(EventType::Created, resource)
+    }
+
+    /// Event announcing that `resource` was updated, occurring now.
+    pub fn updated(resource: AnyResource) -> Self {
+        Self::changed(EventType::Updated, resource)
+    }
+
+    /// Event announcing that a resource was deleted, occurring now.
+    pub fn deleted(resource_type: impl Into<String>, id: impl Into<String>) -> Self {
+        RecordEvent {
+            event_version: EVENT_VERSION,
+            event_type: EventType::Deleted,
+            resource_type: resource_type.into(),
+            id: id.into(),
+            resource: None,
+            occurred_at: Utc::now(),
+            trace_id: None,
+        }
+    }
+
+    fn changed(event_type: EventType, resource: AnyResource) -> Self {
+        RecordEvent {
+            event_type,
+            resource_type: resource.resource_type().to_string(),
+            id: resource.id().to_string(),
+            resource: Some(resource),
+            ..Self::deleted("", "")
+        }
+    }
+
+    /// Sets when the change happened.
+    pub fn with_occurred_at(mut self, occurred_at: DateTime<Utc>) -> Self {
+        self.occurred_at = occurred_at;
+        self
+    }
+
+    /// Sets the trace the change belongs to.
+    pub fn with_trace_id(mut self, trace_id: impl Into<String>) -> Self {
+        self.trace_id = Some(trace_id.into());
+        self
+    }
+
+    /// Message key: `Type/id` of the record.
+    pub fn key(&self) -> String {
+        format!("{}/{}", self.resource_type, self.id)
+    }
+
+    /// Canonical JSON text: sorted keys, compact, integral numbers without
+    /// fraction (see [`conformance::canonical`]).
+    pub fn to_canonical_json(&self) -> Result<String, WellAllyError> {
+        let value = serde_json::to_value(self).map_err(|err| event_error("", None, err.to_string()))?;
+        Ok(conformance::canonical(&value))
+    }
+
+    /// Parses an event, rejecting envelopes of a newer major version and
+    /// resources that are not of the declared type or do not have the declared id.
+    pub fn from_json(json: &str) -> Result<Self, WellAllyError> {
+        let raw: RawEvent = error::from_str("RecordEvent", json)?;
+        if raw.event_version.major > EVENT_VERSION.major {
+            let version = Some(raw.event_version.to_string().into());
+            return Err(event_error("eventVersion", version, format!("newer than supported {}", EVENT_VERSION)));
+        }
+        let resource = match (raw.event_type, raw.resource) {
+            (_, Some(value)) => {
+                let resource = AnyResource::from_typed_json_value(&raw.resource_type, &value);
+                Some(resource.map_err(|err| io::located(err, "resource"))?)
+            }
+            (EventType::Deleted, None) => None,
+            (_, None) => return Err(event_error("resource", None, "is required unless the record was deleted")),
+        };
+        if let Some(resource) = resource.as_ref().filter(|resource| resource.id() != raw.id) {
+            return Err(event_error("id", Some(raw.id.into()), format!("does not match {}", resource.id())));
+        }
+        Ok(RecordEvent {
+            event_version: raw.event_version,
+            event_type: raw.event_type,
+            resource_type: raw.resource_type,
+            id: raw.id,
+            resource,
+            occurred_at: raw.occurred_at,
+            trace_id: raw.trace_id,
+        })
+    }
+}
+
+fn event_error(path: &str, value: Option<Value>, message: impl Into<String>) -> WellAllyError {
+    WellAllyError::Parse {
+        resource_type: "RecordEvent".to_string(),
+        path: path.to_string(),
+        value: value.map(Into::into),
+        message: message.into(),
+    }
+}
@@ -0,0 +1,495 @@
+//! FHIRPath-like expressions over resources.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//!
+//! An [`Expression`] selects and tests values of a resource's canonical JSON
+//! (field names as serialized, e.g. `patientId`), so mappings, rules and
+//! profile constraints can be written as configuration instead of Rust. As
+//! in FHIRPath, every expression evaluates to a collection: a field step
+//! collects the field of each input item, arrays are flattened and absent
+//! fields yield nothing.
+//!
+//! The language is a small subset of FHIRPath:
+//!
+//! - paths: `results.code.coding`, indexes `results[0]`, `$this` for the
+//!   current item, and names in backticks for fields such as CSV columns
+//!   (`` `Test Name` ``);
+//! - literals: `'text'`, `4.5`, `-1`, `true`, `false`;
+//! - comparisons `=`, `!=`, `<`, `<=`, `>`, `>=`, true when any value on the
+//!   left compares so with any value on the right;
+//! - `and`, `or` and parentheses;
+//! - functions `where(criteria)`, `select(projection)`, `exists()`,
+//!   `exists(criteria)`, `all(criteria)`, `empty()`, `count()`, `first()`,
+//!   `last()` and `not()`.
+//!
+//! A collection counts as true when it holds a single `true`, or any values
+//! other than a single `false`.
+//!
+//! ```
+//! use wellally::expr::Expression;
+//! use wellally::LabReport;
+//!
+//! let report: LabReport = serde_json::from_value(serde_json::json!({
+//!     "id": "lab-1", "patientId": "p-1", "issuedAt": "2024-11-02T09:30:00Z",
+//!     "results": [
+//!         {"code": {"coding": [{"system": "http://loinc.org", "code": "2345-7"}]},
+//!          "value": {"value": 97, "unit": "mg/dL"}},
+//!         {"code": {"coding": [{"system": "http://loinc.org", "code": "4548-4"}]},
+//!          "value": {"value": 6.1, "unit": "%"}}
+//!     ]
+//! }))
+//! .unwrap();
+//!
+//! let hba1c = Expression::parse("results.where(code.coding.code = '4548-4').value.value")?;
+//! assert_eq!(hba1c.evaluate(&report)?, [serde_json::json!(6.1)]);
+//!
+//! let diabetic = Expression::parse("results.exists(code.coding.code = '4548-4' and value.value >= 6.5)")?;
+//! assert!(!diabetic.matches(&report)?);
+//! # Ok::<(), wellally::WellAllyError>(())
+//! ```
+
+use alloc::{boxed::Box, format, string::{String, ToString}, vec, vec::Vec};
+use core::cmp::Ordering;
+use core::fmt;
+use core::str::FromStr;
+
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+
+use crate::error::{type_name, WellAllyError};
+
+/// A parsed expression; serializes as its source text.
+#[derive(Debug, Clone)]
+pub struct Expression {
+    source: String,
+    root: Node,
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    /// The current item (`$this`), or the resource at the top level
+    This,
+    Literal(Value),
+    Field(Box<Node>, String),
+    Index(Box<Node>, usize),
+    Call(Box<Node>, Function, Option<Box<Node>>),
+    Compare(Box<Node>, Comparison, Box<Node>),
+    And(Box<Node>, Box<Node>),
+    Or(Box<Node>, Box<Node>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Function {
+    Where,
+    Select,
+    Exists,
+    All,
+    Empty,
+    Count,
+    First,
+    Last,
+    Not,
+}
+
+impl Function {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "where" => Function::Where,
+            "select" => Function::Select,
+            "exists" => Function::Exists,
+            "all" => Function::All,
+            "empty" => Function::Empty,
+            "count" => Function::Count,
+            "first" => Function::First,
+            "last" => Function::Last,
+            "not" => Function::Not,
+            _ => return None,
+        })
+    }
+
+    /// Whether the function takes an argument: required, optional or none.
+    fn arity(self) -> (bool, bool) {
+        match self {
+            Function::Where | Function::Select | Function::All => (true, true),
+            Function::Exists => (false, true),
+            _ => (false, false),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Comparison {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Expression {
+    /// Parses `source`; syntax errors name the offending position.
+    pub fn parse(source: &str) -> Result<Self, WellAllyError> {
+        let tokens = lex(source)?;
+        let mut parser = Parser { source, tokens, next: 0 };
+        let root = parser.or()?;
+        match parser.tokens.get(parser.next) {
+            Some((at, _)) => Err(syntax(source, *at, "unexpected input")),
+            None => Ok(Expression { source: source.to_string(), root }),
+        }
+    }
+
+    /// Source text of the expression.
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// Values the expression selects from `resource`.
+    pub fn evaluate<T: Serialize + ?Sized>(&self, resource: &T) -> Result<Vec<Value>, WellAllyError> {
+        let value = serde_json::to_value(resource).map_err(|err| WellAllyError::Conversion {
+            resource_type: type_name::<T>(),
+            path: String::new(),
+            value: None,
+            message: err.to_string(),
+        })?;
+        Ok(self.evaluate_value(&value))
+    }
+
+    /// Values the expression selects from a JSON value.
+    pub fn evaluate_value(&self, value: &Value) -> Vec<Value> {
+        eval(&self.root, core::slice::from_ref(value))
+    }
+
+    /// Whether the expression is true for `resource`.
+    pub fn matches<T: Serialize + ?Sized>(&self, resource: &T) -> Result<bool, WellAllyError> {
+        Ok(truthy(&self.evaluate(resource)?))
+    }
+
+    /// Whether the expression is true for a JSON value.
+    pub fn matches_value(&self, value: &Value) -> bool {
+        truthy(&self.evaluate_value(value))
+    }
+}
+
+/// Parses `expression` and evaluates it against `resource`.
+pub fn evaluate<T: Serialize + ?Sized>(expression: &str, resource: &T) -> Result<Vec<Value>, WellAllyError> {
+    Expression::parse(expression)?.evaluate(resource)
+}
+
+impl PartialEq for Expression {
+    fn eq(&self, other: &Self) -> bool {
+        self.source == other.source
+    }
+}
+
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+impl FromStr for Expression {
+    type Err = WellAllyError;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        Expression::parse(source)
+    }
+}
+
+impl Serialize for Expression {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.source)
+    }
+}
+
+impl<'de> Deserialize<'de> for Expression {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let source = String::deserialize(deserializer)?;
+        Expression::parse(&source).map_err(D::Error::custom)
+    }
+}
+
+fn eval(node: &Node, focus: &[Value]) -> Vec<Value> {
+    match node {
+        Node::This => focus.to_vec(),
+        Node::Literal(value) => vec![value.clone()],
+        Node::Field(base, name) => {
+            let mut values = Vec::new();
+            for item in eval(base, focus) {
+                match item.get(name) {
+                    Some(Value::Array(items)) => values.extend(items.iter().filter(|item| !item.is_null()).cloned()),
+                    Some(Value::Null) | None => {}
+                    Some(value) => values.push(value.clone()),
+                }
+            }
+            values
+        }
+        Node::Index(base, index) => eval(base, focus).into_iter().nth(*index).into_iter().collect(),
+        Node::Call(base, function, argument) => call(*function, eval(base, focus), argument.as_deref()),
+        Node::Compare(left, comparison, right) => {
+            let (left, right) = (eval(left, focus), eval(right, focus));
+            let holds = left.iter().any(|a| right.iter().any(|b| compare(a, *comparison, b)));
+            vec![Value::Bool(holds)]
+        }
+        Node::And(left, right) => vec![Value::Bool(truthy(&eval(left, focus)) && truthy(&eval(right, focus)))],
+        Node::Or(left, right) => vec![Value::Bool(truthy(&eval(left, focus)) || truthy(&eval(right, focus)))],
+    }
+}
+
+fn call(function: Function, input: Vec<Value>, argument: Option<&Node>) -> Vec<Value> {
+    let holds = |item: &Value| argument.is_none_or(|argument| truthy(&eval(argument, core::slice::from_ref(item))));
+    match function {
+        Function::Where => input.into_iter().filter(|item| holds(item)).collect(),
+        Function::Select => match argument {
+            Some(argument) => input.iter().flat_map(|item| eval(argument, core::slice::from_ref(item))).collect(),
+            None => input,
+        },
+        Function::Exists => vec![Value::Bool(input.iter().any(holds))],
+        Function::All => vec![Value::Bool(input.iter().all(holds))],
+        Function::Empty => vec![Value::Bool(input.is_empty())],
+        Function::Count => vec![Value::from(input.len())],
+        Function::First => input.into_iter().take(1).collect(),
+        Function::Last => input.into_iter().last().into_iter().collect(),
+        Function::Not => vec![Value::Bool(!truthy(&input))],
+    }
+}
+
+fn compare(a: &Value, comparison: Comparison, b: &Value) -> bool {
+    let ordering = match (a, b) {
+        (Value::Number(a), Value::Number(b)) => a.as_f64().partial_cmp(&b.as_f64()),
+        (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+        (Value::Bool(a), Value::Bool(b)) => Some(a.cmp(b)),
+        _ => None,
+    };
+    match comparison {
+        Comparison::Eq => ordering.map_or(a == b, Ordering::is_eq),
+        Comparison::Ne => ordering.map_or(a != b, Ordering::is_ne),
+        Comparison::Lt => ordering.is_some_and(Ordering::is_lt),
+        Comparison::Le => ordering.is_some_and(Ordering::is_le),
+        Comparison::Gt => ordering.is_some_and(Ordering::is_gt),
+        Comparison::Ge => ordering.is_some_and(Ordering::is_ge),
+    }
+}
+
+fn truthy(values: &[Value]) -> bool {
+    match values {
+        [] => false,
+        [Value::Bool(value)] => *value,
+        _ => true,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Identifier(String),
+    Variable(String),
+    String(String),
+    Number(serde_json::Number),
+    Symbol(&'static str),
+}
+
+const SYMBOLS: [&str; 13] = ["!=", "<=", ">=", "=", "<", ">", ".", "(", ")", "[", "]", ",", "-"];
+
+fn lex(source: &str) -> Result<Vec<(usize, Token)>, WellAllyError> {
+    let mut tokens = Vec::new();
+    let mut rest = source.char_indices().peekable();
+    while let Some(&(at, c)) = rest.peek() {
+        let tail = &source[at..];
+        if c.is_whitespace() {
+            rest.next();
+        } else if c == '\'' || c == '`' {
+            rest.next();
+            let mut text = String::new();
+            loop {
+                match rest.next() {
+                    Some((_, end)) if end == c => break,
+                    Some((_, '\\')) => match rest.next() {
+                        Some((_, 'n')) => text.push('\n'),
+                        Some((_, 't')) => text.push('\t'),
+                        Some((_, escaped)) => text.push(escaped),
+                        None => return Err(syntax(source, at, "unterminated string")),
+                    },
+                    Some((_, c)) => text.push(c),
+                    None => return Err(syntax(source, at, "unterminated string")),
+                }
+            }
+            tokens.push((at, if c == '`' { Token::Identifier(text) } else { Token::String(text) }));
+        } else if c.is_ascii_digit() {
+            let len = tail.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(tail.len());
+            let number = tail[..len].parse::<Value>().ok().and_then(|number| match number {
+                Value::Number(number) => Some(number),
+                _ => None,
+            });
+            let number = number.ok_or_else(|| syntax(source, at, "invalid number"))?;
+            tokens.push((at, Token::Number(number)));
+            rest.nth(len - 1);
+        } else if c.is_alphabetic() || c == '_' || c == '$' {
+            let first = c.len_utf8();
+            let len =
+                tail[first..].find(|c: char| !c.is_alphanumeric() && c != '_').map_or(tail.len(), |len| len + first);
+            let name = tail[..len].to_string();
+            tokens.push((at, if c == '$' { Token::Variable(name) } else { Token::Identifier(name) }));
+            while rest.next_if(|&(i, _)| i < at + len).is_some() {}
+        } else {
+            let symbol = SYMBOLS.iter().find(|symbol| tail.starts_with(**symbol));
+            let symbol = symbol.ok_or_else(|| syntax(source, at, &format!("unexpected character '{}'", c)))?;
+            tokens.push((at, Token::Symbol(symbol)));
+            rest.nth(symbol.len() - 1);
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser<'s> {
+    source: &'s str,
+    tokens: Vec<(usize, Token)>,
+    next: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.next).map(|(_, token)| token)
+    }
+
+    fn position(&self) -> usize {
+        self.tokens.get(self.next).map_or(self.source.len(), |(at, _)| *at)
+    }
+
+    fn eat(&mut self, expected: &Token) -> bool {
+        let found = self.peek() == Some(expected);
+        if found {
+            self.next += 1;
+        }
+        found
+    }
+
+    fn expect(&mut self, symbol: &'static str) -> Result<(), WellAllyError> {
+        if self.eat(&Token::Symbol(symbol)) {
+            Ok(())
+        } else {
+            Err(syntax(self.source, self.position(), &format!("expected '{}'", symbol)))
+        }
+    }
+
+    fn keyword(&mut self, keyword: &str) -> bool {
+        self.eat(&Token::Identifier(keyword.to_string()))
+    }
+
+    fn or(&mut self) -> Result<Node, WellAllyError> {
+        let mut node = self.and()?;
+        while self.keyword("or") {
+            node = Node::Or(Box::new(node), Box::new(self.and()?));
+        }
+        Ok(node)
+    }
+
+    fn and(&mut self) -> Result<Node, WellAllyError> {
+        let mut node = self.comparison()?;
+        while self.keyword("and") {
+            node = Node::And(Box::new(node), Box::new(self.comparison()?));
+        }
+        Ok(node)
+    }
+
+    fn comparison(&mut self) -> Result<Node, WellAllyError> {
+        let left = self.path()?;
+        let comparison = match self.peek() {
+            Some(Token::Symbol("=")) => Comparison::Eq,
+            Some(Token::Symbol("!=")) => Comparison::Ne,
+            Some(Token::Symbol("<")) => Comparison::Lt,
+            Some(Token::Symbol("<=")) => Comparison::Le,
+            Some(Token::Symbol(">")) => Comparison::Gt,
+            Some(Token::Symbol(">=")) => Comparison::Ge,
+            _ => return Ok(left),
+        };
+        self.next += 1;
+        Ok(Node::Compare(Box::new(left), comparison, Box::new(self.path()?)))
+    }
+
+    fn path(&mut self) -> Result<Node, WellAllyError> {
+        let mut node = self.primary()?;
+        loop {
+            if self.eat(&Token::Symbol(".")) {
+                node = self.step(node)?;
+            } else if self.eat(&Token::Symbol("[")) {
+                let at = self.position();
+                let index = match self.tokens.get(self.next) {
+                    Some((_, Token::Number(number))) => number.as_u64().and_then(|index| usize::try_from(index).ok()),
+                    _ => None,
+                };
+                let index = index.ok_or_else(|| syntax(self.source, at, "expected an index"))?;
+                self.next += 1;
+                self.expect("]")?;
+                node = Node::Index(Box::new(node), index);
+            } else {
+                return Ok(node);
+            }
+        }
+    }
+
+    fn primary(&mut self) -> Result<Node, WellAllyError> {
+        let at = self.position();
+        let negative = self.eat(&Token::Symbol("-"));
+        let token = self.tokens.get(self.next).map(|(_, token)| token.clone());
+        let node = match token {
+            Some(Token::Number(number)) if negative => {
+                let number = number.as_f64().and_then(|number| serde_json::Number::from_f64(-number));
+                Node::Literal(number.map_or(Value::Null, Value::Number))
+            }
+            _ if negative => return Err(syntax(self.source, self.position(), "expected a number")),
+            Some(Token::Number(number)) => Node::Literal(Value::Number(number)),
+            Some(Token::String(text)) => Node::Literal(Value::String(text)),
+            Some(Token::Identifier(name)) if name == "true" || name == "false" => {
+                Node::Literal(Value::Bool(name == "true"))
+            }
+            Some(Token::Variable(name)) if name == "$this" => Node::This,
+            Some(Token::Variable(name)) => return Err(syntax(self.source, at, &format!("unknown variable {}", name))),
+            Some(Token::Identifier(_)) => return self.step(Node::This),
+            Some(Token::Symbol("(")) => {
+                self.next += 1;
+                let node = self.or()?;
+                self.expect(")")?;
+                return Ok(node);
+            }
+            _ => return Err(syntax(self.source, at, "expected a path, literal or '('")),
+        };
+        self.next += 1;
+        Ok(node)
+    }
+
+    /// A field or function step applied to `base`.
+    fn step(&mut self, base: Node) -> Result<Node, WellAllyError> {
+        let at = self.position();
+        let Some(Token::Identifier(name)) = self.peek().cloned() else {
+            return Err(syntax(self.source, at, "expected a field or function name"));
+        };
+        self.next += 1;
+        if !self.eat(&Token::Symbol("(")) {
+            return Ok(Node::Field(Box::new(base), name));
+        }
+        let function = Function::from_name(&name)
+            .ok_or_else(|| syntax(self.source, at, &format!("unknown function {}()", name)))?;
+        let (required, allowed) = function.arity();
+        let argument = if allowed && self.peek() != Some(&Token::Symbol(")")) {
+            Some(Box::new(self.or()?))
+        } else {
+            None
+        };
+        if required && argument.is_none() {
+            return Err(syntax(self.source, at, &format!("{}() takes an expression", name)));
+        }
+        self.expect(")")?;
+        Ok(Node::Call(Box::new(base), function, argument))
+    }
+}
+
+fn syntax(source: &str, at: usize, message: &str) -> WellAllyError {
+    WellAllyError::Parse {
+        resource_type: "Expression".to_string(),
+        path: String::new(),
+        value: Some(Box::new(Value::String(source.to_string()))),
+        message: format!("{} at offset {}", message, at),
+    }
+}
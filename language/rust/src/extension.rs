@@ -0,0 +1,231 @@
+//! Extensions for site-specific data.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//!
+//! Every top-level resource carries an optional `extensions` list of
+//! FHIR-style [`Extension`]s: a URL naming the extension and one typed
+//! `value[x]`, serialized side by side
+//! (`{"url": "https://example.org/ward", "valueString": "4B"}`). Partners
+//! define their own URLs; the models keep the values through parsing and
+//! serialization without knowing them. [`Extensible`] reads and writes them
+//! by URL.
+
+use alloc::{string::String, vec::Vec};
+use serde::{Deserialize, Serialize};
+
+use crate::common::{CodeableConcept, Coding, Quantity, Reference};
+use crate::datetime::{FlexibleDate, FlexibleDateTime};
+
+/// Typed value of an extension
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub enum ExtensionValue {
+    #[serde(rename = "valueBoolean", alias = "value_boolean")]
+    Boolean(bool),
+    #[serde(rename = "valueInteger", alias = "value_integer")]
+    Integer(i64),
+    #[serde(rename = "valueDecimal", alias = "value_decimal")]
+    Decimal(f64),
+    #[serde(rename = "valueString", alias = "value_string")]
+    String(String),
+    /// Code from a value set known to the extension
+    #[serde(rename = "valueCode", alias = "value_code")]
+    Code(String),
+    #[serde(rename = "valueUri", alias = "value_uri")]
+    Uri(String),
+    #[serde(rename = "valueDate", alias = "value_date")]
+    Date(FlexibleDate),
+    #[serde(rename = "valueDateTime", alias = "value_date_time")]
+    DateTime(FlexibleDateTime),
+    #[serde(rename = "valueCoding", alias = "value_coding")]
+    Coding(Coding),
+    #[serde(rename = "valueCodeableConcept", alias = "value_codeable_concept")]
+    CodeableConcept(CodeableConcept),
+    #[serde(rename = "valueQuantity", alias = "value_quantity")]
+    Quantity(Quantity),
+    #[serde(rename = "valueReference", alias = "value_reference")]
+    Reference(Reference),
+}
+
+#[cfg(feature = "async-graphql")]
+async_graphql::scalar!(
+    ExtensionValue,
+    "ExtensionValue",
+    "Typed extension value as JSON, e.g. {\"valueString\": \"4B\"} or {\"valueCoding\": {...}}"
+);
+
+impl ExtensionValue {
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            ExtensionValue::Boolean(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_integer(&self) -> Option<i64> {
+        match self {
+            ExtensionValue::Integer(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Decimal value; integers are widened.
+    pub fn as_decimal(&self) -> Option<f64> {
+        match self {
+            ExtensionValue::Decimal(value) => Some(*value),
+            ExtensionValue::Integer(value) => Some(*value as f64),
+            _ => None,
+        }
+    }
+
+    /// Text of a string, code or URI value.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            ExtensionValue::String(value) | ExtensionValue::Code(value) | ExtensionValue::Uri(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_date(&self) -> Option<FlexibleDate> {
+        match self {
+            ExtensionValue::Date(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_date_time(&self) -> Option<FlexibleDateTime> {
+        match self {
+            ExtensionValue::DateTime(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_coding(&self) -> Option<&Coding> {
+        match self {
+            ExtensionValue::Coding(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_concept(&self) -> Option<&CodeableConcept> {
+        match self {
+            ExtensionValue::CodeableConcept(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_quantity(&self) -> Option<&Quantity> {
+        match self {
+            ExtensionValue::Quantity(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_reference(&self) -> Option<&Reference> {
+        match self {
+            ExtensionValue::Reference(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+macro_rules! extension_value_from {
+    ($($ty:ty => $variant:ident),* $(,)?) => {$(
+        impl From<$ty> for ExtensionValue {
+            fn from(value: $ty) -> Self {
+                ExtensionValue::$variant(value.into())
+            }
+        }
+    )*};
+}
+
+extension_value_from!(
+    bool => Boolean,
+    i64 => Integer,
+    f64 => Decimal,
+    String => String,
+    &str => String,
+    FlexibleDate => Date,
+    FlexibleDateTime => DateTime,
+    Coding => Coding,
+    CodeableConcept => CodeableConcept,
+    Quantity => Quantity,
+    Reference => Reference,
+);
+
+/// Site-specific data attached to a resource.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "ExtensionInput")
+)]
+pub struct Extension {
+    /// URL identifying the extension and the meaning of its value
+    pub url: String,
+    /// Value, serialized as a `value[x]` property next to `url`
+    #[serde(flatten)]
+    pub value: ExtensionValue,
+}
+
+impl Extension {
+    pub fn new(url: impl Into<String>, value: impl Into<ExtensionValue>) -> Self {
+        Self { url: url.into(), value: value.into() }
+    }
+}
+
+/// Access to the extensions of a resource by URL.
+pub trait Extensible {
+    /// Extensions in document order
+    fn all_extensions(&self) -> &[Extension];
+
+    /// The extension list; `None` when the resource has none.
+    fn extensions_mut(&mut self) -> &mut Option<Vec<Extension>>;
+
+    /// Value of the first extension with `url`.
+    fn extension(&self, url: &str) -> Option<&ExtensionValue> {
+        self.all_extensions().iter().find(|extension| extension.url == url).map(|extension| &extension.value)
+    }
+
+    /// Values of all extensions with `url`, for repeating extensions.
+    fn extension_values(&self, url: &str) -> Vec<&ExtensionValue> {
+        self.all_extensions().iter().filter(|extension| extension.url == url).map(|extension| &extension.value).collect()
+    }
+
+    /// Appends an extension, keeping any others with the same URL.
+    fn add_extension(&mut self, url: &str, value: impl Into<ExtensionValue>) {
+        self.extensions_mut().get_or_insert_with(Vec::new).push(Extension::new(url, value));
+    }
+
+    /// Replaces all extensions with `url` by one holding `value`, in the
+    /// position of the first one.
+    fn set_extension(&mut self, url: &str, value: impl Into<ExtensionValue>) {
+        let extensions = self.extensions_mut().get_or_insert_with(Vec::new);
+        match extensions.iter().position(|extension| extension.url == url) {
+            Some(first) => {
+                extensions[first].value = value.into();
+                let mut i = 0;
+                extensions.retain(|extension| {
+                    i += 1;
+                    i - 1 == first || extension.url != url
+                });
+            }
+            None => extensions.push(Extension::new(url, value)),
+        }
+    }
+
+    /// Removes all extensions with `url`, returning their values. An emptied
+    /// list is dropped so the resource serializes as before.
+    fn remove_extension(&mut self, url: &str) -> Vec<ExtensionValue> {
+        let Some(extensions) = self.extensions_mut() else {
+            return Vec::new();
+        };
+        let (removed, kept): (Vec<_>, Vec<_>) = extensions.drain(..).partition(|extension| extension.url == url);
+        *self.extensions_mut() = if kept.is_empty() { None } else { Some(kept) };
+        removed.into_iter().map(|extension| extension.value).collect()
+    }
+}
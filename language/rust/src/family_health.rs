@@ -3,15 +3,31 @@
 //! Package: wellally
 //! Website: https://www.wellally.tech/
 //! Schema: https://wellall.health/schemas/family-health/v0.1.0
+//!
+//! [`FamilyHealthTree::stats`] summarizes a tree without walking it: members
+//! per relation, members affected by each condition with their mean age at
+//! onset, and the generations spanned. [`FamilyHealthTree::anonymized`]
+//! gives a copy of the pedigree that can be shared without identifying its
+//! members, and [`FamilyHealthTree::rebase`] re-roots it on another member
+//! using the parent links. Relatives who have a [`Person`] record of their
+//! own are linked with `personId`; [`FamilyHealthTree::hydrate`] pulls their
+//! recorded conditions into the tree.
 
+use alloc::{collections::BTreeMap, format, string::String, vec::Vec};
 use serde::{Deserialize, Serialize};
-use crate::common::CodeableConcept;
+use crate::common::{Annotation, CodeableConcept, Coding};
+use crate::extension::Extension;
+use crate::health::Person;
 
 /// Relationship to proband
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "async-graphql", derive(async_graphql::Enum))]
 #[serde(rename_all = "lowercase")]
 pub enum RelationToProband {
     #[serde(rename = "self")]
+    #[cfg_attr(feature = "async-graphql", graphql(name = "SELF"))]
     Self_,
     Mother,
     Father,
@@ -25,8 +41,29 @@ pub enum RelationToProband {
     Other,
 }
 
+impl RelationToProband {
+    /// Generation relative to the proband: 1 for parents, aunts and uncles,
+    /// 2 for grandparents, -1 for children; `None` for `Other`.
+    pub fn generation(self) -> Option<i8> {
+        match self {
+            RelationToProband::Self_ | RelationToProband::Sibling | RelationToProband::Cousin => Some(0),
+            RelationToProband::Mother
+            | RelationToProband::Father
+            | RelationToProband::Aunt
+            | RelationToProband::Uncle => Some(1),
+            RelationToProband::Grandparent => Some(2),
+            RelationToProband::Child => Some(-1),
+            RelationToProband::Grandchild => Some(-2),
+            RelationToProband::Other => None,
+        }
+    }
+}
+
 /// Biological sex
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "async-graphql", derive(async_graphql::Enum))]
 #[serde(rename_all = "lowercase")]
 pub enum Sex {
     Male,
@@ -35,19 +72,52 @@ pub enum Sex {
     Unknown,
 }
 
+/// Age at which a member's condition began.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "ConditionOnsetInput")
+)]
+pub struct ConditionOnset {
+    /// Condition, matching a coding in the member's `conditions`
+    pub condition: Coding,
+    /// Age in years when the condition began
+    #[serde(rename = "ageAtOnset", alias = "age_at_onset")]
+    pub age_at_onset: u32,
+}
+
 /// Family member in a health tree.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "FamilyMemberInput")
+)]
 pub struct FamilyMember {
     /// Member identifier
     pub id: String,
+    /// Reference to Person.id, when the relative has a record of their own
+    #[serde(rename = "personId", alias = "person_id", skip_serializing_if = "Option::is_none")]
+    pub person_id: Option<String>,
     /// Relationship to proband
-    #[serde(rename = "relationToProband")]
+    #[serde(rename = "relationToProband", alias = "relation_to_proband")]
     pub relation_to_proband: RelationToProband,
+    /// Member id of the biological mother
+    #[serde(rename = "motherId", alias = "mother_id", skip_serializing_if = "Option::is_none")]
+    pub mother_id: Option<String>,
+    /// Member id of the biological father
+    #[serde(rename = "fatherId", alias = "father_id", skip_serializing_if = "Option::is_none")]
+    pub father_id: Option<String>,
     /// Biological sex
     #[serde(skip_serializing_if = "Option::is_none")]
     pub sex: Option<Sex>,
     /// Year of birth
-    #[serde(rename = "birthYear", skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "birthYear", alias = "birth_year", skip_serializing_if = "Option::is_none")]
     pub birth_year: Option<i32>,
     /// Whether deceased
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -55,14 +125,277 @@ pub struct FamilyMember {
     /// Health conditions (SNOMED CT or ICD-10)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub conditions: Option<Vec<CodeableConcept>>,
+    /// Age at onset of the conditions, where known
+    #[serde(
+        rename = "conditionOnsets",
+        alias = "condition_onsets",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub condition_onsets: Vec<ConditionOnset>,
+    /// Free-text notes and comments
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notes: Option<Vec<Annotation>>,
 }
 
 /// Family health tree for genetic and hereditary disease tracking.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "FamilyHealthTreeInput")
+)]
 pub struct FamilyHealthTree {
     /// ID of the proband (main individual)
-    #[serde(rename = "probandId")]
+    #[serde(rename = "probandId", alias = "proband_id")]
     pub proband_id: String,
     /// List of family members
     pub members: Vec<FamilyMember>,
+    /// Schema version the resource was written against (e.g., "0.1.0"); absent means 0.1.0
+    #[serde(rename = "schemaVersion", alias = "schema_version", skip_serializing_if = "Option::is_none")]
+    pub schema_version: Option<String>,
+    /// Confidentiality and sensitivity labels (e.g., v3 ActCode HIV); see [`crate::security`]
+    #[serde(rename = "securityLabels", alias = "security_labels", skip_serializing_if = "Option::is_none")]
+    pub security_labels: Option<Vec<Coding>>,
+    /// Site-specific extensions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extensions: Option<Vec<Extension>>,
+    /// Properties this model version does not define, kept for re-serialization
+    #[cfg(feature = "preserve-unknown")]
+    #[serde(flatten)]
+    #[cfg_attr(feature = "schemars", schemars(skip))]
+    #[cfg_attr(feature = "utoipa", schema(ignore))]
+    #[cfg_attr(feature = "async-graphql", graphql(skip))]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// Members affected by one condition coding.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConditionStats {
+    pub condition: Coding,
+    /// Members with the condition
+    pub affected: usize,
+    /// Mean age at onset over the members with a recorded onset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mean_onset_age: Option<f64>,
+}
+
+/// Summary of a tree for dashboards and risk screens.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FamilyTreeStats {
+    /// Members, proband included
+    pub members: usize,
+    /// Members per relation to the proband
+    pub by_relation: BTreeMap<RelationToProband, usize>,
+    /// Per condition coding, most affected first
+    pub conditions: Vec<ConditionStats>,
+    /// Mean age at onset over every recorded onset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mean_onset_age: Option<f64>,
+    /// Generations spanned by members of known generation (e.g., 3 from
+    /// grandparents to the proband)
+    pub generations: usize,
+}
+
+impl FamilyHealthTree {
+    /// Counts per relation and condition, mean ages at onset and generation
+    /// depth. A member with a condition coded several ways counts once per
+    /// coding.
+    pub fn stats(&self) -> FamilyTreeStats {
+        let mut by_relation = BTreeMap::new();
+        let mut conditions: Vec<ConditionStats> = Vec::new();
+        for member in &self.members {
+            *by_relation.entry(member.relation_to_proband).or_insert(0) += 1;
+            let mut counted: Vec<&Coding> = Vec::new();
+            for coding in member.conditions.iter().flatten().flat_map(|concept| &concept.coding) {
+                if counted.iter().any(|seen| same_code(seen, coding)) {
+                    continue;
+                }
+                counted.push(coding);
+                match conditions.iter_mut().find(|stats| same_code(&stats.condition, coding)) {
+                    Some(stats) => stats.affected += 1,
+                    None => {
+                        conditions.push(ConditionStats { condition: coding.clone(), affected: 1, mean_onset_age: None })
+                    }
+                }
+            }
+        }
+        let onsets = || self.members.iter().flat_map(|member| &member.condition_onsets);
+        for stats in &mut conditions {
+            let ages = onsets().filter(|onset| same_code(&onset.condition, &stats.condition));
+            stats.mean_onset_age = mean(ages.map(|onset| onset.age_at_onset));
+        }
+        conditions.sort_by_key(|stats| core::cmp::Reverse(stats.affected));
+        let generations: Vec<i8> =
+            self.members.iter().filter_map(|member| member.relation_to_proband.generation()).collect();
+        FamilyTreeStats {
+            members: self.members.len(),
+            by_relation,
+            conditions,
+            mean_onset_age: mean(onsets().map(|onset| onset.age_at_onset)),
+            generations: match (generations.iter().min(), generations.iter().max()) {
+                (Some(lowest), Some(highest)) => usize::from(highest.abs_diff(*lowest)) + 1,
+                _ => 0,
+            },
+        }
+    }
+
+    /// Copy safe to share with researchers or relatives: members are
+    /// renumbered `member-1`, `member-2`, … in tree order (parent links
+    /// follow the new numbers), birth years are
+    /// rounded down to the decade (1965 becomes 1960), and only relations,
+    /// sex and coded conditions are kept; condition text stays only where a
+    /// condition has no coding. Person links, notes, vital status, onset
+    /// ages and extensions are dropped; security labels are kept.
+    pub fn anonymized(&self) -> FamilyHealthTree {
+        let new_id = |i: usize| format!("member-{}", i + 1);
+        let renumbered = |id: &str| self.members.iter().position(|member| member.id == id).map(new_id);
+        let proband = self.members.iter().position(|member| member.id == self.proband_id);
+        let members = self
+            .members
+            .iter()
+            .enumerate()
+            .map(|(i, member)| FamilyMember {
+                id: new_id(i),
+                person_id: None,
+                relation_to_proband: member.relation_to_proband,
+                mother_id: member.mother_id.as_deref().and_then(renumbered),
+                father_id: member.father_id.as_deref().and_then(renumbered),
+                sex: member.sex,
+                birth_year: member.birth_year.map(|year| year - year.rem_euclid(10)),
+                deceased: None,
+                conditions: member.conditions.as_ref().map(|conditions| {
+                    conditions
+                        .iter()
+                        .map(|concept| CodeableConcept {
+                            coding: concept.coding.clone(),
+                            text: concept.text.clone().filter(|_| concept.coding.is_empty()),
+                        })
+                        .collect()
+                }),
+                condition_onsets: Vec::new(),
+                notes: None,
+            })
+            .collect();
+        FamilyHealthTree {
+            proband_id: proband.map_or_else(|| "proband".into(), new_id),
+            members,
+            schema_version: self.schema_version.clone(),
+            security_labels: self.security_labels.clone(),
+            extensions: None,
+            #[cfg(feature = "preserve-unknown")]
+            extra: Default::default(),
+        }
+    }
+
+    /// The tree as seen from member `new_proband_id`, so a relative can
+    /// adopt it as their own: every `relationToProband` is recomputed from
+    /// the `motherId`/`fatherId` links. Members the links do not place keep
+    /// their relation when the new proband is a sibling of the old one
+    /// (taken as a full sibling) and become `Other` otherwise. `None` when
+    /// no member has that id.
+    pub fn rebase(&self, new_proband_id: &str) -> Option<FamilyHealthTree> {
+        let proband = self.members.iter().find(|member| member.id == new_proband_id)?;
+        let previous = proband.relation_to_proband;
+        let proband_parents: Vec<&FamilyMember> = self.parents(proband).collect();
+        let relation = |member: &FamilyMember| {
+            let parents: Vec<&FamilyMember> = self.parents(member).collect();
+            if member.id == proband.id {
+                RelationToProband::Self_
+            } else if proband.mother_id.as_ref() == Some(&member.id) {
+                RelationToProband::Mother
+            } else if proband.father_id.as_ref() == Some(&member.id) {
+                RelationToProband::Father
+            } else if parents.iter().any(|parent| parent.id == proband.id) {
+                RelationToProband::Child
+            } else if is_sibling(member, proband) {
+                RelationToProband::Sibling
+            } else if proband_parents.iter().any(|parent| self.parents(parent).any(|p| p.id == member.id)) {
+                RelationToProband::Grandparent
+            } else if parents.iter().any(|parent| self.parents(parent).any(|p| p.id == proband.id)) {
+                RelationToProband::Grandchild
+            } else if proband_parents.iter().any(|parent| is_sibling(member, parent)) {
+                match member.sex {
+                    Some(Sex::Female) => RelationToProband::Aunt,
+                    Some(Sex::Male) => RelationToProband::Uncle,
+                    _ => RelationToProband::Other,
+                }
+            } else if parents.iter().any(|parent| proband_parents.iter().any(|p| is_sibling(parent, p))) {
+                RelationToProband::Cousin
+            } else {
+                match (previous, member.relation_to_proband) {
+                    (RelationToProband::Self_, relation) => relation,
+                    (RelationToProband::Sibling, RelationToProband::Self_) => RelationToProband::Sibling,
+                    (RelationToProband::Sibling, RelationToProband::Child | RelationToProband::Grandchild) => {
+                        RelationToProband::Other
+                    }
+                    (RelationToProband::Sibling, relation) => relation,
+                    _ => RelationToProband::Other,
+                }
+            }
+        };
+        let mut tree = self.clone();
+        for (member, original) in tree.members.iter_mut().zip(&self.members) {
+            member.relation_to_proband = relation(original);
+        }
+        tree.proband_id = proband.id.clone();
+        Some(tree)
+    }
+
+    /// Adds to each member linked by `personId` the conditions of the
+    /// linked person's clinical summary that the member does not list yet
+    /// (same coding, or same text when uncoded). Conditions already in the
+    /// tree are kept. Returns the number of conditions added.
+    pub fn hydrate(&mut self, persons: &[Person]) -> usize {
+        let mut added = 0;
+        for member in &mut self.members {
+            let Some(person) = member.person_id.as_ref().and_then(|id| persons.iter().find(|p| p.id == *id)) else {
+                continue;
+            };
+            let recorded = person.clinical_summary.as_ref().and_then(|summary| summary.conditions.as_ref());
+            for condition in recorded.into_iter().flatten() {
+                let conditions = member.conditions.get_or_insert_with(Vec::new);
+                if !conditions.iter().any(|known| same_concept(known, condition)) {
+                    conditions.push(condition.clone());
+                    added += 1;
+                }
+            }
+        }
+        added
+    }
+
+    /// Linked parents of `member` that are in the tree.
+    fn parents<'a>(&'a self, member: &'a FamilyMember) -> impl Iterator<Item = &'a FamilyMember> + 'a {
+        [&member.mother_id, &member.father_id]
+            .into_iter()
+            .flatten()
+            .filter_map(|id| self.members.iter().find(|parent| parent.id == *id))
+    }
+}
+
+/// Whether `a` and `b` are different members sharing a linked parent.
+fn is_sibling(a: &FamilyMember, b: &FamilyMember) -> bool {
+    let shared = |x: &Option<String>, y: &Option<String>| x.is_some() && x == y;
+    a.id != b.id && (shared(&a.mother_id, &b.mother_id) || shared(&a.father_id, &b.father_id))
+}
+
+fn same_code(a: &Coding, b: &Coding) -> bool {
+    a.system == b.system && a.code == b.code
+}
+
+fn same_concept(a: &CodeableConcept, b: &CodeableConcept) -> bool {
+    if a.coding.is_empty() || b.coding.is_empty() {
+        let text = |concept: &CodeableConcept| concept.text.as_deref().map(str::trim).map(str::to_lowercase);
+        return a.coding.is_empty() && b.coding.is_empty() && text(a).is_some() && text(a) == text(b);
+    }
+    a.coding.iter().any(|coding| b.coding.iter().any(|other| same_code(coding, other)))
+}
+
+fn mean(values: impl Iterator<Item = u32>) -> Option<f64> {
+    let (count, total) = values.fold((0u32, 0f64), |(count, total), value| (count + 1, total + f64::from(value)));
+    (count > 0).then(|| total / f64::from(count))
 }
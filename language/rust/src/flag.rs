@@ -0,0 +1,223 @@
+//! Clinical alert (flag) data model.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//! Schema: https://wellall.health/schemas/flag/v0.1.0
+//!
+//! A [`Flag`] is an alert about a patient that should be shown to whoever
+//! works with their record: a prescription conflicting with an allergy, a
+//! dose to adjust for kidney function, a critical lab value or a care alert
+//! such as a fall risk. The safety checks and lab reports turn their findings
+//! into flags with [`AllergyAlert::to_flag`], [`RenalAdvisory::to_flag`] and
+//! [`LabReport::critical_flags`].
+
+use alloc::{format, string::String, vec::Vec};
+use serde::{Deserialize, Serialize};
+
+use crate::allergy::{Criticality, ReactionSeverity};
+use crate::catalog::concept;
+use crate::common::{CodeableConcept, Period, Reference, Coding};
+use crate::extension::Extension;
+use crate::lab_report::LabReport;
+use crate::medication::MedicationRecord;
+use crate::resource::Resource;
+use crate::safety::renal::{RenalAction, RenalAdvisory};
+use crate::safety::AllergyAlert;
+
+/// Code system of the alerts WellAlly raises itself
+pub const FLAG_SYSTEM: &str = "https://www.wellally.tech/fhir/CodeSystem/flag";
+
+/// Whether a flag is in effect
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "async-graphql", derive(async_graphql::Enum))]
+#[serde(rename_all = "kebab-case")]
+pub enum FlagStatus {
+    Active,
+    Inactive,
+    /// Raised by mistake
+    EnteredInError,
+}
+
+/// What a flag is about
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "async-graphql", derive(async_graphql::Enum))]
+#[serde(rename_all = "lowercase")]
+pub enum FlagCategory {
+    Clinical,
+    /// Medication and prescribing
+    Drug,
+    Lab,
+    /// Patient safety, e.g., fall risk
+    Safety,
+    Behavioral,
+    Diet,
+    Administrative,
+}
+
+/// How urgently a flag needs attention
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "async-graphql", derive(async_graphql::Enum))]
+#[serde(rename_all = "lowercase")]
+pub enum FlagSeverity {
+    Info,
+    Warning,
+    Critical,
+}
+
+/// An alert about a patient.
+///
+/// Only its `focus` tells a flag apart from other resources, so in a mixed
+/// payload a flag without one is recognized by its `resourceType`, which
+/// `AnyResource` writes.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "FlagInput")
+)]
+pub struct Flag {
+    /// Unique flag identifier
+    pub id: String,
+    /// Reference to Person.id
+    #[serde(rename = "patientId", alias = "patient_id")]
+    pub patient_id: String,
+    /// Whether the flag is in effect
+    pub status: FlagStatus,
+    /// What the flag is about
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<FlagCategory>,
+    /// The alert; its text is the message shown to users
+    pub code: CodeableConcept,
+    /// How urgently the flag needs attention
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub severity: Option<FlagSeverity>,
+    /// When the flag applies
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub period: Option<Period>,
+    /// Who or what raised the flag (e.g., "Practitioner/dr-li", "Device/cds-1")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author: Option<Reference>,
+    /// Resource the flag is about (e.g., "MedicationRecord/med-1")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub focus: Option<Reference>,
+    /// Schema version the resource was written against (e.g., "0.1.0"); absent means 0.1.0
+    #[serde(rename = "schemaVersion", alias = "schema_version", skip_serializing_if = "Option::is_none")]
+    pub schema_version: Option<String>,
+    /// Confidentiality and sensitivity labels (e.g., v3 ActCode HIV); see [`crate::security`]
+    #[serde(rename = "securityLabels", alias = "security_labels", skip_serializing_if = "Option::is_none")]
+    pub security_labels: Option<Vec<Coding>>,
+    /// Site-specific extensions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extensions: Option<Vec<Extension>>,
+    /// Properties this model version does not define, kept for re-serialization
+    #[cfg(feature = "preserve-unknown")]
+    #[serde(flatten)]
+    #[cfg_attr(feature = "schemars", schemars(skip))]
+    #[cfg_attr(feature = "utoipa", schema(ignore))]
+    #[cfg_attr(feature = "async-graphql", graphql(skip))]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Flag {
+    /// Active flag with `code`.
+    pub fn new(id: impl Into<String>, patient_id: impl Into<String>, code: CodeableConcept) -> Self {
+        Flag {
+            id: id.into(),
+            patient_id: patient_id.into(),
+            status: FlagStatus::Active,
+            category: None,
+            code,
+            severity: None,
+            period: None,
+            author: None,
+            focus: None,
+            schema_version: None,
+            security_labels: None,
+            extensions: None,
+            #[cfg(feature = "preserve-unknown")]
+            extra: Default::default(),
+        }
+    }
+
+    /// Whether the flag is in effect.
+    pub fn is_active(&self) -> bool {
+        self.status == FlagStatus::Active
+    }
+
+    /// Message shown to users: the code text, else its first display.
+    pub fn message(&self) -> Option<&str> {
+        self.code.text.as_deref().or_else(|| self.code.coding.iter().find_map(|coding| coding.display.as_deref()))
+    }
+}
+
+fn flag_code(code: &str, display: &str, text: String) -> CodeableConcept {
+    CodeableConcept { text: Some(text), ..concept(FLAG_SYSTEM, code, display) }
+}
+
+fn medication_reference(id: &str) -> Reference {
+    Reference { reference: format!("{}/{}", MedicationRecord::RESOURCE_TYPE, id), display: None }
+}
+
+impl AllergyAlert {
+    /// Drug flag on the prescription, critical when the allergy is of high
+    /// criticality or has caused a severe reaction.
+    pub fn to_flag(&self, id: impl Into<String>) -> Flag {
+        let critical = self.criticality == Some(Criticality::High) || self.worst_reaction == Some(ReactionSeverity::Severe);
+        let text = format!("Prescription conflicts with recorded allergy to {}", self.substance);
+        Flag {
+            category: Some(FlagCategory::Drug),
+            severity: Some(if critical { FlagSeverity::Critical } else { FlagSeverity::Warning }),
+            focus: Some(medication_reference(&self.medication_id)),
+            ..Flag::new(id, self.patient_id.clone(), flag_code("allergy-conflict", "Allergy conflict", text))
+        }
+    }
+}
+
+impl RenalAdvisory {
+    /// Drug flag on the medication: a warning to reduce the dose, critical
+    /// to avoid the drug.
+    pub fn to_flag(&self, id: impl Into<String>) -> Flag {
+        let severity = match self.action {
+            RenalAction::ReduceDose => FlagSeverity::Warning,
+            RenalAction::Avoid => FlagSeverity::Critical,
+        };
+        let text = format!("eGFR {} is below {}: {}", self.egfr, self.below_egfr, self.advice);
+        Flag {
+            category: Some(FlagCategory::Drug),
+            severity: Some(severity),
+            focus: Some(medication_reference(&self.medication_id)),
+            ..Flag::new(id, self.patient_id.clone(), flag_code("renal-dose-adjustment", "Renal dose adjustment", text))
+        }
+    }
+}
+
+impl LabReport {
+    /// A critical lab flag on the report for each result interpreted at a
+    /// critical level, with ids `<report id>-critical-<result index>`.
+    pub fn critical_flags(&self) -> Vec<Flag> {
+        self.results
+            .iter()
+            .enumerate()
+            .filter(|(_, result)| result.interpretation.as_ref().is_some_and(|interpretation| interpretation.is_critical()))
+            .map(|(i, result)| {
+                let name = result.code.text.as_deref().or_else(|| result.code.coding.iter().find_map(|coding| coding.display.as_deref()));
+                let name = name.or_else(|| result.code.coding.first().map(|coding| coding.code.as_str())).unwrap_or("result");
+                let text = format!("Critical {} result", name);
+                Flag {
+                    category: Some(FlagCategory::Lab),
+                    severity: Some(FlagSeverity::Critical),
+                    focus: Some(Reference::to(self)),
+                    ..Flag::new(format!("{}-critical-{}", self.id, i), self.patient_id.clone(), flag_code("critical-lab-value", "Critical lab value", text))
+                }
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,110 @@
+//! Medication formulary and therapeutic substitution.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//!
+//! A formulary is a payer's or pharmacy's list of covered drugs. Each
+//! [`FormularyEntry`] gives the cost-sharing tier of a drug, whether it needs
+//! prior authorization and which drugs the plan prefers instead.
+//! [`suggest_alternatives`] reads a [`MedicationRecord`] against the list so
+//! savings features work on the same medication model as the rest of the
+//! crate.
+//!
+//! ```
+//! use wellally::catalog::{coding, DRUGS, RXNORM};
+//! use wellally::formulary::{self, FormularyEntry};
+//!
+//! let med = DRUGS[0].record("med-1".into(), "p-1".into(), "2024-03-01".parse().unwrap(), 30);
+//! let generic = coding(RXNORM, "999001", "Generic alternative");
+//! let mut brand = FormularyEntry::new(med.medication.clone(), 3);
+//! brand.prior_authorization = true;
+//! brand.alternatives.push(generic.clone());
+//! let list = [brand, FormularyEntry::new(generic, 1)];
+//! let suggested = formulary::suggest_alternatives(&med, &list);
+//! assert_eq!(suggested[0].tier, Some(1));
+//! ```
+
+use alloc::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+
+use crate::common::{CodeableConcept, Coding};
+use crate::medication::MedicationRecord;
+
+/// Coverage of one drug.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FormularyEntry {
+    /// Drug covered (e.g., RxNorm)
+    pub drug: Coding,
+    /// Cost-sharing tier, 1 being the lowest (usually preferred generics);
+    /// absent when the drug is not covered
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tier: Option<u8>,
+    /// Whether the plan must approve the drug before it pays
+    #[serde(
+        rename = "priorAuthorization",
+        alias = "prior_authorization",
+        default,
+        skip_serializing_if = "core::ops::Not::not"
+    )]
+    pub prior_authorization: bool,
+    /// Drugs the plan prefers in its place, most preferred first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub alternatives: Vec<Coding>,
+    /// Therapeutic class (e.g., ATC C10AA HMG CoA reductase inhibitors)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub class: Option<CodeableConcept>,
+}
+
+impl FormularyEntry {
+    /// Covered drug in `tier` without prior authorization.
+    pub fn new(drug: Coding, tier: u8) -> Self {
+        FormularyEntry { drug, tier: Some(tier), prior_authorization: false, alternatives: Vec::new(), class: None }
+    }
+
+    /// Drug the formulary lists as not covered.
+    pub fn excluded(drug: Coding) -> Self {
+        FormularyEntry { drug, tier: None, prior_authorization: false, alternatives: Vec::new(), class: None }
+    }
+
+    /// Whether the plan covers the drug.
+    pub fn is_covered(&self) -> bool {
+        self.tier.is_some()
+    }
+
+    /// Whether this drug costs the patient less than `other` or needs less
+    /// paperwork: covered against uncovered, a lower tier, or the same tier
+    /// without prior authorization.
+    pub fn is_better_than(&self, other: &FormularyEntry) -> bool {
+        match (self.tier, other.tier) {
+            (None, _) => false,
+            (Some(_), None) => true,
+            (Some(tier), Some(other_tier)) => {
+                tier < other_tier || (tier == other_tier && !self.prior_authorization && other.prior_authorization)
+            }
+        }
+    }
+}
+
+/// Entry of `formulary` for `drug`, matched on system and code.
+pub fn entry_for<'a>(formulary: &'a [FormularyEntry], drug: &Coding) -> Option<&'a FormularyEntry> {
+    formulary.iter().find(|entry| entry.drug.system == drug.system && entry.drug.code == drug.code)
+}
+
+/// Preferred alternatives to the drug of `med` that the formulary covers on
+/// better terms, lowest tier first and in the order the formulary prefers
+/// them within a tier. Empty when the drug is not listed or nothing listed
+/// in its place is better.
+pub fn suggest_alternatives<'a>(med: &MedicationRecord, formulary: &'a [FormularyEntry]) -> Vec<&'a FormularyEntry> {
+    let Some(current) = entry_for(formulary, &med.medication) else {
+        return Vec::new();
+    };
+    let mut suggested: Vec<_> = current
+        .alternatives
+        .iter()
+        .filter_map(|alternative| entry_for(formulary, alternative))
+        .filter(|alternative| alternative.is_better_than(current))
+        .collect();
+    suggested.sort_by_key(|alternative| (alternative.tier, alternative.prior_authorization));
+    suggested
+}
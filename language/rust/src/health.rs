@@ -4,12 +4,20 @@
 //! Website: https://www.wellally.tech/
 //! Schema: https://wellall.health/schemas/health/v0.1.0
 
+use alloc::{string::{String, ToString}, vec::Vec};
 use serde::{Deserialize, Serialize};
 use chrono::NaiveDate;
-use crate::common::{Identifier, HumanName, ContactPoint, Address, CodeableConcept};
+use crate::catalog;
+use crate::common::{HasIdentifiers, Identifier, IdentifierType, HumanName, ContactPoint, Address, CodeableConcept, Coding};
+use crate::datetime::{FlexibleDate, FlexibleDateTime};
+use crate::extension::Extension;
+use crate::vitals::Vitals;
 
 /// Gender type
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "async-graphql", derive(async_graphql::Enum))]
 #[serde(rename_all = "lowercase")]
 pub enum Gender {
     Male,
@@ -18,8 +26,121 @@ pub enum Gender {
     Unknown,
 }
 
+/// Marital status codes from HL7 v3 MaritalStatus
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum MaritalStatus {
+    /// Annulled
+    #[serde(rename = "A")]
+    Annulled,
+    /// Divorced
+    #[serde(rename = "D")]
+    Divorced,
+    /// Interlocutory
+    #[serde(rename = "I")]
+    Interlocutory,
+    /// Legally separated
+    #[serde(rename = "L")]
+    LegallySeparated,
+    /// Married
+    #[serde(rename = "M")]
+    Married,
+    /// Common law
+    #[serde(rename = "C")]
+    CommonLaw,
+    /// Polygamous
+    #[serde(rename = "P")]
+    Polygamous,
+    /// Domestic partner
+    #[serde(rename = "T")]
+    DomesticPartner,
+    /// Unmarried
+    #[serde(rename = "U")]
+    Unmarried,
+    /// Never married
+    #[serde(rename = "S")]
+    NeverMarried,
+    /// Widowed
+    #[serde(rename = "W")]
+    Widowed,
+}
+
+impl MaritalStatus {
+    /// Every status, in declaration order
+    pub const ALL: [MaritalStatus; 11] = [
+        MaritalStatus::Annulled,
+        MaritalStatus::Divorced,
+        MaritalStatus::Interlocutory,
+        MaritalStatus::LegallySeparated,
+        MaritalStatus::Married,
+        MaritalStatus::CommonLaw,
+        MaritalStatus::Polygamous,
+        MaritalStatus::DomesticPartner,
+        MaritalStatus::Unmarried,
+        MaritalStatus::NeverMarried,
+        MaritalStatus::Widowed,
+    ];
+
+    /// HL7 v3 code (e.g., "M")
+    pub fn code(self) -> &'static str {
+        match self {
+            MaritalStatus::Annulled => "A",
+            MaritalStatus::Divorced => "D",
+            MaritalStatus::Interlocutory => "I",
+            MaritalStatus::LegallySeparated => "L",
+            MaritalStatus::Married => "M",
+            MaritalStatus::CommonLaw => "C",
+            MaritalStatus::Polygamous => "P",
+            MaritalStatus::DomesticPartner => "T",
+            MaritalStatus::Unmarried => "U",
+            MaritalStatus::NeverMarried => "S",
+            MaritalStatus::Widowed => "W",
+        }
+    }
+
+    /// HL7 v3 display text
+    pub fn display(self) -> &'static str {
+        match self {
+            MaritalStatus::Annulled => "Annulled",
+            MaritalStatus::Divorced => "Divorced",
+            MaritalStatus::Interlocutory => "Interlocutory",
+            MaritalStatus::LegallySeparated => "Legally Separated",
+            MaritalStatus::Married => "Married",
+            MaritalStatus::CommonLaw => "Common Law",
+            MaritalStatus::Polygamous => "Polygamous",
+            MaritalStatus::DomesticPartner => "Domestic partner",
+            MaritalStatus::Unmarried => "unmarried",
+            MaritalStatus::NeverMarried => "Never Married",
+            MaritalStatus::Widowed => "Widowed",
+        }
+    }
+
+    /// Status for an HL7 v3 code, matched case-insensitively.
+    pub fn from_code(code: &str) -> Option<Self> {
+        MaritalStatus::ALL.into_iter().find(|status| status.code().eq_ignore_ascii_case(code.trim()))
+    }
+
+    /// The status as a concept for `Person.maritalStatus`.
+    pub fn concept(self) -> CodeableConcept {
+        CodeableConcept {
+            coding: alloc::vec![Coding {
+                system: catalog::MARITAL_STATUS.to_string(),
+                code: self.code().to_string(),
+                display: Some(self.display().to_string()),
+            }],
+            text: None,
+        }
+    }
+}
+
 /// Clinical summary information.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "ClinicalSummaryInput")
+)]
 pub struct ClinicalSummary {
     /// Known conditions/diagnoses (SNOMED CT or ICD-10)
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -28,26 +149,118 @@ pub struct ClinicalSummary {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub allergies: Option<Vec<CodeableConcept>>,
     /// Blood type (e.g., A+, O-)
-    #[serde(rename = "bloodType", skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "bloodType", alias = "blood_type", skip_serializing_if = "Option::is_none")]
     pub blood_type: Option<String>,
     /// Primary care provider ID
-    #[serde(rename = "primaryCareProvider", skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "primaryCareProvider", alias = "primary_care_provider", skip_serializing_if = "Option::is_none")]
     pub primary_care_provider: Option<String>,
+    /// Number of medications current when the summary was derived
+    #[serde(
+        rename = "currentMedicationCount",
+        alias = "current_medication_count",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub current_medication_count: Option<u32>,
+    /// Most recent vital signs
+    #[serde(rename = "latestVitals", alias = "latest_vitals", skip_serializing_if = "Option::is_none")]
+    pub latest_vitals: Option<VitalsSnapshot>,
+    /// When the summary was derived from the chart; absent for a summary kept by hand
+    #[serde(rename = "derivedAt", alias = "derived_at", skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "utoipa", schema(value_type = Option<String>))]
+    pub derived_at: Option<FlexibleDateTime>,
+}
+
+/// Vital signs read from one report, for [`ClinicalSummary::latest_vitals`].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "VitalsSnapshotInput")
+)]
+pub struct VitalsSnapshot {
+    /// Reference to LabReport.id the signs were read from
+    #[serde(rename = "reportId", alias = "report_id")]
+    pub report_id: String,
+    /// When the report was issued
+    #[serde(rename = "takenAt", alias = "taken_at")]
+    #[cfg_attr(feature = "utoipa", schema(value_type = String))]
+    pub taken_at: FlexibleDateTime,
+    /// Respiratory rate in breaths per minute
+    #[serde(rename = "respiratoryRate", alias = "respiratory_rate", skip_serializing_if = "Option::is_none")]
+    pub respiratory_rate: Option<f64>,
+    /// Peripheral oxygen saturation in percent
+    #[serde(rename = "oxygenSaturation", alias = "oxygen_saturation", skip_serializing_if = "Option::is_none")]
+    pub oxygen_saturation: Option<f64>,
+    /// Whether the patient was on supplemental oxygen
+    #[serde(rename = "supplementalOxygen", alias = "supplemental_oxygen", skip_serializing_if = "Option::is_none")]
+    pub supplemental_oxygen: Option<bool>,
+    /// Systolic blood pressure in mmHg
+    #[serde(
+        rename = "systolicBloodPressure",
+        alias = "systolic_blood_pressure",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub systolic_blood_pressure: Option<f64>,
+    /// Pulse rate in beats per minute
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pulse: Option<f64>,
+    /// Body temperature in degrees Celsius
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f64>,
+}
+
+impl VitalsSnapshot {
+    /// Snapshot of `vitals` read from `report_id`; consciousness is not kept.
+    pub fn new(report_id: impl Into<String>, taken_at: FlexibleDateTime, vitals: &Vitals) -> Self {
+        VitalsSnapshot {
+            report_id: report_id.into(),
+            taken_at,
+            respiratory_rate: vitals.respiratory_rate,
+            oxygen_saturation: vitals.oxygen_saturation,
+            supplemental_oxygen: vitals.supplemental_oxygen,
+            systolic_blood_pressure: vitals.systolic_blood_pressure,
+            pulse: vitals.pulse,
+            temperature: vitals.temperature,
+        }
+    }
+
+    /// The signs as [`Vitals`], e.g. for [`crate::vitals::scoring`].
+    pub fn vitals(&self) -> Vitals {
+        Vitals {
+            respiratory_rate: self.respiratory_rate,
+            oxygen_saturation: self.oxygen_saturation,
+            supplemental_oxygen: self.supplemental_oxygen,
+            systolic_blood_pressure: self.systolic_blood_pressure,
+            pulse: self.pulse,
+            consciousness: None,
+            temperature: self.temperature,
+        }
+    }
 }
 
 /// Personal health record.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "PersonInput")
+)]
 pub struct Person {
     /// Unique person identifier (UUID/ULID)
     pub id: String,
     /// Resource type (always "Person")
-    #[serde(rename = "resourceType")]
+    #[serde(rename = "resourceType", alias = "resource_type", default = "default_resource_type")]
     pub resource_type: String,
     /// Person name(s)
     pub name: Vec<HumanName>,
     /// Date of birth
-    #[serde(rename = "birthDate")]
-    pub birth_date: NaiveDate,
+    #[serde(rename = "birthDate", alias = "birth_date")]
+    #[cfg_attr(feature = "utoipa", schema(value_type = String))]
+    pub birth_date: FlexibleDate,
     /// External identifiers (MRN, national ID, etc.)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub identifier: Option<Vec<Identifier>>,
@@ -61,23 +274,80 @@ pub struct Person {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub address: Option<Vec<Address>>,
     /// Marital status
-    #[serde(rename = "maritalStatus", skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "maritalStatus", alias = "marital_status", skip_serializing_if = "Option::is_none")]
     pub marital_status: Option<CodeableConcept>,
     /// Language preferences (IETF BCP-47 tags)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub language: Option<Vec<String>>,
     /// Clinical summary
-    #[serde(rename = "clinicalSummary", skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "clinicalSummary", alias = "clinical_summary", skip_serializing_if = "Option::is_none")]
     pub clinical_summary: Option<ClinicalSummary>,
+    /// Schema version the resource was written against (e.g., "0.1.0"); absent means 0.1.0
+    #[serde(rename = "schemaVersion", alias = "schema_version", skip_serializing_if = "Option::is_none")]
+    pub schema_version: Option<String>,
+    /// Confidentiality and sensitivity labels (e.g., v3 ActCode HIV); see [`crate::security`]
+    #[serde(rename = "securityLabels", alias = "security_labels", skip_serializing_if = "Option::is_none")]
+    pub security_labels: Option<Vec<Coding>>,
+    /// Site-specific extensions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extensions: Option<Vec<Extension>>,
+    /// Properties this model version does not define, kept for re-serialization
+    #[cfg(feature = "preserve-unknown")]
+    #[serde(flatten)]
+    #[cfg_attr(feature = "schemars", schemars(skip))]
+    #[cfg_attr(feature = "utoipa", schema(ignore))]
+    #[cfg_attr(feature = "async-graphql", graphql(skip))]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+fn default_resource_type() -> String {
+    "Person".to_string()
+}
+
+impl Person {
+    /// First identifier whose `type` is `kind`.
+    pub fn identifier_of(&self, kind: IdentifierType) -> Option<&Identifier> {
+        self.identifiers().iter().find(|identifier| identifier.identifier_type() == Some(kind))
+    }
+
+    /// Medical record number (identifier typed MR).
+    pub fn mrn(&self) -> Option<&Identifier> {
+        self.identifier_of(IdentifierType::MedicalRecordNumber)
+    }
+
+    /// National ID (identifier typed NI).
+    pub fn national_id(&self) -> Option<&Identifier> {
+        self.identifier_of(IdentifierType::NationalId)
+    }
+
+    /// `maritalStatus` as an HL7 v3 [`MaritalStatus`], if coded in that system.
+    pub fn marital_status_code(&self) -> Option<MaritalStatus> {
+        self.marital_status
+            .as_ref()?
+            .coding
+            .iter()
+            .filter(|coding| coding.system == catalog::MARITAL_STATUS)
+            .find_map(|coding| MaritalStatus::from_code(&coding.code))
+    }
+}
+
+impl HasIdentifiers for Person {
+    fn identifiers(&self) -> &[Identifier] {
+        self.identifier.as_deref().unwrap_or_default()
+    }
+
+    fn identifiers_mut(&mut self) -> &mut Option<Vec<Identifier>> {
+        &mut self.identifier
+    }
 }
 
 impl Default for Person {
     fn default() -> Self {
         Self {
             id: String::new(),
-            resource_type: "Person".to_string(),
+            resource_type: default_resource_type(),
             name: Vec::new(),
-            birth_date: NaiveDate::from_ymd_opt(2000, 1, 1).unwrap(),
+            birth_date: NaiveDate::from_ymd_opt(2000, 1, 1).unwrap().into(),
             identifier: None,
             gender: None,
             telecom: None,
@@ -85,6 +355,11 @@ impl Default for Person {
             marital_status: None,
             language: None,
             clinical_summary: None,
+            schema_version: None,
+            security_labels: None,
+            extensions: None,
+            #[cfg(feature = "preserve-unknown")]
+            extra: Default::default(),
         }
     }
 }
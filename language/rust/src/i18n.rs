@@ -0,0 +1,337 @@
+//! Localized display text for coded values and units.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//!
+//! Patient-facing apps show interpretations, genders, modalities and family
+//! relations in the reader's language. [`LocalizedDisplay`] gives every coded
+//! enum a label per [`Locale`], and [`unit_label`] does the same for common
+//! UCUM units, so apps do not need their own translation tables.
+//!
+//! ```
+//! use wellally::i18n::{unit_label, Locale, LocalizedDisplay};
+//! use wellally::Interpretation;
+//!
+//! let locale = Locale::from_tag("zh-CN");
+//! assert_eq!(Interpretation::H.localized_display(locale), "偏高");
+//! assert_eq!(unit_label("mg/dL", locale), Some("毫克/分升"));
+//! ```
+
+use crate::common::{ContactSystem, ContactUse, IdentifierType, ModalityCode, NameUse, RouteCode};
+use crate::family_health::{RelationToProband, Sex};
+use crate::health::{Gender, MaritalStatus};
+use crate::lab_report::Interpretation;
+
+/// Language of display text.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Locale {
+    /// English
+    #[default]
+    En,
+    /// Simplified Chinese
+    Zh,
+}
+
+impl Locale {
+    /// Every supported locale.
+    pub const ALL: &'static [Locale] = &[Locale::En, Locale::Zh];
+
+    /// Locale for a BCP 47 tag such as `"zh-CN"` or `"en_US"`, matched on the
+    /// language subtag; unsupported languages fall back to English.
+    pub fn from_tag(tag: &str) -> Self {
+        let language = tag.split(['-', '_']).next().unwrap_or_default();
+        if language.eq_ignore_ascii_case("zh") {
+            Locale::Zh
+        } else {
+            Locale::En
+        }
+    }
+
+    /// Language subtag of the locale.
+    pub fn tag(&self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::Zh => "zh",
+        }
+    }
+}
+
+/// Values with a display label per [`Locale`].
+pub trait LocalizedDisplay {
+    /// Label of the value in `locale`.
+    fn localized_display(&self, locale: Locale) -> &'static str;
+}
+
+/// Implements [`LocalizedDisplay`] from `Variant => (english, chinese)` tables.
+macro_rules! localized {
+    ($($ty:ident { $($variant:ident => ($en:expr, $zh:expr),)* })*) => {
+        $(
+            impl LocalizedDisplay for $ty {
+                fn localized_display(&self, locale: Locale) -> &'static str {
+                    let (en, zh) = match self {
+                        $($ty::$variant => ($en, $zh),)*
+                    };
+                    match locale {
+                        Locale::En => en,
+                        Locale::Zh => zh,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+localized! {
+    Interpretation {
+        N => ("Normal", "正常"),
+        L => ("Low", "偏低"),
+        H => ("High", "偏高"),
+        A => ("Abnormal", "异常"),
+        LL => ("Critically low", "危急偏低"),
+        HH => ("Critically high", "危急偏高"),
+        AA => ("Critically abnormal", "危急异常"),
+        LU => ("Significantly low", "显著偏低"),
+        HU => ("Significantly high", "显著偏高"),
+        OffScaleLow => ("Off scale low", "低于检测下限"),
+        OffScaleHigh => ("Off scale high", "高于检测上限"),
+        B => ("Better", "好转"),
+        W => ("Worse", "恶化"),
+        U => ("Significant change up", "显著上升"),
+        D => ("Significant change down", "显著下降"),
+        S => ("Susceptible", "敏感"),
+        R => ("Resistant", "耐药"),
+        I => ("Intermediate", "中介"),
+        MS => ("Moderately susceptible", "中度敏感"),
+        VS => ("Very susceptible", "高度敏感"),
+        NS => ("Non-susceptible", "不敏感"),
+        SDD => ("Susceptible-dose dependent", "剂量依赖性敏感"),
+        SynR => ("Synergy resistant", "协同耐药"),
+        SynS => ("Synergy susceptible", "协同敏感"),
+        NCL => ("No CLSI breakpoint", "无 CLSI 折点"),
+        POS => ("Positive", "阳性"),
+        NEG => ("Negative", "阴性"),
+        IND => ("Indeterminate", "不确定"),
+        E => ("Equivocal", "可疑"),
+        DET => ("Detected", "检出"),
+        ND => ("Not detected", "未检出"),
+        RR => ("Reactive", "反应性"),
+        WR => ("Weakly reactive", "弱反应性"),
+        NR => ("Non-reactive", "无反应性"),
+        EXP => ("Expected", "符合预期"),
+        UNE => ("Unexpected", "不符合预期"),
+        EX => ("Outside threshold", "超出阈值"),
+        HX => ("Above high threshold", "高于阈值上限"),
+        LX => ("Below low threshold", "低于阈值下限"),
+        CAR => ("Carrier", "携带者"),
+        IE => ("Insufficient evidence", "证据不足"),
+    }
+    Gender {
+        Male => ("Male", "男"),
+        Female => ("Female", "女"),
+        Other => ("Other", "其他"),
+        Unknown => ("Unknown", "未知"),
+    }
+    Sex {
+        Male => ("Male", "男"),
+        Female => ("Female", "女"),
+        Other => ("Other", "其他"),
+        Unknown => ("Unknown", "未知"),
+    }
+    RelationToProband {
+        Self_ => ("Self", "本人"),
+        Mother => ("Mother", "母亲"),
+        Father => ("Father", "父亲"),
+        Sibling => ("Sibling", "兄弟姐妹"),
+        Child => ("Child", "子女"),
+        Grandparent => ("Grandparent", "祖父母/外祖父母"),
+        Grandchild => ("Grandchild", "孙子女/外孙子女"),
+        Aunt => ("Aunt", "姑母/姨母"),
+        Uncle => ("Uncle", "伯叔/舅舅"),
+        Cousin => ("Cousin", "堂/表兄弟姐妹"),
+        Other => ("Other relative", "其他亲属"),
+    }
+    NameUse {
+        Official => ("Official", "正式姓名"),
+        Usual => ("Usual", "常用名"),
+        Nickname => ("Nickname", "昵称"),
+        Anonymous => ("Anonymous", "匿名"),
+        Old => ("Former", "曾用名"),
+        Maiden => ("Maiden", "婚前姓名"),
+    }
+    ContactSystem {
+        Phone => ("Phone", "电话"),
+        Email => ("Email", "电子邮件"),
+    }
+    ContactUse {
+        Home => ("Home", "住宅"),
+        Work => ("Work", "工作"),
+        Mobile => ("Mobile", "手机"),
+    }
+    MaritalStatus {
+        Annulled => ("Annulled", "婚姻无效"),
+        Divorced => ("Divorced", "离婚"),
+        Interlocutory => ("Interlocutory", "离婚判决未生效"),
+        LegallySeparated => ("Legally separated", "合法分居"),
+        Married => ("Married", "已婚"),
+        CommonLaw => ("Common law", "事实婚姻"),
+        Polygamous => ("Polygamous", "多配偶"),
+        DomesticPartner => ("Domestic partner", "同居伴侣"),
+        Unmarried => ("Unmarried", "未婚"),
+        NeverMarried => ("Never married", "从未结婚"),
+        Widowed => ("Widowed", "丧偶"),
+    }
+    IdentifierType {
+        MedicalRecordNumber => ("Medical record number", "病历号"),
+        NationalId => ("National ID", "身份证号"),
+        Passport => ("Passport", "护照号"),
+        DriversLicense => ("Driver's license", "驾驶证号"),
+        InsuranceMember => ("Insurance member ID", "医保/保险会员号"),
+    }
+}
+
+/// Display labels of common UCUM units: `(ucum, english, chinese)`.
+const UNITS: &[(&str, &str, &str)] = &[
+    ("mg/dL", "mg/dL", "毫克/分升"),
+    ("mg/L", "mg/L", "毫克/升"),
+    ("ug/dL", "µg/dL", "微克/分升"),
+    ("ng/dL", "ng/dL", "纳克/分升"),
+    ("g/dL", "g/dL", "克/分升"),
+    ("g/L", "g/L", "克/升"),
+    ("mmol/L", "mmol/L", "毫摩尔/升"),
+    ("umol/L", "µmol/L", "微摩尔/升"),
+    ("mmol/mol", "mmol/mol", "毫摩尔/摩尔"),
+    ("pmol/L", "pmol/L", "皮摩尔/升"),
+    ("ng/mL", "ng/mL", "纳克/毫升"),
+    ("pg/mL", "pg/mL", "皮克/毫升"),
+    ("mIU/L", "mIU/L", "毫国际单位/升"),
+    ("mIU/mL", "mIU/mL", "毫国际单位/毫升"),
+    ("m[IU]/mL", "mIU/mL", "毫国际单位/毫升"),
+    ("uIU/mL", "µIU/mL", "微国际单位/毫升"),
+    ("IU/mL", "IU/mL", "国际单位/毫升"),
+    ("U/L", "U/L", "单位/升"),
+    ("U/mL", "U/mL", "单位/毫升"),
+    ("kU/L", "kU/L", "千单位/升"),
+    ("mm/h", "mm/h", "毫米/小时"),
+    ("{titer}", "titer", "滴度"),
+    ("mL/min/{1.73_m2}", "mL/min/1.73 m²", "毫升/分钟/1.73 平方米"),
+    ("10*3/uL", "×10³/µL", "×10³/微升"),
+    ("10*6/uL", "×10⁶/µL", "×10⁶/微升"),
+    ("10*9/L", "×10⁹/L", "×10⁹/升"),
+    ("10*12/L", "×10¹²/L", "×10¹²/升"),
+    ("fL", "fL", "飞升"),
+    ("pg", "pg", "皮克"),
+    ("%", "%", "%"),
+    ("mm[Hg]", "mmHg", "毫米汞柱"),
+    ("/min", "/min", "次/分钟"),
+    ("Cel", "°C", "摄氏度"),
+    ("[degF]", "°F", "华氏度"),
+    ("kg", "kg", "千克"),
+    ("g", "g", "克"),
+    ("[lb_av]", "lb", "磅"),
+    ("cm", "cm", "厘米"),
+    ("m", "m", "米"),
+    ("[in_i]", "in", "英寸"),
+    ("kg/m2", "kg/m²", "千克/平方米"),
+    ("m2", "m²", "平方米"),
+    ("mg", "mg", "毫克"),
+    ("ug", "µg", "微克"),
+    ("mL", "mL", "毫升"),
+    ("L", "L", "升"),
+    ("[IU]", "IU", "国际单位"),
+    ("d", "days", "天"),
+    ("wk", "weeks", "周"),
+    ("mo", "months", "月"),
+    ("a", "years", "年"),
+    ("h", "hours", "小时"),
+    ("min", "minutes", "分钟"),
+    ("mGy", "mGy", "毫戈瑞"),
+    ("mGy.cm", "mGy·cm", "毫戈瑞·厘米"),
+];
+
+/// Display label of a UCUM unit in `locale`, if the unit is in the catalog.
+pub fn unit_label(unit: &str, locale: Locale) -> Option<&'static str> {
+    UNITS.iter().find(|(ucum, _, _)| *ucum == unit).map(|(_, en, zh)| match locale {
+        Locale::En => *en,
+        Locale::Zh => *zh,
+    })
+}
+
+impl LocalizedDisplay for ModalityCode {
+    fn localized_display(&self, locale: Locale) -> &'static str {
+        use ModalityCode::*;
+        let (en, zh) = match self {
+            CT => ("Computed tomography", "CT（计算机断层扫描）"),
+            MR => ("Magnetic resonance imaging", "磁共振成像"),
+            US => ("Ultrasound", "超声"),
+            XR => ("X-ray", "X 线摄影"),
+            PT => ("Positron emission tomography", "PET（正电子发射断层扫描）"),
+            AR => ("Autorefraction", "自动验光"),
+            BDUS => ("Bone densitometry (ultrasound)", "超声骨密度测定"),
+            BI => ("Biomagnetic imaging", "生物磁成像"),
+            BMD => ("Bone densitometry (X-ray)", "X 线骨密度测定"),
+            CR => ("Computed radiography", "计算机 X 线摄影"),
+            DG => ("Diaphanography", "透照成像"),
+            DX => ("Digital radiography", "数字 X 线摄影"),
+            EPS => ("Cardiac electrophysiology", "心脏电生理"),
+            ES => ("Endoscopy", "内镜"),
+            GM => ("General microscopy", "普通显微镜"),
+            HD => ("Hemodynamic waveform", "血流动力学波形"),
+            IO => ("Intra-oral radiography", "口内 X 线摄影"),
+            IVOCT => ("Intravascular OCT", "血管内光学相干断层扫描"),
+            IVUS => ("Intravascular ultrasound", "血管内超声"),
+            KER => ("Keratometry", "角膜曲率测量"),
+            LEN => ("Lensometry", "镜片测度"),
+            LS => ("Laser surface scan", "激光表面扫描"),
+            MG => ("Mammography", "乳腺 X 线摄影"),
+            NM => ("Nuclear medicine", "核医学"),
+            OAM => ("Ophthalmic axial measurements", "眼轴测量"),
+            OCT => ("Optical coherence tomography", "光学相干断层扫描"),
+            OP => ("Ophthalmic photography", "眼科摄影"),
+            OPM => ("Ophthalmic mapping", "眼科地形图"),
+            OPT => ("Ophthalmic tomography", "眼科断层扫描"),
+            OPTBSV => ("Ophthalmic B-scan volume analysis", "眼科 B 扫描容积分析"),
+            OPTENF => ("Ophthalmic en face tomography", "眼科正面断层扫描"),
+            OPV => ("Ophthalmic visual field", "视野检查"),
+            OSS => ("Optical surface scan", "光学表面扫描"),
+            OT => ("Other", "其他"),
+            PX => ("Panoramic X-ray", "全景 X 线摄影"),
+            RF => ("Radiofluoroscopy", "X 线透视"),
+            RG => ("Radiographic imaging", "常规 X 线摄影"),
+            SM => ("Slide microscopy", "玻片显微镜"),
+            SRF => ("Subjective refraction", "主觉验光"),
+            VA => ("Visual acuity", "视力检查"),
+            XA => ("X-ray angiography", "X 线血管造影"),
+            XC => ("External-camera photography", "外部相机摄影"),
+            Other(_) => ("Other modality", "其他检查方式"),
+        };
+        match locale {
+            Locale::En => en,
+            Locale::Zh => zh,
+        }
+    }
+}
+
+impl LocalizedDisplay for RouteCode {
+    fn localized_display(&self, locale: Locale) -> &'static str {
+        use RouteCode::*;
+        let (en, zh) = match self {
+            Oral => ("Oral", "口服"),
+            Intravenous => ("Intravenous", "静脉注射"),
+            Intramuscular => ("Intramuscular", "肌内注射"),
+            Subcutaneous => ("Subcutaneous", "皮下注射"),
+            Sublingual => ("Sublingual", "舌下含服"),
+            Rectal => ("Rectal", "直肠给药"),
+            Inhalation => ("Inhalation", "吸入"),
+            Topical => ("Topical", "外用"),
+            Ophthalmic => ("Ophthalmic", "滴眼"),
+            Otic => ("Otic", "滴耳"),
+            Nasal => ("Nasal", "鼻用"),
+            Other(_) => ("Other route", "其他给药途径"),
+        };
+        match locale {
+            Locale::En => en,
+            Locale::Zh => zh,
+        }
+    }
+}
@@ -4,12 +4,33 @@
 //! Website: https://www.wellally.tech/
 //! Schema: https://wellall.health/schemas/imaging-report/v0.1.0
 
+use alloc::{format, string::String, vec::Vec};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
 use serde::{Deserialize, Serialize};
-use chrono::{DateTime, Utc};
-use crate::common::{Modality, Coding};
+use sha1::{Digest, Sha1};
+use thiserror::Error;
+use crate::common::{Annotation, BodySite, CodeableConcept, Modality, Coding, Reference};
+use crate::datetime::FlexibleDateTime;
+use crate::error::WellAllyError;
+use crate::extension::Extension;
+use crate::validation::{join, ValidationIssue};
+
+pub mod dexa;
+pub mod echo;
+pub mod measurements;
+
+pub use measurements::ImagingMeasurements;
 
 /// Imaging report performer (radiologist).
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "PerformerInput")
+)]
 pub struct Performer {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub id: Option<String>,
@@ -21,46 +42,294 @@ pub struct Performer {
 
 /// CT radiation dose information.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "RadiationDoseInput")
+)]
 pub struct RadiationDose {
     /// CT Dose Index Volume (mGy)
-    #[serde(rename = "ctdiVol_mGy", skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "ctdiVol_mGy", alias = "ctdi_vol_mgy", skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "async-graphql", graphql(name = "ctdiVol_mGy"))]
     pub ctdi_vol_mgy: Option<f64>,
     /// Dose Length Product (mGy·cm)
-    #[serde(rename = "dlp_mGy_cm", skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "dlp_mGy_cm", alias = "dlp_mgy_cm", skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "async-graphql", graphql(name = "dlp_mGy_cm"))]
     pub dlp_mgy_cm: Option<f64>,
 }
 
-/// Report attachment (image, PDF, etc.).
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+/// Report attachment (image, PDF, etc.), referenced by URL or carried inline.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "AttachmentInput")
+)]
 pub struct Attachment {
+    /// Where the content can be fetched
     #[serde(skip_serializing_if = "Option::is_none")]
     pub url: Option<String>,
+    /// MIME type of the content (e.g., application/pdf)
     #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "async-graphql", graphql(name = "type"))]
     pub attachment_type: Option<String>,
+    /// Inline content, base64 encoded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<String>,
+    /// SHA-1 digest of the content, base64 encoded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hash: Option<String>,
+    /// Content size in bytes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    /// Label to show in place of the content
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// When the content was first created
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "utoipa", schema(value_type = Option<String>))]
+    pub creation: Option<FlexibleDateTime>,
+}
+
+/// Largest content [`AttachmentLimits::default`] allows inline: 10 MiB.
+pub const DEFAULT_MAX_INLINE_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Limits on attachment content, checked by validation and before inlining.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AttachmentLimits {
+    /// Largest decoded size of inline `data`, in bytes
+    pub max_inline_bytes: u64,
+}
+
+impl Default for AttachmentLimits {
+    fn default() -> Self {
+        AttachmentLimits { max_inline_bytes: DEFAULT_MAX_INLINE_BYTES }
+    }
+}
+
+impl AttachmentLimits {
+    /// Limits allowing at most `max_inline_bytes` of inline content.
+    pub fn new(max_inline_bytes: u64) -> Self {
+        AttachmentLimits { max_inline_bytes }
+    }
+
+    /// Issues with the inline content of `attachment`: data that is not
+    /// base64 or exceeds the limit, and a `size` or `hash` that disagrees
+    /// with it. Paths are relative to the attachment.
+    pub fn check(&self, attachment: &Attachment) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+        self.check_into(&mut issues, "", attachment);
+        issues
+    }
+
+    pub(crate) fn check_into(&self, issues: &mut Vec<ValidationIssue>, path: &str, attachment: &Attachment) {
+        if let Some(hash) = &attachment.hash {
+            if BASE64.decode(hash).map_or(true, |digest| digest.len() != SHA1_LEN) {
+                issues.push(ValidationIssue::new(join(path, "hash"), "must be a base64 SHA-1 digest"));
+            }
+        }
+        let Some(data) = &attachment.data else {
+            return;
+        };
+        let Ok(content) = BASE64.decode(data) else {
+            issues.push(ValidationIssue::new(join(path, "data"), "must be base64 encoded"));
+            return;
+        };
+        if content.len() as u64 > self.max_inline_bytes {
+            issues.push(ValidationIssue::new(join(path, "data"), format!("exceeds the inline limit of {} bytes", self.max_inline_bytes)));
+        }
+        if attachment.size.is_some_and(|size| size != content.len() as u64) {
+            issues.push(ValidationIssue::new(join(path, "size"), format!("does not match the {} bytes of data", content.len())));
+        }
+        if attachment.hash.as_deref().is_some_and(|hash| hash != sha1_base64(&content)) {
+            issues.push(ValidationIssue::new(join(path, "hash"), "does not match the SHA-1 of data"));
+        }
+    }
+}
+
+/// Retrieves the content of URL-backed attachments, e.g. over HTTP or from
+/// a PACS. Implemented by callers; the crate does no I/O itself.
+pub trait AttachmentFetcher {
+    /// Error raised when the content cannot be retrieved
+    type Error;
+
+    /// Content at `url`. `max_bytes` is the most the caller will accept, so
+    /// implementations can stop reading early.
+    fn fetch(&self, url: &str, max_bytes: u64) -> Result<Vec<u8>, Self::Error>;
+}
+
+/// Why [`Attachment::inline_with`] left an attachment unchanged.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum InlineError<E> {
+    /// The attachment has no URL to fetch from
+    #[error("attachment has no url to fetch")]
+    NoUrl,
+    /// The declared or fetched content is larger than the limit
+    #[error("attachment content of {size} bytes exceeds the inline limit of {limit} bytes")]
+    TooLarge {
+        /// Content size in bytes
+        size: u64,
+        /// Limit in bytes
+        limit: u64,
+    },
+    /// The fetched content does not match the declared `hash`
+    #[error("fetched content does not match the attachment hash")]
+    HashMismatch,
+    /// The fetcher failed
+    #[error("fetching the attachment failed: {0}")]
+    Fetch(E),
+}
+
+const SHA1_LEN: usize = 20;
+
+fn sha1_base64(content: &[u8]) -> String {
+    BASE64.encode(Sha1::digest(content))
+}
+
+impl Attachment {
+    /// Attachment carrying `content` inline, with its size and SHA-1 hash.
+    pub fn inline(content_type: impl Into<String>, content: &[u8]) -> Self {
+        Attachment {
+            attachment_type: Some(content_type.into()),
+            data: Some(BASE64.encode(content)),
+            hash: Some(sha1_base64(content)),
+            size: Some(content.len() as u64),
+            ..Attachment::default()
+        }
+    }
+
+    /// Attachment referring to content at `url`.
+    pub fn from_url(url: impl Into<String>, content_type: impl Into<String>) -> Self {
+        Attachment { url: Some(url.into()), attachment_type: Some(content_type.into()), ..Attachment::default() }
+    }
+
+    /// Decoded inline content; `None` when the attachment has no `data`.
+    pub fn content(&self) -> Result<Option<Vec<u8>>, WellAllyError> {
+        let Some(data) = &self.data else {
+            return Ok(None);
+        };
+        BASE64.decode(data).map(Some).map_err(|err| WellAllyError::Conversion {
+            resource_type: "Attachment".into(),
+            path: "data".into(),
+            value: None,
+            message: format!("data is not base64: {}", err),
+        })
+    }
+
+    /// Whether the inline content matches `hash`; `None` without both or
+    /// when `data` cannot be decoded.
+    pub fn verify_hash(&self) -> Option<bool> {
+        let hash = self.hash.as_deref()?;
+        let content = self.content().ok()??;
+        Some(sha1_base64(&content) == hash)
+    }
+
+    /// Fetches the content at `url` and stores it inline, filling in `size`
+    /// and `hash`. Content over `limits` is refused, before fetching when
+    /// `size` already says so; a declared `hash` must match what was fetched.
+    pub fn inline_with<F: AttachmentFetcher>(&mut self, fetcher: &F, limits: &AttachmentLimits) -> Result<(), InlineError<F::Error>> {
+        let url = self.url.as_deref().ok_or(InlineError::NoUrl)?;
+        let limit = limits.max_inline_bytes;
+        if let Some(size) = self.size.filter(|size| *size > limit) {
+            return Err(InlineError::TooLarge { size, limit });
+        }
+        let content = fetcher.fetch(url, limit).map_err(InlineError::Fetch)?;
+        let size = content.len() as u64;
+        if size > limit {
+            return Err(InlineError::TooLarge { size, limit });
+        }
+        let hash = sha1_base64(&content);
+        if self.hash.as_deref().is_some_and(|expected| expected != hash) {
+            return Err(InlineError::HashMismatch);
+        }
+        self.data = Some(BASE64.encode(&content));
+        self.hash = Some(hash);
+        self.size = Some(size);
+        Ok(())
+    }
+}
+
+/// Overall change since a prior study, as a follow-up report concludes it.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "async-graphql", derive(async_graphql::Enum))]
+#[serde(rename_all = "kebab-case")]
+pub enum CodedChange {
+    /// Finding not seen on the prior study
+    New,
+    Improved,
+    Stable,
+    Worsened,
+    /// Finding seen on the prior study is gone
+    Resolved,
+    /// Some findings improved while others worsened
+    Mixed,
 }
 
 /// Diagnostic imaging report.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "ImagingReportInput")
+)]
 pub struct ImagingReport {
     /// Unique report identifier
     pub id: String,
     /// Reference to Person.id
-    #[serde(rename = "patientId")]
+    #[serde(rename = "patientId", alias = "patient_id")]
     pub patient_id: String,
     /// Imaging modality (CT, MR, US, XR, PT)
     pub modality: Modality,
-    /// Body site examined (SNOMED CT code)
-    #[serde(rename = "bodySite")]
-    pub body_site: Coding,
+    /// Body site examined (SNOMED CT code), with laterality
+    #[serde(rename = "bodySite", alias = "body_site")]
+    pub body_site: BodySite,
     /// Report timestamp
-    #[serde(rename = "reportedAt")]
-    pub reported_at: DateTime<Utc>,
+    #[serde(rename = "reportedAt", alias = "reported_at")]
+    #[cfg_attr(feature = "utoipa", schema(value_type = String))]
+    pub reported_at: FlexibleDateTime,
     /// DICOM Study Instance UID
-    #[serde(rename = "studyInstanceUid", skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "studyInstanceUid", alias = "study_instance_uid", skip_serializing_if = "Option::is_none")]
     pub study_instance_uid: Option<String>,
     /// Radiologist information
     #[serde(skip_serializing_if = "Option::is_none")]
     pub performer: Option<Performer>,
+    /// Order the study was performed for (e.g., "ServiceRequest/order-1")
+    #[serde(rename = "basedOn", alias = "based_on", skip_serializing_if = "Option::is_none")]
+    pub based_on: Option<Reference>,
+    /// Indications for the exam (ICD-10 or SNOMED CT), as appropriateness criteria key on them
+    #[serde(rename = "reasonCode", alias = "reason_code", skip_serializing_if = "Option::is_none")]
+    pub reason_code: Option<Vec<CodeableConcept>>,
+    /// Clinical history given by the ordering clinician
+    #[serde(rename = "clinicalHistory", alias = "clinical_history", skip_serializing_if = "Option::is_none")]
+    pub clinical_history: Option<String>,
+    /// Coded protocol performed (e.g., RadLex Playbook RPID)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub protocol: Option<CodeableConcept>,
+    /// How the study was performed (protocol, contrast, sequences)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub technique: Option<String>,
+    /// Prior studies compared against
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comparison: Option<String>,
+    /// DICOM Study Instance UIDs of the prior studies compared against
+    #[serde(
+        rename = "comparisonStudyUids",
+        alias = "comparison_study_uids",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub comparison_study_uids: Vec<String>,
+    /// Overall change since the prior study
+    #[serde(rename = "changeFromPrior", alias = "change_from_prior", skip_serializing_if = "Option::is_none")]
+    pub change_from_prior: Option<CodedChange>,
     /// Imaging findings list
     #[serde(skip_serializing_if = "Option::is_none")]
     pub findings: Option<Vec<String>>,
@@ -68,9 +337,81 @@ pub struct ImagingReport {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub impression: Option<String>,
     /// Radiation dose (for CT)
-    #[serde(rename = "radiationDose", skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "radiationDose", alias = "radiation_dose", skip_serializing_if = "Option::is_none")]
     pub radiation_dose: Option<RadiationDose>,
+    /// Structured measurements, typed by study kind
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub measurements: Option<ImagingMeasurements>,
     /// Attached files
     #[serde(skip_serializing_if = "Option::is_none")]
     pub attachments: Option<Vec<Attachment>>,
+    /// Free-text notes and comments
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notes: Option<Vec<Annotation>>,
+    /// Schema version the resource was written against (e.g., "0.1.0"); absent means 0.1.0
+    #[serde(rename = "schemaVersion", alias = "schema_version", skip_serializing_if = "Option::is_none")]
+    pub schema_version: Option<String>,
+    /// Confidentiality and sensitivity labels (e.g., v3 ActCode HIV); see [`crate::security`]
+    #[serde(rename = "securityLabels", alias = "security_labels", skip_serializing_if = "Option::is_none")]
+    pub security_labels: Option<Vec<Coding>>,
+    /// Site-specific extensions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extensions: Option<Vec<Extension>>,
+    /// Properties this model version does not define, kept for re-serialization
+    #[cfg(feature = "preserve-unknown")]
+    #[serde(flatten)]
+    #[cfg_attr(feature = "schemars", schemars(skip))]
+    #[cfg_attr(feature = "utoipa", schema(ignore))]
+    #[cfg_attr(feature = "async-graphql", graphql(skip))]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl ImagingReport {
+    /// Whether `other` is the same exam: same modality and same body site
+    /// on the same side.
+    pub fn is_same_exam(&self, other: &ImagingReport) -> bool {
+        self.modality.code == other.modality.code && self.body_site.is_same_site(&other.body_site)
+    }
+
+    /// Latest report among `reports` of the same exam of the same patient
+    /// reported before this one; the report to compare against.
+    pub fn prior<'a>(&self, reports: &'a [ImagingReport]) -> Option<&'a ImagingReport> {
+        self.priors(reports).into_iter().next()
+    }
+
+    /// Reports among `reports` of the same exam of the same patient reported
+    /// before this one, latest first.
+    pub fn priors<'a>(&self, reports: &'a [ImagingReport]) -> Vec<&'a ImagingReport> {
+        let reported_at = self.reported_at.to_utc();
+        let mut priors: Vec<_> = reports
+            .iter()
+            .filter(|report| report.id != self.id && report.patient_id == self.patient_id)
+            .filter(|report| self.is_same_exam(report) && report.reported_at.to_utc() < reported_at)
+            .collect();
+        priors.sort_by_key(|report| core::cmp::Reverse(report.reported_at.to_utc()));
+        priors
+    }
+
+    /// Reports of the same patient among `reports` whose study is listed in
+    /// `comparisonStudyUids`, in list order.
+    pub fn compared_reports<'a>(&self, reports: &'a [ImagingReport]) -> Vec<&'a ImagingReport> {
+        let compared = |uid: &String| {
+            reports.iter().find(|report| {
+                report.patient_id == self.patient_id && report.study_instance_uid.as_ref() == Some(uid)
+            })
+        };
+        self.comparison_study_uids.iter().filter_map(compared).collect()
+    }
+
+    /// Finds the prior report in `reports` and lists its study in
+    /// `comparisonStudyUids` unless already there. Returns the prior, if any.
+    pub fn link_prior<'a>(&mut self, reports: &'a [ImagingReport]) -> Option<&'a ImagingReport> {
+        let prior = self.prior(reports)?;
+        if let Some(uid) = &prior.study_instance_uid {
+            if !self.comparison_study_uids.contains(uid) {
+                self.comparison_study_uids.push(uid.clone());
+            }
+        }
+        Some(prior)
+    }
 }
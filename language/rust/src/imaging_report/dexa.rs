@@ -0,0 +1,235 @@
+//! Bone densitometry (DEXA) measurements.
+//!
+//! A [`DexaReport`] holds the bone mineral density, T-score and Z-score of
+//! each site scanned, plus the clinical risk factors FRAX needs, so
+//! osteoporosis screening programs can classify and trend results instead of
+//! reading them out of text. It is attached to an [`ImagingReport`] as
+//! [`ImagingMeasurements::Dexa`].
+//!
+//! ```
+//! use wellally::imaging_report::dexa::{DexaReport, DexaSite, SiteDensity, WhoClassification};
+//! use wellally::Quantity;
+//!
+//! let mut dexa = DexaReport::default();
+//! let mut spine = SiteDensity::new(DexaSite::LumbarSpine, Quantity::new(0.812, "g/cm2"));
+//! spine.t_score = Some(-2.7);
+//! let mut neck = SiteDensity::new(DexaSite::FemoralNeck, Quantity::new(0.701, "g/cm2"));
+//! neck.t_score = Some(-1.9);
+//! dexa.sites = vec![spine, neck];
+//! assert_eq!(dexa.who_classification(), Some(WhoClassification::Osteoporosis));
+//! ```
+//!
+//! [`ImagingReport`]: crate::ImagingReport
+//! [`ImagingMeasurements::Dexa`]: super::ImagingMeasurements::Dexa
+
+use alloc::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+
+use crate::common::Quantity;
+use crate::health::Gender;
+use crate::ucum;
+
+/// T-score at or below which bone density is osteoporotic
+pub const OSTEOPOROSIS_T_SCORE: f64 = -2.5;
+/// T-score below which bone density is low (osteopenia)
+pub const LOW_BONE_MASS_T_SCORE: f64 = -1.0;
+/// Z-score at or below which bone density is below the expected range for age
+pub const LOW_Z_SCORE: f64 = -2.0;
+
+/// Site scanned.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[serde(rename_all = "kebab-case")]
+pub enum DexaSite {
+    /// L1–L4
+    LumbarSpine,
+    TotalHip,
+    FemoralNeck,
+    /// One-third (33%) radius of the non-dominant forearm
+    Radius33,
+    TotalBody,
+}
+
+impl DexaSite {
+    /// Whether the WHO classification uses the site: lumbar spine, total hip,
+    /// femoral neck or 33% radius, not total body.
+    pub fn is_diagnostic(self) -> bool {
+        self != DexaSite::TotalBody
+    }
+}
+
+/// WHO diagnostic category of a T-score.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[serde(rename_all = "kebab-case")]
+pub enum WhoClassification {
+    /// T-score −1.0 or above
+    Normal,
+    /// T-score between −1.0 and −2.5
+    Osteopenia,
+    /// T-score −2.5 or below
+    Osteoporosis,
+}
+
+impl WhoClassification {
+    /// Category of `t_score`.
+    pub fn of(t_score: f64) -> Self {
+        if t_score <= OSTEOPOROSIS_T_SCORE {
+            WhoClassification::Osteoporosis
+        } else if t_score < LOW_BONE_MASS_T_SCORE {
+            WhoClassification::Osteopenia
+        } else {
+            WhoClassification::Normal
+        }
+    }
+}
+
+/// Density of one site.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub struct SiteDensity {
+    pub site: DexaSite,
+    /// Bone mineral density (e.g., 0.812 g/cm2)
+    pub bmd: Quantity,
+    /// Standard deviations from the young-adult reference mean
+    #[serde(rename = "tScore", alias = "t_score", skip_serializing_if = "Option::is_none")]
+    pub t_score: Option<f64>,
+    /// Standard deviations from the age- and sex-matched reference mean
+    #[serde(rename = "zScore", alias = "z_score", skip_serializing_if = "Option::is_none")]
+    pub z_score: Option<f64>,
+}
+
+impl SiteDensity {
+    /// Density of `site` without scores.
+    pub fn new(site: DexaSite, bmd: Quantity) -> Self {
+        SiteDensity { site, bmd, t_score: None, z_score: None }
+    }
+}
+
+/// Clinical risk factors entered into the FRAX fracture risk calculator.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub struct FraxInputs {
+    /// Age in years (FRAX accepts 40–90)
+    pub age: u8,
+    pub sex: Gender,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub weight: Option<Quantity>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<Quantity>,
+    /// Fragility fracture in adult life
+    #[serde(rename = "previousFracture", alias = "previous_fracture", default)]
+    pub previous_fracture: bool,
+    #[serde(rename = "parentFracturedHip", alias = "parent_fractured_hip", default)]
+    pub parent_fractured_hip: bool,
+    #[serde(rename = "currentSmoking", alias = "current_smoking", default)]
+    pub current_smoking: bool,
+    /// Oral glucocorticoids for 3 months or more at 5 mg prednisolone daily or more
+    #[serde(default)]
+    pub glucocorticoids: bool,
+    #[serde(rename = "rheumatoidArthritis", alias = "rheumatoid_arthritis", default)]
+    pub rheumatoid_arthritis: bool,
+    /// Disorder strongly associated with osteoporosis (e.g., type 1 diabetes, untreated hypogonadism)
+    #[serde(rename = "secondaryOsteoporosis", alias = "secondary_osteoporosis", default)]
+    pub secondary_osteoporosis: bool,
+    /// Three or more units of alcohol daily
+    #[serde(rename = "alcoholThreeOrMoreUnits", alias = "alcohol_three_or_more_units", default)]
+    pub alcohol_three_or_more_units: bool,
+}
+
+impl FraxInputs {
+    /// Inputs with no risk factor present.
+    pub fn new(age: u8, sex: Gender) -> Self {
+        FraxInputs {
+            age,
+            sex,
+            weight: None,
+            height: None,
+            previous_fracture: false,
+            parent_fractured_hip: false,
+            current_smoking: false,
+            glucocorticoids: false,
+            rheumatoid_arthritis: false,
+            secondary_osteoporosis: false,
+            alcohol_three_or_more_units: false,
+        }
+    }
+
+    /// Body mass index in kg/m2; `None` without a convertible weight and height.
+    pub fn bmi(&self) -> Option<f64> {
+        let (weight, height) = (self.weight.as_ref()?, self.height.as_ref()?);
+        let kg = ucum::convert(weight.value, &weight.unit, "kg").ok()?;
+        let m = ucum::convert(height.value, &height.unit, "m").ok()?;
+        (m > 0.0).then(|| kg / (m * m))
+    }
+
+    /// Number of the yes/no risk factors present.
+    pub fn risk_factor_count(&self) -> usize {
+        [
+            self.previous_fracture,
+            self.parent_fractured_hip,
+            self.current_smoking,
+            self.glucocorticoids,
+            self.rheumatoid_arthritis,
+            self.secondary_osteoporosis,
+            self.alcohol_three_or_more_units,
+        ]
+        .into_iter()
+        .filter(|present| *present)
+        .count()
+    }
+}
+
+/// Structured bone densitometry results.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub struct DexaReport {
+    /// Sites scanned, one entry per site
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sites: Vec<SiteDensity>,
+    /// Risk factors for a FRAX calculation
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frax: Option<FraxInputs>,
+}
+
+impl DexaReport {
+    /// Density of `site`.
+    pub fn site(&self, site: DexaSite) -> Option<&SiteDensity> {
+        self.sites.iter().find(|density| density.site == site)
+    }
+
+    /// Lowest T-score of the diagnostic sites.
+    pub fn lowest_t_score(&self) -> Option<f64> {
+        self.sites
+            .iter()
+            .filter(|density| density.site.is_diagnostic())
+            .filter_map(|density| density.t_score)
+            .min_by(f64::total_cmp)
+    }
+
+    /// WHO category of the lowest diagnostic T-score. T-scores apply to
+    /// postmenopausal women and men aged 50 and over; use
+    /// [`DexaReport::below_expected_for_age`] for younger patients.
+    pub fn who_classification(&self) -> Option<WhoClassification> {
+        self.lowest_t_score().map(WhoClassification::of)
+    }
+
+    /// Whether a diagnostic site has a Z-score of −2.0 or below.
+    pub fn below_expected_for_age(&self) -> bool {
+        self.sites
+            .iter()
+            .filter(|density| density.site.is_diagnostic())
+            .any(|density| density.z_score.is_some_and(|z_score| z_score <= LOW_Z_SCORE))
+    }
+
+    /// Femoral neck BMD, the density FRAX takes.
+    pub fn frax_bmd(&self) -> Option<&Quantity> {
+        self.site(DexaSite::FemoralNeck).map(|density| &density.bmd)
+    }
+}
@@ -0,0 +1,248 @@
+//! Echocardiography measurements.
+//!
+//! An [`EchoReport`] records what an echo reader measures rather than
+//! describes: left ventricular ejection fraction, chamber dimensions, the
+//! stenosis and regurgitation grade of each valve and AHA 17-segment wall
+//! motion scores. It is attached to an [`ImagingReport`] as
+//! [`ImagingMeasurements::Echocardiography`].
+//!
+//! ```
+//! use wellally::imaging_report::echo::{EchoReport, EjectionFractionCategory, SegmentWallMotion, WallMotion};
+//!
+//! let mut echo = EchoReport::default();
+//! echo.lv_ejection_fraction = Some(38.0);
+//! echo.wall_motion = (1..=17).map(|segment| SegmentWallMotion::new(segment, WallMotion::Normal)).collect();
+//! echo.wall_motion[6].motion = WallMotion::Akinetic;
+//! assert_eq!(echo.ejection_fraction_category(), Some(EjectionFractionCategory::Reduced));
+//! assert_eq!(echo.wall_motion_score_index(), Some(19.0 / 17.0));
+//! ```
+//!
+//! [`ImagingReport`]: crate::ImagingReport
+//! [`ImagingMeasurements::Echocardiography`]: super::ImagingMeasurements::Echocardiography
+
+use alloc::{string::String, vec::Vec};
+
+use serde::{Deserialize, Serialize};
+
+use crate::common::Quantity;
+
+/// Segments of the AHA 17-segment left ventricular model
+pub const LV_SEGMENTS: u8 = 17;
+
+/// Heart failure category by ejection fraction (Universal Definition of
+/// Heart Failure, 2021).
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[serde(rename_all = "kebab-case")]
+pub enum EjectionFractionCategory {
+    /// 40% or less
+    Reduced,
+    /// 41–49%
+    MildlyReduced,
+    /// 50% or more
+    Preserved,
+}
+
+impl EjectionFractionCategory {
+    /// Category of an ejection fraction in percent.
+    pub fn of(percent: f64) -> Self {
+        if percent <= 40.0 {
+            EjectionFractionCategory::Reduced
+        } else if percent < 50.0 {
+            EjectionFractionCategory::MildlyReduced
+        } else {
+            EjectionFractionCategory::Preserved
+        }
+    }
+}
+
+/// Cardiac valve.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[serde(rename_all = "kebab-case")]
+pub enum Valve {
+    Aortic,
+    Mitral,
+    Tricuspid,
+    Pulmonic,
+}
+
+/// Grade of a stenosis or regurgitation, from none to severe.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[serde(rename_all = "kebab-case")]
+pub enum ValveSeverity {
+    None,
+    Trace,
+    Mild,
+    Moderate,
+    Severe,
+}
+
+/// Assessment of one valve.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub struct ValveAssessment {
+    pub valve: Valve,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stenosis: Option<ValveSeverity>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub regurgitation: Option<ValveSeverity>,
+    /// Whether the valve is a prosthesis
+    #[serde(default, skip_serializing_if = "core::ops::Not::not")]
+    pub prosthetic: bool,
+    /// Reader's comment (e.g., "Eccentric jet, posteriorly directed")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+}
+
+impl ValveAssessment {
+    /// Native valve with no lesion graded yet.
+    pub fn new(valve: Valve) -> Self {
+        ValveAssessment { valve, stenosis: None, regurgitation: None, prosthetic: false, comment: None }
+    }
+
+    /// Worse of the stenosis and regurgitation grades.
+    pub fn worst(&self) -> Option<ValveSeverity> {
+        self.stenosis.max(self.regurgitation)
+    }
+}
+
+/// Wall motion of a segment, scored 1 (normal) to 5 (aneurysmal).
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[serde(rename_all = "kebab-case")]
+pub enum WallMotion {
+    Normal,
+    Hypokinetic,
+    Akinetic,
+    Dyskinetic,
+    Aneurysmal,
+}
+
+impl WallMotion {
+    /// ASE wall motion score.
+    pub fn score(self) -> u8 {
+        match self {
+            WallMotion::Normal => 1,
+            WallMotion::Hypokinetic => 2,
+            WallMotion::Akinetic => 3,
+            WallMotion::Dyskinetic => 4,
+            WallMotion::Aneurysmal => 5,
+        }
+    }
+}
+
+/// Wall motion of one segment of the AHA 17-segment model.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub struct SegmentWallMotion {
+    /// Segment number, 1 (basal anterior) to 17 (apex)
+    pub segment: u8,
+    pub motion: WallMotion,
+}
+
+impl SegmentWallMotion {
+    /// `segment` scored as `motion`.
+    pub fn new(segment: u8, motion: WallMotion) -> Self {
+        SegmentWallMotion { segment, motion }
+    }
+}
+
+/// Linear chamber and wall dimensions.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub struct ChamberDimensions {
+    /// Left ventricular internal diameter in diastole
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lvidd: Option<Quantity>,
+    /// Left ventricular internal diameter in systole
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lvids: Option<Quantity>,
+    /// Interventricular septum thickness in diastole
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ivsd: Option<Quantity>,
+    /// Left ventricular posterior wall thickness in diastole
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lvpwd: Option<Quantity>,
+    /// Left atrial anteroposterior diameter
+    #[serde(rename = "leftAtrium", alias = "left_atrium", skip_serializing_if = "Option::is_none")]
+    pub left_atrium: Option<Quantity>,
+    /// Aortic root diameter at the sinuses
+    #[serde(rename = "aorticRoot", alias = "aortic_root", skip_serializing_if = "Option::is_none")]
+    pub aortic_root: Option<Quantity>,
+    /// Right ventricular basal diameter
+    #[serde(rename = "rightVentricle", alias = "right_ventricle", skip_serializing_if = "Option::is_none")]
+    pub right_ventricle: Option<Quantity>,
+}
+
+impl ChamberDimensions {
+    /// Dimensions present, labelled with their JSON names.
+    pub fn measured(&self) -> Vec<(&'static str, &Quantity)> {
+        [
+            ("lvidd", &self.lvidd),
+            ("lvids", &self.lvids),
+            ("ivsd", &self.ivsd),
+            ("lvpwd", &self.lvpwd),
+            ("leftAtrium", &self.left_atrium),
+            ("aorticRoot", &self.aortic_root),
+            ("rightVentricle", &self.right_ventricle),
+        ]
+        .into_iter()
+        .filter_map(|(name, dimension)| Some((name, dimension.as_ref()?)))
+        .collect()
+    }
+}
+
+/// Structured transthoracic or transesophageal echocardiogram results.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub struct EchoReport {
+    /// Left ventricular ejection fraction in percent
+    #[serde(rename = "lvEjectionFraction", alias = "lv_ejection_fraction", skip_serializing_if = "Option::is_none")]
+    pub lv_ejection_fraction: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chambers: Option<ChamberDimensions>,
+    /// One assessment per valve examined
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub valves: Vec<ValveAssessment>,
+    /// Scored segments; unscored segments are left out
+    #[serde(rename = "wallMotion", alias = "wall_motion", default, skip_serializing_if = "Vec::is_empty")]
+    pub wall_motion: Vec<SegmentWallMotion>,
+}
+
+impl EchoReport {
+    /// Heart failure category of the ejection fraction.
+    pub fn ejection_fraction_category(&self) -> Option<EjectionFractionCategory> {
+        self.lv_ejection_fraction.map(EjectionFractionCategory::of)
+    }
+
+    /// Assessment of `valve`.
+    pub fn valve(&self, valve: Valve) -> Option<&ValveAssessment> {
+        self.valves.iter().find(|assessment| assessment.valve == valve)
+    }
+
+    /// Valves with a moderate or severe lesion, the usual threshold for
+    /// follow-up imaging or referral.
+    pub fn significant_valve_disease(&self) -> Vec<&ValveAssessment> {
+        self.valves.iter().filter(|assessment| assessment.worst() >= Some(ValveSeverity::Moderate)).collect()
+    }
+
+    /// Wall motion score index: the mean score of the scored segments. 1 is
+    /// normal; `None` when no segment is scored.
+    pub fn wall_motion_score_index(&self) -> Option<f64> {
+        if self.wall_motion.is_empty() {
+            return None;
+        }
+        let total: u32 = self.wall_motion.iter().map(|segment| u32::from(segment.motion.score())).sum();
+        Some(f64::from(total) / self.wall_motion.len() as f64)
+    }
+}
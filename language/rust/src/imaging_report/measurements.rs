@@ -0,0 +1,186 @@
+//! Structured measurements attached to imaging reports.
+//!
+//! Free-text findings cannot be trended or checked, so an [`ImagingReport`]
+//! may carry a typed [`ImagingMeasurements`] block whose variant is keyed by
+//! the kind of study: obstetric and abdominal ultrasound record
+//! [`UltrasoundMeasurements`] with fetal biometry and organ dimensions,
+//! echocardiograms an [`EchoReport`] and bone densitometry a [`DexaReport`].
+//!
+//! ```
+//! use wellally::imaging_report::measurements::{BiometryMeasurement, FetalBiometry, UltrasoundMeasurements};
+//! use wellally::imaging_report::ImagingMeasurements;
+//! use wellally::{ModalityCode, Quantity};
+//!
+//! let mut biometry = FetalBiometry::default();
+//! biometry.ac = Some(BiometryMeasurement::new(Quantity::new(262.0, "mm"), Some(6.0)));
+//! let scan = UltrasoundMeasurements { fetal_biometry: Some(biometry), organs: Vec::new() };
+//! assert!(scan.fetal_biometry.as_ref().unwrap().is_small_for_gestational_age());
+//! assert_eq!(ImagingMeasurements::Ultrasound(Box::new(scan)).modality(), ModalityCode::US);
+//! ```
+//!
+//! [`ImagingReport`]: crate::ImagingReport
+
+use alloc::{boxed::Box, vec::Vec};
+
+use serde::{Deserialize, Serialize};
+
+use crate::common::{Coding, ModalityCode, Quantity};
+use crate::ucum;
+
+use super::dexa::DexaReport;
+use super::echo::EchoReport;
+
+/// Percentile below which a fetus is small for gestational age
+pub const SGA_PERCENTILE: f64 = 10.0;
+
+/// Measurements of a study, tagged by study kind in `type`. Variants are
+/// boxed so reports without measurements stay small.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum ImagingMeasurements {
+    /// Obstetric or abdominal ultrasound
+    Ultrasound(Box<UltrasoundMeasurements>),
+    /// Echocardiogram
+    Echocardiography(Box<EchoReport>),
+    /// Dual-energy X-ray absorptiometry
+    Dexa(Box<DexaReport>),
+}
+
+#[cfg(feature = "async-graphql")]
+async_graphql::scalar!(
+    ImagingMeasurements,
+    "ImagingMeasurements",
+    "Structured imaging measurements as JSON, tagged by study kind in \"type\""
+);
+
+impl ImagingMeasurements {
+    /// Modality the measurements are taken with; a report carrying them
+    /// must be of this modality.
+    pub fn modality(&self) -> ModalityCode {
+        match self {
+            ImagingMeasurements::Ultrasound(_) | ImagingMeasurements::Echocardiography(_) => ModalityCode::US,
+            ImagingMeasurements::Dexa(_) => ModalityCode::BMD,
+        }
+    }
+}
+
+/// Ultrasound measurements.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub struct UltrasoundMeasurements {
+    /// Fetal biometry of an obstetric scan
+    #[serde(rename = "fetalBiometry", alias = "fetal_biometry", skip_serializing_if = "Option::is_none")]
+    pub fetal_biometry: Option<FetalBiometry>,
+    /// Organ sizes of an abdominal or pelvic scan
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub organs: Vec<OrganDimensions>,
+}
+
+/// One biometric measurement with its centile for gestational age.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub struct BiometryMeasurement {
+    /// Measured value (e.g., 88 mm)
+    pub value: Quantity,
+    /// Centile for gestational age on the chart the scanner uses (0–100)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub percentile: Option<f64>,
+}
+
+impl BiometryMeasurement {
+    /// Measurement with its centile, when the scanner reports one.
+    pub fn new(value: Quantity, percentile: Option<f64>) -> Self {
+        BiometryMeasurement { value, percentile }
+    }
+}
+
+/// Standard fetal biometry.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub struct FetalBiometry {
+    /// Gestational age the centiles refer to (e.g., 32 wk)
+    #[serde(rename = "gestationalAge", alias = "gestational_age", skip_serializing_if = "Option::is_none")]
+    pub gestational_age: Option<Quantity>,
+    /// Biparietal diameter
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bpd: Option<BiometryMeasurement>,
+    /// Head circumference
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hc: Option<BiometryMeasurement>,
+    /// Abdominal circumference
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ac: Option<BiometryMeasurement>,
+    /// Femur length
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fl: Option<BiometryMeasurement>,
+    /// Estimated fetal weight (e.g., 1850 g)
+    #[serde(
+        rename = "estimatedFetalWeight",
+        alias = "estimated_fetal_weight",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub estimated_fetal_weight: Option<BiometryMeasurement>,
+}
+
+impl FetalBiometry {
+    /// Length measurements present, labelled with their JSON names.
+    pub fn lengths(&self) -> Vec<(&'static str, &BiometryMeasurement)> {
+        [("bpd", &self.bpd), ("hc", &self.hc), ("ac", &self.ac), ("fl", &self.fl)]
+            .into_iter()
+            .filter_map(|(name, measurement)| Some((name, measurement.as_ref()?)))
+            .collect()
+    }
+
+    /// Whether the estimated fetal weight or the abdominal circumference is
+    /// below the 10th centile, the usual screen for growth restriction.
+    pub fn is_small_for_gestational_age(&self) -> bool {
+        [&self.estimated_fetal_weight, &self.ac]
+            .into_iter()
+            .flatten()
+            .any(|measurement| measurement.percentile.is_some_and(|percentile| percentile < SGA_PERCENTILE))
+    }
+}
+
+/// Size of one organ.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub struct OrganDimensions {
+    /// Organ measured (SNOMED CT, e.g., 78961009 spleen)
+    pub organ: Coding,
+    /// Longest axis (e.g., craniocaudal span of the liver)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub length: Option<Quantity>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub width: Option<Quantity>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub depth: Option<Quantity>,
+    /// Volume as reported by the scanner
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub volume: Option<Quantity>,
+}
+
+impl OrganDimensions {
+    /// Organ with no dimensions yet.
+    pub fn new(organ: Coding) -> Self {
+        OrganDimensions { organ, length: None, width: None, depth: None, volume: None }
+    }
+
+    /// Reported volume, else the ellipsoid estimate length × width × depth ×
+    /// π/6 in mL; `None` without all three lengths.
+    pub fn estimated_volume(&self) -> Option<Quantity> {
+        if let Some(volume) = &self.volume {
+            return Some(volume.clone());
+        }
+        let cm = |axis: &Option<Quantity>| {
+            axis.as_ref().and_then(|axis| ucum::convert(axis.value, &axis.unit, "cm").ok())
+        };
+        let product = cm(&self.length)? * cm(&self.width)? * cm(&self.depth)?;
+        Some(Quantity::new(product * core::f64::consts::PI / 6.0, "mL"))
+    }
+}
@@ -0,0 +1,226 @@
+//! Immunization record model and vaccine lot recalls.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//! Schema: https://wellall.health/schemas/immunization/v0.1.0
+//!
+//! An [`ImmunizationRecord`] is one vaccine dose given to a patient, with the
+//! lot number and manufacturer public health needs to trace it. When a
+//! manufacturer or regulator recalls lots, [`recall_check`] matches the
+//! records against the recall feed and returns the affected doses.
+//!
+//! ```
+//! use wellally::immunization::{self, ImmunizationRecord, VaccineRecall, CVX};
+//! use wellally::Coding;
+//!
+//! let influenza = Coding { system: CVX.into(), code: "150".into(), display: None };
+//! let mut dose = ImmunizationRecord::new("imm-1", "p-1", influenza, "2024-10-03".parse().unwrap());
+//! dose.lot_number = Some("ab-1234".into());
+//! let records = [dose];
+//! let recalls = [VaccineRecall::new("recall-7", vec!["AB1234".into()])];
+//! let affected = immunization::recall_check(&records, &recalls);
+//! assert_eq!(immunization::affected_patients(&affected), ["p-1"]);
+//! ```
+
+use alloc::{string::String, vec::Vec};
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::common::{Annotation, Coding, Period, Quantity, Reference, Route};
+use crate::extension::Extension;
+
+/// CVX vaccine code system
+pub const CVX: &str = "http://hl7.org/fhir/sid/cvx";
+/// MVX vaccine manufacturer code system
+pub const MVX: &str = "http://hl7.org/fhir/sid/mvx";
+
+/// One vaccine dose given.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "ImmunizationRecordInput")
+)]
+pub struct ImmunizationRecord {
+    /// Unique record identifier
+    pub id: String,
+    /// Reference to Person.id
+    #[serde(rename = "patientId", alias = "patient_id")]
+    pub patient_id: String,
+    /// Vaccine given (CVX)
+    pub vaccine: Coding,
+    /// Day the dose was given
+    #[serde(rename = "administeredOn", alias = "administered_on")]
+    pub administered_on: NaiveDate,
+    /// Dose number within the series (e.g., 2 for the second dose)
+    #[serde(rename = "doseNumber", alias = "dose_number", skip_serializing_if = "Option::is_none")]
+    pub dose_number: Option<u32>,
+    /// Lot number printed on the vial or syringe
+    #[serde(rename = "lotNumber", alias = "lot_number", skip_serializing_if = "Option::is_none")]
+    pub lot_number: Option<String>,
+    /// Manufacturer (MVX)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub manufacturer: Option<Coding>,
+    /// Expiration date of the lot
+    #[serde(rename = "expirationDate", alias = "expiration_date", skip_serializing_if = "Option::is_none")]
+    pub expiration_date: Option<NaiveDate>,
+    /// Amount given (e.g., 0.5 mL)
+    #[serde(rename = "doseQuantity", alias = "dose_quantity", skip_serializing_if = "Option::is_none")]
+    pub dose_quantity: Option<Quantity>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub route: Option<Route>,
+    /// Body site (e.g., SNOMED CT left deltoid)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub site: Option<Coding>,
+    /// Who gave the dose (e.g., "Practitioner/nurse-chen")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub performer: Option<Reference>,
+    /// Where the dose was given (e.g., "Location/clinic-2")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<Reference>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notes: Option<Vec<Annotation>>,
+    /// Schema version the resource was written against (e.g., "0.1.0"); absent means 0.1.0
+    #[serde(rename = "schemaVersion", alias = "schema_version", skip_serializing_if = "Option::is_none")]
+    pub schema_version: Option<String>,
+    /// Confidentiality and sensitivity labels (e.g., v3 ActCode HIV); see [`crate::security`]
+    #[serde(rename = "securityLabels", alias = "security_labels", skip_serializing_if = "Option::is_none")]
+    pub security_labels: Option<Vec<Coding>>,
+    /// Site-specific extensions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extensions: Option<Vec<Extension>>,
+    /// Properties this model version does not define, kept for re-serialization
+    #[cfg(feature = "preserve-unknown")]
+    #[serde(flatten)]
+    #[cfg_attr(feature = "schemars", schemars(skip))]
+    #[cfg_attr(feature = "utoipa", schema(ignore))]
+    #[cfg_attr(feature = "async-graphql", graphql(skip))]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl ImmunizationRecord {
+    /// Dose of `vaccine` given on `administered_on`, without lot details.
+    pub fn new(
+        id: impl Into<String>,
+        patient_id: impl Into<String>,
+        vaccine: Coding,
+        administered_on: NaiveDate,
+    ) -> Self {
+        ImmunizationRecord {
+            id: id.into(),
+            patient_id: patient_id.into(),
+            vaccine,
+            administered_on,
+            dose_number: None,
+            lot_number: None,
+            manufacturer: None,
+            expiration_date: None,
+            dose_quantity: None,
+            route: None,
+            site: None,
+            performer: None,
+            location: None,
+            notes: None,
+            schema_version: None,
+            security_labels: None,
+            extensions: None,
+            #[cfg(feature = "preserve-unknown")]
+            extra: Default::default(),
+        }
+    }
+
+    /// Whether the dose was given after its lot expired.
+    pub fn is_expired_dose(&self) -> bool {
+        self.expiration_date.is_some_and(|expiration| self.administered_on > expiration)
+    }
+}
+
+/// One entry of a recall feed: lots withdrawn by a manufacturer or regulator.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VaccineRecall {
+    /// Identifier of the notice in the feed
+    pub id: String,
+    /// Lot numbers recalled
+    #[serde(rename = "lotNumbers", alias = "lot_numbers")]
+    pub lot_numbers: Vec<String>,
+    /// Vaccine recalled (CVX); absent when the lot numbers alone identify it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vaccine: Option<Coding>,
+    /// Manufacturer (MVX); absent when the lot numbers alone identify it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub manufacturer: Option<Coding>,
+    /// Days on which doses from the lots are affected; open ends are unbounded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub administered: Option<Period>,
+    /// Why the lots were recalled (e.g., "Loss of potency")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl VaccineRecall {
+    /// Recall of `lot_numbers` whatever the vaccine and date.
+    pub fn new(id: impl Into<String>, lot_numbers: Vec<String>) -> Self {
+        VaccineRecall {
+            id: id.into(),
+            lot_numbers,
+            vaccine: None,
+            manufacturer: None,
+            administered: None,
+            reason: None,
+        }
+    }
+
+    /// Whether `record` is a dose from a recalled lot given in the recall
+    /// window. Lot numbers are compared ignoring case, spaces and hyphens, as
+    /// they are often typed by hand.
+    pub fn affects(&self, record: &ImmunizationRecord) -> bool {
+        let same = |a: &Coding, b: &Coding| a.system == b.system && a.code == b.code;
+        let same_maker = |maker: &Coding| record.manufacturer.as_ref().is_some_and(|m| same(maker, m));
+        let Some(lot) = record.lot_number.as_deref() else {
+            return false;
+        };
+        let lot = normalize_lot(lot);
+        !lot.is_empty()
+            && self.lot_numbers.iter().any(|recalled| normalize_lot(recalled) == lot)
+            && self.vaccine.as_ref().is_none_or(|vaccine| same(vaccine, &record.vaccine))
+            && self.manufacturer.as_ref().is_none_or(same_maker)
+            && self.administered.is_none_or(|period| period.contains(record.administered_on))
+    }
+}
+
+fn normalize_lot(lot: &str) -> String {
+    lot.chars().filter(|c| !c.is_whitespace() && *c != '-').map(|c| c.to_ascii_uppercase()).collect()
+}
+
+/// A dose hit by a recall.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RecallMatch<'a> {
+    pub recall: &'a VaccineRecall,
+    pub record: &'a ImmunizationRecord,
+}
+
+/// Doses among `records` affected by any of `recalls`, in record order and,
+/// for a dose under several notices, in feed order.
+pub fn recall_check<'a>(records: &'a [ImmunizationRecord], recalls: &'a [VaccineRecall]) -> Vec<RecallMatch<'a>> {
+    records
+        .iter()
+        .flat_map(|record| {
+            recalls.iter().filter(|recall| recall.affects(record)).map(move |recall| RecallMatch { recall, record })
+        })
+        .collect()
+}
+
+/// Patients with at least one affected dose, each once, in the order first
+/// seen.
+pub fn affected_patients<'a>(matches: &[RecallMatch<'a>]) -> Vec<&'a str> {
+    let mut patients: Vec<&str> = Vec::new();
+    for hit in matches {
+        if !patients.contains(&hit.record.patient_id.as_str()) {
+            patients.push(&hit.record.patient_id);
+        }
+    }
+    patients
+}
@@ -0,0 +1,297 @@
+//! Infusion and IV therapy administration model.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//! Schema: https://wellall.health/schemas/infusion/v0.1.0
+//!
+//! A single dose and frequency, as in [`crate::MedicationRecord`], cannot
+//! describe a drip. An [`InfusionRecord`] adds what does: the rate as an
+//! amount per unit of time, the volume to infuse, the line and site, rate
+//! changes (titrations) with when they happened, and interruptions with
+//! their reasons. From these it works out the rate running at any moment
+//! and the volume actually infused.
+//!
+//! ```
+//! use wellally::catalog::RXNORM;
+//! use wellally::infusion::{InfusionRecord, Titration};
+//! use wellally::{Coding, Quantity, Route};
+//!
+//! let saline = Coding { system: RXNORM.into(), code: "313002".into(), display: None };
+//! let mut infusion = InfusionRecord::new(
+//!     "inf-1", "p-1", saline, Route::intravenous(),
+//!     "2024-10-03T08:00:00Z".parse().unwrap(), Quantity::new(100.0, "mL/h"),
+//! );
+//! infusion.titrations.push(Titration::new("2024-10-03T10:00:00Z".parse().unwrap(), Quantity::new(50.0, "mL/h")));
+//! infusion.end = Some("2024-10-03T12:00:00Z".parse().unwrap());
+//! assert_eq!(infusion.volume_infused_ml(), Some(300.0));
+//! ```
+
+use alloc::{string::String, vec::Vec};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::common::{Annotation, CodeableConcept, Coding, Quantity, Reference, Route};
+use crate::datetime::FlexibleDateTime;
+use crate::extension::Extension;
+use crate::ucum;
+
+/// Whether `unit` is an amount per unit of time (e.g., `mL/h`, `ug/kg/min`, `[iU]/h`).
+pub fn is_rate(unit: &str) -> bool {
+    unit.rsplit_once('/').is_some_and(|(_, per)| ucum::commensurable(per, "h"))
+}
+
+/// Stretch of an infusion from one time to another at one rate (`None` while stopped).
+type Segment<'a> = (DateTime<Utc>, DateTime<Utc>, Option<&'a Quantity>);
+
+/// Kind of vascular line.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "async-graphql", derive(async_graphql::Enum))]
+#[serde(rename_all = "kebab-case")]
+pub enum LineType {
+    PeripheralIntravenous,
+    Midline,
+    /// Peripherally inserted central catheter
+    #[serde(rename = "picc")]
+    Picc,
+    CentralVenous,
+    /// Implanted port
+    Port,
+    Subcutaneous,
+}
+
+/// Line an infusion runs through.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "InfusionLineInput")
+)]
+pub struct InfusionLine {
+    #[serde(rename = "type")]
+    #[cfg_attr(feature = "async-graphql", graphql(name = "type"))]
+    pub line_type: LineType,
+    /// Insertion site (e.g., SNOMED CT right forearm)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub site: Option<Coding>,
+}
+
+/// Change of the infusion rate.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "TitrationInput")
+)]
+pub struct Titration {
+    /// When the new rate took effect
+    #[cfg_attr(feature = "utoipa", schema(value_type = String))]
+    pub at: FlexibleDateTime,
+    /// Rate from then on
+    pub rate: Quantity,
+    /// Why the rate changed (e.g., blood pressure target, protocol step)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<CodeableConcept>,
+}
+
+impl Titration {
+    /// Change to `rate` at `at`.
+    pub fn new(at: FlexibleDateTime, rate: Quantity) -> Self {
+        Titration { at, rate, reason: None }
+    }
+}
+
+/// Period the infusion was stopped for.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "InterruptionInput")
+)]
+pub struct Interruption {
+    #[cfg_attr(feature = "utoipa", schema(value_type = String))]
+    pub start: FlexibleDateTime,
+    /// When it resumed; absent while still stopped
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "utoipa", schema(value_type = Option<String>))]
+    pub end: Option<FlexibleDateTime>,
+    /// Why it stopped (e.g., occlusion alarm, infiltration, patient off the ward)
+    pub reason: CodeableConcept,
+}
+
+/// Infusion given over time through a line.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "InfusionRecordInput")
+)]
+pub struct InfusionRecord {
+    /// Unique record identifier
+    pub id: String,
+    /// Reference to Person.id
+    #[serde(rename = "patientId", alias = "patient_id")]
+    pub patient_id: String,
+    /// Medication or fluid infused (RxNorm)
+    pub medication: Coding,
+    /// Medication record the infusion carries out (e.g., "MedicationRecord/med-12")
+    #[serde(rename = "medicationRecord", alias = "medication_record", skip_serializing_if = "Option::is_none")]
+    pub medication_record: Option<Reference>,
+    /// Administration route (IV, SC, epidural)
+    pub route: Route,
+    /// Drug per volume of the infused solution (e.g., 1 mg/mL)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub concentration: Option<Quantity>,
+    /// When the infusion started
+    #[cfg_attr(feature = "utoipa", schema(value_type = String))]
+    pub start: FlexibleDateTime,
+    /// When it was stopped for good; absent while running
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "utoipa", schema(value_type = Option<String>))]
+    pub end: Option<FlexibleDateTime>,
+    /// Starting rate as an amount per unit of time (e.g., 125 mL/h, 0.1 ug/kg/min)
+    pub rate: Quantity,
+    /// Volume to infuse (e.g., 1000 mL)
+    #[serde(rename = "totalVolume", alias = "total_volume", skip_serializing_if = "Option::is_none")]
+    pub total_volume: Option<Quantity>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<InfusionLine>,
+    /// Rate changes after the start
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub titrations: Vec<Titration>,
+    /// Periods the infusion was stopped
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub interruptions: Vec<Interruption>,
+    /// Infusion pump (e.g., "Device/pump-3")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device: Option<Reference>,
+    /// Free-text notes and comments
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notes: Option<Vec<Annotation>>,
+    /// Schema version the resource was written against (e.g., "0.1.0"); absent means 0.1.0
+    #[serde(rename = "schemaVersion", alias = "schema_version", skip_serializing_if = "Option::is_none")]
+    pub schema_version: Option<String>,
+    /// Confidentiality and sensitivity labels (e.g., v3 ActCode HIV); see [`crate::security`]
+    #[serde(rename = "securityLabels", alias = "security_labels", skip_serializing_if = "Option::is_none")]
+    pub security_labels: Option<Vec<Coding>>,
+    /// Site-specific extensions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extensions: Option<Vec<Extension>>,
+    /// Properties this model version does not define, kept for re-serialization
+    #[cfg(feature = "preserve-unknown")]
+    #[serde(flatten)]
+    #[cfg_attr(feature = "schemars", schemars(skip))]
+    #[cfg_attr(feature = "utoipa", schema(ignore))]
+    #[cfg_attr(feature = "async-graphql", graphql(skip))]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl InfusionRecord {
+    /// Running infusion without titrations or interruptions.
+    pub fn new(
+        id: impl Into<String>,
+        patient_id: impl Into<String>,
+        medication: Coding,
+        route: Route,
+        start: FlexibleDateTime,
+        rate: Quantity,
+    ) -> Self {
+        InfusionRecord {
+            id: id.into(),
+            patient_id: patient_id.into(),
+            medication,
+            medication_record: None,
+            route,
+            concentration: None,
+            start,
+            end: None,
+            rate,
+            total_volume: None,
+            line: None,
+            titrations: Vec::new(),
+            interruptions: Vec::new(),
+            device: None,
+            notes: None,
+            schema_version: None,
+            security_labels: None,
+            extensions: None,
+            #[cfg(feature = "preserve-unknown")]
+            extra: Default::default(),
+        }
+    }
+
+    /// Whether the infusion was stopped at `at` by an interruption.
+    pub fn is_interrupted_at(&self, at: DateTime<Utc>) -> bool {
+        self.interruptions.iter().any(|interruption| {
+            interruption.start.to_utc() <= at && interruption.end.as_ref().is_none_or(|end| at < end.to_utc())
+        })
+    }
+
+    /// Rate running at `at`: the latest titration by then, else the starting
+    /// rate. `None` before the start, from the end on and while interrupted.
+    pub fn rate_at(&self, at: DateTime<Utc>) -> Option<&Quantity> {
+        let ended = self.end.as_ref().is_some_and(|end| at >= end.to_utc());
+        if at < self.start.to_utc() || ended || self.is_interrupted_at(at) {
+            return None;
+        }
+        let titration = self
+            .titrations
+            .iter()
+            .filter(|titration| titration.at.to_utc() <= at)
+            .max_by_key(|titration| titration.at.to_utc());
+        Some(titration.map_or(&self.rate, |titration| &titration.rate))
+    }
+
+    /// Stretches between the start and the end over which the rate stays the
+    /// same, with the rate running; `None` while the infusion has no end.
+    fn segments(&self) -> Option<Vec<Segment<'_>>> {
+        let (start, end) = (self.start.to_utc(), self.end.as_ref()?.to_utc());
+        let mut bounds = Vec::from([start, end]);
+        bounds.extend(self.titrations.iter().map(|titration| titration.at.to_utc()));
+        for interruption in &self.interruptions {
+            bounds.push(interruption.start.to_utc());
+            bounds.extend(interruption.end.as_ref().map(FlexibleDateTime::to_utc));
+        }
+        bounds.retain(|at| (start..=end).contains(at));
+        bounds.sort();
+        bounds.dedup();
+        Some(bounds.windows(2).map(|pair| (pair[0], pair[1], self.rate_at(pair[0]))).collect())
+    }
+
+    /// Minutes the infusion actually ran, leaving out interruptions.
+    pub fn running_minutes(&self) -> Option<i64> {
+        let segments = self.segments()?;
+        let running = segments.iter().filter(|(.., rate)| rate.is_some());
+        Some(running.map(|(from, to, _)| (*to - *from).num_minutes()).sum())
+    }
+
+    /// Volume infused in mL from the start to the end, following titrations
+    /// and leaving out interruptions; `None` while running or when a rate is
+    /// not a volume per time.
+    pub fn volume_infused_ml(&self) -> Option<f64> {
+        let mut volume = 0.0;
+        for (from, to, rate) in self.segments()? {
+            let Some(rate) = rate else { continue };
+            let ml_per_hour = ucum::convert(rate.value, &rate.unit, "mL/h").ok()?;
+            volume += ml_per_hour * (to - from).num_milliseconds() as f64 / 3_600_000.0;
+        }
+        Some(volume)
+    }
+
+    /// Whether the volume infused has reached `totalVolume`.
+    pub fn is_complete(&self) -> Option<bool> {
+        let total = self.total_volume.as_ref()?;
+        let planned = ucum::convert(total.value, &total.unit, "mL").ok()?;
+        Some(self.volume_infused_ml()? >= planned - 1e-6)
+    }
+}
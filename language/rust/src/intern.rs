@@ -0,0 +1,394 @@
+//! Interned codes and units for large in-memory datasets.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//!
+//! Coding systems, codes, displays and UCUM units repeat across millions of
+//! lab results. [`Symbol`] is a shared `Arc<str>` that deserializes through a
+//! process-wide [`Interner`], so every distinct string is stored once. The
+//! `Interned*` mirror types use symbols for those fields and convert to and
+//! from the regular models; free text (result strings, range text) stays a
+//! plain `String`. See `benches/intern.rs` for the memory comparison.
+
+use std::borrow::Borrow;
+use std::collections::HashSet;
+use std::fmt;
+use std::ops::Deref;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::common::{CodeableConcept, Coding, Effective, Quantity, Range, ReferenceRange, ReferenceRangeType};
+use crate::lab_report::{Comparator, Interpretation, LabResult, LabValue};
+
+/// Shared, immutable string obtained from an [`Interner`].
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Symbol(Arc<str>);
+
+impl Symbol {
+    /// Interns `value` in the global interner.
+    pub fn new(value: &str) -> Self {
+        Interner::global().intern(value)
+    }
+
+    /// The string value.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Deref for Symbol {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for Symbol {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Borrow<str> for Symbol {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&*self.0, f)
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<&str> for Symbol {
+    fn from(value: &str) -> Self {
+        Symbol::new(value)
+    }
+}
+
+impl Serialize for Symbol {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for Symbol {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor;
+
+        impl serde::de::Visitor<'_> for Visitor {
+            type Value = Symbol;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a string")
+            }
+
+            fn visit_str<E: serde::de::Error>(self, value: &str) -> Result<Symbol, E> {
+                Ok(Symbol::new(value))
+            }
+        }
+
+        deserializer.deserialize_str(Visitor)
+    }
+}
+
+/// Table of distinct strings handing out shared [`Symbol`]s.
+#[derive(Debug, Default)]
+pub struct Interner {
+    strings: Mutex<HashSet<Arc<str>>>,
+}
+
+impl Interner {
+    /// Empty interner.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Process-wide interner used by [`Symbol::new`] and deserialization.
+    pub fn global() -> &'static Interner {
+        static GLOBAL: OnceLock<Interner> = OnceLock::new();
+        GLOBAL.get_or_init(Interner::new)
+    }
+
+    /// Symbol for `value`, reusing the stored string when present.
+    pub fn intern(&self, value: &str) -> Symbol {
+        let mut strings = self.strings.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(existing) = strings.get(value) {
+            return Symbol(existing.clone());
+        }
+        let stored: Arc<str> = Arc::from(value);
+        strings.insert(stored.clone());
+        Symbol(stored)
+    }
+
+    /// Number of distinct strings stored.
+    pub fn len(&self) -> usize {
+        self.strings.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).len()
+    }
+
+    /// Whether no string has been interned yet.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// [`Coding`] with interned system, code and display.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct InternedCoding {
+    /// URI identifying the terminology system
+    pub system: Symbol,
+    /// The code value from the system
+    pub code: Symbol,
+    /// Optional human-readable display text
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display: Option<Symbol>,
+}
+
+impl From<&Coding> for InternedCoding {
+    fn from(coding: &Coding) -> Self {
+        Self {
+            system: Symbol::new(&coding.system),
+            code: Symbol::new(&coding.code),
+            display: coding.display.as_deref().map(Symbol::new),
+        }
+    }
+}
+
+impl InternedCoding {
+    /// Regular coding with owned strings.
+    pub fn to_owned(&self) -> Coding {
+        Coding {
+            system: self.system.to_string(),
+            code: self.code.to_string(),
+            display: self.display.as_ref().map(Symbol::to_string),
+        }
+    }
+}
+
+/// [`CodeableConcept`] with interned codings and text.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct InternedConcept {
+    /// List of coded values
+    pub coding: Vec<InternedCoding>,
+    /// Optional plain text representation
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<Symbol>,
+}
+
+impl From<&CodeableConcept> for InternedConcept {
+    fn from(concept: &CodeableConcept) -> Self {
+        Self {
+            coding: concept.coding.iter().map(InternedCoding::from).collect(),
+            text: concept.text.as_deref().map(Symbol::new),
+        }
+    }
+}
+
+impl InternedConcept {
+    /// Regular concept with owned strings.
+    pub fn to_owned(&self) -> CodeableConcept {
+        CodeableConcept {
+            coding: self.coding.iter().map(InternedCoding::to_owned).collect(),
+            text: self.text.as_ref().map(Symbol::to_string),
+        }
+    }
+}
+
+/// [`Quantity`] with an interned UCUM unit.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct InternedQuantity {
+    /// Numerical value
+    pub value: f64,
+    /// UCUM unit string
+    pub unit: Symbol,
+    /// How to read `value` when it is a limit
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comparator: Option<Comparator>,
+    /// Coded form of the unit
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<Symbol>,
+    /// System of `code`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system: Option<Symbol>,
+}
+
+impl From<&Quantity> for InternedQuantity {
+    fn from(quantity: &Quantity) -> Self {
+        Self {
+            value: quantity.value,
+            unit: Symbol::new(&quantity.unit),
+            comparator: quantity.comparator,
+            code: quantity.code.as_deref().map(Symbol::new),
+            system: quantity.system.as_deref().map(Symbol::new),
+        }
+    }
+}
+
+impl InternedQuantity {
+    /// Regular quantity with an owned unit.
+    pub fn to_owned(&self) -> Quantity {
+        Quantity {
+            comparator: self.comparator,
+            code: self.code.as_ref().map(Symbol::to_string),
+            system: self.system.as_ref().map(Symbol::to_string),
+            ..Quantity::new(self.value, self.unit.to_string())
+        }
+    }
+}
+
+/// [`ReferenceRange`] with interned bounds.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct InternedReferenceRange {
+    /// Lower bound quantity
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub low: Option<InternedQuantity>,
+    /// Upper bound quantity
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub high: Option<InternedQuantity>,
+    /// Optional textual description
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    /// Kind of range; absent means normal
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub range_type: Option<ReferenceRangeType>,
+    /// Populations or states the range is for
+    #[serde(rename = "appliesTo", alias = "applies_to", skip_serializing_if = "Option::is_none")]
+    pub applies_to: Option<Vec<InternedConcept>>,
+    /// Ages the range is for
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub age: Option<Range>,
+}
+
+impl From<&ReferenceRange> for InternedReferenceRange {
+    fn from(range: &ReferenceRange) -> Self {
+        Self {
+            low: range.low.as_ref().map(InternedQuantity::from),
+            high: range.high.as_ref().map(InternedQuantity::from),
+            text: range.text.clone(),
+            range_type: range.range_type,
+            applies_to: range.applies_to.as_ref().map(|concepts| concepts.iter().map(InternedConcept::from).collect()),
+            age: range.age.clone(),
+        }
+    }
+}
+
+impl InternedReferenceRange {
+    /// Regular reference range.
+    pub fn to_owned(&self) -> ReferenceRange {
+        ReferenceRange {
+            low: self.low.as_ref().map(InternedQuantity::to_owned),
+            high: self.high.as_ref().map(InternedQuantity::to_owned),
+            text: self.text.clone(),
+            range_type: self.range_type,
+            applies_to: self.applies_to.as_ref().map(|concepts| concepts.iter().map(InternedConcept::to_owned).collect()),
+            age: self.age.clone(),
+        }
+    }
+}
+
+/// [`LabValue`] with interned codes and units.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum InternedLabValue {
+    Quantity(InternedQuantity),
+    Ratio { numerator: f64, denominator: f64 },
+    Comparator { comparator: Comparator, value: InternedQuantity },
+    Concept(InternedConcept),
+    String(String),
+}
+
+impl From<&LabValue> for InternedLabValue {
+    fn from(value: &LabValue) -> Self {
+        match value {
+            LabValue::Quantity(quantity) => InternedLabValue::Quantity(quantity.into()),
+            LabValue::Ratio { numerator, denominator } => {
+                InternedLabValue::Ratio { numerator: *numerator, denominator: *denominator }
+            }
+            LabValue::Comparator { comparator, value } => {
+                InternedLabValue::Comparator { comparator: *comparator, value: value.into() }
+            }
+            LabValue::Concept(concept) => InternedLabValue::Concept(concept.into()),
+            LabValue::String(text) => InternedLabValue::String(text.clone()),
+        }
+    }
+}
+
+impl InternedLabValue {
+    /// Regular lab value.
+    pub fn to_owned(&self) -> LabValue {
+        match self {
+            InternedLabValue::Quantity(quantity) => LabValue::Quantity(quantity.to_owned()),
+            InternedLabValue::Ratio { numerator, denominator } => {
+                LabValue::Ratio { numerator: *numerator, denominator: *denominator }
+            }
+            InternedLabValue::Comparator { comparator, value } => {
+                LabValue::Comparator { comparator: *comparator, value: value.to_owned() }
+            }
+            InternedLabValue::Concept(concept) => LabValue::Concept(concept.to_owned()),
+            InternedLabValue::String(text) => LabValue::String(text.clone()),
+        }
+    }
+}
+
+/// [`LabResult`] with interned codes and units.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct InternedLabResult {
+    /// LOINC code for the test
+    pub code: InternedConcept,
+    /// Result value
+    pub value: InternedLabValue,
+    /// Reference ranges
+    #[serde(rename = "referenceRange", alias = "reference_range", skip_serializing_if = "Option::is_none")]
+    pub reference_range: Option<Vec<InternedReferenceRange>>,
+    /// HL7 v3 interpretation code (N, L, H, A, HH, LL, POS, ...)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub interpretation: Option<Interpretation>,
+    /// Test method used
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub method: Option<InternedConcept>,
+    /// Instant or period the result applies to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub effective: Option<Effective>,
+}
+
+impl From<&LabResult> for InternedLabResult {
+    fn from(result: &LabResult) -> Self {
+        Self {
+            code: (&result.code).into(),
+            value: (&result.value).into(),
+            reference_range: result
+                .reference_range
+                .as_ref()
+                .map(|ranges| ranges.iter().map(InternedReferenceRange::from).collect()),
+            interpretation: result.interpretation,
+            method: result.method.as_ref().map(InternedConcept::from),
+            effective: result.effective,
+        }
+    }
+}
+
+impl InternedLabResult {
+    /// Regular lab result.
+    pub fn to_owned(&self) -> LabResult {
+        LabResult {
+            code: self.code.to_owned(),
+            value: self.value.to_owned(),
+            reference_range: self
+                .reference_range
+                .as_ref()
+                .map(|ranges| ranges.iter().map(InternedReferenceRange::to_owned).collect()),
+            interpretation: self.interpretation,
+            method: self.method.as_ref().map(InternedConcept::to_owned),
+            effective: self.effective,
+        }
+    }
+}
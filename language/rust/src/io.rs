@@ -0,0 +1,507 @@
+//! Parsing of resources in whatever supported format they arrive in.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//!
+//! [`parse_auto`] is meant for ingestion endpoints that accept "whatever the
+//! partner sends": it sniffs the payload (optionally guided by its media
+//! type), parses every resource it holds into an [`AnyResource`] and reports
+//! the format it found:
+//!
+//! ```
+//! use wellally::io::{parse_auto, Format};
+//!
+//! let body = br#"{"id":"lab-1","patientId":"p-1","issuedAt":"2024-11-02T09:30:00Z","results":[]}
+//! {"i":"pt-1","n":[{"fa":"Silva","g":["Ana"]}],"bd":"1990-04-01"}"#;
+//! let parsed = parse_auto(body, Some("application/x-ndjson"))?;
+//! assert_eq!(parsed.format, Format::Ndjson);
+//! assert_eq!(parsed.resources[1].resource_type(), "Person");
+//! # Ok::<(), wellally::WellAllyError>(())
+//! ```
+//!
+//! [`Bundle`] holds resources to send on: it partitions them per patient and
+//! splits them into chunks that fit an API's entry and size limits.
+//!
+//! The supported formats are JSON (a resource, an array of resources or a
+//! bundle whose `entry` items hold `{"resource": ...}`) and NDJSON, each in
+//! either the canonical or the [wire](crate::wire) profile. XML (including
+//! C-CDA), CBOR and HL7 v2 payloads are recognised but rejected, since the
+//! crate has no reader for them.
+
+use std::collections::HashMap;
+
+use serde::de::Error as _;
+use serde::ser::{Error as _, SerializeStruct};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{Map, Value};
+
+use crate::common::Coding;
+use crate::communication::CommunicationRecord;
+use crate::error::WellAllyError;
+use crate::family_health::FamilyHealthTree;
+use crate::health::Person;
+use crate::imaging_report::ImagingReport;
+use crate::lab_report::LabReport;
+use crate::location::Location;
+use crate::medication::MedicationRecord;
+use crate::questionnaire::{Questionnaire, QuestionnaireResponse};
+use crate::referral::Referral;
+use crate::condition::Condition;
+use crate::allergy::AllergyIntolerance;
+use crate::flag::Flag;
+use crate::cgm::GlucoseSeries;
+use crate::ecg::EcgRecord;
+use crate::consent::ConsentRecord;
+use crate::nephrology::DialysisSession;
+use crate::rehab::RehabSession;
+use crate::wound::WoundAssessment;
+use crate::infusion::InfusionRecord;
+use crate::pathology::PathologyReport;
+use crate::audiology::AudiogramReport;
+use crate::birth::BirthRecord;
+use crate::clearance::ClearanceDocument;
+use crate::travel::TravelHealthRecord;
+use crate::compendium::Compendium;
+use crate::dispense::DispenseRecord;
+use crate::immunization::ImmunizationRecord;
+use crate::relationships::RelatedPerson;
+use crate::resource::Resource;
+use crate::security::SecurityLabeled;
+use crate::task::Task;
+use crate::validation::{self, Validate, ValidationIssue};
+use crate::wire;
+
+/// Container format of a payload read by [`parse_auto`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Format {
+    /// A single JSON resource
+    Json,
+    /// A JSON array of resources
+    JsonArray,
+    /// A JSON bundle whose `entry` items hold the resources
+    Bundle,
+    /// Newline-delimited JSON, one resource per line
+    Ndjson,
+}
+
+/// Resources read by [`parse_auto`] and the format they were found in.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Parsed {
+    /// Detected container format
+    pub format: Format,
+    /// Whether any resource used the compact wire profile
+    pub wire: bool,
+    /// Resources in the order they appeared
+    pub resources: Vec<AnyResource>,
+}
+
+macro_rules! any_resource {
+    ($($ty:ident),* $(,)?) => {
+        /// A resource of any type, as found in mixed payloads.
+        #[derive(Debug, Clone, PartialEq)]
+        pub enum AnyResource {
+            $($ty($ty),)*
+        }
+
+        impl AnyResource {
+            /// Resource type names of every variant, in declaration order
+            pub const RESOURCE_TYPES: &'static [&'static str] = &[$($ty::RESOURCE_TYPE,)*];
+
+            /// Resource type name (e.g., "LabReport")
+            pub fn resource_type(&self) -> &'static str {
+                match self {
+                    $(AnyResource::$ty(_) => $ty::RESOURCE_TYPE,)*
+                }
+            }
+
+            /// Logical identifier of the resource
+            pub fn id(&self) -> &str {
+                match self {
+                    $(AnyResource::$ty(resource) => Resource::id(resource),)*
+                }
+            }
+
+            /// Identifier of the person the resource belongs to
+            pub fn patient_id(&self) -> &str {
+                match self {
+                    $(AnyResource::$ty(resource) => Resource::patient_id(resource),)*
+                }
+            }
+
+            /// Builds the resource from canonical JSON, taking its type from
+            /// `resourceType` or, failing that, from the fields only one type has.
+            pub fn from_json_value(value: &Value) -> Result<Self, WellAllyError> {
+                match value.as_object().and_then(validation::detect_resource_type) {
+                    Some(resource_type) => Self::from_typed_json_value(resource_type, value),
+                    None => Err(WellAllyError::Parse {
+                        resource_type: "AnyResource".to_string(),
+                        path: String::new(),
+                        value: None,
+                        message: "cannot determine resource type".to_string(),
+                    }),
+                }
+            }
+
+            /// Builds a resource of the named type from canonical JSON, for
+            /// containers that carry the type next to the resource.
+            pub fn from_typed_json_value(resource_type: &str, value: &Value) -> Result<Self, WellAllyError> {
+                $(if resource_type == $ty::RESOURCE_TYPE {
+                    return match without_resource_type::<$ty>(value) {
+                        Some(value) => $ty::from_json_value(&value),
+                        None => $ty::from_json_value(value),
+                    }
+                    .map(AnyResource::$ty);
+                })*
+                Err(WellAllyError::Parse {
+                    resource_type: "AnyResource".to_string(),
+                    path: String::new(),
+                    value: Some(Box::new(Value::from(resource_type))),
+                    message: "unknown resource type".to_string(),
+                })
+            }
+        }
+
+        impl Validate for AnyResource {
+            fn validate(&self) -> Vec<ValidationIssue> {
+                match self {
+                    $(AnyResource::$ty(resource) => Validate::validate(resource),)*
+                }
+            }
+        }
+
+        impl SecurityLabeled for AnyResource {
+            fn all_security_labels(&self) -> &[Coding] {
+                match self {
+                    $(AnyResource::$ty(resource) => resource.all_security_labels(),)*
+                }
+            }
+
+            fn security_labels_mut(&mut self) -> &mut Option<Vec<Coding>> {
+                match self {
+                    $(AnyResource::$ty(resource) => resource.security_labels_mut(),)*
+                }
+            }
+        }
+
+        /// Serializes as the wrapped resource with its `resourceType`, so it
+        /// reads back without guessing the type from its fields.
+        impl Serialize for AnyResource {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                let mut value = match self {
+                    $(AnyResource::$ty(resource) => serde_json::to_value(resource),)*
+                }
+                .map_err(S::Error::custom)?;
+                if let Value::Object(map) = &mut value {
+                    map.entry("resourceType").or_insert_with(|| self.resource_type().into());
+                }
+                value.serialize(serializer)
+            }
+        }
+
+        /// Deserializes any resource, detecting its type as
+        /// [`AnyResource::from_json_value`] does.
+        impl<'de> Deserialize<'de> for AnyResource {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let value = Value::deserialize(deserializer)?;
+                AnyResource::from_json_value(&value).map_err(D::Error::custom)
+            }
+        }
+
+        $(impl From<$ty> for AnyResource {
+            fn from(resource: $ty) -> Self {
+                AnyResource::$ty(resource)
+            }
+        })*
+    };
+}
+
+any_resource!(
+    Person,
+    LabReport,
+    ImagingReport,
+    MedicationRecord,
+    FamilyHealthTree,
+    Questionnaire,
+    QuestionnaireResponse,
+    Referral,
+    Task,
+    CommunicationRecord,
+    Location,
+    Condition,
+    AllergyIntolerance,
+    Flag,
+    GlucoseSeries,
+    EcgRecord,
+    ConsentRecord,
+    DialysisSession,
+    RehabSession,
+    WoundAssessment,
+    InfusionRecord,
+    PathologyReport,
+    AudiogramReport,
+    BirthRecord,
+    ClearanceDocument,
+    TravelHealthRecord,
+    Compendium,
+    DispenseRecord,
+    ImmunizationRecord,
+    RelatedPerson,
+);
+
+/// Copy of `value` without the `resourceType` [`AnyResource`] writes, for
+/// types that do not keep it as a field; `None` when there is nothing to drop.
+fn without_resource_type<R: Resource>(value: &Value) -> Option<Value> {
+    let map = value.as_object().filter(|map| map.get("resourceType").is_some_and(|name| name == R::RESOURCE_TYPE))?;
+    if R::RESOURCE_TYPE == Person::RESOURCE_TYPE {
+        return None;
+    }
+    let mut map = map.clone();
+    map.remove("resourceType");
+    Some(Value::Object(map))
+}
+
+/// Resources exchanged together. Serializes as a bundle of type
+/// `collection` whose `entry` items hold `{"resource": ...}`, which
+/// [`parse_auto`] reads back.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Bundle {
+    /// Resources in entry order
+    pub resources: Vec<AnyResource>,
+}
+
+impl Bundle {
+    /// Bundle of `resources`.
+    pub fn new(resources: Vec<AnyResource>) -> Self {
+        Bundle { resources }
+    }
+
+    /// Number of entries.
+    pub fn len(&self) -> usize {
+        self.resources.len()
+    }
+
+    /// Whether the bundle has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.resources.is_empty()
+    }
+
+    /// One bundle per patient, keyed by Person.id, each keeping the entry
+    /// order. Resources that belong to no patient (questionnaires,
+    /// locations) are keyed by the empty string.
+    pub fn partition_by_patient(&self) -> HashMap<String, Bundle> {
+        let mut partitions: HashMap<String, Bundle> = HashMap::new();
+        for resource in &self.resources {
+            partitions.entry(resource.patient_id().to_string()).or_default().resources.push(resource.clone());
+        }
+        partitions
+    }
+
+    /// Consecutive chunks of the entries for APIs with payload limits: each
+    /// has at most `max_entries` entries (at least one) and serializes to at
+    /// most `max_bytes` bytes. Fails when a single entry does not fit in
+    /// `max_bytes`.
+    pub fn split(&self, max_entries: Option<usize>, max_bytes: Option<usize>) -> Result<Vec<Bundle>, WellAllyError> {
+        let max_entries = max_entries.unwrap_or(usize::MAX).max(1);
+        let envelope = serialized_len(&Bundle::default())?;
+        let mut chunks: Vec<Bundle> = Vec::new();
+        let mut size = 0;
+        for (index, resource) in self.resources.iter().enumerate() {
+            let entry = serialized_len(&Entry { resource })?;
+            if let Some(max_bytes) = max_bytes.filter(|max_bytes| envelope + entry > *max_bytes) {
+                return Err(WellAllyError::Conversion {
+                    resource_type: "Bundle".to_string(),
+                    path: format!("entry[{}]", index),
+                    value: None,
+                    message: format!("entry of {} bytes does not fit in a bundle of {} bytes", entry, max_bytes),
+                });
+            }
+            let fits = chunks.last().is_some_and(|chunk| {
+                chunk.len() < max_entries && max_bytes.is_none_or(|max_bytes| size + 1 + entry <= max_bytes)
+            });
+            if fits {
+                size += 1 + entry;
+            } else {
+                chunks.push(Bundle::default());
+                size = envelope + entry;
+            }
+            chunks.last_mut().expect("a chunk was just pushed").resources.push(resource.clone());
+        }
+        Ok(chunks)
+    }
+}
+
+/// Bundle entry as serialized.
+#[derive(Serialize)]
+struct Entry<'a> {
+    resource: &'a AnyResource,
+}
+
+fn serialized_len<T: Serialize>(value: &T) -> Result<usize, WellAllyError> {
+    serde_json::to_vec(value).map(|bytes| bytes.len()).map_err(|err| WellAllyError::Conversion {
+        resource_type: "Bundle".to_string(),
+        path: String::new(),
+        value: None,
+        message: err.to_string(),
+    })
+}
+
+impl Serialize for Bundle {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut bundle = serializer.serialize_struct("Bundle", 3)?;
+        bundle.serialize_field("resourceType", "Bundle")?;
+        bundle.serialize_field("type", "collection")?;
+        let entries: Vec<Entry<'_>> = self.resources.iter().map(|resource| Entry { resource }).collect();
+        bundle.serialize_field("entry", &entries)?;
+        bundle.end()
+    }
+}
+
+impl From<Parsed> for Bundle {
+    fn from(parsed: Parsed) -> Self {
+        Bundle::new(parsed.resources)
+    }
+}
+
+impl FromIterator<AnyResource> for Bundle {
+    fn from_iter<I: IntoIterator<Item = AnyResource>>(resources: I) -> Self {
+        Bundle::new(resources.into_iter().collect())
+    }
+}
+
+/// Detects the format of `bytes` and parses every resource it holds.
+///
+/// `hint` is the payload's media type (e.g., a `Content-Type` header). NDJSON
+/// types (`application/x-ndjson`, `application/fhir+ndjson`, ...) read the
+/// payload line by line even when it holds a single resource; XML, CBOR and
+/// HL7 v2 types are rejected; anything else is sniffed from the content.
+/// Errors of a record in an array, bundle or NDJSON stream are located with
+/// its position (`[2].patientId`, `entry[0].resource.results`).
+pub fn parse_auto(bytes: &[u8], hint: Option<&str>) -> Result<Parsed, WellAllyError> {
+    let text = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+    let start = text.iter().position(|b| !b.is_ascii_whitespace()).unwrap_or(text.len());
+    let text = &text[start..];
+
+    let hinted = hint.map(media_type);
+    if let Some(name) = hinted.as_deref().and_then(unsupported_media_type).or_else(|| sniff_unsupported(text)) {
+        return Err(unsupported(name));
+    }
+
+    let mut values = read_values(text)?;
+    let ndjson = hinted.is_some_and(|essence| essence.ends_with("ndjson") || essence.ends_with("jsonl"));
+    let (format, records): (_, Vec<_>) = if ndjson || values.len() != 1 {
+        (Format::Ndjson, values.into_iter().enumerate().map(indexed).collect())
+    } else {
+        match values.remove(0) {
+            Value::Array(items) => (Format::JsonArray, items.into_iter().enumerate().map(indexed).collect()),
+            Value::Object(mut map) if is_bundle(&map) => {
+                let Some(Value::Array(entries)) = map.remove("entry") else { unreachable!() };
+                let records = entries.into_iter().enumerate().map(|(index, mut entry)| match entry.get_mut("resource") {
+                    Some(resource) => (format!("entry[{}].resource", index), resource.take()),
+                    None => (format!("entry[{}]", index), entry),
+                });
+                (Format::Bundle, records.collect())
+            }
+            value => (Format::Json, vec![(String::new(), value)]),
+        }
+    };
+
+    let mut wire = false;
+    let resources = records
+        .into_iter()
+        .map(|(prefix, value)| {
+            let value = if is_wire(&value) {
+                wire = true;
+                wire::decode(&value)
+            } else {
+                value
+            };
+            AnyResource::from_json_value(&value).map_err(|err| located(err, &prefix))
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(Parsed { format, wire, resources })
+}
+
+/// Lower-cased media type without parameters.
+fn media_type(hint: &str) -> String {
+    hint.split(';').next().unwrap_or_default().trim().to_ascii_lowercase()
+}
+
+fn unsupported_media_type(essence: &str) -> Option<&'static str> {
+    if essence.ends_with("xml") {
+        Some("XML")
+    } else if essence.ends_with("cbor") {
+        Some("CBOR")
+    } else if essence.contains("hl7v2") || essence.contains("hl7-v2") {
+        Some("HL7 v2")
+    } else {
+        None
+    }
+}
+
+fn sniff_unsupported(text: &[u8]) -> Option<&'static str> {
+    match text.first()? {
+        b'<' => Some("XML"),
+        b'{' | b'[' => None,
+        _ if text.starts_with(b"MSH|") => Some("HL7 v2"),
+        // JSON text only starts with an object or array here; a leading
+        // control or non-ASCII byte means a binary encoding.
+        byte if !byte.is_ascii() || byte.is_ascii_control() => Some("CBOR"),
+        _ => None,
+    }
+}
+
+fn unsupported(name: &str) -> WellAllyError {
+    WellAllyError::Parse {
+        resource_type: "AnyResource".to_string(),
+        path: String::new(),
+        value: None,
+        message: format!("{} input is not supported", name),
+    }
+}
+
+/// Every JSON value in `text`, which may hold one or several.
+fn read_values(text: &[u8]) -> Result<Vec<Value>, WellAllyError> {
+    serde_json::Deserializer::from_slice(text)
+        .into_iter::<Value>()
+        .collect::<Result<_, _>>()
+        .map_err(|err| WellAllyError::Parse {
+            resource_type: "AnyResource".to_string(),
+            path: String::new(),
+            value: None,
+            message: err.to_string(),
+        })
+}
+
+fn indexed((index, value): (usize, Value)) -> (String, Value) {
+    (format!("[{}]", index), value)
+}
+
+/// Whether an object is a bundle rather than a resource with an `entry` field.
+fn is_bundle(map: &Map<String, Value>) -> bool {
+    let declared = map.get("resourceType").or_else(|| map.get("rt"));
+    declared.is_none_or(|name| name == "Bundle") && map.get("entry").is_some_and(Value::is_array)
+}
+
+/// Whether a record uses wire aliases instead of canonical field names.
+fn is_wire(value: &Value) -> bool {
+    value.as_object().is_some_and(|map| {
+        (map.contains_key("i") && !map.contains_key("id")) || (map.contains_key("pi") && !map.contains_key("probandId"))
+    })
+}
+
+/// Prefixes the error path with the position of the record in the payload.
+pub(crate) fn located(mut err: WellAllyError, prefix: &str) -> WellAllyError {
+    match &mut err {
+        WellAllyError::Parse { path, .. }
+        | WellAllyError::Validation { path, .. }
+        | WellAllyError::Conversion { path, .. }
+        | WellAllyError::Terminology { path, .. } => {
+            *path = if path.is_empty() || prefix.is_empty() || path.starts_with('[') {
+                format!("{}{}", prefix, path)
+            } else {
+                format!("{}.{}", prefix, path)
+            };
+        }
+    }
+    err
+}
@@ -0,0 +1,176 @@
+//! International Patient Summary (IPS) documents.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//!
+//! The International Patient Summary (ISO 27269, HL7 FHIR IPS) is the
+//! minimal record a clinician abroad needs to treat a patient. [`generate`]
+//! builds one from a [`Person`] and their [`PatientChart`] as an
+//! [`IpsComposition`] with the sections IPS defines, each under its LOINC
+//! section code:
+//!
+//! - problems: the active conditions, reconciled as [`problem_list::update`] does;
+//! - medications: the medications current on the day of the document;
+//! - allergies: the active allergies and intolerances;
+//! - results: the latest report of each panel, latest first;
+//! - immunizations: the doses given, latest first.
+//!
+//! Problems, medications and allergies are required: when the chart has
+//! none, the section says so with the `unavailable` empty reason. Results
+//! and immunizations are left out when empty. The composition serializes as
+//! JSON with the entries as this crate's resources; with the `fhir` feature,
+//! `IpsComposition::to_fhir` writes it as a FHIR R4 document bundle.
+//!
+//! ```
+//! use wellally::catalog::{concept, SNOMED_CT};
+//! use wellally::chart::PatientChart;
+//! use wellally::ips::{self, PROBLEMS_SECTION};
+//! use wellally::{Condition, Person, Resource};
+//!
+//! let person = Person::from_json(r#"{"id":"p-1","name":[{"family":"Wang","given":["Min"]}],"birthDate":"1960"}"#)?;
+//! let mut chart = PatientChart::new("p-1");
+//! chart.conditions.push(Condition::new("c-1", "p-1", concept(SNOMED_CT, "38341003", "Hypertension")));
+//!
+//! let document = ips::generate_at(&person, &chart, "2024-06-01T09:00:00Z".parse().unwrap());
+//! assert_eq!(document.section(PROBLEMS_SECTION).unwrap().entries.len(), 1);
+//! assert_eq!(document.sections.len(), 3);
+//! # Ok::<(), wellally::WellAllyError>(())
+//! ```
+
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::catalog::{self, LOINC};
+use crate::chart::PatientChart;
+use crate::common::Coding;
+use crate::datetime::FlexibleDateTime;
+use crate::health::Person;
+use crate::io::AnyResource;
+use crate::problem_list;
+
+#[cfg(feature = "fhir")]
+mod fhir;
+
+/// LOINC code of the patient summary document
+pub const PATIENT_SUMMARY: &str = "60591-5";
+/// LOINC code of the problems section
+pub const PROBLEMS_SECTION: &str = "11450-4";
+/// LOINC code of the medications section
+pub const MEDICATIONS_SECTION: &str = "10160-0";
+/// LOINC code of the allergies and intolerances section
+pub const ALLERGIES_SECTION: &str = "48765-2";
+/// LOINC code of the results section
+pub const RESULTS_SECTION: &str = "30954-2";
+/// LOINC code of the immunizations section
+pub const IMMUNIZATIONS_SECTION: &str = "11369-6";
+/// FHIR list empty reason code system
+pub const LIST_EMPTY_REASON: &str = "http://terminology.hl7.org/CodeSystem/list-empty-reason";
+
+/// A patient summary: the patient and the IPS sections.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IpsComposition {
+    /// Document type, LOINC 60591-5
+    pub r#type: Coding,
+    pub title: String,
+    /// When the summary was generated
+    pub date: FlexibleDateTime,
+    pub patient: Person,
+    /// Sections in IPS order
+    pub sections: Vec<IpsSection>,
+}
+
+/// One section of a patient summary.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IpsSection {
+    pub title: String,
+    /// LOINC section code (e.g., 11450-4 for problems)
+    pub code: Coding,
+    /// Resources the section lists
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub entries: Vec<AnyResource>,
+    /// Why a required section has no entries
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub empty_reason: Option<Coding>,
+}
+
+impl IpsSection {
+    fn new(code: &str, title: &str, entries: Vec<AnyResource>, required: bool) -> Option<Self> {
+        let empty_reason = (required && entries.is_empty())
+            .then(|| catalog::coding(LIST_EMPTY_REASON, "unavailable", "Unavailable"));
+        (required || !entries.is_empty()).then(|| IpsSection {
+            title: title.into(),
+            code: catalog::coding(LOINC, code, title),
+            entries,
+            empty_reason,
+        })
+    }
+}
+
+impl IpsComposition {
+    /// The section with LOINC `code`, if the document has it.
+    pub fn section(&self, code: &str) -> Option<&IpsSection> {
+        self.sections.iter().find(|section| section.code.code == code)
+    }
+}
+
+/// Patient summary of `person` from `chart`, generated now.
+pub fn generate(person: &Person, chart: &PatientChart) -> IpsComposition {
+    generate_at(person, chart, chrono::Utc::now().into())
+}
+
+/// Patient summary of `person` from `chart` as of `date`: medications must
+/// be current, and reports issued and doses given, by then.
+pub fn generate_at(person: &Person, chart: &PatientChart, date: FlexibleDateTime) -> IpsComposition {
+    let at = date.to_utc();
+    let day = at.date_naive();
+
+    let problems = problem_list::update(Vec::new(), chart.conditions.clone())
+        .into_iter()
+        .filter(|condition| condition.is_active())
+        .map(AnyResource::from)
+        .collect();
+    let medications = chart
+        .medications
+        .iter()
+        .filter(|medication| medication.is_active_on(day))
+        .cloned()
+        .map(AnyResource::from)
+        .collect();
+    let allergies =
+        chart.allergies.iter().filter(|allergy| allergy.is_active()).cloned().map(AnyResource::from).collect();
+
+    let mut reports: Vec<_> = chart.lab_reports.iter().filter(|report| report.issued_at.to_utc() <= at).collect();
+    reports.sort_by_key(|report| core::cmp::Reverse(report.issued_at.to_utc()));
+    let mut panels = HashSet::new();
+    let results = reports
+        .into_iter()
+        .filter(|report| match report.panel.as_ref().and_then(|panel| panel.coding.first()) {
+            Some(panel) => panels.insert((panel.system.as_str(), panel.code.as_str())),
+            None => true,
+        })
+        .cloned()
+        .map(AnyResource::from)
+        .collect();
+
+    let mut doses: Vec<_> = chart.immunizations.iter().filter(|dose| dose.administered_on <= day).collect();
+    doses.sort_by_key(|dose| core::cmp::Reverse(dose.administered_on));
+    let immunizations = doses.into_iter().cloned().map(AnyResource::from).collect();
+
+    let sections = [
+        IpsSection::new(PROBLEMS_SECTION, "Problem list", problems, true),
+        IpsSection::new(MEDICATIONS_SECTION, "Medication summary", medications, true),
+        IpsSection::new(ALLERGIES_SECTION, "Allergies and intolerances", allergies, true),
+        IpsSection::new(RESULTS_SECTION, "Results", results, false),
+        IpsSection::new(IMMUNIZATIONS_SECTION, "Immunizations", immunizations, false),
+    ];
+    IpsComposition {
+        r#type: catalog::coding(LOINC, PATIENT_SUMMARY, "Patient summary Document"),
+        title: "International Patient Summary".into(),
+        date,
+        patient: person.clone(),
+        sections: sections.into_iter().flatten().collect(),
+    }
+}
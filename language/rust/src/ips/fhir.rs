@@ -0,0 +1,331 @@
+//! FHIR R4 document bundles of patient summaries.
+
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+
+use super::{IpsComposition, IpsSection};
+use crate::allergy::AllergyIntolerance;
+use crate::catalog::UCUM;
+use crate::common::{CodeableConcept, Coding, Effective, Quantity};
+use crate::condition::Condition;
+use crate::health::Person;
+use crate::immunization::ImmunizationRecord;
+use crate::io::AnyResource;
+use crate::lab_report::{LabReport, LabValue};
+use crate::medication::MedicationRecord;
+use crate::resource::Resource;
+
+/// Base of the `fullUrl` of bundle entries
+const BASE: &str = "https://www.wellally.tech/fhir";
+
+impl IpsComposition {
+    /// The summary as a FHIR R4 `document` bundle: the Composition first,
+    /// then the Patient and the resources its sections reference. Lab
+    /// results become one Observation each; medications become
+    /// MedicationStatements. Section narratives list the entries' displays.
+    pub fn to_fhir(&self) -> Value {
+        let patient_id = &self.patient.id;
+        let composition_id = format!("ips-{}", patient_id);
+        let mut entries = vec![entry("Patient", patient(&self.patient))];
+        let mut sections = Vec::new();
+        for section in &self.sections {
+            let mut references = Vec::new();
+            let mut items = Vec::new();
+            for resource in &section.entries {
+                for (resource_type, fhir, label) in convert(resource) {
+                    let id = fhir["id"].as_str().unwrap_or_default();
+                    references.push(json!({ "reference": format!("{}/{}", resource_type, id) }));
+                    items.push(label);
+                    entries.push(entry(resource_type, fhir));
+                }
+            }
+            sections.push(section_json(section, references, &items));
+        }
+        let composition = json!({
+            "resourceType": "Composition",
+            "id": composition_id,
+            "status": "final",
+            "type": { "coding": [&self.r#type] },
+            "subject": { "reference": format!("Patient/{}", patient_id) },
+            "date": &self.date,
+            "author": [{ "display": "WellAlly" }],
+            "title": &self.title,
+            "section": sections,
+        });
+        entries.insert(0, entry("Composition", composition));
+        json!({
+            "resourceType": "Bundle",
+            "id": composition_id,
+            "identifier": { "system": format!("{}/ips", BASE), "value": composition_id },
+            "type": "document",
+            "timestamp": &self.date,
+            "entry": entries,
+        })
+    }
+}
+
+fn entry(resource_type: &str, resource: Value) -> Value {
+    let id = resource["id"].as_str().unwrap_or_default().to_string();
+    json!({ "fullUrl": format!("{}/{}/{}", BASE, resource_type, id), "resource": resource })
+}
+
+fn section_json(section: &IpsSection, references: Vec<Value>, items: &[String]) -> Value {
+    let list: String = items.iter().map(|item| format!("<li>{}</li>", escape(item))).collect();
+    let div = match list.is_empty() {
+        true => "<div xmlns=\"http://www.w3.org/1999/xhtml\">No information available</div>".to_string(),
+        false => format!("<div xmlns=\"http://www.w3.org/1999/xhtml\"><ul>{}</ul></div>", list),
+    };
+    let mut fhir = json!({
+        "title": &section.title,
+        "code": { "coding": [&section.code] },
+        "text": { "status": "generated", "div": div },
+    });
+    if !references.is_empty() {
+        fhir["entry"] = Value::Array(references);
+    }
+    if let Some(reason) = &section.empty_reason {
+        fhir["emptyReason"] = json!({ "coding": [reason] });
+    }
+    fhir
+}
+
+/// FHIR resources for one entry, with their type and a label for the narrative.
+fn convert(resource: &AnyResource) -> Vec<(&'static str, Value, String)> {
+    match resource {
+        AnyResource::Condition(condition) => vec![("Condition", condition_json(condition), label(&condition.code))],
+        AnyResource::MedicationRecord(medication) => {
+            let name = medication.medication.display.clone().unwrap_or_else(|| medication.medication.code.clone());
+            vec![("MedicationStatement", medication_json(medication), name)]
+        }
+        AnyResource::AllergyIntolerance(allergy) => {
+            vec![("AllergyIntolerance", allergy_json(allergy), label(&allergy.code))]
+        }
+        AnyResource::LabReport(report) => observations(report),
+        AnyResource::ImmunizationRecord(dose) => {
+            let name = dose.vaccine.display.clone().unwrap_or_else(|| dose.vaccine.code.clone());
+            vec![("Immunization", immunization_json(dose), format!("{} ({})", name, dose.administered_on))]
+        }
+        _ => Vec::new(),
+    }
+}
+
+fn value<T: Serialize>(value: &T) -> Value {
+    serde_json::to_value(value).unwrap_or(Value::Null)
+}
+
+/// CodeableConcept of one code of a FHIR code system (e.g., condition-clinical).
+fn status<T: Serialize>(system: &str, status: &T) -> Value {
+    let system = format!("http://terminology.hl7.org/CodeSystem/{}", system);
+    json!({ "coding": [{ "system": system, "code": value(status) }] })
+}
+
+fn subject(resource: &impl Resource) -> Value {
+    json!({ "reference": format!("Patient/{}", resource.patient_id()) })
+}
+
+fn label(concept: &CodeableConcept) -> String {
+    concept
+        .text
+        .clone()
+        .or_else(|| concept.coding.iter().find_map(|coding| coding.display.clone()))
+        .or_else(|| concept.coding.first().map(|coding| coding.code.clone()))
+        .unwrap_or_default()
+}
+
+fn concept_of(coding: &Coding) -> Value {
+    json!({ "coding": [coding] })
+}
+
+fn quantity(quantity: &Quantity) -> Value {
+    let mut fhir = json!({ "value": quantity.value, "unit": &quantity.unit, "system": UCUM, "code": &quantity.unit });
+    if let Some(comparator) = quantity.comparator {
+        fhir["comparator"] = json!(comparator.as_str());
+    }
+    fhir
+}
+
+/// Inserts the fields of `optional` that are set.
+fn with(mut fhir: Value, optional: Vec<(&str, Option<Value>)>) -> Value {
+    let object: &mut Map<String, Value> = fhir.as_object_mut().expect("resources are objects");
+    for (key, field) in optional {
+        if let Some(field) = field {
+            object.insert(key.to_string(), field);
+        }
+    }
+    fhir
+}
+
+fn patient(person: &Person) -> Value {
+    with(
+        json!({ "resourceType": "Patient", "id": &person.id, "name": &person.name, "birthDate": &person.birth_date }),
+        vec![
+            ("identifier", person.identifier.as_ref().map(value)),
+            ("gender", person.gender.as_ref().map(value)),
+            ("telecom", person.telecom.as_ref().map(value)),
+            ("address", person.address.as_ref().map(value)),
+        ],
+    )
+}
+
+fn condition_json(condition: &Condition) -> Value {
+    with(
+        json!({
+            "resourceType": "Condition",
+            "id": &condition.id,
+            "clinicalStatus": status("condition-clinical", &condition.clinical_status),
+            "code": &condition.code,
+            "subject": subject(condition),
+        }),
+        vec![
+            ("verificationStatus", condition.verification_status.map(|v| status("condition-ver-status", &v))),
+            ("severity", condition.severity.as_ref().map(value)),
+            ("onsetDateTime", condition.onset.as_ref().map(value)),
+            ("recordedDate", condition.recorded_date.as_ref().map(value)),
+        ],
+    )
+}
+
+fn medication_json(medication: &MedicationRecord) -> Value {
+    let mut dosage = json!({
+        "route": concept_of(&Coding {
+            system: medication.route.system.clone(),
+            code: medication.route.code.clone(),
+            display: medication.route.display.clone(),
+        }),
+        "doseAndRate": [{ "doseQuantity": {
+            "value": medication.dosage.value,
+            "unit": &medication.dosage.unit,
+            "system": UCUM,
+            "code": &medication.dosage.unit,
+        } }],
+    });
+    if let Some(text) = medication.instructions.as_ref().or(medication.frequency.as_ref()) {
+        dosage["text"] = json!(text);
+    }
+    if medication.is_prn {
+        dosage["asNeededBoolean"] = json!(true);
+    }
+    let mut period = json!({ "start": medication.start_date });
+    if let Some(end) = medication.last_date() {
+        period["end"] = json!(end);
+    }
+    with(
+        json!({
+            "resourceType": "MedicationStatement",
+            "id": &medication.id,
+            "status": "active",
+            "medicationCodeableConcept": concept_of(&medication.medication),
+            "subject": subject(medication),
+            "effectivePeriod": period,
+            "dosage": [dosage],
+        }),
+        vec![("reasonCode", medication.indication.as_ref().map(|indication| json!([indication])))],
+    )
+}
+
+fn allergy_json(allergy: &AllergyIntolerance) -> Value {
+    let reactions = allergy.reactions.as_ref().map(|reactions| {
+        reactions
+            .iter()
+            .map(|reaction| {
+                with(
+                    json!({ "manifestation": &reaction.manifestation }),
+                    vec![
+                        ("severity", reaction.severity.as_ref().map(value)),
+                        ("description", reaction.description.as_ref().map(value)),
+                    ],
+                )
+            })
+            .collect::<Vec<_>>()
+    });
+    with(
+        json!({
+            "resourceType": "AllergyIntolerance",
+            "id": &allergy.id,
+            "code": &allergy.code,
+            "patient": subject(allergy),
+        }),
+        vec![
+            ("clinicalStatus", allergy.clinical_status.map(|s| status("allergyintolerance-clinical", &s))),
+            ("verificationStatus", allergy.verification_status.map(|s| status("allergyintolerance-verification", &s))),
+            ("type", allergy.allergy_type.as_ref().map(value)),
+            ("category", allergy.category.as_ref().map(value)),
+            ("criticality", allergy.criticality.as_ref().map(value)),
+            ("onsetDateTime", allergy.onset.as_ref().map(value)),
+            ("reaction", reactions.map(Value::Array)),
+        ],
+    )
+}
+
+/// One Observation per result, with ids `<report id>-<index>`.
+fn observations(report: &LabReport) -> Vec<(&'static str, Value, String)> {
+    report
+        .results
+        .iter()
+        .enumerate()
+        .map(|(i, result)| {
+            let (key, fhir_value) = match &result.value {
+                LabValue::Quantity(q) => ("valueQuantity", quantity(q)),
+                LabValue::Comparator { comparator, value } => {
+                    let mut limited = quantity(value);
+                    limited["comparator"] = json!(comparator.as_str());
+                    ("valueQuantity", limited)
+                }
+                LabValue::Ratio { numerator, denominator } => {
+                    let ratio = json!({ "numerator": { "value": numerator }, "denominator": { "value": denominator } });
+                    ("valueRatio", ratio)
+                }
+                LabValue::Concept(concept) => ("valueCodeableConcept", value(concept)),
+                LabValue::String(text) => ("valueString", json!(text)),
+            };
+            let interpretation = result.interpretation.as_ref().map(|interpretation| {
+                json!([status("v3-ObservationInterpretation", interpretation)])
+            });
+            let mut fhir = with(
+                json!({
+                    "resourceType": "Observation",
+                    "id": format!("{}-{}", report.id, i),
+                    "status": "final",
+                    "category": [status("observation-category", &"laboratory")],
+                    "code": &result.code,
+                    "subject": subject(report),
+                    "issued": report.issued_at.to_utc(),
+                }),
+                vec![("interpretation", interpretation)],
+            );
+            match report.effective_of(result) {
+                Effective::Instant(instant) => fhir["effectiveDateTime"] = value(&instant),
+                Effective::Period(period) => fhir["effectivePeriod"] = value(&period),
+            }
+            fhir[key] = fhir_value;
+            ("Observation", fhir, label(&result.code))
+        })
+        .collect()
+}
+
+fn immunization_json(dose: &ImmunizationRecord) -> Value {
+    let manufacturer =
+        dose.manufacturer.as_ref().map(|mvx| json!({ "display": mvx.display.as_deref().unwrap_or(&mvx.code) }));
+    with(
+        json!({
+            "resourceType": "Immunization",
+            "id": &dose.id,
+            "status": "completed",
+            "vaccineCode": concept_of(&dose.vaccine),
+            "patient": subject(dose),
+            "occurrenceDateTime": dose.administered_on,
+        }),
+        vec![
+            ("lotNumber", dose.lot_number.as_ref().map(value)),
+            ("expirationDate", dose.expiration_date.as_ref().map(value)),
+            ("manufacturer", manufacturer),
+            ("site", dose.site.as_ref().map(concept_of)),
+            ("doseQuantity", dose.dose_quantity.as_ref().map(quantity)),
+            ("protocolApplied", dose.dose_number.map(|number| json!([{ "doseNumberPositiveInt": number }]))),
+        ],
+    )
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
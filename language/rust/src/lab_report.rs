@@ -4,12 +4,27 @@
 //! Website: https://www.wellally.tech/
 //! Schema: https://wellall.health/schemas/lab-report/v0.1.0
 
-use serde::{Deserialize, Serialize};
-use chrono::{DateTime, Utc};
-use crate::common::{CodeableConcept, Quantity, ReferenceRange, Coding};
+use alloc::{string::String, vec, vec::Vec};
+use core::fmt;
+use core::marker::PhantomData;
+use serde::de::value::{MapAccessDeserializer, SeqAccessDeserializer};
+use serde::de::{MapAccess, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize};
+use crate::common::{Annotation, CodeableConcept, Effective, Quantity, Reference, ReferenceRange, ReferenceRangeType, Coding};
+pub use crate::common::Comparator;
+use crate::datetime::FlexibleDateTime;
+use crate::extension::Extension;
 
-/// Lab result interpretation
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub mod import;
+pub mod merge;
+
+pub use merge::dedupe;
+
+/// Lab result interpretation (HL7 v3 ObservationInterpretation)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "async-graphql", derive(async_graphql::Enum))]
 pub enum Interpretation {
     /// Normal
     N,
@@ -19,64 +34,354 @@ pub enum Interpretation {
     H,
     /// Abnormal
     A,
+    /// Critically low
+    LL,
+    /// Critically high
+    HH,
+    /// Critically abnormal
+    AA,
+    /// Significantly low
+    LU,
+    /// Significantly high
+    HU,
+    /// Below the measurable range
+    #[serde(rename = "<")]
+    OffScaleLow,
+    /// Above the measurable range
+    #[serde(rename = ">")]
+    OffScaleHigh,
+    /// Better than the previous result
+    B,
+    /// Worse than the previous result
+    W,
+    /// Significant change up
+    U,
+    /// Significant change down
+    D,
+    /// Susceptible
+    S,
+    /// Resistant
+    R,
+    /// Intermediate susceptibility
+    I,
+    /// Moderately susceptible
+    MS,
+    /// Very susceptible
+    VS,
+    /// Non-susceptible
+    NS,
+    /// Susceptible-dose dependent
+    SDD,
+    /// Synergy resistant
+    #[serde(rename = "SYN-R")]
+    SynR,
+    /// Synergy susceptible
+    #[serde(rename = "SYN-S")]
+    SynS,
+    /// No CLSI defined breakpoint
+    NCL,
+    /// Positive
+    POS,
+    /// Negative
+    NEG,
+    /// Indeterminate
+    IND,
+    /// Equivocal
+    E,
+    /// Detected
+    DET,
+    /// Not detected
+    ND,
+    /// Reactive
+    RR,
+    /// Weakly reactive
+    WR,
+    /// Non-reactive
+    NR,
+    /// Expected
+    EXP,
+    /// Unexpected
+    UNE,
+    /// Outside the threshold
+    EX,
+    /// Above the high threshold
+    HX,
+    /// Below the low threshold
+    LX,
+    /// Carrier
+    CAR,
+    /// Insufficient evidence
+    IE,
+}
+
+impl Interpretation {
+    /// Whether the result is outside the normal range or otherwise flagged
+    /// abnormal (A, L, H and their critical, significant and off-scale forms).
+    pub fn is_abnormal(&self) -> bool {
+        use Interpretation::*;
+        matches!(self, A | L | H | AA | LL | HH | LU | HU | OffScaleLow | OffScaleHigh)
+    }
+
+    /// Whether the result is at a critical level (AA, LL, HH).
+    pub fn is_critical(&self) -> bool {
+        matches!(self, Interpretation::AA | Interpretation::LL | Interpretation::HH)
+    }
 }
 
 /// Lab facility information.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "FacilityInput")
+)]
 pub struct Facility {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub id: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
+    /// Location resource of the facility
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<Reference>,
 }
 
 /// Specimen information.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "SpecimenInput")
+)]
 pub struct Specimen {
     #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "async-graphql", graphql(name = "type"))]
     pub specimen_type: Option<Coding>,
-    #[serde(rename = "collectedAt", skip_serializing_if = "Option::is_none")]
-    pub collected_at: Option<DateTime<Utc>>,
+    #[serde(rename = "collectedAt", alias = "collected_at", skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "utoipa", schema(value_type = Option<String>))]
+    pub collected_at: Option<FlexibleDateTime>,
 }
 
-/// Lab result value (can be Quantity, CodeableConcept, or String)
+/// Lab result value (Quantity, ratio, limited quantity, CodeableConcept, or String)
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
 #[serde(untagged)]
 pub enum LabValue {
     Quantity(Quantity),
+    /// Ratio such as a titer ("1:128")
+    Ratio { numerator: f64, denominator: f64 },
+    /// Quantity beyond a detection or reporting limit ("<5", ">1000 U/L")
+    Comparator { comparator: Comparator, value: Quantity },
     Concept(CodeableConcept),
     String(String),
 }
 
+#[cfg(feature = "async-graphql")]
+async_graphql::scalar!(
+    LabValue,
+    "LabValue",
+    "Lab result value: a Quantity, ratio, limited quantity, CodeableConcept or string, as JSON"
+);
+
+impl LabValue {
+    /// Reads the string forms of ratios ("1:128") and limited quantities
+    /// ("<5", ">= 1000 U/L", "≤0.1 ng/mL"); anything else stays a string.
+    /// A limit without a unit gets the UCUM unity "1".
+    pub fn parse(text: &str) -> LabValue {
+        let text = text.trim();
+        if let Some((numerator, denominator)) = text.split_once(':') {
+            if let (Ok(numerator), Ok(denominator)) = (numerator.trim().parse(), denominator.trim().parse()) {
+                return LabValue::Ratio { numerator, denominator };
+            }
+        }
+        let limit = [
+            ("<=", Comparator::LessOrEqual),
+            ("≤", Comparator::LessOrEqual),
+            (">=", Comparator::GreaterOrEqual),
+            ("≥", Comparator::GreaterOrEqual),
+            ("<", Comparator::LessThan),
+            (">", Comparator::GreaterThan),
+        ]
+        .into_iter()
+        .find_map(|(symbol, comparator)| Some((comparator, text.strip_prefix(symbol)?.trim_start())));
+        if let Some((comparator, rest)) = limit {
+            let end = rest.find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '-')).unwrap_or(rest.len());
+            if let Ok(value) = rest[..end].parse() {
+                let unit = rest[end..].trim();
+                let unit = if unit.is_empty() { "1" } else { unit };
+                return LabValue::Comparator { comparator, value: Quantity::new(value, unit) };
+            }
+        }
+        LabValue::String(text.into())
+    }
+
+    /// The value as a number, for trending and range checks: the quantity,
+    /// the limit of a limited quantity, or numerator ÷ denominator of a ratio.
+    /// Strings are read with [`LabValue::parse`] first.
+    pub fn numeric_value(&self) -> Option<f64> {
+        match self {
+            LabValue::Quantity(quantity) | LabValue::Comparator { value: quantity, .. } => Some(quantity.value),
+            LabValue::Ratio { numerator, denominator } => {
+                Some(numerator / denominator).filter(|ratio| ratio.is_finite())
+            }
+            LabValue::Concept(_) => None,
+            LabValue::String(text) => match LabValue::parse(text) {
+                LabValue::String(_) => None,
+                parsed => parsed.numeric_value(),
+            },
+        }
+    }
+}
+
 /// Individual lab test result.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "LabResultInput")
+)]
 pub struct LabResult {
     /// LOINC code for the test
     pub code: CodeableConcept,
     /// Result value (Quantity, CodeableConcept, or string)
     pub value: LabValue,
-    /// Normal reference range
-    #[serde(rename = "referenceRange", skip_serializing_if = "Option::is_none")]
-    pub reference_range: Option<ReferenceRange>,
-    /// N (normal), L (low), H (high), A (abnormal)
+    /// Reference ranges; a single range object is also accepted
+    #[serde(
+        rename = "referenceRange",
+        alias = "reference_range",
+        default,
+        deserialize_with = "one_or_many",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub reference_range: Option<Vec<ReferenceRange>>,
+    /// HL7 v3 interpretation code (N, L, H, A, HH, LL, POS, ...)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub interpretation: Option<Interpretation>,
     /// Test method used
     #[serde(skip_serializing_if = "Option::is_none")]
     pub method: Option<CodeableConcept>,
+    /// Instant or period the result applies to, when it differs from the
+    /// specimen collection (e.g., a daily step count or a weekly average)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub effective: Option<Effective>,
+}
+
+/// Facts about the patient that decide which reference range applies.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RangeContext {
+    /// Age in years, if known
+    pub age_years: Option<f64>,
+    /// States and populations the patient is in (e.g., pregnant, fasting)
+    pub conditions: Vec<Coding>,
+}
+
+impl LabResult {
+    /// Most specific range of `kind` that applies in `context`: every
+    /// `appliesTo` concept must match one of the context's conditions and the
+    /// age must fall in the range's `age`, when given. Ranges without a type
+    /// count as normal. Among applicable ranges, the one with the most
+    /// `appliesTo` concepts wins, then one limited by age, then the first.
+    pub fn applicable_range(&self, kind: ReferenceRangeType, context: &RangeContext) -> Option<&ReferenceRange> {
+        let mut best: Option<(&ReferenceRange, (usize, bool))> = None;
+        for range in self.reference_range.iter().flatten() {
+            if range.range_type.unwrap_or(ReferenceRangeType::Normal) != kind || !applies(range, context) {
+                continue;
+            }
+            let specificity = (range.applies_to.as_ref().map_or(0, Vec::len), range.age.is_some());
+            if best.is_none_or(|(_, best)| specificity > best) {
+                best = Some((range, specificity));
+            }
+        }
+        best.map(|(range, _)| range)
+    }
+}
+
+fn applies(range: &ReferenceRange, context: &RangeContext) -> bool {
+    let conditions_met = range.applies_to.iter().flatten().all(|concept| {
+        concept.coding.iter().any(|coding| {
+            context.conditions.iter().any(|condition| condition.system == coding.system && condition.code == coding.code)
+        })
+    });
+    let age_met = match &range.age {
+        None => true,
+        Some(age) => context.age_years.is_some_and(|years| {
+            let above = age.low.as_ref().is_none_or(|low| years_of(low).is_some_and(|low| years >= low));
+            let below = age.high.as_ref().is_none_or(|high| years_of(high).is_some_and(|high| years <= high));
+            above && below
+        }),
+    };
+    conditions_met && age_met
+}
+
+/// An age quantity in years; `None` for units that are not UCUM time units.
+fn years_of(age: &Quantity) -> Option<f64> {
+    let per_year = match age.unit.as_str() {
+        "a" => 1.0,
+        "mo" => 12.0,
+        "wk" => 365.25 / 7.0,
+        "d" => 365.25,
+        "h" => 365.25 * 24.0,
+        _ => return None,
+    };
+    Some(age.value / per_year)
+}
+
+/// Reads a list that may also be given as its only element.
+pub(crate) fn one_or_many<'de, D, T>(deserializer: D) -> Result<Option<Vec<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    struct OneOrMany<T>(PhantomData<T>);
+
+    impl<'de, T: Deserialize<'de>> Visitor<'de> for OneOrMany<T> {
+        type Value = Option<Vec<T>>;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("an object or an array of objects")
+        }
+
+        fn visit_unit<E: serde::de::Error>(self) -> Result<Self::Value, E> {
+            Ok(None)
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+            T::deserialize(MapAccessDeserializer::new(map)).map(|one| Some(vec![one]))
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<Self::Value, A::Error> {
+            Vec::deserialize(SeqAccessDeserializer::new(seq)).map(Some)
+        }
+    }
+
+    deserializer.deserialize_any(OneOrMany(PhantomData))
 }
 
 /// Laboratory test report.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "LabReportInput")
+)]
 pub struct LabReport {
     /// Unique report identifier
     pub id: String,
     /// Reference to Person.id
-    #[serde(rename = "patientId")]
+    #[serde(rename = "patientId", alias = "patient_id")]
     pub patient_id: String,
     /// Report issue timestamp
-    #[serde(rename = "issuedAt")]
-    pub issued_at: DateTime<Utc>,
+    #[serde(rename = "issuedAt", alias = "issued_at")]
+    #[cfg_attr(feature = "utoipa", schema(value_type = String))]
+    pub issued_at: FlexibleDateTime,
     /// List of lab test results
     pub results: Vec<LabResult>,
     /// Lab facility information
@@ -88,4 +393,62 @@ pub struct LabReport {
     /// Specimen information
     #[serde(skip_serializing_if = "Option::is_none")]
     pub specimen: Option<Specimen>,
+    /// Free-text notes and comments
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notes: Option<Vec<Annotation>>,
+    /// Schema version the resource was written against (e.g., "0.1.0"); absent means 0.1.0
+    #[serde(rename = "schemaVersion", alias = "schema_version", skip_serializing_if = "Option::is_none")]
+    pub schema_version: Option<String>,
+    /// Confidentiality and sensitivity labels (e.g., v3 ActCode HIV); see [`crate::security`]
+    #[serde(rename = "securityLabels", alias = "security_labels", skip_serializing_if = "Option::is_none")]
+    pub security_labels: Option<Vec<Coding>>,
+    /// Site-specific extensions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extensions: Option<Vec<Extension>>,
+    /// Properties this model version does not define, kept for re-serialization
+    #[cfg(feature = "preserve-unknown")]
+    #[serde(flatten)]
+    #[cfg_attr(feature = "schemars", schemars(skip))]
+    #[cfg_attr(feature = "utoipa", schema(ignore))]
+    #[cfg_attr(feature = "async-graphql", graphql(skip))]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl LabReport {
+    /// When `result` applies: its own effective time, else the specimen
+    /// collection time, else the report's issue time.
+    pub fn effective_of(&self, result: &LabResult) -> Effective {
+        result
+            .effective
+            .or_else(|| self.specimen.as_ref()?.collected_at.map(Effective::Instant))
+            .unwrap_or(Effective::Instant(self.issued_at))
+    }
+}
+
+/// One value of a [`time_series`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SeriesPoint<'a> {
+    /// When the value applies; a period for aggregated values
+    pub effective: Effective,
+    /// The result
+    pub result: &'a LabResult,
+    /// LabReport.id of the result
+    pub report_id: &'a str,
+}
+
+/// Every result coded `code` (in any code system) across `reports`, oldest
+/// first by [`LabReport::effective_of`]; a period sorts by its first day.
+pub fn time_series<'a>(reports: &'a [LabReport], code: &str) -> Vec<SeriesPoint<'a>> {
+    let mut points: Vec<SeriesPoint<'a>> = reports
+        .iter()
+        .flat_map(|report| {
+            report
+                .results
+                .iter()
+                .filter(|result| result.code.coding.iter().any(|coding| coding.code == code))
+                .map(move |result| SeriesPoint { effective: report.effective_of(result), result, report_id: &report.id })
+        })
+        .collect();
+    points.sort_by_key(|point| point.effective.sort_key());
+    points
 }
@@ -0,0 +1,321 @@
+//! Mapping of extracted lab tables onto coded results.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//!
+//! Consumer lab data mostly arrives as PDFs; extraction and OCR tools turn
+//! them into rows of test name, value, unit, reference range and flag.
+//! [`tabular`] maps such rows onto [`LabResult`]s, coding each test name
+//! through a [`LoincDictionary`] and reporting how confident the match is so
+//! callers can send doubtful rows for review.
+//!
+//! ```
+//! use wellally::lab_report::import::{tabular, TabularRow};
+//! use wellally::{Interpretation, LabValue};
+//!
+//! let rows = [TabularRow::new("Glucose, Fasting", "105", "mg/dL", "70-99", "H")];
+//! let imported = tabular(&rows);
+//! assert_eq!(imported[0].result.code.coding[0].code, "2345-7");
+//! assert!(imported[0].confidence > 0.8);
+//! assert_eq!(imported[0].result.interpretation, Some(Interpretation::H));
+//! assert!(matches!(imported[0].result.value, LabValue::Quantity(_)));
+//! ```
+
+use alloc::{string::{String, ToString}, vec, vec::Vec};
+use serde::de::value::StrDeserializer;
+use serde::de::IntoDeserializer;
+use serde::Deserialize;
+
+use crate::catalog::{self, LOINC};
+use crate::common::{CodeableConcept, Coding, Quantity, ReferenceRange};
+use super::{Interpretation, LabResult, LabValue};
+
+/// Matches below this confidence are left uncoded by default.
+pub const DEFAULT_MIN_CONFIDENCE: f64 = 0.6;
+
+/// One extracted table row; empty cells are `None`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TabularRow {
+    /// Test name as printed (e.g., "Glucose, Fasting", "ALT", "血红蛋白")
+    pub name: String,
+    /// Result as printed (e.g., "5.4", "<0.5", "1:80", "Positive")
+    pub value: String,
+    /// Unit as printed
+    pub unit: Option<String>,
+    /// Reference range as printed (e.g., "70-99", "<200", "3.5 ~ 5.1")
+    pub range: Option<String>,
+    /// Abnormal flag as printed (e.g., "H", "L", "↑", "偏高")
+    pub flag: Option<String>,
+}
+
+fn cell(text: &str) -> Option<String> {
+    Some(text.trim()).filter(|text| !text.is_empty()).map(Into::into)
+}
+
+impl TabularRow {
+    /// Row from its five cells; blank cells become `None`.
+    pub fn new(name: &str, value: &str, unit: &str, range: &str, flag: &str) -> Self {
+        TabularRow { name: name.trim().into(), value: value.trim().into(), unit: cell(unit), range: cell(range), flag: cell(flag) }
+    }
+}
+
+/// A dictionary hit for a test name.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoincMatch {
+    /// LOINC code
+    pub code: String,
+    /// LOINC display name
+    pub display: String,
+    /// How closely the name matched, from 0 (unrelated) to 1 (exact)
+    pub confidence: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Entry {
+    term: String,
+    code: String,
+    display: String,
+}
+
+/// Test names and abbreviations mapped to LOINC codes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoincDictionary {
+    entries: Vec<Entry>,
+}
+
+impl Default for LoincDictionary {
+    /// The catalog analytes under their LOINC names and the aliases in
+    /// [`catalog::LAB_ALIASES`].
+    fn default() -> Self {
+        let mut dictionary = LoincDictionary::new();
+        for analyte in catalog::PANELS.iter().flat_map(|panel| panel.analytes) {
+            dictionary.insert(analyte.display, analyte.code, analyte.display);
+            let aliases = catalog::LAB_ALIASES.iter().filter(|(code, _)| *code == analyte.code);
+            for alias in aliases.flat_map(|(_, aliases)| aliases.iter()) {
+                dictionary.insert(alias, analyte.code, analyte.display);
+            }
+        }
+        dictionary
+    }
+}
+
+impl LoincDictionary {
+    /// Empty dictionary.
+    pub fn new() -> Self {
+        LoincDictionary { entries: Vec::new() }
+    }
+
+    /// Adds `name` as a way of writing the test `code`.
+    pub fn insert(&mut self, name: &str, code: &str, display: &str) {
+        let term = normalize(name);
+        if !term.is_empty() {
+            self.entries.push(Entry { term, code: code.into(), display: display.into() });
+        }
+    }
+
+    /// Best entry for `name`. Exact names score 1; names containing a known
+    /// term (e.g., "Glucose, Fasting") score by how much of the name it
+    /// covers; anything else scores by character bigram overlap.
+    pub fn lookup(&self, name: &str) -> Option<LoincMatch> {
+        let name = normalize(name);
+        if name.is_empty() {
+            return None;
+        }
+        let mut best: Option<(&Entry, f64)> = None;
+        for entry in &self.entries {
+            let score = similarity(&name, &entry.term);
+            if score > best.map_or(0.0, |(_, score)| score) {
+                best = Some((entry, score));
+            }
+        }
+        best.map(|(entry, confidence)| LoincMatch { code: entry.code.clone(), display: entry.display.clone(), confidence })
+    }
+}
+
+/// Lowercase words of alphanumeric characters separated by single spaces.
+fn normalize(name: &str) -> String {
+    let mapped: String = name.chars().map(|c| if c.is_alphanumeric() { c.to_ascii_lowercase() } else { ' ' }).collect();
+    mapped.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn similarity(name: &str, term: &str) -> f64 {
+    if name == term {
+        return 1.0;
+    }
+    let term_len = term.chars().count();
+    // Whole words for Latin terms ("k" is not in "vitamin k2"); CJK names
+    // have no spaces, so any run of characters counts.
+    let contained = if term.is_ascii() {
+        alloc::format!(" {} ", name).contains(&alloc::format!(" {} ", term))
+    } else {
+        name.contains(term)
+    };
+    if contained && term_len >= 3 {
+        return 0.75 + 0.2 * term_len as f64 / name.chars().count() as f64;
+    }
+    dice(name, term)
+}
+
+/// Sørensen–Dice coefficient of the character bigrams, ignoring spaces.
+fn dice(a: &str, b: &str) -> f64 {
+    let bigrams = |text: &str| {
+        let chars: Vec<char> = text.chars().filter(|c| *c != ' ').collect();
+        chars.windows(2).map(|pair| (pair[0], pair[1])).collect::<Vec<_>>()
+    };
+    let (a, mut b) = (bigrams(a), bigrams(b));
+    let total = a.len() + b.len();
+    if total == 0 {
+        return 0.0;
+    }
+    let mut shared = 0;
+    for pair in &a {
+        if let Some(at) = b.iter().position(|other| other == pair) {
+            b.swap_remove(at);
+            shared += 1;
+        }
+    }
+    2.0 * shared as f64 / total as f64
+}
+
+/// A mapped row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedResult {
+    /// The result; its code keeps the printed name as `text` and has no
+    /// coding when no dictionary entry was close enough
+    pub result: LabResult,
+    /// Confidence of the LOINC coding, 0 when uncoded
+    pub confidence: f64,
+}
+
+impl ImportedResult {
+    /// Whether the test name was coded.
+    pub fn is_coded(&self) -> bool {
+        !self.result.code.coding.is_empty()
+    }
+}
+
+/// Maps rows with a dictionary and a confidence threshold.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TabularImporter {
+    dictionary: LoincDictionary,
+    min_confidence: f64,
+}
+
+impl Default for TabularImporter {
+    fn default() -> Self {
+        TabularImporter::new(LoincDictionary::default())
+    }
+}
+
+impl TabularImporter {
+    /// Importer using `dictionary` and [`DEFAULT_MIN_CONFIDENCE`].
+    pub fn new(dictionary: LoincDictionary) -> Self {
+        TabularImporter { dictionary, min_confidence: DEFAULT_MIN_CONFIDENCE }
+    }
+
+    /// Leaves names matching below `min_confidence` uncoded.
+    pub fn with_min_confidence(mut self, min_confidence: f64) -> Self {
+        self.min_confidence = min_confidence;
+        self
+    }
+
+    /// One result per row, in order.
+    pub fn import(&self, rows: &[TabularRow]) -> Vec<ImportedResult> {
+        rows.iter().map(|row| self.import_row(row)).collect()
+    }
+
+    /// Maps a single row.
+    pub fn import_row(&self, row: &TabularRow) -> ImportedResult {
+        let found = self.dictionary.lookup(&row.name).filter(|found| found.confidence >= self.min_confidence);
+        let confidence = found.as_ref().map_or(0.0, |found| found.confidence);
+        let coding = found
+            .map(|found| vec![Coding { system: LOINC.to_string(), code: found.code, display: Some(found.display) }])
+            .unwrap_or_default();
+        let unit = row.unit.as_deref().map(str::trim).filter(|unit| !unit.is_empty());
+        ImportedResult {
+            result: LabResult {
+                code: CodeableConcept { coding, text: cell(&row.name) },
+                value: parse_value(&row.value, unit),
+                reference_range: row.range.as_deref().and_then(|range| parse_range(range, unit)).map(|range| vec![range]),
+                interpretation: row.flag.as_deref().and_then(parse_flag),
+                method: None,
+                effective: None,
+            },
+            confidence,
+        }
+    }
+}
+
+/// Maps rows with the catalog dictionary; see [`TabularImporter`].
+pub fn tabular(rows: &[TabularRow]) -> Vec<ImportedResult> {
+    TabularImporter::default().import(rows)
+}
+
+/// Number at the start of `text` and the rest of it.
+fn leading_number(text: &str) -> Option<(f64, &str)> {
+    let text = text.trim_start();
+    let end = text
+        .char_indices()
+        .find(|&(i, c)| !(c.is_ascii_digit() || c == '.' || (i == 0 && (c == '-' || c == '+'))))
+        .map_or(text.len(), |(i, _)| i);
+    Some((text[..end].parse().ok()?, text[end..].trim()))
+}
+
+fn parse_value(value: &str, unit: Option<&str>) -> LabValue {
+    let quantity = |value: f64, unit: &str| LabValue::Quantity(Quantity::new(value, unit));
+    match leading_number(value) {
+        Some((number, "")) => return quantity(number, unit.unwrap_or("1")),
+        Some((number, printed)) if unit.is_none() && !printed.contains(char::is_whitespace) && !printed.starts_with(':') => {
+            return quantity(number, printed)
+        }
+        _ => {}
+    }
+    match LabValue::parse(value) {
+        LabValue::Comparator { comparator, value } if value.unit == "1" => LabValue::Comparator {
+            comparator,
+            value: Quantity::new(value.value, unit.unwrap_or("1")),
+        },
+        parsed => parsed,
+    }
+}
+
+fn parse_range(range: &str, unit: Option<&str>) -> Option<ReferenceRange> {
+    let text = range.trim();
+    if text.is_empty() {
+        return None;
+    }
+    let quantity = |value: f64| Quantity::new(value, unit.unwrap_or("1"));
+    let bounds = if let Some(rest) = ["<=", "≤", "<"].iter().find_map(|symbol| text.strip_prefix(symbol)) {
+        leading_number(rest).map(|(high, _)| (None, Some(high)))
+    } else if let Some(rest) = [">=", "≥", ">"].iter().find_map(|symbol| text.strip_prefix(symbol)) {
+        leading_number(rest).map(|(low, _)| (Some(low), None))
+    } else {
+        leading_number(text).and_then(|(low, rest)| {
+            let rest = ["-", "–", "—", "~", "～", "to"].iter().find_map(|separator| rest.strip_prefix(separator))?;
+            let (high, _) = leading_number(rest)?;
+            Some((Some(low), Some(high)))
+        })
+    };
+    Some(match bounds {
+        Some((low, high)) => ReferenceRange::new(low.map(quantity), high.map(quantity)),
+        None => ReferenceRange { text: Some(text.into()), ..ReferenceRange::new(None, None) },
+    })
+}
+
+fn parse_flag(flag: &str) -> Option<Interpretation> {
+    let upper = flag.trim().to_uppercase();
+    let code = match upper.as_str() {
+        "" => return None,
+        "↑" | "HIGH" | "偏高" | "高" => "H",
+        "↓" | "LOW" | "偏低" | "低" => "L",
+        "↑↑" | "CRITICAL HIGH" => "HH",
+        "↓↓" | "CRITICAL LOW" => "LL",
+        "NORMAL" | "正常" => "N",
+        "*" | "ABNORMAL" | "异常" => "A",
+        "+" | "POSITIVE" | "阳性" => "POS",
+        "-" | "NEGATIVE" | "阴性" => "NEG",
+        code => code,
+    };
+    let code: StrDeserializer<'_, serde::de::value::Error> = code.into_deserializer();
+    Interpretation::deserialize(code).ok()
+}
@@ -0,0 +1,204 @@
+//! Detection and merging of duplicate lab reports.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//!
+//! The same panel often reaches a record twice: from the lab's portal and
+//! from the hospital's EHR export, with different report ids, rounding and
+//! sometimes different units. [`dedupe`] groups reports of one patient whose
+//! specimens were collected at the same time and whose shared analytes agree
+//! within a tolerance, and merges each group into one report. The merged
+//! report lists its sources in [`MERGED_FROM`] extensions, and
+//! [`MergedReport`] records which source each result came from.
+//!
+//! ```
+//! use wellally::lab_report::merge::dedupe;
+//! use wellally::LabReport;
+//!
+//! let report = |id: &str, glucose: f64, unit: &str| -> LabReport {
+//!     serde_json::from_value(serde_json::json!({
+//!         "id": id,
+//!         "patientId": "p-1",
+//!         "issuedAt": "2024-11-02T09:30:00Z",
+//!         "specimen": { "collectedAt": "2024-11-02T07:15:00Z" },
+//!         "results": [{
+//!             "code": { "coding": [{ "system": "http://loinc.org", "code": "2345-7" }] },
+//!             "value": { "value": glucose, "unit": unit }
+//!         }]
+//!     }))
+//!     .unwrap()
+//! };
+//! let merged = dedupe(&[report("portal-1", 97.0, "mg/dL"), report("ehr-7", 0.97, "g/L")]);
+//! assert_eq!(merged.len(), 1);
+//! assert_eq!(merged[0].sources, ["portal-1", "ehr-7"]);
+//! ```
+
+use alloc::{format, string::String, vec, vec::Vec};
+use chrono::Duration;
+
+use crate::common::{Coding, Reference};
+use crate::extension::Extensible;
+use crate::ucum;
+use super::{LabReport, LabResult, LabValue};
+
+/// Extension on a merged report referencing each source report
+pub const MERGED_FROM: &str = "https://www.wellally.tech/fhir/StructureDefinition/merged-from";
+
+/// Default largest difference between collection times of duplicates.
+pub const DEFAULT_COLLECTION_WINDOW_MINUTES: i64 = 10;
+
+/// Default largest relative difference between values of duplicates.
+pub const DEFAULT_TOLERANCE: f64 = 0.02;
+
+/// A report merged from one or more duplicates.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergedReport {
+    /// The first source, completed with the results, facility, panel,
+    /// specimen and notes only the others have
+    pub report: LabReport,
+    /// LabReport.id of each source, in input order
+    pub sources: Vec<String>,
+    /// LabReport.id of the source of each result of `report`
+    pub result_sources: Vec<String>,
+}
+
+impl MergedReport {
+    /// Whether the report was merged from more than one source.
+    pub fn is_merged(&self) -> bool {
+        self.sources.len() > 1
+    }
+
+    fn new(report: &LabReport) -> Self {
+        MergedReport {
+            report: report.clone(),
+            sources: vec![report.id.clone()],
+            result_sources: vec![report.id.clone(); report.results.len()],
+        }
+    }
+
+    fn absorb(&mut self, other: &LabReport) {
+        for result in &other.results {
+            if !self.report.results.iter().any(|kept| same_analyte(kept, result)) {
+                self.report.results.push(result.clone());
+                self.result_sources.push(other.id.clone());
+            }
+        }
+        let report = &mut self.report;
+        report.facility = report.facility.take().or_else(|| other.facility.clone());
+        report.panel = report.panel.take().or_else(|| other.panel.clone());
+        report.specimen = report.specimen.take().or_else(|| other.specimen.clone());
+        for note in other.notes.iter().flatten() {
+            let notes = report.notes.get_or_insert_with(Vec::new);
+            if !notes.contains(note) {
+                notes.push(note.clone());
+            }
+        }
+        self.sources.push(other.id.clone());
+    }
+}
+
+/// Finds and merges duplicate reports with a collection window and a value
+/// tolerance.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReportMerger {
+    collection_window: Duration,
+    tolerance: f64,
+}
+
+impl Default for ReportMerger {
+    fn default() -> Self {
+        ReportMerger {
+            collection_window: Duration::minutes(DEFAULT_COLLECTION_WINDOW_MINUTES),
+            tolerance: DEFAULT_TOLERANCE,
+        }
+    }
+}
+
+impl ReportMerger {
+    /// Treats specimens collected up to `window` apart as the same.
+    pub fn with_collection_window(mut self, window: Duration) -> Self {
+        self.collection_window = window;
+        self
+    }
+
+    /// Treats values differing by up to `tolerance` of the larger as equal.
+    pub fn with_tolerance(mut self, tolerance: f64) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Whether `a` and `b` report the same specimen: same patient, both
+    /// collection times known and within the window, at least one shared
+    /// analyte, and every shared analyte's values agreeing.
+    pub fn is_duplicate(&self, a: &LabReport, b: &LabReport) -> bool {
+        let collected = |report: &LabReport| report.specimen.as_ref()?.collected_at.map(|at| at.to_utc());
+        let same_collection = match (collected(a), collected(b)) {
+            (Some(a), Some(b)) => (a - b).abs() <= self.collection_window,
+            _ => false,
+        };
+        if a.patient_id != b.patient_id || !same_collection {
+            return false;
+        }
+        let mut shared = false;
+        for result in &a.results {
+            for other in b.results.iter().filter(|other| same_analyte(result, other)) {
+                if !self.agree(&result.value, &other.value) {
+                    return false;
+                }
+                shared = true;
+            }
+        }
+        shared
+    }
+
+    /// One merged report per group of duplicates, in order of each group's
+    /// first report; reports without duplicates come back unchanged.
+    pub fn dedupe(&self, reports: &[LabReport]) -> Vec<MergedReport> {
+        let mut groups: Vec<(&LabReport, MergedReport)> = Vec::new();
+        for report in reports {
+            match groups.iter_mut().find(|(first, _)| self.is_duplicate(first, report)) {
+                Some((_, merged)) => merged.absorb(report),
+                None => groups.push((report, MergedReport::new(report))),
+            }
+        }
+        groups
+            .into_iter()
+            .map(|(_, mut merged)| {
+                if merged.is_merged() {
+                    for source in &merged.sources {
+                        let reference = Reference { reference: format!("LabReport/{}", source), display: None };
+                        merged.report.add_extension(MERGED_FROM, reference);
+                    }
+                }
+                merged
+            })
+            .collect()
+    }
+
+    fn agree(&self, a: &LabValue, b: &LabValue) -> bool {
+        match (a, b) {
+            (LabValue::Quantity(a), LabValue::Quantity(b)) => {
+                a.comparator == b.comparator
+                    && ucum::convert(b.value, b.ucum_code(), a.ucum_code())
+                        .is_ok_and(|b| (a.value - b).abs() <= self.tolerance * a.value.abs().max(b.abs()))
+            }
+            _ => a == b,
+        }
+    }
+}
+
+/// Whether two results measure the same analyte: a shared coding, or the
+/// same text when neither is coded.
+fn same_analyte(a: &LabResult, b: &LabResult) -> bool {
+    if a.code.coding.is_empty() && b.code.coding.is_empty() {
+        return a.code.text.is_some() && a.code.text == b.code.text;
+    }
+    let same = |coding: &Coding, other: &Coding| coding.system == other.system && coding.code == other.code;
+    a.code.coding.iter().any(|coding| b.code.coding.iter().any(|other| same(coding, other)))
+}
+
+/// Groups and merges duplicate reports with the default window and
+/// tolerance; see [`ReportMerger`].
+pub fn dedupe(reports: &[LabReport]) -> Vec<MergedReport> {
+    ReportMerger::default().dedupe(reports)
+}
@@ -1,259 +1,155 @@
 //! WellAlly Health Data Models for Rust
-//! 
+//!
 //! Package: wellally
 //! Website: https://www.wellally.tech/
 //! Version: 0.1.0
-//! 
+//!
 //! This crate provides Rust data models for health-related data structures,
 //! including lab reports, imaging reports, medication records, and personal health records.
+//!
+//! The models only need `alloc`: disable the default `std` feature to use them
+//! on `no_std` targets such as device firmware. Storage, schema and binding
+//! features require `std`.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
 
 pub mod common;
+pub mod datetime;
+pub mod extension;
 pub mod lab_report;
 pub mod imaging_report;
 pub mod medication;
 pub mod health;
 pub mod family_health;
-use serde::Serialize;
-use serde::Deserialize;
-use serde_with::skip_serializing_none;
+pub mod legacy;
+pub mod questionnaire;
+pub mod referral;
+pub mod task;
+pub mod communication;
+pub mod location;
+pub mod condition;
+pub mod allergy;
+pub mod consent;
+pub mod flag;
+pub mod cgm;
+pub mod ecg;
+pub mod nephrology;
+pub mod rehab;
+pub mod wound;
+pub mod infusion;
+pub mod pathology;
+pub mod audiology;
+pub mod birth;
+pub mod clearance;
+pub mod travel;
+pub mod compendium;
+pub mod dispense;
+pub mod immunization;
+pub mod relationships;
+pub mod resource;
+pub mod migrations;
+pub mod catalog;
+pub mod validation;
+pub mod borrowed;
+pub mod wire;
+pub mod error;
+pub mod i18n;
+pub mod ucum;
+pub mod crdt;
+pub mod expr;
+pub mod authz;
+pub mod security;
+pub mod narrative;
+pub mod problem_list;
+pub mod chart;
+pub mod safety;
+pub mod vitals;
+pub mod oncology;
+pub mod formulary;
+#[cfg(feature = "std")]
+pub mod conformance;
+#[cfg(feature = "std")]
+pub mod calc;
+#[cfg(feature = "std")]
+pub mod io;
+#[cfg(feature = "std")]
+pub mod sync;
+#[cfg(feature = "std")]
+pub mod anonymize;
+#[cfg(feature = "std")]
+pub mod events;
+#[cfg(feature = "std")]
+pub mod research;
+#[cfg(feature = "std")]
+pub mod ips;
+#[cfg(feature = "profiles")]
+pub mod profiles;
+#[cfg(feature = "mapping")]
+pub mod mapping;
+#[cfg(feature = "client")]
+pub mod client;
+#[cfg(feature = "webhook")]
+pub mod webhook;
+#[cfg(feature = "omop")]
+pub mod omop;
+#[cfg(feature = "pcornet")]
+pub mod pcornet;
+
+#[cfg(feature = "proptest")]
+pub mod strategies;
+#[cfg(feature = "synthetic")]
+pub mod synthetic;
+#[cfg(feature = "intern")]
+pub mod intern;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+#[cfg(feature = "postgres")]
+pub mod postgres;
+#[cfg(feature = "utoipa")]
+pub mod openapi;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
 pub use common::*;
+pub use datetime::*;
+pub use extension::*;
 pub use lab_report::*;
 pub use imaging_report::*;
 pub use medication::*;
 pub use health::*;
 pub use family_health::*;
-
-#[skip_serializing_none]
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ReferenceRange {
-    pub low: Option<Quantity>,
-    pub high: Option<Quantity>,
-    pub text: Option<String>,
-}
-
-#[skip_serializing_none]
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Identifier {
-    pub system: String,
-    pub value: String,
-    #[serde(rename = "type")]
-    pub id_type: Option<CodeableConcept>,
-    pub period: Option<Period>,
-}
-
-#[skip_serializing_none]
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct HumanName {
-    pub family: String,
-    pub given: Vec<String>,
-    pub use_: Option<String>,
-    pub prefix: Option<Vec<String>>,
-    pub suffix: Option<Vec<String>>,
-}
-
-#[skip_serializing_none]
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ContactPoint {
-    pub system: Option<String>,
-    pub value: String,
-    pub use_: Option<String>,
-}
-
-#[skip_serializing_none]
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Address {
-    pub line: Option<Vec<String>>,
-    pub city: Option<String>,
-    pub state: Option<String>,
-    pub postalCode: Option<String>,
-    pub country: Option<String>,
-}
-
-#[skip_serializing_none]
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Period {
-    pub start: Option<String>,
-    pub end: Option<String>,
-}
-
-#[skip_serializing_none]
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Modality {
-    pub system: String,
-    pub code: String,
-    pub display: Option<String>,
-}
-
-#[skip_serializing_none]
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Route {
-    pub system: String,
-    pub code: String,
-    pub display: Option<String>,
-}
-
-#[skip_serializing_none]
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct SpecimenType {
-    pub system: String,
-    pub code: String,
-    pub display: Option<String>,
-}
-
-// ---------- Health Person ----------
-#[skip_serializing_none]
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ClinicalSummary {
-    pub conditions: Option<Vec<CodeableConcept>>,
-    pub allergies: Option<Vec<CodeableConcept>>,
-    pub bloodType: Option<String>,
-    pub primaryCareProvider: Option<String>,
-}
-
-#[skip_serializing_none]
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct HealthPerson {
-    pub id: String,
-    pub name: Vec<HumanName>,
-    pub birthDate: String,
-    #[serde(default = "default_resource_type")]
-    pub resourceType: String,
-    pub identifier: Option<Vec<Identifier>>,
-    pub gender: Option<String>,
-    pub telecom: Option<Vec<ContactPoint>>,
-    pub address: Option<Vec<Address>>,
-    pub maritalStatus: Option<CodeableConcept>,
-    pub language: Option<Vec<String>>,
-    pub clinicalSummary: Option<ClinicalSummary>,
-}
-
-fn default_resource_type() -> String {
-    "Person".to_string()
-}
-
-// ---------- Lab Report ----------
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(untagged)]
-pub enum LabResultValue {
-    Quantity(Quantity),
-    CodeableConcept(CodeableConcept),
-    Text(String),
-}
-
-#[skip_serializing_none]
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct LabResult {
-    pub code: CodeableConcept,
-    pub value: LabResultValue,
-    pub referenceRange: Option<ReferenceRange>,
-    pub interpretation: Option<String>,
-    pub method: Option<CodeableConcept>,
-}
-
-#[skip_serializing_none]
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Specimen {
-    pub r#type: Option<SpecimenType>,
-    pub collectedAt: Option<String>,
-}
-
-#[skip_serializing_none]
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Facility {
-    pub id: Option<String>,
-    pub name: Option<String>,
-}
-
-#[skip_serializing_none]
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct LabReport {
-    pub id: String,
-    pub patientId: String,
-    pub issuedAt: String,
-    pub results: Vec<LabResult>,
-    pub facility: Option<Facility>,
-    pub panel: Option<CodeableConcept>,
-    pub specimen: Option<Specimen>,
-}
-
-// ---------- Imaging Report ----------
-#[skip_serializing_none]
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Attachment {
-    pub url: Option<String>,
-    pub r#type: Option<String>,
-}
-
-#[skip_serializing_none]
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct RadiationDose {
-    pub ctdiVol_mGy: Option<f64>,
-    pub dlp_mGy_cm: Option<f64>,
-}
-
-#[skip_serializing_none]
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Performer {
-    pub id: Option<String>,
-    pub name: Option<String>,
-    pub role: Option<String>,
-}
-
-#[skip_serializing_none]
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ImagingReport {
-    pub id: String,
-    pub patientId: String,
-    pub modality: Modality,
-    pub bodySite: Coding,
-    pub reportedAt: String,
-    pub studyInstanceUid: Option<String>,
-    pub performer: Option<Performer>,
-    pub findings: Option<Vec<String>>,
-    pub impression: Option<String>,
-    pub radiationDose: Option<RadiationDose>,
-    pub attachments: Option<Vec<Attachment>>,
-}
-
-// ---------- Medication ----------
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Dosage {
-    pub value: f64,
-    pub unit: String,
-}
-
-#[skip_serializing_none]
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct MedicationRecord {
-    pub id: String,
-    pub patientId: String,
-    pub medication: Coding,
-    pub dosage: Dosage,
-    pub route: Route,
-    pub startDate: String,
-    pub form: Option<Coding>,
-    pub frequency: Option<String>,
-    pub durationDays: Option<i64>,
-    pub endDate: Option<String>,
-    pub indication: Option<CodeableConcept>,
-    pub instructions: Option<String>,
-}
-
-// ---------- Family Health ----------
-#[skip_serializing_none]
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct FamilyMember {
-    pub id: String,
-    pub relationToProband: String,
-    pub sex: Option<String>,
-    pub birthYear: Option<i64>,
-    pub deceased: Option<bool>,
-    pub conditions: Option<Vec<CodeableConcept>>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct FamilyHealthTree {
-    pub probandId: String,
-    pub members: Vec<FamilyMember>,
-}
+#[allow(deprecated)]
+pub use legacy::{HealthPerson, LabResultValue, SpecimenType};
+pub use questionnaire::*;
+pub use referral::*;
+pub use task::*;
+pub use communication::*;
+pub use location::*;
+pub use condition::*;
+pub use allergy::*;
+pub use consent::{ConsentDecision, ConsentProvision, ConsentRecord, ConsentStatus, Purpose};
+pub use flag::*;
+pub use cgm::*;
+pub use ecg::*;
+pub use nephrology::{AccessType, DialysisAccess, DialysisModality, DialysisSession};
+pub use rehab::{Exercise, FunctionalScore, RehabSession};
+pub use wound::{
+    DressingPlan, Exudate, ExudateAmount, ExudateType, PressureInjuryStage, TissueProportion, TissueType,
+    WoundAssessment, WoundDimensions,
+};
+pub use infusion::{InfusionLine, InfusionRecord, Interruption, LineType, Titration};
+pub use pathology::{PathologyReport, PathologySpecimen, SynopticElement};
+pub use audiology::{AudiogramReport, AudiometryPurpose, EarResult, SpeechRecognition, TympanogramType};
+pub use birth::{ApgarComponents, ApgarScore, BirthRecord, DeliveryMethod};
+pub use clearance::{ClearanceDocument, ClearanceOutcome, ClearanceType, RequiredEvidence, Restriction};
+pub use travel::{Destination, TravelAdvisory, TravelHealthRecord, TravelVaccine, VaccineRequirement};
+pub use compendium::{Compendium, SpecimenRequirement, TestDefinition};
+pub use dispense::DispenseRecord;
+pub use immunization::ImmunizationRecord;
+pub use relationships::{RelatedPerson, RelationshipRole};
+pub use resource::{Resource, UnknownFields};
+pub use security::SecurityLabeled;
+pub use error::WellAllyError;
+pub use validation::{Validate, ValidationIssue};
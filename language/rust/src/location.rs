@@ -0,0 +1,242 @@
+//! Location data model.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//! Schema: https://wellall.health/schemas/location/v0.1.0
+//!
+//! A [`Location`] is a physical place where care is provided: a hospital,
+//! a clinic, or a ward or room inside one (linked through `partOf`). It
+//! carries the address, WGS84 coordinates and opening hours shared by every
+//! [`Facility`](crate::lab_report::Facility) that references it.
+
+use alloc::{string::String, vec::Vec};
+use chrono::{Datelike, NaiveDateTime, NaiveTime, Weekday};
+use serde::{Deserialize, Serialize};
+
+use crate::common::{Address, CodeableConcept, Reference, Coding};
+use crate::extension::Extension;
+
+/// Operational status of a location
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "async-graphql", derive(async_graphql::Enum))]
+#[serde(rename_all = "lowercase")]
+pub enum LocationStatus {
+    Active,
+    /// Temporarily closed
+    Suspended,
+    /// No longer in use
+    Inactive,
+}
+
+/// Day of the week
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "async-graphql", derive(async_graphql::Enum))]
+#[serde(rename_all = "lowercase")]
+pub enum DayOfWeek {
+    Mon,
+    Tue,
+    Wed,
+    Thu,
+    Fri,
+    Sat,
+    Sun,
+}
+
+impl From<Weekday> for DayOfWeek {
+    fn from(day: Weekday) -> Self {
+        match day {
+            Weekday::Mon => DayOfWeek::Mon,
+            Weekday::Tue => DayOfWeek::Tue,
+            Weekday::Wed => DayOfWeek::Wed,
+            Weekday::Thu => DayOfWeek::Thu,
+            Weekday::Fri => DayOfWeek::Fri,
+            Weekday::Sat => DayOfWeek::Sat,
+            Weekday::Sun => DayOfWeek::Sun,
+        }
+    }
+}
+
+/// WGS84 coordinates of a location.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "PositionInput")
+)]
+pub struct Position {
+    /// Latitude in decimal degrees (-90 to 90)
+    pub latitude: f64,
+    /// Longitude in decimal degrees (-180 to 180)
+    pub longitude: f64,
+    /// Altitude in metres
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub altitude: Option<f64>,
+}
+
+impl Position {
+    /// Mean Earth radius used for distances, in kilometres
+    pub const EARTH_RADIUS_KM: f64 = 6371.0;
+
+    /// Great-circle (haversine) distance to `other` in kilometres, ignoring altitude.
+    #[cfg(feature = "std")]
+    pub fn distance_km(&self, other: &Position) -> f64 {
+        let (lat1, lat2) = (self.latitude.to_radians(), other.latitude.to_radians());
+        let dlat = lat2 - lat1;
+        let dlon = (other.longitude - self.longitude).to_radians();
+        let a = (dlat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (dlon / 2.0).sin().powi(2);
+        2.0 * Self::EARTH_RADIUS_KM * a.sqrt().asin()
+    }
+}
+
+/// Opening hours on some days of the week.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "OperatingHoursInput")
+)]
+pub struct OperatingHours {
+    /// Days the hours apply to
+    #[serde(rename = "daysOfWeek", alias = "days_of_week")]
+    pub days_of_week: Vec<DayOfWeek>,
+    /// Open around the clock on those days
+    #[serde(rename = "allDay", alias = "all_day", skip_serializing_if = "Option::is_none")]
+    pub all_day: Option<bool>,
+    /// Opening time (local)
+    #[serde(rename = "openingTime", alias = "opening_time", skip_serializing_if = "Option::is_none")]
+    pub opening_time: Option<NaiveTime>,
+    /// Closing time (local); before the opening time when open past midnight
+    #[serde(rename = "closingTime", alias = "closing_time", skip_serializing_if = "Option::is_none")]
+    pub closing_time: Option<NaiveTime>,
+}
+
+impl OperatingHours {
+    /// Whether the hours cover the local date-time `at`. Hours that run past
+    /// midnight are attributed to the day they open on.
+    pub fn covers(&self, at: NaiveDateTime) -> bool {
+        let day = |date: chrono::NaiveDate| self.days_of_week.contains(&date.weekday().into());
+        if self.all_day == Some(true) {
+            return day(at.date());
+        }
+        let (Some(open), Some(close)) = (self.opening_time, self.closing_time) else {
+            return false;
+        };
+        let time = at.time();
+        if open <= close {
+            day(at.date()) && open <= time && time < close
+        } else {
+            (day(at.date()) && time >= open) || (at.date().pred_opt().is_some_and(day) && time < close)
+        }
+    }
+}
+
+/// Physical place where care is provided.
+///
+/// Only its `position` tells a location apart from other resources, so in a
+/// mixed payload a location without one is recognized by its `resourceType`,
+/// which `AnyResource` writes.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "LocationInput")
+)]
+pub struct Location {
+    /// Unique location identifier
+    pub id: String,
+    /// Name of the location
+    pub name: String,
+    /// Operational status
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<LocationStatus>,
+    /// Kind of location (e.g., HL7 v3 RoleCode "HOSP", "CARD", "ER")
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "async-graphql", graphql(name = "type"))]
+    pub location_type: Option<Vec<CodeableConcept>>,
+    /// Postal address
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<Address>,
+    /// Geographic coordinates
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position: Option<Position>,
+    /// Location this one is part of (e.g., the hospital of a ward)
+    #[serde(rename = "partOf", alias = "part_of", skip_serializing_if = "Option::is_none")]
+    pub part_of: Option<Reference>,
+    /// Opening hours; none means unknown
+    #[serde(rename = "hoursOfOperation", alias = "hours_of_operation", skip_serializing_if = "Option::is_none")]
+    pub hours_of_operation: Option<Vec<OperatingHours>>,
+    /// Schema version the resource was written against (e.g., "0.1.0"); absent means 0.1.0
+    #[serde(rename = "schemaVersion", alias = "schema_version", skip_serializing_if = "Option::is_none")]
+    pub schema_version: Option<String>,
+    /// Confidentiality and sensitivity labels (e.g., v3 ActCode HIV); see [`crate::security`]
+    #[serde(rename = "securityLabels", alias = "security_labels", skip_serializing_if = "Option::is_none")]
+    pub security_labels: Option<Vec<Coding>>,
+    /// Site-specific extensions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extensions: Option<Vec<Extension>>,
+    /// Properties this model version does not define, kept for re-serialization
+    #[cfg(feature = "preserve-unknown")]
+    #[serde(flatten)]
+    #[cfg_attr(feature = "schemars", schemars(skip))]
+    #[cfg_attr(feature = "utoipa", schema(ignore))]
+    #[cfg_attr(feature = "async-graphql", graphql(skip))]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Location {
+    /// Whether the location is open at the local date-time `at`; `None` if
+    /// its hours are unknown. Suspended and inactive locations are closed.
+    pub fn is_open_at(&self, at: NaiveDateTime) -> Option<bool> {
+        if matches!(self.status, Some(LocationStatus::Suspended | LocationStatus::Inactive)) {
+            return Some(false);
+        }
+        let hours = self.hours_of_operation.as_ref()?;
+        Some(hours.iter().any(|hours| hours.covers(at)))
+    }
+
+    /// Parent location among `locations`, following `partOf`.
+    pub fn parent<'a>(&self, locations: &'a [Location]) -> Option<&'a Location> {
+        let reference = self.part_of.as_ref()?;
+        locations.iter().find(|location| reference.points_to(*location))
+    }
+
+    /// Enclosing locations, innermost first. Stops at a missing parent or a cycle.
+    pub fn ancestors<'a>(&self, locations: &'a [Location]) -> Vec<&'a Location> {
+        let mut ancestors: Vec<&'a Location> = Vec::new();
+        let mut current = self.parent(locations);
+        while let Some(location) = current {
+            if location.id == self.id || ancestors.iter().any(|seen| seen.id == location.id) {
+                break;
+            }
+            ancestors.push(location);
+            current = location.parent(locations);
+        }
+        ancestors
+    }
+
+    /// Distance from `from` in kilometres; `None` without a position.
+    #[cfg(feature = "std")]
+    pub fn distance_km(&self, from: &Position) -> Option<f64> {
+        self.position.map(|position| position.distance_km(from))
+    }
+}
+
+/// Locations with a position sorted by distance from `from`, nearest first,
+/// paired with the distance in kilometres.
+#[cfg(feature = "std")]
+pub fn nearest_locations<'a>(locations: &'a [Location], from: &Position) -> Vec<(&'a Location, f64)> {
+    let mut found: Vec<_> =
+        locations.iter().filter_map(|location| Some((location, location.distance_km(from)?))).collect();
+    found.sort_by(|a, b| a.1.total_cmp(&b.1));
+    found
+}
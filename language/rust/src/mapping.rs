@@ -0,0 +1,341 @@
+//! Declarative mappings from partner formats onto resources.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//!
+//! Partners send their own JSON and CSV layouts. A [`Mapping`], usually kept
+//! in a `mapping.yaml` next to the partner's configuration, says where each
+//! target field comes from: a source [`Expression`], a list of
+//! [`Transform`]s (date parsing, code lookup, unit conversion, ...) and, for
+//! nested objects and arrays, nested field mappings. [`Mapping::map`] runs it
+//! and returns parsed and validated resources, so a new partner layout needs
+//! a new file rather than new code.
+//!
+//! CSV input is read as `{"rows": [{"<column>": "<cell>", ...}, ...]}`;
+//! columns whose names are not plain identifiers are written in backticks
+//! (`` `Test Name` ``).
+//!
+//! ```
+//! use wellally::mapping::Mapping;
+//! use wellally::LabReport;
+//!
+//! let mapping = Mapping::from_yaml(r#"
+//! name: partner-x
+//! resourceType: LabReport
+//! fields:
+//!   - { target: id, source: orderNo }
+//!   - { target: patientId, source: mrn }
+//!   - { target: issuedAt, source: reported, transforms: [{ date: "%d/%m/%Y %H:%M" }] }
+//!   - target: results
+//!     source: tests
+//!     array: true
+//!     fields:
+//!       - { target: code, source: test, transforms: [{ lookup: { GLU: "2345-7" } }, { concept: "http://loinc.org" }] }
+//!       - target: value
+//!         fields:
+//!           - { target: value, source: result, transforms: [number] }
+//!           - { target: unit, source: unit }
+//!         transforms: [{ convert: mg/dL }]
+//! "#)?;
+//!
+//! let source = serde_json::json!({
+//!     "orderNo": "A-17", "mrn": "p-1", "reported": "02/11/2024 09:30",
+//!     "tests": [{ "test": "GLU", "result": "0.97", "unit": "g/L" }]
+//! });
+//! let reports: Vec<LabReport> = mapping.map(&source)?;
+//! assert_eq!(reports[0].results[0].code.coding[0].code, "2345-7");
+//! # Ok::<(), wellally::WellAllyError>(())
+//! ```
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::error::{self, WellAllyError};
+use crate::expr::Expression;
+use crate::resource::Resource;
+use crate::ucum;
+use crate::validation::{index, join, Validate};
+
+/// How a partner's records become resources of one type.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Mapping {
+    /// Mapping name (e.g., "partner-x-labs")
+    pub name: String,
+    /// What the mapping is for
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Resource type produced (e.g., "LabReport")
+    #[serde(rename = "resourceType", alias = "resource_type")]
+    pub resource_type: String,
+    /// Records in the source, one resource each (e.g., `rows` for CSV);
+    /// absent maps the whole source to one resource
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub records: Option<Expression>,
+    /// Target fields
+    pub fields: Vec<FieldMapping>,
+}
+
+/// Where one target field comes from.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FieldMapping {
+    /// Dotted target field in JSON names (e.g., "specimen.collectedAt")
+    pub target: String,
+    /// Values to map, selected from the current record; absent means the
+    /// record itself when `fields` are given
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<Expression>,
+    /// Value used when the source selects nothing; a constant without a source
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default: Option<Value>,
+    /// Transforms applied to each value, in order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub transforms: Vec<Transform>,
+    /// Builds an object from each selected value with these mappings
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fields: Option<Vec<FieldMapping>>,
+    /// Collects every selected value into an array; otherwise at most one
+    /// value may be selected
+    #[serde(default, skip_serializing_if = "core::ops::Not::not")]
+    pub array: bool,
+}
+
+/// A transformation of a mapped value.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Transform {
+    /// Strips surrounding whitespace
+    Trim,
+    /// Upper-cases text
+    Uppercase,
+    /// Lower-cases text
+    Lowercase,
+    /// Reads text as a number
+    Number,
+    /// Reads text as a date or date-time in a chrono format (e.g.,
+    /// "%d/%m/%Y %H:%M"); writes ISO 8601
+    Date(String),
+    /// Replaces the value by its entry in the table; a value without an
+    /// entry is an error
+    Lookup(BTreeMap<String, Value>),
+    /// Wraps a code in a CodeableConcept of the given system
+    Concept(String),
+    /// Converts a `{value, unit}` quantity to the given UCUM unit
+    Convert(String),
+}
+
+impl Mapping {
+    /// Reads a mapping from YAML; errors carry the path of the offending field.
+    pub fn from_yaml(text: &str) -> Result<Self, WellAllyError> {
+        let value: Value = serde_yaml::from_str(text).map_err(|err| WellAllyError::Parse {
+            resource_type: "Mapping".to_string(),
+            path: String::new(),
+            value: None,
+            message: err.to_string(),
+        })?;
+        error::from_value("Mapping", &value)
+    }
+
+    /// Reads a mapping from JSON; errors carry the path of the offending field.
+    pub fn from_json(json: &str) -> Result<Self, WellAllyError> {
+        error::from_str("Mapping", json)
+    }
+
+    /// The JSON of each mapped resource, before parsing.
+    pub fn map_value(&self, source: &Value) -> Result<Vec<Value>, WellAllyError> {
+        let records = match &self.records {
+            Some(records) => records.evaluate_value(source),
+            None => vec![source.clone()],
+        };
+        records.iter().map(|record| self.object(&self.fields, record, "")).collect()
+    }
+
+    /// Maps `source` onto resources of type `R`, parsed and validated.
+    pub fn map<R: Resource + Validate>(&self, source: &Value) -> Result<Vec<R>, WellAllyError> {
+        if self.resource_type != R::RESOURCE_TYPE {
+            return Err(WellAllyError::Conversion {
+                resource_type: self.resource_type.clone(),
+                path: String::new(),
+                value: None,
+                message: format!("the mapping produces {}, not {}", self.resource_type, R::RESOURCE_TYPE),
+            });
+        }
+        let mut resources = Vec::new();
+        for value in self.map_value(source)? {
+            let resource = R::from_json_value(&value)?;
+            if let Some(err) = resource.validation_errors().into_iter().next() {
+                return Err(err);
+            }
+            resources.push(resource);
+        }
+        Ok(resources)
+    }
+
+    /// The JSON of each resource mapped from CSV text with a header row.
+    pub fn map_csv_value(&self, text: &str) -> Result<Vec<Value>, WellAllyError> {
+        self.map_value(&csv_rows(text)?)
+    }
+
+    /// Maps CSV text with a header row; see the module documentation.
+    pub fn map_csv<R: Resource + Validate>(&self, text: &str) -> Result<Vec<R>, WellAllyError> {
+        self.map(&csv_rows(text)?)
+    }
+
+    fn object(&self, fields: &[FieldMapping], record: &Value, path: &str) -> Result<Value, WellAllyError> {
+        let mut object = Map::new();
+        for field in fields {
+            let target = join(path, &field.target);
+            if let Some(value) = self.field(field, record, &target)? {
+                insert(&mut object, &field.target, value);
+            }
+        }
+        Ok(Value::Object(object))
+    }
+
+    fn field(&self, field: &FieldMapping, record: &Value, target: &str) -> Result<Option<Value>, WellAllyError> {
+        let inputs = match (&field.source, &field.fields) {
+            (Some(source), _) => source.evaluate_value(record),
+            (None, Some(_)) => vec![record.clone()],
+            (None, None) => Vec::new(),
+        };
+        let mut values = Vec::new();
+        for (i, input) in inputs.iter().enumerate() {
+            let path = if field.array { index(target, i) } else { target.to_string() };
+            let mut value = match &field.fields {
+                Some(fields) => self.object(fields, input, &path)?,
+                None => input.clone(),
+            };
+            for transform in &field.transforms {
+                value = transform.apply(value).map_err(|message| self.error(&path, input, message))?;
+            }
+            values.push(value);
+        }
+        if values.is_empty() {
+            return Ok(field.default.clone());
+        }
+        if field.array {
+            return Ok(Some(Value::Array(values)));
+        }
+        match <[Value; 1]>::try_from(values) {
+            Ok([value]) => Ok(Some(value)),
+            Err(values) => {
+                let message = format!("the source selects {} values; set `array: true` to keep them all", values.len());
+                Err(self.error(target, &Value::Array(values), message))
+            }
+        }
+    }
+
+    fn error(&self, path: &str, value: &Value, message: String) -> WellAllyError {
+        WellAllyError::Conversion {
+            resource_type: self.resource_type.clone(),
+            path: path.to_string(),
+            value: Some(Box::new(value.clone())),
+            message,
+        }
+    }
+}
+
+impl Transform {
+    fn apply(&self, value: Value) -> Result<Value, String> {
+        let text = || match &value {
+            Value::String(text) => Ok(text.clone()),
+            Value::Number(number) => Ok(number.to_string()),
+            Value::Bool(flag) => Ok(flag.to_string()),
+            _ => Err("expected text".to_string()),
+        };
+        Ok(match self {
+            Transform::Trim => Value::String(text()?.trim().to_string()),
+            Transform::Uppercase => Value::String(text()?.to_uppercase()),
+            Transform::Lowercase => Value::String(text()?.to_lowercase()),
+            Transform::Number => match &value {
+                Value::Number(_) => value,
+                _ => {
+                    let number = text()?.trim().parse::<f64>().map_err(|_| "expected a number".to_string())?;
+                    serde_json::Number::from_f64(number).map(Value::Number).ok_or("expected a finite number")?
+                }
+            },
+            Transform::Date(format) => Value::String(date(&text()?, format)?),
+            Transform::Lookup(table) => match table.get(&text()?) {
+                Some(mapped) => mapped.clone(),
+                None => return Err(format!("no lookup entry for {}", value)),
+            },
+            Transform::Concept(system) => serde_json::json!({ "coding": [{ "system": system, "code": text()? }] }),
+            Transform::Convert(unit) => {
+                let amount = value.get("value").and_then(Value::as_f64);
+                let from = value.get("unit").and_then(Value::as_str);
+                let (Some(amount), Some(from)) = (amount, from) else {
+                    return Err("expected a quantity with a numeric value and a unit".to_string());
+                };
+                let converted = ucum::convert(amount, from, unit).map_err(|err| err.to_string())?;
+                let mut quantity = value.clone();
+                quantity["value"] = converted.into();
+                quantity["unit"] = unit.as_str().into();
+                quantity
+            }
+        })
+    }
+}
+
+/// `text` read with the chrono `format`, written as an ISO 8601 date-time
+/// (with its offset, if the format has one) or date.
+fn date(text: &str, format: &str) -> Result<String, String> {
+    let text = text.trim();
+    if let Ok(at) = DateTime::parse_from_str(text, format) {
+        return Ok(at.to_rfc3339());
+    }
+    if let Ok(at) = NaiveDateTime::parse_from_str(text, format) {
+        return Ok(at.format("%Y-%m-%dT%H:%M:%S").to_string());
+    }
+    NaiveDate::parse_from_str(text, format)
+        .map(|date| date.format("%Y-%m-%d").to_string())
+        .map_err(|err| format!("cannot read '{}' as {}: {}", text, format, err))
+}
+
+/// Sets the dotted `target` field of `object`, creating the objects along the way.
+fn insert(object: &mut Map<String, Value>, target: &str, value: Value) {
+    match target.split_once('.') {
+        None => {
+            object.insert(target.to_string(), value);
+        }
+        Some((head, rest)) => {
+            let child = object.entry(head).or_insert_with(|| Value::Object(Map::new()));
+            if !child.is_object() {
+                *child = Value::Object(Map::new());
+            }
+            if let Value::Object(child) = child {
+                insert(child, rest, value);
+            }
+        }
+    }
+}
+
+/// CSV text with a header row as `{"rows": [{"<column>": "<cell>", ...}]}`;
+/// empty cells are left out.
+fn csv_rows(text: &str) -> Result<Value, WellAllyError> {
+    let parse_error = |err: csv::Error| WellAllyError::Parse {
+        resource_type: "CSV".to_string(),
+        path: err
+            .position()
+            .map_or_else(String::new, |position| index("rows", position.record().saturating_sub(1) as usize)),
+        value: None,
+        message: err.to_string(),
+    };
+    let mut reader = csv::ReaderBuilder::new().trim(csv::Trim::All).from_reader(text.as_bytes());
+    let headers = reader.headers().map_err(parse_error)?.clone();
+    let mut rows = Vec::new();
+    for record in reader.records() {
+        let record = record.map_err(parse_error)?;
+        let row: Map<String, Value> = headers
+            .iter()
+            .zip(record.iter())
+            .filter(|(_, cell)| !cell.is_empty())
+            .map(|(column, cell)| (column.to_string(), Value::String(cell.to_string())))
+            .collect();
+        rows.push(Value::Object(row));
+    }
+    Ok(serde_json::json!({ "rows": rows }))
+}
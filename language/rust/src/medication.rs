@@ -4,12 +4,21 @@
 //! Website: https://www.wellally.tech/
 //! Schema: https://wellall.health/schemas/medication/v0.1.0
 
+use alloc::{string::String, vec::Vec};
 use serde::{Deserialize, Serialize};
-use chrono::NaiveDate;
-use crate::common::{Coding, CodeableConcept, Route};
+use chrono::{Days, NaiveDate};
+use crate::common::{Annotation, Coding, CodeableConcept, Quantity, Route};
+use crate::extension::Extension;
 
 /// Medication dosage amount.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "DosageInput")
+)]
 pub struct Dosage {
     /// Dose amount
     pub value: f64,
@@ -17,13 +26,56 @@ pub struct Dosage {
     pub unit: String,
 }
 
+/// US DEA controlled substance schedule, written "CII" as on labels.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "async-graphql", derive(async_graphql::Enum))]
+pub enum DeaSchedule {
+    /// No accepted medical use (e.g., heroin)
+    #[serde(rename = "CI")]
+    ScheduleI,
+    /// High potential for abuse (e.g., oxycodone, methylphenidate)
+    #[serde(rename = "CII")]
+    ScheduleII,
+    /// Moderate potential for abuse (e.g., buprenorphine, testosterone)
+    #[serde(rename = "CIII")]
+    ScheduleIII,
+    /// Low potential for abuse (e.g., alprazolam, tramadol)
+    #[serde(rename = "CIV")]
+    ScheduleIV,
+    /// Lowest potential for abuse (e.g., pregabalin, low-dose codeine cough syrups)
+    #[serde(rename = "CV")]
+    ScheduleV,
+}
+
+impl DeaSchedule {
+    /// Refills federal law allows on one prescription: none for schedules I
+    /// and II, five within six months for III and IV; `None` for V, which
+    /// has no federal limit.
+    pub fn max_refills(&self) -> Option<u32> {
+        match self {
+            DeaSchedule::ScheduleI | DeaSchedule::ScheduleII => Some(0),
+            DeaSchedule::ScheduleIII | DeaSchedule::ScheduleIV => Some(5),
+            DeaSchedule::ScheduleV => None,
+        }
+    }
+}
+
 /// Medication administration record.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "MedicationRecordInput")
+)]
 pub struct MedicationRecord {
     /// Unique record identifier
     pub id: String,
     /// Reference to Person.id
-    #[serde(rename = "patientId")]
+    #[serde(rename = "patientId", alias = "patient_id")]
     pub patient_id: String,
     /// Medication code (RxNorm)
     pub medication: Coding,
@@ -32,7 +84,7 @@ pub struct MedicationRecord {
     /// Administration route (PO, IV, etc.)
     pub route: Route,
     /// Start date
-    #[serde(rename = "startDate")]
+    #[serde(rename = "startDate", alias = "start_date")]
     pub start_date: NaiveDate,
     /// Medication form (tablet, capsule, etc.)
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -41,10 +93,10 @@ pub struct MedicationRecord {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub frequency: Option<String>,
     /// Treatment duration in days
-    #[serde(rename = "durationDays", skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "durationDays", alias = "duration_days", skip_serializing_if = "Option::is_none")]
     pub duration_days: Option<i32>,
     /// End date
-    #[serde(rename = "endDate", skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "endDate", alias = "end_date", skip_serializing_if = "Option::is_none")]
     pub end_date: Option<NaiveDate>,
     /// Indication for use
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -52,4 +104,114 @@ pub struct MedicationRecord {
     /// Additional instructions
     #[serde(skip_serializing_if = "Option::is_none")]
     pub instructions: Option<String>,
+    /// Taken as needed (PRN) rather than on a schedule
+    #[serde(rename = "isPrn", alias = "is_prn", default, skip_serializing_if = "core::ops::Not::not")]
+    pub is_prn: bool,
+    /// What the as-needed doses are for (e.g., SNOMED CT pain)
+    #[serde(rename = "prnReason", alias = "prn_reason", skip_serializing_if = "Option::is_none")]
+    pub prn_reason: Option<CodeableConcept>,
+    /// Most that may be taken in 24 hours (e.g., 4000 mg of paracetamol)
+    #[serde(rename = "maxDosePerDay", alias = "max_dose_per_day", skip_serializing_if = "Option::is_none")]
+    pub max_dose_per_day: Option<Quantity>,
+    /// US DEA controlled substance schedule; absent for uncontrolled drugs
+    #[serde(rename = "deaSchedule", alias = "dea_schedule", skip_serializing_if = "Option::is_none")]
+    pub dea_schedule: Option<DeaSchedule>,
+    /// Free-text notes and comments
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notes: Option<Vec<Annotation>>,
+    /// Schema version the resource was written against (e.g., "0.1.0"); absent means 0.1.0
+    #[serde(rename = "schemaVersion", alias = "schema_version", skip_serializing_if = "Option::is_none")]
+    pub schema_version: Option<String>,
+    /// Confidentiality and sensitivity labels (e.g., v3 ActCode HIV); see [`crate::security`]
+    #[serde(rename = "securityLabels", alias = "security_labels", skip_serializing_if = "Option::is_none")]
+    pub security_labels: Option<Vec<Coding>>,
+    /// Site-specific extensions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extensions: Option<Vec<Extension>>,
+    /// Properties this model version does not define, kept for re-serialization
+    #[cfg(feature = "preserve-unknown")]
+    #[serde(flatten)]
+    #[cfg_attr(feature = "schemars", schemars(skip))]
+    #[cfg_attr(feature = "utoipa", schema(ignore))]
+    #[cfg_attr(feature = "async-graphql", graphql(skip))]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl MedicationRecord {
+    /// Whether the drug is a controlled substance.
+    pub fn is_controlled(&self) -> bool {
+        self.dea_schedule.is_some()
+    }
+
+    /// Amount taken per day: the dose times the doses per day of `frequency`.
+    /// `None` without a frequency or for as-needed (PRN) and unrecognised ones.
+    pub fn daily_dose(&self) -> Option<Quantity> {
+        let per_day = doses_per_day(self.frequency.as_deref()?)?;
+        Some(Quantity::new(self.dosage.value * per_day, self.dosage.unit.clone()))
+    }
+
+    /// Days of treatment: `durationDays`, else the days from the start to the
+    /// end date inclusive.
+    pub fn treatment_days(&self) -> Option<i64> {
+        match (self.duration_days, self.end_date) {
+            (Some(days), _) => Some(days.into()),
+            (None, Some(end)) => Some((end - self.start_date).num_days() + 1),
+            (None, None) => None,
+        }
+        .filter(|days| *days > 0)
+    }
+
+    /// Last day of treatment, when its length is known.
+    pub fn last_date(&self) -> Option<NaiveDate> {
+        self.start_date.checked_add_days(Days::new(self.treatment_days()? as u64 - 1))
+    }
+
+    /// Whether the medication is taken on `date`: on or after the start date
+    /// and, when the treatment length is known, within it.
+    pub fn is_active_on(&self, date: NaiveDate) -> bool {
+        date >= self.start_date && self.treatment_days().is_none_or(|days| (date - self.start_date).num_days() < days)
+    }
+
+    /// Amount needed for the whole treatment, in the dose unit.
+    pub fn total_quantity_needed(&self) -> Option<Quantity> {
+        let daily = self.daily_dose()?;
+        let days = self.treatment_days()?;
+        Some(Quantity::new(daily.value * days as f64, daily.unit))
+    }
+
+    /// Whole days `quantity_dispensed` lasts at the daily dose. `None` when the
+    /// daily dose is unknown or the dispensed unit differs from the dose unit.
+    pub fn days_supply(&self, quantity_dispensed: &Quantity) -> Option<i64> {
+        let daily = self.daily_dose()?;
+        if daily.unit != quantity_dispensed.unit || daily.value <= 0.0 || quantity_dispensed.value < 0.0 {
+            return None;
+        }
+        // Truncation floors the non-negative ratio; the epsilon absorbs
+        // rounding such as 3 x 0.1 falling short of 0.3.
+        Some((quantity_dispensed.value / daily.value + 1e-9) as i64)
+    }
+}
+
+/// Doses per day of a dosing frequency code: QD/OD/daily, BID, TID, QID,
+/// QOD, QW/weekly, QnH (every n hours), QAM/QPM/QHS/HS. Case and dots are
+/// ignored (`b.i.d.`). `None` for PRN (as needed) and unknown codes.
+pub fn doses_per_day(frequency: &str) -> Option<f64> {
+    let code = frequency.chars().filter(|c| !matches!(c, '.' | ' ')).collect::<String>().to_ascii_uppercase();
+    let per_day = match code.as_str() {
+        "QD" | "OD" | "DAILY" | "QAM" | "QPM" | "QHS" | "HS" => 1.0,
+        "BID" => 2.0,
+        "TID" => 3.0,
+        "QID" => 4.0,
+        "QOD" => 0.5,
+        "QW" | "WEEKLY" => 1.0 / 7.0,
+        "QH" => 24.0,
+        _ => {
+            let hours: f64 = code.strip_prefix('Q')?.strip_suffix('H')?.parse().ok()?;
+            if hours <= 0.0 {
+                return None;
+            }
+            24.0 / hours
+        }
+    };
+    Some(per_day)
 }
@@ -0,0 +1,189 @@
+//! Schema versions and migrations between them.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//!
+//! Every resource may declare the schema version it was written against in
+//! `schemaVersion`; documents without it predate the field and are
+//! [`SchemaVersion::V0_1_0`]. [`migrate`] rewrites a JSON document from one
+//! published version to another by chaining [`Migration`] steps, so stored
+//! records can be upgraded before they are parsed with the current models:
+//!
+//! ```
+//! use serde_json::json;
+//! use wellally::migrations::{self, SchemaVersion};
+//!
+//! let stored = json!({"id": "lab-1", "patientId": "p-1", "issuedAt": "2024-11-02", "results": []});
+//! assert_eq!(migrations::detect_version(&stored)?, SchemaVersion::V0_1_0);
+//! let current = migrations::upgrade(stored)?;
+//! # Ok::<(), wellally::WellAllyError>(())
+//! ```
+//!
+//! Each breaking release adds its steps to [`MIGRATIONS`]; services with
+//! their own intermediate formats can register more on a [`Migrator`].
+
+use alloc::{format, string::{String, ToString}, vec::Vec};
+use core::fmt;
+use core::str::FromStr;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+
+use crate::error::WellAllyError;
+
+/// Semantic version of the published schemas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SchemaVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl SchemaVersion {
+    /// First published version
+    pub const V0_1_0: SchemaVersion = SchemaVersion::new(0, 1, 0);
+
+    /// Version the models in this crate implement
+    pub const CURRENT: SchemaVersion = SchemaVersion::V0_1_0;
+
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self { major, minor, patch }
+    }
+}
+
+impl fmt::Display for SchemaVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+impl FromStr for SchemaVersion {
+    type Err = WellAllyError;
+
+    /// Accepts `MAJOR.MINOR.PATCH`, optionally prefixed with `v`.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || version_error("", Some(value.into()), "expected a version such as \"0.1.0\"");
+        let mut parts = value.strip_prefix('v').unwrap_or(value).split('.');
+        let mut number = || -> Result<u32, WellAllyError> {
+            let part = parts.next().filter(|part| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit()));
+            part.and_then(|part| part.parse().ok()).ok_or_else(invalid)
+        };
+        let version = SchemaVersion::new(number()?, number()?, number()?);
+        if parts.next().is_some() {
+            return Err(invalid());
+        }
+        Ok(version)
+    }
+}
+
+impl Serialize for SchemaVersion {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for SchemaVersion {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        value.parse().map_err(|err: WellAllyError| serde::de::Error::custom(err.message()))
+    }
+}
+
+fn version_error(path: &str, value: Option<Value>, message: impl Into<String>) -> WellAllyError {
+    WellAllyError::Conversion {
+        resource_type: "SchemaVersion".to_string(),
+        path: path.to_string(),
+        value: value.map(Into::into),
+        message: message.into(),
+    }
+}
+
+/// Version a document was written against: its `schemaVersion`, or 0.1.0
+/// for documents that predate the field.
+pub fn detect_version(document: &Value) -> Result<SchemaVersion, WellAllyError> {
+    match document.get("schemaVersion") {
+        None | Some(Value::Null) => Ok(SchemaVersion::V0_1_0),
+        Some(Value::String(version)) => {
+            version.parse().map_err(|err: WellAllyError| version_error("schemaVersion", Some(version.as_str().into()), err.message()))
+        }
+        Some(other) => Err(version_error("schemaVersion", Some(other.clone()), "must be a string")),
+    }
+}
+
+/// One step between two adjacent versions, in either direction.
+#[derive(Debug, Clone, Copy)]
+pub struct Migration {
+    pub from: SchemaVersion,
+    pub to: SchemaVersion,
+    /// Rewrites a document of any resource type in place
+    pub apply: fn(&mut Value) -> Result<(), WellAllyError>,
+}
+
+/// Steps between the published versions. Empty while 0.1.0 is the only one.
+pub const MIGRATIONS: &[Migration] = &[];
+
+/// Set of migration steps that chains them to move documents between versions.
+#[derive(Debug, Clone)]
+pub struct Migrator {
+    steps: Vec<Migration>,
+}
+
+impl Default for Migrator {
+    fn default() -> Self {
+        Self { steps: MIGRATIONS.to_vec() }
+    }
+}
+
+impl Migrator {
+    /// Migrator with the published steps.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a step.
+    pub fn with(mut self, step: Migration) -> Self {
+        self.steps.push(step);
+        self
+    }
+
+    /// Rewrites `document` from version `from` to `to`, stamping `schemaVersion`
+    /// when it changes. Steps are taken towards `to`, preferring the longest
+    /// jump that does not overshoot it.
+    pub fn migrate(&self, mut document: Value, from: SchemaVersion, to: SchemaVersion) -> Result<Value, WellAllyError> {
+        let mut current = from;
+        while current != to {
+            let candidates = self.steps.iter().filter(|step| step.from == current);
+            let step = if to > current {
+                candidates.filter(|step| step.to > current && step.to <= to).max_by_key(|step| step.to)
+            } else {
+                candidates.filter(|step| step.to < current && step.to >= to).min_by_key(|step| step.to)
+            };
+            let step = step.ok_or_else(|| {
+                version_error("schemaVersion", None, format!("no migration from {} towards {}", current, to))
+            })?;
+            (step.apply)(&mut document)?;
+            current = step.to;
+        }
+        if from != to {
+            if let Value::Object(map) = &mut document {
+                map.insert("schemaVersion".into(), to.to_string().into());
+            }
+        }
+        Ok(document)
+    }
+
+    /// Rewrites `document` from its detected version to [`SchemaVersion::CURRENT`].
+    pub fn upgrade(&self, document: Value) -> Result<Value, WellAllyError> {
+        let from = detect_version(&document)?;
+        self.migrate(document, from, SchemaVersion::CURRENT)
+    }
+}
+
+/// Rewrites `document` from version `from` to `to` with the published steps.
+pub fn migrate(document: Value, from: SchemaVersion, to: SchemaVersion) -> Result<Value, WellAllyError> {
+    Migrator::new().migrate(document, from, to)
+}
+
+/// Rewrites `document` from its detected version to the current one.
+pub fn upgrade(document: Value) -> Result<Value, WellAllyError> {
+    Migrator::new().upgrade(document)
+}
@@ -0,0 +1,237 @@
+//! Clinician-readable narrative text rendered from structured records.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//!
+//! PDF and portal layers show reports as prose sections rather than JSON.
+//! [`Template`] is a small logic-less engine: `{{name}}` inserts a field,
+//! `{{#name}}...{{/name}}` renders its body once per value of a non-empty
+//! field with `{{.}}` standing for the value, and `{{^name}}...{{/name}}`
+//! renders only when the field is empty. Output is plain text; nothing is
+//! escaped.
+//!
+//! ```
+//! use wellally::narrative::Template;
+//! use wellally::{ImagingReport, Resource};
+//!
+//! let report = ImagingReport::from_json(r#"{"id":"img-1","patientId":"p-1",
+//!     "modality":{"system":"http://dicom.nema.org/resources/ontology/DCM","code":"CT","display":"CT"},
+//!     "bodySite":{"system":"http://snomed.info/sct","code":"51185008","display":"chest"},
+//!     "reportedAt":"2024-10-15T14:20:00Z","impression":"No acute findings."}"#).unwrap();
+//! let template = Template::parse("{{modalityDisplay}} {{bodySite}}: {{impression}}").unwrap();
+//! assert_eq!(report.render_narrative(&template), "CT chest: No acute findings.");
+//! ```
+
+use alloc::collections::BTreeMap;
+use alloc::{boxed::Box, format, string::{String, ToString}, vec, vec::Vec};
+use core::str::FromStr;
+
+use crate::common::{CodeableConcept, Laterality};
+use crate::error::WellAllyError;
+use crate::imaging_report::ImagingReport;
+
+/// Values a template can refer to by name; single values are one-item lists.
+pub type NarrativeFields = BTreeMap<String, Vec<String>>;
+
+/// Default imaging report layout: clinical history (when given), technique,
+/// comparison, findings and impression sections under a modality and body
+/// site heading.
+pub const IMAGING_REPORT_TEMPLATE: &str = "\
+{{modalityDisplay}} {{bodySite}}
+Reported: {{reportedAt}}{{#performer}} by {{.}}{{/performer}}
+
+{{#clinicalHistory}}CLINICAL HISTORY:
+{{.}}
+
+{{/clinicalHistory}}TECHNIQUE:
+{{#technique}}{{.}}{{/technique}}{{^technique}}{{modalityDisplay}} of the {{bodySite}}.{{/technique}}
+{{#radiationDose}}Radiation dose: {{.}}
+{{/radiationDose}}
+COMPARISON:
+{{#comparison}}{{.}}{{/comparison}}{{^comparison}}None.{{/comparison}}
+
+FINDINGS:
+{{#findings}}- {{.}}
+{{/findings}}{{^findings}}No findings recorded.
+{{/findings}}
+IMPRESSION:
+{{#impression}}{{.}}{{/impression}}{{^impression}}Pending.{{/impression}}
+";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Node {
+    Text(String),
+    Field(String),
+    Section { name: String, inverted: bool, body: Vec<Node> },
+}
+
+/// A parsed narrative template.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    nodes: Vec<Node>,
+}
+
+fn template_error(tag: &str, message: &str) -> WellAllyError {
+    WellAllyError::Parse {
+        resource_type: "Template".into(),
+        path: String::new(),
+        value: Some(Box::new(serde_json::Value::from(tag))),
+        message: message.into(),
+    }
+}
+
+impl Template {
+    /// Parses `source`, failing on an unterminated tag or an unbalanced section.
+    pub fn parse(source: &str) -> Result<Self, WellAllyError> {
+        // Open sections with the nodes collected before each of them.
+        let mut stack: Vec<(String, bool, Vec<Node>)> = Vec::new();
+        let mut nodes = Vec::new();
+        let mut rest = source;
+        while let Some(start) = rest.find("{{") {
+            if start > 0 {
+                nodes.push(Node::Text(rest[..start].to_string()));
+            }
+            let after = &rest[start + 2..];
+            let end = after.find("}}").ok_or_else(|| template_error(&rest[start..], "unterminated tag"))?;
+            let tag = after[..end].trim();
+            rest = &after[end + 2..];
+            if let Some(name) = tag.strip_prefix('#').or_else(|| tag.strip_prefix('^')) {
+                let parent = core::mem::take(&mut nodes);
+                stack.push((name.trim().to_string(), tag.starts_with('^'), parent));
+            } else if let Some(name) = tag.strip_prefix('/') {
+                let (open, inverted, parent) = stack.pop().ok_or_else(|| template_error(tag, "closes a section that is not open"))?;
+                if open != name.trim() {
+                    return Err(template_error(tag, &format!("does not close section '{}'", open)));
+                }
+                let body = core::mem::replace(&mut nodes, parent);
+                nodes.push(Node::Section { name: open, inverted, body });
+            } else if tag.is_empty() {
+                return Err(template_error("{{}}", "empty tag"));
+            } else {
+                nodes.push(Node::Field(tag.to_string()));
+            }
+        }
+        if !rest.is_empty() {
+            nodes.push(Node::Text(rest.to_string()));
+        }
+        match stack.pop() {
+            Some((open, _, _)) => Err(template_error(&open, "section is not closed")),
+            None => Ok(Template { nodes }),
+        }
+    }
+
+    /// Renders the template with `fields`; missing fields render as empty.
+    pub fn render(&self, fields: &NarrativeFields) -> String {
+        let mut out = String::new();
+        render_nodes(&self.nodes, fields, None, &mut out);
+        out
+    }
+}
+
+impl FromStr for Template {
+    type Err = WellAllyError;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        Template::parse(source)
+    }
+}
+
+fn render_nodes(nodes: &[Node], fields: &NarrativeFields, current: Option<&str>, out: &mut String) {
+    for node in nodes {
+        match node {
+            Node::Text(text) => out.push_str(text),
+            Node::Field(name) if name == "." => out.push_str(current.unwrap_or_default()),
+            Node::Field(name) => {
+                if let Some(values) = fields.get(name) {
+                    out.push_str(&values.join("\n"));
+                }
+            }
+            Node::Section { name, inverted, body } => {
+                let values = fields.get(name).map(Vec::as_slice).unwrap_or_default();
+                if *inverted {
+                    if values.is_empty() {
+                        render_nodes(body, fields, current, out);
+                    }
+                } else {
+                    for value in values {
+                        render_nodes(body, fields, Some(value), out);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn put(fields: &mut NarrativeFields, name: &str, value: Option<&str>) {
+    if let Some(value) = value.map(str::trim).filter(|value| !value.is_empty()) {
+        fields.insert(name.into(), vec![value.into()]);
+    }
+}
+
+/// Text of `concept`, else the display or code of its first coding.
+fn concept_label(concept: &CodeableConcept) -> Option<String> {
+    let coding = concept.coding.first().map(|coding| coding.display.as_deref().unwrap_or(&coding.code));
+    let label = concept.text.as_deref().or(coding);
+    label.map(str::trim).filter(|label| !label.is_empty()).map(Into::into)
+}
+
+impl ImagingReport {
+    /// Fields available to narrative templates: `id`, `patientId`,
+    /// `modality`, `modalityDisplay`, `bodySite` (with laterality and
+    /// qualifiers), `bodySiteCode`, `laterality`, `reportedAt`, `studyInstanceUid`, `performer`, `performerRole`,
+    /// `clinicalHistory`, `protocol`, `technique`, `comparison`,
+    /// `radiationDose`, `impression` and the lists `reasons`, `findings` and
+    /// `notes`. Empty values are left out.
+    pub fn narrative_fields(&self) -> NarrativeFields {
+        let mut fields = NarrativeFields::new();
+        put(&mut fields, "id", Some(&self.id));
+        put(&mut fields, "patientId", Some(&self.patient_id));
+        put(&mut fields, "modality", Some(self.modality.code.as_str()));
+        put(&mut fields, "modalityDisplay", Some(self.modality.display.as_deref().unwrap_or(self.modality.code.as_str())));
+        put(&mut fields, "bodySite", Some(&self.body_site.label()));
+        put(&mut fields, "bodySiteCode", Some(&self.body_site.site.code));
+        put(&mut fields, "laterality", self.body_site.laterality.map(Laterality::as_str));
+        put(&mut fields, "reportedAt", Some(&self.reported_at.to_string()));
+        put(&mut fields, "studyInstanceUid", self.study_instance_uid.as_deref());
+        if let Some(performer) = &self.performer {
+            put(&mut fields, "performer", performer.name.as_deref().or(performer.id.as_deref()));
+            put(&mut fields, "performerRole", performer.role.as_deref());
+        }
+        let reasons: Vec<String> = self.reason_code.iter().flatten().filter_map(concept_label).collect();
+        if !reasons.is_empty() {
+            fields.insert("reasons".into(), reasons);
+        }
+        put(&mut fields, "clinicalHistory", self.clinical_history.as_deref());
+        put(&mut fields, "protocol", self.protocol.as_ref().and_then(concept_label).as_deref());
+        put(&mut fields, "technique", self.technique.as_deref());
+        put(&mut fields, "comparison", self.comparison.as_deref());
+        if let Some(dose) = &self.radiation_dose {
+            let parts: Vec<String> = [("CTDIvol", dose.ctdi_vol_mgy, "mGy"), ("DLP", dose.dlp_mgy_cm, "mGy·cm")]
+                .into_iter()
+                .filter_map(|(label, value, unit)| value.map(|value| format!("{} {} {}", label, value, unit)))
+                .collect();
+            put(&mut fields, "radiationDose", Some(&parts.join(", ")));
+        }
+        put(&mut fields, "impression", self.impression.as_deref());
+        let findings: Vec<String> = self.findings.iter().flatten().map(|finding| finding.trim()).filter(|finding| !finding.is_empty()).map(Into::into).collect();
+        if !findings.is_empty() {
+            fields.insert("findings".into(), findings);
+        }
+        let notes: Vec<String> = self.notes.iter().flatten().map(|note| note.text.clone()).collect();
+        if !notes.is_empty() {
+            fields.insert("notes".into(), notes);
+        }
+        fields
+    }
+
+    /// Report text laid out by `template`.
+    pub fn render_narrative(&self, template: &Template) -> String {
+        template.render(&self.narrative_fields())
+    }
+
+    /// Report text in the [`IMAGING_REPORT_TEMPLATE`] layout.
+    pub fn narrative(&self) -> String {
+        let template = Template::parse(IMAGING_REPORT_TEMPLATE).expect("built-in template is well formed");
+        self.render_narrative(&template)
+    }
+}
@@ -0,0 +1,429 @@
+//! Kidney care: dialysis sessions and chronic kidney disease (CKD) staging.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//! Schema: https://wellall.health/schemas/dialysis/v0.1.0
+//!
+//! A [`DialysisSession`] records one hemodialysis or peritoneal dialysis
+//! treatment: the modality, how long it ran, the fluid removed, the access
+//! used and any complications. [`CkdStage`] places kidney function in the
+//! KDIGO 2012 GFR (G1–G5) and albuminuria (A1–A3) categories and reads the
+//! risk from their heat map; with the `std` feature [`CkdStage::assess`]
+//! stages a patient from lab reports, computing eGFR from serum creatinine
+//! with [`crate::calc::kidney`].
+//!
+//! ```
+//! use wellally::nephrology::{AlbuminuriaCategory, CkdRisk, CkdStage, GfrCategory};
+//!
+//! let stage = CkdStage::new(41.0, Some(AlbuminuriaCategory::from_acr_mg_g(120.0)));
+//! assert_eq!(stage.gfr, GfrCategory::G3b);
+//! assert_eq!(stage.to_string(), "G3b A2");
+//! assert_eq!(stage.risk(), Some(CkdRisk::VeryHigh));
+//! ```
+
+use alloc::{string::String, vec::Vec};
+use core::fmt;
+
+use chrono::{Duration, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+
+use crate::common::{CodeableConcept, Coding, DataSource, HasDataSource, Quantity, Reference};
+use crate::datetime::FlexibleDateTime;
+use crate::extension::Extension;
+use crate::ucum;
+#[cfg(feature = "std")]
+use crate::{
+    calc::kidney::{KidneyFunction, SERUM_CREATININE_CODES},
+    family_health::Sex,
+    health::{Gender, Person},
+    lab_report::{LabReport, LabResult, LabValue},
+    safety::renal,
+};
+
+/// Ultrafiltration rate (mL/kg/h) above which hemodialysis is associated
+/// with higher mortality
+pub const ULTRAFILTRATION_RATE_LIMIT: f64 = 13.0;
+
+/// LOINC codes of urine albumin/creatinine ratio results (albumin, microalbumin)
+pub const ACR_CODES: &[&str] = &["9318-7", "14959-1"];
+
+/// Kind of dialysis.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "async-graphql", derive(async_graphql::Enum))]
+#[serde(rename_all = "kebab-case")]
+pub enum DialysisModality {
+    Hemodialysis,
+    Hemodiafiltration,
+    Hemofiltration,
+    /// Continuous ambulatory peritoneal dialysis
+    #[serde(rename = "capd")]
+    Capd,
+    /// Automated (cycler) peritoneal dialysis
+    #[serde(rename = "apd")]
+    Apd,
+}
+
+impl DialysisModality {
+    /// Whether the peritoneum is the dialysis membrane.
+    pub fn is_peritoneal(&self) -> bool {
+        matches!(self, DialysisModality::Capd | DialysisModality::Apd)
+    }
+}
+
+/// Kind of dialysis access.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "async-graphql", derive(async_graphql::Enum))]
+#[serde(rename_all = "kebab-case")]
+pub enum AccessType {
+    ArteriovenousFistula,
+    ArteriovenousGraft,
+    CentralVenousCatheter,
+    PeritonealCatheter,
+}
+
+/// Access used for a session.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "DialysisAccessInput")
+)]
+pub struct DialysisAccess {
+    /// Fistula, graft or catheter
+    #[serde(rename = "type")]
+    pub access_type: AccessType,
+    /// Where the access is (e.g., SNOMED CT left forearm)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub site: Option<Coding>,
+}
+
+/// One dialysis treatment.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "DialysisSessionInput")
+)]
+pub struct DialysisSession {
+    /// Unique session identifier
+    pub id: String,
+    /// Reference to Person.id
+    #[serde(rename = "patientId", alias = "patient_id")]
+    pub patient_id: String,
+    pub modality: DialysisModality,
+    /// When the treatment started
+    #[cfg_attr(feature = "utoipa", schema(value_type = String))]
+    pub start: FlexibleDateTime,
+    /// Length of the treatment in minutes (for peritoneal dialysis, of all exchanges or the cycler run)
+    #[serde(rename = "durationMinutes", alias = "duration_minutes")]
+    pub duration_minutes: u32,
+    /// Access the treatment ran through
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub access: Option<DialysisAccess>,
+    /// Fluid removed (net ultrafiltration for peritoneal dialysis), e.g. 2500 mL
+    #[serde(
+        rename = "ultrafiltrationVolume",
+        alias = "ultrafiltration_volume",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub ultrafiltration_volume: Option<Quantity>,
+    /// Body weight before the treatment
+    #[serde(rename = "preWeight", alias = "pre_weight", skip_serializing_if = "Option::is_none")]
+    pub pre_weight: Option<Quantity>,
+    /// Body weight after the treatment
+    #[serde(rename = "postWeight", alias = "post_weight", skip_serializing_if = "Option::is_none")]
+    pub post_weight: Option<Quantity>,
+    /// Single-pool Kt/V of the treatment (dialysis adequacy)
+    #[serde(rename = "ktV", alias = "kt_v", skip_serializing_if = "Option::is_none")]
+    pub kt_v: Option<f64>,
+    /// Complications during the treatment (e.g., hypotension, cramps, access clotting)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub complications: Option<Vec<CodeableConcept>>,
+    /// Dialysis machine or cycler (e.g., "Device/cycler-1")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device: Option<Reference>,
+    /// Device, app and capture method of the record
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<DataSource>,
+    /// Schema version the resource was written against (e.g., "0.1.0"); absent means 0.1.0
+    #[serde(rename = "schemaVersion", alias = "schema_version", skip_serializing_if = "Option::is_none")]
+    pub schema_version: Option<String>,
+    /// Confidentiality and sensitivity labels (e.g., v3 ActCode HIV); see [`crate::security`]
+    #[serde(rename = "securityLabels", alias = "security_labels", skip_serializing_if = "Option::is_none")]
+    pub security_labels: Option<Vec<Coding>>,
+    /// Site-specific extensions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extensions: Option<Vec<Extension>>,
+    /// Properties this model version does not define, kept for re-serialization
+    #[cfg(feature = "preserve-unknown")]
+    #[serde(flatten)]
+    #[cfg_attr(feature = "schemars", schemars(skip))]
+    #[cfg_attr(feature = "utoipa", schema(ignore))]
+    #[cfg_attr(feature = "async-graphql", graphql(skip))]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl DialysisSession {
+    /// Session without measurements yet.
+    pub fn new(
+        id: impl Into<String>,
+        patient_id: impl Into<String>,
+        modality: DialysisModality,
+        start: FlexibleDateTime,
+        duration_minutes: u32,
+    ) -> Self {
+        DialysisSession {
+            id: id.into(),
+            patient_id: patient_id.into(),
+            modality,
+            start,
+            duration_minutes,
+            access: None,
+            ultrafiltration_volume: None,
+            pre_weight: None,
+            post_weight: None,
+            kt_v: None,
+            complications: None,
+            device: None,
+            source: None,
+            schema_version: None,
+            security_labels: None,
+            extensions: None,
+            #[cfg(feature = "preserve-unknown")]
+            extra: Default::default(),
+        }
+    }
+
+    /// Local time the treatment ended.
+    pub fn end(&self) -> NaiveDateTime {
+        self.start.local() + Duration::minutes(i64::from(self.duration_minutes))
+    }
+
+    /// Fluid removed in mL.
+    pub fn ultrafiltration_ml(&self) -> Option<f64> {
+        let volume = self.ultrafiltration_volume.as_ref()?;
+        ucum::convert(volume.value, &volume.unit, "mL").ok()
+    }
+
+    /// Weight lost during the treatment in kg.
+    pub fn weight_loss_kg(&self) -> Option<f64> {
+        Some(kilograms(self.pre_weight.as_ref()?)? - kilograms(self.post_weight.as_ref()?)?)
+    }
+
+    /// Ultrafiltration rate in mL/kg/h, relative to the post-treatment
+    /// weight; compare with [`ULTRAFILTRATION_RATE_LIMIT`].
+    pub fn ultrafiltration_rate(&self) -> Option<f64> {
+        let weight = kilograms(self.post_weight.as_ref()?)?;
+        let hours = f64::from(self.duration_minutes) / 60.0;
+        (weight > 0.0 && hours > 0.0).then_some(self.ultrafiltration_ml()? / weight / hours)
+    }
+
+    /// Whether any complication was recorded.
+    pub fn has_complications(&self) -> bool {
+        self.complications.as_ref().is_some_and(|complications| !complications.is_empty())
+    }
+}
+
+impl HasDataSource for DialysisSession {
+    fn data_source(&self) -> Option<&DataSource> {
+        self.source.as_ref()
+    }
+}
+
+fn kilograms(weight: &Quantity) -> Option<f64> {
+    ucum::convert(weight.value, &weight.unit, "kg").ok()
+}
+
+/// KDIGO GFR category.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum GfrCategory {
+    /// 90 or more
+    G1,
+    /// 60–89
+    G2,
+    /// 45–59
+    G3a,
+    /// 30–44
+    G3b,
+    /// 15–29
+    G4,
+    /// Below 15
+    G5,
+}
+
+impl GfrCategory {
+    /// Category of an eGFR in mL/min/1.73 m².
+    pub fn from_egfr(egfr: f64) -> Self {
+        match egfr {
+            e if e >= 90.0 => GfrCategory::G1,
+            e if e >= 60.0 => GfrCategory::G2,
+            e if e >= 45.0 => GfrCategory::G3a,
+            e if e >= 30.0 => GfrCategory::G3b,
+            e if e >= 15.0 => GfrCategory::G4,
+            _ => GfrCategory::G5,
+        }
+    }
+
+    /// KDIGO code (e.g., "G3a").
+    pub fn code(&self) -> &'static str {
+        match self {
+            GfrCategory::G1 => "G1",
+            GfrCategory::G2 => "G2",
+            GfrCategory::G3a => "G3a",
+            GfrCategory::G3b => "G3b",
+            GfrCategory::G4 => "G4",
+            GfrCategory::G5 => "G5",
+        }
+    }
+
+    /// KDIGO label of the category.
+    pub fn display(&self) -> &'static str {
+        match self {
+            GfrCategory::G1 => "Normal or high",
+            GfrCategory::G2 => "Mildly decreased",
+            GfrCategory::G3a => "Mildly to moderately decreased",
+            GfrCategory::G3b => "Moderately to severely decreased",
+            GfrCategory::G4 => "Severely decreased",
+            GfrCategory::G5 => "Kidney failure",
+        }
+    }
+}
+
+/// KDIGO albuminuria category.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum AlbuminuriaCategory {
+    /// ACR below 30 mg/g
+    A1,
+    /// ACR 30–300 mg/g
+    A2,
+    /// ACR above 300 mg/g
+    A3,
+}
+
+/// ACR units with their factor to mg/g.
+const ACR_UNITS: &[(&str, f64)] = &[("mg/g", 1.0), ("ug/mg", 1.0), ("mg/mmol", 8.84), ("g/mol", 8.84)];
+
+impl AlbuminuriaCategory {
+    /// Category of a urine albumin/creatinine ratio in mg/g.
+    pub fn from_acr_mg_g(acr: f64) -> Self {
+        match acr {
+            a if a < 30.0 => AlbuminuriaCategory::A1,
+            a if a <= 300.0 => AlbuminuriaCategory::A2,
+            _ => AlbuminuriaCategory::A3,
+        }
+    }
+
+    /// Category of a ratio in `mg/g`, `ug/mg`, `mg/mmol` or `g/mol`; `None`
+    /// for other units.
+    pub fn from_acr(acr: &Quantity) -> Option<Self> {
+        let (_, factor) = ACR_UNITS.iter().find(|(unit, _)| *unit == acr.unit)?;
+        Some(Self::from_acr_mg_g(acr.value * factor))
+    }
+
+    /// KDIGO label of the category.
+    pub fn display(&self) -> &'static str {
+        match self {
+            AlbuminuriaCategory::A1 => "Normal to mildly increased",
+            AlbuminuriaCategory::A2 => "Moderately increased",
+            AlbuminuriaCategory::A3 => "Severely increased",
+        }
+    }
+}
+
+/// Prognosis of CKD by the KDIGO heat map.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "kebab-case")]
+pub enum CkdRisk {
+    /// Green: no CKD if there are no other markers of kidney damage
+    Low,
+    /// Yellow
+    ModeratelyIncreased,
+    /// Orange
+    High,
+    /// Red
+    VeryHigh,
+}
+
+/// Kidney function placed in the KDIGO categories.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CkdStage {
+    /// eGFR in mL/min/1.73 m²
+    pub egfr: f64,
+    pub gfr: GfrCategory,
+    /// Albuminuria category, when an albumin/creatinine ratio is known
+    pub albuminuria: Option<AlbuminuriaCategory>,
+}
+
+impl CkdStage {
+    /// Stage of `egfr` (mL/min/1.73 m²) with an optional albuminuria category.
+    pub fn new(egfr: f64, albuminuria: Option<AlbuminuriaCategory>) -> Self {
+        CkdStage { egfr, gfr: GfrCategory::from_egfr(egfr), albuminuria }
+    }
+
+    /// Risk from the KDIGO heat map; `None` without the albuminuria category.
+    pub fn risk(&self) -> Option<CkdRisk> {
+        use AlbuminuriaCategory::*;
+        use GfrCategory::*;
+        Some(match (self.gfr, self.albuminuria?) {
+            (G1 | G2, A1) => CkdRisk::Low,
+            (G1 | G2, A2) | (G3a, A1) => CkdRisk::ModeratelyIncreased,
+            (G1 | G2, A3) | (G3a, A2) | (G3b, A1) => CkdRisk::High,
+            _ => CkdRisk::VeryHigh,
+        })
+    }
+
+    /// Stage of a patient from their lab reports: eGFR computed with CKD-EPI
+    /// 2021 from the latest serum creatinine, or else the latest reported
+    /// eGFR, with the albuminuria of the latest albumin/creatinine ratio.
+    /// `None` when neither gives an eGFR (e.g., a child or unknown sex and
+    /// no reported eGFR).
+    #[cfg(feature = "std")]
+    pub fn assess(reports: &[LabReport], person: &Person) -> Option<Self> {
+        let calculated = latest(reports, SERUM_CREATININE_CODES).and_then(|(creatinine, date)| {
+            let age = date.years_since(person.birth_date.date())?;
+            let sex = match person.gender {
+                Some(Gender::Male) => Sex::Male,
+                Some(Gender::Female) => Sex::Female,
+                _ => return None,
+            };
+            KidneyFunction::new(creatinine, f64::from(age), sex).ok()?.egfr()
+        });
+        let egfr = calculated.or_else(|| renal::latest_egfr(reports).map(|egfr| egfr.value))?;
+        let albuminuria = latest(reports, ACR_CODES).and_then(|(acr, _)| AlbuminuriaCategory::from_acr(acr));
+        Some(CkdStage::new(egfr, albuminuria))
+    }
+}
+
+impl fmt::Display for CkdStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.gfr.code())?;
+        if let Some(albuminuria) = self.albuminuria {
+            write!(f, " {:?}", albuminuria)?;
+        }
+        Ok(())
+    }
+}
+
+/// Quantity of the latest result with one of the LOINC `codes`, with the
+/// date it applies to.
+#[cfg(feature = "std")]
+fn latest<'a>(reports: &'a [LabReport], codes: &[&str]) -> Option<(&'a Quantity, chrono::NaiveDate)> {
+    let coded = |result: &LabResult| result.code.coding.iter().any(|coding| codes.contains(&coding.code.as_str()));
+    reports
+        .iter()
+        .flat_map(|report| report.results.iter().filter(|result| coded(result)).map(move |result| (report, result)))
+        .filter_map(|(report, result)| match &result.value {
+            LabValue::Quantity(quantity) => Some((quantity, report.effective_of(result))),
+            _ => None,
+        })
+        .max_by_key(|(_, effective)| effective.sort_key())
+        .and_then(|(quantity, effective)| Some((quantity, effective.first_date()?)))
+}
@@ -0,0 +1,372 @@
+//! Export to the OMOP Common Data Model (CDM v5.4).
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//!
+//! [`OmopExporter`] turns resources into rows of the OHDSI tables: `Person`
+//! into PERSON, each `LabResult` into MEASUREMENT, `MedicationRecord` into
+//! DRUG_EXPOSURE and `Condition` into CONDITION_OCCURRENCE. Rows serialize
+//! with the CDM column names, so they can be written as CSV or inserted into
+//! a CDM database as they are.
+//!
+//! Standard concept ids come from a lookup hook, given each coding (and each
+//! unit, as a UCUM coding): usually a [`ConceptMap`] loaded from the OHDSI
+//! vocabularies, or a call into a vocabulary service. Codes the hook does not
+//! know map to concept 0 ("No matching concept"); the original code is kept
+//! in the `*_source_value` column either way.
+//!
+//! ```
+//! use wellally::io::AnyResource;
+//! use wellally::omop::{ConceptMap, OmopExporter};
+//!
+//! let condition: AnyResource = serde_json::from_value(serde_json::json!({
+//!     "id": "cond-1", "patientId": "p-1", "clinicalStatus": "active", "onset": "2023-04-02",
+//!     "code": {"coding": [{"system": "http://snomed.info/sct", "code": "44054006"}]}
+//! }))
+//! .unwrap();
+//! let mut concepts = ConceptMap::new();
+//! concepts.insert("http://snomed.info/sct", "44054006", 201826);
+//! let tables = OmopExporter::new().with_concepts(move |coding| concepts.get(coding)).export(&[condition]);
+//! assert_eq!(tables.condition_occurrence[0].condition_concept_id, 201826);
+//! assert_eq!(tables.condition_occurrence[0].person_id, 1);
+//! ```
+
+use std::collections::BTreeMap;
+
+use chrono::{Datelike, NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+
+use crate::catalog;
+use crate::common::{CodeableConcept, Coding, Comparator, Quantity};
+use crate::condition::{Condition, VerificationStatus};
+use crate::datetime::DatePrecision;
+use crate::health::{Gender, Person};
+use crate::io::AnyResource;
+use crate::lab_report::{LabReport, LabResult, LabValue};
+use crate::medication::MedicationRecord;
+
+/// Concept id for "No matching concept"
+pub const NO_MATCHING_CONCEPT: i32 = 0;
+/// Type concept "EHR", the provenance of every exported row
+pub const EHR: i32 = 32817;
+/// Gender concept "MALE"
+pub const MALE: i32 = 8507;
+/// Gender concept "FEMALE"
+pub const FEMALE: i32 = 8532;
+
+/// Standard concept id of a coding, as given to [`OmopExporter::with_concepts`].
+type Lookup<'a> = Box<dyn Fn(&Coding) -> Option<i32> + 'a>;
+
+/// Standard concept ids by code system URI and code.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConceptMap {
+    concepts: BTreeMap<String, BTreeMap<String, i32>>,
+}
+
+impl ConceptMap {
+    /// Empty map.
+    pub fn new() -> Self {
+        ConceptMap::default()
+    }
+
+    /// Maps `code` of `system` to `concept_id`, replacing any earlier mapping.
+    pub fn insert(&mut self, system: impl Into<String>, code: impl Into<String>, concept_id: i32) {
+        self.concepts.entry(system.into()).or_default().insert(code.into(), concept_id);
+    }
+
+    /// Concept id of `coding`, if mapped.
+    pub fn get(&self, coding: &Coding) -> Option<i32> {
+        self.concepts.get(&coding.system)?.get(&coding.code).copied()
+    }
+}
+
+impl<S: Into<String>, C: Into<String>> FromIterator<(S, C, i32)> for ConceptMap {
+    fn from_iter<I: IntoIterator<Item = (S, C, i32)>>(iter: I) -> Self {
+        let mut map = ConceptMap::new();
+        for (system, code, concept_id) in iter {
+            map.insert(system, code, concept_id);
+        }
+        map
+    }
+}
+
+/// Maps resources to OMOP CDM rows.
+pub struct OmopExporter<'a> {
+    concepts: Lookup<'a>,
+}
+
+impl Default for OmopExporter<'_> {
+    fn default() -> Self {
+        OmopExporter::new()
+    }
+}
+
+impl<'a> OmopExporter<'a> {
+    /// Exporter mapping every code to [`NO_MATCHING_CONCEPT`].
+    pub fn new() -> Self {
+        OmopExporter { concepts: Box::new(|_| None) }
+    }
+
+    /// Sets the standard concept id lookup. It is given the codings of
+    /// conditions, lab results, coded lab values, medications and routes,
+    /// and units as codings of [`catalog::UCUM`].
+    pub fn with_concepts(mut self, lookup: impl Fn(&Coding) -> Option<i32> + 'a) -> Self {
+        self.concepts = Box::new(lookup);
+        self
+    }
+
+    /// Rows for `resources`. Person ids are numbered from 1 in order of
+    /// first appearance, and every table numbers its rows from 1. Resources
+    /// of other types, refuted conditions and conditions entered in error,
+    /// and conditions without an onset or recorded date are left out.
+    pub fn export(&self, resources: &[AnyResource]) -> OmopTables {
+        let mut tables = OmopTables::default();
+        for resource in resources {
+            let next = tables.person_ids.len() as i64 + 1;
+            let person_id = *tables.person_ids.entry(resource.patient_id().to_string()).or_insert(next);
+            match resource {
+                AnyResource::Person(person) => tables.person.push(self.person(person_id, person)),
+                AnyResource::LabReport(report) => {
+                    for result in &report.results {
+                        let row = self.measurement(tables.measurement.len() as i64 + 1, person_id, report, result);
+                        tables.measurement.push(row);
+                    }
+                }
+                AnyResource::MedicationRecord(medication) => {
+                    let row = self.drug_exposure(tables.drug_exposure.len() as i64 + 1, person_id, medication);
+                    tables.drug_exposure.push(row);
+                }
+                AnyResource::Condition(condition) => {
+                    let id = tables.condition_occurrence.len() as i64 + 1;
+                    tables.condition_occurrence.extend(self.condition_occurrence(id, person_id, condition));
+                }
+                _ => {}
+            }
+        }
+        tables
+    }
+
+    fn concept(&self, coding: &Coding) -> i32 {
+        (self.concepts)(coding).unwrap_or(NO_MATCHING_CONCEPT)
+    }
+
+    /// Concept of the first coding the lookup knows, and the source value of the first coding.
+    fn codeable_concept(&self, concept: &CodeableConcept) -> (i32, String) {
+        let concept_id = concept.coding.iter().find_map(|coding| (self.concepts)(coding));
+        let source_value = concept.coding.first().map(|coding| coding.code.clone());
+        (concept_id.unwrap_or(NO_MATCHING_CONCEPT), source_value.or_else(|| concept.text.clone()).unwrap_or_default())
+    }
+
+    fn unit_concept(&self, quantity: &Quantity) -> i32 {
+        self.concept(&Coding { system: catalog::UCUM.into(), code: quantity.ucum_code().into(), display: None })
+    }
+
+    fn person(&self, person_id: i64, person: &Person) -> PersonRow {
+        let birth = person.birth_date.date();
+        let precision = person.birth_date.precision();
+        PersonRow {
+            person_id,
+            gender_concept_id: match person.gender {
+                Some(Gender::Male) => MALE,
+                Some(Gender::Female) => FEMALE,
+                _ => NO_MATCHING_CONCEPT,
+            },
+            year_of_birth: birth.year(),
+            month_of_birth: (precision >= DatePrecision::Month).then(|| birth.month()),
+            day_of_birth: (precision >= DatePrecision::Day).then(|| birth.day()),
+            race_concept_id: NO_MATCHING_CONCEPT,
+            ethnicity_concept_id: NO_MATCHING_CONCEPT,
+            person_source_value: person.id.clone(),
+            gender_source_value: person.gender.and_then(gender_code),
+        }
+    }
+
+    fn measurement(&self, id: i64, person_id: i64, report: &LabReport, result: &LabResult) -> MeasurementRow {
+        let (measurement_concept_id, measurement_source_value) = self.codeable_concept(&result.code);
+        let issued_at = report.issued_at.local();
+        let effective = result.effective.as_ref().and_then(|effective| effective.first_date());
+        let quantity = match &result.value {
+            LabValue::Quantity(quantity) | LabValue::Comparator { value: quantity, .. } => Some(quantity),
+            _ => None,
+        };
+        let comparator = match &result.value {
+            LabValue::Comparator { comparator, .. } => Some(*comparator),
+            _ => quantity.and_then(|quantity| quantity.comparator),
+        };
+        let (value_as_concept_id, value_source_value) = match &result.value {
+            LabValue::Concept(concept) => {
+                let (concept_id, source_value) = self.codeable_concept(concept);
+                (Some(concept_id), Some(source_value))
+            }
+            LabValue::String(text) => (None, Some(text.clone())),
+            _ => (None, None),
+        };
+        let range = result.reference_range.as_ref().and_then(|ranges| ranges.first());
+        MeasurementRow {
+            measurement_id: id,
+            person_id,
+            measurement_concept_id,
+            measurement_date: effective.unwrap_or(issued_at.date()),
+            measurement_datetime: effective.is_none().then_some(issued_at),
+            measurement_type_concept_id: EHR,
+            operator_concept_id: comparator.map(operator_concept),
+            value_as_number: result.value.numeric_value(),
+            value_as_concept_id,
+            unit_concept_id: quantity.map(|quantity| self.unit_concept(quantity)),
+            range_low: range.and_then(|range| Some(range.low.as_ref()?.value)),
+            range_high: range.and_then(|range| Some(range.high.as_ref()?.value)),
+            measurement_source_value,
+            unit_source_value: quantity.map(|quantity| quantity.unit.clone()),
+            value_source_value,
+        }
+    }
+
+    fn drug_exposure(&self, drug_exposure_id: i64, person_id: i64, medication: &MedicationRecord) -> DrugExposureRow {
+        let days_supply = medication.treatment_days();
+        let route = Coding {
+            system: medication.route.system.clone(),
+            code: medication.route.code.clone(),
+            display: None,
+        };
+        DrugExposureRow {
+            drug_exposure_id,
+            person_id,
+            drug_concept_id: self.concept(&medication.medication),
+            drug_exposure_start_date: medication.start_date,
+            drug_exposure_end_date: medication.last_date().unwrap_or(medication.start_date),
+            drug_type_concept_id: EHR,
+            days_supply: days_supply.and_then(|days| i32::try_from(days).ok()),
+            sig: medication.instructions.clone().or_else(|| medication.frequency.clone()),
+            route_concept_id: self.concept(&route),
+            drug_source_value: medication.medication.code.clone(),
+            route_source_value: Some(medication.route.code.clone()),
+            dose_unit_source_value: Some(medication.dosage.unit.clone()),
+        }
+    }
+
+    fn condition_occurrence(
+        &self,
+        condition_occurrence_id: i64,
+        person_id: i64,
+        condition: &Condition,
+    ) -> Option<ConditionOccurrenceRow> {
+        let verification = condition.verification_status;
+        if matches!(verification, Some(VerificationStatus::Refuted | VerificationStatus::EnteredInError)) {
+            return None;
+        }
+        let start = condition.onset.or(condition.recorded_date)?;
+        let (condition_concept_id, condition_source_value) = self.codeable_concept(&condition.code);
+        Some(ConditionOccurrenceRow {
+            condition_occurrence_id,
+            person_id,
+            condition_concept_id,
+            condition_start_date: start.local().date(),
+            condition_end_date: condition.abatement.map(|abatement| abatement.local().date()),
+            condition_type_concept_id: EHR,
+            condition_source_value,
+        })
+    }
+}
+
+/// Code of `gender` as written in JSON (e.g., "female").
+fn gender_code(gender: Gender) -> Option<String> {
+    Some(serde_json::to_value(gender).ok()?.as_str()?.into())
+}
+
+/// Operator concept of a comparator (OMOP "Meas Value Operator" domain).
+pub fn operator_concept(comparator: Comparator) -> i32 {
+    match comparator {
+        Comparator::LessThan => 4171756,
+        Comparator::LessOrEqual => 4171754,
+        Comparator::GreaterOrEqual => 4171755,
+        Comparator::GreaterThan => 4172704,
+    }
+}
+
+/// Exported rows, one vector per CDM table.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct OmopTables {
+    pub person: Vec<PersonRow>,
+    pub measurement: Vec<MeasurementRow>,
+    pub drug_exposure: Vec<DrugExposureRow>,
+    pub condition_occurrence: Vec<ConditionOccurrenceRow>,
+    /// `person_id` of every patient id, including patients without a
+    /// `Person` resource (and so without a PERSON row)
+    pub person_ids: BTreeMap<String, i64>,
+}
+
+/// Row of the PERSON table.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PersonRow {
+    pub person_id: i64,
+    pub gender_concept_id: i32,
+    pub year_of_birth: i32,
+    /// `None` when the birth date is only a year
+    pub month_of_birth: Option<u32>,
+    /// `None` when the birth date is only a year or a month
+    pub day_of_birth: Option<u32>,
+    pub race_concept_id: i32,
+    pub ethnicity_concept_id: i32,
+    /// Id of the `Person` resource
+    pub person_source_value: String,
+    pub gender_source_value: Option<String>,
+}
+
+/// Row of the MEASUREMENT table.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MeasurementRow {
+    pub measurement_id: i64,
+    pub person_id: i64,
+    pub measurement_concept_id: i32,
+    /// Day the result applies to, or the report was issued
+    pub measurement_date: NaiveDate,
+    /// Issue time in the facility's local time, when the result has no effective time
+    pub measurement_datetime: Option<NaiveDateTime>,
+    pub measurement_type_concept_id: i32,
+    /// Concept of the comparator of a value beyond a limit (see [`operator_concept`])
+    pub operator_concept_id: Option<i32>,
+    pub value_as_number: Option<f64>,
+    /// Concept of a coded value
+    pub value_as_concept_id: Option<i32>,
+    pub unit_concept_id: Option<i32>,
+    /// Low bound of the first reference range
+    pub range_low: Option<f64>,
+    /// High bound of the first reference range
+    pub range_high: Option<f64>,
+    pub measurement_source_value: String,
+    pub unit_source_value: Option<String>,
+    /// Code of a coded value or text of a text value
+    pub value_source_value: Option<String>,
+}
+
+/// Row of the DRUG_EXPOSURE table.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DrugExposureRow {
+    pub drug_exposure_id: i64,
+    pub person_id: i64,
+    pub drug_concept_id: i32,
+    pub drug_exposure_start_date: NaiveDate,
+    /// Last day of treatment; the start date when the duration is unknown
+    pub drug_exposure_end_date: NaiveDate,
+    pub drug_type_concept_id: i32,
+    pub days_supply: Option<i32>,
+    /// Instructions, or the frequency without them
+    pub sig: Option<String>,
+    pub route_concept_id: i32,
+    pub drug_source_value: String,
+    pub route_source_value: Option<String>,
+    pub dose_unit_source_value: Option<String>,
+}
+
+/// Row of the CONDITION_OCCURRENCE table.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConditionOccurrenceRow {
+    pub condition_occurrence_id: i64,
+    pub person_id: i64,
+    pub condition_concept_id: i32,
+    /// Onset, or the recorded date without one
+    pub condition_start_date: NaiveDate,
+    pub condition_end_date: Option<NaiveDate>,
+    pub condition_type_concept_id: i32,
+    pub condition_source_value: String,
+}
@@ -0,0 +1,507 @@
+//! Oncology structures aligned with mCODE.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//!
+//! mCODE (minimal Common Oncology Data Elements) describes a cancer patient
+//! by the cancer condition and its stage, tumor marker tests, the treatments
+//! given and how the disease responded. The pieces here build on the
+//! existing resources instead of adding new ones:
+//!
+//! - [`CancerCondition`] reads and writes the histology and TNM staging of a
+//!   [`Condition`] kept in its extensions;
+//! - [`TumorMarker`] names the common marker tests and reads their series
+//!   from lab reports;
+//! - [`ChemotherapyRegimen`] groups the [`MedicationRecord`]s of a regimen
+//!   given in cycles, and [`RadiotherapyCourse`] records a course of
+//!   radiation with its dose and fractions;
+//! - [`TumorAssessment`] holds the target lesion measurements read from an
+//!   [`ImagingReport`], and [`recist_response`] classifies the response by
+//!   RECIST 1.1.
+//!
+//! ```
+//! use wellally::catalog::concept;
+//! use wellally::oncology::{CancerCondition, TnmStaging};
+//! use wellally::Condition;
+//!
+//! let cancer = concept("http://hl7.org/fhir/sid/icd-10", "C18.7", "Malignant neoplasm of sigmoid colon");
+//! let mut staged = CancerCondition::new(Condition::new("c-1", "p-1", cancer));
+//! staged.staging = TnmStaging::parse("pT3 N1a M0");
+//! staged.stage_group = Some("IIIB".into());
+//!
+//! let condition = staged.into_condition();
+//! let read = CancerCondition::from_condition(condition);
+//! assert_eq!(read.staging.unwrap().to_string(), "pT3N1aM0");
+//! assert_eq!(read.stage_group.as_deref(), Some("IIIB"));
+//! ```
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::common::{BodySite, CodeableConcept, Coding, Quantity, Reference};
+use crate::condition::Condition;
+use crate::extension::{Extensible, ExtensionValue};
+use crate::imaging_report::ImagingReport;
+use crate::lab_report::{time_series, LabReport, LabResult, SeriesPoint};
+use crate::medication::MedicationRecord;
+use crate::ucum;
+
+/// mCODE extension with the histology, morphology and behavior of a cancer (ICD-O-3 or SNOMED CT)
+pub const HISTOLOGY: &str = "http://hl7.org/fhir/us/mcode/StructureDefinition/mcode-histology-morphology-behavior";
+/// Extension with the TNM staging of a cancer condition, written as in "cT2N1M0"
+pub const TNM_STAGING: &str = "https://www.wellally.tech/fhir/StructureDefinition/tnm-staging";
+/// Extension with the stage group of a cancer condition (e.g., "IIIB")
+pub const STAGE_GROUP: &str = "https://www.wellally.tech/fhir/StructureDefinition/tnm-stage-group";
+
+/// TNM classification of a tumor, as in "cT2 N1 M0" or "ypT0N0M0".
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TnmStaging {
+    /// Lowercase prefixes: c (clinical), p (pathologic), y (after therapy),
+    /// r (recurrence), a (autopsy)
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub prefix: String,
+    /// Primary tumor category (e.g., "T2", "Tis")
+    #[serde(rename = "primaryTumor", alias = "primary_tumor", skip_serializing_if = "Option::is_none")]
+    pub primary_tumor: Option<String>,
+    /// Regional nodes category (e.g., "N1a")
+    #[serde(rename = "regionalNodes", alias = "regional_nodes", skip_serializing_if = "Option::is_none")]
+    pub regional_nodes: Option<String>,
+    /// Distant metastases category (e.g., "M0")
+    #[serde(rename = "distantMetastases", alias = "distant_metastases", skip_serializing_if = "Option::is_none")]
+    pub distant_metastases: Option<String>,
+}
+
+impl TnmStaging {
+    /// Reads TNM notation, with or without spaces between the categories;
+    /// `None` when it has none of T, N and M or anything else before them
+    /// than prefixes.
+    pub fn parse(text: &str) -> Option<TnmStaging> {
+        let text: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+        let start = text.find(['T', 'N', 'M'])?;
+        let prefix = &text[..start];
+        if !prefix.chars().all(|c| matches!(c, 'c' | 'p' | 'y' | 'r' | 'a')) {
+            return None;
+        }
+        let mut staging = TnmStaging { prefix: prefix.into(), ..TnmStaging::default() };
+        let mut rest = &text[start..];
+        while !rest.is_empty() {
+            let end = rest[1..].find(['T', 'N', 'M']).map_or(rest.len(), |end| end + 1);
+            let category = Some(rest[..end].to_string());
+            let slot = match rest.as_bytes()[0] {
+                b'T' => &mut staging.primary_tumor,
+                b'N' => &mut staging.regional_nodes,
+                _ => &mut staging.distant_metastases,
+            };
+            if slot.is_some() || end == 1 {
+                return None;
+            }
+            *slot = category;
+            rest = &rest[end..];
+        }
+        Some(staging)
+    }
+
+    /// Whether the staging was assigned from pathology.
+    pub fn is_pathologic(&self) -> bool {
+        self.prefix.contains('p')
+    }
+
+    /// Whether distant metastases are present (M1 or a subcategory).
+    pub fn is_metastatic(&self) -> bool {
+        self.distant_metastases.as_deref().is_some_and(|category| category.starts_with("M1"))
+    }
+}
+
+impl fmt::Display for TnmStaging {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.prefix)?;
+        for category in [&self.primary_tumor, &self.regional_nodes, &self.distant_metastases].into_iter().flatten() {
+            f.write_str(category)?;
+        }
+        Ok(())
+    }
+}
+
+/// A cancer diagnosis with its histology and stage.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CancerCondition {
+    /// The diagnosis, without the histology and staging extensions
+    pub condition: Condition,
+    /// Histology, morphology and behavior (e.g., ICD-O-3 8140/3 adenocarcinoma)
+    pub histology: Option<CodeableConcept>,
+    /// Latest TNM staging
+    pub staging: Option<TnmStaging>,
+    /// Stage group of the staging (e.g., "IIIB")
+    pub stage_group: Option<String>,
+}
+
+impl CancerCondition {
+    /// Cancer condition without histology or staging.
+    pub fn new(condition: Condition) -> Self {
+        CancerCondition { condition, histology: None, staging: None, stage_group: None }
+    }
+
+    /// Reads the histology and staging extensions of `condition`. Staging
+    /// written in a form [`TnmStaging::parse`] does not read is left in the
+    /// condition's extensions.
+    pub fn from_condition(mut condition: Condition) -> Self {
+        let histology = condition.extension(HISTOLOGY).and_then(ExtensionValue::as_concept).cloned();
+        let staging = condition.extension(TNM_STAGING).and_then(ExtensionValue::as_str).and_then(TnmStaging::parse);
+        let stage_group = condition.extension(STAGE_GROUP).and_then(ExtensionValue::as_str).map(Into::into);
+        if histology.is_some() {
+            condition.remove_extension(HISTOLOGY);
+        }
+        if staging.is_some() {
+            condition.remove_extension(TNM_STAGING);
+        }
+        if stage_group.is_some() {
+            condition.remove_extension(STAGE_GROUP);
+        }
+        CancerCondition { condition, histology, staging, stage_group }
+    }
+
+    /// The condition with the histology and staging written to its extensions.
+    pub fn into_condition(self) -> Condition {
+        let mut condition = self.condition;
+        if let Some(histology) = self.histology {
+            condition.set_extension(HISTOLOGY, histology);
+        }
+        if let Some(staging) = self.staging {
+            condition.set_extension(TNM_STAGING, staging.to_string());
+        }
+        if let Some(stage_group) = self.stage_group {
+            condition.set_extension(STAGE_GROUP, ExtensionValue::Code(stage_group));
+        }
+        condition
+    }
+
+    /// Reference to the condition, for treatments and assessments.
+    pub fn reference(&self) -> Reference {
+        Reference::to(&self.condition)
+    }
+}
+
+/// Whether `condition` is coded as a malignant or in situ neoplasm
+/// (ICD-10 C00-C97 or D00-D09).
+pub fn is_cancer(condition: &Condition) -> bool {
+    condition.code.coding.iter().any(|coding| {
+        coding.system.starts_with("http://hl7.org/fhir/sid/icd-10")
+            && (coding.code.starts_with('C') || coding.code.starts_with("D0"))
+    })
+}
+
+/// Common serum tumor marker tests.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TumorMarker {
+    /// Carcinoembryonic antigen
+    Cea,
+    /// Prostate specific antigen
+    Psa,
+    /// Cancer antigen 125
+    Ca125,
+    /// Cancer antigen 15-3
+    Ca153,
+    /// Cancer antigen 19-9
+    Ca199,
+    /// Alpha-1-fetoprotein
+    Afp,
+    /// Beta subunit of human chorionic gonadotropin
+    BetaHcg,
+}
+
+impl TumorMarker {
+    /// Every marker.
+    pub const ALL: [TumorMarker; 7] = [
+        TumorMarker::Cea,
+        TumorMarker::Psa,
+        TumorMarker::Ca125,
+        TumorMarker::Ca153,
+        TumorMarker::Ca199,
+        TumorMarker::Afp,
+        TumorMarker::BetaHcg,
+    ];
+
+    /// LOINC code of the serum or plasma test.
+    pub fn loinc(self) -> &'static str {
+        match self {
+            TumorMarker::Cea => "2039-6",
+            TumorMarker::Psa => "2857-1",
+            TumorMarker::Ca125 => "10334-1",
+            TumorMarker::Ca153 => "6875-9",
+            TumorMarker::Ca199 => "24108-3",
+            TumorMarker::Afp => "1834-1",
+            TumorMarker::BetaHcg => "21198-7",
+        }
+    }
+
+    /// Marker tested by `result`, if any.
+    pub fn of(result: &LabResult) -> Option<TumorMarker> {
+        TumorMarker::ALL
+            .into_iter()
+            .find(|marker| result.code.coding.iter().any(|coding| coding.code == marker.loinc()))
+    }
+
+    /// Results of this marker across `reports`, oldest first.
+    pub fn series(self, reports: &[LabReport]) -> Vec<SeriesPoint<'_>> {
+        time_series(reports, self.loinc())
+    }
+
+    /// Change of the last value of the series from the first, in percent;
+    /// `None` with fewer than two numeric values or a first value of 0.
+    pub fn percent_change(series: &[SeriesPoint<'_>]) -> Option<f64> {
+        let mut values = series.iter().filter_map(|point| point.result.value.numeric_value());
+        let first = values.next()?;
+        let last = values.next_back()?;
+        (first != 0.0).then(|| (last - first) / first * 100.0)
+    }
+}
+
+/// Goal of a cancer treatment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TreatmentIntent {
+    Curative,
+    Palliative,
+    /// After the primary treatment, to lower the risk of recurrence
+    Adjuvant,
+    /// Before the primary treatment, to shrink the tumor
+    Neoadjuvant,
+}
+
+/// Drugs given together in repeated cycles (e.g., FOLFOX every 14 days).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChemotherapyRegimen {
+    /// Regimen name (e.g., "FOLFOX")
+    pub name: String,
+    /// Cancer condition treated
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub condition: Option<Reference>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub intent: Option<TreatmentIntent>,
+    /// Days from the start of one cycle to the start of the next
+    #[serde(rename = "cycleDays", alias = "cycle_days")]
+    pub cycle_days: u32,
+    /// Number of cycles planned
+    #[serde(rename = "plannedCycles", alias = "planned_cycles", skip_serializing_if = "Option::is_none")]
+    pub planned_cycles: Option<u32>,
+    /// The drugs given, one record per drug and cycle or per drug for the
+    /// whole regimen
+    pub medications: Vec<MedicationRecord>,
+}
+
+impl ChemotherapyRegimen {
+    /// Regimen of `medications` given every `cycle_days` days.
+    pub fn new(name: impl Into<String>, cycle_days: u32, medications: Vec<MedicationRecord>) -> Self {
+        ChemotherapyRegimen {
+            name: name.into(),
+            condition: None,
+            intent: None,
+            cycle_days,
+            planned_cycles: None,
+            medications,
+        }
+    }
+
+    /// First day of the first cycle: the earliest medication start.
+    pub fn start_date(&self) -> Option<NaiveDate> {
+        self.medications.iter().map(|medication| medication.start_date).min()
+    }
+
+    /// Cycle `date` falls in, from 1; `None` before the start, and after the
+    /// planned cycles when they are known.
+    pub fn cycle_on(&self, date: NaiveDate) -> Option<u32> {
+        let days = (date - self.start_date()?).num_days();
+        if days < 0 || self.cycle_days == 0 {
+            return None;
+        }
+        let cycle = u32::try_from(days / i64::from(self.cycle_days)).ok()? + 1;
+        self.planned_cycles.is_none_or(|planned| cycle <= planned).then_some(cycle)
+    }
+
+    /// Distinct drugs of the regimen, in order of first appearance.
+    pub fn agents(&self) -> Vec<&Coding> {
+        let mut agents: Vec<&Coding> = Vec::new();
+        for medication in &self.medications {
+            let drug = &medication.medication;
+            if !agents.iter().any(|agent| agent.system == drug.system && agent.code == drug.code) {
+                agents.push(drug);
+            }
+        }
+        agents
+    }
+}
+
+/// Kind of radiation therapy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RadiotherapyModality {
+    /// External beam with photons (e.g., IMRT, VMAT)
+    Photon,
+    /// External beam with electrons
+    Electron,
+    /// External beam with protons
+    Proton,
+    /// Sources placed in or next to the tumor
+    Brachytherapy,
+    /// Radioactive drugs (e.g., I-131)
+    Radiopharmaceutical,
+}
+
+/// A course of radiation therapy to one site.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RadiotherapyCourse {
+    pub modality: RadiotherapyModality,
+    /// Site treated (e.g., SNOMED CT body structure), with laterality
+    #[serde(rename = "bodySite", alias = "body_site")]
+    pub body_site: BodySite,
+    /// Cancer condition treated
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub condition: Option<Reference>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub intent: Option<TreatmentIntent>,
+    /// Total dose delivered or planned (e.g., 50 Gy)
+    #[serde(rename = "totalDose", alias = "total_dose")]
+    pub total_dose: Quantity,
+    /// Number of fractions the dose is split into
+    pub fractions: u32,
+    #[serde(rename = "startDate", alias = "start_date")]
+    pub start_date: NaiveDate,
+    #[serde(rename = "endDate", alias = "end_date", skip_serializing_if = "Option::is_none")]
+    pub end_date: Option<NaiveDate>,
+}
+
+impl RadiotherapyCourse {
+    /// Dose of one fraction, in the unit of the total dose.
+    pub fn dose_per_fraction(&self) -> Option<Quantity> {
+        let dose = &self.total_dose;
+        (self.fractions > 0).then(|| Quantity::new(dose.value / f64::from(self.fractions), dose.unit.clone()))
+    }
+
+    /// Biologically effective dose in Gy for tissue with `alpha_beta` Gy
+    /// (about 10 for most tumors, 3 for late-responding tissue):
+    /// n·d·(1 + d / (α/β)).
+    pub fn biologically_effective_dose(&self, alpha_beta: f64) -> Option<f64> {
+        let total = ucum::convert(self.total_dose.value, &self.total_dose.unit, "Gy").ok()?;
+        let per_fraction = total / f64::from(self.fractions);
+        (self.fractions > 0 && alpha_beta > 0.0).then(|| total * (1.0 + per_fraction / alpha_beta))
+    }
+
+    /// Equivalent dose in 2 Gy fractions (EQD2), in Gy.
+    pub fn eqd2(&self, alpha_beta: f64) -> Option<f64> {
+        Some(self.biologically_effective_dose(alpha_beta)? / (1.0 + 2.0 / alpha_beta))
+    }
+}
+
+/// Target lesions measured on one imaging study.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TumorAssessment {
+    /// Imaging report the measurements come from
+    #[serde(rename = "imagingReport", alias = "imaging_report")]
+    pub imaging_report: Reference,
+    pub date: NaiveDate,
+    /// Longest diameter of each target lesion (short axis for lymph
+    /// nodes), in mm; 0 for a lesion that disappeared
+    #[serde(rename = "targetLesionsMm", alias = "target_lesions_mm")]
+    pub target_lesions_mm: Vec<f64>,
+    /// Whether new lesions were found
+    #[serde(rename = "newLesions", alias = "new_lesions", default)]
+    pub new_lesions: bool,
+}
+
+impl TumorAssessment {
+    /// Assessment of the study of `report` on its report date.
+    pub fn from_imaging(report: &ImagingReport, target_lesions_mm: Vec<f64>, new_lesions: bool) -> Self {
+        TumorAssessment {
+            imaging_report: Reference::to(report),
+            date: report.reported_at.local().date(),
+            target_lesions_mm,
+            new_lesions,
+        }
+    }
+
+    /// Sum of the target lesion diameters, in mm.
+    pub fn sum_of_diameters(&self) -> f64 {
+        self.target_lesions_mm.iter().sum()
+    }
+}
+
+/// Response of target lesions by RECIST 1.1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RecistResponse {
+    /// Every target lesion disappeared
+    CompleteResponse,
+    /// Sum of diameters at least 30% below baseline
+    PartialResponse,
+    /// Neither response nor progression
+    StableDisease,
+    /// Sum at least 20% and 5 mm above the smallest so far, or new lesions
+    ProgressiveDisease,
+}
+
+/// Response at the latest assessment, tied to the imaging it was read from.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TreatmentResponse {
+    pub response: RecistResponse,
+    pub date: NaiveDate,
+    /// Imaging report of the latest assessment
+    #[serde(rename = "imagingReport", alias = "imaging_report")]
+    pub imaging_report: Reference,
+    /// Imaging report of the baseline assessment
+    pub baseline: Reference,
+    /// Change of the sum of diameters from baseline, in percent
+    #[serde(rename = "changeFromBaseline", alias = "change_from_baseline")]
+    pub change_from_baseline: Option<f64>,
+}
+
+/// RECIST 1.1 response of the last of `assessments` (in date order, the
+/// first being the baseline) from target lesions and new lesions; `None`
+/// with fewer than two assessments.
+pub fn recist_response(assessments: &[TumorAssessment]) -> Option<TreatmentResponse> {
+    let [baseline, .., current] = assessments else {
+        return None;
+    };
+    let (baseline_sum, sum) = (baseline.sum_of_diameters(), current.sum_of_diameters());
+    let nadir = assessments[..assessments.len() - 1]
+        .iter()
+        .map(TumorAssessment::sum_of_diameters)
+        .fold(f64::INFINITY, f64::min);
+    let response = if current.new_lesions || (sum >= nadir * 1.2 && sum - nadir >= 5.0) {
+        RecistResponse::ProgressiveDisease
+    } else if sum == 0.0 {
+        RecistResponse::CompleteResponse
+    } else if sum <= baseline_sum * 0.7 {
+        RecistResponse::PartialResponse
+    } else {
+        RecistResponse::StableDisease
+    };
+    Some(TreatmentResponse {
+        response,
+        date: current.date,
+        imaging_report: current.imaging_report.clone(),
+        baseline: baseline.imaging_report.clone(),
+        change_from_baseline: (baseline_sum > 0.0).then(|| (sum - baseline_sum) / baseline_sum * 100.0),
+    })
+}
+
+/// Describes a response for display (e.g., "PR (-42% from baseline)").
+pub fn describe(response: &TreatmentResponse) -> String {
+    let code = match response.response {
+        RecistResponse::CompleteResponse => "CR",
+        RecistResponse::PartialResponse => "PR",
+        RecistResponse::StableDisease => "SD",
+        RecistResponse::ProgressiveDisease => "PD",
+    };
+    match response.change_from_baseline {
+        Some(change) => format!("{} ({:+.0}% from baseline)", code, change),
+        None => code.into(),
+    }
+}
@@ -0,0 +1,229 @@
+//! OpenAPI components for WellAlly resources.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+
+use utoipa::OpenApi;
+
+use crate::common::*;
+use crate::extension::*;
+use crate::family_health::*;
+use crate::health::*;
+use crate::imaging_report::*;
+use crate::imaging_report::dexa::*;
+use crate::imaging_report::echo::*;
+use crate::imaging_report::measurements::*;
+use crate::lab_report::*;
+use crate::medication::*;
+use crate::ecg::*;
+use crate::consent::*;
+use crate::nephrology::*;
+use crate::rehab::*;
+use crate::wound::*;
+use crate::infusion::*;
+use crate::pathology::*;
+use crate::audiology::*;
+use crate::birth::*;
+use crate::clearance::*;
+use crate::travel::*;
+use crate::compendium::*;
+use crate::dispense::*;
+use crate::immunization::*;
+use crate::relationships::*;
+use crate::cgm::*;
+use crate::flag::*;
+use crate::allergy::*;
+use crate::condition::*;
+use crate::location::*;
+use crate::communication::*;
+use crate::task::*;
+use crate::referral::*;
+use crate::questionnaire::*;
+
+/// OpenAPI document holding a component schema for every model.
+///
+/// Merge it into a service's own document to reference the models from paths:
+///
+/// ```ignore
+/// let mut api = MyApi::openapi();
+/// api.merge(wellally::openapi::ComponentsDoc::openapi());
+/// ```
+#[derive(OpenApi)]
+#[openapi(components(schemas(
+    Coding,
+    CodeableConcept,
+    Quantity,
+    ReferenceRange,
+    ReferenceRangeType,
+    Range,
+    Identifier,
+    HumanName,
+    NameUse,
+    ContactPoint,
+    ContactSystem,
+    ContactUse,
+    Address,
+    Period,
+    Effective,
+    Annotation,
+    Extension,
+    ExtensionValue,
+    DataSource,
+    CollectionMethod,
+    Reliability,
+    QualityFlag,
+    Modality,
+    ModalityCode,
+    Route,
+    BodySite,
+    Laterality,
+    Person,
+    Gender,
+    ClinicalSummary,
+    VitalsSnapshot,
+    LabReport,
+    LabResult,
+    LabValue,
+    Comparator,
+    Interpretation,
+    Facility,
+    Specimen,
+    ImagingReport,
+    CodedChange,
+    Performer,
+    RadiationDose,
+    Attachment,
+    ImagingMeasurements,
+    UltrasoundMeasurements,
+    BiometryMeasurement,
+    FetalBiometry,
+    OrganDimensions,
+    EchoReport,
+    ChamberDimensions,
+    EjectionFractionCategory,
+    ValveAssessment,
+    Valve,
+    ValveSeverity,
+    SegmentWallMotion,
+    WallMotion,
+    DexaReport,
+    SiteDensity,
+    DexaSite,
+    WhoClassification,
+    FraxInputs,
+    MedicationRecord,
+    Dosage,
+    DeaSchedule,
+    FamilyHealthTree,
+    FamilyMember,
+    RelationToProband,
+    Sex,
+    ConditionOnset,
+    Questionnaire,
+    QuestionnaireStatus,
+    QuestionnaireItem,
+    ItemType,
+    AnswerOption,
+    AnswerValue,
+    EnableWhen,
+    EnableWhenOperator,
+    EnableBehavior,
+    QuestionnaireResponse,
+    ResponseStatus,
+    ResponseItem,
+    Referral,
+    ReferralStatus,
+    ReferralParty,
+    Reference,
+    RequestPriority,
+    Task,
+    TaskStatus,
+    CommunicationRecord,
+    CommunicationStatus,
+    MessagePayload,
+    Location,
+    LocationStatus,
+    Position,
+    OperatingHours,
+    DayOfWeek,
+    Condition,
+    ClinicalStatus,
+    VerificationStatus,
+    ConditionCategory,
+    AllergyIntolerance,
+    AllergyReaction,
+    AllergyClinicalStatus,
+    AllergyType,
+    AllergyCategory,
+    Criticality,
+    ReactionSeverity,
+    Flag,
+    FlagStatus,
+    FlagCategory,
+    FlagSeverity,
+    GlucoseSeries,
+    EcgRecord,
+    EcgLead,
+    EcgLeadName,
+    EcgIntervals,
+    ConsentRecord,
+    ConsentStatus,
+    ConsentDecision,
+    ConsentProvision,
+    Purpose,
+    DialysisSession,
+    DialysisModality,
+    DialysisAccess,
+    AccessType,
+    RehabSession,
+    Exercise,
+    FunctionalScore,
+    WoundAssessment,
+    WoundDimensions,
+    PressureInjuryStage,
+    Exudate,
+    ExudateAmount,
+    ExudateType,
+    TissueType,
+    TissueProportion,
+    DressingPlan,
+    InfusionRecord,
+    InfusionLine,
+    LineType,
+    Titration,
+    Interruption,
+    PathologyReport,
+    PathologySpecimen,
+    SynopticElement,
+    AudiogramReport,
+    AudiometryPurpose,
+    EarResult,
+    Ear,
+    Threshold,
+    Conduction,
+    SpeechRecognition,
+    TympanogramType,
+    BirthRecord,
+    ApgarScore,
+    ApgarComponents,
+    DeliveryMethod,
+    GestationalAge,
+    ClearanceDocument,
+    ClearanceType,
+    ClearanceOutcome,
+    Restriction,
+    RequiredEvidence,
+    TravelHealthRecord,
+    Destination,
+    TravelVaccine,
+    VaccineRequirement,
+    TravelAdvisory,
+    Compendium,
+    TestDefinition,
+    SpecimenRequirement,
+    DispenseRecord,
+    ImmunizationRecord,
+    RelatedPerson,
+    RelationshipRole,
+)))]
+pub struct ComponentsDoc;
@@ -0,0 +1,244 @@
+//! Anatomic pathology report model.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//! Schema: https://wellall.health/schemas/pathology/v0.1.0
+//!
+//! Anatomic pathology fits neither [`crate::LabReport`], whose results are
+//! single analytes, nor [`crate::ImagingReport`]. A [`PathologyReport`] keeps
+//! the specimens with their gross and microscopic descriptions, the synoptic
+//! elements of a CAP-style checklist as code/value pairs, the coded
+//! diagnoses, and the histologic grade and pathologic TNM staging.
+//! [`PathologyReport::apply_to`] carries histology and staging over to a
+//! [`CancerCondition`].
+//!
+//! ```
+//! use wellally::catalog::concept;
+//! use wellally::pathology::{PathologyReport, PathologySpecimen, ICD_O_3};
+//!
+//! let specimens = vec![PathologySpecimen::new("A")];
+//! let mut report = PathologyReport::new("path-1", "p-1", "2024-10-04".parse().unwrap(), specimens);
+//! report.diagnoses.push(concept(ICD_O_3, "8140/3", "Adenocarcinoma, NOS"));
+//! report.tnm = Some("pT3 N1a M0".into());
+//! assert!(report.is_malignant());
+//! assert_eq!(report.tnm_staging().unwrap().to_string(), "pT3N1aM0");
+//! ```
+
+use alloc::{string::String, vec::Vec};
+
+use serde::{Deserialize, Serialize};
+
+use crate::common::{Annotation, CodeableConcept, Coding, Reference};
+use crate::datetime::FlexibleDateTime;
+use crate::extension::Extension;
+use crate::lab_report::LabValue;
+use crate::oncology::{CancerCondition, TnmStaging};
+
+/// ICD-O-3 morphology code system (e.g., "8140/3", the digit after the slash being the behavior)
+pub const ICD_O_3: &str = "http://terminology.hl7.org/CodeSystem/icd-o-3";
+
+/// Specimen examined, with what the pathologist saw.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "PathologySpecimenInput")
+)]
+pub struct PathologySpecimen {
+    /// Label of the specimen within the report (e.g., "A", "B")
+    pub id: String,
+    /// Specimen type (e.g., SNOMED CT excision specimen, core needle biopsy)
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "async-graphql", graphql(name = "type"))]
+    pub specimen_type: Option<Coding>,
+    /// Where the specimen was taken from (e.g., SNOMED CT sigmoid colon)
+    #[serde(rename = "bodySite", alias = "body_site", skip_serializing_if = "Option::is_none")]
+    pub body_site: Option<Coding>,
+    /// Procedure that obtained it (e.g., sigmoid colectomy)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub procedure: Option<CodeableConcept>,
+    #[serde(rename = "collectedAt", alias = "collected_at", skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "utoipa", schema(value_type = Option<String>))]
+    pub collected_at: Option<FlexibleDateTime>,
+    /// Macroscopic description: size, weight, appearance and sections taken
+    #[serde(rename = "grossDescription", alias = "gross_description", skip_serializing_if = "Option::is_none")]
+    pub gross_description: Option<String>,
+    /// Microscopic findings
+    #[serde(rename = "microscopicFindings", alias = "microscopic_findings", skip_serializing_if = "Option::is_none")]
+    pub microscopic_findings: Option<String>,
+}
+
+impl PathologySpecimen {
+    /// Specimen labelled `id`, not yet described.
+    pub fn new(id: impl Into<String>) -> Self {
+        PathologySpecimen {
+            id: id.into(),
+            specimen_type: None,
+            body_site: None,
+            procedure: None,
+            collected_at: None,
+            gross_description: None,
+            microscopic_findings: None,
+        }
+    }
+}
+
+/// One element of a synoptic (checklist) report, such as margin status or
+/// lymphovascular invasion.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "SynopticElementInput")
+)]
+pub struct SynopticElement {
+    /// Checklist question (e.g., LOINC or CAP eCC code)
+    pub code: Coding,
+    /// Answer: a quantity, coded answer or text
+    pub value: LabValue,
+    /// Id of the specimen the element is about, when the report has several
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub specimen: Option<String>,
+}
+
+/// Anatomic pathology report.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "PathologyReportInput")
+)]
+pub struct PathologyReport {
+    /// Unique report identifier
+    pub id: String,
+    /// Reference to Person.id
+    #[serde(rename = "patientId", alias = "patient_id")]
+    pub patient_id: String,
+    /// When the report was signed out
+    #[serde(rename = "issuedAt", alias = "issued_at")]
+    #[cfg_attr(feature = "utoipa", schema(value_type = String))]
+    pub issued_at: FlexibleDateTime,
+    /// Specimens examined
+    pub specimens: Vec<PathologySpecimen>,
+    /// Synoptic checklist elements
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub synoptic: Vec<SynopticElement>,
+    /// Diagnoses (e.g., ICD-O-3 morphology, SNOMED CT)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub diagnoses: Vec<CodeableConcept>,
+    /// Histologic grade (e.g., G2 moderately differentiated)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub grade: Option<CodeableConcept>,
+    /// Pathologic TNM classification, as in "pT3 N1a M0"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tnm: Option<String>,
+    /// Stage group (e.g., "IIIB")
+    #[serde(rename = "stageGroup", alias = "stage_group", skip_serializing_if = "Option::is_none")]
+    pub stage_group: Option<String>,
+    /// Final diagnosis as written
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conclusion: Option<String>,
+    /// Condition the report diagnoses (e.g., "Condition/cond-1")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub condition: Option<Reference>,
+    /// Pathologist who signed the report (e.g., "Practitioner/path-zhao")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pathologist: Option<Reference>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notes: Option<Vec<Annotation>>,
+    /// Schema version the resource was written against (e.g., "0.1.0"); absent means 0.1.0
+    #[serde(rename = "schemaVersion", alias = "schema_version", skip_serializing_if = "Option::is_none")]
+    pub schema_version: Option<String>,
+    /// Confidentiality and sensitivity labels (e.g., v3 ActCode HIV); see [`crate::security`]
+    #[serde(rename = "securityLabels", alias = "security_labels", skip_serializing_if = "Option::is_none")]
+    pub security_labels: Option<Vec<Coding>>,
+    /// Site-specific extensions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extensions: Option<Vec<Extension>>,
+    /// Properties this model version does not define, kept for re-serialization
+    #[cfg(feature = "preserve-unknown")]
+    #[serde(flatten)]
+    #[cfg_attr(feature = "schemars", schemars(skip))]
+    #[cfg_attr(feature = "utoipa", schema(ignore))]
+    #[cfg_attr(feature = "async-graphql", graphql(skip))]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl PathologyReport {
+    /// Report on `specimens` without findings yet.
+    pub fn new(
+        id: impl Into<String>,
+        patient_id: impl Into<String>,
+        issued_at: FlexibleDateTime,
+        specimens: Vec<PathologySpecimen>,
+    ) -> Self {
+        PathologyReport {
+            id: id.into(),
+            patient_id: patient_id.into(),
+            issued_at,
+            specimens,
+            synoptic: Vec::new(),
+            diagnoses: Vec::new(),
+            grade: None,
+            tnm: None,
+            stage_group: None,
+            conclusion: None,
+            condition: None,
+            pathologist: None,
+            notes: None,
+            schema_version: None,
+            security_labels: None,
+            extensions: None,
+            #[cfg(feature = "preserve-unknown")]
+            extra: Default::default(),
+        }
+    }
+
+    /// Specimen labelled `id`.
+    pub fn specimen(&self, id: &str) -> Option<&PathologySpecimen> {
+        self.specimens.iter().find(|specimen| specimen.id == id)
+    }
+
+    /// Answer of the synoptic element coded `code`.
+    pub fn synoptic_value(&self, code: &str) -> Option<&LabValue> {
+        self.synoptic.iter().find(|element| element.code.code == code).map(|element| &element.value)
+    }
+
+    /// The `tnm` text parsed; `None` when absent or not a TNM classification.
+    pub fn tnm_staging(&self) -> Option<TnmStaging> {
+        TnmStaging::parse(self.tnm.as_deref()?)
+    }
+
+    /// First ICD-O-3 morphology among the diagnoses.
+    pub fn histology(&self) -> Option<&CodeableConcept> {
+        self.diagnoses.iter().find(|diagnosis| diagnosis.coding.iter().any(|coding| coding.system == ICD_O_3))
+    }
+
+    /// Whether a diagnosis has ICD-O-3 behavior 3 (malignant, primary site)
+    /// or 6 (malignant, metastatic site).
+    pub fn is_malignant(&self) -> bool {
+        self.diagnoses.iter().flat_map(|diagnosis| &diagnosis.coding).any(|coding| {
+            coding.system == ICD_O_3 && (coding.code.ends_with("/3") || coding.code.ends_with("/6"))
+        })
+    }
+
+    /// Records the histology, staging and stage group of the report on
+    /// `cancer`, keeping what it already has where the report is silent.
+    pub fn apply_to(&self, cancer: &mut CancerCondition) {
+        if let Some(histology) = self.histology() {
+            cancer.histology = Some(histology.clone());
+        }
+        if let Some(staging) = self.tnm_staging() {
+            cancer.staging = Some(staging);
+        }
+        if let Some(stage_group) = &self.stage_group {
+            cancer.stage_group = Some(stage_group.clone());
+        }
+    }
+}
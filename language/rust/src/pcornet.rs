@@ -0,0 +1,505 @@
+//! Export to PCORnet Common Data Model flat tables.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//!
+//! [`PcornetTables::from_bundles`] turns bundles into rows of the PCORnet CDM
+//! tables a research network asks for first: DEMOGRAPHIC (one row per
+//! `Person`), LAB_RESULT_CM (one row per lab result) and PRESCRIBING (one row
+//! per `MedicationRecord`). Rows serialize with the CDM column names and
+//! value sets (`NI` for no information, `OT` for other); [`PcornetTables::write_dir`]
+//! writes them as `DEMOGRAPHIC.csv`, `LAB_RESULT_CM.csv` and `PRESCRIBING.csv`
+//! with a header row, dates as `YYYY-MM-DD` and times as `HH:MM`.
+//!
+//! Patient and record ids are written as they are; pseudonymize the bundles
+//! first (see [`crate::anonymize`]) when the network expects a study `PATID`.
+//!
+//! ```
+//! use wellally::io::{AnyResource, Bundle};
+//! use wellally::pcornet::PcornetTables;
+//!
+//! let person: AnyResource = serde_json::from_value(serde_json::json!({
+//!     "id": "p-1", "resourceType": "Person", "birthDate": "1980-04-12", "gender": "female",
+//!     "name": [{"family": "Doe", "given": ["Jane"]}]
+//! }))
+//! .unwrap();
+//! let tables = PcornetTables::from_bundles(&[Bundle::new(vec![person])]);
+//! let mut csv = Vec::new();
+//! tables.write_demographic(&mut csv)?;
+//! assert!(String::from_utf8(csv).unwrap().lines().nth(1).unwrap().starts_with("p-1,1980-04-12,,F,"));
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::Path;
+
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Serialize, Serializer};
+
+use crate::catalog;
+use crate::common::{CodeableConcept, Comparator};
+use crate::health::{Gender, Person};
+use crate::io::{AnyResource, Bundle};
+use crate::lab_report::{Interpretation, LabReport, LabResult, LabValue};
+use crate::medication::MedicationRecord;
+
+/// Value-set code for "no information"
+pub const NO_INFORMATION: &str = "NI";
+/// Value-set code for "other"
+pub const OTHER: &str = "OT";
+
+/// Rows of the exported tables.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct PcornetTables {
+    pub demographic: Vec<Demographic>,
+    pub lab_result_cm: Vec<LabResultCm>,
+    pub prescribing: Vec<Prescribing>,
+}
+
+impl PcornetTables {
+    /// Rows for the resources of `bundles`; resources of other types are
+    /// left out.
+    pub fn from_bundles(bundles: &[Bundle]) -> Self {
+        let mut tables = PcornetTables::default();
+        for resource in bundles.iter().flat_map(|bundle| &bundle.resources) {
+            match resource {
+                AnyResource::Person(person) => tables.demographic.push(Demographic::from_person(person)),
+                AnyResource::LabReport(report) => {
+                    let rows = report.results.iter().enumerate();
+                    tables.lab_result_cm.extend(rows.map(|(i, result)| LabResultCm::from_result(report, i, result)));
+                }
+                AnyResource::MedicationRecord(medication) => {
+                    tables.prescribing.push(Prescribing::from_medication(medication));
+                }
+                _ => {}
+            }
+        }
+        tables
+    }
+
+    /// Writes DEMOGRAPHIC as CSV.
+    pub fn write_demographic(&self, writer: impl Write) -> io::Result<()> {
+        write_csv(&self.demographic, writer)
+    }
+
+    /// Writes LAB_RESULT_CM as CSV.
+    pub fn write_lab_result_cm(&self, writer: impl Write) -> io::Result<()> {
+        write_csv(&self.lab_result_cm, writer)
+    }
+
+    /// Writes PRESCRIBING as CSV.
+    pub fn write_prescribing(&self, writer: impl Write) -> io::Result<()> {
+        write_csv(&self.prescribing, writer)
+    }
+
+    /// Writes every table to `<TABLE>.csv` in `dir`, replacing existing files.
+    pub fn write_dir(&self, dir: impl AsRef<Path>) -> io::Result<()> {
+        let dir = dir.as_ref();
+        self.write_demographic(File::create(dir.join(format!("{}.csv", Demographic::TABLE)))?)?;
+        self.write_lab_result_cm(File::create(dir.join(format!("{}.csv", LabResultCm::TABLE)))?)?;
+        self.write_prescribing(File::create(dir.join(format!("{}.csv", Prescribing::TABLE)))?)
+    }
+}
+
+/// A row type of a PCORnet table.
+pub trait PcornetTable: Serialize {
+    /// Table name (e.g., "DEMOGRAPHIC")
+    const TABLE: &'static str;
+    /// Column names in order, as written in the header row
+    const COLUMNS: &'static [&'static str];
+}
+
+/// Writes `rows` as CSV with a header row, which is written for an empty table too.
+pub fn write_csv<T: PcornetTable>(rows: &[T], writer: impl Write) -> io::Result<()> {
+    let mut writer = csv::WriterBuilder::new().has_headers(false).from_writer(writer);
+    writer.write_record(T::COLUMNS)?;
+    for row in rows {
+        writer.serialize(row)?;
+    }
+    writer.flush()
+}
+
+/// Row of the DEMOGRAPHIC table.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub struct Demographic {
+    pub patid: String,
+    pub birth_date: NaiveDate,
+    /// Not recorded on `Person`; always empty
+    pub birth_time: Option<String>,
+    /// F, M, A (ambiguous), OT, UN (unknown) or NI
+    pub sex: &'static str,
+    pub sexual_orientation: &'static str,
+    pub gender_identity: &'static str,
+    pub hispanic: &'static str,
+    pub race: &'static str,
+    pub biobank_flag: &'static str,
+    pub pat_pref_language_spoken: &'static str,
+    pub raw_sex: Option<String>,
+    /// Preferred languages as recorded (BCP 47 tags), separated by `;`
+    pub raw_pat_pref_language_spoken: Option<String>,
+}
+
+impl PcornetTable for Demographic {
+    const TABLE: &'static str = "DEMOGRAPHIC";
+    const COLUMNS: &'static [&'static str] = &[
+        "PATID",
+        "BIRTH_DATE",
+        "BIRTH_TIME",
+        "SEX",
+        "SEXUAL_ORIENTATION",
+        "GENDER_IDENTITY",
+        "HISPANIC",
+        "RACE",
+        "BIOBANK_FLAG",
+        "PAT_PREF_LANGUAGE_SPOKEN",
+        "RAW_SEX",
+        "RAW_PAT_PREF_LANGUAGE_SPOKEN",
+    ];
+}
+
+impl Demographic {
+    /// Row for `person`. A birth date known only to the year or month is
+    /// written as its first day. Race, ethnicity, sexual orientation and
+    /// gender identity are not recorded on `Person` and are written as `NI`.
+    pub fn from_person(person: &Person) -> Self {
+        Demographic {
+            patid: person.id.clone(),
+            birth_date: person.birth_date.date(),
+            birth_time: None,
+            sex: match person.gender {
+                Some(Gender::Female) => "F",
+                Some(Gender::Male) => "M",
+                Some(Gender::Other) => OTHER,
+                Some(Gender::Unknown) => "UN",
+                None => NO_INFORMATION,
+            },
+            sexual_orientation: NO_INFORMATION,
+            gender_identity: NO_INFORMATION,
+            hispanic: NO_INFORMATION,
+            race: NO_INFORMATION,
+            biobank_flag: "N",
+            pat_pref_language_spoken: NO_INFORMATION,
+            raw_sex: person.gender.and_then(|gender| Some(serde_json::to_value(gender).ok()?.as_str()?.into())),
+            raw_pat_pref_language_spoken: person.language.as_ref().map(|languages| languages.join(";")),
+        }
+    }
+}
+
+/// Row of the LAB_RESULT_CM table.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub struct LabResultCm {
+    /// Report id and the result's position from 1 (e.g., "lab-1-2")
+    pub lab_result_cm_id: String,
+    pub patid: String,
+    pub encounterid: Option<String>,
+    /// BLOOD, SERUM, PLASMA, URINE, CSF, OT or NI
+    pub specimen_source: &'static str,
+    pub lab_loinc: Option<String>,
+    pub lab_result_source: &'static str,
+    pub priority: &'static str,
+    pub result_loc: &'static str,
+    #[serde(serialize_with = "date")]
+    pub specimen_date: Option<NaiveDateTime>,
+    #[serde(serialize_with = "time")]
+    pub specimen_time: Option<NaiveDateTime>,
+    pub result_date: NaiveDate,
+    #[serde(serialize_with = "time")]
+    pub result_time: Option<NaiveDateTime>,
+    /// Qualitative result (e.g., POSITIVE); NI for numeric results
+    pub result_qual: &'static str,
+    /// SNOMED CT code of a coded result
+    pub result_snomed: Option<String>,
+    pub result_num: Option<f64>,
+    /// EQ, LT, LE, GT, GE, TX (text) or NI
+    pub result_modifier: &'static str,
+    pub result_unit: Option<String>,
+    pub norm_range_low: Option<f64>,
+    pub norm_modifier_low: &'static str,
+    pub norm_range_high: Option<f64>,
+    pub norm_modifier_high: &'static str,
+    /// NL, AL, AH, CL, CH, CR, AB, OT or NI
+    pub abn_ind: &'static str,
+    pub raw_lab_name: Option<String>,
+    pub raw_lab_code: Option<String>,
+    pub raw_result: Option<String>,
+    pub raw_unit: Option<String>,
+}
+
+impl PcornetTable for LabResultCm {
+    const TABLE: &'static str = "LAB_RESULT_CM";
+    const COLUMNS: &'static [&'static str] = &[
+        "LAB_RESULT_CM_ID",
+        "PATID",
+        "ENCOUNTERID",
+        "SPECIMEN_SOURCE",
+        "LAB_LOINC",
+        "LAB_RESULT_SOURCE",
+        "PRIORITY",
+        "RESULT_LOC",
+        "SPECIMEN_DATE",
+        "SPECIMEN_TIME",
+        "RESULT_DATE",
+        "RESULT_TIME",
+        "RESULT_QUAL",
+        "RESULT_SNOMED",
+        "RESULT_NUM",
+        "RESULT_MODIFIER",
+        "RESULT_UNIT",
+        "NORM_RANGE_LOW",
+        "NORM_MODIFIER_LOW",
+        "NORM_RANGE_HIGH",
+        "NORM_MODIFIER_HIGH",
+        "ABN_IND",
+        "RAW_LAB_NAME",
+        "RAW_LAB_CODE",
+        "RAW_RESULT",
+        "RAW_UNIT",
+    ];
+}
+
+impl LabResultCm {
+    /// Row for the result at `index` of `report`. The result date is the
+    /// result's effective day when it has one, else the issue day.
+    pub fn from_result(report: &LabReport, index: usize, result: &LabResult) -> Self {
+        let loinc = result.code.coding.iter().find(|coding| coding.system == catalog::LOINC);
+        let raw = result.code.coding.first();
+        let collected_at = report.specimen.as_ref().and_then(|specimen| specimen.collected_at).map(|at| at.local());
+        let issued_at = report.issued_at.local();
+        let quantity = match &result.value {
+            LabValue::Quantity(quantity) | LabValue::Comparator { value: quantity, .. } => Some(quantity),
+            _ => None,
+        };
+        let comparator = match &result.value {
+            LabValue::Comparator { comparator, .. } => Some(*comparator),
+            _ => quantity.and_then(|quantity| quantity.comparator),
+        };
+        let result_modifier = match (&result.value, comparator) {
+            (_, Some(comparator)) => modifier(comparator),
+            (LabValue::Concept(_) | LabValue::String(_), None) => "TX",
+            (_, None) => "EQ",
+        };
+        let range = result.reference_range.as_ref().and_then(|ranges| ranges.first());
+        let low = range.and_then(|range| Some(range.low.as_ref()?.value));
+        let high = range.and_then(|range| Some(range.high.as_ref()?.value));
+        let (norm_modifier_low, norm_modifier_high) = match (low, high) {
+            (Some(_), Some(_)) => ("EQ", "EQ"),
+            (Some(_), None) => ("GE", "NO"),
+            (None, Some(_)) => ("NO", "LE"),
+            (None, None) => (NO_INFORMATION, NO_INFORMATION),
+        };
+        LabResultCm {
+            lab_result_cm_id: format!("{}-{}", report.id, index + 1),
+            patid: report.patient_id.clone(),
+            encounterid: None,
+            specimen_source: specimen_source(report),
+            lab_loinc: loinc.map(|coding| coding.code.clone()),
+            lab_result_source: "OD",
+            priority: NO_INFORMATION,
+            result_loc: NO_INFORMATION,
+            specimen_date: collected_at,
+            specimen_time: collected_at,
+            result_date: result.effective.and_then(|effective| effective.first_date()).unwrap_or(issued_at.date()),
+            result_time: Some(issued_at),
+            result_qual: result_qual(result),
+            result_snomed: match &result.value {
+                LabValue::Concept(concept) => snomed(concept),
+                _ => None,
+            },
+            result_num: result.value.numeric_value(),
+            result_modifier,
+            result_unit: quantity.map(|quantity| quantity.ucum_code().into()),
+            norm_range_low: low,
+            norm_modifier_low,
+            norm_range_high: high,
+            norm_modifier_high,
+            abn_ind: abnormal_indicator(result.interpretation),
+            raw_lab_name: raw.and_then(|coding| coding.display.clone()).or_else(|| result.code.text.clone()),
+            raw_lab_code: raw.map(|coding| coding.code.clone()),
+            raw_result: raw_result(&result.value),
+            raw_unit: quantity.map(|quantity| quantity.unit.clone()),
+        }
+    }
+}
+
+/// Row of the PRESCRIBING table.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub struct Prescribing {
+    pub prescribingid: String,
+    pub patid: String,
+    pub encounterid: Option<String>,
+    pub rx_order_date: NaiveDate,
+    pub rx_start_date: NaiveDate,
+    /// Last day of treatment, when its length is known
+    pub rx_end_date: Option<NaiveDate>,
+    pub rx_dose_ordered: f64,
+    pub rx_dose_ordered_unit: String,
+    pub rx_days_supply: Option<i64>,
+    /// 01 (every day) to 04 (four times a day), OT or NI
+    pub rx_frequency: &'static str,
+    /// Y for as-needed medications
+    pub rx_prn_flag: &'static str,
+    /// SNOMED CT route code
+    pub rx_route: Option<String>,
+    pub rx_basis: &'static str,
+    pub rxnorm_cui: Option<String>,
+    pub rx_source: &'static str,
+    pub raw_rx_med_name: Option<String>,
+    pub raw_rx_frequency: Option<String>,
+    pub raw_rxnorm_cui: Option<String>,
+    pub raw_rx_route: String,
+}
+
+impl PcornetTable for Prescribing {
+    const TABLE: &'static str = "PRESCRIBING";
+    const COLUMNS: &'static [&'static str] = &[
+        "PRESCRIBINGID",
+        "PATID",
+        "ENCOUNTERID",
+        "RX_ORDER_DATE",
+        "RX_START_DATE",
+        "RX_END_DATE",
+        "RX_DOSE_ORDERED",
+        "RX_DOSE_ORDERED_UNIT",
+        "RX_DAYS_SUPPLY",
+        "RX_FREQUENCY",
+        "RX_PRN_FLAG",
+        "RX_ROUTE",
+        "RX_BASIS",
+        "RXNORM_CUI",
+        "RX_SOURCE",
+        "RAW_RX_MED_NAME",
+        "RAW_RX_FREQUENCY",
+        "RAW_RXNORM_CUI",
+        "RAW_RX_ROUTE",
+    ];
+}
+
+impl Prescribing {
+    /// Row for `medication`, ordered on its start date.
+    pub fn from_medication(medication: &MedicationRecord) -> Self {
+        let rxnorm = (medication.medication.system == catalog::RXNORM).then(|| medication.medication.code.clone());
+        let frequency = medication.frequency.as_deref();
+        Prescribing {
+            prescribingid: medication.id.clone(),
+            patid: medication.patient_id.clone(),
+            encounterid: None,
+            rx_order_date: medication.start_date,
+            rx_start_date: medication.start_date,
+            rx_end_date: medication.last_date(),
+            rx_dose_ordered: medication.dosage.value,
+            rx_dose_ordered_unit: medication.dosage.unit.clone(),
+            rx_days_supply: medication.treatment_days(),
+            rx_frequency: frequency.map_or(NO_INFORMATION, frequency_code),
+            rx_prn_flag: match frequency {
+                _ if medication.is_prn => "Y",
+                Some(frequency) if frequency.to_ascii_uppercase().contains("PRN") => "Y",
+                Some(_) => "N",
+                None => NO_INFORMATION,
+            },
+            rx_route: (medication.route.system == catalog::SNOMED_CT).then(|| medication.route.code.clone()),
+            rx_basis: NO_INFORMATION,
+            rxnorm_cui: rxnorm.clone(),
+            rx_source: "OD",
+            raw_rx_med_name: medication.medication.display.clone(),
+            raw_rx_frequency: medication.frequency.clone(),
+            raw_rxnorm_cui: rxnorm,
+            raw_rx_route: medication.route.code.clone(),
+        }
+    }
+}
+
+fn modifier(comparator: Comparator) -> &'static str {
+    match comparator {
+        Comparator::LessThan => "LT",
+        Comparator::LessOrEqual => "LE",
+        Comparator::GreaterThan => "GT",
+        Comparator::GreaterOrEqual => "GE",
+    }
+}
+
+/// PCORnet frequency code of a frequency such as "BID".
+fn frequency_code(frequency: &str) -> &'static str {
+    let code: String = frequency.chars().filter(|c| !matches!(c, '.' | ' ')).collect();
+    match code.to_ascii_uppercase().as_str() {
+        "QD" | "OD" | "DAILY" => "01",
+        "BID" => "02",
+        "TID" => "03",
+        "QID" => "04",
+        "QAM" => "05",
+        "QPM" | "QHS" | "HS" => "10",
+        _ => OTHER,
+    }
+}
+
+fn specimen_source(report: &LabReport) -> &'static str {
+    let Some(specimen_type) = report.specimen.as_ref().and_then(|specimen| specimen.specimen_type.as_ref()) else {
+        return NO_INFORMATION;
+    };
+    match specimen_type.code.as_str() {
+        "BLD" => "BLOOD",
+        "SER" => "SERUM",
+        "PLAS" => "PLASMA",
+        "UR" => "URINE",
+        "CSF" => "CSF",
+        _ => OTHER,
+    }
+}
+
+fn result_qual(result: &LabResult) -> &'static str {
+    match result.interpretation {
+        Some(Interpretation::POS) => "POSITIVE",
+        Some(Interpretation::NEG) => "NEGATIVE",
+        Some(Interpretation::DET) => "DETECTED",
+        Some(Interpretation::ND) => "NOT DETECTED",
+        Some(Interpretation::RR) => "REACTIVE",
+        Some(Interpretation::NR) => "NON-REACTIVE",
+        Some(Interpretation::IND) => "INDETERMINATE",
+        Some(Interpretation::E) => "EQUIVOCAL",
+        _ if result.value.numeric_value().is_some() => NO_INFORMATION,
+        _ => OTHER,
+    }
+}
+
+fn abnormal_indicator(interpretation: Option<Interpretation>) -> &'static str {
+    match interpretation {
+        None => NO_INFORMATION,
+        Some(Interpretation::N) => "NL",
+        Some(Interpretation::L | Interpretation::LU) => "AL",
+        Some(Interpretation::H | Interpretation::HU) => "AH",
+        Some(Interpretation::LL) => "CL",
+        Some(Interpretation::HH) => "CH",
+        Some(Interpretation::AA) => "CR",
+        Some(Interpretation::A) => "AB",
+        Some(_) => OTHER,
+    }
+}
+
+fn snomed(concept: &CodeableConcept) -> Option<String> {
+    concept.coding.iter().find(|coding| coding.system == catalog::SNOMED_CT).map(|coding| coding.code.clone())
+}
+
+/// The value as reported (e.g., "<5", "1:128", "Positive").
+fn raw_result(value: &LabValue) -> Option<String> {
+    match value {
+        LabValue::Quantity(quantity) => Some(quantity.value.to_string()),
+        LabValue::Comparator { comparator, value } => Some(format!("{}{}", comparator.as_str(), value.value)),
+        LabValue::Ratio { numerator, denominator } => Some(format!("{}:{}", numerator, denominator)),
+        LabValue::Concept(concept) => concept.text.clone().or_else(|| {
+            let coding = concept.coding.first()?;
+            Some(coding.display.clone().unwrap_or_else(|| coding.code.clone()))
+        }),
+        LabValue::String(text) => Some(text.clone()),
+    }
+}
+
+fn date<S: Serializer>(at: &Option<NaiveDateTime>, serializer: S) -> Result<S::Ok, S::Error> {
+    at.map(|at| at.date()).serialize(serializer)
+}
+
+fn time<S: Serializer>(at: &Option<NaiveDateTime>, serializer: S) -> Result<S::Ok, S::Error> {
+    at.map(|at| at.format("%H:%M").to_string()).serialize(serializer)
+}
@@ -0,0 +1,223 @@
+//! PostgreSQL (sqlx) mappings for WellAlly resources.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//!
+//! Top-level resources encode to and decode from `JSONB`, and can be read
+//! straight out of the reference schema in `sql/postgres/` with
+//! `sqlx::query_as`. Queries run on whichever runtime the application enables
+//! on its own `sqlx` dependency.
+
+use chrono::{DateTime, Utc};
+use sqlx::encode::IsNull;
+use sqlx::error::BoxDynError;
+use sqlx::postgres::{PgArgumentBuffer, PgExecutor, PgRow, PgTypeInfo, PgValueRef, Postgres};
+use sqlx::types::Json;
+use sqlx::{Decode, Encode, FromRow, Row, Type};
+
+use crate::family_health::FamilyHealthTree;
+use crate::health::Person;
+use crate::imaging_report::ImagingReport;
+use crate::lab_report::LabReport;
+use crate::medication::MedicationRecord;
+use crate::questionnaire::{Questionnaire, QuestionnaireResponse};
+use crate::referral::Referral;
+use crate::task::Task;
+use crate::communication::CommunicationRecord;
+use crate::location::Location;
+use crate::condition::Condition;
+use crate::allergy::AllergyIntolerance;
+use crate::flag::Flag;
+use crate::cgm::GlucoseSeries;
+use crate::ecg::EcgRecord;
+use crate::consent::ConsentRecord;
+use crate::nephrology::DialysisSession;
+use crate::rehab::RehabSession;
+use crate::wound::WoundAssessment;
+use crate::infusion::InfusionRecord;
+use crate::pathology::PathologyReport;
+use crate::audiology::AudiogramReport;
+use crate::birth::BirthRecord;
+use crate::clearance::ClearanceDocument;
+use crate::travel::TravelHealthRecord;
+use crate::compendium::Compendium;
+use crate::dispense::DispenseRecord;
+use crate::immunization::ImmunizationRecord;
+use crate::relationships::RelatedPerson;
+use crate::resource::Resource;
+
+/// Reference schema migration; also usable with `sqlx::migrate!("sql/postgres")`.
+pub const MIGRATION: &str = include_str!("../sql/postgres/0001_resources.sql");
+
+/// A row of the `resources` table.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResourceRow<R> {
+    /// Resource type name
+    pub resource_type: String,
+    /// Resource id
+    pub id: String,
+    /// Person.id the resource belongs to
+    pub patient_id: String,
+    /// Time of the last write
+    pub updated_at: DateTime<Utc>,
+    /// Decoded `body` document
+    pub resource: R,
+}
+
+impl<'r, R: Resource> FromRow<'r, PgRow> for ResourceRow<R> {
+    fn from_row(row: &'r PgRow) -> Result<Self, sqlx::Error> {
+        let Json(resource) = row.try_get::<Json<R>, _>("body")?;
+        Ok(Self {
+            resource_type: row.try_get("resource_type")?,
+            id: row.try_get("id")?,
+            patient_id: row.try_get("patient_id")?,
+            updated_at: row.try_get("updated_at")?,
+            resource,
+        })
+    }
+}
+
+/// Implements JSONB `Type`/`Encode`/`Decode` and a `body`-column `FromRow` for a resource.
+macro_rules! jsonb_resource {
+    ($($ty:ty),* $(,)?) => {$(
+        impl Type<Postgres> for $ty {
+            fn type_info() -> PgTypeInfo {
+                <Json<Self> as Type<Postgres>>::type_info()
+            }
+
+            fn compatible(ty: &PgTypeInfo) -> bool {
+                <Json<Self> as Type<Postgres>>::compatible(ty)
+            }
+        }
+
+        impl Encode<'_, Postgres> for $ty {
+            fn encode_by_ref(&self, buf: &mut PgArgumentBuffer) -> Result<IsNull, BoxDynError> {
+                Json(self).encode_by_ref(buf)
+            }
+        }
+
+        impl<'r> Decode<'r, Postgres> for $ty {
+            fn decode(value: PgValueRef<'r>) -> Result<Self, BoxDynError> {
+                Ok(<Json<Self> as Decode<Postgres>>::decode(value)?.0)
+            }
+        }
+
+        impl<'r> FromRow<'r, PgRow> for $ty {
+            fn from_row(row: &'r PgRow) -> Result<Self, sqlx::Error> {
+                row.try_get("body")
+            }
+        }
+    )*};
+}
+
+jsonb_resource!(
+    Person,
+    LabReport,
+    ImagingReport,
+    MedicationRecord,
+    FamilyHealthTree,
+    Questionnaire,
+    QuestionnaireResponse,
+    Referral,
+    Task,
+    CommunicationRecord,
+    Location,
+    Condition,
+    AllergyIntolerance,
+    Flag,
+    GlucoseSeries,
+    EcgRecord,
+    ConsentRecord,
+    DialysisSession,
+    RehabSession,
+    WoundAssessment,
+    InfusionRecord,
+    PathologyReport,
+    AudiogramReport,
+    BirthRecord,
+    ClearanceDocument,
+    TravelHealthRecord,
+    Compendium,
+    DispenseRecord,
+    ImmunizationRecord,
+    RelatedPerson,
+);
+
+/// Inserts or replaces a resource, returning its new `updated_at`.
+pub async fn save<'e, R, E>(executor: E, resource: &R) -> Result<DateTime<Utc>, sqlx::Error>
+where
+    R: Resource + Sync + 'static,
+    E: PgExecutor<'e>,
+{
+    sqlx::query_scalar(
+        "INSERT INTO resources (resource_type, id, patient_id, updated_at, body)
+         VALUES ($1, $2, $3, clock_timestamp(), $4)
+         ON CONFLICT (resource_type, id) DO UPDATE SET
+             patient_id = excluded.patient_id,
+             updated_at = excluded.updated_at,
+             body = excluded.body
+         RETURNING updated_at",
+    )
+    .bind(R::RESOURCE_TYPE)
+    .bind(resource.id())
+    .bind(resource.patient_id())
+    .bind(Json(resource))
+    .fetch_one(executor)
+    .await
+}
+
+/// Loads a resource by id.
+pub async fn load<'e, R, E>(executor: E, id: &str) -> Result<Option<R>, sqlx::Error>
+where
+    R: Resource + Send + Unpin + 'static,
+    E: PgExecutor<'e>,
+{
+    let body: Option<Json<R>> =
+        sqlx::query_scalar("SELECT body FROM resources WHERE resource_type = $1 AND id = $2")
+            .bind(R::RESOURCE_TYPE)
+            .bind(id)
+            .fetch_optional(executor)
+            .await?;
+    Ok(body.map(|Json(resource)| resource))
+}
+
+/// Loads every resource of type `R` belonging to a patient.
+pub async fn load_by_patient<'e, R, E>(executor: E, patient_id: &str) -> Result<Vec<R>, sqlx::Error>
+where
+    R: Resource + Send + Unpin + 'static,
+    E: PgExecutor<'e>,
+{
+    let bodies: Vec<Json<R>> = sqlx::query_scalar(
+        "SELECT body FROM resources
+         WHERE resource_type = $1 AND patient_id = $2
+         ORDER BY id",
+    )
+    .bind(R::RESOURCE_TYPE)
+    .bind(patient_id)
+    .fetch_all(executor)
+    .await?;
+    Ok(bodies.into_iter().map(|Json(resource)| resource).collect())
+}
+
+/// Loads rows of type `R` written after `since`, oldest first.
+pub async fn updated_since<'e, R, E>(
+    executor: E,
+    since: DateTime<Utc>,
+    limit: i64,
+) -> Result<Vec<ResourceRow<R>>, sqlx::Error>
+where
+    R: Resource + Send + Unpin + 'static,
+    E: PgExecutor<'e>,
+{
+    sqlx::query_as(
+        "SELECT resource_type, id, patient_id, updated_at, body FROM resources
+         WHERE resource_type = $1 AND updated_at > $2
+         ORDER BY updated_at
+         LIMIT $3",
+    )
+    .bind(R::RESOURCE_TYPE)
+    .bind(since)
+    .bind(limit)
+    .fetch_all(executor)
+    .await
+}
@@ -0,0 +1,116 @@
+//! Problem list maintenance.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//!
+//! A patient's problem list is the set of their [`Condition`]s with one
+//! entry per problem. [`update`] folds newly received conditions into an
+//! existing list the way problem lists are usually reconciled: an incoming
+//! condition with the same code updates the entry already there instead of
+//! adding a duplicate, the earliest known onset is kept, a resolved report
+//! closes the problem and one entered in error removes it.
+//! [`active_problems`] gives the snapshot shown in
+//! [`ClinicalSummary::conditions`](crate::ClinicalSummary).
+//!
+//! ```
+//! use wellally::catalog::{concept, SNOMED_CT};
+//! use wellally::problem_list;
+//! use wellally::{ClinicalStatus, Condition};
+//!
+//! let diabetes = concept(SNOMED_CT, "44054006", "Diabetes mellitus type 2");
+//! let existing = vec![Condition::new("c-1", "p-1", diabetes.clone())];
+//! let mut resolved = Condition::new("c-9", "p-1", diabetes);
+//! resolved.clinical_status = ClinicalStatus::Resolved;
+//!
+//! let list = problem_list::update(existing, vec![resolved]);
+//! assert_eq!(list.len(), 1);
+//! assert_eq!(list[0].id, "c-1");
+//! assert_eq!(list[0].clinical_status, ClinicalStatus::Resolved);
+//! assert!(problem_list::active_problems(&list).is_empty());
+//! ```
+
+use alloc::vec::Vec;
+
+use crate::common::CodeableConcept;
+use crate::condition::{ClinicalStatus, Condition, VerificationStatus};
+use crate::datetime::FlexibleDateTime;
+
+/// Merges `incoming` conditions into `existing`, in order.
+///
+/// An incoming condition matching an entry of the same patient by code
+/// (see [`Condition::same_code`]) updates that entry, which keeps its id:
+///
+/// - entered in error: the entry is removed;
+/// - the clinical and verification status, severity and abatement are taken
+///   from the incoming condition, so a resolved report closes the problem; a
+///   problem reported active again after it was resolved becomes a
+///   recurrence and loses its abatement;
+/// - the onset is the earliest of the two and the recorded date the latest;
+/// - codings and notes the entry lacks are added.
+///
+/// Other incoming conditions are appended, except ones entered in error.
+pub fn update(existing: Vec<Condition>, incoming: Vec<Condition>) -> Vec<Condition> {
+    let mut list = existing;
+    for condition in incoming {
+        let entered_in_error = condition.verification_status == Some(VerificationStatus::EnteredInError);
+        let found = list.iter().position(|entry| entry.patient_id == condition.patient_id && entry.same_code(&condition));
+        match found {
+            Some(at) if entered_in_error => {
+                list.remove(at);
+            }
+            Some(at) => merge(&mut list[at], condition),
+            None if entered_in_error => {}
+            None => list.push(condition),
+        }
+    }
+    list
+}
+
+fn merge(entry: &mut Condition, incoming: Condition) {
+    let reopened = !entry.clinical_status.is_active() && incoming.clinical_status.is_active();
+    entry.clinical_status = match incoming.clinical_status {
+        ClinicalStatus::Active if reopened && entry.clinical_status == ClinicalStatus::Resolved => ClinicalStatus::Recurrence,
+        ClinicalStatus::Active if reopened && entry.clinical_status == ClinicalStatus::Remission => ClinicalStatus::Relapse,
+        status => status,
+    };
+    entry.abatement = if entry.clinical_status.is_active() { None } else { incoming.abatement.or(entry.abatement) };
+    if incoming.verification_status.is_some() {
+        entry.verification_status = incoming.verification_status;
+    }
+    entry.category = entry.category.or(incoming.category);
+    if incoming.severity.is_some() {
+        entry.severity = incoming.severity;
+    }
+    entry.onset = earliest(entry.onset, incoming.onset);
+    entry.recorded_date = match (entry.recorded_date, incoming.recorded_date) {
+        (Some(ours), Some(theirs)) if theirs.to_utc() > ours.to_utc() => Some(theirs),
+        (ours, theirs) => ours.or(theirs),
+    };
+    for coding in incoming.code.coding {
+        if !entry.code.coding.iter().any(|known| known.system == coding.system && known.code == coding.code) {
+            entry.code.coding.push(coding);
+        }
+    }
+    if entry.code.text.is_none() {
+        entry.code.text = incoming.code.text;
+    }
+    for note in incoming.notes.into_iter().flatten() {
+        let notes = entry.notes.get_or_insert_with(Vec::new);
+        if !notes.contains(&note) {
+            notes.push(note);
+        }
+    }
+}
+
+fn earliest(a: Option<FlexibleDateTime>, b: Option<FlexibleDateTime>) -> Option<FlexibleDateTime> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(if b.to_utc() < a.to_utc() { b } else { a }),
+        (a, b) => a.or(b),
+    }
+}
+
+/// Codes of the active conditions (see [`Condition::is_active`]), in list
+/// order, for `ClinicalSummary.conditions`.
+pub fn active_problems(conditions: &[Condition]) -> Vec<CodeableConcept> {
+    conditions.iter().filter(|condition| condition.is_active()).map(|condition| condition.code.clone()).collect()
+}
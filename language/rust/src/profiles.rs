@@ -0,0 +1,270 @@
+//! Data-quality profiles declared in JSON or TOML.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//!
+//! A [`Profile`] tightens the base schemas for one deployment without
+//! changing the structs: each [`Rule`] names a resource type and a dotted
+//! path of JSON field names, and constrains what is found there. Arrays
+//! along the path are traversed, so `results.code.coding` reaches every
+//! coding of every result. A rule can require the field (in every parent
+//! the path reaches), bound how many items it holds, restrict code systems
+//! or values, match strings against a regular expression, and test values
+//! with an [`Expression`].
+//!
+//! ```
+//! use wellally::profiles::Profile;
+//! use wellally::{LabReport, Resource};
+//!
+//! let profile = Profile::from_toml(r#"
+//!     name = "clinic-a"
+//!
+//!     [[rules]]
+//!     resourceType = "LabReport"
+//!     path = "results.code"
+//!     systems = ["http://loinc.org"]
+//!
+//!     [[rules]]
+//!     resourceType = "LabReport"
+//!     path = "specimen.collectedAt"
+//!     required = true
+//!     message = "collection time is mandatory at clinic A"
+//! "#)?;
+//!
+//! let report = LabReport::from_json(r#"{"id": "lab-1", "patientId": "p-1", "issuedAt": "2024-11-02T09:30:00Z",
+//!     "specimen": {"type": {"system": "http://snomed.info/sct", "code": "119297000"}},
+//!     "results": [{"code": {"coding": [{"system": "urn:local", "code": "GLU"}]}, "value": "normal"}]}"#)?;
+//! let issues = profile.validate(&report);
+//! assert_eq!(issues[0].path, "results[0].code.coding[0].system");
+//! assert_eq!(issues[1].message, "collection time is mandatory at clinic A");
+//! # Ok::<(), wellally::WellAllyError>(())
+//! ```
+
+use regex::Regex;
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+
+use crate::error::{self, WellAllyError};
+use crate::expr::Expression;
+use crate::io::AnyResource;
+use crate::resource::Resource;
+use crate::validation::{index, join, ValidationIssue};
+
+/// A named set of data-quality rules.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    /// Profile name (e.g., "clinic-a")
+    pub name: String,
+    /// What the profile is for
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Rules, checked in order
+    #[serde(default)]
+    pub rules: Vec<Rule>,
+}
+
+/// A constraint on the values found at a path of one resource type.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Rule {
+    /// Resource type the rule applies to (e.g., "LabReport")
+    #[serde(rename = "resourceType", alias = "resource_type")]
+    pub resource_type: String,
+    /// Dotted JSON field names (e.g., "results.code.coding"); arrays are traversed
+    pub path: String,
+    /// The field must be present in every parent the path reaches
+    #[serde(default, skip_serializing_if = "core::ops::Not::not")]
+    pub required: bool,
+    /// Fewest items the field may hold in each parent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min: Option<usize>,
+    /// Most items the field may hold in each parent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max: Option<usize>,
+    /// Allowed code systems of the codings at the path (a Coding, or a
+    /// CodeableConcept's codings)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub systems: Option<Vec<String>>,
+    /// Allowed values
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub values: Option<Vec<Value>>,
+    /// Regular expression every string value must match
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<Pattern>,
+    /// [`Expression`] every value at the path must satisfy, evaluated with
+    /// the value as `$this` (e.g., `value.exists() or dataAbsentReason.exists()`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expression: Option<Expression>,
+    /// Message reported instead of the default one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+/// A regular expression, compiled when the profile is read.
+#[derive(Debug, Clone)]
+pub struct Pattern(Regex);
+
+impl Pattern {
+    /// Compiles `pattern`.
+    pub fn new(pattern: &str) -> Result<Self, regex::Error> {
+        Regex::new(pattern).map(Pattern)
+    }
+
+    /// Source of the expression.
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+
+    /// Whether `text` contains a match; anchor with `^...$` to match whole values.
+    pub fn is_match(&self, text: &str) -> bool {
+        self.0.is_match(text)
+    }
+}
+
+impl PartialEq for Pattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Serialize for Pattern {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for Pattern {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let pattern = String::deserialize(deserializer)?;
+        Pattern::new(&pattern).map_err(D::Error::custom)
+    }
+}
+
+impl Profile {
+    /// Reads a profile from JSON; errors carry the path of the offending field.
+    pub fn from_json(json: &str) -> Result<Self, WellAllyError> {
+        error::from_str("Profile", json)
+    }
+
+    /// Reads a profile from TOML.
+    pub fn from_toml(text: &str) -> Result<Self, WellAllyError> {
+        toml::from_str(text).map_err(|err| WellAllyError::Parse {
+            resource_type: "Profile".to_string(),
+            path: String::new(),
+            value: None,
+            message: err.message().to_string(),
+        })
+    }
+
+    /// Issues with `resource` under the profile's rules for its type.
+    pub fn validate<R: Resource>(&self, resource: &R) -> Vec<ValidationIssue> {
+        match serde_json::to_value(resource) {
+            Ok(value) => self.validate_value(R::RESOURCE_TYPE, &value),
+            Err(err) => vec![ValidationIssue::new("", err.to_string())],
+        }
+    }
+
+    /// Issues with a resource of any type.
+    pub fn validate_any(&self, resource: &AnyResource) -> Vec<ValidationIssue> {
+        match serde_json::to_value(resource) {
+            Ok(value) => self.validate_value(resource.resource_type(), &value),
+            Err(err) => vec![ValidationIssue::new("", err.to_string())],
+        }
+    }
+
+    /// Issues with the canonical JSON `value` of a `resource_type` resource.
+    pub fn validate_value(&self, resource_type: &str, value: &Value) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+        for rule in self.rules.iter().filter(|rule| rule.resource_type == resource_type) {
+            rule.check(value, &mut issues);
+        }
+        issues
+    }
+}
+
+impl Rule {
+    fn check(&self, root: &Value, issues: &mut Vec<ValidationIssue>) {
+        let mut report = |path: String, default: String| {
+            issues.push(ValidationIssue::new(path, self.message.clone().unwrap_or(default)));
+        };
+        let (parents, field) = match self.path.rsplit_once('.') {
+            Some((parents, field)) => (select(root, parents), field),
+            None => (vec![(String::new(), root)], self.path.as_str()),
+        };
+        let min = self.min.unwrap_or(0).max(usize::from(self.required));
+        let mut nodes = Vec::new();
+        for (parent_path, parent) in parents {
+            let path = join(&parent_path, field);
+            let count = match parent.get(field) {
+                None | Some(Value::Null) => 0,
+                Some(Value::Array(items)) => items.len(),
+                Some(_) => 1,
+            };
+            if count == 0 && min > 0 {
+                report(path.clone(), "is required".to_string());
+            } else if count < min {
+                report(path.clone(), format!("must have at least {} items", min));
+            }
+            if let Some(max) = self.max.filter(|max| count > *max) {
+                report(path.clone(), format!("must have at most {} items", max));
+            }
+            if let Some(value) = parent.get(field) {
+                nodes.extend(items(path, value));
+            }
+        }
+        for (path, node) in nodes {
+            if let Some(systems) = &self.systems {
+                for (coding_path, coding) in codings(path.clone(), node) {
+                    let system = coding.get("system").and_then(Value::as_str).unwrap_or_default();
+                    if !systems.iter().any(|allowed| allowed == system) {
+                        report(join(&coding_path, "system"), format!("code system '{}' is not allowed", system));
+                    }
+                }
+            }
+            if self.values.as_ref().is_some_and(|values| !values.contains(node)) {
+                report(path.clone(), format!("value {} is not allowed", node));
+            }
+            if let (Some(pattern), Some(text)) = (&self.pattern, node.as_str()) {
+                if !pattern.is_match(text) {
+                    report(path.clone(), format!("'{}' does not match {}", text, pattern.as_str()));
+                }
+            }
+            if let Some(expression) = self.expression.as_ref().filter(|expression| !expression.matches_value(node)) {
+                report(path.clone(), format!("does not satisfy {}", expression));
+            }
+        }
+    }
+}
+
+/// Non-null values at `path` below `root`, with their indexed paths.
+fn select<'v>(root: &'v Value, path: &str) -> Vec<(String, &'v Value)> {
+    let mut nodes = vec![(String::new(), root)];
+    for field in path.split('.') {
+        nodes = nodes
+            .into_iter()
+            .filter_map(|(path, node)| Some((join(&path, field), node.get(field)?)))
+            .flat_map(|(path, value)| items(path, value))
+            .collect();
+    }
+    nodes
+}
+
+/// The items of an array, or the value itself; nulls are dropped.
+fn items(path: String, value: &Value) -> Vec<(String, &Value)> {
+    match value {
+        Value::Array(items) => items.iter().enumerate().map(|(i, item)| (index(&path, i), item)).collect(),
+        Value::Null => Vec::new(),
+        value => vec![(path, value)],
+    }
+}
+
+/// The value as a Coding, or the codings of a CodeableConcept.
+fn codings(path: String, value: &Value) -> Vec<(String, &Value)> {
+    match value.get("coding") {
+        Some(coding) => items(join(&path, "coding"), coding),
+        None if value.get("system").is_some() || value.get("code").is_some() => vec![(path, value)],
+        None => Vec::new(),
+    }
+}
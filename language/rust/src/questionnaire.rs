@@ -0,0 +1,565 @@
+//! Questionnaire and QuestionnaireResponse data models.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//! Schema: https://wellall.health/schemas/questionnaire/v0.1.0
+//!
+//! A [`Questionnaire`] defines a form (intake, screening, PROM) as nested
+//! items with types, answer options and `enableWhen` conditions; a
+//! [`QuestionnaireResponse`] holds one person's answers. Answer options may
+//! carry a `score`, which [`SumOfOptionScores`] adds up (PHQ-9, GAD-7 style);
+//! other instruments plug in their own [`ScoringRule`].
+
+use alloc::{format, string::String, vec::Vec};
+use core::cmp::Ordering;
+
+use serde::{Deserialize, Serialize};
+
+use crate::common::{Coding, Quantity};
+use crate::datetime::{FlexibleDate, FlexibleDateTime};
+use crate::validation::{index, join, ValidationIssue};
+use crate::extension::Extension;
+
+/// Publication status of a questionnaire
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "async-graphql", derive(async_graphql::Enum))]
+#[serde(rename_all = "lowercase")]
+pub enum QuestionnaireStatus {
+    Draft,
+    Active,
+    Retired,
+}
+
+/// Kind of questionnaire item and of the answers it accepts
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "async-graphql", derive(async_graphql::Enum))]
+#[serde(rename_all = "camelCase")]
+pub enum ItemType {
+    /// Container of nested items, no answer
+    Group,
+    /// Text shown to the user, no answer
+    Display,
+    Boolean,
+    Decimal,
+    Integer,
+    Date,
+    DateTime,
+    String,
+    /// Multi-line free text
+    Text,
+    /// One of the answer options
+    Choice,
+    /// One of the answer options or free text
+    OpenChoice,
+    Quantity,
+}
+
+impl ItemType {
+    /// Whether items of this type take answers.
+    pub fn is_question(&self) -> bool {
+        !matches!(self, ItemType::Group | ItemType::Display)
+    }
+
+    /// Whether `value` is an acceptable answer type for the item.
+    pub fn accepts(&self, value: &AnswerValue) -> bool {
+        matches!(
+            (self, value),
+            (ItemType::Boolean, AnswerValue::Boolean(_))
+                | (ItemType::Decimal, AnswerValue::Decimal(_) | AnswerValue::Integer(_))
+                | (ItemType::Integer, AnswerValue::Integer(_))
+                | (ItemType::Date, AnswerValue::Date(_))
+                | (ItemType::DateTime, AnswerValue::DateTime(_) | AnswerValue::Date(_))
+                | (ItemType::String | ItemType::Text, AnswerValue::String(_))
+                | (ItemType::Choice, AnswerValue::Coding(_) | AnswerValue::String(_) | AnswerValue::Integer(_))
+                | (ItemType::OpenChoice, AnswerValue::Coding(_) | AnswerValue::String(_) | AnswerValue::Integer(_))
+                | (ItemType::Quantity, AnswerValue::Quantity(_))
+        )
+    }
+}
+
+/// Typed answer value
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub enum AnswerValue {
+    #[serde(rename = "valueBoolean", alias = "value_boolean")]
+    Boolean(bool),
+    #[serde(rename = "valueDecimal", alias = "value_decimal")]
+    Decimal(f64),
+    #[serde(rename = "valueInteger", alias = "value_integer")]
+    Integer(i64),
+    #[serde(rename = "valueDate", alias = "value_date")]
+    Date(FlexibleDate),
+    #[serde(rename = "valueDateTime", alias = "value_date_time")]
+    DateTime(FlexibleDateTime),
+    #[serde(rename = "valueString", alias = "value_string")]
+    String(String),
+    #[serde(rename = "valueCoding", alias = "value_coding")]
+    Coding(Coding),
+    #[serde(rename = "valueQuantity", alias = "value_quantity")]
+    Quantity(Quantity),
+}
+
+#[cfg(feature = "async-graphql")]
+async_graphql::scalar!(
+    AnswerValue,
+    "AnswerValue",
+    "Typed answer as JSON, e.g. {\"valueInteger\": 2} or {\"valueCoding\": {...}}"
+);
+
+impl AnswerValue {
+    /// Whether two answers are the same; codings match on system and code.
+    pub fn matches(&self, other: &AnswerValue) -> bool {
+        match (self, other) {
+            (AnswerValue::Coding(a), AnswerValue::Coding(b)) => a.system == b.system && a.code == b.code,
+            _ => self.compare(other) == Some(Ordering::Equal),
+        }
+    }
+
+    /// Order of two answers of comparable types (numbers, dates, strings,
+    /// quantities in commensurable units).
+    pub fn compare(&self, other: &AnswerValue) -> Option<Ordering> {
+        use AnswerValue::*;
+        match (self, other) {
+            (Boolean(a), Boolean(b)) => Some(a.cmp(b)),
+            (Integer(a), Integer(b)) => Some(a.cmp(b)),
+            (Decimal(a), Decimal(b)) => a.partial_cmp(b),
+            (Integer(a), Decimal(b)) => (*a as f64).partial_cmp(b),
+            (Decimal(a), Integer(b)) => a.partial_cmp(&(*b as f64)),
+            (Date(a), Date(b)) => Some(a.cmp(b)),
+            (DateTime(a), DateTime(b)) => Some(a.to_utc().cmp(&b.to_utc())),
+            (String(a), String(b)) => Some(a.cmp(b)),
+            (Coding(a), Coding(b)) => (a.system == b.system && a.code == b.code).then_some(Ordering::Equal),
+            (Quantity(a), Quantity(b)) => a.compare(b),
+            _ => None,
+        }
+    }
+}
+
+/// Allowed answer of a choice item.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "AnswerOptionInput")
+)]
+pub struct AnswerOption {
+    /// Answer value
+    pub value: AnswerValue,
+    /// Whether the option is selected by default
+    #[serde(rename = "initialSelected", alias = "initial_selected", skip_serializing_if = "Option::is_none")]
+    pub initial_selected: Option<bool>,
+    /// Points the option adds to the questionnaire score
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score: Option<f64>,
+}
+
+/// Comparison of an `enableWhen` condition
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "async-graphql", derive(async_graphql::Enum))]
+pub enum EnableWhenOperator {
+    /// The question has (answer `true`) or lacks (answer `false`) an answer
+    #[serde(rename = "exists")]
+    Exists,
+    #[serde(rename = "=")]
+    Equal,
+    #[serde(rename = "!=")]
+    NotEqual,
+    #[serde(rename = ">")]
+    Greater,
+    #[serde(rename = "<")]
+    Less,
+    #[serde(rename = ">=")]
+    GreaterOrEqual,
+    #[serde(rename = "<=")]
+    LessOrEqual,
+}
+
+/// Condition on another question's answer that enables an item.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "EnableWhenInput")
+)]
+pub struct EnableWhen {
+    /// `linkId` of the question the condition depends on
+    pub question: String,
+    /// Comparison to apply
+    pub operator: EnableWhenOperator,
+    /// Value compared with the answer (a boolean for `exists`)
+    pub answer: AnswerValue,
+}
+
+impl EnableWhen {
+    /// Whether the condition holds for the given answers of its question.
+    pub fn holds(&self, answers: &[AnswerValue]) -> bool {
+        use EnableWhenOperator::*;
+        match self.operator {
+            Exists => matches!(self.answer, AnswerValue::Boolean(expected) if expected != answers.is_empty()),
+            NotEqual => !answers.iter().any(|answer| answer.matches(&self.answer)),
+            Equal => answers.iter().any(|answer| answer.matches(&self.answer)),
+            Greater | Less | GreaterOrEqual | LessOrEqual => answers.iter().any(|answer| {
+                let ordering = answer.compare(&self.answer);
+                match self.operator {
+                    Greater => ordering == Some(Ordering::Greater),
+                    Less => ordering == Some(Ordering::Less),
+                    GreaterOrEqual => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+                    _ => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+                }
+            }),
+        }
+    }
+}
+
+/// How multiple `enableWhen` conditions combine
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "async-graphql", derive(async_graphql::Enum))]
+#[serde(rename_all = "lowercase")]
+pub enum EnableBehavior {
+    #[default]
+    All,
+    Any,
+}
+
+/// Question, group or display text of a questionnaire.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "QuestionnaireItemInput")
+)]
+pub struct QuestionnaireItem {
+    /// Identifier of the item, unique within the questionnaire
+    #[serde(rename = "linkId", alias = "link_id")]
+    pub link_id: String,
+    /// Question or display text
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    /// Item type
+    #[serde(rename = "type")]
+    pub r#type: ItemType,
+    /// Terminology codes of the question (e.g., LOINC)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<Vec<Coding>>,
+    /// Whether an answer is required
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub required: Option<bool>,
+    /// Whether the item may be answered more than once
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repeats: Option<bool>,
+    /// Allowed answers of choice items
+    #[serde(rename = "answerOption", alias = "answer_option", skip_serializing_if = "Option::is_none")]
+    pub answer_option: Option<Vec<AnswerOption>>,
+    /// Conditions under which the item is shown
+    #[serde(rename = "enableWhen", alias = "enable_when", skip_serializing_if = "Option::is_none")]
+    pub enable_when: Option<Vec<EnableWhen>>,
+    /// How `enableWhen` conditions combine (default: all)
+    #[serde(rename = "enableBehavior", alias = "enable_behavior", skip_serializing_if = "Option::is_none")]
+    pub enable_behavior: Option<EnableBehavior>,
+    /// Nested items
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "utoipa", schema(no_recursion))]
+    pub item: Option<Vec<QuestionnaireItem>>,
+}
+
+impl QuestionnaireItem {
+    /// Whether the item is enabled given the answers in `response`.
+    pub fn is_enabled(&self, response: &QuestionnaireResponse) -> bool {
+        let Some(conditions) = self.enable_when.as_deref().filter(|c| !c.is_empty()) else {
+            return true;
+        };
+        let holds = |condition: &EnableWhen| condition.holds(&response.answers(&condition.question));
+        match self.enable_behavior.unwrap_or_default() {
+            EnableBehavior::All => conditions.iter().all(holds),
+            EnableBehavior::Any => conditions.iter().any(holds),
+        }
+    }
+
+    /// The item followed by all its nested items, depth first.
+    pub fn flatten(&self) -> Vec<&QuestionnaireItem> {
+        let mut items = alloc::vec![self];
+        for child in self.item.iter().flatten() {
+            items.extend(child.flatten());
+        }
+        items
+    }
+}
+
+/// Form definition.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "QuestionnaireInput")
+)]
+pub struct Questionnaire {
+    /// Unique questionnaire identifier
+    pub id: String,
+    /// Canonical URL responses refer to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// Version of the definition
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// Human-readable title
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Publication status
+    pub status: QuestionnaireStatus,
+    /// Codes of the whole instrument (e.g., LOINC panel)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<Vec<Coding>>,
+    /// Top-level items
+    pub item: Vec<QuestionnaireItem>,
+    /// Schema version the resource was written against (e.g., "0.1.0"); absent means 0.1.0
+    #[serde(rename = "schemaVersion", alias = "schema_version", skip_serializing_if = "Option::is_none")]
+    pub schema_version: Option<String>,
+    /// Confidentiality and sensitivity labels (e.g., v3 ActCode HIV); see [`crate::security`]
+    #[serde(rename = "securityLabels", alias = "security_labels", skip_serializing_if = "Option::is_none")]
+    pub security_labels: Option<Vec<Coding>>,
+    /// Site-specific extensions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extensions: Option<Vec<Extension>>,
+    /// Properties this model version does not define, kept for re-serialization
+    #[cfg(feature = "preserve-unknown")]
+    #[serde(flatten)]
+    #[cfg_attr(feature = "schemars", schemars(skip))]
+    #[cfg_attr(feature = "utoipa", schema(ignore))]
+    #[cfg_attr(feature = "async-graphql", graphql(skip))]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Questionnaire {
+    /// Every item, nested ones included, depth first.
+    pub fn items(&self) -> Vec<&QuestionnaireItem> {
+        self.item.iter().flat_map(QuestionnaireItem::flatten).collect()
+    }
+
+    /// Item with the given `linkId`.
+    pub fn find(&self, link_id: &str) -> Option<&QuestionnaireItem> {
+        self.items().into_iter().find(|item| item.link_id == link_id)
+    }
+
+    /// Whether a response refers to this questionnaire by URL or id.
+    pub fn is_answered_by(&self, response: &QuestionnaireResponse) -> bool {
+        response.questionnaire == self.id || self.url.as_deref() == Some(response.questionnaire.as_str())
+    }
+}
+
+/// Progress of a response
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "async-graphql", derive(async_graphql::Enum))]
+#[serde(rename_all = "kebab-case")]
+pub enum ResponseStatus {
+    InProgress,
+    Completed,
+    Amended,
+    Stopped,
+}
+
+/// Answers to one questionnaire item.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "ResponseItemInput")
+)]
+pub struct ResponseItem {
+    /// `linkId` of the questionnaire item
+    #[serde(rename = "linkId", alias = "link_id")]
+    pub link_id: String,
+    /// Question text as shown
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    /// Answers given
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub answer: Option<Vec<AnswerValue>>,
+    /// Answers to nested items
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "utoipa", schema(no_recursion))]
+    pub item: Option<Vec<ResponseItem>>,
+}
+
+impl ResponseItem {
+    fn flatten<'a>(&'a self, items: &mut Vec<&'a ResponseItem>) {
+        items.push(self);
+        for child in self.item.iter().flatten() {
+            child.flatten(items);
+        }
+    }
+}
+
+/// Answers of one person to a questionnaire.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "QuestionnaireResponseInput")
+)]
+pub struct QuestionnaireResponse {
+    /// Unique response identifier
+    pub id: String,
+    /// Reference to Person.id
+    #[serde(rename = "patientId", alias = "patient_id")]
+    pub patient_id: String,
+    /// Canonical URL or id of the questionnaire
+    pub questionnaire: String,
+    /// Progress of the response
+    pub status: ResponseStatus,
+    /// When the answers were recorded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "utoipa", schema(value_type = Option<String>))]
+    pub authored: Option<FlexibleDateTime>,
+    /// Answered items
+    pub item: Vec<ResponseItem>,
+    /// Schema version the resource was written against (e.g., "0.1.0"); absent means 0.1.0
+    #[serde(rename = "schemaVersion", alias = "schema_version", skip_serializing_if = "Option::is_none")]
+    pub schema_version: Option<String>,
+    /// Confidentiality and sensitivity labels (e.g., v3 ActCode HIV); see [`crate::security`]
+    #[serde(rename = "securityLabels", alias = "security_labels", skip_serializing_if = "Option::is_none")]
+    pub security_labels: Option<Vec<Coding>>,
+    /// Site-specific extensions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extensions: Option<Vec<Extension>>,
+    /// Properties this model version does not define, kept for re-serialization
+    #[cfg(feature = "preserve-unknown")]
+    #[serde(flatten)]
+    #[cfg_attr(feature = "schemars", schemars(skip))]
+    #[cfg_attr(feature = "utoipa", schema(ignore))]
+    #[cfg_attr(feature = "async-graphql", graphql(skip))]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl QuestionnaireResponse {
+    /// Every answered item, nested ones included, depth first.
+    pub fn items(&self) -> Vec<&ResponseItem> {
+        let mut items = Vec::new();
+        for item in &self.item {
+            item.flatten(&mut items);
+        }
+        items
+    }
+
+    /// All answers given to the item with `link_id`.
+    pub fn answers(&self, link_id: &str) -> Vec<AnswerValue> {
+        self.items()
+            .into_iter()
+            .filter(|item| item.link_id == link_id)
+            .flat_map(|item| item.answer.iter().flatten().cloned())
+            .collect()
+    }
+
+    /// Checks the answers against the questionnaire definition: every
+    /// `linkId` must exist, answers must match the item type and answer
+    /// options, only repeating items may have several answers, and required
+    /// enabled questions must be answered once the response is completed.
+    pub fn validate_against(&self, questionnaire: &Questionnaire) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+        if !questionnaire.is_answered_by(self) {
+            issues.push(ValidationIssue::new(
+                "questionnaire",
+                format!("does not refer to questionnaire '{}'", questionnaire.id),
+            ));
+        }
+        for (i, item) in self.item.iter().enumerate() {
+            check_answers(&mut issues, &index("item", i), item, questionnaire);
+        }
+        if self.status == ResponseStatus::Completed {
+            for item in questionnaire.items() {
+                let required = item.required == Some(true) && item.r#type.is_question();
+                if required && item.is_enabled(self) && self.answers(&item.link_id).is_empty() {
+                    issues.push(ValidationIssue::new(
+                        "item",
+                        format!("required question '{}' is not answered", item.link_id),
+                    ));
+                }
+            }
+        }
+        issues
+    }
+}
+
+fn check_answers(issues: &mut Vec<ValidationIssue>, path: &str, item: &ResponseItem, questionnaire: &Questionnaire) {
+    let Some(definition) = questionnaire.find(&item.link_id) else {
+        issues.push(ValidationIssue::new(join(path, "linkId"), format!("unknown item '{}'", item.link_id)));
+        return;
+    };
+    let answers = item.answer.as_deref().unwrap_or_default();
+    if !definition.r#type.is_question() && !answers.is_empty() {
+        issues.push(ValidationIssue::new(join(path, "answer"), "must be empty for group and display items"));
+    } else if answers.len() > 1 && definition.repeats != Some(true) {
+        issues.push(ValidationIssue::new(join(path, "answer"), "must have at most one answer"));
+    }
+    for (i, answer) in answers.iter().enumerate() {
+        let answer_path = index(&join(path, "answer"), i);
+        if definition.r#type.is_question() && !definition.r#type.accepts(answer) {
+            issues.push(ValidationIssue::new(answer_path, "does not match the item type"));
+            continue;
+        }
+        let open_text = definition.r#type == ItemType::OpenChoice && matches!(answer, AnswerValue::String(_));
+        if let Some(options) = definition.answer_option.as_deref().filter(|_| !open_text) {
+            if !options.iter().any(|option| option.value.matches(answer)) {
+                issues.push(ValidationIssue::new(answer_path, "is not one of the answer options"));
+            }
+        }
+    }
+    for (i, child) in item.item.iter().flatten().enumerate() {
+        check_answers(issues, &index(&join(path, "item"), i), child, questionnaire);
+    }
+}
+
+/// Instrument-specific scoring of a response.
+pub trait ScoringRule {
+    /// Score of `response` to `questionnaire`, or `None` if it cannot be scored
+    /// (e.g., too many unanswered items).
+    fn score(&self, questionnaire: &Questionnaire, response: &QuestionnaireResponse) -> Option<f64>;
+}
+
+/// Sums the `score` of the answer option chosen for every enabled question;
+/// `None` if a scored question is unanswered.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SumOfOptionScores;
+
+impl ScoringRule for SumOfOptionScores {
+    fn score(&self, questionnaire: &Questionnaire, response: &QuestionnaireResponse) -> Option<f64> {
+        let mut total = 0.0;
+        for item in questionnaire.items() {
+            let options = item.answer_option.iter().flatten();
+            if !options.clone().any(|option| option.score.is_some()) || !item.is_enabled(response) {
+                continue;
+            }
+            let answers = response.answers(&item.link_id);
+            if answers.is_empty() {
+                return None;
+            }
+            for answer in &answers {
+                let option = options.clone().find(|option| option.value.matches(answer))?;
+                total += option.score.unwrap_or(0.0);
+            }
+        }
+        Some(total)
+    }
+}
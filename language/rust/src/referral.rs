@@ -0,0 +1,148 @@
+//! Referral (transition of care) data model.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//! Schema: https://wellall.health/schemas/referral/v0.1.0
+//!
+//! A [`Referral`] is the document that moves a patient's care from one
+//! provider to another, pointing at the lab and imaging reports that support
+//! it. Its status follows a fixed lifecycle; [`Referral::set_status`] rejects
+//! transitions the lifecycle does not allow.
+
+use alloc::{string::String, vec::Vec};
+use serde::{Deserialize, Serialize};
+
+use crate::common::{CodeableConcept, Reference, RequestPriority, Coding};
+use crate::datetime::FlexibleDateTime;
+use crate::error::{self, WellAllyError};
+use crate::imaging_report::Performer;
+use crate::lab_report::Facility;
+use crate::extension::Extension;
+
+/// Lifecycle status of a referral
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "async-graphql", derive(async_graphql::Enum))]
+#[serde(rename_all = "kebab-case")]
+pub enum ReferralStatus {
+    /// Being prepared, not yet sent
+    Draft,
+    /// Sent and awaiting or in progress at the recipient
+    Active,
+    /// Temporarily suspended
+    OnHold,
+    /// Withdrawn by the referrer
+    Revoked,
+    /// The referred care has been provided
+    Completed,
+    /// Created by mistake
+    EnteredInError,
+}
+
+impl ReferralStatus {
+    /// Whether a referral may move from this status to `next`.
+    pub fn can_transition_to(&self, next: ReferralStatus) -> bool {
+        use ReferralStatus::*;
+        match (self, next) {
+            (_, EnteredInError) => *self != EnteredInError,
+            (Draft, Active | Revoked) => true,
+            (Active, OnHold | Completed | Revoked) => true,
+            (OnHold, Active | Revoked) => true,
+            _ => false,
+        }
+    }
+
+    /// Whether no further transition (other than to entered-in-error) is allowed.
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, ReferralStatus::Revoked | ReferralStatus::Completed | ReferralStatus::EnteredInError)
+    }
+}
+
+/// Practitioner and/or organization on one side of a referral.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "ReferralPartyInput")
+)]
+pub struct ReferralParty {
+    /// Practitioner
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub practitioner: Option<Performer>,
+    /// Organization
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub organization: Option<Facility>,
+}
+
+/// Referral of a patient from one provider to another.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "ReferralInput")
+)]
+pub struct Referral {
+    /// Unique referral identifier
+    pub id: String,
+    /// Reference to Person.id
+    #[serde(rename = "patientId", alias = "patient_id")]
+    pub patient_id: String,
+    /// Lifecycle status
+    pub status: ReferralStatus,
+    /// Urgency
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub priority: Option<RequestPriority>,
+    /// When the referral was written
+    #[serde(rename = "authoredOn", alias = "authored_on")]
+    #[cfg_attr(feature = "utoipa", schema(value_type = String))]
+    pub authored_on: FlexibleDateTime,
+    /// Referring practitioner/organization
+    pub requester: ReferralParty,
+    /// Receiving practitioner/organization, once known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recipient: Option<ReferralParty>,
+    /// Requested service or specialty (e.g., SNOMED CT "Referral to cardiologist")
+    #[serde(rename = "serviceRequested", alias = "service_requested", skip_serializing_if = "Option::is_none")]
+    pub service_requested: Option<CodeableConcept>,
+    /// Reasons for the referral
+    #[serde(rename = "reasonCode", alias = "reason_code", skip_serializing_if = "Option::is_none")]
+    pub reason_code: Option<Vec<CodeableConcept>>,
+    /// Reports and records supporting the referral
+    #[serde(rename = "supportingInfo", alias = "supporting_info", skip_serializing_if = "Option::is_none")]
+    pub supporting_info: Option<Vec<Reference>>,
+    /// Clinical question or summary for the recipient
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Schema version the resource was written against (e.g., "0.1.0"); absent means 0.1.0
+    #[serde(rename = "schemaVersion", alias = "schema_version", skip_serializing_if = "Option::is_none")]
+    pub schema_version: Option<String>,
+    /// Confidentiality and sensitivity labels (e.g., v3 ActCode HIV); see [`crate::security`]
+    #[serde(rename = "securityLabels", alias = "security_labels", skip_serializing_if = "Option::is_none")]
+    pub security_labels: Option<Vec<Coding>>,
+    /// Site-specific extensions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extensions: Option<Vec<Extension>>,
+    /// Properties this model version does not define, kept for re-serialization
+    #[cfg(feature = "preserve-unknown")]
+    #[serde(flatten)]
+    #[cfg_attr(feature = "schemars", schemars(skip))]
+    #[cfg_attr(feature = "utoipa", schema(ignore))]
+    #[cfg_attr(feature = "async-graphql", graphql(skip))]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Referral {
+    /// Moves the referral to `next`, failing if the lifecycle does not allow it.
+    pub fn set_status(&mut self, next: ReferralStatus) -> Result<(), WellAllyError> {
+        if !self.status.can_transition_to(next) {
+            return Err(error::transition_error("Referral", self.status, next));
+        }
+        self.status = next;
+        Ok(())
+    }
+}
@@ -0,0 +1,257 @@
+//! Rehabilitation and physiotherapy session model.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//! Schema: https://wellall.health/schemas/rehab/v0.1.0
+//!
+//! A [`RehabSession`] records one physiotherapy, occupational therapy or
+//! other rehabilitation visit: the therapy given, the exercises with their
+//! sets, repetitions and resistance, pain on a 0–10 numeric rating scale
+//! before and after, and functional assessment scores such as the Lower
+//! Extremity Functional Scale or the Oswestry Disability Index.
+//! [`score_series`] follows one score across sessions to track recovery.
+//!
+//! ```
+//! use wellally::catalog::SNOMED_CT;
+//! use wellally::rehab::{Exercise, RehabSession};
+//! use wellally::Coding;
+//!
+//! let physiotherapy = Coding { system: SNOMED_CT.into(), code: "91251008".into(), display: None };
+//! let mut session = RehabSession::new("rehab-1", "p-1", physiotherapy, "2024-10-01T09:00:00Z".parse().unwrap());
+//! session.exercises.push(Exercise::new("Straight leg raise").with_sets(3, 12));
+//! session.pain_before = Some(6);
+//! session.pain_after = Some(4);
+//! assert_eq!(session.pain_change(), Some(-2));
+//! ```
+
+use alloc::{string::String, vec::Vec};
+
+use serde::{Deserialize, Serialize};
+
+use crate::common::{Annotation, BodySite, CodeableConcept, Coding, DataSource, HasDataSource, Quantity, Reference};
+use crate::datetime::FlexibleDateTime;
+use crate::extension::Extension;
+use crate::ucum;
+
+/// One exercise of a session.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "ExerciseInput")
+)]
+pub struct Exercise {
+    /// Exercise name as prescribed (e.g., "Straight leg raise")
+    pub name: String,
+    /// Coded exercise, when the program uses a terminology
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<Coding>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sets: Option<u32>,
+    /// Repetitions per set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repetitions: Option<u32>,
+    /// Load or resistance (e.g., 5 kg, or a band level in `{band}`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resistance: Option<Quantity>,
+    /// Time per set for timed exercises (e.g., holds, cycling), in seconds
+    #[serde(rename = "durationSeconds", alias = "duration_seconds", skip_serializing_if = "Option::is_none")]
+    pub duration_seconds: Option<u32>,
+}
+
+impl Exercise {
+    /// Exercise with a name only.
+    pub fn new(name: impl Into<String>) -> Self {
+        Exercise {
+            name: name.into(),
+            code: None,
+            sets: None,
+            repetitions: None,
+            resistance: None,
+            duration_seconds: None,
+        }
+    }
+
+    /// The exercise done in `sets` sets of `repetitions`.
+    pub fn with_sets(mut self, sets: u32, repetitions: u32) -> Self {
+        self.sets = Some(sets);
+        self.repetitions = Some(repetitions);
+        self
+    }
+
+    /// The exercise against `resistance`.
+    pub fn with_resistance(mut self, resistance: Quantity) -> Self {
+        self.resistance = Some(resistance);
+        self
+    }
+
+    /// Training volume in kg: sets × repetitions × load; `None` unless the
+    /// resistance is a mass.
+    pub fn volume_kg(&self) -> Option<f64> {
+        let resistance = self.resistance.as_ref()?;
+        let load = ucum::convert(resistance.value, &resistance.unit, "kg").ok()?;
+        Some(f64::from(self.sets?) * f64::from(self.repetitions?) * load)
+    }
+}
+
+/// Result of a functional assessment instrument.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "FunctionalScoreInput")
+)]
+pub struct FunctionalScore {
+    /// Instrument (e.g., LOINC-coded LEFS total score, Oswestry Disability Index)
+    pub instrument: CodeableConcept,
+    pub score: f64,
+    /// Highest possible score, to compare instruments on a common scale
+    #[serde(rename = "maxScore", alias = "max_score", skip_serializing_if = "Option::is_none")]
+    pub max_score: Option<f64>,
+}
+
+/// One rehabilitation session.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "RehabSessionInput")
+)]
+pub struct RehabSession {
+    /// Unique session identifier
+    pub id: String,
+    /// Reference to Person.id
+    #[serde(rename = "patientId", alias = "patient_id")]
+    pub patient_id: String,
+    /// Therapy given (e.g., SNOMED CT physiotherapy, occupational therapy)
+    #[serde(rename = "therapyType", alias = "therapy_type")]
+    pub therapy_type: Coding,
+    /// When the session started
+    #[cfg_attr(feature = "utoipa", schema(value_type = String))]
+    pub start: FlexibleDateTime,
+    /// Length of the session in minutes
+    #[serde(rename = "durationMinutes", alias = "duration_minutes", skip_serializing_if = "Option::is_none")]
+    pub duration_minutes: Option<u32>,
+    /// Body site treated (e.g., SNOMED CT knee region, left)
+    #[serde(rename = "bodySite", alias = "body_site", skip_serializing_if = "Option::is_none")]
+    pub body_site: Option<BodySite>,
+    /// Condition being rehabilitated (e.g., "Condition/acl-tear")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub condition: Option<Reference>,
+    /// Therapist who ran the session (e.g., "Practitioner/pt-wang")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub therapist: Option<Reference>,
+    /// Exercises done, in order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exercises: Vec<Exercise>,
+    /// Pain before the session on the 0–10 numeric rating scale
+    #[serde(rename = "painBefore", alias = "pain_before", skip_serializing_if = "Option::is_none")]
+    pub pain_before: Option<u8>,
+    /// Pain after the session on the 0–10 numeric rating scale
+    #[serde(rename = "painAfter", alias = "pain_after", skip_serializing_if = "Option::is_none")]
+    pub pain_after: Option<u8>,
+    /// Functional assessment scores taken during the session
+    #[serde(rename = "functionalScores", alias = "functional_scores", skip_serializing_if = "Option::is_none")]
+    pub functional_scores: Option<Vec<FunctionalScore>>,
+    /// Therapist notes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notes: Option<Vec<Annotation>>,
+    /// Device, app and capture method of the record
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<DataSource>,
+    /// Schema version the resource was written against (e.g., "0.1.0"); absent means 0.1.0
+    #[serde(rename = "schemaVersion", alias = "schema_version", skip_serializing_if = "Option::is_none")]
+    pub schema_version: Option<String>,
+    /// Confidentiality and sensitivity labels (e.g., v3 ActCode HIV); see [`crate::security`]
+    #[serde(rename = "securityLabels", alias = "security_labels", skip_serializing_if = "Option::is_none")]
+    pub security_labels: Option<Vec<Coding>>,
+    /// Site-specific extensions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extensions: Option<Vec<Extension>>,
+    /// Properties this model version does not define, kept for re-serialization
+    #[cfg(feature = "preserve-unknown")]
+    #[serde(flatten)]
+    #[cfg_attr(feature = "schemars", schemars(skip))]
+    #[cfg_attr(feature = "utoipa", schema(ignore))]
+    #[cfg_attr(feature = "async-graphql", graphql(skip))]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl RehabSession {
+    /// Session without exercises or scores yet.
+    pub fn new(
+        id: impl Into<String>,
+        patient_id: impl Into<String>,
+        therapy_type: Coding,
+        start: FlexibleDateTime,
+    ) -> Self {
+        RehabSession {
+            id: id.into(),
+            patient_id: patient_id.into(),
+            therapy_type,
+            start,
+            duration_minutes: None,
+            body_site: None,
+            condition: None,
+            therapist: None,
+            exercises: Vec::new(),
+            pain_before: None,
+            pain_after: None,
+            functional_scores: None,
+            notes: None,
+            source: None,
+            schema_version: None,
+            security_labels: None,
+            extensions: None,
+            #[cfg(feature = "preserve-unknown")]
+            extra: Default::default(),
+        }
+    }
+
+    /// Pain after the session minus pain before; negative when it eased.
+    pub fn pain_change(&self) -> Option<i16> {
+        Some(i16::from(self.pain_after?) - i16::from(self.pain_before?))
+    }
+
+    /// Total training volume in kg of the exercises done against a mass.
+    pub fn volume_kg(&self) -> f64 {
+        self.exercises.iter().filter_map(Exercise::volume_kg).sum()
+    }
+
+    /// Score of the instrument coded `code`, if assessed.
+    pub fn score(&self, code: &str) -> Option<&FunctionalScore> {
+        self.functional_scores
+            .iter()
+            .flatten()
+            .find(|score| score.instrument.coding.iter().any(|coding| coding.code == code))
+    }
+}
+
+impl HasDataSource for RehabSession {
+    fn data_source(&self) -> Option<&DataSource> {
+        self.source.as_ref()
+    }
+}
+
+/// A functional score at one session.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScorePoint<'a> {
+    pub session: &'a RehabSession,
+    pub score: &'a FunctionalScore,
+}
+
+/// Scores of the instrument coded `code` across `sessions`, oldest first.
+pub fn score_series<'a>(sessions: &'a [RehabSession], code: &str) -> Vec<ScorePoint<'a>> {
+    let mut points: Vec<ScorePoint<'a>> = sessions
+        .iter()
+        .filter_map(|session| Some(ScorePoint { session, score: session.score(code)? }))
+        .collect();
+    points.sort_by_key(|point| point.session.start.to_utc());
+    points
+}
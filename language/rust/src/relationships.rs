@@ -0,0 +1,168 @@
+//! Related persons: caregivers, guardians and proxies who act for a patient.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//! Schema: https://wellall.health/schemas/related-person/v0.1.0
+//!
+//! A [`RelatedPerson`] is someone other than the patient who has a role in
+//! the patient's care: a parent or guardian of a child, a spouse, a paid
+//! caregiver, or a healthcare proxy of an older adult. Besides how to reach
+//! them, the record carries the SMART scopes ([`crate::authz::Scope`]) they
+//! hold on the patient's record and the period the relationship is in
+//! force, so services can check what a family member signed in with their
+//! own account may do.
+//!
+//! ```
+//! use wellally::authz::Action;
+//! use wellally::relationships::{self, RelatedPerson, RelationshipRole};
+//!
+//! let mut mother = RelatedPerson::new("rp-1", "child-1", RelationshipRole::Guardian);
+//! mother.person_id = Some("person-mother".into());
+//! mother.permissions = vec!["patient/*.read".parse()?, "patient/ImmunizationRecord.cu".parse()?];
+//! let today = "2024-09-01".parse().unwrap();
+//! assert!(mother.permits("ImmunizationRecord", Action::Create, today));
+//! assert!(!mother.permits("LabReport", Action::Delete, today));
+//!
+//! let links = [mother];
+//! let managed = relationships::acting_for(&links, "person-mother", today);
+//! assert_eq!(managed[0].patient_id, "child-1");
+//! # Ok::<(), wellally::WellAllyError>(())
+//! ```
+
+use alloc::{string::String, vec::Vec};
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::authz::{Action, Authorization, Scope};
+use crate::common::{Address, Annotation, Coding, ContactPoint, HumanName, Period};
+use crate::extension::Extension;
+
+/// Role a related person has toward the patient.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "async-graphql", derive(async_graphql::Enum))]
+#[serde(rename_all = "kebab-case")]
+pub enum RelationshipRole {
+    /// Looks after the patient day to day, paid or not
+    Caregiver,
+    /// Legal guardian, including the parent of a minor
+    Guardian,
+    Spouse,
+    Parent,
+    Child,
+    Sibling,
+    /// Named to make healthcare decisions when the patient cannot
+    HealthcareProxy,
+    /// Person to call in an emergency
+    EmergencyContact,
+    Other,
+}
+
+/// Someone who has a role in a patient's care.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "RelatedPersonInput")
+)]
+pub struct RelatedPerson {
+    /// Unique record identifier
+    pub id: String,
+    /// Reference to Person.id of the patient
+    #[serde(rename = "patientId", alias = "patient_id")]
+    pub patient_id: String,
+    /// Roles toward the patient (e.g., spouse and healthcare proxy)
+    pub relationship: Vec<RelationshipRole>,
+    /// Reference to Person.id of the related person, when they have their own record or account
+    #[serde(rename = "personId", alias = "person_id", skip_serializing_if = "Option::is_none")]
+    pub person_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<HumanName>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub telecom: Option<Vec<ContactPoint>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<Address>,
+    /// Period the relationship is in force (e.g., until a child turns 18)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub period: Option<Period>,
+    /// SMART `patient` scopes the related person holds on the patient's record
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[cfg_attr(feature = "utoipa", schema(value_type = Vec<String>))]
+    pub permissions: Vec<Scope>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notes: Option<Vec<Annotation>>,
+    /// Schema version the resource was written against (e.g., "0.1.0"); absent means 0.1.0
+    #[serde(rename = "schemaVersion", alias = "schema_version", skip_serializing_if = "Option::is_none")]
+    pub schema_version: Option<String>,
+    /// Confidentiality and sensitivity labels (e.g., v3 ActCode HIV); see [`crate::security`]
+    #[serde(rename = "securityLabels", alias = "security_labels", skip_serializing_if = "Option::is_none")]
+    pub security_labels: Option<Vec<Coding>>,
+    /// Site-specific extensions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extensions: Option<Vec<Extension>>,
+    /// Properties this model version does not define, kept for re-serialization
+    #[cfg(feature = "preserve-unknown")]
+    #[serde(flatten)]
+    #[cfg_attr(feature = "schemars", schemars(skip))]
+    #[cfg_attr(feature = "utoipa", schema(ignore))]
+    #[cfg_attr(feature = "async-graphql", graphql(skip))]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl RelatedPerson {
+    /// Related person with a single role, no contact details and no permissions.
+    pub fn new(id: impl Into<String>, patient_id: impl Into<String>, role: RelationshipRole) -> Self {
+        RelatedPerson {
+            id: id.into(),
+            patient_id: patient_id.into(),
+            relationship: alloc::vec![role],
+            person_id: None,
+            name: None,
+            telecom: None,
+            address: None,
+            period: None,
+            permissions: Vec::new(),
+            notes: None,
+            schema_version: None,
+            security_labels: None,
+            extensions: None,
+            #[cfg(feature = "preserve-unknown")]
+            extra: Default::default(),
+        }
+    }
+
+    /// Whether the related person has `role`.
+    pub fn has_role(&self, role: RelationshipRole) -> bool {
+        self.relationship.contains(&role)
+    }
+
+    /// Whether the relationship is in force on `date`; no period means always.
+    pub fn is_active_on(&self, date: NaiveDate) -> bool {
+        self.period.as_ref().is_none_or(|period| period.contains(date))
+    }
+
+    /// The permissions as an [`Authorization`] launched for the patient.
+    pub fn authorization(&self) -> Authorization {
+        Authorization::new(self.permissions.clone()).with_patient(self.patient_id.clone())
+    }
+
+    /// Whether the related person may perform `action` on the patient's
+    /// `resource_type` resources on `date`.
+    pub fn permits(&self, resource_type: &str, action: Action, date: NaiveDate) -> bool {
+        self.is_active_on(date) && self.authorization().permits(resource_type, action, &self.patient_id)
+    }
+}
+
+/// Relationships through which the person `person_id` may act on someone
+/// else's record on `date`: in force, and holding at least one permission.
+pub fn acting_for<'a>(related: &'a [RelatedPerson], person_id: &str, date: NaiveDate) -> Vec<&'a RelatedPerson> {
+    related
+        .iter()
+        .filter(|link| link.person_id.as_deref() == Some(person_id))
+        .filter(|link| link.is_active_on(date) && !link.permissions.is_empty())
+        .collect()
+}
@@ -0,0 +1,559 @@
+//! Cohort selection and de-identified research exports.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//!
+//! A [`CohortBuilder`] screens the patients found in bundles against
+//! inclusion and exclusion [`Criterion`]s (diagnoses, lab thresholds,
+//! medication exposure, age at the index date), each over a time window, and
+//! exports the selected patients as a [`ResearchDataset`]: flat tables of
+//! patients, conditions, measurements and medications whose columns are
+//! scalars of fixed type, ready to be written as Parquet or CSV, and a
+//! [`Manifest`] recording the criteria, the attrition at each step and the
+//! table schemas.
+//!
+//! Patients are identified by salted pseudonyms (see [`Anonymizer`]), birth
+//! dates are reduced to the year, and no names, identifiers or free text are
+//! exported. Resources carrying a withheld security label are left out
+//! before screening.
+//!
+//! ```
+//! use wellally::io::{AnyResource, Bundle};
+//! use wellally::research::{CohortBuilder, Criterion};
+//! use wellally::{Coding, Period};
+//!
+//! let diabetes = Coding { system: "http://hl7.org/fhir/sid/icd-10".into(), code: "E11.9".into(), display: None };
+//! let condition: AnyResource = serde_json::from_value(serde_json::json!({
+//!     "id": "cond-1", "patientId": "p-1", "clinicalStatus": "active", "onset": "2023-04-02",
+//!     "code": {"coding": [{"system": "http://hl7.org/fhir/sid/icd-10", "code": "E11.9"}]}
+//! }))
+//! .unwrap();
+//! let year = Period { start: "2023-01-01".parse().ok(), end: "2023-12-31".parse().ok() };
+//! let dataset = CohortBuilder::new("study-salt")
+//!     .include(Criterion::condition([diabetes]))
+//!     .with_window(year)
+//!     .build(&[Bundle::new(vec![condition])]);
+//! assert_eq!(dataset.manifest.selected, 1);
+//! assert_eq!(dataset.conditions[0].code, "E11.9");
+//! assert_ne!(dataset.patients[0].person_id, "p-1");
+//! ```
+
+use std::collections::BTreeMap;
+
+use chrono::{NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::anonymize::Anonymizer;
+use crate::common::{Coding, Comparator, Period, Quantity};
+use crate::condition::{Condition, VerificationStatus};
+use crate::health::Person;
+use crate::io::{AnyResource, Bundle};
+use crate::lab_report::{LabReport, LabResult, LabValue};
+use crate::medication::MedicationRecord;
+use crate::security::same_label;
+use crate::ucum;
+
+/// Window of a criterion when neither it nor the cohort sets one
+const ALL_TIME: Period = Period { start: None, end: None };
+
+/// A condition a patient must meet to be included, or must not meet to stay.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "criterion", rename_all = "camelCase")]
+pub enum Criterion {
+    /// A diagnosis with one of `codes`, present at some time in the window
+    /// (from onset or recording to abatement). Refuted diagnoses and
+    /// diagnoses entered in error do not count.
+    Condition {
+        codes: Vec<Coding>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        window: Option<Period>,
+    },
+    /// A lab result coded `code` in the window whose value compares with
+    /// `threshold` as `comparator` says, after converting it to the
+    /// threshold's unit.
+    Lab {
+        code: Coding,
+        comparator: Comparator,
+        threshold: Quantity,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        window: Option<Period>,
+    },
+    /// A medication with one of `codes` taken on some day in the window.
+    Medication {
+        codes: Vec<Coding>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        window: Option<Period>,
+    },
+    /// Age in whole years at the index date, bounds inclusive.
+    Age {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        min: Option<u32>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        max: Option<u32>,
+    },
+}
+
+impl Criterion {
+    /// Diagnosis with one of `codes` in the cohort's window.
+    pub fn condition(codes: impl IntoIterator<Item = Coding>) -> Self {
+        Criterion::Condition { codes: codes.into_iter().collect(), window: None }
+    }
+
+    /// Lab result `code` comparing with `threshold` as `comparator` says, in the cohort's window.
+    pub fn lab(code: Coding, comparator: Comparator, threshold: Quantity) -> Self {
+        Criterion::Lab { code, comparator, threshold, window: None }
+    }
+
+    /// Exposure to a medication with one of `codes` in the cohort's window.
+    pub fn medication(codes: impl IntoIterator<Item = Coding>) -> Self {
+        Criterion::Medication { codes: codes.into_iter().collect(), window: None }
+    }
+
+    /// Age at the index date between `min` and `max` years, inclusive.
+    pub fn age(min: Option<u32>, max: Option<u32>) -> Self {
+        Criterion::Age { min, max }
+    }
+
+    /// Sets the window the criterion is evaluated over, instead of the cohort's.
+    pub fn within(mut self, period: Period) -> Self {
+        match &mut self {
+            Criterion::Condition { window, .. } | Criterion::Lab { window, .. } => *window = Some(period),
+            Criterion::Medication { window, .. } => *window = Some(period),
+            Criterion::Age { .. } => {}
+        }
+        self
+    }
+
+    fn matches(&self, patient: &Records<'_>, default_window: Option<&Period>, index_date: NaiveDate) -> bool {
+        let window = |own: &Option<Period>| own.as_ref().or(default_window).copied().unwrap_or(ALL_TIME);
+        match self {
+            Criterion::Condition { codes, window: own } => {
+                let window = window(own);
+                patient.conditions.iter().any(|condition| {
+                    condition.code.coding.iter().any(|coding| codes.iter().any(|code| same_label(code, coding)))
+                        && !matches!(
+                            condition.verification_status,
+                            Some(VerificationStatus::Refuted | VerificationStatus::EnteredInError)
+                        )
+                        && overlaps(condition_start(condition), condition_end(condition), &window)
+                })
+            }
+            Criterion::Lab { code, comparator, threshold, window: own } => {
+                let window = window(own);
+                patient.labs().any(|(report, result)| {
+                    result.code.coding.iter().any(|coding| same_label(code, coding))
+                        && window.contains(result_date(report, result))
+                        && value_in(result, &threshold.unit)
+                            .is_some_and(|value| compare(value, *comparator, threshold.value))
+                })
+            }
+            Criterion::Medication { codes, window: own } => {
+                let window = window(own);
+                patient.medications.iter().any(|medication| {
+                    codes.iter().any(|code| same_label(code, &medication.medication))
+                        && overlaps(Some(medication.start_date), medication.last_date(), &window)
+                })
+            }
+            Criterion::Age { min, max } => age_at(patient.person, index_date)
+                .is_some_and(|age| min.is_none_or(|min| age >= min) && max.is_none_or(|max| age <= max)),
+        }
+    }
+}
+
+/// Selects patients by criteria and exports them de-identified.
+#[derive(Debug, Clone)]
+pub struct CohortBuilder {
+    anonymizer: Anonymizer,
+    inclusion: Vec<Criterion>,
+    exclusion: Vec<Criterion>,
+    index_date: NaiveDate,
+    window: Option<Period>,
+}
+
+impl CohortBuilder {
+    /// Cohort of every patient, indexed today, with pseudonyms derived from `salt`.
+    pub fn new(salt: impl Into<String>) -> Self {
+        CohortBuilder {
+            anonymizer: Anonymizer::new(salt),
+            inclusion: Vec::new(),
+            exclusion: Vec::new(),
+            index_date: Utc::now().date_naive(),
+            window: None,
+        }
+    }
+
+    /// Adds a criterion every selected patient meets.
+    pub fn include(mut self, criterion: Criterion) -> Self {
+        self.inclusion.push(criterion);
+        self
+    }
+
+    /// Adds a criterion no selected patient meets.
+    pub fn exclude(mut self, criterion: Criterion) -> Self {
+        self.exclusion.push(criterion);
+        self
+    }
+
+    /// Sets the day ages are computed at.
+    pub fn with_index_date(mut self, index_date: NaiveDate) -> Self {
+        self.index_date = index_date;
+        self
+    }
+
+    /// Sets the study window: criteria without their own window are
+    /// evaluated over it, and only records overlapping it are exported.
+    pub fn with_window(mut self, window: Period) -> Self {
+        self.window = Some(window);
+        self
+    }
+
+    /// Leaves resources labeled with any of `labels` out of screening and export.
+    pub fn withholding(mut self, labels: impl IntoIterator<Item = Coding>) -> Self {
+        self.anonymizer = self.anonymizer.withholding(labels);
+        self
+    }
+
+    /// Screens the patients of `bundles` and exports the selected ones.
+    pub fn build(&self, bundles: &[Bundle]) -> ResearchDataset {
+        let mut patients: BTreeMap<&str, Records<'_>> = BTreeMap::new();
+        let resources = bundles.iter().flat_map(|bundle| &bundle.resources);
+        for resource in resources.filter(|resource| !self.anonymizer.is_withheld(resource)) {
+            let records = patients.entry(resource.patient_id()).or_default();
+            match resource {
+                AnyResource::Person(person) => records.person = Some(person),
+                AnyResource::Condition(condition) => records.conditions.push(condition),
+                AnyResource::LabReport(report) => records.lab_reports.push(report),
+                AnyResource::MedicationRecord(medication) => records.medications.push(medication),
+                _ => {}
+            }
+        }
+
+        let window = self.window.as_ref();
+        let screened = patients.len();
+        let mut remaining: Vec<_> = patients.into_iter().collect();
+        let mut attrition = Vec::new();
+        let steps = self.inclusion.iter().map(|criterion| (true, criterion));
+        let steps = steps.chain(self.exclusion.iter().map(|criterion| (false, criterion)));
+        for (i, (include, criterion)) in steps.enumerate() {
+            remaining.retain(|(_, records)| criterion.matches(records, window, self.index_date) == include);
+            let step = match include {
+                true => format!("inclusion[{}]", i),
+                false => format!("exclusion[{}]", i - self.inclusion.len()),
+            };
+            attrition.push(AttritionStep { step, remaining: remaining.len() });
+        }
+
+        let mut dataset = ResearchDataset {
+            manifest: Manifest {
+                index_date: self.index_date,
+                window: self.window,
+                inclusion: self.inclusion.clone(),
+                exclusion: self.exclusion.clone(),
+                screened,
+                selected: remaining.len(),
+                attrition,
+                tables: tables(),
+            },
+            patients: Vec::new(),
+            conditions: Vec::new(),
+            measurements: Vec::new(),
+            medications: Vec::new(),
+        };
+        for (patient_id, records) in &remaining {
+            self.export(&mut dataset, patient_id, records);
+        }
+        dataset
+    }
+
+    fn export(&self, dataset: &mut ResearchDataset, patient_id: &str, records: &Records<'_>) {
+        let person_id = self.anonymizer.pseudonym(patient_id);
+        let window = self.window.unwrap_or(ALL_TIME);
+        dataset.patients.push(PatientRow {
+            person_id: person_id.clone(),
+            birth_year: records.person.map(|person| person.birth_date.year()),
+            gender: records.person.and_then(|person| Some(serde_json::to_value(person.gender?).ok()?.as_str()?.into())),
+            age_at_index: age_at(records.person, self.index_date),
+        });
+        for condition in &records.conditions {
+            let (start, end) = (condition_start(condition), condition_end(condition));
+            let Some(coding) = condition.code.coding.first().filter(|_| overlaps(start, end, &window)) else {
+                continue;
+            };
+            dataset.conditions.push(ConditionRow {
+                person_id: person_id.clone(),
+                system: coding.system.clone(),
+                code: coding.code.clone(),
+                display: coding.display.clone(),
+                onset_date: start,
+                abatement_date: end,
+            });
+        }
+        for (report, result) in records.labs() {
+            let date = result_date(report, result);
+            let Some(coding) = result.code.coding.first().filter(|_| window.contains(date)) else {
+                continue;
+            };
+            let quantity = match &result.value {
+                LabValue::Quantity(quantity) => Some(quantity),
+                LabValue::Comparator { value, .. } => Some(value),
+                _ => None,
+            };
+            dataset.measurements.push(MeasurementRow {
+                person_id: person_id.clone(),
+                system: coding.system.clone(),
+                code: coding.code.clone(),
+                value: result.value.numeric_value(),
+                unit: quantity.map(|quantity| quantity.unit.clone()),
+                date,
+            });
+        }
+        for medication in &records.medications {
+            let end = medication.last_date();
+            if !overlaps(Some(medication.start_date), end, &window) {
+                continue;
+            }
+            dataset.medications.push(MedicationRow {
+                person_id: person_id.clone(),
+                system: medication.medication.system.clone(),
+                code: medication.medication.code.clone(),
+                display: medication.medication.display.clone(),
+                start_date: medication.start_date,
+                end_date: end,
+            });
+        }
+    }
+}
+
+/// The records of one patient the criteria read.
+#[derive(Default)]
+struct Records<'a> {
+    person: Option<&'a Person>,
+    conditions: Vec<&'a Condition>,
+    lab_reports: Vec<&'a LabReport>,
+    medications: Vec<&'a MedicationRecord>,
+}
+
+impl<'a> Records<'a> {
+    fn labs(&self) -> impl Iterator<Item = (&'a LabReport, &'a LabResult)> + '_ {
+        self.lab_reports.iter().flat_map(|report| report.results.iter().map(move |result| (*report, result)))
+    }
+}
+
+/// Selected patients as flat tables, with the manifest describing them.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ResearchDataset {
+    pub manifest: Manifest,
+    pub patients: Vec<PatientRow>,
+    pub conditions: Vec<ConditionRow>,
+    pub measurements: Vec<MeasurementRow>,
+    pub medications: Vec<MedicationRow>,
+}
+
+/// How a dataset was selected and what its tables hold.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Manifest {
+    /// Day ages were computed at
+    pub index_date: NaiveDate,
+    /// Study window; `None` when records of any date were used
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub window: Option<Period>,
+    /// Criteria every selected patient meets
+    pub inclusion: Vec<Criterion>,
+    /// Criteria no selected patient meets
+    pub exclusion: Vec<Criterion>,
+    /// Patients found in the input
+    pub screened: usize,
+    /// Patients exported
+    pub selected: usize,
+    /// Patients remaining after each criterion, inclusions first
+    pub attrition: Vec<AttritionStep>,
+    /// Columns of each table
+    pub tables: Vec<Table>,
+}
+
+/// Patients left after applying one criterion.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AttritionStep {
+    /// Criterion applied (e.g., "inclusion[0]")
+    pub step: String,
+    /// Patients meeting it and every earlier one
+    pub remaining: usize,
+}
+
+/// Name and columns of an exported table.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Table {
+    pub name: &'static str,
+    pub columns: Vec<Column>,
+}
+
+/// A column and its physical type.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Column {
+    pub name: &'static str,
+    #[serde(rename = "type")]
+    pub column_type: ColumnType,
+    /// Whether the column may be null
+    pub nullable: bool,
+}
+
+/// Column types, named after their Parquet logical types.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColumnType {
+    /// UTF-8 string
+    String,
+    Int32,
+    Int64,
+    Double,
+    /// Calendar date, days since 1970-01-01
+    Date,
+}
+
+/// One row per selected patient.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PatientRow {
+    /// Pseudonym of the patient
+    pub person_id: String,
+    pub birth_year: Option<i32>,
+    /// Administrative gender (e.g., "female")
+    pub gender: Option<String>,
+    /// Age in whole years at the index date
+    pub age_at_index: Option<u32>,
+}
+
+/// One row per coded diagnosis.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ConditionRow {
+    pub person_id: String,
+    pub system: String,
+    pub code: String,
+    pub display: Option<String>,
+    /// Onset, or the recorded date without one
+    pub onset_date: Option<NaiveDate>,
+    pub abatement_date: Option<NaiveDate>,
+}
+
+/// One row per coded lab result.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MeasurementRow {
+    pub person_id: String,
+    pub system: String,
+    pub code: String,
+    /// Numeric value; `None` for coded and text results
+    pub value: Option<f64>,
+    /// UCUM unit of the value
+    pub unit: Option<String>,
+    /// Day the result applies to, or the report was issued
+    pub date: NaiveDate,
+}
+
+/// One row per medication record.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MedicationRow {
+    pub person_id: String,
+    pub system: String,
+    pub code: String,
+    pub display: Option<String>,
+    pub start_date: NaiveDate,
+    /// Last day of treatment; `None` when ongoing or unknown
+    pub end_date: Option<NaiveDate>,
+}
+
+fn tables() -> Vec<Table> {
+    use ColumnType::*;
+    let table = |name, columns: &[(&'static str, ColumnType, bool)]| Table {
+        name,
+        columns: columns
+            .iter()
+            .map(|&(name, column_type, nullable)| Column { name, column_type, nullable })
+            .collect(),
+    };
+    vec![
+        table(
+            "patients",
+            &[
+                ("person_id", String, false),
+                ("birth_year", Int32, true),
+                ("gender", String, true),
+                ("age_at_index", Int32, true),
+            ],
+        ),
+        table(
+            "conditions",
+            &[
+                ("person_id", String, false),
+                ("system", String, false),
+                ("code", String, false),
+                ("display", String, true),
+                ("onset_date", Date, true),
+                ("abatement_date", Date, true),
+            ],
+        ),
+        table(
+            "measurements",
+            &[
+                ("person_id", String, false),
+                ("system", String, false),
+                ("code", String, false),
+                ("value", Double, true),
+                ("unit", String, true),
+                ("date", Date, false),
+            ],
+        ),
+        table(
+            "medications",
+            &[
+                ("person_id", String, false),
+                ("system", String, false),
+                ("code", String, false),
+                ("display", String, true),
+                ("start_date", Date, false),
+                ("end_date", Date, true),
+            ],
+        ),
+    ]
+}
+
+fn condition_start(condition: &Condition) -> Option<NaiveDate> {
+    condition.onset.or(condition.recorded_date).map(|date| date.local().date())
+}
+
+fn condition_end(condition: &Condition) -> Option<NaiveDate> {
+    condition.abatement.map(|date| date.local().date())
+}
+
+fn result_date(report: &LabReport, result: &LabResult) -> NaiveDate {
+    result.effective.as_ref().and_then(|effective| effective.first_date()).unwrap_or(report.issued_at.local().date())
+}
+
+/// Whether days `start` to `end` overlap `window`; open ends are unbounded.
+fn overlaps(start: Option<NaiveDate>, end: Option<NaiveDate>, window: &Period) -> bool {
+    start.zip(window.end).is_none_or(|(start, last)| start <= last)
+        && end.zip(window.start).is_none_or(|(end, first)| end >= first)
+}
+
+/// Numeric value of `result` in `unit`; `None` when not numeric or not convertible.
+fn value_in(result: &LabResult, unit: &str) -> Option<f64> {
+    let value = result.value.numeric_value()?;
+    match &result.value {
+        LabValue::Quantity(quantity) | LabValue::Comparator { value: quantity, .. } => {
+            ucum::convert(value, &quantity.unit, unit).ok()
+        }
+        _ => Some(value),
+    }
+}
+
+fn compare(value: f64, comparator: Comparator, threshold: f64) -> bool {
+    match comparator {
+        Comparator::LessThan => value < threshold,
+        Comparator::LessOrEqual => value <= threshold,
+        Comparator::GreaterThan => value > threshold,
+        Comparator::GreaterOrEqual => value >= threshold,
+    }
+}
+
+fn age_at(person: Option<&Person>, date: NaiveDate) -> Option<u32> {
+    date.years_since(person?.birth_date.date())
+}
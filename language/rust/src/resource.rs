@@ -0,0 +1,574 @@
+//! Shared behaviour of top-level resources.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+
+use alloc::{string::String, vec::Vec};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::error::{self, WellAllyError};
+use crate::extension::{Extensible, Extension};
+use crate::common::Coding;
+use crate::security::SecurityLabeled;
+
+use crate::family_health::FamilyHealthTree;
+use crate::health::Person;
+use crate::imaging_report::ImagingReport;
+use crate::lab_report::LabReport;
+use crate::medication::MedicationRecord;
+use crate::questionnaire::{Questionnaire, QuestionnaireResponse};
+use crate::referral::Referral;
+use crate::task::Task;
+use crate::communication::CommunicationRecord;
+use crate::location::Location;
+use crate::condition::Condition;
+use crate::allergy::AllergyIntolerance;
+use crate::flag::Flag;
+use crate::cgm::GlucoseSeries;
+use crate::ecg::EcgRecord;
+use crate::consent::ConsentRecord;
+use crate::nephrology::DialysisSession;
+use crate::rehab::RehabSession;
+use crate::wound::WoundAssessment;
+use crate::infusion::InfusionRecord;
+use crate::pathology::PathologyReport;
+use crate::audiology::AudiogramReport;
+use crate::birth::BirthRecord;
+use crate::clearance::ClearanceDocument;
+use crate::travel::TravelHealthRecord;
+use crate::compendium::Compendium;
+use crate::dispense::DispenseRecord;
+use crate::immunization::ImmunizationRecord;
+use crate::relationships::RelatedPerson;
+use crate::validation::Validate;
+
+/// A top-level record that can be stored and exchanged on its own.
+pub trait Resource: Serialize + DeserializeOwned + Extensible + SecurityLabeled + UnknownFields {
+    /// Resource type name (e.g., "LabReport")
+    const RESOURCE_TYPE: &'static str;
+
+    /// URI of the published JSON Schema the resource conforms to
+    const SCHEMA_ID: &'static str;
+
+    /// Logical identifier of the resource
+    fn id(&self) -> &str;
+
+    /// Identifier of the person the resource belongs to
+    fn patient_id(&self) -> &str;
+
+    /// Parses the resource from JSON text; errors carry the path and value of the offending field.
+    fn from_json(json: &str) -> Result<Self, WellAllyError> {
+        error::from_str(Self::RESOURCE_TYPE, json)
+    }
+
+    /// Builds the resource from a JSON value; errors carry the path and value of the offending field.
+    fn from_json_value(value: &Value) -> Result<Self, WellAllyError> {
+        error::from_value(Self::RESOURCE_TYPE, value)
+    }
+
+    /// Validation issues as errors carrying the resource type and offending values.
+    fn validation_errors(&self) -> Vec<WellAllyError>
+    where
+        Self: Validate,
+    {
+        error::validation_errors(self, self.validate())
+    }
+}
+
+/// Properties of a parsed resource that its model does not define.
+///
+/// They are dropped on parse unless the `preserve-unknown` feature is
+/// enabled, in which case they are kept in the resource's `extra` map and
+/// written back on serialization, so records from newer schema versions
+/// survive a read-modify-write cycle. Only top-level properties are kept;
+/// unknown properties of nested objects are still dropped.
+pub trait UnknownFields {
+    /// Unknown properties kept from parsing; `None` without `preserve-unknown`.
+    fn unknown_fields(&self) -> Option<&Map<String, Value>>;
+
+    /// Removes and returns the unknown properties.
+    fn take_unknown_fields(&mut self) -> Map<String, Value>;
+}
+
+impl Resource for Person {
+    const RESOURCE_TYPE: &'static str = "Person";
+    const SCHEMA_ID: &'static str = "https://wellall.health/schemas/health/v0.1.0";
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn patient_id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl Resource for LabReport {
+    const RESOURCE_TYPE: &'static str = "LabReport";
+    const SCHEMA_ID: &'static str = "https://wellall.health/schemas/lab-report/v0.1.0";
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn patient_id(&self) -> &str {
+        &self.patient_id
+    }
+}
+
+impl Resource for ImagingReport {
+    const RESOURCE_TYPE: &'static str = "ImagingReport";
+    const SCHEMA_ID: &'static str = "https://wellall.health/schemas/imaging-report/v0.1.0";
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn patient_id(&self) -> &str {
+        &self.patient_id
+    }
+}
+
+impl Resource for MedicationRecord {
+    const RESOURCE_TYPE: &'static str = "MedicationRecord";
+    const SCHEMA_ID: &'static str = "https://wellall.health/schemas/medication/v0.1.0";
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn patient_id(&self) -> &str {
+        &self.patient_id
+    }
+}
+
+/// A family tree is keyed by its proband.
+impl Resource for FamilyHealthTree {
+    const RESOURCE_TYPE: &'static str = "FamilyHealthTree";
+    const SCHEMA_ID: &'static str = "https://wellall.health/schemas/family-health/v0.1.0";
+
+    fn id(&self) -> &str {
+        &self.proband_id
+    }
+
+    fn patient_id(&self) -> &str {
+        &self.proband_id
+    }
+}
+
+/// A questionnaire is a definition shared by all patients and has no owner;
+/// its `patient_id` is empty.
+impl Resource for Questionnaire {
+    const RESOURCE_TYPE: &'static str = "Questionnaire";
+    const SCHEMA_ID: &'static str = "https://wellall.health/schemas/questionnaire/v0.1.0";
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn patient_id(&self) -> &str {
+        ""
+    }
+}
+
+impl Resource for QuestionnaireResponse {
+    const RESOURCE_TYPE: &'static str = "QuestionnaireResponse";
+    const SCHEMA_ID: &'static str = "https://wellall.health/schemas/questionnaire-response/v0.1.0";
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn patient_id(&self) -> &str {
+        &self.patient_id
+    }
+}
+
+impl Resource for Referral {
+    const RESOURCE_TYPE: &'static str = "Referral";
+    const SCHEMA_ID: &'static str = "https://wellall.health/schemas/referral/v0.1.0";
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn patient_id(&self) -> &str {
+        &self.patient_id
+    }
+}
+
+impl Resource for Task {
+    const RESOURCE_TYPE: &'static str = "Task";
+    const SCHEMA_ID: &'static str = "https://wellall.health/schemas/task/v0.1.0";
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn patient_id(&self) -> &str {
+        &self.patient_id
+    }
+}
+
+impl Resource for CommunicationRecord {
+    const RESOURCE_TYPE: &'static str = "CommunicationRecord";
+    const SCHEMA_ID: &'static str = "https://wellall.health/schemas/communication/v0.1.0";
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn patient_id(&self) -> &str {
+        &self.patient_id
+    }
+}
+
+/// A location is shared reference data with no owner; its `patient_id` is empty.
+impl Resource for Location {
+    const RESOURCE_TYPE: &'static str = "Location";
+    const SCHEMA_ID: &'static str = "https://wellall.health/schemas/location/v0.1.0";
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn patient_id(&self) -> &str {
+        ""
+    }
+}
+
+impl Resource for Condition {
+    const RESOURCE_TYPE: &'static str = "Condition";
+    const SCHEMA_ID: &'static str = "https://wellall.health/schemas/condition/v0.1.0";
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn patient_id(&self) -> &str {
+        &self.patient_id
+    }
+}
+
+impl Resource for AllergyIntolerance {
+    const RESOURCE_TYPE: &'static str = "AllergyIntolerance";
+    const SCHEMA_ID: &'static str = "https://wellall.health/schemas/allergy-intolerance/v0.1.0";
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn patient_id(&self) -> &str {
+        &self.patient_id
+    }
+}
+
+impl Resource for Flag {
+    const RESOURCE_TYPE: &'static str = "Flag";
+    const SCHEMA_ID: &'static str = "https://wellall.health/schemas/flag/v0.1.0";
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn patient_id(&self) -> &str {
+        &self.patient_id
+    }
+}
+
+impl Resource for GlucoseSeries {
+    const RESOURCE_TYPE: &'static str = "GlucoseSeries";
+    const SCHEMA_ID: &'static str = "https://wellall.health/schemas/glucose-series/v0.1.0";
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn patient_id(&self) -> &str {
+        &self.patient_id
+    }
+}
+
+impl Resource for EcgRecord {
+    const RESOURCE_TYPE: &'static str = "EcgRecord";
+    const SCHEMA_ID: &'static str = "https://wellall.health/schemas/ecg/v0.1.0";
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn patient_id(&self) -> &str {
+        &self.patient_id
+    }
+}
+
+impl Resource for ConsentRecord {
+    const RESOURCE_TYPE: &'static str = "ConsentRecord";
+    const SCHEMA_ID: &'static str = "https://wellall.health/schemas/consent/v0.1.0";
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn patient_id(&self) -> &str {
+        &self.patient_id
+    }
+}
+
+impl Resource for DialysisSession {
+    const RESOURCE_TYPE: &'static str = "DialysisSession";
+    const SCHEMA_ID: &'static str = "https://wellall.health/schemas/dialysis/v0.1.0";
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn patient_id(&self) -> &str {
+        &self.patient_id
+    }
+}
+
+impl Resource for RehabSession {
+    const RESOURCE_TYPE: &'static str = "RehabSession";
+    const SCHEMA_ID: &'static str = "https://wellall.health/schemas/rehab/v0.1.0";
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn patient_id(&self) -> &str {
+        &self.patient_id
+    }
+}
+
+impl Resource for WoundAssessment {
+    const RESOURCE_TYPE: &'static str = "WoundAssessment";
+    const SCHEMA_ID: &'static str = "https://wellall.health/schemas/wound/v0.1.0";
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn patient_id(&self) -> &str {
+        &self.patient_id
+    }
+}
+
+impl Resource for InfusionRecord {
+    const RESOURCE_TYPE: &'static str = "InfusionRecord";
+    const SCHEMA_ID: &'static str = "https://wellall.health/schemas/infusion/v0.1.0";
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn patient_id(&self) -> &str {
+        &self.patient_id
+    }
+}
+
+impl Resource for PathologyReport {
+    const RESOURCE_TYPE: &'static str = "PathologyReport";
+    const SCHEMA_ID: &'static str = "https://wellall.health/schemas/pathology/v0.1.0";
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn patient_id(&self) -> &str {
+        &self.patient_id
+    }
+}
+
+impl Resource for AudiogramReport {
+    const RESOURCE_TYPE: &'static str = "AudiogramReport";
+    const SCHEMA_ID: &'static str = "https://wellall.health/schemas/audiology/v0.1.0";
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn patient_id(&self) -> &str {
+        &self.patient_id
+    }
+}
+
+impl Resource for BirthRecord {
+    const RESOURCE_TYPE: &'static str = "BirthRecord";
+    const SCHEMA_ID: &'static str = "https://wellall.health/schemas/birth/v0.1.0";
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn patient_id(&self) -> &str {
+        &self.patient_id
+    }
+}
+
+impl Resource for ClearanceDocument {
+    const RESOURCE_TYPE: &'static str = "ClearanceDocument";
+    const SCHEMA_ID: &'static str = "https://wellall.health/schemas/clearance/v0.1.0";
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn patient_id(&self) -> &str {
+        &self.patient_id
+    }
+}
+
+impl Resource for TravelHealthRecord {
+    const RESOURCE_TYPE: &'static str = "TravelHealthRecord";
+    const SCHEMA_ID: &'static str = "https://wellall.health/schemas/travel/v0.1.0";
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn patient_id(&self) -> &str {
+        &self.patient_id
+    }
+}
+
+impl Resource for Compendium {
+    const RESOURCE_TYPE: &'static str = "Compendium";
+    const SCHEMA_ID: &'static str = "https://wellall.health/schemas/compendium/v0.1.0";
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn patient_id(&self) -> &str {
+        ""
+    }
+}
+
+impl Resource for DispenseRecord {
+    const RESOURCE_TYPE: &'static str = "DispenseRecord";
+    const SCHEMA_ID: &'static str = "https://wellall.health/schemas/dispense/v0.1.0";
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn patient_id(&self) -> &str {
+        &self.patient_id
+    }
+}
+
+impl Resource for ImmunizationRecord {
+    const RESOURCE_TYPE: &'static str = "ImmunizationRecord";
+    const SCHEMA_ID: &'static str = "https://wellall.health/schemas/immunization/v0.1.0";
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn patient_id(&self) -> &str {
+        &self.patient_id
+    }
+}
+
+impl Resource for RelatedPerson {
+    const RESOURCE_TYPE: &'static str = "RelatedPerson";
+    const SCHEMA_ID: &'static str = "https://wellall.health/schemas/related-person/v0.1.0";
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn patient_id(&self) -> &str {
+        &self.patient_id
+    }
+}
+
+macro_rules! resource_fields {
+    ($($ty:ty),* $(,)?) => {$(
+        impl Extensible for $ty {
+            fn all_extensions(&self) -> &[Extension] {
+                self.extensions.as_deref().unwrap_or_default()
+            }
+
+            fn extensions_mut(&mut self) -> &mut Option<Vec<Extension>> {
+                &mut self.extensions
+            }
+        }
+
+        impl SecurityLabeled for $ty {
+            fn all_security_labels(&self) -> &[Coding] {
+                self.security_labels.as_deref().unwrap_or_default()
+            }
+
+            fn security_labels_mut(&mut self) -> &mut Option<Vec<Coding>> {
+                &mut self.security_labels
+            }
+        }
+
+        impl UnknownFields for $ty {
+            #[cfg(feature = "preserve-unknown")]
+            fn unknown_fields(&self) -> Option<&Map<String, Value>> {
+                Some(&self.extra)
+            }
+
+            #[cfg(feature = "preserve-unknown")]
+            fn take_unknown_fields(&mut self) -> Map<String, Value> {
+                core::mem::take(&mut self.extra)
+            }
+
+            #[cfg(not(feature = "preserve-unknown"))]
+            fn unknown_fields(&self) -> Option<&Map<String, Value>> {
+                None
+            }
+
+            #[cfg(not(feature = "preserve-unknown"))]
+            fn take_unknown_fields(&mut self) -> Map<String, Value> {
+                Map::new()
+            }
+        }
+    )*};
+}
+
+resource_fields!(
+    Person,
+    LabReport,
+    ImagingReport,
+    MedicationRecord,
+    FamilyHealthTree,
+    Questionnaire,
+    QuestionnaireResponse,
+    Referral,
+    Task,
+    CommunicationRecord,
+    Location,
+    Condition,
+    AllergyIntolerance,
+    Flag,
+    GlucoseSeries,
+    EcgRecord,
+    ConsentRecord,
+    DialysisSession,
+    RehabSession,
+    WoundAssessment,
+    InfusionRecord,
+    PathologyReport,
+    AudiogramReport,
+    BirthRecord,
+    ClearanceDocument,
+    TravelHealthRecord,
+    Compendium,
+    DispenseRecord,
+    ImmunizationRecord,
+    RelatedPerson,
+);
+
+/// Generates the JSON Schema of a resource, identified by its published `$id`.
+#[cfg(feature = "schemars")]
+pub fn json_schema<R: Resource + schemars::JsonSchema>() -> schemars::Schema {
+    let mut schema = schemars::schema_for!(R);
+    schema.insert("$id".to_string(), R::SCHEMA_ID.into());
+    schema
+}
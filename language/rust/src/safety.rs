@@ -0,0 +1,199 @@
+//! Medication safety checks.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//!
+//! [`allergy_check`] flags a prescription that conflicts with a patient's
+//! recorded allergies. A conflict is either a direct ingredient match (the
+//! allergy's substance is coded like the drug, or named in the drug's
+//! display) or a drug-class one looked up in a [`DrugClassMap`]: the crate
+//! ships no drug classification, so callers supply class memberships and the
+//! cross-sensitive class pairs (e.g., penicillins and cephalosporins) from
+//! their own formulary. [`renal`] advises on dose adjustments for reduced
+//! kidney function.
+//!
+//! ```
+//! use wellally::catalog::{coding, concept, DRUGS, RXNORM};
+//! use wellally::safety::{allergy_check, AllergyConflict, DrugClassMap};
+//! use wellally::AllergyIntolerance;
+//!
+//! let mut classes = DrugClassMap::new();
+//! classes.insert("penicillins", coding(RXNORM, "723", "amoxicillin"));
+//! classes.insert("cephalosporins", coding(RXNORM, "2231", "cephalexin"));
+//! classes.cross_sensitive("penicillins", "cephalosporins");
+//!
+//! let allergy = AllergyIntolerance::new("al-1", "p-1", concept(RXNORM, "2231", "cephalexin"));
+//! let amoxicillin = DRUGS.iter().find(|drug| drug.code == "308182").unwrap();
+//! let prescription = amoxicillin.record("med-1".into(), "p-1".into(), "2024-10-15".parse().unwrap(), 7);
+//!
+//! let alerts = allergy_check(&[allergy], &prescription, &classes);
+//! assert_eq!(
+//!     alerts[0].conflict,
+//!     AllergyConflict::CrossSensitivity { allergy_class: "cephalosporins".into(), drug_class: "penicillins".into() }
+//! );
+//! ```
+
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::{string::String, vec, vec::Vec};
+
+use crate::allergy::{AllergyIntolerance, Criticality, ReactionSeverity};
+use crate::common::Coding;
+use crate::medication::MedicationRecord;
+
+pub mod renal;
+
+/// Drug classes and the class pairs that cross-react.
+///
+/// A member matches a drug or allergy substance coded with the same system
+/// and code, or whose name contains the member's display as a whole word
+/// (so "amoxicillin" matches "Amoxicillin 500 MG Oral Capsule").
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DrugClassMap {
+    members: BTreeMap<String, Vec<Coding>>,
+    cross: BTreeSet<(String, String)>,
+}
+
+impl DrugClassMap {
+    /// Empty map.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `member` to `class`.
+    pub fn insert(&mut self, class: &str, member: Coding) {
+        self.members.entry(class.into()).or_default().push(member);
+    }
+
+    /// Records that patients allergic to one class may react to the other,
+    /// in both directions.
+    pub fn cross_sensitive(&mut self, class: &str, other: &str) {
+        self.cross.insert((class.into(), other.into()));
+        self.cross.insert((other.into(), class.into()));
+    }
+
+    /// Whether an allergy to `allergy_class` puts `drug_class` at risk.
+    pub fn is_cross_sensitive(&self, allergy_class: &str, drug_class: &str) -> bool {
+        self.cross.contains(&(allergy_class.into(), drug_class.into()))
+    }
+
+    /// Classes with a member matching the given codings or names.
+    fn classes_of<'a>(&'a self, substance: &Substance<'_>) -> Vec<&'a str> {
+        self.members
+            .iter()
+            .filter(|(_, members)| members.iter().any(|member| substance.matches(member)))
+            .map(|(class, _)| class.as_str())
+            .collect()
+    }
+}
+
+/// How a prescription conflicts with an allergy, most direct first.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AllergyConflict {
+    /// The drug contains the allergen
+    Ingredient,
+    /// The drug belongs to the same class as the allergen
+    SameClass { class: String },
+    /// The drug's class cross-reacts with the allergen's
+    CrossSensitivity { allergy_class: String, drug_class: String },
+}
+
+/// A prescription that conflicts with a recorded allergy.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AllergyAlert {
+    /// Person.id
+    pub patient_id: String,
+    /// AllergyIntolerance.id
+    pub allergy_id: String,
+    /// MedicationRecord.id
+    pub medication_id: String,
+    /// Name of the allergen as recorded
+    pub substance: String,
+    /// Why the prescription conflicts
+    pub conflict: AllergyConflict,
+    /// Criticality recorded on the allergy
+    pub criticality: Option<Criticality>,
+    /// Most severe reaction recorded on the allergy
+    pub worst_reaction: Option<ReactionSeverity>,
+}
+
+/// Codings and names of a drug or allergen.
+struct Substance<'a> {
+    codings: Vec<&'a Coding>,
+    names: Vec<&'a str>,
+}
+
+impl<'a> Substance<'a> {
+    fn of_allergy(allergy: &'a AllergyIntolerance) -> Self {
+        let codings: Vec<&Coding> = allergy.code.coding.iter().collect();
+        let names = codings.iter().filter_map(|coding| coding.display.as_deref()).chain(allergy.code.text.as_deref()).collect();
+        Substance { codings, names }
+    }
+
+    fn of_medication(medication: &'a MedicationRecord) -> Self {
+        Substance { codings: vec![&medication.medication], names: medication.medication.display.as_deref().into_iter().collect() }
+    }
+
+    fn matches(&self, member: &Coding) -> bool {
+        self.codings.iter().any(|coding| same_code(coding, member))
+            || member.display.as_deref().is_some_and(|display| self.names.iter().any(|name| mentions(name, display)))
+    }
+}
+
+fn same_code(a: &Coding, b: &Coding) -> bool {
+    a.system == b.system && a.code == b.code
+}
+
+/// Whether `text` contains `word` as a whole word, ignoring ASCII case.
+fn mentions(text: &str, word: &str) -> bool {
+    let word = word.trim();
+    if word.is_empty() {
+        return false;
+    }
+    let (text, word) = (text.to_ascii_lowercase(), word.to_ascii_lowercase());
+    text.match_indices(&word).any(|(at, _)| {
+        let before = text[..at].chars().next_back();
+        let after = text[at + word.len()..].chars().next();
+        !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+    })
+}
+
+/// Active allergies of the prescription's patient that conflict with it,
+/// with the most direct conflict per allergy.
+///
+/// Inactive, resolved, refuted and entered-in-error allergies are ignored.
+pub fn allergy_check(allergies: &[AllergyIntolerance], med: &MedicationRecord, class_map: &DrugClassMap) -> Vec<AllergyAlert> {
+    let drug = Substance::of_medication(med);
+    let drug_classes = class_map.classes_of(&drug);
+    allergies
+        .iter()
+        .filter(|allergy| allergy.patient_id == med.patient_id && allergy.is_active())
+        .filter_map(|allergy| {
+            let allergen = Substance::of_allergy(allergy);
+            let ingredient = allergen.codings.iter().any(|coding| drug.matches(coding))
+                || allergen.names.iter().any(|name| drug.names.iter().any(|drug_name| mentions(drug_name, name)));
+            let conflict = if ingredient {
+                AllergyConflict::Ingredient
+            } else {
+                let allergy_classes = class_map.classes_of(&allergen);
+                let same = allergy_classes.iter().find(|class| drug_classes.contains(class));
+                match same {
+                    Some(class) => AllergyConflict::SameClass { class: (*class).into() },
+                    None => allergy_classes.iter().find_map(|allergy_class| {
+                        drug_classes.iter().find(|drug_class| class_map.is_cross_sensitive(allergy_class, drug_class)).map(|drug_class| {
+                            AllergyConflict::CrossSensitivity { allergy_class: (*allergy_class).into(), drug_class: (*drug_class).into() }
+                        })
+                    })?,
+                }
+            };
+            Some(AllergyAlert {
+                patient_id: med.patient_id.clone(),
+                allergy_id: allergy.id.clone(),
+                medication_id: med.id.clone(),
+                substance: allergen.names.first().copied().or(allergen.codings.first().map(|coding| coding.code.as_str())).unwrap_or_default().into(),
+                conflict,
+                criticality: allergy.criticality,
+                worst_reaction: allergy.worst_reaction(),
+            })
+        })
+        .collect()
+}
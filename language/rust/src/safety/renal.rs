@@ -0,0 +1,213 @@
+//! Renal dose-adjustment advisories.
+//!
+//! Many drugs are cleared by the kidneys and need a lower dose, or must be
+//! avoided, once kidney function falls. [`RenalRules`] holds per-drug eGFR
+//! thresholds registered by the caller; [`RenalRules::check`] compares each
+//! patient's latest reported eGFR with their active medications and returns
+//! a [`RenalAdvisory`] for every rule that applies.
+//!
+//! ```
+//! use wellally::catalog::{coding, RXNORM};
+//! use wellally::safety::renal::{RenalAction, RenalRule, RenalRules};
+//! use wellally::{LabReport, MedicationRecord, Resource};
+//!
+//! let mut rules = RenalRules::new();
+//! let metformin = coding(RXNORM, "6809", "metformin");
+//! rules.register(metformin.clone(), RenalRule::reduce(45.0, "Review dose; do not start below 45"));
+//! rules.register(metformin, RenalRule::avoid(30.0, "Contraindicated below 30"));
+//!
+//! let report = LabReport::from_json(r#"{"id":"lab-1","patientId":"p-1","issuedAt":"2024-10-01",
+//!     "results":[{"code":{"coding":[{"system":"http://loinc.org","code":"98979-8"}]},
+//!                 "value":{"value":27,"unit":"mL/min/{1.73_m2}"}}]}"#).unwrap();
+//! let med = MedicationRecord::from_json(r#"{"id":"med-1","patientId":"p-1",
+//!     "medication":{"system":"http://www.nlm.nih.gov/research/umls/rxnorm","code":"860975",
+//!                   "display":"metformin 24 HR 500 MG Extended Release Oral Tablet"},
+//!     "dosage":{"value":500,"unit":"mg"},"route":{"system":"http://snomed.info/sct","code":"26643006"},
+//!     "startDate":"2024-01-01"}"#).unwrap();
+//!
+//! let advisories = rules.check(&[report], &[med], "2024-10-15".parse().unwrap());
+//! assert_eq!(advisories[0].action, RenalAction::Avoid);
+//! assert_eq!(advisories[0].egfr, 27.0);
+//! ```
+
+use alloc::collections::BTreeMap;
+use alloc::{string::String, vec::Vec};
+use core::cmp::Ordering;
+use chrono::NaiveDate;
+
+use super::{mentions, same_code};
+use crate::common::{Coding, Quantity};
+use crate::datetime::FlexibleDateTime;
+use crate::lab_report::LabReport;
+use crate::medication::MedicationRecord;
+
+/// LOINC codes of estimated GFR results (MDRD, CKD-EPI and CKD-EPI 2021,
+/// including the race-specific ones)
+pub const EGFR_CODES: &[&str] = &["33914-3", "48642-3", "48643-1", "62238-1", "69405-9", "98979-8"];
+
+/// What to do about a drug below a rule's eGFR threshold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RenalAction {
+    /// Lower the dose or lengthen the dosing interval
+    ReduceDose,
+    /// Stop or do not start the drug
+    Avoid,
+}
+
+/// A drug's adjustment below an eGFR threshold.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenalRule {
+    /// Applies when eGFR (mL/min/1.73 m²) is below this value
+    pub below_egfr: f64,
+    /// What to do
+    pub action: RenalAction,
+    /// Highest daily dose still acceptable below the threshold; a
+    /// [`RenalAction::ReduceDose`] rule is met by records at or under it
+    pub max_daily_dose: Option<Quantity>,
+    /// Advice shown to the prescriber
+    pub advice: String,
+}
+
+impl RenalRule {
+    /// Reduce the dose below `below_egfr`.
+    pub fn reduce(below_egfr: f64, advice: &str) -> Self {
+        RenalRule { below_egfr, action: RenalAction::ReduceDose, max_daily_dose: None, advice: advice.into() }
+    }
+
+    /// Avoid the drug below `below_egfr`.
+    pub fn avoid(below_egfr: f64, advice: &str) -> Self {
+        RenalRule { below_egfr, action: RenalAction::Avoid, max_daily_dose: None, advice: advice.into() }
+    }
+
+    /// The rule with the highest acceptable daily dose.
+    pub fn with_max_daily_dose(mut self, max_daily_dose: Quantity) -> Self {
+        self.max_daily_dose = Some(max_daily_dose);
+        self
+    }
+
+    /// Whether `medication`'s daily dose is already within the limit.
+    fn dose_within_limit(&self, medication: &MedicationRecord) -> bool {
+        let (Some(max), Some(daily)) = (&self.max_daily_dose, medication.daily_dose()) else {
+            return false;
+        };
+        self.action == RenalAction::ReduceDose && daily.compare(max).is_some_and(Ordering::is_le)
+    }
+}
+
+/// A patient's eGFR as reported.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Egfr {
+    /// mL/min/1.73 m²
+    pub value: f64,
+    /// LabReport.id of the result
+    pub report_id: String,
+    /// When the report was issued
+    pub issued_at: FlexibleDateTime,
+}
+
+/// The most recent eGFR result in `reports`, all of one patient, by the
+/// result's effective time.
+pub fn latest_egfr(reports: &[LabReport]) -> Option<Egfr> {
+    latest(reports)
+}
+
+fn latest<'a>(reports: impl IntoIterator<Item = &'a LabReport>) -> Option<Egfr> {
+    reports
+        .into_iter()
+        .filter_map(|report| {
+            let (value, effective) = report
+                .results
+                .iter()
+                .filter(|result| result.code.coding.iter().any(|coding| EGFR_CODES.contains(&coding.code.as_str())))
+                .find_map(|result| Some((result.value.numeric_value()?, report.effective_of(result))))?;
+            Some((Egfr { value, report_id: report.id.clone(), issued_at: report.issued_at }, effective.sort_key()))
+        })
+        .max_by_key(|(_, effective)| *effective)
+        .map(|(egfr, _)| egfr)
+}
+
+/// A medication whose renal rule applies at the patient's eGFR.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenalAdvisory {
+    /// Person.id
+    pub patient_id: String,
+    /// MedicationRecord.id
+    pub medication_id: String,
+    /// The eGFR the advice is based on
+    pub egfr: f64,
+    /// LabReport.id of that eGFR
+    pub egfr_report_id: String,
+    /// Threshold of the applied rule
+    pub below_egfr: f64,
+    /// What to do
+    pub action: RenalAction,
+    /// Advice from the rule
+    pub advice: String,
+}
+
+/// Per-drug renal rules.
+///
+/// A drug matches a registration coded with the same system and code, or
+/// whose display names the registered drug's display as a whole word, so
+/// ingredient-level registrations cover the products containing them.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RenalRules {
+    rules: Vec<(Coding, RenalRule)>,
+}
+
+impl RenalRules {
+    /// No rules.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `rule` for `drug`; a drug may have several thresholds.
+    pub fn register(&mut self, drug: Coding, rule: RenalRule) {
+        self.rules.push((drug, rule));
+    }
+
+    /// The rule for `medication` at `egfr`: of the rules whose threshold the
+    /// eGFR is below, the one with the lowest threshold.
+    pub fn rule_for(&self, medication: &MedicationRecord, egfr: f64) -> Option<&RenalRule> {
+        let drug = &medication.medication;
+        self.rules
+            .iter()
+            .filter(|(registered, _)| {
+                same_code(registered, drug)
+                    || registered.display.as_deref().is_some_and(|name| drug.display.as_deref().is_some_and(|display| mentions(display, name)))
+            })
+            .map(|(_, rule)| rule)
+            .filter(|rule| egfr < rule.below_egfr)
+            .min_by(|a, b| a.below_egfr.total_cmp(&b.below_egfr))
+    }
+
+    /// Advisories for the medications active on `as_of`, each judged by its
+    /// patient's latest eGFR issued on or before that day. Medications of
+    /// patients without an eGFR get none, as do reduce-dose rules the daily
+    /// dose already meets.
+    pub fn check(&self, reports: &[LabReport], medications: &[MedicationRecord], as_of: NaiveDate) -> Vec<RenalAdvisory> {
+        let mut by_patient: BTreeMap<&str, Vec<&LabReport>> = BTreeMap::new();
+        for report in reports.iter().filter(|report| report.issued_at.local().date() <= as_of) {
+            by_patient.entry(report.patient_id.as_str()).or_default().push(report);
+        }
+        let egfrs: BTreeMap<&str, Egfr> =
+            by_patient.into_iter().filter_map(|(patient, reports)| Some((patient, latest(reports)?))).collect();
+        medications
+            .iter()
+            .filter(|medication| medication.is_active_on(as_of))
+            .filter_map(|medication| {
+                let egfr = egfrs.get(medication.patient_id.as_str())?;
+                let rule = self.rule_for(medication, egfr.value).filter(|rule| !rule.dose_within_limit(medication))?;
+                Some(RenalAdvisory {
+                    patient_id: medication.patient_id.clone(),
+                    medication_id: medication.id.clone(),
+                    egfr: egfr.value,
+                    egfr_report_id: egfr.report_id.clone(),
+                    below_egfr: rule.below_egfr,
+                    action: rule.action,
+                    advice: rule.advice.clone(),
+                })
+            })
+            .collect()
+    }
+}
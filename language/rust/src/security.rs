@@ -0,0 +1,228 @@
+//! Confidentiality and sensitivity labels of resources.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//!
+//! Every top-level resource carries optional `securityLabels`: HL7 v3
+//! codings telling how sensitive the record is, either a confidentiality
+//! level ([`RESTRICTED`], ...) or a sensitivity category
+//! ([`BEHAVIORAL_HEALTH`], [`HIV`], ...). Authors set them explicitly; a
+//! [`Classifier`] derives them from the clinical codes of conditions,
+//! medications and lab results, and [`tag`] stores the derived ones on the
+//! resource. Consent filtering ([`crate::consent`]) and de-identification
+//! ([`crate::anonymize`]) read explicit and derived labels alike, so an
+//! untagged record is not treated as less sensitive.
+//!
+//! ```
+//! use wellally::security::{self, SecurityLabeled, BEHAVIORAL_HEALTH};
+//! use wellally::{Condition, Resource};
+//!
+//! let mut condition = Condition::from_json_value(&serde_json::json!({
+//!     "id": "cond-1", "patientId": "p-1", "clinicalStatus": "active",
+//!     "code": {"coding": [{"system": "http://hl7.org/fhir/sid/icd-10", "code": "F32.1"}]}
+//! }))?;
+//! assert_eq!(security::tag(&mut condition), 2);
+//! assert!(condition.has_security_label(&security::category(BEHAVIORAL_HEALTH)));
+//! assert!(condition.has_security_label(&security::confidentiality(security::RESTRICTED)));
+//! # Ok::<(), wellally::WellAllyError>(())
+//! ```
+
+use alloc::{boxed::Box, vec::Vec};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::catalog;
+use crate::common::Coding;
+
+/// ICD-10 code system URI
+const ICD_10: &str = "http://hl7.org/fhir/sid/icd-10";
+/// ICD-10-CM code system URI
+const ICD_10_CM: &str = "http://hl7.org/fhir/sid/icd-10-cm";
+
+/// HL7 v3 act code for behavioral (mental) health information
+pub const BEHAVIORAL_HEALTH: &str = "BH";
+/// HL7 v3 act code for HIV/AIDS information
+pub const HIV: &str = "HIV";
+/// HL7 v3 act code for genetic disease information
+pub const GENETIC: &str = "GDIS";
+/// HL7 v3 act code for substance use information
+pub const SUBSTANCE_USE: &str = "ETH";
+/// HL7 v3 act code for sexual and reproductive health information
+pub const SEXUAL_HEALTH: &str = "SEX";
+
+/// Confidentiality code for information without extra protection
+pub const NORMAL: &str = "N";
+/// Confidentiality code for sensitive information, shared only with consent
+pub const RESTRICTED: &str = "R";
+/// Confidentiality code for information shared only with named recipients
+pub const VERY_RESTRICTED: &str = "V";
+
+/// Codes of the v3 confidentiality code system, least to most protected
+pub const CONFIDENTIALITY_CODES: [&str; 6] = ["U", "L", "M", NORMAL, RESTRICTED, VERY_RESTRICTED];
+
+/// Sensitivity category coding (v3 ActCode) for `code`, e.g. [`HIV`].
+pub fn category(code: &str) -> Coding {
+    Coding { system: catalog::ACT_CODE.into(), code: code.into(), display: None }
+}
+
+/// Confidentiality coding for `code`, e.g. [`RESTRICTED`].
+pub fn confidentiality(code: &str) -> Coding {
+    Coding { system: catalog::CONFIDENTIALITY.into(), code: code.into(), display: None }
+}
+
+/// Whether two labels have the same system and code; displays are ignored.
+pub fn same_label(a: &Coding, b: &Coding) -> bool {
+    a.system == b.system && a.code == b.code
+}
+
+/// Appends the labels not in `labels` yet.
+pub(crate) fn merge(labels: &mut Vec<Coding>, new: impl IntoIterator<Item = Coding>) {
+    for label in new {
+        if !labels.iter().any(|known| same_label(known, &label)) {
+            labels.push(label);
+        }
+    }
+}
+
+/// Access to the security labels of a resource.
+pub trait SecurityLabeled {
+    /// Labels in document order
+    fn all_security_labels(&self) -> &[Coding];
+
+    /// The label list; `None` when the resource has none.
+    fn security_labels_mut(&mut self) -> &mut Option<Vec<Coding>>;
+
+    /// Whether the resource carries `label` (compared by system and code).
+    fn has_security_label(&self, label: &Coding) -> bool {
+        self.all_security_labels().iter().any(|known| same_label(known, label))
+    }
+
+    /// Adds `label` unless the resource already carries it; returns whether it was added.
+    fn add_security_label(&mut self, label: Coding) -> bool {
+        if self.has_security_label(&label) {
+            return false;
+        }
+        self.security_labels_mut().get_or_insert_with(Vec::new).push(label);
+        true
+    }
+}
+
+/// Sensitivity category of a clinical code, as a v3 ActCode coding: mental
+/// health and substance use (ICD-10 chapter F, common SNOMED CT and RxNorm
+/// codes), HIV (ICD-10 B20-B24 and Z21, SNOMED CT and LOINC tests) and
+/// genetic testing (LOINC genetic reports). A baseline; sites with their own
+/// value sets supply a classifier to [`Classifier::new`].
+pub fn sensitivity(coding: &Coding) -> Option<Coding> {
+    let code = coding.code.as_str();
+    let category_code = match coding.system.as_str() {
+        ICD_10 | ICD_10_CM => match code.get(..3).unwrap_or(code) {
+            "F10" | "F11" | "F12" | "F13" | "F14" | "F15" | "F16" | "F17" | "F18" | "F19" => SUBSTANCE_USE,
+            chapter if chapter.starts_with('F') => BEHAVIORAL_HEALTH,
+            "B20" | "B21" | "B22" | "B23" | "B24" | "Z21" => HIV,
+            _ => return None,
+        },
+        catalog::SNOMED_CT => match code {
+            "35489007" | "69322001" | "13746004" | "58214004" | "197480006" => BEHAVIORAL_HEALTH,
+            "66590003" | "7200002" | "191816009" => SUBSTANCE_USE,
+            "86406008" | "165816005" => HIV,
+            _ => return None,
+        },
+        catalog::LOINC => match code {
+            "75622-1" | "7917-8" | "68961-2" | "25836-8" | "56888-1" => HIV,
+            "51969-4" | "81247-9" | "55232-3" => GENETIC,
+            _ => return None,
+        },
+        catalog::RXNORM => match code {
+            "1819" | "6813" | "7243" => SUBSTANCE_USE,
+            "6448" => BEHAVIORAL_HEALTH,
+            _ => return None,
+        },
+        _ => return None,
+    };
+    Some(category(category_code))
+}
+
+/// Category of a code, as given to [`Classifier::new`].
+type Classify<'a> = Box<dyn Fn(&Coding) -> Option<Coding> + 'a>;
+
+/// Derives security labels from the codes in a resource.
+pub struct Classifier<'a> {
+    classify: Classify<'a>,
+}
+
+impl Default for Classifier<'_> {
+    /// Classifier using [`sensitivity`].
+    fn default() -> Self {
+        Classifier::new(sensitivity)
+    }
+}
+
+impl<'a> Classifier<'a> {
+    /// Classifier mapping a code to its sensitivity category with `classify`.
+    pub fn new(classify: impl Fn(&Coding) -> Option<Coding> + 'a) -> Self {
+        Classifier { classify: Box::new(classify) }
+    }
+
+    /// Category of one code.
+    pub fn classify(&self, coding: &Coding) -> Option<Coding> {
+        (self.classify)(coding)
+    }
+
+    /// Categories of every coding (an object with `system` and `code`) in
+    /// `value`, in document order; `securityLabels` are not read.
+    pub fn categories(&self, value: &Value) -> Vec<Coding> {
+        let mut categories = Vec::new();
+        self.collect(value, &mut categories);
+        categories
+    }
+
+    fn collect(&self, value: &Value, categories: &mut Vec<Coding>) {
+        match value {
+            Value::Object(object) => {
+                let (system, code) = (object.get("system"), object.get("code"));
+                if let (Some(Value::String(system)), Some(Value::String(code))) = (system, code) {
+                    let coding = Coding { system: system.clone(), code: code.clone(), display: None };
+                    merge(categories, self.classify(&coding));
+                }
+                for (field, value) in object {
+                    if field != "securityLabels" {
+                        self.collect(value, categories);
+                    }
+                }
+            }
+            Value::Array(items) => items.iter().for_each(|item| self.collect(item, categories)),
+            _ => {}
+        }
+    }
+
+    /// Labels of `resource`: the ones it carries, the categories derived
+    /// from its codes and, when it has a category but no confidentiality
+    /// label, [`RESTRICTED`].
+    pub fn labels<R: SecurityLabeled + Serialize>(&self, resource: &R) -> Vec<Coding> {
+        let mut labels = resource.all_security_labels().to_vec();
+        let derived = serde_json::to_value(resource).map(|value| self.categories(&value)).unwrap_or_default();
+        let sensitive = !derived.is_empty() || labels.iter().any(|label| label.system == catalog::ACT_CODE);
+        merge(&mut labels, derived);
+        if sensitive && !labels.iter().any(|label| label.system == catalog::CONFIDENTIALITY) {
+            labels.push(confidentiality(RESTRICTED));
+        }
+        labels
+    }
+
+    /// Adds the derived labels to `resource`; returns how many were added.
+    pub fn tag<R: SecurityLabeled + Serialize>(&self, resource: &mut R) -> usize {
+        let labels = self.labels(resource);
+        labels.into_iter().filter(|label| resource.add_security_label(label.clone())).count()
+    }
+}
+
+/// Labels of `resource`, explicit and derived with [`sensitivity`].
+pub fn classify<R: SecurityLabeled + Serialize>(resource: &R) -> Vec<Coding> {
+    Classifier::default().labels(resource)
+}
+
+/// Adds the labels derived with [`sensitivity`] to `resource`; returns how
+/// many were added.
+pub fn tag<R: SecurityLabeled + Serialize>(resource: &mut R) -> usize {
+    Classifier::default().tag(resource)
+}
@@ -0,0 +1,325 @@
+//! SQLite persistence for WellAlly resources.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//!
+//! Resources are stored in the relational schema documented in
+//! `sql/sqlite/schema.sql`. Every write stamps the row with a strictly
+//! increasing `updatedAt`, which doubles as the cursor for incremental sync.
+
+use std::fmt;
+use std::path::Path;
+
+use chrono::{DateTime, TimeZone, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+use crate::error::WellAllyError;
+use crate::io::AnyResource;
+use crate::resource::Resource;
+use crate::sync::{ChangeSet, SyncStore};
+
+/// SQL executed by [`SqliteStore::migrate`].
+pub const SCHEMA: &str = include_str!("../sql/sqlite/schema.sql");
+
+/// Errors raised by the SQLite store.
+#[derive(Debug)]
+pub enum StoreError {
+    /// Underlying SQLite failure
+    Sqlite(rusqlite::Error),
+    /// A resource could not be serialized
+    Json(serde_json::Error),
+    /// A stored document does not match the requested resource type
+    Document(WellAllyError),
+}
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StoreError::Sqlite(err) => write!(f, "sqlite error: {}", err),
+            StoreError::Json(err) => write!(f, "cannot serialize resource: {}", err),
+            StoreError::Document(err) => write!(f, "invalid stored document: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for StoreError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            StoreError::Sqlite(err) => Some(err),
+            StoreError::Json(err) => Some(err),
+            StoreError::Document(err) => Some(err),
+        }
+    }
+}
+
+impl From<rusqlite::Error> for StoreError {
+    fn from(err: rusqlite::Error) -> Self {
+        StoreError::Sqlite(err)
+    }
+}
+
+impl From<serde_json::Error> for StoreError {
+    fn from(err: serde_json::Error) -> Self {
+        StoreError::Json(err)
+    }
+}
+
+impl From<WellAllyError> for StoreError {
+    fn from(err: WellAllyError) -> Self {
+        StoreError::Document(err)
+    }
+}
+
+/// Result type of store operations.
+pub type StoreResult<T> = Result<T, StoreError>;
+
+/// Position in a store's change feed.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SyncCursor {
+    /// Last `updatedAt` that has been consumed
+    #[serde(rename = "updatedAt")]
+    pub updated_at: DateTime<Utc>,
+}
+
+impl SyncCursor {
+    /// Cursor positioned before any change.
+    pub fn start() -> Self {
+        Self { updated_at: DateTime::<Utc>::UNIX_EPOCH }
+    }
+}
+
+impl Default for SyncCursor {
+    fn default() -> Self {
+        Self::start()
+    }
+}
+
+/// A single entry of the change feed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change<R> {
+    /// The resource was created or updated
+    Saved(R),
+    /// The resource with this id was deleted
+    Deleted(String),
+}
+
+/// Changes returned by [`SqliteStore::changes_since`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChangeBatch<R> {
+    /// Changes in `updatedAt` order
+    pub changes: Vec<Change<R>>,
+    /// Cursor to pass to the next call
+    pub cursor: SyncCursor,
+}
+
+/// Local resource store backed by a SQLite database.
+pub struct SqliteStore {
+    conn: Connection,
+}
+
+impl SqliteStore {
+    /// Opens (or creates) a database file and applies the schema.
+    pub fn open<P: AsRef<Path>>(path: P) -> StoreResult<Self> {
+        Self::from_connection(Connection::open(path)?)
+    }
+
+    /// Opens a private in-memory database.
+    pub fn open_in_memory() -> StoreResult<Self> {
+        Self::from_connection(Connection::open_in_memory()?)
+    }
+
+    /// Wraps an existing connection and applies the schema.
+    pub fn from_connection(conn: Connection) -> StoreResult<Self> {
+        let store = Self { conn };
+        store.migrate()?;
+        Ok(store)
+    }
+
+    /// Creates the tables and indexes if they do not exist yet.
+    pub fn migrate(&self) -> StoreResult<()> {
+        self.conn.execute_batch(SCHEMA)?;
+        Ok(())
+    }
+
+    /// Underlying connection.
+    pub fn connection(&self) -> &Connection {
+        &self.conn
+    }
+
+    /// Inserts or replaces a resource, returning its new `updatedAt`.
+    pub fn save<R: Resource>(&self, resource: &R) -> StoreResult<DateTime<Utc>> {
+        self.write(R::RESOURCE_TYPE, resource.id(), resource.patient_id(), &serde_json::to_string(resource)?)
+    }
+
+    fn write(&self, resource_type: &str, id: &str, patient_id: &str, body: &str) -> StoreResult<DateTime<Utc>> {
+        let updated_at = self.next_timestamp()?;
+        self.conn.execute(
+            "INSERT INTO resources (resource_type, id, patient_id, updated_at, deleted, body)
+             VALUES (?1, ?2, ?3, ?4, 0, ?5)
+             ON CONFLICT (resource_type, id) DO UPDATE SET
+                 patient_id = excluded.patient_id,
+                 updated_at = excluded.updated_at,
+                 deleted = 0,
+                 body = excluded.body",
+            params![resource_type, id, patient_id, updated_at, body],
+        )?;
+        Ok(millis_to_datetime(updated_at))
+    }
+
+    /// Loads a resource by id; deleted resources are not returned.
+    pub fn load<R: Resource>(&self, id: &str) -> StoreResult<Option<R>> {
+        let body: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT body FROM resources
+                 WHERE resource_type = ?1 AND id = ?2 AND deleted = 0",
+                params![R::RESOURCE_TYPE, id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        body.map(|body| R::from_json(&body)).transpose().map_err(Into::into)
+    }
+
+    /// Loads every live resource of type `R` belonging to a patient.
+    pub fn load_by_patient<R: Resource>(&self, patient_id: &str) -> StoreResult<Vec<R>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT body FROM resources
+             WHERE resource_type = ?1 AND patient_id = ?2 AND deleted = 0
+             ORDER BY id",
+        )?;
+        let rows = stmt.query_map(params![R::RESOURCE_TYPE, patient_id], |row| {
+            row.get::<_, String>(0)
+        })?;
+        let mut resources = Vec::new();
+        for body in rows {
+            resources.push(R::from_json(&body?)?);
+        }
+        Ok(resources)
+    }
+
+    /// Deletes a resource, keeping a tombstone for sync. Returns whether it existed.
+    pub fn delete<R: Resource>(&self, id: &str) -> StoreResult<bool> {
+        self.tombstone(R::RESOURCE_TYPE, id)
+    }
+
+    fn tombstone(&self, resource_type: &str, id: &str) -> StoreResult<bool> {
+        let updated_at = self.next_timestamp()?;
+        let count = self.conn.execute(
+            "UPDATE resources SET deleted = 1, body = NULL, updated_at = ?3
+             WHERE resource_type = ?1 AND id = ?2 AND deleted = 0",
+            params![resource_type, id, updated_at],
+        )?;
+        Ok(count > 0)
+    }
+
+    /// Returns up to `limit` changes of type `R` made after `cursor`.
+    pub fn changes_since<R: Resource>(
+        &self,
+        cursor: SyncCursor,
+        limit: usize,
+    ) -> StoreResult<ChangeBatch<R>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, updated_at, deleted, body FROM resources
+             WHERE resource_type = ?1 AND updated_at > ?2
+             ORDER BY updated_at
+             LIMIT ?3",
+        )?;
+        let limit = i64::try_from(limit).unwrap_or(i64::MAX);
+        let rows = stmt.query_map(
+            params![R::RESOURCE_TYPE, cursor.updated_at.timestamp_millis(), limit],
+            |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, i64>(1)?,
+                    row.get::<_, bool>(2)?,
+                    row.get::<_, Option<String>>(3)?,
+                ))
+            },
+        )?;
+
+        let mut batch = ChangeBatch { changes: Vec::new(), cursor };
+        for row in rows {
+            let (id, updated_at, deleted, body) = row?;
+            let change = match body {
+                Some(body) if !deleted => Change::Saved(R::from_json(&body)?),
+                _ => Change::Deleted(id),
+            };
+            batch.changes.push(change);
+            batch.cursor = SyncCursor { updated_at: millis_to_datetime(updated_at) };
+        }
+        Ok(batch)
+    }
+
+    /// Returns up to `limit` changes of every type made after `cursor` as a
+    /// [`ChangeSet`] for [`crate::sync`], with the cursor to resume from.
+    pub fn changeset_since(&self, cursor: SyncCursor, limit: usize) -> StoreResult<ChangeSet> {
+        let mut stmt = self.conn.prepare(
+            "SELECT resource_type, id, updated_at, deleted, body FROM resources
+             WHERE updated_at > ?1
+             ORDER BY updated_at
+             LIMIT ?2",
+        )?;
+        let limit = i64::try_from(limit).unwrap_or(i64::MAX);
+        let rows = stmt.query_map(params![cursor.updated_at.timestamp_millis(), limit], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, i64>(2)?,
+                row.get::<_, bool>(3)?,
+                row.get::<_, Option<String>>(4)?,
+            ))
+        })?;
+
+        let mut changes = ChangeSet { cursor: Some(cursor.updated_at), ..ChangeSet::default() };
+        for row in rows {
+            let (resource_type, id, updated_at, deleted, body) = row?;
+            match body {
+                Some(body) if !deleted => {
+                    let value = serde_json::from_str(&body)?;
+                    changes.upserts.push(AnyResource::from_typed_json_value(&resource_type, &value)?);
+                }
+                _ => changes.deletes.push((resource_type, id)),
+            }
+            changes.cursor = Some(millis_to_datetime(updated_at));
+        }
+        Ok(changes)
+    }
+
+    /// Current time in milliseconds, bumped past the latest write so cursors never skip rows.
+    fn next_timestamp(&self) -> StoreResult<i64> {
+        let latest: Option<i64> =
+            self.conn.query_row("SELECT MAX(updated_at) FROM resources", [], |row| row.get(0))?;
+        let now = Utc::now().timestamp_millis();
+        Ok(latest.map_or(now, |latest| now.max(latest + 1)))
+    }
+}
+
+impl SyncStore for SqliteStore {
+    type Error = StoreError;
+
+    fn last_updated(&self, resource_type: &str, id: &str) -> StoreResult<Option<DateTime<Utc>>> {
+        let updated_at: Option<i64> = self
+            .conn
+            .query_row(
+                "SELECT updated_at FROM resources WHERE resource_type = ?1 AND id = ?2",
+                params![resource_type, id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(updated_at.map(millis_to_datetime))
+    }
+
+    fn upsert(&mut self, resource: &AnyResource) -> StoreResult<()> {
+        let body = serde_json::to_string(resource)?;
+        self.write(resource.resource_type(), resource.id(), resource.patient_id(), &body).map(drop)
+    }
+
+    fn remove(&mut self, resource_type: &str, id: &str) -> StoreResult<bool> {
+        self.tombstone(resource_type, id)
+    }
+}
+
+fn millis_to_datetime(millis: i64) -> DateTime<Utc> {
+    Utc.timestamp_millis_opt(millis).single().unwrap_or(DateTime::<Utc>::UNIX_EPOCH)
+}
@@ -0,0 +1,1042 @@
+//! Proptest strategies producing structurally valid resources.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//!
+//! Generated values use real codes from [`crate::catalog`], keep dates in a
+//! coherent order (birth before care, start before end) and draw quantities
+//! from plausible clinical spreads, so they pass [`crate::validation`] and
+//! exercise downstream pipelines with realistic shapes. Every top-level
+//! resource also implements [`Arbitrary`], so `any::<LabReport>()` works, and
+//! [`any_resource`] draws from every type [`AnyResource`] can hold.
+
+use std::ops::RangeInclusive;
+
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, TimeZone, Utc};
+use proptest::arbitrary::Arbitrary;
+use proptest::collection::vec;
+use proptest::prelude::*;
+use proptest::sample::{select, subsequence};
+use proptest::strategy::Union;
+
+use crate::allergy::{AllergyCategory, AllergyIntolerance};
+use crate::audiology::{AudiogramReport, Ear, EarResult, Threshold};
+use crate::birth::{BirthRecord, GestationalAge};
+use crate::catalog::{self, coding, Analyte, ConditionCode, Panel};
+use crate::cgm::GlucoseSeries;
+use crate::clearance::{ClearanceDocument, ClearanceType};
+use crate::common::{HumanName, Modality, ModalityCode, Period, Quantity, Reference, RequestPriority, Route};
+use crate::communication::{CommunicationRecord, CommunicationStatus, MessagePayload};
+use crate::compendium::{Compendium, TestDefinition};
+use crate::condition::{ClinicalStatus, Condition};
+use crate::consent::{ConsentDecision, ConsentRecord};
+use crate::dispense::DispenseRecord;
+use crate::ecg::{EcgLead, EcgLeadName, EcgRecord};
+use crate::family_health::{FamilyHealthTree, FamilyMember, RelationToProband, Sex};
+use crate::flag::{Flag, FlagCategory};
+use crate::health::{Gender, Person};
+use crate::imaging_report::{ImagingReport, Performer, RadiationDose};
+use crate::immunization::{ImmunizationRecord, CVX};
+use crate::infusion::InfusionRecord;
+use crate::io::AnyResource;
+use crate::lab_report::{Facility, LabReport, LabResult};
+use crate::location::{DayOfWeek, Location, LocationStatus, OperatingHours, Position};
+use crate::medication::MedicationRecord;
+use crate::nephrology::{AccessType, DialysisAccess, DialysisModality, DialysisSession};
+use crate::pathology::{PathologyReport, PathologySpecimen};
+use crate::questionnaire::{
+    AnswerOption, AnswerValue, ItemType, Questionnaire, QuestionnaireItem, QuestionnaireResponse, QuestionnaireStatus,
+    ResponseItem, ResponseStatus,
+};
+use crate::referral::{Referral, ReferralParty, ReferralStatus};
+use crate::rehab::{Exercise, RehabSession};
+use crate::relationships::{RelatedPerson, RelationshipRole};
+use crate::task::{Task, TaskStatus};
+use crate::travel::{Destination, TravelHealthRecord};
+use crate::wound::{WoundAssessment, WoundDimensions};
+
+/// Identifier with a prefix and a random numeric suffix (e.g., "lab-004211").
+pub fn id(prefix: &'static str) -> impl Strategy<Value = String> {
+    (0u32..1_000_000).prop_map(move |n| format!("{}-{:06}", prefix, n))
+}
+
+/// Calendar date between two dates (inclusive).
+pub fn date_between(start: NaiveDate, end: NaiveDate) -> impl Strategy<Value = NaiveDate> {
+    let days = (end - start).num_days().max(0);
+    (0..=days).prop_map(move |offset| start + Duration::days(offset))
+}
+
+/// UTC instant between two dates, at minute precision.
+pub fn datetime_between(start: NaiveDate, end: NaiveDate) -> impl Strategy<Value = DateTime<Utc>> {
+    let from = Utc.from_utc_datetime(&start.and_hms_opt(0, 0, 0).unwrap()).timestamp() / 60;
+    let to = Utc.from_utc_datetime(&end.and_hms_opt(23, 59, 0).unwrap()).timestamp() / 60;
+    (from..=to).prop_map(|minutes| Utc.timestamp_opt(minutes * 60, 0).unwrap())
+}
+
+fn care_period() -> (NaiveDate, NaiveDate) {
+    (NaiveDate::from_ymd_opt(2015, 1, 1).unwrap(), NaiveDate::from_ymd_opt(2025, 12, 31).unwrap())
+}
+
+/// Person with a realistic name, gender and birth date between 1930 and 2020.
+pub fn person() -> impl Strategy<Value = Person> {
+    let born = date_between(
+        NaiveDate::from_ymd_opt(1930, 1, 1).unwrap(),
+        NaiveDate::from_ymd_opt(2020, 12, 31).unwrap(),
+    );
+    (id("person"), any::<bool>(), select(catalog::FAMILY_NAMES), any::<usize>(), born).prop_map(
+        |(id, female, family, given, birth_date)| {
+            let names = if female { catalog::FEMALE_GIVEN_NAMES } else { catalog::MALE_GIVEN_NAMES };
+            let given = names[given % names.len()];
+            Person {
+                id,
+                name: vec![HumanName {
+                    family: family.to_string(),
+                    given: vec![given.to_string()],
+                    r#use: None,
+                    prefix: None,
+                    suffix: None,
+                }],
+                birth_date: birth_date.into(),
+                gender: Some(if female { Gender::Female } else { Gender::Male }),
+                ..Default::default()
+            }
+        },
+    )
+}
+
+/// Result for one analyte, with interpretation consistent with its reference range.
+pub fn analyte_result(analyte: Analyte) -> impl Strategy<Value = LabResult> {
+    (analyte.min..analyte.max).prop_map(move |value| analyte.result(value))
+}
+
+/// All results of a panel.
+pub fn panel_results(panel: Panel) -> impl Strategy<Value = Vec<LabResult>> {
+    panel.analytes.iter().map(|analyte| analyte_result(*analyte)).collect::<Vec<_>>()
+}
+
+/// Lab report for one of the catalog panels, belonging to `patient_id`.
+pub fn lab_report_for(patient_id: String) -> impl Strategy<Value = LabReport> {
+    let (start, end) = care_period();
+    (id("lab"), select(catalog::PANELS), datetime_between(start, end), 1i64..72)
+        .prop_flat_map(move |(id, panel, collected_at, turnaround_hours)| {
+            let patient_id = patient_id.clone();
+            panel_results(panel).prop_map(move |results| LabReport {
+                facility: Some(Facility { id: Some("lab-central".into()), name: Some("Central Laboratory".into()), location: None }),
+                ..panel.report(
+                    id.clone(),
+                    patient_id.clone(),
+                    collected_at,
+                    collected_at + Duration::hours(turnaround_hours),
+                    results,
+                )
+            })
+        })
+}
+
+/// Lab report for a random patient.
+pub fn lab_report() -> impl Strategy<Value = LabReport> {
+    id("person").prop_flat_map(lab_report_for)
+}
+
+/// Imaging report belonging to `patient_id`; CT studies carry a radiation dose.
+pub fn imaging_report_for(patient_id: String) -> impl Strategy<Value = ImagingReport> {
+    let (start, end) = care_period();
+    (
+        id("img"),
+        select(catalog::IMAGING_STUDIES),
+        datetime_between(start, end),
+        1.0f64..20.0,
+        2.0f64..50.0,
+        any::<bool>(),
+    )
+        .prop_map(move |(id, (modality, modality_display, site, site_display), reported_at, ctdi, length_cm, normal)| {
+            let code = ModalityCode::from_code(modality);
+            let radiation_dose = (code == ModalityCode::CT).then(|| RadiationDose {
+                ctdi_vol_mgy: Some((ctdi * 10.0).round() / 10.0),
+                dlp_mgy_cm: Some((ctdi * length_cm * 10.0).round() / 10.0),
+            });
+            ImagingReport {
+                id,
+                patient_id: patient_id.clone(),
+                modality: Modality {
+                    system: catalog::DICOM.to_string(),
+                    code,
+                    display: Some(modality_display.to_string()),
+                },
+                body_site: coding(catalog::SNOMED_CT, site, site_display).into(),
+                reported_at: reported_at.into(),
+                study_instance_uid: None,
+                performer: Some(Performer {
+                    id: Some("rad-001".into()),
+                    name: Some("Dr. Chen".into()),
+                    role: Some("Radiologist".into()),
+                }),
+                based_on: None,
+                reason_code: None,
+                clinical_history: None,
+                protocol: None,
+                technique: None,
+                comparison: None,
+                comparison_study_uids: Vec::new(),
+                change_from_prior: None,
+                findings: Some(vec![if normal {
+                    format!("{}: no abnormality detected.", site_display)
+                } else {
+                    format!("{}: small nonspecific lesion, follow-up suggested.", site_display)
+                }]),
+                impression: Some(if normal { "Normal study." } else { "Indeterminate finding." }.to_string()),
+                radiation_dose,
+                measurements: None,
+                attachments: None,
+                notes: None,
+                schema_version: None,
+                security_labels: None,
+                extensions: None,
+                #[cfg(feature = "preserve-unknown")]
+                extra: Default::default(),
+            }
+        })
+}
+
+/// Imaging report for a random patient.
+pub fn imaging_report() -> impl Strategy<Value = ImagingReport> {
+    id("person").prop_flat_map(imaging_report_for)
+}
+
+/// Medication record for a catalog drug; end date follows start by the duration.
+pub fn medication_record_for(patient_id: String) -> impl Strategy<Value = MedicationRecord> {
+    let (start, end) = care_period();
+    (id("med"), select(catalog::DRUGS), date_between(start, end), 7i32..=365)
+        .prop_map(move |(id, drug, start_date, days)| drug.record(id, patient_id.clone(), start_date, days))
+}
+
+/// Medication record for a random patient.
+pub fn medication_record() -> impl Strategy<Value = MedicationRecord> {
+    id("person").prop_flat_map(medication_record_for)
+}
+
+/// Relative of the proband with a sex and birth year consistent with the relation.
+fn relative(proband_year: i32) -> impl Strategy<Value = FamilyMember> {
+    let relations: Vec<(RelationToProband, RangeInclusive<i32>, Option<Sex>)> = vec![
+        (RelationToProband::Mother, -40..=-18, Some(Sex::Female)),
+        (RelationToProband::Father, -45..=-18, Some(Sex::Male)),
+        (RelationToProband::Sibling, -12..=12, None),
+        (RelationToProband::Child, 18..=40, None),
+        (RelationToProband::Grandparent, -80..=-40, None),
+        (RelationToProband::Aunt, -50..=-10, Some(Sex::Female)),
+        (RelationToProband::Uncle, -50..=-10, Some(Sex::Male)),
+        (RelationToProband::Cousin, -15..=15, None),
+    ];
+    select(relations)
+        .prop_flat_map(|(relation, offsets, sex)| {
+            (Just(relation), offsets, Just(sex), id("member"), any::<bool>(), vec(select(catalog::CONDITIONS), 0..=2))
+        })
+        .prop_map(move |(relation, offset, sex, id, female, conditions)| {
+            let birth_year = (proband_year + offset).clamp(1900, 2100);
+            FamilyMember {
+                id,
+                person_id: None,
+                relation_to_proband: relation,
+                mother_id: None,
+                father_id: None,
+                sex: Some(sex.unwrap_or(if female { Sex::Female } else { Sex::Male })),
+                birth_year: Some(birth_year),
+                deceased: Some(birth_year < 1940),
+                conditions: Some(conditions.iter().map(ConditionCode::concept).collect()),
+                condition_onsets: Vec::new(),
+                notes: None,
+            }
+        })
+}
+
+/// Family tree rooted at a proband born in `proband_year`, with up to eight relatives.
+pub fn family_health_tree_for(proband_id: String, proband_year: i32) -> impl Strategy<Value = FamilyHealthTree> {
+    (any::<bool>(), vec(relative(proband_year), 1..=8)).prop_map(move |(female, relatives)| {
+        let proband = FamilyMember {
+            id: proband_id.clone(),
+            person_id: None,
+            relation_to_proband: RelationToProband::Self_,
+            mother_id: None,
+            father_id: None,
+            sex: Some(if female { Sex::Female } else { Sex::Male }),
+            birth_year: Some(proband_year),
+            deceased: Some(false),
+            conditions: None,
+            condition_onsets: Vec::new(),
+            notes: None,
+        };
+        FamilyHealthTree {
+            proband_id: proband_id.clone(),
+            members: std::iter::once(proband).chain(relatives).collect(),
+            schema_version: None,
+            security_labels: None,
+            extensions: None,
+            #[cfg(feature = "preserve-unknown")]
+            extra: Default::default(),
+        }
+    })
+}
+
+/// Family tree for a random proband.
+pub fn family_health_tree() -> impl Strategy<Value = FamilyHealthTree> {
+    (id("person"), 1950i32..=2015).prop_flat_map(|(id, year)| family_health_tree_for(id, year))
+}
+
+/// Reference to the resource `id` of type `resource_type` (e.g., "Practitioner/pract-000042").
+fn reference(resource_type: &'static str, id: String) -> Reference {
+    Reference { reference: format!("{}/{}", resource_type, id), display: None }
+}
+
+/// Date `days` after `date`, for offsets the strategies keep small.
+fn after(date: NaiveDate, days: u32) -> NaiveDate {
+    date + Duration::days(i64::from(days))
+}
+
+/// Scored answers shared by the generated questionnaire items (PHQ style).
+const FREQUENCY_ANSWERS: &[&str] = &["Not at all", "Several days", "More than half the days", "Nearly every day"];
+
+/// Active screening questionnaire of one to nine scored choice questions.
+pub fn questionnaire() -> impl Strategy<Value = Questionnaire> {
+    (id("questionnaire"), 1u32..=9).prop_map(|(id, questions)| Questionnaire {
+        url: Some(format!("https://www.wellally.tech/Questionnaire/{}", id)),
+        id,
+        version: Some("1.0.0".into()),
+        title: Some("Mood screening".into()),
+        status: QuestionnaireStatus::Active,
+        code: None,
+        item: (1..=questions)
+            .map(|n| QuestionnaireItem {
+                link_id: format!("q{}", n),
+                text: Some(format!("Question {}", n)),
+                r#type: ItemType::Choice,
+                code: None,
+                required: Some(true),
+                repeats: None,
+                answer_option: Some(
+                    FREQUENCY_ANSWERS
+                        .iter()
+                        .zip(0..)
+                        .map(|(answer, score)| AnswerOption {
+                            value: AnswerValue::String(answer.to_string()),
+                            initial_selected: None,
+                            score: Some(f64::from(score)),
+                        })
+                        .collect(),
+                ),
+                enable_when: None,
+                enable_behavior: None,
+                item: None,
+            })
+            .collect(),
+        schema_version: None,
+        security_labels: None,
+        extensions: None,
+        #[cfg(feature = "preserve-unknown")]
+        extra: Default::default(),
+    })
+}
+
+/// Completed answers of `patient_id` to a screening questionnaire.
+pub fn questionnaire_response_for(patient_id: String) -> impl Strategy<Value = QuestionnaireResponse> {
+    let (start, end) = care_period();
+    (id("response"), id("questionnaire"), datetime_between(start, end), vec(select(FREQUENCY_ANSWERS), 1..=9)).prop_map(
+        move |(id, questionnaire, authored, answers)| QuestionnaireResponse {
+            id,
+            patient_id: patient_id.clone(),
+            questionnaire,
+            status: ResponseStatus::Completed,
+            authored: Some(authored.into()),
+            item: answers
+                .iter()
+                .zip(1..)
+                .map(|(answer, n)| ResponseItem {
+                    link_id: format!("q{}", n),
+                    text: None,
+                    answer: Some(vec![AnswerValue::String(answer.to_string())]),
+                    item: None,
+                })
+                .collect(),
+            schema_version: None,
+            security_labels: None,
+            extensions: None,
+            #[cfg(feature = "preserve-unknown")]
+            extra: Default::default(),
+        },
+    )
+}
+
+/// Questionnaire response for a random patient.
+pub fn questionnaire_response() -> impl Strategy<Value = QuestionnaireResponse> {
+    id("person").prop_flat_map(questionnaire_response_for)
+}
+
+/// Referral of `patient_id` to a specialist clinic for a catalog condition.
+pub fn referral_for(patient_id: String) -> impl Strategy<Value = Referral> {
+    let (start, end) = care_period();
+    let statuses =
+        vec![ReferralStatus::Draft, ReferralStatus::Active, ReferralStatus::OnHold, ReferralStatus::Completed];
+    (id("referral"), select(statuses), datetime_between(start, end), select(catalog::CONDITIONS)).prop_map(
+        move |(id, status, authored_on, reason)| Referral {
+            id,
+            patient_id: patient_id.clone(),
+            status,
+            priority: Some(RequestPriority::Routine),
+            authored_on: authored_on.into(),
+            requester: ReferralParty {
+                practitioner: Some(Performer { id: Some("gp-001".into()), name: Some("Dr. Wang".into()), role: None }),
+                organization: None,
+            },
+            recipient: Some(ReferralParty {
+                practitioner: None,
+                organization: Some(Facility {
+                    id: Some("clinic-specialist".into()),
+                    name: Some("Specialist Clinic".into()),
+                    location: None,
+                }),
+            }),
+            service_requested: Some(catalog::concept(catalog::SNOMED_CT, "3457005", "Patient referral")),
+            reason_code: Some(vec![reason.concept()]),
+            supporting_info: None,
+            description: Some(format!("Please assess: {}.", reason.display)),
+            schema_version: None,
+            security_labels: None,
+            extensions: None,
+            #[cfg(feature = "preserve-unknown")]
+            extra: Default::default(),
+        },
+    )
+}
+
+/// Referral for a random patient.
+pub fn referral() -> impl Strategy<Value = Referral> {
+    id("person").prop_flat_map(referral_for)
+}
+
+/// Task to repeat a catalog panel, due one to ninety days after it was written.
+pub fn task_for(patient_id: String) -> impl Strategy<Value = Task> {
+    let (start, end) = care_period();
+    let statuses = vec![TaskStatus::Requested, TaskStatus::Accepted, TaskStatus::InProgress, TaskStatus::Completed];
+    (id("task"), select(statuses), select(catalog::PANELS), datetime_between(start, end), 1u32..=90).prop_map(
+        move |(id, status, panel, authored_on, due_in)| Task {
+            id,
+            patient_id: patient_id.clone(),
+            status,
+            priority: Some(RequestPriority::Routine),
+            description: format!("Repeat {}", panel.display),
+            code: Some(catalog::concept(catalog::LOINC, panel.code, panel.display)),
+            owner: None,
+            authored_on: Some(authored_on.into()),
+            due_date: Some(after(authored_on.date_naive(), due_in).into()),
+            focus: None,
+            based_on: None,
+            status_reason: None,
+            schema_version: None,
+            security_labels: None,
+            extensions: None,
+            #[cfg(feature = "preserve-unknown")]
+            extra: Default::default(),
+        },
+    )
+}
+
+/// Task for a random patient.
+pub fn task() -> impl Strategy<Value = Task> {
+    id("person").prop_flat_map(task_for)
+}
+
+/// Message from `patient_id` to a practitioner, received within two hours.
+pub fn communication_record_for(patient_id: String) -> impl Strategy<Value = CommunicationRecord> {
+    let (start, end) = care_period();
+    let messages = vec![
+        "Could you renew my prescription?",
+        "Are my lab results back?",
+        "I need to move my appointment.",
+        "The new tablets upset my stomach.",
+    ];
+    (id("message"), id("pract"), select(messages), datetime_between(start, end), 0i64..=120).prop_map(
+        move |(id, practitioner, message, sent, delay)| CommunicationRecord {
+            id,
+            patient_id: patient_id.clone(),
+            status: CommunicationStatus::Completed,
+            sender: reference("Person", patient_id.clone()),
+            recipient: vec![reference("Practitioner", practitioner)],
+            sent: Some(sent.into()),
+            received: Some((sent + Duration::minutes(delay)).into()),
+            topic: None,
+            payload: vec![MessagePayload::Text(message.to_string())],
+            in_response_to: None,
+            schema_version: None,
+            security_labels: None,
+            extensions: None,
+            #[cfg(feature = "preserve-unknown")]
+            extra: Default::default(),
+        },
+    )
+}
+
+/// Message for a random patient.
+pub fn communication_record() -> impl Strategy<Value = CommunicationRecord> {
+    id("person").prop_flat_map(communication_record_for)
+}
+
+/// Clinic with coordinates and either round-the-clock or weekday office hours.
+pub fn location() -> impl Strategy<Value = Location> {
+    let names = vec!["Central Hospital", "Community Health Center", "Cardiology Clinic", "Emergency Department"];
+    (id("location"), select(names), -90.0f64..=90.0, -180.0f64..=180.0, any::<bool>()).prop_map(
+        |(id, name, latitude, longitude, all_day)| {
+            let days = if all_day {
+                vec![
+                    DayOfWeek::Mon,
+                    DayOfWeek::Tue,
+                    DayOfWeek::Wed,
+                    DayOfWeek::Thu,
+                    DayOfWeek::Fri,
+                    DayOfWeek::Sat,
+                    DayOfWeek::Sun,
+                ]
+            } else {
+                vec![DayOfWeek::Mon, DayOfWeek::Tue, DayOfWeek::Wed, DayOfWeek::Thu, DayOfWeek::Fri]
+            };
+            Location {
+                id,
+                name: name.to_string(),
+                status: Some(LocationStatus::Active),
+                location_type: None,
+                address: None,
+                position: Some(Position { latitude, longitude, altitude: None }),
+                part_of: None,
+                hours_of_operation: Some(vec![OperatingHours {
+                    days_of_week: days,
+                    all_day: all_day.then_some(true),
+                    opening_time: (!all_day).then(|| NaiveTime::from_hms_opt(8, 0, 0).unwrap()),
+                    closing_time: (!all_day).then(|| NaiveTime::from_hms_opt(17, 0, 0).unwrap()),
+                }]),
+                schema_version: None,
+                security_labels: None,
+                extensions: None,
+                #[cfg(feature = "preserve-unknown")]
+                extra: Default::default(),
+            }
+        },
+    )
+}
+
+/// Catalog condition of `patient_id`; resolved ones abate after their onset.
+pub fn condition_for(patient_id: String) -> impl Strategy<Value = Condition> {
+    let (start, end) = care_period();
+    (id("condition"), select(catalog::CONDITIONS), datetime_between(start, end), proptest::option::of(1i64..=365))
+        .prop_map(move |(id, code, onset, resolved_after)| Condition {
+            clinical_status: if resolved_after.is_some() { ClinicalStatus::Resolved } else { ClinicalStatus::Active },
+            onset: Some(onset.into()),
+            abatement: resolved_after.map(|days| (onset + Duration::days(days)).into()),
+            recorded_date: Some(onset.into()),
+            ..Condition::new(id, patient_id.clone(), code.concept())
+        })
+}
+
+/// Condition for a random patient.
+pub fn condition() -> impl Strategy<Value = Condition> {
+    id("person").prop_flat_map(condition_for)
+}
+
+/// Allergy of `patient_id` to a common drug, food or environmental substance.
+pub fn allergy_intolerance_for(patient_id: String) -> impl Strategy<Value = AllergyIntolerance> {
+    let allergens = vec![
+        (AllergyCategory::Medication, catalog::RXNORM, "7980", "penicillin G"),
+        (AllergyCategory::Food, catalog::SNOMED_CT, "256349002", "Peanut"),
+        (AllergyCategory::Food, catalog::SNOMED_CT, "102263004", "Eggs"),
+        (AllergyCategory::Environment, catalog::SNOMED_CT, "111088007", "Latex"),
+    ];
+    (id("allergy"), select(allergens)).prop_map(move |(id, (category, system, code, display))| AllergyIntolerance {
+        category: Some(vec![category]),
+        ..AllergyIntolerance::new(id, patient_id.clone(), catalog::concept(system, code, display))
+    })
+}
+
+/// Allergy for a random patient.
+pub fn allergy_intolerance() -> impl Strategy<Value = AllergyIntolerance> {
+    id("person").prop_flat_map(allergy_intolerance_for)
+}
+
+/// Clinical flag on a catalog condition of `patient_id`, in effect for up to a year.
+pub fn flag_for(patient_id: String) -> impl Strategy<Value = Flag> {
+    let (start, end) = care_period();
+    (id("flag"), select(catalog::CONDITIONS), date_between(start, end), 0u32..=365).prop_map(
+        move |(id, code, from, days)| Flag {
+            category: Some(FlagCategory::Clinical),
+            period: Some(Period { start: Some(from), end: Some(after(from, days)) }),
+            ..Flag::new(id, patient_id.clone(), code.concept())
+        },
+    )
+}
+
+/// Flag for a random patient.
+pub fn flag() -> impl Strategy<Value = Flag> {
+    id("person").prop_flat_map(flag_for)
+}
+
+/// One to three days of five-minute glucose readings in mg/dL, with occasional gaps.
+pub fn glucose_series_for(patient_id: String) -> impl Strategy<Value = GlucoseSeries> {
+    let (start, end) = care_period();
+    let reading = (40u32..=400).prop_map(f64::from);
+    (id("cgm"), datetime_between(start, end), vec(proptest::option::weighted(0.95, reading), 12..=864)).prop_map(
+        move |(id, from, samples)| {
+            GlucoseSeries::from_samples(id, patient_id.clone(), "mg/dL", from.into(), 300, &samples)
+        },
+    )
+}
+
+/// Glucose series for a random patient.
+pub fn glucose_series() -> impl Strategy<Value = GlucoseSeries> {
+    id("person").prop_flat_map(glucose_series_for)
+}
+
+const ECG_LEADS: &[EcgLeadName] = &[
+    EcgLeadName::I,
+    EcgLeadName::II,
+    EcgLeadName::III,
+    EcgLeadName::AVR,
+    EcgLeadName::AVL,
+    EcgLeadName::AVF,
+    EcgLeadName::V1,
+    EcgLeadName::V2,
+    EcgLeadName::V3,
+    EcgLeadName::V4,
+    EcgLeadName::V5,
+    EcgLeadName::V6,
+];
+
+/// ECG of `patient_id` with some of the twelve standard leads sampled in microvolts.
+pub fn ecg_record_for(patient_id: String) -> impl Strategy<Value = EcgRecord> {
+    let (start, end) = care_period();
+    (
+        id("ecg"),
+        datetime_between(start, end),
+        select(vec![250.0, 500.0, 1000.0]),
+        subsequence(ECG_LEADS, 1..=ECG_LEADS.len()),
+        vec(-2000i32..=2000, 10..=100),
+    )
+        .prop_map(move |(id, recorded_at, sampling_rate, leads, data)| EcgRecord {
+            leads: leads.into_iter().map(|lead| EcgLead::microvolts(lead, data.clone())).collect(),
+            ..EcgRecord::new(id, patient_id.clone(), recorded_at.into(), sampling_rate)
+        })
+}
+
+/// ECG for a random patient.
+pub fn ecg_record() -> impl Strategy<Value = EcgRecord> {
+    id("person").prop_flat_map(ecg_record_for)
+}
+
+/// Consent of `patient_id`, signed on the first day of its one- to five-year period.
+pub fn consent_record_for(patient_id: String) -> impl Strategy<Value = ConsentRecord> {
+    let (start, end) = care_period();
+    (id("consent"), select(vec![ConsentDecision::Permit, ConsentDecision::Deny]), date_between(start, end), 1u32..=5)
+        .prop_map(move |(id, decision, signed, years)| ConsentRecord {
+            date: Some(signed),
+            period: Some(Period { start: Some(signed), end: Some(after(signed, 365 * years)) }),
+            ..ConsentRecord::new(id, patient_id.clone(), decision)
+        })
+}
+
+/// Consent for a random patient.
+pub fn consent_record() -> impl Strategy<Value = ConsentRecord> {
+    id("person").prop_flat_map(consent_record_for)
+}
+
+/// Dialysis session of `patient_id` through an access matching the modality,
+/// with the weight loss equal to the ultrafiltration volume.
+pub fn dialysis_session_for(patient_id: String) -> impl Strategy<Value = DialysisSession> {
+    let (start, end) = care_period();
+    let modalities = vec![
+        DialysisModality::Hemodialysis,
+        DialysisModality::Hemodiafiltration,
+        DialysisModality::Hemofiltration,
+        DialysisModality::Capd,
+        DialysisModality::Apd,
+    ];
+    (
+        id("dialysis"),
+        select(modalities),
+        datetime_between(start, end),
+        30u32..=300,
+        45u32..=120,
+        0u32..=4000,
+        10u32..=20,
+    )
+        .prop_map(move |(id, modality, from, minutes, pre_kg, ultrafiltration_ml, kt_v)| {
+            let access_type = if modality.is_peritoneal() {
+                AccessType::PeritonealCatheter
+            } else {
+                AccessType::ArteriovenousFistula
+            };
+            let pre_weight = f64::from(pre_kg);
+            DialysisSession {
+                access: Some(DialysisAccess { access_type, site: None }),
+                ultrafiltration_volume: Some(Quantity::new(f64::from(ultrafiltration_ml), "mL")),
+                pre_weight: Some(Quantity::new(pre_weight, "kg")),
+                post_weight: Some(Quantity::new(pre_weight - f64::from(ultrafiltration_ml) / 1000.0, "kg")),
+                kt_v: Some(f64::from(kt_v) / 10.0),
+                ..DialysisSession::new(id, patient_id.clone(), modality, from.into(), minutes)
+            }
+        })
+}
+
+/// Dialysis session for a random patient.
+pub fn dialysis_session() -> impl Strategy<Value = DialysisSession> {
+    id("person").prop_flat_map(dialysis_session_for)
+}
+
+/// Physiotherapy session of `patient_id` with a few exercises and pain no worse afterwards.
+pub fn rehab_session_for(patient_id: String) -> impl Strategy<Value = RehabSession> {
+    let (start, end) = care_period();
+    let exercises = vec!["Squat", "Bridge", "Step-up", "Heel raise", "Shoulder press"];
+    (
+        id("rehab"),
+        datetime_between(start, end),
+        20u32..=90,
+        subsequence(exercises, 1..=3),
+        1u32..=4,
+        5u32..=15,
+        0u8..=10,
+        0u8..=10,
+    )
+        .prop_map(move |(id, from, minutes, exercises, sets, repetitions, pain, relief)| RehabSession {
+            duration_minutes: Some(minutes),
+            exercises: exercises.into_iter().map(|name| Exercise::new(name).with_sets(sets, repetitions)).collect(),
+            pain_before: Some(pain),
+            pain_after: Some(pain.saturating_sub(relief)),
+            ..RehabSession::new(
+                id,
+                patient_id.clone(),
+                coding(catalog::SNOMED_CT, "91251008", "Physical therapy procedure"),
+                from.into(),
+            )
+        })
+}
+
+/// Rehab session for a random patient.
+pub fn rehab_session() -> impl Strategy<Value = RehabSession> {
+    id("person").prop_flat_map(rehab_session_for)
+}
+
+/// Assessment of a pressure wound of `patient_id` measuring up to 10 × 10 cm.
+pub fn wound_assessment_for(patient_id: String) -> impl Strategy<Value = WoundAssessment> {
+    let (start, end) = care_period();
+    let sites = vec![("54735007", "Sacral region"), ("76853006", "Heel"), ("29850006", "Ischial tuberosity")];
+    (id("wound-assessment"), id("wound"), select(sites), datetime_between(start, end), 1u32..=100, 1u32..=100).prop_map(
+        move |(id, wound_id, (code, display), assessed_at, length_mm, width_mm)| WoundAssessment {
+            dimensions: Some(WoundDimensions::new(
+                Quantity::new(f64::from(length_mm) / 10.0, "cm"),
+                Quantity::new(f64::from(width_mm) / 10.0, "cm"),
+            )),
+            ..WoundAssessment::new(
+                id,
+                patient_id.clone(),
+                wound_id,
+                coding(catalog::SNOMED_CT, code, display),
+                assessed_at.into(),
+            )
+        },
+    )
+}
+
+/// Wound assessment for a random patient.
+pub fn wound_assessment() -> impl Strategy<Value = WoundAssessment> {
+    id("person").prop_flat_map(wound_assessment_for)
+}
+
+/// Running intravenous infusion of saline for `patient_id` at 10 to 250 mL/h.
+pub fn infusion_record_for(patient_id: String) -> impl Strategy<Value = InfusionRecord> {
+    let (start, end) = care_period();
+    (id("infusion"), datetime_between(start, end), 10u32..=250).prop_map(move |(id, from, rate)| {
+        InfusionRecord::new(
+            id,
+            patient_id.clone(),
+            coding(catalog::RXNORM, "313002", "sodium chloride 0.9 % Injectable Solution"),
+            Route::intravenous(),
+            from.into(),
+            Quantity::new(f64::from(rate), "mL/h"),
+        )
+    })
+}
+
+/// Infusion for a random patient.
+pub fn infusion_record() -> impl Strategy<Value = InfusionRecord> {
+    id("person").prop_flat_map(infusion_record_for)
+}
+
+/// Pathology report of `patient_id` on one to four specimens lettered A, B, ...
+pub fn pathology_report_for(patient_id: String) -> impl Strategy<Value = PathologyReport> {
+    let (start, end) = care_period();
+    (id("pathology"), datetime_between(start, end), 1u8..=4).prop_map(move |(id, issued_at, count)| {
+        let specimens = (b'A'..b'A' + count).map(|letter| PathologySpecimen::new(char::from(letter).to_string()));
+        PathologyReport::new(id, patient_id.clone(), issued_at.into(), specimens.collect())
+    })
+}
+
+/// Pathology report for a random patient.
+pub fn pathology_report() -> impl Strategy<Value = PathologyReport> {
+    id("person").prop_flat_map(pathology_report_for)
+}
+
+/// Pure-tone air-conduction audiogram of `patient_id` at the standard octave
+/// frequencies, for one or both ears, in 5 dB steps.
+pub fn audiogram_report_for(patient_id: String) -> impl Strategy<Value = AudiogramReport> {
+    const FREQUENCIES: [u32; 6] = [250, 500, 1000, 2000, 4000, 8000];
+    let (start, end) = care_period();
+    let ear = vec(-2i32..=18, FREQUENCIES.len());
+    (id("audiogram"), datetime_between(start, end), subsequence(vec![Ear::Left, Ear::Right], 1..=2), ear.clone(), ear)
+        .prop_map(move |(id, tested_at, ears, left, right)| AudiogramReport {
+            ears: ears
+                .into_iter()
+                .map(|ear| {
+                    let steps = if ear == Ear::Left { &left } else { &right };
+                    EarResult {
+                        thresholds: FREQUENCIES
+                            .iter()
+                            .zip(steps)
+                            .map(|(frequency, step)| Threshold::air(*frequency, f64::from(step * 5)))
+                            .collect(),
+                        ..EarResult::new(ear)
+                    }
+                })
+                .collect(),
+            ..AudiogramReport::new(id, patient_id.clone(), tested_at.into())
+        })
+}
+
+/// Audiogram for a random patient.
+pub fn audiogram_report() -> impl Strategy<Value = AudiogramReport> {
+    id("person").prop_flat_map(audiogram_report_for)
+}
+
+/// Birth of `patient_id` to another person, between 34 and 42 weeks of gestation.
+pub fn birth_record_for(patient_id: String) -> impl Strategy<Value = BirthRecord> {
+    let (start, end) = care_period();
+    let mother = patient_id.clone();
+    (
+        id("birth"),
+        id("person").prop_filter("the mother is not the newborn", move |id| *id != mother),
+        datetime_between(start, end),
+        34u32..=42,
+        0u32..=6,
+        1800u32..=4500,
+    )
+        .prop_map(move |(id, mother_id, born_at, weeks, days, grams)| BirthRecord {
+            birth_order: Some(1),
+            birth_weight: Some(Quantity::new(f64::from(grams), "g")),
+            ..BirthRecord::new(id, patient_id.clone(), mother_id, born_at.into(), GestationalAge::new(weeks, days))
+        })
+}
+
+/// Birth record for a random newborn.
+pub fn birth_record() -> impl Strategy<Value = BirthRecord> {
+    id("person").prop_flat_map(birth_record_for)
+}
+
+/// Unrestricted clearance of `patient_id`, valid for a year.
+pub fn clearance_document_for(patient_id: String) -> impl Strategy<Value = ClearanceDocument> {
+    let (start, end) = care_period();
+    let types = vec![ClearanceType::SportsPhysical, ClearanceType::FitnessForWork, ClearanceType::ReturnToWork];
+    (id("clearance"), select(types), date_between(start, end), id("pract")).prop_map(
+        move |(id, clearance_type, issued_on, clinician)| ClearanceDocument {
+            valid_period: Period { start: Some(issued_on), end: Some(after(issued_on, 365)) },
+            ..ClearanceDocument::new(
+                id,
+                patient_id.clone(),
+                clearance_type,
+                issued_on,
+                reference("Practitioner", clinician),
+            )
+        },
+    )
+}
+
+/// Clearance for a random patient.
+pub fn clearance_document() -> impl Strategy<Value = ClearanceDocument> {
+    id("person").prop_flat_map(clearance_document_for)
+}
+
+/// Pre-travel consultation of `patient_id` for a trip through one to three
+/// countries, leaving a week to two months after the visit.
+pub fn travel_health_record_for(patient_id: String) -> impl Strategy<Value = TravelHealthRecord> {
+    let (start, end) = care_period();
+    let stay = (select(vec!["KE", "TZ", "TH", "VN", "BR", "PE", "IN"]), 2u32..=30);
+    (id("travel"), date_between(start, end), 7u32..=60, vec(stay, 1..=3)).prop_map(
+        move |(id, consulted_on, lead_days, stays)| {
+            let mut arrival = after(consulted_on, lead_days);
+            let destinations = stays
+                .into_iter()
+                .map(|(country, days)| {
+                    let destination = Destination::new(country, arrival, after(arrival, days));
+                    arrival = destination.departure;
+                    destination
+                })
+                .collect();
+            TravelHealthRecord { destinations, ..TravelHealthRecord::new(id, patient_id.clone(), consulted_on) }
+        },
+    )
+}
+
+/// Travel health record for a random patient.
+pub fn travel_health_record() -> impl Strategy<Value = TravelHealthRecord> {
+    id("person").prop_flat_map(travel_health_record_for)
+}
+
+/// Compendium offering some of the catalog analytes as orderable tests.
+pub fn compendium() -> impl Strategy<Value = Compendium> {
+    let mut analytes: Vec<Analyte> = catalog::PANELS.iter().flat_map(|panel| panel.analytes.iter().copied()).collect();
+    analytes.sort_by_key(|analyte| analyte.code);
+    analytes.dedup_by_key(|analyte| analyte.code);
+    let count = analytes.len();
+    (id("compendium"), subsequence(analytes, 1..=count)).prop_map(|(id, analytes)| Compendium {
+        version: Some("2024.1".into()),
+        tests: analytes
+            .iter()
+            .map(|analyte| TestDefinition::new(coding(catalog::LOINC, analyte.code, analyte.display), analyte.display))
+            .collect(),
+        ..Compendium::new(id, "Central Laboratory test menu")
+    })
+}
+
+/// Fill of a tablet prescription of `patient_id` for one to ninety days.
+pub fn dispense_record_for(patient_id: String) -> impl Strategy<Value = DispenseRecord> {
+    let (start, end) = care_period();
+    (id("dispense"), id("med"), select(catalog::DRUGS), date_between(start, end), 1u32..=90, 1u32..=3, 0u32..=5)
+        .prop_map(move |(id, prescription, drug, dispensed_on, days_supply, fill_number, refills_remaining)| {
+            DispenseRecord {
+                medication: Some(coding(catalog::RXNORM, drug.code, drug.display)),
+                fill_number,
+                refills_remaining,
+                ..DispenseRecord::new(
+                    id,
+                    patient_id.clone(),
+                    reference("MedicationRecord", prescription),
+                    dispensed_on,
+                    Quantity::new(f64::from(days_supply), "{tbl}"),
+                    days_supply,
+                )
+            }
+        })
+}
+
+/// Dispense record for a random patient.
+pub fn dispense_record() -> impl Strategy<Value = DispenseRecord> {
+    id("person").prop_flat_map(dispense_record_for)
+}
+
+/// Routine vaccine dose given to `patient_id` before its lot expired.
+pub fn immunization_record_for(patient_id: String) -> impl Strategy<Value = ImmunizationRecord> {
+    let (start, end) = care_period();
+    let vaccines = vec![
+        ("150", "Influenza, injectable, quadrivalent, preservative free"),
+        ("115", "Tdap"),
+        ("43", "Hepatitis B vaccine, adult dosage"),
+        ("208", "COVID-19, mRNA, LNP-S, PF, 30 mcg/0.3 mL dose"),
+    ];
+    (id("imm"), select(vaccines), date_between(start, end), 1u32..=3, 0u32..=99_999, 1u32..=365).prop_map(
+        move |(id, (code, display), administered_on, dose_number, lot, shelf_days)| ImmunizationRecord {
+            dose_number: Some(dose_number),
+            lot_number: Some(format!("LOT-{:05}", lot)),
+            expiration_date: Some(after(administered_on, shelf_days)),
+            dose_quantity: Some(Quantity::new(0.5, "mL")),
+            ..ImmunizationRecord::new(id, patient_id.clone(), coding(CVX, code, display), administered_on)
+        },
+    )
+}
+
+/// Immunization record for a random patient.
+pub fn immunization_record() -> impl Strategy<Value = ImmunizationRecord> {
+    id("person").prop_flat_map(immunization_record_for)
+}
+
+/// Relative or caregiver of `patient_id` with a realistic name.
+pub fn related_person_for(patient_id: String) -> impl Strategy<Value = RelatedPerson> {
+    let roles = vec![
+        RelationshipRole::Caregiver,
+        RelationshipRole::Guardian,
+        RelationshipRole::Spouse,
+        RelationshipRole::Parent,
+        RelationshipRole::Child,
+        RelationshipRole::Sibling,
+        RelationshipRole::HealthcareProxy,
+        RelationshipRole::EmergencyContact,
+    ];
+    (id("related"), select(roles), select(catalog::FAMILY_NAMES), select(catalog::FEMALE_GIVEN_NAMES)).prop_map(
+        move |(id, role, family, given)| RelatedPerson {
+            name: Some(HumanName {
+                family: family.to_string(),
+                given: vec![given.to_string()],
+                r#use: None,
+                prefix: None,
+                suffix: None,
+            }),
+            ..RelatedPerson::new(id, patient_id.clone(), role)
+        },
+    )
+}
+
+/// Related person for a random patient.
+pub fn related_person() -> impl Strategy<Value = RelatedPerson> {
+    id("person").prop_flat_map(related_person_for)
+}
+
+macro_rules! arbitrary_resource {
+    ($($ty:ident => $strategy:ident),* $(,)?) => {
+        $(impl Arbitrary for $ty {
+            type Parameters = ();
+            type Strategy = BoxedStrategy<Self>;
+
+            fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+                $strategy().boxed()
+            }
+        })*
+
+        /// Resource of any type, every type equally likely.
+        pub fn any_resource() -> impl Strategy<Value = AnyResource> {
+            Union::new(vec![$($strategy().prop_map(AnyResource::$ty).boxed()),*])
+        }
+    };
+}
+
+arbitrary_resource!(
+    Person => person,
+    LabReport => lab_report,
+    ImagingReport => imaging_report,
+    MedicationRecord => medication_record,
+    FamilyHealthTree => family_health_tree,
+    Questionnaire => questionnaire,
+    QuestionnaireResponse => questionnaire_response,
+    Referral => referral,
+    Task => task,
+    CommunicationRecord => communication_record,
+    Location => location,
+    Condition => condition,
+    AllergyIntolerance => allergy_intolerance,
+    Flag => flag,
+    GlucoseSeries => glucose_series,
+    EcgRecord => ecg_record,
+    ConsentRecord => consent_record,
+    DialysisSession => dialysis_session,
+    RehabSession => rehab_session,
+    WoundAssessment => wound_assessment,
+    InfusionRecord => infusion_record,
+    PathologyReport => pathology_report,
+    AudiogramReport => audiogram_report,
+    BirthRecord => birth_record,
+    ClearanceDocument => clearance_document,
+    TravelHealthRecord => travel_health_record,
+    Compendium => compendium,
+    DispenseRecord => dispense_record,
+    ImmunizationRecord => immunization_record,
+    RelatedPerson => related_person,
+);
+
+impl Arbitrary for AnyResource {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        any_resource().boxed()
+    }
+}
@@ -0,0 +1,249 @@
+//! Incremental sync primitives.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//!
+//! An offline-first app and its server exchange [`ChangeSet`]s: the
+//! resources created or updated and the resources deleted since a cursor in
+//! the sender's change feed. [`apply_changeset`] writes a change set into any
+//! [`SyncStore`] and reports the changes that conflict with a write the
+//! receiver made after the change set's cursor, comparing the receiver's
+//! last-updated times. [`MemoryStore`] is an in-memory store with a change
+//! feed; the SQLite store implements [`SyncStore`] as well.
+//!
+//! ```
+//! use wellally::io::AnyResource;
+//! use wellally::sync::{apply_changeset, ChangeSet, MemoryStore};
+//!
+//! let task = |description: &str| -> AnyResource {
+//!     let mut json = serde_json::json!({ "id": "task-1", "patientId": "p-1", "status": "requested" });
+//!     json["description"] = description.into();
+//!     serde_json::from_value(json).unwrap()
+//! };
+//! let mut server = MemoryStore::new();
+//! apply_changeset(&mut server, &ChangeSet { upserts: vec![task("Repeat CBC")], ..ChangeSet::default() }).unwrap();
+//!
+//! // The app pulls, edits offline and pushes its edit based on what it pulled.
+//! let pulled = server.changes_since(None);
+//! let push = ChangeSet { upserts: vec![task("Repeat CBC and CMP")], cursor: pulled.cursor, ..ChangeSet::default() };
+//! assert!(apply_changeset(&mut server, &push).unwrap().conflicts.is_empty());
+//!
+//! // A second device pushing an edit of the same pull now conflicts.
+//! let stale = ChangeSet { upserts: vec![task("Cancel")], cursor: pulled.cursor, ..ChangeSet::default() };
+//! assert_eq!(apply_changeset(&mut server, &stale).unwrap().conflicts.len(), 1);
+//! ```
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::io::AnyResource;
+
+/// Changes to send to the other side of a sync.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChangeSet {
+    /// Resources created or updated
+    #[serde(default)]
+    pub upserts: Vec<AnyResource>,
+    /// Resource type and id of deleted resources (e.g., `("LabReport", "lab-1")`)
+    #[serde(default)]
+    pub deletes: Vec<(String, String)>,
+    /// Position in the change feed: where a pulled change set ends, or the
+    /// end of the last pull a pushed one is based on. Receiver writes after
+    /// it conflict; `None` means nothing was pulled yet.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<DateTime<Utc>>,
+}
+
+impl ChangeSet {
+    /// Whether there is nothing to apply.
+    pub fn is_empty(&self) -> bool {
+        self.upserts.is_empty() && self.deletes.is_empty()
+    }
+}
+
+/// How [`apply_changeset_with`] treats conflicting changes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ConflictPolicy {
+    /// Keep the receiver's version and skip the change
+    #[default]
+    KeepStored,
+    /// Apply the change over the receiver's version
+    KeepIncoming,
+}
+
+/// A change made to a resource the receiver wrote after the change set's cursor.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conflict {
+    /// Resource type name (e.g., "LabReport")
+    pub resource_type: String,
+    /// Resource id
+    pub id: String,
+    /// When the receiver last wrote or deleted the resource
+    pub last_updated: DateTime<Utc>,
+    /// Incoming version; `None` for a delete
+    pub incoming: Option<AnyResource>,
+}
+
+/// What [`apply_changeset`] did.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SyncOutcome {
+    /// Resources written
+    pub upserted: usize,
+    /// Resources deleted
+    pub deleted: usize,
+    /// Conflicting changes, skipped or applied per the policy
+    pub conflicts: Vec<Conflict>,
+}
+
+/// A store a change set can be applied to.
+pub trait SyncStore {
+    /// Error of the underlying storage
+    type Error;
+
+    /// When the resource was last written or deleted; `None` if never stored.
+    fn last_updated(&self, resource_type: &str, id: &str) -> Result<Option<DateTime<Utc>>, Self::Error>;
+
+    /// Inserts or replaces a resource.
+    fn upsert(&mut self, resource: &AnyResource) -> Result<(), Self::Error>;
+
+    /// Deletes a resource, returning whether it existed.
+    fn remove(&mut self, resource_type: &str, id: &str) -> Result<bool, Self::Error>;
+}
+
+/// Applies `changes` to `store`, skipping and reporting conflicting changes.
+pub fn apply_changeset<S: SyncStore + ?Sized>(store: &mut S, changes: &ChangeSet) -> Result<SyncOutcome, S::Error> {
+    apply_changeset_with(store, changes, ConflictPolicy::KeepStored)
+}
+
+/// Applies `changes` to `store`, resolving conflicts with `policy`. Upserts
+/// are applied before deletes.
+pub fn apply_changeset_with<S: SyncStore + ?Sized>(
+    store: &mut S,
+    changes: &ChangeSet,
+    policy: ConflictPolicy,
+) -> Result<SyncOutcome, S::Error> {
+    let mut outcome = SyncOutcome::default();
+    for resource in &changes.upserts {
+        let found = conflict(store, changes.cursor, resource.resource_type(), resource.id(), Some(resource))?;
+        if let Some(conflict) = found {
+            outcome.conflicts.push(conflict);
+            if policy == ConflictPolicy::KeepStored {
+                continue;
+            }
+        }
+        store.upsert(resource)?;
+        outcome.upserted += 1;
+    }
+    for (resource_type, id) in &changes.deletes {
+        let found = conflict(store, changes.cursor, resource_type, id, None)?;
+        if let Some(conflict) = found {
+            outcome.conflicts.push(conflict);
+            if policy == ConflictPolicy::KeepStored {
+                continue;
+            }
+        }
+        if store.remove(resource_type, id)? {
+            outcome.deleted += 1;
+        }
+    }
+    Ok(outcome)
+}
+
+fn conflict<S: SyncStore + ?Sized>(
+    store: &S,
+    cursor: Option<DateTime<Utc>>,
+    resource_type: &str,
+    id: &str,
+    incoming: Option<&AnyResource>,
+) -> Result<Option<Conflict>, S::Error> {
+    let Some(last_updated) = store.last_updated(resource_type, id)? else {
+        return Ok(None);
+    };
+    let conflicting = cursor.is_none_or(|cursor| last_updated > cursor);
+    Ok(conflicting.then(|| Conflict {
+        resource_type: resource_type.to_string(),
+        id: id.to_string(),
+        last_updated,
+        incoming: incoming.cloned(),
+    }))
+}
+
+/// In-memory [`SyncStore`] with a change feed. Every write is stamped with
+/// a strictly increasing time, and deletions keep a tombstone.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MemoryStore {
+    entries: BTreeMap<(String, String), (DateTime<Utc>, Option<AnyResource>)>,
+    latest: Option<DateTime<Utc>>,
+}
+
+impl MemoryStore {
+    /// Empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The live resource with `resource_type` and `id`.
+    pub fn get(&self, resource_type: &str, id: &str) -> Option<&AnyResource> {
+        self.entries.get(&(resource_type.to_string(), id.to_string()))?.1.as_ref()
+    }
+
+    /// Live resources in resource type and id order.
+    pub fn resources(&self) -> impl Iterator<Item = &AnyResource> {
+        self.entries.values().filter_map(|(_, resource)| resource.as_ref())
+    }
+
+    /// Writes and deletions after `cursor` (all when `None`), with the
+    /// cursor to pass to the next call.
+    pub fn changes_since(&self, cursor: Option<DateTime<Utc>>) -> ChangeSet {
+        let mut changes = ChangeSet { cursor, ..ChangeSet::default() };
+        let mut entries: Vec<_> = self
+            .entries
+            .iter()
+            .filter(|(_, (updated_at, _))| cursor.is_none_or(|cursor| *updated_at > cursor))
+            .collect();
+        entries.sort_by_key(|(_, (updated_at, _))| *updated_at);
+        for ((resource_type, id), (updated_at, resource)) in entries {
+            match resource {
+                Some(resource) => changes.upserts.push(resource.clone()),
+                None => changes.deletes.push((resource_type.clone(), id.clone())),
+            }
+            changes.cursor = Some(*updated_at);
+        }
+        changes
+    }
+
+    /// Current time, bumped past the latest write so cursors never skip one.
+    fn next_timestamp(&mut self) -> DateTime<Utc> {
+        let now = Utc::now();
+        let stamp = self.latest.map_or(now, |latest| now.max(latest + Duration::milliseconds(1)));
+        self.latest = Some(stamp);
+        stamp
+    }
+}
+
+impl SyncStore for MemoryStore {
+    type Error = core::convert::Infallible;
+
+    fn last_updated(&self, resource_type: &str, id: &str) -> Result<Option<DateTime<Utc>>, Self::Error> {
+        Ok(self.entries.get(&(resource_type.to_string(), id.to_string())).map(|(updated_at, _)| *updated_at))
+    }
+
+    fn upsert(&mut self, resource: &AnyResource) -> Result<(), Self::Error> {
+        let stamp = self.next_timestamp();
+        let key = (resource.resource_type().to_string(), resource.id().to_string());
+        self.entries.insert(key, (stamp, Some(resource.clone())));
+        Ok(())
+    }
+
+    fn remove(&mut self, resource_type: &str, id: &str) -> Result<bool, Self::Error> {
+        let key = (resource_type.to_string(), id.to_string());
+        if self.entries.get(&key).is_none_or(|(_, resource)| resource.is_none()) {
+            return Ok(false);
+        }
+        let stamp = self.next_timestamp();
+        self.entries.insert(key, (stamp, None));
+        Ok(true)
+    }
+}
@@ -0,0 +1,392 @@
+//! Synthetic patient cohorts for demos, load tests and pipeline development.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//!
+//! Unlike [`crate::strategies`], which explores the value space for property
+//! tests, this module produces *coherent* patients: chronic conditions appear
+//! with age-dependent prevalence, routine CBC/CMP panels are drawn around a
+//! per-patient baseline at regular visits, untreated conditions push the
+//! matching analytes out of range until a drug regimen starts, and relatives
+//! in the family tree share the proband's conditions more often than chance.
+//! Generation is deterministic for a given [`CohortConfig::seed`].
+//!
+//! ```no_run
+//! use wellally::synthetic::{Cohort, CohortConfig};
+//!
+//! for patient in Cohort::new(CohortConfig { patients: 100, ..Default::default() }) {
+//!     println!("{} has {} lab reports", patient.person.id, patient.lab_reports.len());
+//! }
+//! ```
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, TimeZone, Utc};
+use rand::rngs::StdRng;
+use rand::seq::IndexedRandom;
+use rand::{Rng, SeedableRng};
+
+use crate::catalog::{self, Analyte, ConditionCode, Panel};
+use crate::common::HumanName;
+use crate::family_health::{FamilyHealthTree, FamilyMember, RelationToProband, Sex};
+use crate::health::{ClinicalSummary, Gender, Person};
+use crate::lab_report::{Facility, LabReport};
+use crate::medication::MedicationRecord;
+
+/// Parameters of a synthetic cohort.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CohortConfig {
+    /// Number of patients to generate
+    pub patients: usize,
+    /// Seed of the random generator; equal seeds give equal cohorts
+    pub seed: u64,
+    /// First day of the observation period
+    pub start: NaiveDate,
+    /// Last day of the observation period
+    pub end: NaiveDate,
+    /// Average number of days between routine lab visits
+    pub visit_interval_days: i64,
+    /// Days supplied by each medication fill
+    pub fill_days: i32,
+}
+
+impl Default for CohortConfig {
+    fn default() -> Self {
+        Self {
+            patients: 10,
+            seed: 0,
+            start: NaiveDate::from_ymd_opt(2020, 1, 1).unwrap(),
+            end: NaiveDate::from_ymd_opt(2024, 12, 31).unwrap(),
+            visit_interval_days: 180,
+            fill_days: 90,
+        }
+    }
+}
+
+/// Every record generated for one patient.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PatientRecords {
+    /// Demographics; the chronic conditions are listed in the clinical summary
+    pub person: Person,
+    /// Routine and condition-specific lab reports in chronological order
+    pub lab_reports: Vec<LabReport>,
+    /// Consecutive medication fills for each treated condition
+    pub medications: Vec<MedicationRecord>,
+    /// Family tree rooted at the patient
+    pub family_tree: FamilyHealthTree,
+}
+
+/// Generates every patient of a cohort at once.
+pub fn generate(config: CohortConfig) -> Vec<PatientRecords> {
+    Cohort::new(config).collect()
+}
+
+/// Chronic condition with its age-dependent prevalence and lab signature.
+struct Profile {
+    condition: ConditionCode,
+    /// Prevalence per year of age (capped at 60%)
+    prevalence_per_year: f64,
+    /// Extra panel ordered to monitor the condition
+    monitor: Option<Panel>,
+    /// Untreated multiplier applied to analyte baselines, by LOINC code
+    effects: &'static [(&'static str, f64)],
+}
+
+const PROFILES: &[Profile] = &[
+    Profile {
+        condition: catalog::HYPERTENSION,
+        prevalence_per_year: 0.006,
+        monitor: None,
+        effects: &[("2160-0", 1.15)],
+    },
+    Profile {
+        condition: catalog::DIABETES_TYPE_2,
+        prevalence_per_year: 0.0025,
+        monitor: Some(catalog::HBA1C),
+        effects: &[("2345-7", 1.6), ("4548-4", 1.45), ("2571-8", 1.3)],
+    },
+    Profile {
+        condition: catalog::HYPERLIPIDEMIA,
+        prevalence_per_year: 0.004,
+        monitor: Some(catalog::LIPID),
+        effects: &[("2093-3", 1.4), ("13457-7", 1.7), ("2571-8", 1.5)],
+    },
+    Profile {
+        condition: catalog::HYPOTHYROIDISM,
+        prevalence_per_year: 0.0012,
+        monitor: Some(catalog::THYROID),
+        effects: &[("3016-3", 3.5), ("3024-7", 0.6)],
+    },
+];
+
+/// Share of the untreated effect that remains once a regimen has started.
+const TREATED_EFFECT: f64 = 0.25;
+
+/// Probability that a first-degree relative shares one of the proband's conditions.
+const FAMILIAL_RISK: f64 = 0.35;
+
+/// Diagnosed condition of a patient.
+struct Diagnosis {
+    profile: &'static Profile,
+    diagnosed: NaiveDate,
+}
+
+/// Iterator over the patients of a synthetic cohort.
+pub struct Cohort {
+    config: CohortConfig,
+    rng: StdRng,
+    generated: usize,
+    ids: HashMap<&'static str, u32>,
+}
+
+impl Cohort {
+    /// Starts a cohort; nothing is generated until the iterator is advanced.
+    pub fn new(config: CohortConfig) -> Self {
+        let rng = StdRng::seed_from_u64(config.seed);
+        Self { config, rng, generated: 0, ids: HashMap::new() }
+    }
+
+    fn id(&mut self, prefix: &'static str) -> String {
+        let counter = self.ids.entry(prefix).or_insert(0);
+        *counter += 1;
+        format!("{}-{:06}", prefix, counter)
+    }
+
+    fn date_between(&mut self, start: NaiveDate, end: NaiveDate) -> NaiveDate {
+        let days = (end - start).num_days().max(0);
+        start + Duration::days(self.rng.random_range(0..=days))
+    }
+
+    /// Instant on `date` during laboratory opening hours.
+    fn morning(&mut self, date: NaiveDate) -> DateTime<Utc> {
+        let minutes = self.rng.random_range(7 * 60..12 * 60);
+        Utc.from_utc_datetime(&date.and_hms_opt(minutes / 60, minutes % 60, 0).unwrap())
+    }
+
+    /// Roughly normal noise with the given relative spread.
+    fn noise(&mut self, spread: f64) -> f64 {
+        let sum: f64 = (0..3).map(|_| self.rng.random_range(-1.0..1.0)).sum();
+        1.0 + spread * sum / 3.0
+    }
+
+    fn patient(&mut self) -> PatientRecords {
+        let config = self.config.clone();
+        let female = self.rng.random_bool(0.5);
+        let age = self.rng.random_range(25..=85);
+        let birth_date = self.date_between(
+            NaiveDate::from_ymd_opt(config.end.year() - age, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(config.end.year() - age, 12, 28).unwrap(),
+        );
+        let names = if female { catalog::FEMALE_GIVEN_NAMES } else { catalog::MALE_GIVEN_NAMES };
+        let given = names.choose(&mut self.rng).unwrap();
+        let family = catalog::FAMILY_NAMES.choose(&mut self.rng).unwrap();
+
+        let midpoint = config.start + (config.end - config.start) / 2;
+        let mut diagnoses = Vec::new();
+        for profile in PROFILES {
+            let mut prevalence = (profile.prevalence_per_year * f64::from(age)).min(0.6);
+            if profile.condition == catalog::HYPOTHYROIDISM && female {
+                prevalence *= 3.0;
+            }
+            if self.rng.random_bool(prevalence) {
+                let diagnosed = self.date_between(config.start, midpoint);
+                diagnoses.push(Diagnosis { profile, diagnosed });
+            }
+        }
+
+        let person = Person {
+            id: self.id("person"),
+            name: vec![HumanName {
+                family: family.to_string(),
+                given: vec![given.to_string()],
+                r#use: None,
+                prefix: None,
+                suffix: None,
+            }],
+            birth_date: birth_date.into(),
+            gender: Some(if female { Gender::Female } else { Gender::Male }),
+            clinical_summary: (!diagnoses.is_empty()).then(|| ClinicalSummary {
+                conditions: Some(diagnoses.iter().map(|d| d.profile.condition.concept()).collect()),
+                allergies: None,
+                blood_type: None,
+                primary_care_provider: None,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let lab_reports = self.lab_reports(&person.id, &diagnoses);
+        let medications = self.medications(&person.id, &diagnoses);
+        let family_tree = self.family_tree(&person.id, birth_date.year(), female, &diagnoses);
+        PatientRecords { person, lab_reports, medications, family_tree }
+    }
+
+    fn lab_reports(&mut self, patient_id: &str, diagnoses: &[Diagnosis]) -> Vec<LabReport> {
+        // Each analyte sits at a stable point within the reference range.
+        let mut baselines = HashMap::new();
+        for panel in catalog::PANELS {
+            for analyte in panel.analytes {
+                let position = self.rng.random_range(0.25..0.75);
+                baselines.insert(analyte.code, analyte.low + (analyte.high - analyte.low) * position);
+            }
+        }
+
+        let mut reports = Vec::new();
+        let mut visit = self.config.start + Duration::days(self.rng.random_range(0..self.config.visit_interval_days));
+        while visit <= self.config.end {
+            let mut panels = vec![catalog::CBC, catalog::CMP];
+            panels.extend(diagnoses.iter().filter_map(|d| d.profile.monitor));
+            let collected_at = self.morning(visit);
+            for panel in panels {
+                let results = panel
+                    .analytes
+                    .iter()
+                    .map(|analyte| {
+                        let value = self.value(analyte, baselines[analyte.code], diagnoses, visit);
+                        analyte.result(value)
+                    })
+                    .collect();
+                let id = self.id("lab");
+                let issued_at = collected_at + Duration::hours(self.rng.random_range(2..48));
+                reports.push(LabReport {
+                    facility: Some(Facility {
+                        id: Some("lab-central".into()),
+                        name: Some("Central Laboratory".into()),
+                        location: None,
+                    }),
+                    ..panel.report(id, patient_id.to_string(), collected_at, issued_at, results)
+                });
+            }
+            let jitter = self.rng.random_range(-14..=14);
+            visit += Duration::days(self.config.visit_interval_days + jitter);
+        }
+        reports
+    }
+
+    fn value(&mut self, analyte: &Analyte, baseline: f64, diagnoses: &[Diagnosis], on: NaiveDate) -> f64 {
+        let mut value = baseline;
+        for diagnosis in diagnoses {
+            let Some(&(_, factor)) = diagnosis.profile.effects.iter().find(|(code, _)| *code == analyte.code) else {
+                continue;
+            };
+            let remaining = if on < diagnosis.diagnosed { 1.0 } else { TREATED_EFFECT };
+            value *= 1.0 + (factor - 1.0) * remaining;
+        }
+        (value * self.noise(0.08)).clamp(analyte.min, analyte.max)
+    }
+
+    fn medications(&mut self, patient_id: &str, diagnoses: &[Diagnosis]) -> Vec<MedicationRecord> {
+        let mut records = Vec::new();
+        for diagnosis in diagnoses {
+            let drugs: Vec<_> =
+                catalog::DRUGS.iter().filter(|drug| drug.indication == diagnosis.profile.condition).collect();
+            let Some(drug) = drugs.choose(&mut self.rng) else {
+                continue;
+            };
+            let mut start_date = diagnosis.diagnosed;
+            while start_date <= self.config.end {
+                let id = self.id("med");
+                records.push(drug.record(id, patient_id.to_string(), start_date, self.config.fill_days));
+                // Refills are picked up a few days late now and then.
+                start_date += Duration::days(i64::from(self.config.fill_days) + self.rng.random_range(0..7));
+            }
+        }
+        records.sort_by_key(|record| record.start_date);
+        records
+    }
+
+    fn family_tree(
+        &mut self,
+        proband_id: &str,
+        proband_year: i32,
+        female: bool,
+        diagnoses: &[Diagnosis],
+    ) -> FamilyHealthTree {
+        let mut members = vec![FamilyMember {
+            id: proband_id.to_string(),
+            person_id: None,
+            relation_to_proband: RelationToProband::Self_,
+            mother_id: None,
+            father_id: None,
+            sex: Some(if female { Sex::Female } else { Sex::Male }),
+            birth_year: Some(proband_year),
+            deceased: Some(false),
+            conditions: (!diagnoses.is_empty())
+                .then(|| diagnoses.iter().map(|d| d.profile.condition.concept()).collect()),
+            condition_onsets: Vec::new(),
+            notes: None,
+        }];
+
+        let mut relatives = vec![
+            (RelationToProband::Mother, Some(Sex::Female), -self.rng.random_range(20..=40)),
+            (RelationToProband::Father, Some(Sex::Male), -self.rng.random_range(20..=45)),
+        ];
+        for _ in 0..self.rng.random_range(0..=3) {
+            relatives.push((RelationToProband::Sibling, None, self.rng.random_range(-10..=10)));
+        }
+        if proband_year + 20 < self.config.end.year() {
+            for _ in 0..self.rng.random_range(0..=2) {
+                relatives.push((RelationToProband::Child, None, self.rng.random_range(20..=38)));
+            }
+        }
+
+        let this_year = self.config.end.year();
+        for (relation, sex, offset) in relatives {
+            let birth_year = (proband_year + offset).clamp(1900, this_year);
+            let mut conditions: Vec<ConditionCode> = diagnoses
+                .iter()
+                .map(|d| d.profile.condition)
+                .filter(|_| self.rng.random_bool(FAMILIAL_RISK))
+                .collect();
+            if self.rng.random_bool(0.2) {
+                let other = *catalog::CONDITIONS.choose(&mut self.rng).unwrap();
+                if !conditions.contains(&other) {
+                    conditions.push(other);
+                }
+            }
+            let age = this_year - birth_year;
+            members.push(FamilyMember {
+                id: self.id("member"),
+                person_id: None,
+                relation_to_proband: relation,
+                mother_id: None,
+                father_id: None,
+                sex: Some(sex.unwrap_or(if self.rng.random_bool(0.5) { Sex::Female } else { Sex::Male })),
+                birth_year: Some(birth_year),
+                deceased: Some(age > 75 && self.rng.random_bool(f64::from((age - 75).min(30)) / 30.0)),
+                conditions: (!conditions.is_empty()).then(|| conditions.iter().map(ConditionCode::concept).collect()),
+                condition_onsets: Vec::new(),
+                notes: None,
+            });
+        }
+
+        FamilyHealthTree {
+            proband_id: proband_id.to_string(),
+            members,
+            schema_version: None,
+            security_labels: None,
+            extensions: None,
+            #[cfg(feature = "preserve-unknown")]
+            extra: Default::default(),
+        }
+    }
+}
+
+impl Iterator for Cohort {
+    type Item = PatientRecords;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.generated == self.config.patients {
+            return None;
+        }
+        self.generated += 1;
+        Some(self.patient())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.config.patients - self.generated;
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for Cohort {}
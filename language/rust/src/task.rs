@@ -0,0 +1,142 @@
+//! Task (follow-up action) data model.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//! Schema: https://wellall.health/schemas/task/v0.1.0
+//!
+//! A [`Task`] tracks a care-coordination action such as "repeat CBC in 3
+//! months" or "schedule follow-up CT", linked to the report it follows up on
+//! and to the request (e.g., a referral) it fulfils.
+
+use alloc::{string::String, vec::Vec};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::common::{CodeableConcept, Reference, RequestPriority, Coding};
+use crate::datetime::{FlexibleDate, FlexibleDateTime};
+use crate::error::{self, WellAllyError};
+use crate::imaging_report::Performer;
+use crate::extension::Extension;
+
+/// Lifecycle status of a task
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "async-graphql", derive(async_graphql::Enum))]
+#[serde(rename_all = "kebab-case")]
+pub enum TaskStatus {
+    /// Created, awaiting an owner
+    Requested,
+    /// Accepted by the owner
+    Accepted,
+    InProgress,
+    /// Temporarily suspended
+    OnHold,
+    Completed,
+    /// Abandoned before completion
+    Cancelled,
+    /// Attempted without success
+    Failed,
+    /// Created by mistake
+    EnteredInError,
+}
+
+impl TaskStatus {
+    /// Whether a task may move from this status to `next`.
+    pub fn can_transition_to(&self, next: TaskStatus) -> bool {
+        use TaskStatus::*;
+        match (self, next) {
+            (_, EnteredInError) => *self != EnteredInError,
+            (Requested, Accepted | InProgress | Cancelled) => true,
+            (Accepted, InProgress | OnHold | Cancelled) => true,
+            (InProgress, OnHold | Completed | Cancelled | Failed) => true,
+            (OnHold, Accepted | InProgress | Cancelled) => true,
+            _ => false,
+        }
+    }
+
+    /// Whether the task is finished, successfully or not.
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, TaskStatus::Completed | TaskStatus::Cancelled | TaskStatus::Failed | TaskStatus::EnteredInError)
+    }
+}
+
+/// Follow-up action for a patient.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "TaskInput")
+)]
+pub struct Task {
+    /// Unique task identifier
+    pub id: String,
+    /// Reference to Person.id of the patient the task is for
+    #[serde(rename = "patientId", alias = "patient_id")]
+    pub patient_id: String,
+    /// Lifecycle status
+    pub status: TaskStatus,
+    /// Urgency
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub priority: Option<RequestPriority>,
+    /// What is to be done (e.g., "Repeat CBC in 3 months")
+    pub description: String,
+    /// Coded action (e.g., the LOINC code of the test to repeat)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<CodeableConcept>,
+    /// Practitioner responsible for the task
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub owner: Option<Performer>,
+    /// When the task was created
+    #[serde(rename = "authoredOn", alias = "authored_on", skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "utoipa", schema(value_type = Option<String>))]
+    pub authored_on: Option<FlexibleDateTime>,
+    /// Date by which the task should be done
+    #[serde(rename = "dueDate", alias = "due_date", skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "utoipa", schema(value_type = Option<String>))]
+    pub due_date: Option<FlexibleDate>,
+    /// Resource the task acts on (e.g., the report with the abnormal result)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub focus: Option<Reference>,
+    /// Requests the task fulfils (e.g., a referral)
+    #[serde(rename = "basedOn", alias = "based_on", skip_serializing_if = "Option::is_none")]
+    pub based_on: Option<Vec<Reference>>,
+    /// Why the task is on hold, cancelled or failed
+    #[serde(rename = "statusReason", alias = "status_reason", skip_serializing_if = "Option::is_none")]
+    pub status_reason: Option<String>,
+    /// Schema version the resource was written against (e.g., "0.1.0"); absent means 0.1.0
+    #[serde(rename = "schemaVersion", alias = "schema_version", skip_serializing_if = "Option::is_none")]
+    pub schema_version: Option<String>,
+    /// Confidentiality and sensitivity labels (e.g., v3 ActCode HIV); see [`crate::security`]
+    #[serde(rename = "securityLabels", alias = "security_labels", skip_serializing_if = "Option::is_none")]
+    pub security_labels: Option<Vec<Coding>>,
+    /// Site-specific extensions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extensions: Option<Vec<Extension>>,
+    /// Properties this model version does not define, kept for re-serialization
+    #[cfg(feature = "preserve-unknown")]
+    #[serde(flatten)]
+    #[cfg_attr(feature = "schemars", schemars(skip))]
+    #[cfg_attr(feature = "utoipa", schema(ignore))]
+    #[cfg_attr(feature = "async-graphql", graphql(skip))]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Task {
+    /// Moves the task to `next`, failing if the lifecycle does not allow it.
+    pub fn set_status(&mut self, next: TaskStatus) -> Result<(), WellAllyError> {
+        if !self.status.can_transition_to(next) {
+            return Err(error::transition_error("Task", self.status, next));
+        }
+        self.status = next;
+        Ok(())
+    }
+
+    /// Whether the task is still open after its due date. A partial due date
+    /// (year or month) is due by the end of the period.
+    pub fn is_overdue(&self, today: NaiveDate) -> bool {
+        !self.status.is_terminal() && self.due_date.is_some_and(|due| due.last_date() < today)
+    }
+}
@@ -0,0 +1,255 @@
+//! Travel health model.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//! Schema: https://wellall.health/schemas/travel/v0.1.0
+//!
+//! A [`TravelHealthRecord`] is what a travel clinic keeps for one trip: the
+//! destinations with arrival and departure dates, the vaccines the trip
+//! requires or calls for with the [`crate::ImmunizationRecord`] given for each,
+//! malaria prophylaxis as references to [`crate::MedicationRecord`]s, and the
+//! health advisories handed to the traveler.
+//!
+//! ```
+//! use wellally::travel::{Destination, TravelHealthRecord, TravelVaccine, VaccineRequirement, CVX};
+//! use wellally::Coding;
+//!
+//! let mut trip = TravelHealthRecord::new("trip-1", "p-1", "2024-09-10".parse().unwrap());
+//! trip.destinations.push(Destination::new("KE", "2024-10-05".parse().unwrap(), "2024-10-19".parse().unwrap()));
+//! let yellow_fever = Coding { system: CVX.into(), code: "37".into(), display: None };
+//! trip.vaccines.push(TravelVaccine::new(yellow_fever, VaccineRequirement::Required));
+//! assert_eq!(trip.unmet_requirements().len(), 1);
+//! assert_eq!(trip.departure_date(), Some("2024-10-05".parse().unwrap()));
+//! ```
+
+use alloc::{string::String, vec::Vec};
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::common::{Annotation, CodeableConcept, Coding, Reference};
+use crate::extension::Extension;
+
+pub use crate::immunization::CVX;
+
+/// A stay in one country.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "DestinationInput")
+)]
+pub struct Destination {
+    /// ISO 3166-1 alpha-2 country code (e.g., "KE")
+    pub country: String,
+    /// Region or city within the country (e.g., "Maasai Mara")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+    pub arrival: NaiveDate,
+    pub departure: NaiveDate,
+    /// Activities that change the risks (e.g., "rural stay", "animal contact")
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub activities: Vec<String>,
+}
+
+impl Destination {
+    /// Stay in `country` from `arrival` to `departure`.
+    pub fn new(country: impl Into<String>, arrival: NaiveDate, departure: NaiveDate) -> Self {
+        Destination { country: country.into(), region: None, arrival, departure, activities: Vec::new() }
+    }
+
+    /// Whether the traveler is in the country on `date`.
+    pub fn includes(&self, date: NaiveDate) -> bool {
+        self.arrival <= date && date <= self.departure
+    }
+}
+
+/// Why a vaccine is on the trip's list.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "async-graphql", derive(async_graphql::Enum))]
+#[serde(rename_all = "kebab-case")]
+pub enum VaccineRequirement {
+    /// Required for entry (e.g., yellow fever under the International Health Regulations)
+    Required,
+    /// Advised for the destinations and activities
+    Recommended,
+}
+
+/// A vaccine for the trip and, once given, the immunization.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "TravelVaccineInput")
+)]
+pub struct TravelVaccine {
+    /// Vaccine (e.g., CVX 37 yellow fever)
+    pub vaccine: Coding,
+    pub requirement: VaccineRequirement,
+    /// Immunization given (e.g., "ImmunizationRecord/imm-1"); absent while outstanding
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub immunization: Option<Reference>,
+    /// Reason the vaccine was not given (e.g., contraindication, declined)
+    #[serde(rename = "notGivenReason", alias = "not_given_reason", skip_serializing_if = "Option::is_none")]
+    pub not_given_reason: Option<CodeableConcept>,
+}
+
+impl TravelVaccine {
+    /// Vaccine not given yet.
+    pub fn new(vaccine: Coding, requirement: VaccineRequirement) -> Self {
+        TravelVaccine { vaccine, requirement, immunization: None, not_given_reason: None }
+    }
+
+    /// Whether the vaccine has been given.
+    pub fn is_received(&self) -> bool {
+        self.immunization.is_some()
+    }
+}
+
+/// Health advisory given to the traveler.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "TravelAdvisoryInput")
+)]
+pub struct TravelAdvisory {
+    /// Advice as given (e.g., "Use DEET repellent from dusk to dawn")
+    pub text: String,
+    /// Country it concerns; absent for the whole trip
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub country: Option<String>,
+    /// Topic (e.g., SNOMED CT insect bite prevention, food and water safety)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub topic: Option<CodeableConcept>,
+    /// Who issued the advice (e.g., "WHO", "CDC")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub issuer: Option<String>,
+}
+
+/// Pre-travel consultation for one trip.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "TravelHealthRecordInput")
+)]
+pub struct TravelHealthRecord {
+    /// Unique record identifier
+    pub id: String,
+    /// Reference to Person.id
+    #[serde(rename = "patientId", alias = "patient_id")]
+    pub patient_id: String,
+    /// Day of the pre-travel consultation
+    #[serde(rename = "consultedOn", alias = "consulted_on")]
+    pub consulted_on: NaiveDate,
+    /// Stays of the trip
+    pub destinations: Vec<Destination>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub vaccines: Vec<TravelVaccine>,
+    /// Malaria chemoprophylaxis prescribed (e.g., "MedicationRecord/med-atovaquone")
+    #[serde(
+        rename = "malariaProphylaxis",
+        alias = "malaria_prophylaxis",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub malaria_prophylaxis: Vec<Reference>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub advisories: Vec<TravelAdvisory>,
+    /// Travel medicine clinician (e.g., "Practitioner/dr-wu")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub clinician: Option<Reference>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notes: Option<Vec<Annotation>>,
+    /// Schema version the resource was written against (e.g., "0.1.0"); absent means 0.1.0
+    #[serde(rename = "schemaVersion", alias = "schema_version", skip_serializing_if = "Option::is_none")]
+    pub schema_version: Option<String>,
+    /// Confidentiality and sensitivity labels (e.g., v3 ActCode HIV); see [`crate::security`]
+    #[serde(rename = "securityLabels", alias = "security_labels", skip_serializing_if = "Option::is_none")]
+    pub security_labels: Option<Vec<Coding>>,
+    /// Site-specific extensions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extensions: Option<Vec<Extension>>,
+    /// Properties this model version does not define, kept for re-serialization
+    #[cfg(feature = "preserve-unknown")]
+    #[serde(flatten)]
+    #[cfg_attr(feature = "schemars", schemars(skip))]
+    #[cfg_attr(feature = "utoipa", schema(ignore))]
+    #[cfg_attr(feature = "async-graphql", graphql(skip))]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl TravelHealthRecord {
+    /// Consultation without destinations yet.
+    pub fn new(id: impl Into<String>, patient_id: impl Into<String>, consulted_on: NaiveDate) -> Self {
+        TravelHealthRecord {
+            id: id.into(),
+            patient_id: patient_id.into(),
+            consulted_on,
+            destinations: Vec::new(),
+            vaccines: Vec::new(),
+            malaria_prophylaxis: Vec::new(),
+            advisories: Vec::new(),
+            clinician: None,
+            notes: None,
+            schema_version: None,
+            security_labels: None,
+            extensions: None,
+            #[cfg(feature = "preserve-unknown")]
+            extra: Default::default(),
+        }
+    }
+
+    /// First arrival of the trip.
+    pub fn departure_date(&self) -> Option<NaiveDate> {
+        self.destinations.iter().map(|destination| destination.arrival).min()
+    }
+
+    /// Last departure of the trip.
+    pub fn return_date(&self) -> Option<NaiveDate> {
+        self.destinations.iter().map(|destination| destination.departure).max()
+    }
+
+    /// Where the traveler is on `date`; on a travel day, the country arrived in.
+    pub fn destination_on(&self, date: NaiveDate) -> Option<&Destination> {
+        self.destinations
+            .iter()
+            .filter(|destination| destination.includes(date))
+            .max_by_key(|destination| destination.arrival)
+    }
+
+    /// Required vaccines not given yet.
+    pub fn unmet_requirements(&self) -> Vec<&TravelVaccine> {
+        self.outstanding_vaccines()
+            .into_iter()
+            .filter(|vaccine| vaccine.requirement == VaccineRequirement::Required)
+            .collect()
+    }
+
+    /// Vaccines neither given nor ruled out with a reason.
+    pub fn outstanding_vaccines(&self) -> Vec<&TravelVaccine> {
+        self.vaccines
+            .iter()
+            .filter(|vaccine| !vaccine.is_received() && vaccine.not_given_reason.is_none())
+            .collect()
+    }
+
+    /// Advisories for `country`, including those for the whole trip.
+    pub fn advisories_for(&self, country: &str) -> Vec<&TravelAdvisory> {
+        self.advisories
+            .iter()
+            .filter(|advisory| advisory.country.as_deref().is_none_or(|c| c.eq_ignore_ascii_case(country)))
+            .collect()
+    }
+}
@@ -0,0 +1,300 @@
+//! UCUM unit conversion.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//!
+//! Converts values between commensurable units of the Unified Code for Units
+//! of Measure. The supported subset covers what health data is recorded in:
+//! the metric units with their prefixes (`mg`, `dL`, `mmol`, `kPa`), time
+//! (`min`, `h`, `d`, `wk`, `mo`, `a`), the customary body measurement units
+//! (`[lb_av]`, `[oz_av]`, `[in_i]`, `[ft_i]`), `mm[Hg]`, `%`, enzyme units
+//! (`U`), absorbed dose (`Gy`) and products and quotients of these (`mg/dL`, `mL/min/{1.73_m2}`,
+//! `10*9/L`). Annotations in braces count as 1. Units outside the subset,
+//! such as `[IU]` or `Cel`, only convert to themselves.
+//!
+//! ```
+//! use wellally::ucum;
+//!
+//! assert_eq!(ucum::convert(1.5, "g", "mg").unwrap(), 1500.0);
+//! assert!((ucum::convert(120.0, "mm[Hg]", "kPa").unwrap() - 16.0).abs() < 0.01);
+//! assert!(ucum::commensurable("mg/dL", "g/L"));
+//! assert!(!ucum::commensurable("mg", "mL"));
+//! ```
+
+use alloc::{boxed::Box, format, string::ToString};
+
+use crate::error::{Result, WellAllyError};
+
+/// Exponents of metre, gram, second, mole and kelvin.
+type Dimension = [i8; 5];
+
+const LENGTH: Dimension = [1, 0, 0, 0, 0];
+const MASS: Dimension = [0, 1, 0, 0, 0];
+const TIME: Dimension = [0, 0, 1, 0, 0];
+const AMOUNT: Dimension = [0, 0, 0, 1, 0];
+const TEMPERATURE: Dimension = [0, 0, 0, 0, 1];
+const VOLUME: Dimension = [3, 0, 0, 0, 0];
+const PRESSURE: Dimension = [-1, 1, -2, 0, 0];
+const ENERGY: Dimension = [2, 1, -2, 0, 0];
+const CATALYTIC: Dimension = [0, 0, -1, 1, 0];
+const ABSORBED_DOSE: Dimension = [2, 0, -2, 0, 0];
+const NONE: Dimension = [0; 5];
+
+/// Atom, magnitude in base units, dimension and whether it takes prefixes.
+const ATOMS: &[(&str, f64, Dimension, bool)] = &[
+    ("m", 1.0, LENGTH, true),
+    ("g", 1.0, MASS, true),
+    ("s", 1.0, TIME, true),
+    ("mol", 1.0, AMOUNT, true),
+    ("eq", 1.0, AMOUNT, true),
+    ("K", 1.0, TEMPERATURE, true),
+    ("L", 0.001, VOLUME, true),
+    ("l", 0.001, VOLUME, true),
+    ("Pa", 1000.0, PRESSURE, true),
+    ("m[Hg]", 133_322_000.0, PRESSURE, true),
+    ("J", 1000.0, ENERGY, true),
+    ("cal", 4184.0, ENERGY, true),
+    ("U", 1e-6 / 60.0, CATALYTIC, true),
+    ("Gy", 1.0, ABSORBED_DOSE, true),
+    ("min", 60.0, TIME, false),
+    ("h", 3600.0, TIME, false),
+    ("d", 86_400.0, TIME, false),
+    ("wk", 604_800.0, TIME, false),
+    ("mo", 2_629_800.0, TIME, false),
+    ("a", 31_557_600.0, TIME, false),
+    ("[lb_av]", 453.592_37, MASS, false),
+    ("[oz_av]", 28.349_523_125, MASS, false),
+    ("[in_i]", 0.0254, LENGTH, false),
+    ("[ft_i]", 0.3048, LENGTH, false),
+    ("%", 0.01, NONE, false),
+];
+
+/// Metric prefixes, longest first so "da" is tried before "d".
+const PREFIXES: &[(&str, i32)] = &[
+    ("da", 1),
+    ("Y", 24),
+    ("Z", 21),
+    ("E", 18),
+    ("P", 15),
+    ("T", 12),
+    ("G", 9),
+    ("M", 6),
+    ("k", 3),
+    ("h", 2),
+    ("d", -1),
+    ("c", -2),
+    ("m", -3),
+    ("u", -6),
+    ("n", -9),
+    ("p", -12),
+    ("f", -15),
+    ("a", -18),
+];
+
+/// Largest `n` in `10*n`; beyond it an `f64` is infinite or zero anyway.
+const MAX_POWER_OF_TEN: u32 = 324;
+
+/// A unit as a multiple of base units.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Unit {
+    magnitude: f64,
+    dimension: Dimension,
+}
+
+impl Unit {
+    const ONE: Unit = Unit { magnitude: 1.0, dimension: NONE };
+
+    /// `None` when a dimension exponent overflows.
+    fn pow(self, exponent: i32) -> Option<Unit> {
+        let factor = i8::try_from(exponent).ok()?;
+        let mut dimension = self.dimension;
+        for d in &mut dimension {
+            *d = d.checked_mul(factor)?;
+        }
+        Some(Unit { magnitude: powi(self.magnitude, exponent), dimension })
+    }
+
+    /// `None` when a dimension exponent overflows.
+    fn mul(self, other: Unit) -> Option<Unit> {
+        let mut dimension = self.dimension;
+        for (d, o) in dimension.iter_mut().zip(other.dimension) {
+            *d = d.checked_add(o)?;
+        }
+        Some(Unit { magnitude: self.magnitude * other.magnitude, dimension })
+    }
+}
+
+/// `base` to an integer power, without `std`.
+fn powi(base: f64, exponent: i32) -> f64 {
+    let mut result = 1.0;
+    for _ in 0..exponent.unsigned_abs() {
+        result *= base;
+    }
+    if exponent < 0 {
+        1.0 / result
+    } else {
+        result
+    }
+}
+
+/// Parser over a unit expression: `term := ["/"] component (("." | "/") component)*`.
+struct Parser<'a> {
+    rest: &'a str,
+}
+
+impl Parser<'_> {
+    fn term(&mut self) -> Option<Unit> {
+        let mut unit = if let Some(rest) = self.rest.strip_prefix('/') {
+            self.rest = rest;
+            self.component()?.pow(-1)?
+        } else {
+            self.component()?
+        };
+        loop {
+            if let Some(rest) = self.rest.strip_prefix('.') {
+                self.rest = rest;
+                unit = unit.mul(self.component()?)?;
+            } else if let Some(rest) = self.rest.strip_prefix('/') {
+                self.rest = rest;
+                unit = unit.mul(self.component()?.pow(-1)?)?;
+            } else {
+                return Some(unit);
+            }
+        }
+    }
+
+    fn component(&mut self) -> Option<Unit> {
+        let unit = if let Some(rest) = self.rest.strip_prefix('(') {
+            self.rest = rest;
+            let unit = self.term()?;
+            self.rest = self.rest.strip_prefix(')')?;
+            unit
+        } else if self.rest.starts_with('{') {
+            Unit::ONE
+        } else if self.rest.starts_with(|c: char| c.is_ascii_digit()) {
+            self.factor()?
+        } else {
+            let symbol = &self.rest[..symbol_len(self.rest)];
+            let atom = atom(symbol)?;
+            self.rest = &self.rest[symbol.len()..];
+            atom.pow(self.exponent().unwrap_or(1))?
+        };
+        self.annotation()?;
+        Some(unit)
+    }
+
+    /// Integer factor, or a power of ten written `10*n` or `10^n`.
+    fn factor(&mut self) -> Option<Unit> {
+        let digits = self.rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(self.rest.len());
+        let (number, rest) = self.rest.split_at(digits);
+        self.rest = rest;
+        match self.rest.strip_prefix(['*', '^']) {
+            Some(rest) if number == "10" => {
+                self.rest = rest;
+                let exponent = self.exponent().filter(|exponent| exponent.unsigned_abs() <= MAX_POWER_OF_TEN)?;
+                Some(Unit { magnitude: powi(10.0, exponent), dimension: NONE })
+            }
+            Some(_) => None,
+            None => Some(Unit { magnitude: number.parse::<u32>().ok()?.into(), dimension: NONE }),
+        }
+    }
+
+    fn exponent(&mut self) -> Option<i32> {
+        let sign = self.rest.starts_with(['-', '+']) as usize;
+        let digits = self.rest[sign..].find(|c: char| !c.is_ascii_digit()).unwrap_or(self.rest.len() - sign);
+        if digits == 0 {
+            return None;
+        }
+        let (exponent, rest) = self.rest.split_at(sign + digits);
+        self.rest = rest;
+        exponent.parse().ok()
+    }
+
+    /// Skips an annotation in braces.
+    fn annotation(&mut self) -> Option<()> {
+        if let Some(rest) = self.rest.strip_prefix('{') {
+            self.rest = &rest[rest.find('}')? + 1..];
+        }
+        Some(())
+    }
+}
+
+/// Length of the symbol `rest` starts with: up to an operator, bracket,
+/// annotation or exponent outside square brackets (so "m[Hg]" is one symbol).
+fn symbol_len(rest: &str) -> usize {
+    let mut depth = 0;
+    for (at, c) in rest.char_indices() {
+        match c {
+            '[' => depth += 1,
+            ']' => depth -= 1,
+            _ if depth > 0 => {}
+            '.' | '/' | '(' | ')' | '{' | '+' | '-' | '0'..='9' => return at,
+            _ => {}
+        }
+    }
+    rest.len()
+}
+
+fn atom(symbol: &str) -> Option<Unit> {
+    let unit = |(_, magnitude, dimension, _): &(&str, f64, Dimension, bool)| Unit { magnitude: *magnitude, dimension: *dimension };
+    if let Some(found) = ATOMS.iter().find(|(code, ..)| *code == symbol) {
+        return Some(unit(found));
+    }
+    PREFIXES.iter().find_map(|(prefix, exponent)| {
+        let found = ATOMS.iter().find(|(code, _, _, metric)| *metric && symbol.strip_prefix(prefix) == Some(code))?;
+        Some(Unit { magnitude: unit(found).magnitude * powi(10.0, *exponent), ..unit(found) })
+    })
+}
+
+fn parse(unit: &str) -> Result<Unit> {
+    let mut parser = Parser { rest: unit };
+    match parser.term() {
+        Some(parsed) if parser.rest.is_empty() => Ok(parsed),
+        _ => Err(conversion_error(unit, format!("'{}' is not a supported UCUM unit", unit))),
+    }
+}
+
+fn conversion_error(unit: &str, message: alloc::string::String) -> WellAllyError {
+    WellAllyError::Conversion {
+        resource_type: "Quantity".to_string(),
+        path: "unit".to_string(),
+        value: Some(Box::new(unit.into())),
+        message,
+    }
+}
+
+/// Factor that turns a value in `from` into one in `to`; identical units
+/// always convert, even outside the supported subset.
+pub fn factor(from: &str, to: &str) -> Result<f64> {
+    if from == to {
+        return Ok(1.0);
+    }
+    let (source, target) = (parse(from)?, parse(to)?);
+    if source.dimension != target.dimension {
+        return Err(conversion_error(from, format!("cannot convert {} to {}: incompatible dimensions", from, to)));
+    }
+    Ok(source.magnitude / target.magnitude)
+}
+
+/// `value` in `from` expressed in `to`.
+pub fn convert(value: f64, from: &str, to: &str) -> Result<f64> {
+    Ok(value * factor(from, to)?)
+}
+
+/// Whether values in `a` and `b` convert into each other.
+pub fn commensurable(a: &str, b: &str) -> bool {
+    factor(a, b).is_ok()
+}
+
+/// `value` in `unit` and `other` in `other_unit` on a common scale, for
+/// comparing them.
+pub(crate) fn common_scale(value: f64, unit: &str, other: f64, other_unit: &str) -> Result<(f64, f64)> {
+    if unit == other_unit {
+        return Ok((value, other));
+    }
+    let (this, that) = (parse(unit)?, parse(other_unit)?);
+    if this.dimension != that.dimension {
+        return Err(conversion_error(other_unit, format!("cannot compare {} with {}: incompatible dimensions", unit, other_unit)));
+    }
+    Ok((value * this.magnitude, other * that.magnitude))
+}
@@ -0,0 +1,1725 @@
+//! Structural validation of WellAlly resources.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//!
+//! Checks the constraints of the published JSON Schemas that the Rust types
+//! cannot express on their own (non-empty strings, minimum item counts,
+//! value ranges) plus basic date coherence. With `std`, [`stream_validate`]
+//! checks large NDJSON exports and bundles record by record.
+
+use alloc::{format, string::{String, ToString}, vec::Vec};
+use serde::{Deserialize, Serialize};
+
+use crate::allergy::AllergyIntolerance;
+use crate::authz::Context;
+use crate::catalog;
+use crate::cgm::GlucoseSeries;
+use crate::common::{
+    Annotation, BodySite, CodeableConcept, Coding, HumanName, Identifier, Laterality, Period, Quantity, Reference,
+};
+use crate::communication::{CommunicationRecord, MessagePayload};
+use crate::condition::{ClinicalStatus, Condition};
+use crate::consent::ConsentRecord;
+use crate::ecg::EcgRecord;
+use crate::extension::{Extension, ExtensionValue};
+use crate::family_health::FamilyHealthTree;
+use crate::flag::Flag;
+use crate::migrations::SchemaVersion;
+use crate::health::Person;
+use crate::imaging_report::dexa::DexaReport;
+use crate::imaging_report::echo::{EchoReport, LV_SEGMENTS};
+use crate::imaging_report::measurements::{BiometryMeasurement, UltrasoundMeasurements};
+use crate::imaging_report::{AttachmentLimits, ImagingMeasurements, ImagingReport};
+use crate::lab_report::{LabReport, LabValue};
+use crate::location::Location;
+use crate::medication::MedicationRecord;
+use crate::nephrology::{AccessType, DialysisSession};
+use crate::questionnaire::{ItemType, Questionnaire, QuestionnaireItem, QuestionnaireResponse, ResponseItem};
+use crate::referral::{Referral, ReferralParty, ReferralStatus};
+use crate::rehab::RehabSession;
+use crate::wound::WoundAssessment;
+use crate::infusion::{self, InfusionRecord};
+use crate::pathology::PathologyReport;
+use crate::audiology::AudiogramReport;
+use crate::birth::BirthRecord;
+use crate::clearance::{ClearanceDocument, ClearanceOutcome};
+use crate::travel::TravelHealthRecord;
+use crate::compendium::Compendium;
+use crate::dispense::{DispenseRecord, MAX_DAYS_SUPPLY, MAX_REFILLS};
+use crate::immunization::ImmunizationRecord;
+use crate::relationships::RelatedPerson;
+use crate::security;
+use crate::task::Task;
+use crate::ucum;
+
+#[cfg(feature = "std")]
+mod stream;
+#[cfg(feature = "std")]
+pub use stream::{stream_validate, StreamIssue, StreamSummary};
+#[cfg(feature = "std")]
+pub(crate) use stream::detect as detect_resource_type;
+
+/// A single problem found while validating a resource.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ValidationIssue {
+    /// Path of the offending field using JSON names (e.g., "results[0].code.coding")
+    pub path: String,
+    /// Human-readable description of the problem
+    pub message: String,
+}
+
+impl ValidationIssue {
+    pub(crate) fn new(path: impl Into<String>, message: impl Into<String>) -> Self {
+        Self { path: path.into(), message: message.into() }
+    }
+}
+
+/// Types that can check their own structural constraints.
+pub trait Validate {
+    /// Returns every issue found; an empty list means the value is valid.
+    fn validate(&self) -> Vec<ValidationIssue>;
+
+    /// Whether the value has no validation issues.
+    fn is_valid(&self) -> bool {
+        self.validate().is_empty()
+    }
+}
+
+pub(crate) fn join(path: &str, field: &str) -> String {
+    if path.is_empty() {
+        field.to_string()
+    } else {
+        format!("{}.{}", path, field)
+    }
+}
+
+pub(crate) fn index(path: &str, i: usize) -> String {
+    format!("{}[{}]", path, i)
+}
+
+fn require_text(issues: &mut Vec<ValidationIssue>, path: String, value: &str) {
+    if value.trim().is_empty() {
+        issues.push(ValidationIssue::new(path, "must not be empty"));
+    }
+}
+
+fn require_items<T>(issues: &mut Vec<ValidationIssue>, path: String, items: &[T]) {
+    if items.is_empty() {
+        issues.push(ValidationIssue::new(path, "must contain at least one item"));
+    }
+}
+
+fn check_coding(issues: &mut Vec<ValidationIssue>, path: &str, coding: &Coding) {
+    require_text(issues, join(path, "system"), &coding.system);
+    require_text(issues, join(path, "code"), &coding.code);
+}
+
+fn check_body_site(issues: &mut Vec<ValidationIssue>, path: &str, site: &BodySite) {
+    check_coding(issues, &join(path, "site"), &site.site);
+    let lateralities = [Laterality::Left, Laterality::Right, Laterality::Bilateral].map(Laterality::snomed_code);
+    for (i, qualifier) in site.qualifiers.iter().enumerate() {
+        let path = index(&join(path, "qualifiers"), i);
+        check_coding(issues, &path, qualifier);
+        if qualifier.system == catalog::SNOMED_CT && lateralities.contains(&qualifier.code.as_str()) {
+            issues.push(ValidationIssue::new(join(&path, "code"), "must not be a laterality; use laterality"));
+        }
+    }
+}
+
+fn check_concept(issues: &mut Vec<ValidationIssue>, path: &str, concept: &CodeableConcept) {
+    let coding_path = join(path, "coding");
+    require_items(issues, coding_path.clone(), &concept.coding);
+    for (i, coding) in concept.coding.iter().enumerate() {
+        check_coding(issues, &index(&coding_path, i), coding);
+    }
+}
+
+fn check_concepts(issues: &mut Vec<ValidationIssue>, path: &str, concepts: &Option<Vec<CodeableConcept>>) {
+    for (i, concept) in concepts.iter().flatten().enumerate() {
+        check_concept(issues, &index(path, i), concept);
+    }
+}
+
+fn check_quantity(issues: &mut Vec<ValidationIssue>, path: &str, quantity: &Quantity) {
+    if !quantity.value.is_finite() {
+        issues.push(ValidationIssue::new(join(path, "value"), "must be a finite number"));
+    }
+    let unit_path = join(path, "unit");
+    if quantity.unit.is_empty() {
+        issues.push(ValidationIssue::new(unit_path, "must not be empty"));
+    } else if !quantity.unit.chars().all(is_ucum_char) {
+        issues.push(ValidationIssue::new(
+            unit_path,
+            format!("'{}' is not a valid UCUM unit", quantity.unit),
+        ));
+    }
+    match (&quantity.code, quantity.system.as_deref()) {
+        (Some(code), None | Some(catalog::UCUM)) if !code.chars().all(is_ucum_char) || code.is_empty() => {
+            issues.push(ValidationIssue::new(join(path, "code"), format!("'{}' is not a valid UCUM code", code)));
+        }
+        (None, Some(_)) => issues.push(ValidationIssue::new(join(path, "system"), "requires a code")),
+        _ => {}
+    }
+}
+
+fn check_lab_value(issues: &mut Vec<ValidationIssue>, path: String, value: &LabValue) {
+    match value {
+        LabValue::Quantity(quantity) => check_quantity(issues, &path, quantity),
+        LabValue::Ratio { numerator, denominator } => {
+            if !numerator.is_finite() {
+                issues.push(ValidationIssue::new(join(&path, "numerator"), "must be a finite number"));
+            }
+            if !denominator.is_finite() || *denominator <= 0.0 {
+                issues.push(ValidationIssue::new(join(&path, "denominator"), "must be a positive number"));
+            }
+        }
+        LabValue::Comparator { value, .. } => check_quantity(issues, &join(&path, "value"), value),
+        LabValue::Concept(concept) => check_concept(issues, &path, concept),
+        LabValue::String(text) => require_text(issues, path, text),
+    }
+}
+
+fn check_age(issues: &mut Vec<ValidationIssue>, path: &str, age: &Quantity) {
+    check_quantity(issues, path, age);
+    if !["a", "mo", "wk", "d", "h"].contains(&age.unit.as_str()) {
+        issues.push(ValidationIssue::new(join(path, "unit"), "must be a UCUM time unit (a, mo, wk, d, h)"));
+    }
+}
+
+/// Character set allowed by the `UCUMUnitPattern` definition.
+fn is_ucum_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "%[]{}().*/^+_-".contains(c)
+}
+
+fn check_identifiers(issues: &mut Vec<ValidationIssue>, path: &str, identifiers: &Option<Vec<Identifier>>) {
+    for (i, identifier) in identifiers.iter().flatten().enumerate() {
+        let path = index(path, i);
+        require_text(issues, join(&path, "system"), &identifier.system);
+        require_text(issues, join(&path, "value"), &identifier.value);
+        if let Some(concept) = &identifier.r#type {
+            check_concept(issues, &join(&path, "type"), concept);
+        }
+    }
+}
+
+fn check_reference(issues: &mut Vec<ValidationIssue>, path: &str, reference: &Reference) {
+    if reference.target().is_none() {
+        issues.push(ValidationIssue::new(
+            join(path, "reference"),
+            format!("'{}' is not a ResourceType/id reference", reference.reference),
+        ));
+    }
+}
+
+fn check_references(issues: &mut Vec<ValidationIssue>, path: &str, references: &Option<Vec<Reference>>) {
+    for (i, reference) in references.iter().flatten().enumerate() {
+        check_reference(issues, &index(path, i), reference);
+    }
+}
+
+fn check_schema_version(issues: &mut Vec<ValidationIssue>, version: &Option<String>) {
+    let Some(version) = version else {
+        return;
+    };
+    match version.parse::<SchemaVersion>() {
+        Err(err) => issues.push(ValidationIssue::new("schemaVersion", err.message())),
+        Ok(version) if version > SchemaVersion::CURRENT => issues.push(ValidationIssue::new(
+            "schemaVersion",
+            format!("{} is newer than the supported {}", version, SchemaVersion::CURRENT),
+        )),
+        Ok(_) => {}
+    }
+}
+
+fn check_security_labels(issues: &mut Vec<ValidationIssue>, labels: &Option<Vec<Coding>>) {
+    for (i, label) in labels.iter().flatten().enumerate() {
+        let path = index("securityLabels", i);
+        check_coding(issues, &path, label);
+        if label.system == catalog::CONFIDENTIALITY && !security::CONFIDENTIALITY_CODES.contains(&label.code.as_str()) {
+            issues.push(ValidationIssue::new(join(&path, "code"), format!("unknown confidentiality '{}'", label.code)));
+        }
+    }
+}
+
+fn check_extensions(issues: &mut Vec<ValidationIssue>, extensions: &Option<Vec<Extension>>) {
+    for (i, extension) in extensions.iter().flatten().enumerate() {
+        let path = index("extensions", i);
+        if !extension.url.split_once(':').is_some_and(|(scheme, rest)| !scheme.is_empty() && !rest.is_empty()) {
+            issues.push(ValidationIssue::new(
+                join(&path, "url"),
+                format!("'{}' is not an absolute URL", extension.url),
+            ));
+        }
+        match &extension.value {
+            ExtensionValue::Coding(coding) => check_coding(issues, &join(&path, "valueCoding"), coding),
+            ExtensionValue::CodeableConcept(concept) => {
+                check_concept(issues, &join(&path, "valueCodeableConcept"), concept)
+            }
+            ExtensionValue::Reference(reference) => check_reference(issues, &join(&path, "valueReference"), reference),
+            _ => {}
+        }
+    }
+}
+
+fn check_notes(issues: &mut Vec<ValidationIssue>, path: &str, notes: &Option<Vec<Annotation>>) {
+    for (i, note) in notes.iter().flatten().enumerate() {
+        let path = index(path, i);
+        require_text(issues, join(&path, "text"), &note.text);
+        if let Some(author) = &note.author_reference {
+            check_reference(issues, &join(&path, "authorReference"), author);
+            if note.author_string.is_some() {
+                issues.push(ValidationIssue::new(path, "must not have both authorReference and authorString"));
+            }
+        }
+    }
+}
+
+fn check_name(issues: &mut Vec<ValidationIssue>, path: &str, name: &HumanName) {
+    require_text(issues, join(path, "family"), &name.family);
+    require_items(issues, join(path, "given"), &name.given);
+}
+
+impl Validate for Person {
+    fn validate(&self) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+        require_text(&mut issues, "id".into(), &self.id);
+        if self.resource_type != "Person" {
+            issues.push(ValidationIssue::new("resourceType", "must be \"Person\""));
+        }
+        require_items(&mut issues, "name".into(), &self.name);
+        for (i, name) in self.name.iter().enumerate() {
+            check_name(&mut issues, &index("name", i), name);
+        }
+        check_identifiers(&mut issues, "identifier", &self.identifier);
+        for (i, contact) in self.telecom.iter().flatten().enumerate() {
+            require_text(&mut issues, join(&index("telecom", i), "value"), &contact.value);
+        }
+        if let Some(status) = &self.marital_status {
+            check_concept(&mut issues, "maritalStatus", status);
+        }
+        if let Some(summary) = &self.clinical_summary {
+            check_concepts(&mut issues, "clinicalSummary.conditions", &summary.conditions);
+            check_concepts(&mut issues, "clinicalSummary.allergies", &summary.allergies);
+            if let Some(vitals) = &summary.latest_vitals {
+                require_text(&mut issues, "clinicalSummary.latestVitals.reportId".into(), &vitals.report_id);
+            }
+        }
+        check_schema_version(&mut issues, &self.schema_version);
+        check_security_labels(&mut issues, &self.security_labels);
+        check_extensions(&mut issues, &self.extensions);
+        issues
+    }
+}
+
+impl Validate for LabReport {
+    fn validate(&self) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+        require_text(&mut issues, "id".into(), &self.id);
+        require_text(&mut issues, "patientId".into(), &self.patient_id);
+        require_items(&mut issues, "results".into(), &self.results);
+        for (i, result) in self.results.iter().enumerate() {
+            let path = index("results", i);
+            check_concept(&mut issues, &join(&path, "code"), &result.code);
+            check_lab_value(&mut issues, join(&path, "value"), &result.value);
+            for (j, range) in result.reference_range.iter().flatten().enumerate() {
+                let range_path = index(&join(&path, "referenceRange"), j);
+                if let Some(low) = &range.low {
+                    check_quantity(&mut issues, &join(&range_path, "low"), low);
+                }
+                if let Some(high) = &range.high {
+                    check_quantity(&mut issues, &join(&range_path, "high"), high);
+                }
+                for (k, concept) in range.applies_to.iter().flatten().enumerate() {
+                    check_concept(&mut issues, &index(&join(&range_path, "appliesTo"), k), concept);
+                }
+                if let Some(age) = &range.age {
+                    for (bound, quantity) in [("low", &age.low), ("high", &age.high)] {
+                        if let Some(quantity) = quantity {
+                            check_age(&mut issues, &join(&join(&range_path, "age"), bound), quantity);
+                        }
+                    }
+                }
+            }
+            if let Some(method) = &result.method {
+                check_concept(&mut issues, &join(&path, "method"), method);
+            }
+        }
+        if let Some(panel) = &self.panel {
+            check_concept(&mut issues, "panel", panel);
+        }
+        if let Some(coding) = self.specimen.as_ref().and_then(|s| s.specimen_type.as_ref()) {
+            check_coding(&mut issues, "specimen.type", coding);
+        }
+        check_notes(&mut issues, "notes", &self.notes);
+        check_schema_version(&mut issues, &self.schema_version);
+        check_security_labels(&mut issues, &self.security_labels);
+        check_extensions(&mut issues, &self.extensions);
+        issues
+    }
+}
+
+impl Validate for ImagingReport {
+    fn validate(&self) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+        require_text(&mut issues, "id".into(), &self.id);
+        require_text(&mut issues, "patientId".into(), &self.patient_id);
+        require_text(&mut issues, "modality.system".into(), &self.modality.system);
+        require_text(&mut issues, "modality.code".into(), self.modality.code.as_str());
+        check_body_site(&mut issues, "bodySite", &self.body_site);
+        if let Some(based_on) = &self.based_on {
+            check_reference(&mut issues, "basedOn", based_on);
+            if based_on.target().is_some_and(|(resource_type, _)| resource_type != "ServiceRequest") {
+                issues.push(ValidationIssue::new("basedOn.reference", "must reference a ServiceRequest"));
+            }
+        }
+        check_concepts(&mut issues, "reasonCode", &self.reason_code);
+        if let Some(history) = &self.clinical_history {
+            require_text(&mut issues, "clinicalHistory".into(), history);
+        }
+        if let Some(protocol) = &self.protocol {
+            check_concept(&mut issues, "protocol", protocol);
+        }
+        for (i, uid) in self.comparison_study_uids.iter().enumerate() {
+            let path = index("comparisonStudyUids", i);
+            if !is_dicom_uid(uid) {
+                issues.push(ValidationIssue::new(path, "must be a DICOM UID"));
+            } else if self.study_instance_uid.as_ref() == Some(uid) {
+                issues.push(ValidationIssue::new(path, "must not be the report's own study"));
+            } else if self.comparison_study_uids[..i].contains(uid) {
+                issues.push(ValidationIssue::new(path, "must be unique"));
+            }
+        }
+        if let Some(dose) = &self.radiation_dose {
+            for (field, value) in [("ctdiVol_mGy", dose.ctdi_vol_mgy), ("dlp_mGy_cm", dose.dlp_mgy_cm)] {
+                if value.is_some_and(|v| !v.is_finite() || v < 0.0) {
+                    issues.push(ValidationIssue::new(
+                        join("radiationDose", field),
+                        "must be a non-negative number",
+                    ));
+                }
+            }
+        }
+        if let Some(measurements) = &self.measurements {
+            if measurements.modality() != self.modality.code {
+                issues.push(ValidationIssue::new(
+                    "measurements.type",
+                    format!("must match modality {}", self.modality.code.as_str()),
+                ));
+            }
+            check_imaging_measurements(&mut issues, "measurements", measurements);
+        }
+        for (i, attachment) in self.attachments.iter().flatten().enumerate() {
+            AttachmentLimits::default().check_into(&mut issues, &index("attachments", i), attachment);
+        }
+        check_notes(&mut issues, "notes", &self.notes);
+        check_schema_version(&mut issues, &self.schema_version);
+        check_security_labels(&mut issues, &self.security_labels);
+        check_extensions(&mut issues, &self.extensions);
+        issues
+    }
+}
+
+/// Digits in dot-separated components without leading zeros, at most 64 characters (DICOM PS3.5 9.1).
+fn is_dicom_uid(uid: &str) -> bool {
+    uid.len() <= 64
+        && uid.split('.').all(|component| {
+            !component.is_empty()
+                && component.bytes().all(|b| b.is_ascii_digit())
+                && (component == "0" || !component.starts_with('0'))
+        })
+}
+
+fn check_imaging_measurements(issues: &mut Vec<ValidationIssue>, path: &str, measurements: &ImagingMeasurements) {
+    match measurements {
+        ImagingMeasurements::Ultrasound(ultrasound) => check_ultrasound(issues, path, ultrasound),
+        ImagingMeasurements::Echocardiography(echo) => check_echo(issues, path, echo),
+        ImagingMeasurements::Dexa(dexa) => check_dexa(issues, path, dexa),
+    }
+}
+
+fn check_dexa(issues: &mut Vec<ValidationIssue>, path: &str, dexa: &DexaReport) {
+    for (i, density) in dexa.sites.iter().enumerate() {
+        let path = index(&join(path, "sites"), i);
+        if dexa.sites[..i].iter().any(|earlier| earlier.site == density.site) {
+            issues.push(ValidationIssue::new(join(&path, "site"), "must be unique"));
+        }
+        check_measured(issues, &join(&path, "bmd"), Some(&density.bmd), "g/cm2", "must be an areal density");
+        if density.bmd.value <= 0.0 {
+            issues.push(ValidationIssue::new(join(&path, "bmd.value"), "must be positive"));
+        }
+        for (name, score) in [("tScore", density.t_score), ("zScore", density.z_score)] {
+            if score.is_some_and(|score| !score.is_finite()) {
+                issues.push(ValidationIssue::new(join(&path, name), "must be a finite number"));
+            }
+        }
+    }
+    if let Some(frax) = &dexa.frax {
+        let path = join(path, "frax");
+        if !(40..=90).contains(&frax.age) {
+            issues.push(ValidationIssue::new(join(&path, "age"), "must be between 40 and 90"));
+        }
+        check_measured(issues, &join(&path, "weight"), frax.weight.as_ref(), "kg", "must be a mass");
+        check_measured(issues, &join(&path, "height"), frax.height.as_ref(), "cm", "must be a length");
+    }
+}
+
+fn check_echo(issues: &mut Vec<ValidationIssue>, path: &str, echo: &EchoReport) {
+    if echo.lv_ejection_fraction.is_some_and(|percent| !(0.0..=100.0).contains(&percent)) {
+        issues.push(ValidationIssue::new(join(path, "lvEjectionFraction"), "must be between 0 and 100"));
+    }
+    if let Some(chambers) = &echo.chambers {
+        let path = join(path, "chambers");
+        for (name, dimension) in chambers.measured() {
+            check_measured(issues, &join(&path, name), Some(dimension), "mm", "must be a length");
+        }
+    }
+    for (i, assessment) in echo.valves.iter().enumerate() {
+        if echo.valves[..i].iter().any(|earlier| earlier.valve == assessment.valve) {
+            issues.push(ValidationIssue::new(join(&index(&join(path, "valves"), i), "valve"), "must be unique"));
+        }
+    }
+    for (i, scored) in echo.wall_motion.iter().enumerate() {
+        let path = join(&index(&join(path, "wallMotion"), i), "segment");
+        if !(1..=LV_SEGMENTS).contains(&scored.segment) {
+            issues.push(ValidationIssue::new(path, format!("must be between 1 and {}", LV_SEGMENTS)));
+        } else if echo.wall_motion[..i].iter().any(|earlier| earlier.segment == scored.segment) {
+            issues.push(ValidationIssue::new(path, "must be unique"));
+        }
+    }
+}
+
+fn check_ultrasound(issues: &mut Vec<ValidationIssue>, path: &str, ultrasound: &UltrasoundMeasurements) {
+    if let Some(biometry) = &ultrasound.fetal_biometry {
+        let path = join(path, "fetalBiometry");
+        let gestational_age = biometry.gestational_age.as_ref();
+        check_measured(issues, &join(&path, "gestationalAge"), gestational_age, "wk", "must be a time");
+        for (name, measurement) in biometry.lengths() {
+            check_biometry(issues, &join(&path, name), measurement, "mm", "must be a length");
+        }
+        if let Some(weight) = &biometry.estimated_fetal_weight {
+            check_biometry(issues, &join(&path, "estimatedFetalWeight"), weight, "g", "must be a mass");
+        }
+    }
+    for (i, organ) in ultrasound.organs.iter().enumerate() {
+        let path = index(&join(path, "organs"), i);
+        check_coding(issues, &join(&path, "organ"), &organ.organ);
+        for (axis, length) in [("length", &organ.length), ("width", &organ.width), ("depth", &organ.depth)] {
+            check_measured(issues, &join(&path, axis), length.as_ref(), "mm", "must be a length");
+        }
+        check_measured(issues, &join(&path, "volume"), organ.volume.as_ref(), "mL", "must be a volume");
+    }
+}
+
+fn check_biometry(
+    issues: &mut Vec<ValidationIssue>,
+    path: &str,
+    measurement: &BiometryMeasurement,
+    unit: &str,
+    message: &str,
+) {
+    check_measured(issues, &join(path, "value"), Some(&measurement.value), unit, message);
+    if measurement.percentile.is_some_and(|percentile| !(0.0..=100.0).contains(&percentile)) {
+        issues.push(ValidationIssue::new(join(path, "percentile"), "must be between 0 and 100"));
+    }
+}
+
+/// Checks an optional quantity and that its unit converts to `unit`.
+fn check_measured(
+    issues: &mut Vec<ValidationIssue>,
+    path: &str,
+    quantity: Option<&Quantity>,
+    unit: &str,
+    message: &str,
+) {
+    if let Some(quantity) = quantity {
+        check_quantity(issues, path, quantity);
+        if !ucum::commensurable(&quantity.unit, unit) {
+            issues.push(ValidationIssue::new(join(path, "unit"), message));
+        }
+    }
+}
+
+impl Validate for MedicationRecord {
+    fn validate(&self) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+        require_text(&mut issues, "id".into(), &self.id);
+        require_text(&mut issues, "patientId".into(), &self.patient_id);
+        check_coding(&mut issues, "medication", &self.medication);
+        if !self.dosage.value.is_finite() || self.dosage.value < 0.0 {
+            issues.push(ValidationIssue::new("dosage.value", "must be a non-negative number"));
+        }
+        require_text(&mut issues, "dosage.unit".into(), &self.dosage.unit);
+        require_text(&mut issues, "route.system".into(), &self.route.system);
+        require_text(&mut issues, "route.code".into(), &self.route.code);
+        if let Some(form) = &self.form {
+            check_coding(&mut issues, "form", form);
+        }
+        if self.duration_days.is_some_and(|days| days < 1) {
+            issues.push(ValidationIssue::new("durationDays", "must be at least 1"));
+        }
+        if self.end_date.is_some_and(|end| end < self.start_date) {
+            issues.push(ValidationIssue::new("endDate", "must not be before startDate"));
+        }
+        if let Some(indication) = &self.indication {
+            check_concept(&mut issues, "indication", indication);
+        }
+        if let Some(reason) = &self.prn_reason {
+            check_concept(&mut issues, "prnReason", reason);
+            if !self.is_prn {
+                issues.push(ValidationIssue::new("isPrn", "must be true when prnReason is set"));
+            }
+        }
+        match &self.max_dose_per_day {
+            Some(max) => {
+                check_quantity(&mut issues, "maxDosePerDay", max);
+                let dose = ucum::convert(self.dosage.value, &self.dosage.unit, &max.unit);
+                if dose.is_err() {
+                    issues.push(ValidationIssue::new("maxDosePerDay.unit", "must be commensurable with dosage.unit"));
+                } else if dose.is_ok_and(|dose| dose > max.value) {
+                    issues.push(ValidationIssue::new("maxDosePerDay", "must not be less than one dose"));
+                }
+            }
+            None if self.is_prn => {
+                issues.push(ValidationIssue::new("maxDosePerDay", "must be set for PRN medications"));
+            }
+            None => {}
+        }
+        check_notes(&mut issues, "notes", &self.notes);
+        check_schema_version(&mut issues, &self.schema_version);
+        check_security_labels(&mut issues, &self.security_labels);
+        check_extensions(&mut issues, &self.extensions);
+        issues
+    }
+}
+
+impl Validate for FamilyHealthTree {
+    fn validate(&self) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+        require_text(&mut issues, "probandId".into(), &self.proband_id);
+        require_items(&mut issues, "members".into(), &self.members);
+        for (i, member) in self.members.iter().enumerate() {
+            let path = index("members", i);
+            require_text(&mut issues, join(&path, "id"), &member.id);
+            if let Some(person_id) = &member.person_id {
+                require_text(&mut issues, join(&path, "personId"), person_id);
+            }
+            if member.birth_year.is_some_and(|year| !(1900..=2100).contains(&year)) {
+                issues.push(ValidationIssue::new(
+                    join(&path, "birthYear"),
+                    "must be between 1900 and 2100",
+                ));
+            }
+            for (field, parent) in [("motherId", &member.mother_id), ("fatherId", &member.father_id)] {
+                let Some(parent) = parent else { continue };
+                if *parent == member.id {
+                    issues.push(ValidationIssue::new(join(&path, field), "must not be the member itself"));
+                } else if !self.members.iter().any(|other| other.id == *parent) {
+                    issues.push(ValidationIssue::new(join(&path, field), "must be the id of a member"));
+                }
+            }
+            check_concepts(&mut issues, &join(&path, "conditions"), &member.conditions);
+            for (j, onset) in member.condition_onsets.iter().enumerate() {
+                let path = index(&join(&path, "conditionOnsets"), j);
+                check_coding(&mut issues, &join(&path, "condition"), &onset.condition);
+                let recorded = member.conditions.iter().flatten().flat_map(|concept| &concept.coding).any(|coding| {
+                    coding.system == onset.condition.system && coding.code == onset.condition.code
+                });
+                if !recorded {
+                    issues.push(ValidationIssue::new(
+                        join(&path, "condition"),
+                        "must be one of the member's conditions",
+                    ));
+                }
+                if onset.age_at_onset > 130 {
+                    issues.push(ValidationIssue::new(join(&path, "ageAtOnset"), "must be at most 130"));
+                }
+            }
+            check_notes(&mut issues, &join(&path, "notes"), &member.notes);
+        }
+        check_schema_version(&mut issues, &self.schema_version);
+        check_security_labels(&mut issues, &self.security_labels);
+        check_extensions(&mut issues, &self.extensions);
+        issues
+    }
+}
+
+fn check_questionnaire_item(
+    issues: &mut Vec<ValidationIssue>,
+    path: &str,
+    item: &QuestionnaireItem,
+    questionnaire: &Questionnaire,
+) {
+    require_text(issues, join(path, "linkId"), &item.link_id);
+    if matches!(item.r#type, ItemType::Choice | ItemType::OpenChoice) {
+        require_items(issues, join(path, "answerOption"), item.answer_option.as_deref().unwrap_or_default());
+    }
+    for (i, option) in item.answer_option.iter().flatten().enumerate() {
+        if option.score.is_some_and(|score| !score.is_finite()) {
+            issues.push(ValidationIssue::new(join(&index(&join(path, "answerOption"), i), "score"), "must be a finite number"));
+        }
+    }
+    for (i, condition) in item.enable_when.iter().flatten().enumerate() {
+        if questionnaire.find(&condition.question).is_none() {
+            issues.push(ValidationIssue::new(
+                join(&index(&join(path, "enableWhen"), i), "question"),
+                format!("unknown item '{}'", condition.question),
+            ));
+        }
+    }
+    if item.r#type == ItemType::Group {
+        require_items(issues, join(path, "item"), item.item.as_deref().unwrap_or_default());
+    }
+    for (i, child) in item.item.iter().flatten().enumerate() {
+        check_questionnaire_item(issues, &index(&join(path, "item"), i), child, questionnaire);
+    }
+}
+
+impl Validate for Questionnaire {
+    fn validate(&self) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+        require_text(&mut issues, "id".into(), &self.id);
+        require_items(&mut issues, "item".into(), &self.item);
+        for (i, item) in self.item.iter().enumerate() {
+            check_questionnaire_item(&mut issues, &index("item", i), item, self);
+        }
+        let items = self.items();
+        for (i, item) in items.iter().enumerate() {
+            if items[..i].iter().any(|earlier| earlier.link_id == item.link_id) {
+                issues.push(ValidationIssue::new("item", format!("duplicate linkId '{}'", item.link_id)));
+            }
+        }
+        check_schema_version(&mut issues, &self.schema_version);
+        check_security_labels(&mut issues, &self.security_labels);
+        check_extensions(&mut issues, &self.extensions);
+        issues
+    }
+}
+
+fn check_response_item(issues: &mut Vec<ValidationIssue>, path: &str, item: &ResponseItem) {
+    require_text(issues, join(path, "linkId"), &item.link_id);
+    for (i, child) in item.item.iter().flatten().enumerate() {
+        check_response_item(issues, &index(&join(path, "item"), i), child);
+    }
+}
+
+impl Validate for QuestionnaireResponse {
+    fn validate(&self) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+        require_text(&mut issues, "id".into(), &self.id);
+        require_text(&mut issues, "patientId".into(), &self.patient_id);
+        require_text(&mut issues, "questionnaire".into(), &self.questionnaire);
+        for (i, item) in self.item.iter().enumerate() {
+            check_response_item(&mut issues, &index("item", i), item);
+        }
+        check_schema_version(&mut issues, &self.schema_version);
+        check_security_labels(&mut issues, &self.security_labels);
+        check_extensions(&mut issues, &self.extensions);
+        issues
+    }
+}
+
+fn check_party(issues: &mut Vec<ValidationIssue>, path: &str, party: &ReferralParty) {
+    if party.practitioner.is_none() && party.organization.is_none() {
+        issues.push(ValidationIssue::new(path, "must name a practitioner or an organization"));
+    }
+}
+
+impl Validate for Referral {
+    fn validate(&self) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+        require_text(&mut issues, "id".into(), &self.id);
+        require_text(&mut issues, "patientId".into(), &self.patient_id);
+        check_party(&mut issues, "requester", &self.requester);
+        if let Some(recipient) = &self.recipient {
+            check_party(&mut issues, "recipient", recipient);
+        } else if self.status == ReferralStatus::Active {
+            issues.push(ValidationIssue::new("recipient", "is required once the referral is active"));
+        }
+        if let Some(service) = &self.service_requested {
+            check_concept(&mut issues, "serviceRequested", service);
+        }
+        check_concepts(&mut issues, "reasonCode", &self.reason_code);
+        check_references(&mut issues, "supportingInfo", &self.supporting_info);
+        check_schema_version(&mut issues, &self.schema_version);
+        check_security_labels(&mut issues, &self.security_labels);
+        check_extensions(&mut issues, &self.extensions);
+        issues
+    }
+}
+
+impl Validate for Task {
+    fn validate(&self) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+        require_text(&mut issues, "id".into(), &self.id);
+        require_text(&mut issues, "patientId".into(), &self.patient_id);
+        require_text(&mut issues, "description".into(), &self.description);
+        if let Some(code) = &self.code {
+            check_concept(&mut issues, "code", code);
+        }
+        if let Some(focus) = &self.focus {
+            check_reference(&mut issues, "focus", focus);
+        }
+        check_references(&mut issues, "basedOn", &self.based_on);
+        if let (Some(authored), Some(due)) = (self.authored_on, self.due_date) {
+            if due.last_date() < authored.local().date() {
+                issues.push(ValidationIssue::new("dueDate", "must not be before authoredOn"));
+            }
+        }
+        check_schema_version(&mut issues, &self.schema_version);
+        check_security_labels(&mut issues, &self.security_labels);
+        check_extensions(&mut issues, &self.extensions);
+        issues
+    }
+}
+
+impl Validate for CommunicationRecord {
+    fn validate(&self) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+        require_text(&mut issues, "id".into(), &self.id);
+        require_text(&mut issues, "patientId".into(), &self.patient_id);
+        check_reference(&mut issues, "sender", &self.sender);
+        require_items(&mut issues, "recipient".into(), &self.recipient);
+        for (i, recipient) in self.recipient.iter().enumerate() {
+            check_reference(&mut issues, &index("recipient", i), recipient);
+        }
+        require_items(&mut issues, "payload".into(), &self.payload);
+        for (i, payload) in self.payload.iter().enumerate() {
+            if let MessagePayload::Attachment(attachment) = payload {
+                AttachmentLimits::default().check_into(&mut issues, &join(&index("payload", i), "contentAttachment"), attachment);
+            }
+        }
+        if let Some(topic) = &self.topic {
+            check_concept(&mut issues, "topic", topic);
+        }
+        if let Some(reply) = &self.in_response_to {
+            check_reference(&mut issues, "inResponseTo", reply);
+            if reply.target().is_some() && self.reply_to().is_none() {
+                issues.push(ValidationIssue::new("inResponseTo.reference", "must reference a CommunicationRecord"));
+            }
+        }
+        if let (Some(sent), Some(received)) = (self.sent, self.received) {
+            if received.to_utc() < sent.to_utc() {
+                issues.push(ValidationIssue::new("received", "must not be before sent"));
+            }
+        }
+        check_schema_version(&mut issues, &self.schema_version);
+        check_security_labels(&mut issues, &self.security_labels);
+        check_extensions(&mut issues, &self.extensions);
+        issues
+    }
+}
+
+impl Validate for Location {
+    fn validate(&self) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+        require_text(&mut issues, "id".into(), &self.id);
+        require_text(&mut issues, "name".into(), &self.name);
+        check_concepts(&mut issues, "type", &self.location_type);
+        if let Some(position) = &self.position {
+            if !(-90.0..=90.0).contains(&position.latitude) {
+                issues.push(ValidationIssue::new("position.latitude", "must be between -90 and 90"));
+            }
+            if !(-180.0..=180.0).contains(&position.longitude) {
+                issues.push(ValidationIssue::new("position.longitude", "must be between -180 and 180"));
+            }
+        }
+        if let Some(parent) = &self.part_of {
+            check_reference(&mut issues, "partOf", parent);
+            if parent.target() == Some(("Location", self.id.as_str())) {
+                issues.push(ValidationIssue::new("partOf", "must not reference the location itself"));
+            }
+        }
+        for (i, hours) in self.hours_of_operation.iter().flatten().enumerate() {
+            let path = index("hoursOfOperation", i);
+            require_items(&mut issues, join(&path, "daysOfWeek"), &hours.days_of_week);
+            if hours.all_day != Some(true) && (hours.opening_time.is_none() || hours.closing_time.is_none()) {
+                issues.push(ValidationIssue::new(path, "must be allDay or have openingTime and closingTime"));
+            }
+        }
+        check_schema_version(&mut issues, &self.schema_version);
+        check_security_labels(&mut issues, &self.security_labels);
+        check_extensions(&mut issues, &self.extensions);
+        issues
+    }
+}
+
+impl Validate for Condition {
+    fn validate(&self) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+        require_text(&mut issues, "id".into(), &self.id);
+        require_text(&mut issues, "patientId".into(), &self.patient_id);
+        check_concept(&mut issues, "code", &self.code);
+        if let Some(severity) = &self.severity {
+            check_concept(&mut issues, "severity", severity);
+        }
+        if let Some(abatement) = self.abatement {
+            if !matches!(self.clinical_status, ClinicalStatus::Inactive | ClinicalStatus::Remission | ClinicalStatus::Resolved) {
+                issues.push(ValidationIssue::new("abatement", "requires an inactive, remission or resolved clinicalStatus"));
+            }
+            if self.onset.is_some_and(|onset| abatement.to_utc() < onset.to_utc()) {
+                issues.push(ValidationIssue::new("abatement", "must not be before onset"));
+            }
+        }
+        check_notes(&mut issues, "notes", &self.notes);
+        check_schema_version(&mut issues, &self.schema_version);
+        check_security_labels(&mut issues, &self.security_labels);
+        check_extensions(&mut issues, &self.extensions);
+        issues
+    }
+}
+
+impl Validate for AllergyIntolerance {
+    fn validate(&self) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+        require_text(&mut issues, "id".into(), &self.id);
+        require_text(&mut issues, "patientId".into(), &self.patient_id);
+        check_concept(&mut issues, "code", &self.code);
+        for (i, reaction) in self.reactions.iter().flatten().enumerate() {
+            if reaction.manifestation.is_empty() {
+                issues.push(ValidationIssue::new(format!("reactions[{}].manifestation", i), "must not be empty"));
+            }
+            for (j, manifestation) in reaction.manifestation.iter().enumerate() {
+                check_concept(&mut issues, &format!("reactions[{}].manifestation[{}]", i, j), manifestation);
+            }
+        }
+        check_notes(&mut issues, "notes", &self.notes);
+        check_schema_version(&mut issues, &self.schema_version);
+        check_security_labels(&mut issues, &self.security_labels);
+        check_extensions(&mut issues, &self.extensions);
+        issues
+    }
+}
+
+impl Validate for Flag {
+    fn validate(&self) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+        require_text(&mut issues, "id".into(), &self.id);
+        require_text(&mut issues, "patientId".into(), &self.patient_id);
+        check_concept(&mut issues, "code", &self.code);
+        if let Some(period) = &self.period {
+            if let (Some(start), Some(end)) = (period.start, period.end) {
+                if end < start {
+                    issues.push(ValidationIssue::new("period.end", "must not be before period.start"));
+                }
+            }
+        }
+        if let Some(author) = &self.author {
+            check_reference(&mut issues, "author", author);
+        }
+        if let Some(focus) = &self.focus {
+            check_reference(&mut issues, "focus", focus);
+        }
+        check_schema_version(&mut issues, &self.schema_version);
+        check_security_labels(&mut issues, &self.security_labels);
+        check_extensions(&mut issues, &self.extensions);
+        issues
+    }
+}
+
+impl Validate for GlucoseSeries {
+    fn validate(&self) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+        require_text(&mut issues, "id".into(), &self.id);
+        require_text(&mut issues, "patientId".into(), &self.patient_id);
+        if let Some(device) = &self.device {
+            check_reference(&mut issues, "device", device);
+        }
+        if !ucum::commensurable(&self.unit, "mg/dL") && !ucum::commensurable(&self.unit, "mmol/L") {
+            issues.push(ValidationIssue::new("unit", format!("'{}' is not a glucose concentration unit", self.unit)));
+        }
+        if self.interval_seconds == 0 {
+            issues.push(ValidationIssue::new("intervalSeconds", "must be positive"));
+        }
+        for (i, value) in self.values.iter().enumerate() {
+            if value.is_some_and(|value| !value.is_finite() || value < 0.0) {
+                issues.push(ValidationIssue::new(format!("values[{}]", i), "must be a non-negative number"));
+            }
+        }
+        if let Some(counts) = &self.counts {
+            if counts.len() != self.values.len() {
+                issues.push(ValidationIssue::new("counts", "must have one count per value"));
+            }
+            for (i, _) in counts.iter().enumerate().filter(|(_, count)| **count == 0) {
+                issues.push(ValidationIssue::new(format!("counts[{}]", i), "must be positive"));
+            }
+        }
+        check_schema_version(&mut issues, &self.schema_version);
+        check_security_labels(&mut issues, &self.security_labels);
+        check_extensions(&mut issues, &self.extensions);
+        issues
+    }
+}
+
+impl Validate for EcgRecord {
+    fn validate(&self) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+        require_text(&mut issues, "id".into(), &self.id);
+        require_text(&mut issues, "patientId".into(), &self.patient_id);
+        if let Some(device) = &self.device {
+            check_reference(&mut issues, "device", device);
+        }
+        if !(self.sampling_rate.is_finite() && self.sampling_rate > 0.0) {
+            issues.push(ValidationIssue::new("samplingRate", "must be positive"));
+        }
+        require_items(&mut issues, "leads".into(), &self.leads);
+        for (i, lead) in self.leads.iter().enumerate() {
+            let path = index("leads", i);
+            if self.leads[..i].iter().any(|earlier| earlier.lead == lead.lead) {
+                issues.push(ValidationIssue::new(join(&path, "lead"), "duplicates an earlier lead"));
+            }
+            if lead.data.is_none() && lead.attachment.is_none() {
+                issues.push(ValidationIssue::new(path.clone(), "needs data or an attachment"));
+            }
+            if lead.factor.is_some_and(|factor| !(factor.is_finite() && factor > 0.0)) {
+                issues.push(ValidationIssue::new(join(&path, "factor"), "must be positive"));
+            }
+            if let Some(attachment) = &lead.attachment {
+                AttachmentLimits::default().check_into(&mut issues, &join(&path, "attachment"), attachment);
+            }
+        }
+        if let Some(intervals) = &self.intervals {
+            let measured = [
+                ("heartRate", intervals.heart_rate),
+                ("prInterval", intervals.pr_interval),
+                ("qrsDuration", intervals.qrs_duration),
+                ("qtInterval", intervals.qt_interval),
+                ("qtcInterval", intervals.qtc_interval),
+            ];
+            for (name, value) in measured {
+                if value.is_some_and(|value| !(value.is_finite() && value > 0.0)) {
+                    issues.push(ValidationIssue::new(join("intervals", name), "must be positive"));
+                }
+            }
+        }
+        check_concepts(&mut issues, "interpretation", &self.interpretation);
+        check_schema_version(&mut issues, &self.schema_version);
+        check_security_labels(&mut issues, &self.security_labels);
+        check_extensions(&mut issues, &self.extensions);
+        issues
+    }
+}
+
+impl Validate for ConsentRecord {
+    fn validate(&self) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+        require_text(&mut issues, "id".into(), &self.id);
+        require_text(&mut issues, "patientId".into(), &self.patient_id);
+        check_period(&mut issues, "period", &self.period);
+        for (i, provision) in self.provisions.iter().flatten().enumerate() {
+            let path = index("provisions", i);
+            if provision.purposes.as_ref().is_some_and(Vec::is_empty) {
+                issues.push(ValidationIssue::new(join(&path, "purposes"), "must not be empty"));
+            }
+            check_references(&mut issues, &join(&path, "actors"), &provision.actors);
+            for (j, category) in provision.categories.iter().flatten().enumerate() {
+                check_coding(&mut issues, &index(&join(&path, "categories"), j), category);
+            }
+            for (j, resource_type) in provision.resource_types.iter().flatten().enumerate() {
+                require_text(&mut issues, index(&join(&path, "resourceTypes"), j), resource_type);
+            }
+            check_period(&mut issues, &join(&path, "period"), &provision.period);
+        }
+        check_schema_version(&mut issues, &self.schema_version);
+        check_security_labels(&mut issues, &self.security_labels);
+        check_extensions(&mut issues, &self.extensions);
+        issues
+    }
+}
+
+impl Validate for DialysisSession {
+    fn validate(&self) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+        require_text(&mut issues, "id".into(), &self.id);
+        require_text(&mut issues, "patientId".into(), &self.patient_id);
+        if self.duration_minutes == 0 {
+            issues.push(ValidationIssue::new("durationMinutes", "must be positive"));
+        }
+        if let Some(access) = &self.access {
+            let peritoneal = access.access_type == AccessType::PeritonealCatheter;
+            if peritoneal != self.modality.is_peritoneal() {
+                issues.push(ValidationIssue::new("access.type", "does not match the modality"));
+            }
+            if let Some(site) = &access.site {
+                check_coding(&mut issues, "access.site", site);
+            }
+        }
+        let measured = [
+            ("ultrafiltrationVolume", &self.ultrafiltration_volume, "mL", "must be a volume"),
+            ("preWeight", &self.pre_weight, "kg", "must be a mass"),
+            ("postWeight", &self.post_weight, "kg", "must be a mass"),
+        ];
+        for (path, quantity, unit, message) in measured {
+            if let Some(quantity) = quantity {
+                check_quantity(&mut issues, path, quantity);
+                if !ucum::commensurable(&quantity.unit, unit) {
+                    issues.push(ValidationIssue::new(join(path, "unit"), message));
+                }
+            }
+        }
+        if self.kt_v.is_some_and(|kt_v| !(kt_v.is_finite() && kt_v > 0.0)) {
+            issues.push(ValidationIssue::new("ktV", "must be positive"));
+        }
+        check_concepts(&mut issues, "complications", &self.complications);
+        if let Some(device) = &self.device {
+            check_reference(&mut issues, "device", device);
+        }
+        check_schema_version(&mut issues, &self.schema_version);
+        check_security_labels(&mut issues, &self.security_labels);
+        check_extensions(&mut issues, &self.extensions);
+        issues
+    }
+}
+
+impl Validate for RehabSession {
+    fn validate(&self) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+        require_text(&mut issues, "id".into(), &self.id);
+        require_text(&mut issues, "patientId".into(), &self.patient_id);
+        check_coding(&mut issues, "therapyType", &self.therapy_type);
+        if self.duration_minutes == Some(0) {
+            issues.push(ValidationIssue::new("durationMinutes", "must be positive"));
+        }
+        if let Some(site) = &self.body_site {
+            check_body_site(&mut issues, "bodySite", site);
+        }
+        if let Some(condition) = &self.condition {
+            check_reference(&mut issues, "condition", condition);
+        }
+        if let Some(therapist) = &self.therapist {
+            check_reference(&mut issues, "therapist", therapist);
+        }
+        for (i, exercise) in self.exercises.iter().enumerate() {
+            let path = index("exercises", i);
+            require_text(&mut issues, join(&path, "name"), &exercise.name);
+            if let Some(code) = &exercise.code {
+                check_coding(&mut issues, &join(&path, "code"), code);
+            }
+            let counts = [("sets", exercise.sets), ("repetitions", exercise.repetitions)];
+            for (field, count) in counts {
+                if count == Some(0) {
+                    issues.push(ValidationIssue::new(join(&path, field), "must be positive"));
+                }
+            }
+            if let Some(resistance) = &exercise.resistance {
+                check_quantity(&mut issues, &join(&path, "resistance"), resistance);
+            }
+            if exercise.duration_seconds == Some(0) {
+                issues.push(ValidationIssue::new(join(&path, "durationSeconds"), "must be positive"));
+            }
+        }
+        for (path, pain) in [("painBefore", self.pain_before), ("painAfter", self.pain_after)] {
+            if pain.is_some_and(|pain| pain > 10) {
+                issues.push(ValidationIssue::new(path, "must be between 0 and 10"));
+            }
+        }
+        for (i, score) in self.functional_scores.iter().flatten().enumerate() {
+            let path = index("functionalScores", i);
+            check_concept(&mut issues, &join(&path, "instrument"), &score.instrument);
+            if !score.score.is_finite() {
+                issues.push(ValidationIssue::new(join(&path, "score"), "must be a finite number"));
+            }
+            match score.max_score {
+                Some(max) if !(max.is_finite() && max > 0.0) => {
+                    issues.push(ValidationIssue::new(join(&path, "maxScore"), "must be positive"));
+                }
+                Some(max) if score.score > max => {
+                    issues.push(ValidationIssue::new(join(&path, "score"), "must not exceed maxScore"));
+                }
+                _ => {}
+            }
+        }
+        check_notes(&mut issues, "notes", &self.notes);
+        check_schema_version(&mut issues, &self.schema_version);
+        check_security_labels(&mut issues, &self.security_labels);
+        check_extensions(&mut issues, &self.extensions);
+        issues
+    }
+}
+
+impl Validate for WoundAssessment {
+    fn validate(&self) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+        require_text(&mut issues, "id".into(), &self.id);
+        require_text(&mut issues, "patientId".into(), &self.patient_id);
+        require_text(&mut issues, "woundId".into(), &self.wound_id);
+        if let Some(wound_type) = &self.wound_type {
+            check_concept(&mut issues, "woundType", wound_type);
+        }
+        check_coding(&mut issues, "location", &self.location);
+        if let Some(dimensions) = &self.dimensions {
+            let measured = [
+                ("length", Some(&dimensions.length)),
+                ("width", Some(&dimensions.width)),
+                ("depth", dimensions.depth.as_ref()),
+            ];
+            for (field, quantity) in measured {
+                let Some(quantity) = quantity else { continue };
+                let path = join("dimensions", field);
+                check_quantity(&mut issues, &path, quantity);
+                if !ucum::commensurable(&quantity.unit, "cm") {
+                    issues.push(ValidationIssue::new(join(&path, "unit"), "must be a length"));
+                }
+            }
+        }
+        for (i, proportion) in self.tissue.iter().enumerate() {
+            if !(0.0..=100.0).contains(&proportion.percent) {
+                issues.push(ValidationIssue::new(join(&index("tissue", i), "percent"), "must be between 0 and 100"));
+            }
+        }
+        if self.tissue.iter().map(|proportion| proportion.percent).sum::<f64>() > 100.0 + 1e-9 {
+            issues.push(ValidationIssue::new("tissue", "percentages must not add up to more than 100"));
+        }
+        for (i, photograph) in self.photographs.iter().flatten().enumerate() {
+            AttachmentLimits::default().check_into(&mut issues, &index("photographs", i), photograph);
+        }
+        if let Some(plan) = &self.dressing_plan {
+            for (i, dressing) in plan.dressings.iter().enumerate() {
+                check_concept(&mut issues, &index("dressingPlan.dressings", i), dressing);
+            }
+            if plan.change_every_days == Some(0) {
+                issues.push(ValidationIssue::new("dressingPlan.changeEveryDays", "must be positive"));
+            }
+        }
+        if let Some(assessor) = &self.assessor {
+            check_reference(&mut issues, "assessor", assessor);
+        }
+        check_notes(&mut issues, "notes", &self.notes);
+        check_schema_version(&mut issues, &self.schema_version);
+        check_security_labels(&mut issues, &self.security_labels);
+        check_extensions(&mut issues, &self.extensions);
+        issues
+    }
+}
+
+impl Validate for InfusionRecord {
+    fn validate(&self) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+        require_text(&mut issues, "id".into(), &self.id);
+        require_text(&mut issues, "patientId".into(), &self.patient_id);
+        check_coding(&mut issues, "medication", &self.medication);
+        if let Some(record) = &self.medication_record {
+            check_reference(&mut issues, "medicationRecord", record);
+        }
+        require_text(&mut issues, "route.system".into(), &self.route.system);
+        require_text(&mut issues, "route.code".into(), &self.route.code);
+        if let Some(concentration) = &self.concentration {
+            check_quantity(&mut issues, "concentration", concentration);
+        }
+        let start = self.start.to_utc();
+        let end = self.end.as_ref().map(|end| end.to_utc());
+        if end.is_some_and(|end| end < start) {
+            issues.push(ValidationIssue::new("end", "must not be before start"));
+        }
+        let during = |at| at >= start && end.is_none_or(|end| at <= end);
+        check_rate(&mut issues, "rate", &self.rate);
+        if let Some(volume) = &self.total_volume {
+            check_quantity(&mut issues, "totalVolume", volume);
+            if !ucum::commensurable(&volume.unit, "mL") {
+                issues.push(ValidationIssue::new("totalVolume.unit", "must be a volume"));
+            }
+        }
+        if let Some(site) = self.line.as_ref().and_then(|line| line.site.as_ref()) {
+            check_coding(&mut issues, "line.site", site);
+        }
+        for (i, titration) in self.titrations.iter().enumerate() {
+            let path = index("titrations", i);
+            if !during(titration.at.to_utc()) {
+                issues.push(ValidationIssue::new(join(&path, "at"), "must be within the infusion"));
+            }
+            check_rate(&mut issues, &join(&path, "rate"), &titration.rate);
+            if let Some(reason) = &titration.reason {
+                check_concept(&mut issues, &join(&path, "reason"), reason);
+            }
+        }
+        for (i, interruption) in self.interruptions.iter().enumerate() {
+            let path = index("interruptions", i);
+            if !during(interruption.start.to_utc()) {
+                issues.push(ValidationIssue::new(join(&path, "start"), "must be within the infusion"));
+            }
+            if interruption.end.as_ref().is_some_and(|resumed| resumed.to_utc() < interruption.start.to_utc()) {
+                issues.push(ValidationIssue::new(join(&path, "end"), "must not be before start"));
+            }
+            check_concept(&mut issues, &join(&path, "reason"), &interruption.reason);
+        }
+        if let Some(device) = &self.device {
+            check_reference(&mut issues, "device", device);
+        }
+        check_notes(&mut issues, "notes", &self.notes);
+        check_schema_version(&mut issues, &self.schema_version);
+        check_security_labels(&mut issues, &self.security_labels);
+        check_extensions(&mut issues, &self.extensions);
+        issues
+    }
+}
+
+impl Validate for PathologyReport {
+    fn validate(&self) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+        require_text(&mut issues, "id".into(), &self.id);
+        require_text(&mut issues, "patientId".into(), &self.patient_id);
+        require_items(&mut issues, "specimens".into(), &self.specimens);
+        for (i, specimen) in self.specimens.iter().enumerate() {
+            let path = index("specimens", i);
+            require_text(&mut issues, join(&path, "id"), &specimen.id);
+            if self.specimens[..i].iter().any(|earlier| earlier.id == specimen.id) {
+                issues.push(ValidationIssue::new(join(&path, "id"), "must be unique"));
+            }
+            if let Some(specimen_type) = &specimen.specimen_type {
+                check_coding(&mut issues, &join(&path, "type"), specimen_type);
+            }
+            if let Some(site) = &specimen.body_site {
+                check_coding(&mut issues, &join(&path, "bodySite"), site);
+            }
+            if let Some(procedure) = &specimen.procedure {
+                check_concept(&mut issues, &join(&path, "procedure"), procedure);
+            }
+        }
+        for (i, element) in self.synoptic.iter().enumerate() {
+            let path = index("synoptic", i);
+            check_coding(&mut issues, &join(&path, "code"), &element.code);
+            check_lab_value(&mut issues, join(&path, "value"), &element.value);
+            if element.specimen.as_deref().is_some_and(|id| self.specimen(id).is_none()) {
+                issues.push(ValidationIssue::new(join(&path, "specimen"), "must name a specimen of the report"));
+            }
+        }
+        for (i, diagnosis) in self.diagnoses.iter().enumerate() {
+            check_concept(&mut issues, &index("diagnoses", i), diagnosis);
+        }
+        if let Some(grade) = &self.grade {
+            check_concept(&mut issues, "grade", grade);
+        }
+        if let Some(tnm) = &self.tnm {
+            if self.tnm_staging().is_none() {
+                issues.push(ValidationIssue::new("tnm", format!("'{}' is not a TNM classification", tnm)));
+            }
+        }
+        if let Some(condition) = &self.condition {
+            check_reference(&mut issues, "condition", condition);
+        }
+        if let Some(pathologist) = &self.pathologist {
+            check_reference(&mut issues, "pathologist", pathologist);
+        }
+        check_notes(&mut issues, "notes", &self.notes);
+        check_schema_version(&mut issues, &self.schema_version);
+        check_security_labels(&mut issues, &self.security_labels);
+        check_extensions(&mut issues, &self.extensions);
+        issues
+    }
+}
+
+impl Validate for AudiogramReport {
+    fn validate(&self) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+        require_text(&mut issues, "id".into(), &self.id);
+        require_text(&mut issues, "patientId".into(), &self.patient_id);
+        require_items(&mut issues, "ears".into(), &self.ears);
+        for (i, ear) in self.ears.iter().enumerate() {
+            let path = index("ears", i);
+            if self.ears[..i].iter().any(|earlier| earlier.ear == ear.ear) {
+                issues.push(ValidationIssue::new(join(&path, "ear"), "must be unique"));
+            }
+            for (j, threshold) in ear.thresholds.iter().enumerate() {
+                let path = index(&join(&path, "thresholds"), j);
+                if !(125..=20000).contains(&threshold.frequency_hz) {
+                    issues.push(ValidationIssue::new(join(&path, "frequencyHz"), "must be between 125 and 20000"));
+                } else if ear.thresholds[..j].iter().any(|earlier| {
+                    earlier.frequency_hz == threshold.frequency_hz && earlier.conduction == threshold.conduction
+                }) {
+                    issues.push(ValidationIssue::new(join(&path, "frequencyHz"), "must be unique per conduction"));
+                }
+                if !(-10.0..=130.0).contains(&threshold.level_db) {
+                    issues.push(ValidationIssue::new(join(&path, "levelDb"), "must be between -10 and 130"));
+                }
+            }
+            if let Some(speech) = &ear.speech {
+                let path = join(&path, "speech");
+                if !(0.0..=100.0).contains(&speech.score_percent) {
+                    issues.push(ValidationIssue::new(join(&path, "scorePercent"), "must be between 0 and 100"));
+                }
+                for (field, level) in [
+                    ("presentationLevelDb", speech.presentation_level_db),
+                    ("receptionThresholdDb", speech.reception_threshold_db),
+                ] {
+                    if level.is_some_and(|level| !level.is_finite()) {
+                        issues.push(ValidationIssue::new(join(&path, field), "must be a finite number"));
+                    }
+                }
+            }
+        }
+        if let Some(tested_by) = &self.tested_by {
+            check_reference(&mut issues, "testedBy", tested_by);
+        }
+        check_notes(&mut issues, "notes", &self.notes);
+        check_schema_version(&mut issues, &self.schema_version);
+        check_security_labels(&mut issues, &self.security_labels);
+        check_extensions(&mut issues, &self.extensions);
+        issues
+    }
+}
+
+impl Validate for BirthRecord {
+    fn validate(&self) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+        require_text(&mut issues, "id".into(), &self.id);
+        require_text(&mut issues, "patientId".into(), &self.patient_id);
+        require_text(&mut issues, "motherId".into(), &self.mother_id);
+        if !self.mother_id.is_empty() && self.mother_id == self.patient_id {
+            issues.push(ValidationIssue::new("motherId", "must differ from patientId"));
+        }
+        if !(20..=45).contains(&self.gestational_age.weeks) {
+            issues.push(ValidationIssue::new("gestationalAge.weeks", "must be between 20 and 45"));
+        }
+        if self.gestational_age.days > 6 {
+            issues.push(ValidationIssue::new("gestationalAge.days", "must be between 0 and 6"));
+        }
+        if self.birth_order == Some(0) {
+            issues.push(ValidationIssue::new("birthOrder", "must be at least 1"));
+        }
+        let measured = [
+            ("birthWeight", &self.birth_weight, "g", "must be a mass"),
+            ("birthLength", &self.birth_length, "cm", "must be a length"),
+            ("headCircumference", &self.head_circumference, "cm", "must be a length"),
+        ];
+        for (path, quantity, unit, message) in measured {
+            if let Some(quantity) = quantity {
+                check_quantity(&mut issues, path, quantity);
+                if !ucum::commensurable(&quantity.unit, unit) {
+                    issues.push(ValidationIssue::new(join(path, "unit"), message));
+                }
+            }
+        }
+        for (i, apgar) in self.apgar.iter().enumerate() {
+            let path = index("apgar", i);
+            if apgar.minute == 0 {
+                issues.push(ValidationIssue::new(join(&path, "minute"), "must be at least 1"));
+            } else if self.apgar[..i].iter().any(|earlier| earlier.minute == apgar.minute) {
+                issues.push(ValidationIssue::new(join(&path, "minute"), "must be unique"));
+            }
+            if apgar.score > 10 {
+                issues.push(ValidationIssue::new(join(&path, "score"), "must be between 0 and 10"));
+            }
+            if let Some(components) = &apgar.components {
+                let signs = [
+                    ("appearance", components.appearance),
+                    ("pulse", components.pulse),
+                    ("grimace", components.grimace),
+                    ("activity", components.activity),
+                    ("respiration", components.respiration),
+                ];
+                let path = join(&path, "components");
+                for (sign, score) in signs {
+                    if score > 2 {
+                        issues.push(ValidationIssue::new(join(&path, sign), "must be between 0 and 2"));
+                    }
+                }
+                if components.total() != apgar.score {
+                    issues.push(ValidationIssue::new(path, "must add up to the score"));
+                }
+            }
+        }
+        for (i, complication) in self.complications.iter().enumerate() {
+            check_concept(&mut issues, &index("complications", i), complication);
+        }
+        let references = [
+            ("newbornScreening", &self.newborn_screening),
+            ("location", &self.location),
+            ("attendant", &self.attendant),
+        ];
+        for (path, reference) in references {
+            if let Some(reference) = reference {
+                check_reference(&mut issues, path, reference);
+            }
+        }
+        check_notes(&mut issues, "notes", &self.notes);
+        check_schema_version(&mut issues, &self.schema_version);
+        check_security_labels(&mut issues, &self.security_labels);
+        check_extensions(&mut issues, &self.extensions);
+        issues
+    }
+}
+
+impl Validate for ClearanceDocument {
+    fn validate(&self) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+        require_text(&mut issues, "id".into(), &self.id);
+        require_text(&mut issues, "patientId".into(), &self.patient_id);
+        check_period(&mut issues, "validPeriod", &Some(self.valid_period));
+        match self.outcome {
+            ClearanceOutcome::ClearedWithRestrictions if self.restrictions.is_empty() => {
+                issues.push(ValidationIssue::new("restrictions", "must not be empty when cleared with restrictions"));
+            }
+            ClearanceOutcome::Cleared if !self.restrictions.is_empty() => {
+                issues.push(ValidationIssue::new(
+                    "outcome",
+                    "must be cleared-with-restrictions when restrictions are listed",
+                ));
+            }
+            _ => {}
+        }
+        for (i, restriction) in self.restrictions.iter().enumerate() {
+            let path = index("restrictions", i);
+            require_text(&mut issues, join(&path, "description"), &restriction.description);
+            if let Some(code) = &restriction.code {
+                check_concept(&mut issues, &join(&path, "code"), code);
+            }
+        }
+        for (i, required) in self.required_evidence.iter().enumerate() {
+            let path = index("requiredEvidence", i);
+            check_concept(&mut issues, &join(&path, "code"), &required.code);
+            if let Some(evidence) = &required.evidence {
+                check_reference(&mut issues, &join(&path, "evidence"), evidence);
+            }
+        }
+        if let Some(requested_by) = &self.requested_by {
+            check_reference(&mut issues, "requestedBy", requested_by);
+        }
+        check_reference(&mut issues, "clinician", &self.clinician);
+        check_notes(&mut issues, "notes", &self.notes);
+        check_schema_version(&mut issues, &self.schema_version);
+        check_security_labels(&mut issues, &self.security_labels);
+        check_extensions(&mut issues, &self.extensions);
+        issues
+    }
+}
+
+impl Validate for TravelHealthRecord {
+    fn validate(&self) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+        require_text(&mut issues, "id".into(), &self.id);
+        require_text(&mut issues, "patientId".into(), &self.patient_id);
+        require_items(&mut issues, "destinations".into(), &self.destinations);
+        for (i, destination) in self.destinations.iter().enumerate() {
+            let path = index("destinations", i);
+            let country = destination.country.as_bytes();
+            if country.len() != 2 || !country.iter().all(u8::is_ascii_uppercase) {
+                issues.push(ValidationIssue::new(join(&path, "country"), "must be an ISO 3166-1 alpha-2 code"));
+            }
+            if destination.departure < destination.arrival {
+                issues.push(ValidationIssue::new(join(&path, "departure"), "must not be before arrival"));
+            }
+        }
+        for (i, vaccine) in self.vaccines.iter().enumerate() {
+            let path = index("vaccines", i);
+            check_coding(&mut issues, &join(&path, "vaccine"), &vaccine.vaccine);
+            if let Some(immunization) = &vaccine.immunization {
+                let path = join(&path, "immunization");
+                check_reference(&mut issues, &path, immunization);
+                if immunization.target().is_some_and(|(resource_type, _)| resource_type != "ImmunizationRecord") {
+                    issues.push(ValidationIssue::new(join(&path, "reference"), "must reference an ImmunizationRecord"));
+                }
+            }
+            if let Some(reason) = &vaccine.not_given_reason {
+                check_concept(&mut issues, &join(&path, "notGivenReason"), reason);
+            }
+        }
+        for (i, prophylaxis) in self.malaria_prophylaxis.iter().enumerate() {
+            let path = index("malariaProphylaxis", i);
+            check_reference(&mut issues, &path, prophylaxis);
+            if prophylaxis.target().is_some_and(|(resource_type, _)| resource_type != "MedicationRecord") {
+                issues.push(ValidationIssue::new(join(&path, "reference"), "must reference a MedicationRecord"));
+            }
+        }
+        for (i, advisory) in self.advisories.iter().enumerate() {
+            let path = index("advisories", i);
+            require_text(&mut issues, join(&path, "text"), &advisory.text);
+            if let Some(topic) = &advisory.topic {
+                check_concept(&mut issues, &join(&path, "topic"), topic);
+            }
+        }
+        if let Some(clinician) = &self.clinician {
+            check_reference(&mut issues, "clinician", clinician);
+        }
+        check_notes(&mut issues, "notes", &self.notes);
+        check_schema_version(&mut issues, &self.schema_version);
+        check_security_labels(&mut issues, &self.security_labels);
+        check_extensions(&mut issues, &self.extensions);
+        issues
+    }
+}
+
+impl Validate for Compendium {
+    fn validate(&self) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+        require_text(&mut issues, "id".into(), &self.id);
+        require_text(&mut issues, "name".into(), &self.name);
+        if let Some(publisher) = &self.publisher {
+            check_reference(&mut issues, "publisher", publisher);
+        }
+        require_items(&mut issues, "tests".into(), &self.tests);
+        for (i, test) in self.tests.iter().enumerate() {
+            let path = index("tests", i);
+            check_coding(&mut issues, &join(&path, "code"), &test.code);
+            if test.code.system != catalog::LOINC {
+                issues.push(ValidationIssue::new(join(&path, "code.system"), "must be a LOINC code"));
+            } else if self.tests[..i].iter().any(|earlier| earlier.code == test.code) {
+                issues.push(ValidationIssue::new(join(&path, "code"), "must be unique"));
+            }
+            require_text(&mut issues, join(&path, "name"), &test.name);
+            for (j, specimen) in test.specimens.iter().enumerate() {
+                let path = index(&join(&path, "specimens"), j);
+                check_coding(&mut issues, &join(&path, "type"), &specimen.specimen_type);
+                if let Some(container) = &specimen.container {
+                    check_concept(&mut issues, &join(&path, "container"), container);
+                }
+                if let Some(volume) = &specimen.minimum_volume {
+                    let path = join(&path, "minimumVolume");
+                    check_quantity(&mut issues, &path, volume);
+                    if !ucum::commensurable(&volume.unit, "mL") {
+                        issues.push(ValidationIssue::new(join(&path, "unit"), "must be a volume"));
+                    }
+                }
+            }
+            for (j, component) in test.components.iter().enumerate() {
+                check_coding(&mut issues, &index(&join(&path, "components"), j), component);
+            }
+            if let Some(turnaround) = &test.turnaround {
+                let path = join(&path, "turnaround");
+                check_quantity(&mut issues, &path, turnaround);
+                if !ucum::commensurable(&turnaround.unit, "h") {
+                    issues.push(ValidationIssue::new(join(&path, "unit"), "must be a time"));
+                }
+            }
+            if let Some(reference_lab) = &test.reference_lab {
+                check_reference(&mut issues, &join(&path, "referenceLab"), reference_lab);
+            }
+        }
+        check_schema_version(&mut issues, &self.schema_version);
+        check_security_labels(&mut issues, &self.security_labels);
+        check_extensions(&mut issues, &self.extensions);
+        issues
+    }
+}
+
+impl Validate for DispenseRecord {
+    fn validate(&self) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+        require_text(&mut issues, "id".into(), &self.id);
+        require_text(&mut issues, "patientId".into(), &self.patient_id);
+        check_reference(&mut issues, "prescription", &self.prescription);
+        if self.prescription.target().is_some_and(|(resource_type, _)| resource_type != "MedicationRecord") {
+            issues.push(ValidationIssue::new("prescription.reference", "must reference a MedicationRecord"));
+        }
+        if let Some(medication) = &self.medication {
+            check_coding(&mut issues, "medication", medication);
+        }
+        check_quantity(&mut issues, "quantity", &self.quantity);
+        if self.quantity.value <= 0.0 {
+            issues.push(ValidationIssue::new("quantity.value", "must be positive"));
+        }
+        if self.days_supply == 0 {
+            issues.push(ValidationIssue::new("daysSupply", "must be at least 1"));
+        } else if self.days_supply > MAX_DAYS_SUPPLY {
+            issues.push(ValidationIssue::new("daysSupply", format!("must be at most {}", MAX_DAYS_SUPPLY)));
+        }
+        if let Some(pharmacy) = &self.pharmacy {
+            check_reference(&mut issues, "pharmacy", pharmacy);
+        }
+        if self.fill_number == 0 {
+            issues.push(ValidationIssue::new("fillNumber", "must be at least 1"));
+        }
+        if self.refills_remaining > MAX_REFILLS {
+            issues.push(ValidationIssue::new("refillsRemaining", format!("must be at most {}", MAX_REFILLS)));
+        }
+        check_notes(&mut issues, "notes", &self.notes);
+        check_schema_version(&mut issues, &self.schema_version);
+        check_security_labels(&mut issues, &self.security_labels);
+        check_extensions(&mut issues, &self.extensions);
+        issues
+    }
+}
+
+impl Validate for ImmunizationRecord {
+    fn validate(&self) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+        require_text(&mut issues, "id".into(), &self.id);
+        require_text(&mut issues, "patientId".into(), &self.patient_id);
+        check_coding(&mut issues, "vaccine", &self.vaccine);
+        if self.dose_number == Some(0) {
+            issues.push(ValidationIssue::new("doseNumber", "must be at least 1"));
+        }
+        if let Some(lot_number) = &self.lot_number {
+            require_text(&mut issues, "lotNumber".into(), lot_number);
+        }
+        if let Some(manufacturer) = &self.manufacturer {
+            check_coding(&mut issues, "manufacturer", manufacturer);
+        }
+        if self.is_expired_dose() {
+            issues.push(ValidationIssue::new("administeredOn", "must not be after expirationDate"));
+        }
+        if let Some(dose_quantity) = &self.dose_quantity {
+            check_quantity(&mut issues, "doseQuantity", dose_quantity);
+            if dose_quantity.value <= 0.0 {
+                issues.push(ValidationIssue::new("doseQuantity.value", "must be positive"));
+            }
+        }
+        if let Some(route) = &self.route {
+            require_text(&mut issues, "route.system".into(), &route.system);
+            require_text(&mut issues, "route.code".into(), &route.code);
+        }
+        if let Some(site) = &self.site {
+            check_coding(&mut issues, "site", site);
+        }
+        if let Some(performer) = &self.performer {
+            check_reference(&mut issues, "performer", performer);
+        }
+        if let Some(location) = &self.location {
+            check_reference(&mut issues, "location", location);
+        }
+        check_notes(&mut issues, "notes", &self.notes);
+        check_schema_version(&mut issues, &self.schema_version);
+        check_security_labels(&mut issues, &self.security_labels);
+        check_extensions(&mut issues, &self.extensions);
+        issues
+    }
+}
+
+impl Validate for RelatedPerson {
+    fn validate(&self) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+        require_text(&mut issues, "id".into(), &self.id);
+        require_text(&mut issues, "patientId".into(), &self.patient_id);
+        require_items(&mut issues, "relationship".into(), &self.relationship);
+        if let Some(person_id) = &self.person_id {
+            require_text(&mut issues, "personId".into(), person_id);
+            if *person_id == self.patient_id {
+                issues.push(ValidationIssue::new("personId", "must not be the patient"));
+            }
+        }
+        if let Some(name) = &self.name {
+            check_name(&mut issues, "name", name);
+        }
+        for (i, contact) in self.telecom.iter().flatten().enumerate() {
+            require_text(&mut issues, join(&index("telecom", i), "value"), &contact.value);
+        }
+        check_period(&mut issues, "period", &self.period);
+        for (i, scope) in self.permissions.iter().enumerate() {
+            if scope.context() != Context::Patient {
+                issues.push(ValidationIssue::new(index("permissions", i), "must be a patient scope"));
+            }
+        }
+        check_notes(&mut issues, "notes", &self.notes);
+        check_schema_version(&mut issues, &self.schema_version);
+        check_security_labels(&mut issues, &self.security_labels);
+        check_extensions(&mut issues, &self.extensions);
+        issues
+    }
+}
+
+fn check_rate(issues: &mut Vec<ValidationIssue>, path: &str, rate: &Quantity) {
+    check_quantity(issues, path, rate);
+    if rate.value < 0.0 {
+        issues.push(ValidationIssue::new(join(path, "value"), "must not be negative"));
+    }
+    if !infusion::is_rate(&rate.unit) {
+        issues.push(ValidationIssue::new(join(path, "unit"), "must be an amount per unit of time"));
+    }
+}
+
+fn check_period(issues: &mut Vec<ValidationIssue>, path: &str, period: &Option<Period>) {
+    if let Some(Period { start: Some(start), end: Some(end) }) = period {
+        if end < start {
+            issues.push(ValidationIssue::new(join(path, "end"), "must not be before start"));
+        }
+    }
+}
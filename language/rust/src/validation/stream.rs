@@ -0,0 +1,334 @@
+//! Incremental validation of NDJSON streams, JSON arrays and bundles.
+
+use std::io::{BufReader, Read};
+use std::fmt;
+
+use serde::de::{DeserializeSeed, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde_json::{Map, Value};
+
+use super::{Validate, ValidationIssue};
+use crate::family_health::FamilyHealthTree;
+use crate::health::Person;
+use crate::imaging_report::ImagingReport;
+use crate::lab_report::LabReport;
+use crate::medication::MedicationRecord;
+use crate::questionnaire::{Questionnaire, QuestionnaireResponse};
+use crate::referral::Referral;
+use crate::task::Task;
+use crate::communication::CommunicationRecord;
+use crate::location::Location;
+use crate::condition::Condition;
+use crate::allergy::AllergyIntolerance;
+use crate::flag::Flag;
+use crate::cgm::GlucoseSeries;
+use crate::ecg::EcgRecord;
+use crate::consent::ConsentRecord;
+use crate::nephrology::DialysisSession;
+use crate::rehab::RehabSession;
+use crate::wound::WoundAssessment;
+use crate::infusion::InfusionRecord;
+use crate::pathology::PathologyReport;
+use crate::audiology::AudiogramReport;
+use crate::birth::BirthRecord;
+use crate::clearance::ClearanceDocument;
+use crate::travel::TravelHealthRecord;
+use crate::compendium::Compendium;
+use crate::dispense::DispenseRecord;
+use crate::immunization::ImmunizationRecord;
+use crate::relationships::RelatedPerson;
+use crate::resource::Resource;
+
+/// Validation issue of one record in a stream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamIssue {
+    /// Zero-based position of the record in the stream
+    pub record: usize,
+    /// Detected resource type, if any
+    pub resource_type: Option<&'static str>,
+    /// Identifier of the record, if it could be read
+    pub id: Option<String>,
+    /// The problem found
+    pub issue: ValidationIssue,
+}
+
+/// Totals of a [`stream_validate`] run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StreamSummary {
+    /// Records read
+    pub records: usize,
+    /// Records with at least one issue
+    pub invalid: usize,
+    /// Issues reported
+    pub issues: usize,
+}
+
+/// Validates every resource read from `reader`, reporting issues as they are found.
+///
+/// The input may be NDJSON (or any sequence of JSON values), a JSON array of
+/// resources, or a bundle object whose `entry` array holds `{"resource": ...}`
+/// items. Only one record is held in memory at a time. The resource type is
+/// taken from `resourceType` when present, else inferred from the fields that
+/// only one resource has (`probandId`, `modality`, `medication`, `results`,
+/// `birthDate`, `questionnaire`, `item`, or their snake_case aliases). Records of unknown type are
+/// reported as issues with an empty path, records of the wrong shape with the
+/// path of the offending field; malformed JSON stops the stream with an error.
+///
+/// ```
+/// use wellally::validation::stream_validate;
+///
+/// let ndjson = r#"{"id":"lab-1","patientId":"","issuedAt":"2024-11-02T09:30:00Z","results":[]}"#;
+/// let mut issues = Vec::new();
+/// let summary = stream_validate(ndjson.as_bytes(), |issue| issues.push(issue))?;
+/// assert_eq!(summary.invalid, 1);
+/// assert_eq!(issues[0].issue.path, "patientId");
+/// # Ok::<(), serde_json::Error>(())
+/// ```
+pub fn stream_validate<R: Read>(reader: R, on_issue: impl FnMut(StreamIssue)) -> serde_json::Result<StreamSummary> {
+    let mut deserializer = serde_json::Deserializer::from_reader(BufReader::new(reader));
+    let mut stream = Stream { summary: StreamSummary::default(), on_issue };
+    while deserializer.end().is_err() {
+        TopLevel(&mut stream).deserialize(&mut deserializer)?;
+    }
+    Ok(stream.summary)
+}
+
+struct Stream<F> {
+    summary: StreamSummary,
+    on_issue: F,
+}
+
+impl<F: FnMut(StreamIssue)> Stream<F> {
+    fn record(&mut self, record: Map<String, Value>) {
+        let index = self.summary.records;
+        self.summary.records += 1;
+        let resource_type = detect(&record);
+        let (id, issues) = match resource_type {
+            Some(Person::RESOURCE_TYPE) => check::<Person>(record),
+            Some(LabReport::RESOURCE_TYPE) => check::<LabReport>(record),
+            Some(ImagingReport::RESOURCE_TYPE) => check::<ImagingReport>(record),
+            Some(MedicationRecord::RESOURCE_TYPE) => check::<MedicationRecord>(record),
+            Some(FamilyHealthTree::RESOURCE_TYPE) => check::<FamilyHealthTree>(record),
+            Some(Questionnaire::RESOURCE_TYPE) => check::<Questionnaire>(record),
+            Some(QuestionnaireResponse::RESOURCE_TYPE) => check::<QuestionnaireResponse>(record),
+            Some(Referral::RESOURCE_TYPE) => check::<Referral>(record),
+            Some(Task::RESOURCE_TYPE) => check::<Task>(record),
+            Some(CommunicationRecord::RESOURCE_TYPE) => check::<CommunicationRecord>(record),
+            Some(Location::RESOURCE_TYPE) => check::<Location>(record),
+            Some(Condition::RESOURCE_TYPE) => check::<Condition>(record),
+            Some(AllergyIntolerance::RESOURCE_TYPE) => check::<AllergyIntolerance>(record),
+            Some(Flag::RESOURCE_TYPE) => check::<Flag>(record),
+            Some(GlucoseSeries::RESOURCE_TYPE) => check::<GlucoseSeries>(record),
+            Some(EcgRecord::RESOURCE_TYPE) => check::<EcgRecord>(record),
+            Some(ConsentRecord::RESOURCE_TYPE) => check::<ConsentRecord>(record),
+            Some(DialysisSession::RESOURCE_TYPE) => check::<DialysisSession>(record),
+            Some(RehabSession::RESOURCE_TYPE) => check::<RehabSession>(record),
+            Some(WoundAssessment::RESOURCE_TYPE) => check::<WoundAssessment>(record),
+            Some(InfusionRecord::RESOURCE_TYPE) => check::<InfusionRecord>(record),
+            Some(PathologyReport::RESOURCE_TYPE) => check::<PathologyReport>(record),
+            Some(AudiogramReport::RESOURCE_TYPE) => check::<AudiogramReport>(record),
+            Some(BirthRecord::RESOURCE_TYPE) => check::<BirthRecord>(record),
+            Some(ClearanceDocument::RESOURCE_TYPE) => check::<ClearanceDocument>(record),
+            Some(TravelHealthRecord::RESOURCE_TYPE) => check::<TravelHealthRecord>(record),
+            Some(Compendium::RESOURCE_TYPE) => check::<Compendium>(record),
+            Some(DispenseRecord::RESOURCE_TYPE) => check::<DispenseRecord>(record),
+            Some(ImmunizationRecord::RESOURCE_TYPE) => check::<ImmunizationRecord>(record),
+            Some(RelatedPerson::RESOURCE_TYPE) => check::<RelatedPerson>(record),
+            _ => (None, vec![ValidationIssue::new("", "cannot determine resource type")]),
+        };
+        if !issues.is_empty() {
+            self.summary.invalid += 1;
+            self.summary.issues += issues.len();
+        }
+        for issue in issues {
+            (self.on_issue)(StreamIssue { record: index, resource_type, id: id.clone(), issue });
+        }
+    }
+}
+
+/// Resource type of a record: its `resourceType`, or the type inferred from its fields.
+pub(crate) fn detect(record: &Map<String, Value>) -> Option<&'static str> {
+    const TYPES: [&str; 30] = [
+        Person::RESOURCE_TYPE,
+        LabReport::RESOURCE_TYPE,
+        ImagingReport::RESOURCE_TYPE,
+        MedicationRecord::RESOURCE_TYPE,
+        FamilyHealthTree::RESOURCE_TYPE,
+        Questionnaire::RESOURCE_TYPE,
+        QuestionnaireResponse::RESOURCE_TYPE,
+        Referral::RESOURCE_TYPE,
+        Task::RESOURCE_TYPE,
+        CommunicationRecord::RESOURCE_TYPE,
+        Location::RESOURCE_TYPE,
+        Condition::RESOURCE_TYPE,
+        AllergyIntolerance::RESOURCE_TYPE,
+        Flag::RESOURCE_TYPE,
+        GlucoseSeries::RESOURCE_TYPE,
+        EcgRecord::RESOURCE_TYPE,
+        ConsentRecord::RESOURCE_TYPE,
+        DialysisSession::RESOURCE_TYPE,
+        RehabSession::RESOURCE_TYPE,
+        WoundAssessment::RESOURCE_TYPE,
+        InfusionRecord::RESOURCE_TYPE,
+        PathologyReport::RESOURCE_TYPE,
+        AudiogramReport::RESOURCE_TYPE,
+        BirthRecord::RESOURCE_TYPE,
+        ClearanceDocument::RESOURCE_TYPE,
+        TravelHealthRecord::RESOURCE_TYPE,
+        Compendium::RESOURCE_TYPE,
+        DispenseRecord::RESOURCE_TYPE,
+        ImmunizationRecord::RESOURCE_TYPE,
+        RelatedPerson::RESOURCE_TYPE,
+    ];
+    let declared = record.get("resourceType").or_else(|| record.get("resource_type"));
+    if let Some(name) = declared.and_then(Value::as_str) {
+        return TYPES.into_iter().find(|t| *t == name);
+    }
+    [
+        ("motherId", BirthRecord::RESOURCE_TYPE),
+        ("mother_id", BirthRecord::RESOURCE_TYPE),
+        ("validPeriod", ClearanceDocument::RESOURCE_TYPE),
+        ("valid_period", ClearanceDocument::RESOURCE_TYPE),
+        ("destinations", TravelHealthRecord::RESOURCE_TYPE),
+        ("tests", Compendium::RESOURCE_TYPE),
+        ("daysSupply", DispenseRecord::RESOURCE_TYPE),
+        ("days_supply", DispenseRecord::RESOURCE_TYPE),
+        ("administeredOn", ImmunizationRecord::RESOURCE_TYPE),
+        ("administered_on", ImmunizationRecord::RESOURCE_TYPE),
+        ("relationship", RelatedPerson::RESOURCE_TYPE),
+        ("probandId", FamilyHealthTree::RESOURCE_TYPE),
+        ("proband_id", FamilyHealthTree::RESOURCE_TYPE),
+        ("woundId", WoundAssessment::RESOURCE_TYPE),
+        ("wound_id", WoundAssessment::RESOURCE_TYPE),
+        // Rehab sessions have a duration too.
+        ("therapyType", RehabSession::RESOURCE_TYPE),
+        ("therapy_type", RehabSession::RESOURCE_TYPE),
+        // Dialysis sessions have a modality too.
+        ("durationMinutes", DialysisSession::RESOURCE_TYPE),
+        ("duration_minutes", DialysisSession::RESOURCE_TYPE),
+        ("modality", ImagingReport::RESOURCE_TYPE),
+        // Infusions name a medication too.
+        ("rate", InfusionRecord::RESOURCE_TYPE),
+        ("medication", MedicationRecord::RESOURCE_TYPE),
+        ("specimens", PathologyReport::RESOURCE_TYPE),
+        ("ears", AudiogramReport::RESOURCE_TYPE),
+        ("results", LabReport::RESOURCE_TYPE),
+        ("birthDate", Person::RESOURCE_TYPE),
+        ("birth_date", Person::RESOURCE_TYPE),
+        ("questionnaire", QuestionnaireResponse::RESOURCE_TYPE),
+        ("item", Questionnaire::RESOURCE_TYPE),
+        ("requester", Referral::RESOURCE_TYPE),
+        ("description", Task::RESOURCE_TYPE),
+        ("payload", CommunicationRecord::RESOURCE_TYPE),
+        ("position", Location::RESOURCE_TYPE),
+        // Allergies may carry a clinicalStatus too.
+        ("criticality", AllergyIntolerance::RESOURCE_TYPE),
+        ("reactions", AllergyIntolerance::RESOURCE_TYPE),
+        ("clinicalStatus", Condition::RESOURCE_TYPE),
+        ("clinical_status", Condition::RESOURCE_TYPE),
+        ("focus", Flag::RESOURCE_TYPE),
+        ("intervalSeconds", GlucoseSeries::RESOURCE_TYPE),
+        ("interval_seconds", GlucoseSeries::RESOURCE_TYPE),
+        ("samplingRate", EcgRecord::RESOURCE_TYPE),
+        ("sampling_rate", EcgRecord::RESOURCE_TYPE),
+        ("decision", ConsentRecord::RESOURCE_TYPE),
+    ]
+    .into_iter()
+    .find(|(field, _)| record.contains_key(*field))
+    .map(|(_, resource_type)| match resource_type {
+        // Only allergies have a type or a list of categories.
+        Condition::RESOURCE_TYPE
+            if record.contains_key("type") || record.get("category").is_some_and(Value::is_array) =>
+        {
+            AllergyIntolerance::RESOURCE_TYPE
+        }
+        resource_type => resource_type,
+    })
+}
+
+fn check<R: Resource + Validate>(record: Map<String, Value>) -> (Option<String>, Vec<ValidationIssue>) {
+    let id = record.get("id").or_else(|| record.get("probandId")).and_then(Value::as_str).map(String::from);
+    match R::from_json_value(&Value::Object(record)) {
+        Ok(resource) => (Some(resource.id().to_string()), resource.validate()),
+        Err(err) => (id, vec![ValidationIssue::new(err.path(), err.message())]),
+    }
+}
+
+/// A top-level value: a resource, an array of resources or a bundle.
+struct TopLevel<'s, F>(&'s mut Stream<F>);
+
+impl<'de, F: FnMut(StreamIssue)> DeserializeSeed<'de> for TopLevel<'_, F> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de, F: FnMut(StreamIssue)> Visitor<'de> for TopLevel<'_, F> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a resource, an array of resources or a bundle")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        while let Some(value) = seq.next_element::<Value>()? {
+            self.0.record(into_record(value));
+        }
+        Ok(())
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        let mut record = Map::new();
+        let mut bundle = false;
+        while let Some(key) = map.next_key::<String>()? {
+            if key == "entry" {
+                map.next_value_seed(Entries(&mut *self.0))?;
+                bundle = true;
+            } else {
+                let value: Value = map.next_value()?;
+                bundle |= key == "resourceType" && value == "Bundle";
+                record.insert(key, value);
+            }
+        }
+        if !bundle {
+            self.0.record(record);
+        }
+        Ok(())
+    }
+}
+
+/// The `entry` array of a bundle, streamed one entry at a time.
+struct Entries<'s, F>(&'s mut Stream<F>);
+
+impl<'de, F: FnMut(StreamIssue)> DeserializeSeed<'de> for Entries<'_, F> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, F: FnMut(StreamIssue)> Visitor<'de> for Entries<'_, F> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("an array of bundle entries")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        while let Some(mut entry) = seq.next_element::<Value>()? {
+            let resource = entry.get_mut("resource").map(Value::take).unwrap_or(entry);
+            self.0.record(into_record(resource));
+        }
+        Ok(())
+    }
+}
+
+fn into_record(value: Value) -> Map<String, Value> {
+    match value {
+        Value::Object(record) => record,
+        // Leaves detection to fail, reporting the record as unknown.
+        _ => Map::new(),
+    }
+}
@@ -0,0 +1,289 @@
+//! Vital signs and early warning scores.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//!
+//! [`Vitals`] gathers one set of vital sign measurements, either directly or
+//! from LOINC-coded results; [`scoring`] turns it into a NEWS2 early warning
+//! score for remote-monitoring and ward apps. A [`BloodPressure`] keeps the
+//! systolic and diastolic values of one reading together with how it was
+//! taken, classifies it and exports it as the two LOINC component results.
+//!
+//! ```
+//! use wellally::vitals::{AccAhaCategory, BloodPressure, Jnc7Category};
+//!
+//! let reading = BloodPressure::mm_hg(134.0, 82.0);
+//! assert_eq!(reading.acc_aha_category(), Some(AccAhaCategory::Stage1));
+//! assert_eq!(reading.jnc7_category(), Some(Jnc7Category::Prehypertension));
+//!
+//! let results = reading.to_results();
+//! assert_eq!(results[1].code.coding[0].code, "8462-4");
+//! assert_eq!(BloodPressure::from_results(&results), Some(reading));
+//! ```
+
+use crate::catalog::{concept, LOINC};
+use crate::common::{CodeableConcept, DataSource, Effective, HasDataSource, Quantity};
+use crate::lab_report::{LabResult, LabValue};
+
+pub mod scoring;
+
+/// LOINC respiratory rate
+pub const RESPIRATORY_RATE: &str = "9279-1";
+/// LOINC oxygen saturation by pulse oximetry
+pub const OXYGEN_SATURATION: &str = "59408-5";
+/// LOINC oxygen saturation in arterial blood by pulse oximetry
+pub const OXYGEN_SATURATION_ARTERIAL: &str = "2708-6";
+/// LOINC inhaled oxygen flow rate
+pub const INHALED_OXYGEN_FLOW: &str = "3151-8";
+/// LOINC systolic blood pressure
+pub const SYSTOLIC_BLOOD_PRESSURE: &str = "8480-6";
+/// LOINC diastolic blood pressure
+pub const DIASTOLIC_BLOOD_PRESSURE: &str = "8462-4";
+/// LOINC blood pressure panel with the systolic and diastolic components
+pub const BLOOD_PRESSURE_PANEL: &str = "85354-9";
+/// LOINC heart rate
+pub const HEART_RATE: &str = "8867-4";
+/// LOINC body temperature
+pub const BODY_TEMPERATURE: &str = "8310-5";
+
+/// Level of consciousness on the ACVPU scale.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Consciousness {
+    /// Alert
+    Alert,
+    /// New confusion
+    Confusion,
+    /// Responds to voice
+    Voice,
+    /// Responds to pain
+    Pain,
+    /// Unresponsive
+    Unresponsive,
+}
+
+/// One set of vital sign measurements; unmeasured signs are `None`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Vitals {
+    /// Respiratory rate in breaths per minute
+    pub respiratory_rate: Option<f64>,
+    /// Peripheral oxygen saturation in percent
+    pub oxygen_saturation: Option<f64>,
+    /// Whether the patient is on supplemental oxygen
+    pub supplemental_oxygen: Option<bool>,
+    /// Systolic blood pressure in mmHg
+    pub systolic_blood_pressure: Option<f64>,
+    /// Pulse rate in beats per minute
+    pub pulse: Option<f64>,
+    /// Level of consciousness
+    pub consciousness: Option<Consciousness>,
+    /// Body temperature in degrees Celsius
+    pub temperature: Option<f64>,
+}
+
+/// First quantity result coded with one of the LOINC `codes`.
+fn quantity<'r>(results: &'r [LabResult], codes: &[&str]) -> Option<&'r Quantity> {
+    results.iter().find_map(|result| {
+        let coded = result.code.coding.iter().any(|c| c.system == LOINC && codes.contains(&c.code.as_str()));
+        match &result.value {
+            LabValue::Quantity(quantity) if coded => Some(quantity),
+            _ => None,
+        }
+    })
+}
+
+impl Vitals {
+    /// Vitals from LOINC-coded quantity results (the first result per sign
+    /// wins). Supplemental oxygen is inferred from an inhaled oxygen flow
+    /// result; consciousness is not coded in lab results and stays `None`.
+    pub fn from_results(results: &[LabResult]) -> Self {
+        let value = |codes: &[&str]| quantity(results, codes);
+        Self {
+            respiratory_rate: value(&[RESPIRATORY_RATE]).map(|q| q.value),
+            oxygen_saturation: value(&[OXYGEN_SATURATION, OXYGEN_SATURATION_ARTERIAL]).map(|q| q.value),
+            supplemental_oxygen: value(&[INHALED_OXYGEN_FLOW]).map(|q| q.value > 0.0),
+            systolic_blood_pressure: value(&[SYSTOLIC_BLOOD_PRESSURE]).map(|q| q.value),
+            pulse: value(&[HEART_RATE]).map(|q| q.value),
+            consciousness: None,
+            temperature: value(&[BODY_TEMPERATURE]).map(|q| match q.unit.as_str() {
+                "[degF]" => (q.value - 32.0) * 5.0 / 9.0,
+                _ => q.value,
+            }),
+        }
+    }
+}
+
+/// Body position during a blood pressure reading.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BodyPosition {
+    Sitting,
+    Standing,
+    /// Lying on the back
+    Supine,
+}
+
+/// Where the cuff was placed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CuffSite {
+    LeftUpperArm,
+    RightUpperArm,
+    LeftWrist,
+    RightWrist,
+    LeftThigh,
+    RightThigh,
+}
+
+/// Blood pressure category of the 2017 ACC/AHA guideline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum AccAhaCategory {
+    /// Below 120/80
+    Normal,
+    /// Systolic 120–129 and diastolic below 80
+    Elevated,
+    /// Systolic 130–139 or diastolic 80–89
+    Stage1,
+    /// Systolic 140 or more, or diastolic 90 or more
+    Stage2,
+    /// Systolic above 180 and/or diastolic above 120
+    HypertensiveCrisis,
+}
+
+/// Blood pressure category of JNC 7.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Jnc7Category {
+    /// Below 120/80
+    Normal,
+    /// Systolic 120–139 or diastolic 80–89
+    Prehypertension,
+    /// Systolic 140–159 or diastolic 90–99
+    Stage1,
+    /// Systolic 160 or more, or diastolic 100 or more
+    Stage2,
+}
+
+/// One blood pressure reading: systolic and diastolic values are only
+/// meaningful together.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BloodPressure {
+    /// Systolic pressure, normally in mm[Hg]
+    pub systolic: Quantity,
+    /// Diastolic pressure, normally in mm[Hg]
+    pub diastolic: Quantity,
+    /// Body position during the reading
+    pub position: Option<BodyPosition>,
+    /// Where the cuff was placed
+    pub cuff_site: Option<CuffSite>,
+    /// How the pressure was measured (e.g., SNOMED CT auscultation)
+    pub method: Option<CodeableConcept>,
+    /// When the reading was taken, or the period an averaged reading covers
+    pub effective: Option<Effective>,
+    /// Device, app and capture method of the reading
+    pub source: Option<DataSource>,
+}
+
+impl BloodPressure {
+    /// Reading of `systolic`/`diastolic` mm[Hg].
+    pub fn mm_hg(systolic: f64, diastolic: f64) -> Self {
+        BloodPressure {
+            systolic: Quantity::new(systolic, "mm[Hg]"),
+            diastolic: Quantity::new(diastolic, "mm[Hg]"),
+            position: None,
+            cuff_site: None,
+            method: None,
+            effective: None,
+            source: None,
+        }
+    }
+
+    /// Reading from the first LOINC-coded systolic and diastolic quantity
+    /// results, with the systolic result's method and effective time; `None`
+    /// unless both are present.
+    pub fn from_results(results: &[LabResult]) -> Option<Self> {
+        let systolic = quantity(results, &[SYSTOLIC_BLOOD_PRESSURE])?;
+        let diastolic = quantity(results, &[DIASTOLIC_BLOOD_PRESSURE])?;
+        let systolic_result = results
+            .iter()
+            .find(|result| result.code.coding.iter().any(|c| c.system == LOINC && c.code == SYSTOLIC_BLOOD_PRESSURE));
+        Some(BloodPressure {
+            systolic: systolic.clone(),
+            diastolic: diastolic.clone(),
+            position: None,
+            cuff_site: None,
+            method: systolic_result.and_then(|result| result.method.clone()),
+            effective: systolic_result.and_then(|result| result.effective),
+            source: None,
+        })
+    }
+
+    /// Systolic and diastolic values in mm[Hg]; `None` when a unit does not
+    /// convert to mm[Hg] or a value is a limit.
+    pub fn values_mm_hg(&self) -> Option<(f64, f64)> {
+        let value = |pressure: &Quantity| match pressure.comparator {
+            Some(_) => None,
+            None => pressure.convert_to("mm[Hg]").ok().map(|q| q.value),
+        };
+        Some((value(&self.systolic)?, value(&self.diastolic)?))
+    }
+
+    /// Category of the 2017 ACC/AHA guideline; the higher of the systolic
+    /// and diastolic categories.
+    pub fn acc_aha_category(&self) -> Option<AccAhaCategory> {
+        let (systolic, diastolic) = self.values_mm_hg()?;
+        let by_systolic = match systolic {
+            s if s > 180.0 => AccAhaCategory::HypertensiveCrisis,
+            s if s >= 140.0 => AccAhaCategory::Stage2,
+            s if s >= 130.0 => AccAhaCategory::Stage1,
+            s if s >= 120.0 => AccAhaCategory::Elevated,
+            _ => AccAhaCategory::Normal,
+        };
+        let by_diastolic = match diastolic {
+            d if d > 120.0 => AccAhaCategory::HypertensiveCrisis,
+            d if d >= 90.0 => AccAhaCategory::Stage2,
+            d if d >= 80.0 => AccAhaCategory::Stage1,
+            _ => AccAhaCategory::Normal,
+        };
+        Some(by_systolic.max(by_diastolic))
+    }
+
+    /// Category of JNC 7; the higher of the systolic and diastolic categories.
+    pub fn jnc7_category(&self) -> Option<Jnc7Category> {
+        let (systolic, diastolic) = self.values_mm_hg()?;
+        let by_systolic = match systolic {
+            s if s >= 160.0 => Jnc7Category::Stage2,
+            s if s >= 140.0 => Jnc7Category::Stage1,
+            s if s >= 120.0 => Jnc7Category::Prehypertension,
+            _ => Jnc7Category::Normal,
+        };
+        let by_diastolic = match diastolic {
+            d if d >= 100.0 => Jnc7Category::Stage2,
+            d if d >= 90.0 => Jnc7Category::Stage1,
+            d if d >= 80.0 => Jnc7Category::Prehypertension,
+            _ => Jnc7Category::Normal,
+        };
+        Some(by_systolic.max(by_diastolic))
+    }
+
+    /// The systolic (LOINC 8480-6) and diastolic (LOINC 8462-4) component
+    /// results of the blood pressure panel (LOINC 85354-9), both with the
+    /// method and effective time. Position and cuff site have no place in a
+    /// result and are left out.
+    pub fn to_results(&self) -> [LabResult; 2] {
+        let result = |code: &str, display: &str, value: &Quantity| LabResult {
+            code: concept(LOINC, code, display),
+            value: LabValue::Quantity(value.clone()),
+            reference_range: None,
+            interpretation: None,
+            method: self.method.clone(),
+            effective: self.effective,
+        };
+        [
+            result(SYSTOLIC_BLOOD_PRESSURE, "Systolic blood pressure", &self.systolic),
+            result(DIASTOLIC_BLOOD_PRESSURE, "Diastolic blood pressure", &self.diastolic),
+        ]
+    }
+}
+
+impl HasDataSource for BloodPressure {
+    fn data_source(&self) -> Option<&DataSource> {
+        self.source.as_ref()
+    }
+}
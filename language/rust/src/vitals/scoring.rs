@@ -0,0 +1,209 @@
+//! National Early Warning Score 2 (NEWS2).
+//!
+//! Scores follow the Royal College of Physicians NEWS2 chart (2017). NEWS2 is
+//! validated for adults aged 16 and over; it is not a paediatric score. Risk
+//! levels use configurable [`AlertThresholds`], defaulting to the RCP ones.
+
+use alloc::vec::Vec;
+
+use super::{Consciousness, Vitals};
+
+/// A parameter of the NEWS2 chart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Parameter {
+    /// Respiratory rate in breaths/min
+    RespiratoryRate,
+    /// SpO₂ in %
+    OxygenSaturation,
+    /// Air or supplemental oxygen
+    SupplementalOxygen,
+    /// Systolic blood pressure in mmHg
+    SystolicBloodPressure,
+    /// Pulse in bpm
+    Pulse,
+    /// ACVPU level of consciousness
+    Consciousness,
+    /// Temperature in °C
+    Temperature,
+}
+
+/// SpO₂ scoring scale.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum SpO2Scale {
+    /// Standard scale
+    #[default]
+    Scale1,
+    /// For hypercapnic respiratory failure with a prescribed 88–92% target
+    Scale2,
+}
+
+/// Clinical risk band of an aggregate score.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RiskLevel {
+    /// Aggregate 0–4
+    Low,
+    /// A single parameter scoring 3, aggregate below medium
+    LowMedium,
+    /// Aggregate 5–6
+    Medium,
+    /// Aggregate 7 or more
+    High,
+}
+
+/// Scores at which risk levels start.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AlertThresholds {
+    /// Lowest aggregate score with medium risk
+    pub medium: u8,
+    /// Lowest aggregate score with high risk
+    pub high: u8,
+    /// Lowest single-parameter score that raises low to low-medium risk
+    pub single_parameter: u8,
+}
+
+/// The RCP thresholds: medium from 5, high from 7, any single 3.
+impl Default for AlertThresholds {
+    fn default() -> Self {
+        Self { medium: 5, high: 7, single_parameter: 3 }
+    }
+}
+
+impl AlertThresholds {
+    /// Risk level of an aggregate score whose highest parameter score is `max_component`.
+    pub fn risk(&self, total: u8, max_component: u8) -> RiskLevel {
+        if total >= self.high {
+            RiskLevel::High
+        } else if total >= self.medium {
+            RiskLevel::Medium
+        } else if max_component >= self.single_parameter {
+            RiskLevel::LowMedium
+        } else {
+            RiskLevel::Low
+        }
+    }
+}
+
+/// Score of one parameter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ComponentScore {
+    /// Scored parameter
+    pub parameter: Parameter,
+    /// Points (0 to 3)
+    pub score: u8,
+}
+
+/// NEWS2 result.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct News2 {
+    /// Aggregate score
+    pub total: u8,
+    /// Risk level under the thresholds used
+    pub risk: RiskLevel,
+    /// Score of every measured parameter
+    pub components: Vec<ComponentScore>,
+    /// Parameters that were not measured and scored nothing
+    pub missing: Vec<Parameter>,
+}
+
+impl News2 {
+    /// Parameters that added points to the total.
+    pub fn contributing(&self) -> impl Iterator<Item = &ComponentScore> {
+        self.components.iter().filter(|component| component.score > 0)
+    }
+
+    /// Whether every parameter was measured; an incomplete score may understate risk.
+    pub fn is_complete(&self) -> bool {
+        self.missing.is_empty()
+    }
+}
+
+fn respiratory_rate(rate: f64) -> u8 {
+    match rate {
+        r if r <= 8.0 => 3,
+        r if r <= 11.0 => 1,
+        r if r <= 20.0 => 0,
+        r if r <= 24.0 => 2,
+        _ => 3,
+    }
+}
+
+fn oxygen_saturation(spo2: f64, on_oxygen: bool, scale: SpO2Scale) -> u8 {
+    match scale {
+        SpO2Scale::Scale1 => match spo2 {
+            s if s <= 91.0 => 3,
+            s if s <= 93.0 => 2,
+            s if s <= 95.0 => 1,
+            _ => 0,
+        },
+        SpO2Scale::Scale2 => match spo2 {
+            s if s <= 83.0 => 3,
+            s if s <= 85.0 => 2,
+            s if s <= 87.0 => 1,
+            s if s <= 92.0 || !on_oxygen => 0,
+            s if s <= 94.0 => 1,
+            s if s <= 96.0 => 2,
+            _ => 3,
+        },
+    }
+}
+
+fn systolic_blood_pressure(pressure: f64) -> u8 {
+    match pressure {
+        p if p <= 90.0 => 3,
+        p if p <= 100.0 => 2,
+        p if p <= 110.0 => 1,
+        p if p <= 219.0 => 0,
+        _ => 3,
+    }
+}
+
+fn pulse(rate: f64) -> u8 {
+    match rate {
+        r if r <= 40.0 => 3,
+        r if r <= 50.0 => 1,
+        r if r <= 90.0 => 0,
+        r if r <= 110.0 => 1,
+        r if r <= 130.0 => 2,
+        _ => 3,
+    }
+}
+
+fn temperature(celsius: f64) -> u8 {
+    match celsius {
+        t if t <= 35.0 => 3,
+        t if t <= 36.0 => 1,
+        t if t <= 38.0 => 0,
+        t if t <= 39.0 => 1,
+        _ => 2,
+    }
+}
+
+/// NEWS2 of a set of vitals. Unmeasured parameters score 0 and are listed in
+/// [`News2::missing`]; SpO₂ on scale 2 assumes air when oxygen use is unknown.
+pub fn news2(vitals: &Vitals, scale: SpO2Scale, thresholds: &AlertThresholds) -> News2 {
+    let on_oxygen = vitals.supplemental_oxygen.unwrap_or(false);
+    let scores = [
+        (Parameter::RespiratoryRate, vitals.respiratory_rate.map(respiratory_rate)),
+        (Parameter::OxygenSaturation, vitals.oxygen_saturation.map(|spo2| oxygen_saturation(spo2, on_oxygen, scale))),
+        (Parameter::SupplementalOxygen, vitals.supplemental_oxygen.map(|oxygen| if oxygen { 2 } else { 0 })),
+        (Parameter::SystolicBloodPressure, vitals.systolic_blood_pressure.map(systolic_blood_pressure)),
+        (Parameter::Pulse, vitals.pulse.map(pulse)),
+        (
+            Parameter::Consciousness,
+            vitals.consciousness.map(|level| if level == Consciousness::Alert { 0 } else { 3 }),
+        ),
+        (Parameter::Temperature, vitals.temperature.map(temperature)),
+    ];
+
+    let mut result = News2 { total: 0, risk: RiskLevel::Low, components: Vec::new(), missing: Vec::new() };
+    for (parameter, score) in scores {
+        match score {
+            Some(score) => result.components.push(ComponentScore { parameter, score }),
+            None => result.missing.push(parameter),
+        }
+    }
+    result.total = result.components.iter().map(|component| component.score).sum();
+    let max_component = result.components.iter().map(|component| component.score).max().unwrap_or(0);
+    result.risk = thresholds.risk(result.total, max_component);
+    result
+}
@@ -0,0 +1,1196 @@
+//! JavaScript bindings for WellAlly resources.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//!
+//! Each top-level resource is exported as a JS class wrapping the Rust model.
+//! Classes can be built from JSON text or plain JS objects, validated with the
+//! same rules as the Rust backend, and converted back losslessly. Scalar fields
+//! have getters/setters; nested structures are exchanged as plain JS objects.
+
+use chrono::NaiveDate;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::allergy::AllergyIntolerance;
+use crate::cgm::GlucoseSeries;
+use crate::common::BodySite;
+use crate::communication::CommunicationRecord;
+use crate::condition::Condition;
+use crate::consent::ConsentRecord;
+use crate::datetime::ParseDateError;
+use crate::ecg::EcgRecord;
+use crate::family_health::FamilyHealthTree;
+use crate::flag::Flag;
+use crate::health::Person;
+use crate::imaging_report::ImagingReport;
+use crate::lab_report::LabReport;
+use crate::location::Location;
+use crate::medication::MedicationRecord;
+use crate::nephrology::DialysisSession;
+use crate::rehab::RehabSession;
+use crate::wound::WoundAssessment;
+use crate::infusion::InfusionRecord;
+use crate::pathology::PathologyReport;
+use crate::audiology::AudiogramReport;
+use crate::birth::BirthRecord;
+use crate::clearance::ClearanceDocument;
+use crate::travel::TravelHealthRecord;
+use crate::compendium::Compendium;
+use crate::dispense::DispenseRecord;
+use crate::immunization::ImmunizationRecord;
+use crate::relationships::RelatedPerson;
+use crate::questionnaire::{Questionnaire, QuestionnaireResponse, ScoringRule, SumOfOptionScores};
+use crate::referral::Referral;
+use crate::resource::Resource;
+use crate::task::Task;
+use crate::validation::Validate;
+
+fn to_js<T: Serialize + ?Sized>(value: &T) -> Result<JsValue, JsError> {
+    let serializer = serde_wasm_bindgen::Serializer::json_compatible();
+    value.serialize(&serializer).map_err(|err| JsError::new(&err.to_string()))
+}
+
+fn from_js<T: DeserializeOwned>(value: JsValue) -> Result<T, JsError> {
+    serde_wasm_bindgen::from_value(value).map_err(|err| JsError::new(&err.to_string()))
+}
+
+fn parse_date(value: &str) -> Result<NaiveDate, JsError> {
+    value.parse().map_err(|err| JsError::new(&format!("invalid date '{}': {}", value, err)))
+}
+
+/// Parses a lenient [`FlexibleDate`] or [`FlexibleDateTime`].
+fn parse_flexible<T: core::str::FromStr<Err = ParseDateError>>(value: &str) -> Result<T, JsError> {
+    value.parse().map_err(|err| JsError::new(&format!("invalid date '{}': {}", value, err)))
+}
+
+/// Methods shared by every exported resource class.
+macro_rules! js_resource {
+    ($js:ident, $model:ty) => {
+        #[wasm_bindgen]
+        impl $js {
+            /// Parses a resource from JSON text.
+            #[wasm_bindgen(js_name = fromJson)]
+            pub fn from_json(json: &str) -> Result<$js, JsError> {
+                <$model>::from_json(json).map($js).map_err(|err| JsError::new(&err.to_string()))
+            }
+
+            /// Serializes the resource to canonical JSON text.
+            #[wasm_bindgen(js_name = toJson)]
+            pub fn to_json(&self) -> Result<String, JsError> {
+                serde_json::to_string(&self.0).map_err(|err| JsError::new(&err.to_string()))
+            }
+
+            /// Builds a resource from a plain JS object.
+            #[wasm_bindgen(js_name = fromObject)]
+            pub fn from_object(value: JsValue) -> Result<$js, JsError> {
+                from_js(value).map($js)
+            }
+
+            /// Converts the resource to a plain JS object.
+            #[wasm_bindgen(js_name = toObject)]
+            pub fn to_object(&self) -> Result<JsValue, JsError> {
+                to_js(&self.0)
+            }
+
+            /// Returns the validation issues as an array of `{ path, message }`.
+            pub fn validate(&self) -> Result<JsValue, JsError> {
+                to_js(&self.0.validate())
+            }
+
+            /// Whether the resource has no validation issues.
+            #[wasm_bindgen(js_name = isValid)]
+            pub fn is_valid(&self) -> bool {
+                self.0.is_valid()
+            }
+        }
+
+        impl From<$model> for $js {
+            fn from(model: $model) -> Self {
+                $js(model)
+            }
+        }
+
+        impl From<$js> for $model {
+            fn from(js: $js) -> Self {
+                js.0
+            }
+        }
+    };
+}
+
+/// JS class for [`Person`].
+#[wasm_bindgen(js_name = Person)]
+pub struct JsPerson(Person);
+
+js_resource!(JsPerson, Person);
+
+#[wasm_bindgen(js_class = Person)]
+impl JsPerson {
+    /// Creates a person with the required fields; `name` is an array of HumanName objects.
+    #[wasm_bindgen(constructor)]
+    pub fn new(id: String, name: JsValue, birth_date: &str) -> Result<JsPerson, JsError> {
+        Ok(JsPerson(Person {
+            id,
+            name: from_js(name)?,
+            birth_date: parse_flexible(birth_date)?,
+            ..Default::default()
+        }))
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn id(&self) -> String {
+        self.0.id.clone()
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_id(&mut self, id: String) {
+        self.0.id = id;
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn name(&self) -> Result<JsValue, JsError> {
+        to_js(&self.0.name)
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_name(&mut self, name: JsValue) -> Result<(), JsError> {
+        self.0.name = from_js(name)?;
+        Ok(())
+    }
+
+    #[wasm_bindgen(getter = birthDate)]
+    pub fn birth_date(&self) -> String {
+        self.0.birth_date.to_string()
+    }
+
+    #[wasm_bindgen(setter = birthDate)]
+    pub fn set_birth_date(&mut self, birth_date: &str) -> Result<(), JsError> {
+        self.0.birth_date = parse_flexible(birth_date)?;
+        Ok(())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn gender(&self) -> Result<JsValue, JsError> {
+        to_js(&self.0.gender)
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_gender(&mut self, gender: JsValue) -> Result<(), JsError> {
+        self.0.gender = from_js(gender)?;
+        Ok(())
+    }
+
+    #[wasm_bindgen(getter = clinicalSummary)]
+    pub fn clinical_summary(&self) -> Result<JsValue, JsError> {
+        to_js(&self.0.clinical_summary)
+    }
+
+    #[wasm_bindgen(setter = clinicalSummary)]
+    pub fn set_clinical_summary(&mut self, summary: JsValue) -> Result<(), JsError> {
+        self.0.clinical_summary = from_js(summary)?;
+        Ok(())
+    }
+}
+
+/// JS class for [`LabReport`].
+#[wasm_bindgen(js_name = LabReport)]
+pub struct JsLabReport(LabReport);
+
+js_resource!(JsLabReport, LabReport);
+
+#[wasm_bindgen(js_class = LabReport)]
+impl JsLabReport {
+    /// Creates a report; `issued_at` is an ISO 8601 date-time and `results` an array of LabResult objects.
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        id: String,
+        patient_id: String,
+        issued_at: &str,
+        results: JsValue,
+    ) -> Result<JsLabReport, JsError> {
+        Ok(JsLabReport(LabReport {
+            id,
+            patient_id,
+            issued_at: parse_flexible(issued_at)?,
+            results: from_js(results)?,
+            facility: None,
+            panel: None,
+            specimen: None,
+            notes: None,
+            schema_version: None,
+            security_labels: None,
+            extensions: None,
+            #[cfg(feature = "preserve-unknown")]
+            extra: Default::default(),
+        }))
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn id(&self) -> String {
+        self.0.id.clone()
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_id(&mut self, id: String) {
+        self.0.id = id;
+    }
+
+    #[wasm_bindgen(getter = patientId)]
+    pub fn patient_id(&self) -> String {
+        self.0.patient_id.clone()
+    }
+
+    #[wasm_bindgen(setter = patientId)]
+    pub fn set_patient_id(&mut self, patient_id: String) {
+        self.0.patient_id = patient_id;
+    }
+
+    #[wasm_bindgen(getter = issuedAt)]
+    pub fn issued_at(&self) -> String {
+        self.0.issued_at.to_string()
+    }
+
+    #[wasm_bindgen(setter = issuedAt)]
+    pub fn set_issued_at(&mut self, issued_at: &str) -> Result<(), JsError> {
+        self.0.issued_at = parse_flexible(issued_at)?;
+        Ok(())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn results(&self) -> Result<JsValue, JsError> {
+        to_js(&self.0.results)
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_results(&mut self, results: JsValue) -> Result<(), JsError> {
+        self.0.results = from_js(results)?;
+        Ok(())
+    }
+}
+
+/// JS class for [`ImagingReport`].
+#[wasm_bindgen(js_name = ImagingReport)]
+pub struct JsImagingReport(ImagingReport);
+
+js_resource!(JsImagingReport, ImagingReport);
+
+#[wasm_bindgen(js_class = ImagingReport)]
+impl JsImagingReport {
+    /// Creates a report; `modality` and `body_site` are Modality/BodySite
+    /// objects (a bare Coding is accepted as the site).
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        id: String,
+        patient_id: String,
+        modality: JsValue,
+        body_site: JsValue,
+        reported_at: &str,
+    ) -> Result<JsImagingReport, JsError> {
+        Ok(JsImagingReport(ImagingReport {
+            id,
+            patient_id,
+            modality: from_js(modality)?,
+            body_site: from_js::<BodySite>(body_site)?,
+            reported_at: parse_flexible(reported_at)?,
+            study_instance_uid: None,
+            performer: None,
+            based_on: None,
+            reason_code: None,
+            clinical_history: None,
+            protocol: None,
+            technique: None,
+            comparison: None,
+            comparison_study_uids: Vec::new(),
+            change_from_prior: None,
+            findings: None,
+            impression: None,
+            radiation_dose: None,
+            measurements: None,
+            attachments: None,
+            notes: None,
+            schema_version: None,
+            security_labels: None,
+            extensions: None,
+            #[cfg(feature = "preserve-unknown")]
+            extra: Default::default(),
+        }))
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn id(&self) -> String {
+        self.0.id.clone()
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_id(&mut self, id: String) {
+        self.0.id = id;
+    }
+
+    #[wasm_bindgen(getter = patientId)]
+    pub fn patient_id(&self) -> String {
+        self.0.patient_id.clone()
+    }
+
+    #[wasm_bindgen(setter = patientId)]
+    pub fn set_patient_id(&mut self, patient_id: String) {
+        self.0.patient_id = patient_id;
+    }
+
+    #[wasm_bindgen(getter = reportedAt)]
+    pub fn reported_at(&self) -> String {
+        self.0.reported_at.to_string()
+    }
+
+    #[wasm_bindgen(setter = reportedAt)]
+    pub fn set_reported_at(&mut self, reported_at: &str) -> Result<(), JsError> {
+        self.0.reported_at = parse_flexible(reported_at)?;
+        Ok(())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn findings(&self) -> Option<Vec<String>> {
+        self.0.findings.clone()
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_findings(&mut self, findings: Option<Vec<String>>) {
+        self.0.findings = findings;
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn impression(&self) -> Option<String> {
+        self.0.impression.clone()
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_impression(&mut self, impression: Option<String>) {
+        self.0.impression = impression;
+    }
+}
+
+/// JS class for [`MedicationRecord`].
+#[wasm_bindgen(js_name = MedicationRecord)]
+pub struct JsMedicationRecord(MedicationRecord);
+
+js_resource!(JsMedicationRecord, MedicationRecord);
+
+#[wasm_bindgen(js_class = MedicationRecord)]
+impl JsMedicationRecord {
+    /// Creates a record; `medication`, `dosage` and `route` are Coding/Dosage/Route objects.
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        id: String,
+        patient_id: String,
+        medication: JsValue,
+        dosage: JsValue,
+        route: JsValue,
+        start_date: &str,
+    ) -> Result<JsMedicationRecord, JsError> {
+        Ok(JsMedicationRecord(MedicationRecord {
+            id,
+            patient_id,
+            medication: from_js(medication)?,
+            dosage: from_js(dosage)?,
+            route: from_js(route)?,
+            start_date: parse_date(start_date)?,
+            form: None,
+            frequency: None,
+            duration_days: None,
+            end_date: None,
+            indication: None,
+            instructions: None,
+            is_prn: false,
+            prn_reason: None,
+            max_dose_per_day: None,
+            dea_schedule: None,
+            notes: None,
+            schema_version: None,
+            security_labels: None,
+            extensions: None,
+            #[cfg(feature = "preserve-unknown")]
+            extra: Default::default(),
+        }))
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn id(&self) -> String {
+        self.0.id.clone()
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_id(&mut self, id: String) {
+        self.0.id = id;
+    }
+
+    #[wasm_bindgen(getter = patientId)]
+    pub fn patient_id(&self) -> String {
+        self.0.patient_id.clone()
+    }
+
+    #[wasm_bindgen(setter = patientId)]
+    pub fn set_patient_id(&mut self, patient_id: String) {
+        self.0.patient_id = patient_id;
+    }
+
+    #[wasm_bindgen(getter = startDate)]
+    pub fn start_date(&self) -> String {
+        self.0.start_date.to_string()
+    }
+
+    #[wasm_bindgen(setter = startDate)]
+    pub fn set_start_date(&mut self, start_date: &str) -> Result<(), JsError> {
+        self.0.start_date = parse_date(start_date)?;
+        Ok(())
+    }
+
+    #[wasm_bindgen(getter = endDate)]
+    pub fn end_date(&self) -> Option<String> {
+        self.0.end_date.map(|date| date.to_string())
+    }
+
+    #[wasm_bindgen(setter = endDate)]
+    pub fn set_end_date(&mut self, end_date: Option<String>) -> Result<(), JsError> {
+        self.0.end_date = end_date.as_deref().map(parse_date).transpose()?;
+        Ok(())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn frequency(&self) -> Option<String> {
+        self.0.frequency.clone()
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_frequency(&mut self, frequency: Option<String>) {
+        self.0.frequency = frequency;
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn instructions(&self) -> Option<String> {
+        self.0.instructions.clone()
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_instructions(&mut self, instructions: Option<String>) {
+        self.0.instructions = instructions;
+    }
+
+    #[wasm_bindgen(getter = isPrn)]
+    pub fn is_prn(&self) -> bool {
+        self.0.is_prn
+    }
+
+    #[wasm_bindgen(setter = isPrn)]
+    pub fn set_is_prn(&mut self, is_prn: bool) {
+        self.0.is_prn = is_prn;
+    }
+}
+
+/// JS class for [`FamilyHealthTree`].
+#[wasm_bindgen(js_name = FamilyHealthTree)]
+pub struct JsFamilyHealthTree(FamilyHealthTree);
+
+js_resource!(JsFamilyHealthTree, FamilyHealthTree);
+
+#[wasm_bindgen(js_class = FamilyHealthTree)]
+impl JsFamilyHealthTree {
+    /// Creates a tree; `members` is an array of FamilyMember objects.
+    #[wasm_bindgen(constructor)]
+    pub fn new(proband_id: String, members: JsValue) -> Result<JsFamilyHealthTree, JsError> {
+        Ok(JsFamilyHealthTree(FamilyHealthTree {
+            proband_id,
+            members: from_js(members)?,
+            schema_version: None,
+            security_labels: None,
+            extensions: None,
+            #[cfg(feature = "preserve-unknown")]
+            extra: Default::default(),
+        }))
+    }
+
+    #[wasm_bindgen(getter = probandId)]
+    pub fn proband_id(&self) -> String {
+        self.0.proband_id.clone()
+    }
+
+    #[wasm_bindgen(setter = probandId)]
+    pub fn set_proband_id(&mut self, proband_id: String) {
+        self.0.proband_id = proband_id;
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn members(&self) -> Result<JsValue, JsError> {
+        to_js(&self.0.members)
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_members(&mut self, members: JsValue) -> Result<(), JsError> {
+        self.0.members = from_js(members)?;
+        Ok(())
+    }
+}
+
+/// JS class for [`Questionnaire`].
+#[wasm_bindgen(js_name = Questionnaire)]
+pub struct JsQuestionnaire(Questionnaire);
+
+js_resource!(JsQuestionnaire, Questionnaire);
+
+#[wasm_bindgen(js_class = Questionnaire)]
+impl JsQuestionnaire {
+    #[wasm_bindgen(getter)]
+    pub fn id(&self) -> String {
+        self.0.id.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn items(&self) -> Result<JsValue, JsError> {
+        to_js(&self.0.item)
+    }
+
+    /// Sum of the answer option scores of a response; `undefined` if a scored question is unanswered.
+    pub fn score(&self, response: &JsQuestionnaireResponse) -> Option<f64> {
+        SumOfOptionScores.score(&self.0, &response.0)
+    }
+}
+
+/// JS class for [`QuestionnaireResponse`].
+#[wasm_bindgen(js_name = QuestionnaireResponse)]
+pub struct JsQuestionnaireResponse(QuestionnaireResponse);
+
+js_resource!(JsQuestionnaireResponse, QuestionnaireResponse);
+
+#[wasm_bindgen(js_class = QuestionnaireResponse)]
+impl JsQuestionnaireResponse {
+    #[wasm_bindgen(getter)]
+    pub fn id(&self) -> String {
+        self.0.id.clone()
+    }
+
+    #[wasm_bindgen(getter = patientId)]
+    pub fn patient_id(&self) -> String {
+        self.0.patient_id.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn items(&self) -> Result<JsValue, JsError> {
+        to_js(&self.0.item)
+    }
+
+    /// Returns the issues of the answers against `questionnaire` as an array of `{ path, message }`.
+    #[wasm_bindgen(js_name = validateAgainst)]
+    pub fn validate_against(&self, questionnaire: &JsQuestionnaire) -> Result<JsValue, JsError> {
+        to_js(&self.0.validate_against(&questionnaire.0))
+    }
+}
+
+/// JS class for [`Referral`].
+#[wasm_bindgen(js_name = Referral)]
+pub struct JsReferral(Referral);
+
+js_resource!(JsReferral, Referral);
+
+#[wasm_bindgen(js_class = Referral)]
+impl JsReferral {
+    #[wasm_bindgen(getter)]
+    pub fn id(&self) -> String {
+        self.0.id.clone()
+    }
+
+    #[wasm_bindgen(getter = patientId)]
+    pub fn patient_id(&self) -> String {
+        self.0.patient_id.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn status(&self) -> Result<JsValue, JsError> {
+        to_js(&self.0.status)
+    }
+
+    /// Moves the referral to another status (e.g., "active"), failing if the lifecycle does not allow it.
+    #[wasm_bindgen(js_name = setStatus)]
+    pub fn set_status(&mut self, status: JsValue) -> Result<(), JsError> {
+        self.0.set_status(from_js(status)?).map_err(|err| JsError::new(&err.to_string()))
+    }
+}
+
+/// JS class for [`Task`].
+#[wasm_bindgen(js_name = Task)]
+pub struct JsTask(Task);
+
+js_resource!(JsTask, Task);
+
+#[wasm_bindgen(js_class = Task)]
+impl JsTask {
+    #[wasm_bindgen(getter)]
+    pub fn id(&self) -> String {
+        self.0.id.clone()
+    }
+
+    #[wasm_bindgen(getter = patientId)]
+    pub fn patient_id(&self) -> String {
+        self.0.patient_id.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn description(&self) -> String {
+        self.0.description.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn status(&self) -> Result<JsValue, JsError> {
+        to_js(&self.0.status)
+    }
+
+    /// Moves the task to another status (e.g., "in-progress"), failing if the lifecycle does not allow it.
+    #[wasm_bindgen(js_name = setStatus)]
+    pub fn set_status(&mut self, status: JsValue) -> Result<(), JsError> {
+        self.0.set_status(from_js(status)?).map_err(|err| JsError::new(&err.to_string()))
+    }
+
+    /// Whether the task is still open after its due date (`today` is an ISO 8601 date).
+    #[wasm_bindgen(js_name = isOverdue)]
+    pub fn is_overdue(&self, today: &str) -> Result<bool, JsError> {
+        Ok(self.0.is_overdue(parse_date(today)?))
+    }
+}
+
+/// JS class for [`CommunicationRecord`].
+#[wasm_bindgen(js_name = CommunicationRecord)]
+pub struct JsCommunicationRecord(CommunicationRecord);
+
+js_resource!(JsCommunicationRecord, CommunicationRecord);
+
+#[wasm_bindgen(js_class = CommunicationRecord)]
+impl JsCommunicationRecord {
+    #[wasm_bindgen(getter)]
+    pub fn id(&self) -> String {
+        self.0.id.clone()
+    }
+
+    #[wasm_bindgen(getter = patientId)]
+    pub fn patient_id(&self) -> String {
+        self.0.patient_id.clone()
+    }
+
+    /// Text parts of the message joined by blank lines.
+    #[wasm_bindgen(getter)]
+    pub fn text(&self) -> String {
+        self.0.text()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn payload(&self) -> Result<JsValue, JsError> {
+        to_js(&self.0.payload)
+    }
+}
+
+/// JS class for [`Location`].
+#[wasm_bindgen(js_name = Location)]
+pub struct JsLocation(Location);
+
+js_resource!(JsLocation, Location);
+
+#[wasm_bindgen(js_class = Location)]
+impl JsLocation {
+    #[wasm_bindgen(getter)]
+    pub fn id(&self) -> String {
+        self.0.id.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn name(&self) -> String {
+        self.0.name.clone()
+    }
+
+    /// Distance in kilometres from the given coordinates; `undefined` without a position.
+    #[wasm_bindgen(js_name = distanceKm)]
+    pub fn distance_km(&self, latitude: f64, longitude: f64) -> Option<f64> {
+        self.0.distance_km(&crate::location::Position { latitude, longitude, altitude: None })
+    }
+}
+
+/// JS class for [`Condition`].
+#[wasm_bindgen(js_name = Condition)]
+pub struct JsCondition(Condition);
+
+js_resource!(JsCondition, Condition);
+
+#[wasm_bindgen(js_class = Condition)]
+impl JsCondition {
+    #[wasm_bindgen(getter)]
+    pub fn id(&self) -> String {
+        self.0.id.clone()
+    }
+
+    #[wasm_bindgen(getter = patientId)]
+    pub fn patient_id(&self) -> String {
+        self.0.patient_id.clone()
+    }
+
+    /// Whether the condition is active and not refuted or entered in error.
+    #[wasm_bindgen(getter = isActive)]
+    pub fn is_active(&self) -> bool {
+        self.0.is_active()
+    }
+}
+
+/// JS class for [`AllergyIntolerance`].
+#[wasm_bindgen(js_name = AllergyIntolerance)]
+pub struct JsAllergyIntolerance(AllergyIntolerance);
+
+js_resource!(JsAllergyIntolerance, AllergyIntolerance);
+
+#[wasm_bindgen(js_class = AllergyIntolerance)]
+impl JsAllergyIntolerance {
+    #[wasm_bindgen(getter)]
+    pub fn id(&self) -> String {
+        self.0.id.clone()
+    }
+
+    #[wasm_bindgen(getter = patientId)]
+    pub fn patient_id(&self) -> String {
+        self.0.patient_id.clone()
+    }
+
+    /// Whether the allergy still applies.
+    #[wasm_bindgen(getter = isActive)]
+    pub fn is_active(&self) -> bool {
+        self.0.is_active()
+    }
+}
+
+/// JS class for [`Flag`].
+#[wasm_bindgen(js_name = Flag)]
+pub struct JsFlag(Flag);
+
+js_resource!(JsFlag, Flag);
+
+#[wasm_bindgen(js_class = Flag)]
+impl JsFlag {
+    #[wasm_bindgen(getter)]
+    pub fn id(&self) -> String {
+        self.0.id.clone()
+    }
+
+    #[wasm_bindgen(getter = patientId)]
+    pub fn patient_id(&self) -> String {
+        self.0.patient_id.clone()
+    }
+
+    /// Message shown to users; `undefined` when the code has no text or display.
+    #[wasm_bindgen(getter)]
+    pub fn message(&self) -> Option<String> {
+        self.0.message().map(Into::into)
+    }
+}
+
+/// JS class for [`EcgRecord`].
+#[wasm_bindgen(js_name = EcgRecord)]
+pub struct JsEcgRecord(EcgRecord);
+
+js_resource!(JsEcgRecord, EcgRecord);
+
+#[wasm_bindgen(js_class = EcgRecord)]
+impl JsEcgRecord {
+    #[wasm_bindgen(getter)]
+    pub fn id(&self) -> String {
+        self.0.id.clone()
+    }
+
+    #[wasm_bindgen(getter = patientId)]
+    pub fn patient_id(&self) -> String {
+        self.0.patient_id.clone()
+    }
+
+    /// Length of the longest inline lead in seconds; `undefined` without inline samples.
+    #[wasm_bindgen(getter = durationSeconds)]
+    pub fn duration_seconds(&self) -> Option<f64> {
+        self.0.duration_seconds()
+    }
+}
+
+/// JS class for [`ConsentRecord`].
+#[wasm_bindgen(js_name = ConsentRecord)]
+pub struct JsConsentRecord(ConsentRecord);
+
+js_resource!(JsConsentRecord, ConsentRecord);
+
+#[wasm_bindgen(js_class = ConsentRecord)]
+impl JsConsentRecord {
+    #[wasm_bindgen(getter)]
+    pub fn id(&self) -> String {
+        self.0.id.clone()
+    }
+
+    #[wasm_bindgen(getter = patientId)]
+    pub fn patient_id(&self) -> String {
+        self.0.patient_id.clone()
+    }
+
+    /// Whether the consent is in force on `date` (YYYY-MM-DD).
+    #[wasm_bindgen(js_name = isInForce)]
+    pub fn is_in_force(&self, date: &str) -> Result<bool, JsError> {
+        Ok(self.0.is_in_force(parse_date(date)?))
+    }
+}
+
+/// JS class for [`DialysisSession`].
+#[wasm_bindgen(js_name = DialysisSession)]
+pub struct JsDialysisSession(DialysisSession);
+
+js_resource!(JsDialysisSession, DialysisSession);
+
+#[wasm_bindgen(js_class = DialysisSession)]
+impl JsDialysisSession {
+    #[wasm_bindgen(getter)]
+    pub fn id(&self) -> String {
+        self.0.id.clone()
+    }
+
+    #[wasm_bindgen(getter = patientId)]
+    pub fn patient_id(&self) -> String {
+        self.0.patient_id.clone()
+    }
+
+    /// Ultrafiltration rate in mL/kg/h; `undefined` without the volume and post-treatment weight.
+    #[wasm_bindgen(getter = ultrafiltrationRate)]
+    pub fn ultrafiltration_rate(&self) -> Option<f64> {
+        self.0.ultrafiltration_rate()
+    }
+}
+
+/// JS class for [`RehabSession`].
+#[wasm_bindgen(js_name = RehabSession)]
+pub struct JsRehabSession(RehabSession);
+
+js_resource!(JsRehabSession, RehabSession);
+
+#[wasm_bindgen(js_class = RehabSession)]
+impl JsRehabSession {
+    #[wasm_bindgen(getter)]
+    pub fn id(&self) -> String {
+        self.0.id.clone()
+    }
+
+    #[wasm_bindgen(getter = patientId)]
+    pub fn patient_id(&self) -> String {
+        self.0.patient_id.clone()
+    }
+
+    /// Pain after minus pain before on the 0–10 scale; `undefined` unless both were recorded.
+    #[wasm_bindgen(getter = painChange)]
+    pub fn pain_change(&self) -> Option<i16> {
+        self.0.pain_change()
+    }
+}
+
+/// JS class for [`WoundAssessment`].
+#[wasm_bindgen(js_name = WoundAssessment)]
+pub struct JsWoundAssessment(WoundAssessment);
+
+js_resource!(JsWoundAssessment, WoundAssessment);
+
+#[wasm_bindgen(js_class = WoundAssessment)]
+impl JsWoundAssessment {
+    #[wasm_bindgen(getter)]
+    pub fn id(&self) -> String {
+        self.0.id.clone()
+    }
+
+    #[wasm_bindgen(getter = patientId)]
+    pub fn patient_id(&self) -> String {
+        self.0.patient_id.clone()
+    }
+
+    /// Wound area in cm² (length × width); `undefined` without dimensions.
+    #[wasm_bindgen(getter = areaCm2)]
+    pub fn area_cm2(&self) -> Option<f64> {
+        self.0.area_cm2()
+    }
+}
+
+/// JS class for [`InfusionRecord`].
+#[wasm_bindgen(js_name = InfusionRecord)]
+pub struct JsInfusionRecord(InfusionRecord);
+
+js_resource!(JsInfusionRecord, InfusionRecord);
+
+#[wasm_bindgen(js_class = InfusionRecord)]
+impl JsInfusionRecord {
+    #[wasm_bindgen(getter)]
+    pub fn id(&self) -> String {
+        self.0.id.clone()
+    }
+
+    #[wasm_bindgen(getter = patientId)]
+    pub fn patient_id(&self) -> String {
+        self.0.patient_id.clone()
+    }
+
+    /// Volume infused in mL, following titrations and leaving out interruptions; `undefined` while running.
+    #[wasm_bindgen(getter = volumeInfusedMl)]
+    pub fn volume_infused_ml(&self) -> Option<f64> {
+        self.0.volume_infused_ml()
+    }
+}
+
+/// JS class for [`PathologyReport`].
+#[wasm_bindgen(js_name = PathologyReport)]
+pub struct JsPathologyReport(PathologyReport);
+
+js_resource!(JsPathologyReport, PathologyReport);
+
+#[wasm_bindgen(js_class = PathologyReport)]
+impl JsPathologyReport {
+    #[wasm_bindgen(getter)]
+    pub fn id(&self) -> String {
+        self.0.id.clone()
+    }
+
+    #[wasm_bindgen(getter = patientId)]
+    pub fn patient_id(&self) -> String {
+        self.0.patient_id.clone()
+    }
+
+    /// Whether a diagnosis is ICD-O-3 coded as malignant.
+    #[wasm_bindgen(getter = isMalignant)]
+    pub fn is_malignant(&self) -> bool {
+        self.0.is_malignant()
+    }
+}
+
+/// JS class for [`AudiogramReport`].
+#[wasm_bindgen(js_name = AudiogramReport)]
+pub struct JsAudiogramReport(AudiogramReport);
+
+js_resource!(JsAudiogramReport, AudiogramReport);
+
+#[wasm_bindgen(js_class = AudiogramReport)]
+impl JsAudiogramReport {
+    #[wasm_bindgen(getter)]
+    pub fn id(&self) -> String {
+        self.0.id.clone()
+    }
+
+    #[wasm_bindgen(getter = patientId)]
+    pub fn patient_id(&self) -> String {
+        self.0.patient_id.clone()
+    }
+
+    /// Whether every air-conduction tone was heard at or below `levelDb` dB HL.
+    #[wasm_bindgen(js_name = passesScreening)]
+    pub fn passes_screening(&self, level_db: f64) -> bool {
+        self.0.passes_screening(level_db)
+    }
+}
+
+/// JS class for [`BirthRecord`].
+#[wasm_bindgen(js_name = BirthRecord)]
+pub struct JsBirthRecord(BirthRecord);
+
+js_resource!(JsBirthRecord, BirthRecord);
+
+#[wasm_bindgen(js_class = BirthRecord)]
+impl JsBirthRecord {
+    #[wasm_bindgen(getter)]
+    pub fn id(&self) -> String {
+        self.0.id.clone()
+    }
+
+    #[wasm_bindgen(getter = patientId)]
+    pub fn patient_id(&self) -> String {
+        self.0.patient_id.clone()
+    }
+
+    /// Whether the baby was born before 37 weeks.
+    #[wasm_bindgen(getter = isPreterm)]
+    pub fn is_preterm(&self) -> bool {
+        self.0.is_preterm()
+    }
+}
+
+/// JS class for [`ClearanceDocument`].
+#[wasm_bindgen(js_name = ClearanceDocument)]
+pub struct JsClearanceDocument(ClearanceDocument);
+
+js_resource!(JsClearanceDocument, ClearanceDocument);
+
+#[wasm_bindgen(js_class = ClearanceDocument)]
+impl JsClearanceDocument {
+    #[wasm_bindgen(getter)]
+    pub fn id(&self) -> String {
+        self.0.id.clone()
+    }
+
+    #[wasm_bindgen(getter = patientId)]
+    pub fn patient_id(&self) -> String {
+        self.0.patient_id.clone()
+    }
+
+    /// Whether the document clears the patient on `date` (YYYY-MM-DD).
+    #[wasm_bindgen(js_name = isValidOn)]
+    pub fn is_valid_on(&self, date: &str) -> Result<bool, JsError> {
+        Ok(self.0.is_valid_on(parse_date(date)?))
+    }
+}
+
+/// JS class for [`TravelHealthRecord`].
+#[wasm_bindgen(js_name = TravelHealthRecord)]
+pub struct JsTravelHealthRecord(TravelHealthRecord);
+
+js_resource!(JsTravelHealthRecord, TravelHealthRecord);
+
+#[wasm_bindgen(js_class = TravelHealthRecord)]
+impl JsTravelHealthRecord {
+    #[wasm_bindgen(getter)]
+    pub fn id(&self) -> String {
+        self.0.id.clone()
+    }
+
+    #[wasm_bindgen(getter = patientId)]
+    pub fn patient_id(&self) -> String {
+        self.0.patient_id.clone()
+    }
+
+    /// Whether a required vaccine is still outstanding.
+    #[wasm_bindgen(js_name = hasUnmetRequirements)]
+    pub fn has_unmet_requirements(&self) -> bool {
+        !self.0.unmet_requirements().is_empty()
+    }
+}
+
+/// JS class for [`Compendium`].
+#[wasm_bindgen(js_name = Compendium)]
+pub struct JsCompendium(Compendium);
+
+js_resource!(JsCompendium, Compendium);
+
+#[wasm_bindgen(js_class = Compendium)]
+impl JsCompendium {
+    #[wasm_bindgen(getter)]
+    pub fn id(&self) -> String {
+        self.0.id.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn tests(&self) -> Result<JsValue, JsError> {
+        to_js(&self.0.tests)
+    }
+
+    /// Test with LOINC code `code`; `undefined` if the catalog lacks it.
+    #[wasm_bindgen(js_name = byCode)]
+    pub fn by_code(&self, code: &str) -> Result<JsValue, JsError> {
+        self.0.by_code(code).map_or(Ok(JsValue::UNDEFINED), to_js)
+    }
+
+    /// Tests whose name or an alias contains `query`.
+    pub fn search(&self, query: &str) -> Result<JsValue, JsError> {
+        to_js(&self.0.search(query))
+    }
+}
+
+/// JS class for [`DispenseRecord`].
+#[wasm_bindgen(js_name = DispenseRecord)]
+pub struct JsDispenseRecord(DispenseRecord);
+
+js_resource!(JsDispenseRecord, DispenseRecord);
+
+#[wasm_bindgen(js_class = DispenseRecord)]
+impl JsDispenseRecord {
+    #[wasm_bindgen(getter)]
+    pub fn id(&self) -> String {
+        self.0.id.clone()
+    }
+
+    #[wasm_bindgen(getter = patientId)]
+    pub fn patient_id(&self) -> String {
+        self.0.patient_id.clone()
+    }
+
+    /// Due dates (YYYY-MM-DD) of the remaining refills.
+    #[wasm_bindgen(js_name = refillDueDates)]
+    pub fn refill_due_dates(&self) -> Result<JsValue, JsError> {
+        to_js(&self.0.refill_due_dates())
+    }
+}
+
+/// JS class for [`ImmunizationRecord`].
+#[wasm_bindgen(js_name = ImmunizationRecord)]
+pub struct JsImmunizationRecord(ImmunizationRecord);
+
+js_resource!(JsImmunizationRecord, ImmunizationRecord);
+
+#[wasm_bindgen(js_class = ImmunizationRecord)]
+impl JsImmunizationRecord {
+    #[wasm_bindgen(getter)]
+    pub fn id(&self) -> String {
+        self.0.id.clone()
+    }
+
+    #[wasm_bindgen(getter = patientId)]
+    pub fn patient_id(&self) -> String {
+        self.0.patient_id.clone()
+    }
+
+    #[wasm_bindgen(getter, js_name = lotNumber)]
+    pub fn lot_number(&self) -> Option<String> {
+        self.0.lot_number.clone()
+    }
+}
+
+/// JS class for [`RelatedPerson`].
+#[wasm_bindgen(js_name = RelatedPerson)]
+pub struct JsRelatedPerson(RelatedPerson);
+
+js_resource!(JsRelatedPerson, RelatedPerson);
+
+#[wasm_bindgen(js_class = RelatedPerson)]
+impl JsRelatedPerson {
+    #[wasm_bindgen(getter)]
+    pub fn id(&self) -> String {
+        self.0.id.clone()
+    }
+
+    #[wasm_bindgen(getter = patientId)]
+    pub fn patient_id(&self) -> String {
+        self.0.patient_id.clone()
+    }
+
+    #[wasm_bindgen(getter = personId)]
+    pub fn person_id(&self) -> Option<String> {
+        self.0.person_id.clone()
+    }
+}
+
+/// JS class for [`GlucoseSeries`].
+#[wasm_bindgen(js_name = GlucoseSeries)]
+pub struct JsGlucoseSeries(GlucoseSeries);
+
+js_resource!(JsGlucoseSeries, GlucoseSeries);
+
+#[wasm_bindgen(js_class = GlucoseSeries)]
+impl JsGlucoseSeries {
+    #[wasm_bindgen(getter)]
+    pub fn id(&self) -> String {
+        self.0.id.clone()
+    }
+
+    #[wasm_bindgen(getter = patientId)]
+    pub fn patient_id(&self) -> String {
+        self.0.patient_id.clone()
+    }
+
+    /// Mean glucose in mg/dL; `undefined` without readings.
+    #[wasm_bindgen(getter)]
+    pub fn mean(&self) -> Option<f64> {
+        self.0.mean()
+    }
+
+    /// Percentage of readings in the 70–180 mg/dL target range.
+    #[wasm_bindgen(js_name = timeInRange)]
+    pub fn time_in_range(&self) -> Option<f64> {
+        self.0.time_in_range().map(|tir| tir.in_range)
+    }
+}
@@ -0,0 +1,209 @@
+//! Verification of pushed webhook payloads.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//!
+//! Webhook deliveries carry a [`SIGNATURE_HEADER`] of the form
+//! `t=<unix seconds>,v1=<hex>`: the HMAC-SHA256, keyed with the endpoint's
+//! shared secret, of the timestamp, a `.` and the raw request body. A header
+//! may hold several `v1` signatures while a secret is being rotated; one
+//! matching is enough. [`verify`] checks the signature in constant time and
+//! rejects deliveries whose timestamp is more than [`DEFAULT_TOLERANCE`]
+//! away from now, so captured requests cannot be replayed later.
+//! [`verify_event`] also parses the body as a [`RecordEvent`].
+//!
+//! Verify the body exactly as received, before any JSON parsing:
+//!
+//! ```
+//! use chrono::Utc;
+//! use wellally::webhook::{self, sign};
+//!
+//! let body = concat!(
+//!     r#"{"eventType":"deleted","eventVersion":"1.0.0","id":"lab-1","#,
+//!     r#""occurredAt":"2024-11-02T09:30:00Z","resourceType":"LabReport"}"#
+//! )
+//! .as_bytes();
+//! let header = sign(body, b"whsec-123", Utc::now());
+//! let event = webhook::verify_event(body, &header, b"whsec-123")?;
+//! assert_eq!(event.key(), "LabReport/lab-1");
+//! assert!(webhook::verify(body, &header, b"another-secret").is_err());
+//! # Ok::<(), webhook::WebhookError>(())
+//! ```
+
+use std::fmt;
+
+use chrono::{DateTime, Duration, Utc};
+use hmac::{Hmac, KeyInit, Mac};
+use sha2::Sha256;
+
+use crate::error::WellAllyError;
+use crate::events::RecordEvent;
+
+/// Name of the HTTP header carrying the signature
+pub const SIGNATURE_HEADER: &str = "WellAlly-Signature";
+
+/// How far a delivery's timestamp may be from now
+pub const DEFAULT_TOLERANCE: Duration = Duration::minutes(5);
+
+/// Errors raised while verifying a webhook delivery.
+#[derive(Debug)]
+pub enum WebhookError {
+    /// The signature header is missing its timestamp or signatures
+    MalformedHeader(String),
+    /// The delivery was signed too long ago or too far in the future
+    Expired {
+        /// Timestamp of the delivery
+        signed_at: DateTime<Utc>,
+    },
+    /// No signature matches the payload and secret
+    SignatureMismatch,
+    /// The verified payload is not a valid event
+    Event(WellAllyError),
+}
+
+impl fmt::Display for WebhookError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WebhookError::MalformedHeader(message) => write!(f, "malformed signature header: {}", message),
+            WebhookError::Expired { signed_at } => {
+                write!(f, "signature timestamp {} is outside the tolerance", signed_at)
+            }
+            WebhookError::SignatureMismatch => write!(f, "signature does not match the payload"),
+            WebhookError::Event(err) => write!(f, "invalid event: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for WebhookError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            WebhookError::Event(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<WellAllyError> for WebhookError {
+    fn from(err: WellAllyError) -> Self {
+        WebhookError::Event(err)
+    }
+}
+
+/// Checks deliveries signed with one secret.
+#[derive(Clone)]
+pub struct Verifier {
+    secret: Vec<u8>,
+    tolerance: Duration,
+}
+
+impl fmt::Debug for Verifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Verifier").field("tolerance", &self.tolerance).finish_non_exhaustive()
+    }
+}
+
+impl Verifier {
+    /// Verifier of deliveries signed with `secret`, with the default tolerance.
+    pub fn new(secret: impl Into<Vec<u8>>) -> Self {
+        Verifier { secret: secret.into(), tolerance: DEFAULT_TOLERANCE }
+    }
+
+    /// Sets how far a delivery's timestamp may be from now.
+    pub fn with_tolerance(mut self, tolerance: Duration) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Checks `payload` against `signature_header` at the current time.
+    pub fn verify(&self, payload: &[u8], signature_header: &str) -> Result<(), WebhookError> {
+        self.verify_at(payload, signature_header, Utc::now())
+    }
+
+    /// Checks `payload` against `signature_header` as of `now`.
+    pub fn verify_at(&self, payload: &[u8], signature_header: &str, now: DateTime<Utc>) -> Result<(), WebhookError> {
+        let (timestamp, signatures) = parse_header(signature_header)?;
+        let signed_at = DateTime::from_timestamp(timestamp, 0)
+            .ok_or_else(|| WebhookError::MalformedHeader(format!("timestamp {} is out of range", timestamp)))?;
+        if (now - signed_at).abs() > self.tolerance {
+            return Err(WebhookError::Expired { signed_at });
+        }
+        let mac = mac(&self.secret, timestamp, payload);
+        if signatures.iter().any(|signature| mac.clone().verify_slice(signature).is_ok()) {
+            Ok(())
+        } else {
+            Err(WebhookError::SignatureMismatch)
+        }
+    }
+
+    /// Checks `payload` and parses it as a [`RecordEvent`].
+    pub fn verify_event(&self, payload: &[u8], signature_header: &str) -> Result<RecordEvent, WebhookError> {
+        self.verify(payload, signature_header)?;
+        let json = std::str::from_utf8(payload).map_err(|err| {
+            WebhookError::Event(WellAllyError::Parse {
+                resource_type: "RecordEvent".to_string(),
+                path: String::new(),
+                value: None,
+                message: err.to_string(),
+            })
+        })?;
+        Ok(RecordEvent::from_json(json)?)
+    }
+}
+
+/// Checks `payload` against `signature_header` with the default tolerance.
+pub fn verify(payload: &[u8], signature_header: &str, secret: &[u8]) -> Result<(), WebhookError> {
+    Verifier::new(secret).verify(payload, signature_header)
+}
+
+/// Checks `payload` and parses it as a [`RecordEvent`].
+pub fn verify_event(payload: &[u8], signature_header: &str, secret: &[u8]) -> Result<RecordEvent, WebhookError> {
+    Verifier::new(secret).verify_event(payload, signature_header)
+}
+
+/// Signature header for `payload` signed with `secret` at `signed_at`, for
+/// senders and tests.
+pub fn sign(payload: &[u8], secret: &[u8], signed_at: DateTime<Utc>) -> String {
+    let timestamp = signed_at.timestamp();
+    let digest = mac(secret, timestamp, payload).finalize().into_bytes();
+    let hex: String = digest.iter().map(|byte| format!("{:02x}", byte)).collect();
+    format!("t={},v1={}", timestamp, hex)
+}
+
+fn mac(secret: &[u8], timestamp: i64, payload: &[u8]) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("HMAC accepts keys of any length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(payload);
+    mac
+}
+
+/// Timestamp and decoded `v1` signatures of a header; other schemes are ignored.
+fn parse_header(header: &str) -> Result<(i64, Vec<Vec<u8>>), WebhookError> {
+    let mut timestamp = None;
+    let mut signatures = Vec::new();
+    for part in header.split(',') {
+        match part.trim().split_once('=') {
+            Some(("t", value)) => {
+                let invalid = || WebhookError::MalformedHeader(format!("bad timestamp '{}'", value));
+                timestamp = Some(value.parse().map_err(|_| invalid())?);
+            }
+            Some(("v1", value)) => {
+                let invalid = || WebhookError::MalformedHeader("bad v1 signature".into());
+                signatures.push(decode_hex(value).ok_or_else(invalid)?);
+            }
+            _ => {}
+        }
+    }
+    let timestamp = timestamp.ok_or_else(|| WebhookError::MalformedHeader("missing timestamp".into()))?;
+    if signatures.is_empty() {
+        return Err(WebhookError::MalformedHeader("missing v1 signature".into()));
+    }
+    Ok((timestamp, signatures))
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) || !hex.is_ascii() {
+        return None;
+    }
+    (0..hex.len()).step_by(2).map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok()).collect()
+}
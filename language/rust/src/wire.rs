@@ -0,0 +1,242 @@
+//! Compact "wire" JSON profile for mobile sync.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//!
+//! The wire profile is the canonical camelCase JSON with every known field
+//! name replaced by a one- or two-letter alias, enumerated values (gender,
+//! interpretation, relation, name/contact use) and well-known coding systems
+//! replaced by small integers. The mapping is a fixed table, so conversion is
+//! lossless in both directions; unknown field names and values pass through
+//! unchanged. Codes are part of the wire contract: new entries may only be
+//! appended.
+//!
+//! ```
+//! use wellally::wire::Wire;
+//! use wellally::LabReport;
+//!
+//! let json = r#"{"id":"lab-1","patientId":"p-1","issuedAt":"2024-11-02T09:30:00Z","results":[]}"#;
+//! let report: LabReport = serde_json::from_str(json)?;
+//! let wire = serde_json::to_string(&Wire(&report))?;
+//! assert_eq!(wire, r#"{"i":"lab-1","ia":"2024-11-02T09:30:00Z","p":"p-1","r":[]}"#);
+//! let Wire(back): Wire<LabReport> = serde_json::from_str(&wire)?;
+//! assert_eq!(back, report);
+//! # Ok::<(), serde_json::Error>(())
+//! ```
+
+use alloc::string::{String, ToString};
+use serde::de::{DeserializeOwned, Error as _};
+use serde::ser::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{Map, Value};
+
+use crate::catalog;
+use crate::error::{self, type_name, WellAllyError};
+
+/// Canonical field names and their wire aliases.
+const FIELDS: &[(&str, &str)] = &[
+    ("id", "i"),
+    ("resourceType", "rt"),
+    ("patientId", "p"),
+    ("issuedAt", "ia"),
+    ("results", "r"),
+    ("facility", "f"),
+    ("panel", "pn"),
+    ("specimen", "sp"),
+    ("code", "c"),
+    ("system", "s"),
+    ("display", "d"),
+    ("coding", "cd"),
+    ("text", "t"),
+    ("value", "v"),
+    ("unit", "u"),
+    ("low", "lo"),
+    ("high", "hi"),
+    ("referenceRange", "rr"),
+    ("interpretation", "in"),
+    ("method", "m"),
+    ("type", "ty"),
+    ("collectedAt", "ca"),
+    ("name", "n"),
+    ("period", "pe"),
+    ("family", "fa"),
+    ("given", "g"),
+    ("use", "us"),
+    ("prefix", "px"),
+    ("suffix", "sf"),
+    ("line", "ln"),
+    ("city", "ci"),
+    ("state", "st"),
+    ("postalCode", "pc"),
+    ("country", "co"),
+    ("start", "sa"),
+    ("end", "en"),
+    ("role", "ro"),
+    ("ctdiVol_mGy", "cv"),
+    ("dlp_mGy_cm", "dl"),
+    ("url", "ur"),
+    ("modality", "mo"),
+    ("bodySite", "bs"),
+    ("reportedAt", "ra"),
+    ("studyInstanceUid", "su"),
+    ("performer", "pf"),
+    ("findings", "fi"),
+    ("impression", "im"),
+    ("radiationDose", "rd"),
+    ("attachments", "at"),
+    ("medication", "md"),
+    ("dosage", "do"),
+    ("route", "ru"),
+    ("startDate", "sd"),
+    ("form", "fo"),
+    ("frequency", "fq"),
+    ("durationDays", "dd"),
+    ("endDate", "ed"),
+    ("indication", "ic"),
+    ("instructions", "is"),
+    ("conditions", "cn"),
+    ("allergies", "al"),
+    ("bloodType", "bt"),
+    ("primaryCareProvider", "pp"),
+    ("birthDate", "bd"),
+    ("identifier", "ix"),
+    ("gender", "ge"),
+    ("telecom", "tc"),
+    ("address", "ad"),
+    ("maritalStatus", "ms"),
+    ("language", "la"),
+    ("clinicalSummary", "cs"),
+    ("relationToProband", "rl"),
+    ("sex", "se"),
+    ("birthYear", "by"),
+    ("deceased", "de"),
+    ("probandId", "pi"),
+    ("members", "mb"),
+    ("at", "ta"),
+];
+
+const ADMINISTRATIVE_GENDER: &[&str] = &["male", "female", "other", "unknown"];
+
+/// Enumerated values by canonical field name; the index is the wire code.
+const VALUES: &[(&str, &[&str])] = &[
+    (
+        "interpretation",
+        &[
+            "N", "L", "H", "A", "LL", "HH", "AA", "LU", "HU", "<", ">", "B", "W", "U", "D", "S", "R", "I",
+            "MS", "VS", "NS", "SDD", "SYN-R", "SYN-S", "NCL", "POS", "NEG", "IND", "E", "DET", "ND", "RR",
+            "WR", "NR", "EXP", "UNE", "EX", "HX", "LX", "CAR", "IE",
+        ],
+    ),
+    ("gender", ADMINISTRATIVE_GENDER),
+    ("sex", ADMINISTRATIVE_GENDER),
+    (
+        "relationToProband",
+        &[
+            "self", "mother", "father", "sibling", "child", "grandparent", "grandchild", "aunt", "uncle", "cousin",
+            "other",
+        ],
+    ),
+    ("use", &["official", "usual", "nickname", "anonymous", "old", "maiden", "home", "work", "mobile"]),
+    (
+        "system",
+        &[
+            "phone",
+            "email",
+            catalog::LOINC,
+            catalog::SNOMED_CT,
+            catalog::RXNORM,
+            catalog::DICOM,
+            catalog::SPECIMEN_TYPE,
+        ],
+    ),
+];
+
+fn short_name(field: &str) -> Option<&'static str> {
+    FIELDS.iter().find(|(name, _)| *name == field).map(|(_, short)| *short)
+}
+
+fn long_name(short: &str) -> Option<&'static str> {
+    FIELDS.iter().find(|(_, alias)| *alias == short).map(|(name, _)| *name)
+}
+
+fn values(field: &str) -> Option<&'static [&'static str]> {
+    VALUES.iter().find(|(name, _)| *name == field).map(|(_, values)| *values)
+}
+
+/// Converts canonical JSON into the wire profile.
+pub fn encode(value: &Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, value)| {
+                    let value = match (values(key), value) {
+                        (Some(values), Value::String(text)) => match values.iter().position(|v| v == text) {
+                            Some(code) => Value::from(code),
+                            None => value.clone(),
+                        },
+                        _ => encode(value),
+                    };
+                    (short_name(key).map(String::from).unwrap_or_else(|| key.clone()), value)
+                })
+                .collect::<Map<_, _>>(),
+        ),
+        Value::Array(items) => Value::Array(items.iter().map(encode).collect()),
+        _ => value.clone(),
+    }
+}
+
+/// Converts wire-profile JSON back into canonical JSON.
+pub fn decode(value: &Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, value)| {
+                    let key = long_name(key).map(String::from).unwrap_or_else(|| key.clone());
+                    let value = match (values(&key), value.as_u64()) {
+                        (Some(values), Some(code)) => match values.get(code as usize) {
+                            Some(text) => Value::from(*text),
+                            None => value.clone(),
+                        },
+                        _ => decode(value),
+                    };
+                    (key, value)
+                })
+                .collect::<Map<_, _>>(),
+        ),
+        Value::Array(items) => Value::Array(items.iter().map(decode).collect()),
+        _ => value.clone(),
+    }
+}
+
+/// Serializes a value in the wire profile.
+pub fn to_wire<T: Serialize + ?Sized>(value: &T) -> Result<Value, WellAllyError> {
+    let canonical = serde_json::to_value(value).map_err(|err| WellAllyError::Conversion {
+        resource_type: type_name::<T>(),
+        path: String::new(),
+        value: None,
+        message: err.to_string(),
+    })?;
+    Ok(encode(&canonical))
+}
+
+/// Deserializes a value from the wire profile; error paths use canonical field names.
+pub fn from_wire<T: DeserializeOwned>(value: &Value) -> Result<T, WellAllyError> {
+    error::from_value(&type_name::<T>(), &decode(value))
+}
+
+/// Wrapper that (de)serializes its content in the wire profile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Wire<T>(pub T);
+
+impl<T: Serialize> Serialize for Wire<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        to_wire(&self.0).map_err(S::Error::custom)?.serialize(serializer)
+    }
+}
+
+impl<'de, T: DeserializeOwned> Deserialize<'de> for Wire<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = Value::deserialize(deserializer)?;
+        from_wire(&value).map(Wire).map_err(D::Error::custom)
+    }
+}
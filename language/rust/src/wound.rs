@@ -0,0 +1,350 @@
+//! Wound care documentation model.
+//!
+//! Package: wellally
+//! Website: https://www.wellally.tech/
+//! Schema: https://wellall.health/schemas/wound/v0.1.0
+//!
+//! A [`WoundAssessment`] records one look at a wound: where it is, its
+//! length, width and depth, the NPIAP stage of a pressure injury, the
+//! exudate, the share of each tissue type in the wound bed, photographs and
+//! the dressing plan. Assessments of the same wound share a `woundId`, so
+//! [`area_series`] and [`percent_area_reduction`] can follow it over time.
+//!
+//! ```
+//! use wellally::wound::{self, WoundAssessment, WoundDimensions};
+//! use wellally::{Coding, Quantity};
+//!
+//! let heel = Coding { system: "http://snomed.info/sct".into(), code: "76853006".into(), display: None };
+//! let mut first = WoundAssessment::new("wa-1", "p-1", "wound-1", heel.clone(), "2024-09-01".parse().unwrap());
+//! first.dimensions = Some(WoundDimensions::new(Quantity::new(4.0, "cm"), Quantity::new(3.0, "cm")));
+//! let mut later = WoundAssessment::new("wa-2", "p-1", "wound-1", heel, "2024-09-29".parse().unwrap());
+//! later.dimensions = Some(WoundDimensions::new(Quantity::new(30.0, "mm"), Quantity::new(2.0, "cm")));
+//! assert_eq!(later.area_cm2(), Some(6.0));
+//! assert_eq!(wound::percent_area_reduction(&[first, later], "wound-1"), Some(50.0));
+//! ```
+
+use alloc::{string::String, vec::Vec};
+
+use serde::{Deserialize, Serialize};
+
+use crate::common::{Annotation, CodeableConcept, Coding, DataSource, HasDataSource, Quantity, Reference};
+use crate::datetime::FlexibleDateTime;
+use crate::extension::Extension;
+use crate::imaging_report::Attachment;
+use crate::ucum;
+
+/// Length, width and depth of a wound.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "WoundDimensionsInput")
+)]
+pub struct WoundDimensions {
+    /// Longest head-to-toe extent
+    pub length: Quantity,
+    /// Widest extent perpendicular to the length
+    pub width: Quantity,
+    /// Deepest point of the wound bed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub depth: Option<Quantity>,
+}
+
+impl WoundDimensions {
+    /// Dimensions of a wound whose depth was not measured.
+    pub fn new(length: Quantity, width: Quantity) -> Self {
+        WoundDimensions { length, width, depth: None }
+    }
+
+    /// Length × width in cm²; `None` unless both are lengths.
+    pub fn area_cm2(&self) -> Option<f64> {
+        Some(centimetres(&self.length)? * centimetres(&self.width)?)
+    }
+
+    /// Length × width × depth in cm³; `None` without a depth.
+    pub fn volume_cm3(&self) -> Option<f64> {
+        Some(self.area_cm2()? * centimetres(self.depth.as_ref()?)?)
+    }
+}
+
+fn centimetres(quantity: &Quantity) -> Option<f64> {
+    ucum::convert(quantity.value, &quantity.unit, "cm").ok()
+}
+
+/// NPIAP pressure injury stage.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "async-graphql", derive(async_graphql::Enum))]
+#[serde(rename_all = "kebab-case")]
+pub enum PressureInjuryStage {
+    /// Non-blanchable erythema of intact skin
+    #[serde(rename = "stage-1")]
+    Stage1,
+    /// Partial-thickness skin loss with exposed dermis
+    #[serde(rename = "stage-2")]
+    Stage2,
+    /// Full-thickness skin loss
+    #[serde(rename = "stage-3")]
+    Stage3,
+    /// Full-thickness skin and tissue loss
+    #[serde(rename = "stage-4")]
+    Stage4,
+    /// Full-thickness loss obscured by slough or eschar
+    Unstageable,
+    /// Persistent non-blanchable deep red, maroon or purple discoloration
+    DeepTissue,
+}
+
+/// How much a wound drains.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "async-graphql", derive(async_graphql::Enum))]
+#[serde(rename_all = "kebab-case")]
+pub enum ExudateAmount {
+    None,
+    Scant,
+    Small,
+    Moderate,
+    Large,
+}
+
+/// What a wound drains.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "async-graphql", derive(async_graphql::Enum))]
+#[serde(rename_all = "kebab-case")]
+pub enum ExudateType {
+    Serous,
+    Sanguineous,
+    Serosanguineous,
+    Purulent,
+}
+
+/// Drainage seen at an assessment.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "ExudateInput")
+)]
+pub struct Exudate {
+    pub amount: ExudateAmount,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "async-graphql", graphql(name = "type"))]
+    pub exudate_type: Option<ExudateType>,
+}
+
+/// Tissue in the wound bed.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "async-graphql", derive(async_graphql::Enum))]
+#[serde(rename_all = "kebab-case")]
+pub enum TissueType {
+    Epithelial,
+    Granulation,
+    Slough,
+    Eschar,
+    Necrotic,
+}
+
+/// Share of the wound bed covered by one tissue type.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "TissueProportionInput")
+)]
+pub struct TissueProportion {
+    pub tissue: TissueType,
+    /// Percentage of the wound bed, 0–100
+    pub percent: f64,
+}
+
+/// Dressings and how often to change them.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "DressingPlanInput")
+)]
+pub struct DressingPlan {
+    /// Primary and secondary dressings (e.g., SNOMED CT alginate dressing, foam dressing)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dressings: Vec<CodeableConcept>,
+    /// Days between dressing changes
+    #[serde(rename = "changeEveryDays", alias = "change_every_days", skip_serializing_if = "Option::is_none")]
+    pub change_every_days: Option<u32>,
+    /// Cleansing, offloading and other instructions for the next visit
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instructions: Option<String>,
+}
+
+/// One assessment of a wound.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "async-graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject),
+    graphql(input_name = "WoundAssessmentInput")
+)]
+pub struct WoundAssessment {
+    /// Unique assessment identifier
+    pub id: String,
+    /// Reference to Person.id
+    #[serde(rename = "patientId", alias = "patient_id")]
+    pub patient_id: String,
+    /// Identifier of the wound, shared by all its assessments
+    #[serde(rename = "woundId", alias = "wound_id")]
+    pub wound_id: String,
+    /// Etiology (e.g., SNOMED CT pressure injury, diabetic foot ulcer, surgical wound)
+    #[serde(rename = "woundType", alias = "wound_type", skip_serializing_if = "Option::is_none")]
+    pub wound_type: Option<CodeableConcept>,
+    /// Body site of the wound (e.g., SNOMED CT left heel)
+    pub location: Coding,
+    /// When the wound was assessed
+    #[serde(rename = "assessedAt", alias = "assessed_at")]
+    #[cfg_attr(feature = "utoipa", schema(value_type = String))]
+    pub assessed_at: FlexibleDateTime,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dimensions: Option<WoundDimensions>,
+    /// Stage, for pressure injuries
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stage: Option<PressureInjuryStage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exudate: Option<Exudate>,
+    /// Tissue types in the wound bed; the percentages add up to at most 100
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tissue: Vec<TissueProportion>,
+    /// Photographs of the wound
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub photographs: Option<Vec<Attachment>>,
+    #[serde(rename = "dressingPlan", alias = "dressing_plan", skip_serializing_if = "Option::is_none")]
+    pub dressing_plan: Option<DressingPlan>,
+    /// Nurse or clinician who assessed the wound (e.g., "Practitioner/rn-li")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub assessor: Option<Reference>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notes: Option<Vec<Annotation>>,
+    /// Device, app and capture method of the record
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<DataSource>,
+    /// Schema version the resource was written against (e.g., "0.1.0"); absent means 0.1.0
+    #[serde(rename = "schemaVersion", alias = "schema_version", skip_serializing_if = "Option::is_none")]
+    pub schema_version: Option<String>,
+    /// Confidentiality and sensitivity labels (e.g., v3 ActCode HIV); see [`crate::security`]
+    #[serde(rename = "securityLabels", alias = "security_labels", skip_serializing_if = "Option::is_none")]
+    pub security_labels: Option<Vec<Coding>>,
+    /// Site-specific extensions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extensions: Option<Vec<Extension>>,
+    /// Properties this model version does not define, kept for re-serialization
+    #[cfg(feature = "preserve-unknown")]
+    #[serde(flatten)]
+    #[cfg_attr(feature = "schemars", schemars(skip))]
+    #[cfg_attr(feature = "utoipa", schema(ignore))]
+    #[cfg_attr(feature = "async-graphql", graphql(skip))]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl WoundAssessment {
+    /// Assessment recording only the wound and where it is.
+    pub fn new(
+        id: impl Into<String>,
+        patient_id: impl Into<String>,
+        wound_id: impl Into<String>,
+        location: Coding,
+        assessed_at: FlexibleDateTime,
+    ) -> Self {
+        WoundAssessment {
+            id: id.into(),
+            patient_id: patient_id.into(),
+            wound_id: wound_id.into(),
+            wound_type: None,
+            location,
+            assessed_at,
+            dimensions: None,
+            stage: None,
+            exudate: None,
+            tissue: Vec::new(),
+            photographs: None,
+            dressing_plan: None,
+            assessor: None,
+            notes: None,
+            source: None,
+            schema_version: None,
+            security_labels: None,
+            extensions: None,
+            #[cfg(feature = "preserve-unknown")]
+            extra: Default::default(),
+        }
+    }
+
+    /// Wound area in cm², as length × width.
+    pub fn area_cm2(&self) -> Option<f64> {
+        self.dimensions.as_ref()?.area_cm2()
+    }
+
+    /// Percentage of the wound bed covered by `tissue`; 0 when not recorded.
+    pub fn tissue_percent(&self, tissue: TissueType) -> f64 {
+        self.tissue.iter().filter(|proportion| proportion.tissue == tissue).map(|proportion| proportion.percent).sum()
+    }
+
+    /// Whether slough, eschar or necrotic tissue is in the wound bed.
+    pub fn has_devitalized_tissue(&self) -> bool {
+        self.tissue.iter().any(|proportion| {
+            proportion.percent > 0.0
+                && matches!(proportion.tissue, TissueType::Slough | TissueType::Eschar | TissueType::Necrotic)
+        })
+    }
+}
+
+impl HasDataSource for WoundAssessment {
+    fn data_source(&self) -> Option<&DataSource> {
+        self.source.as_ref()
+    }
+}
+
+/// Area of a wound at one assessment.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AreaPoint<'a> {
+    pub assessment: &'a WoundAssessment,
+    pub area_cm2: f64,
+}
+
+/// Areas of wound `wound_id` across `assessments` that measured it, oldest first.
+pub fn area_series<'a>(assessments: &'a [WoundAssessment], wound_id: &str) -> Vec<AreaPoint<'a>> {
+    let mut points: Vec<AreaPoint<'a>> = assessments
+        .iter()
+        .filter(|assessment| assessment.wound_id == wound_id)
+        .filter_map(|assessment| Some(AreaPoint { assessment, area_cm2: assessment.area_cm2()? }))
+        .collect();
+    points.sort_by_key(|point| point.assessment.assessed_at.to_utc());
+    points
+}
+
+/// Percent area reduction of wound `wound_id` from its first to its latest
+/// measurement; negative when it grew. A reduction under 40–50% after four
+/// weeks predicts a diabetic foot ulcer that will not heal by twelve weeks.
+pub fn percent_area_reduction(assessments: &[WoundAssessment], wound_id: &str) -> Option<f64> {
+    let series = area_series(assessments, wound_id);
+    let [first, .., latest] = series.as_slice() else {
+        return None;
+    };
+    if first.area_cm2 <= 0.0 {
+        return None;
+    }
+    Some((first.area_cm2 - latest.area_cm2) / first.area_cm2 * 100.0)
+}
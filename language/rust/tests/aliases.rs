@@ -0,0 +1,106 @@
+//! Tests for the snake_case input aliases of renamed fields.
+
+use std::fs;
+use std::path::PathBuf;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use wellally::borrowed::LabReportRef;
+use wellally::{FamilyHealthTree, ImagingReport, LabReport, MedicationRecord, Person};
+
+/// Camel-case fields renamed on the wire and the snake_case alias each accepts.
+const ALIASES: &[(&str, &str)] = &[
+    ("patientId", "patient_id"),
+    ("issuedAt", "issued_at"),
+    ("collectedAt", "collected_at"),
+    ("referenceRange", "reference_range"),
+    ("postalCode", "postal_code"),
+    ("relationToProband", "relation_to_proband"),
+    ("birthYear", "birth_year"),
+    ("probandId", "proband_id"),
+    ("bloodType", "blood_type"),
+    ("primaryCareProvider", "primary_care_provider"),
+    ("resourceType", "resource_type"),
+    ("birthDate", "birth_date"),
+    ("maritalStatus", "marital_status"),
+    ("clinicalSummary", "clinical_summary"),
+    ("ctdiVol_mGy", "ctdi_vol_mgy"),
+    ("dlp_mGy_cm", "dlp_mgy_cm"),
+    ("bodySite", "body_site"),
+    ("reportedAt", "reported_at"),
+    ("studyInstanceUid", "study_instance_uid"),
+    ("radiationDose", "radiation_dose"),
+    ("startDate", "start_date"),
+    ("durationDays", "duration_days"),
+    ("endDate", "end_date"),
+];
+
+fn snake_case(value: &Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, value)| {
+                    let alias = ALIASES.iter().find(|(name, _)| name == key).map_or(key.as_str(), |(_, alias)| alias);
+                    (alias.to_string(), snake_case(value))
+                })
+                .collect::<Map<_, _>>(),
+        ),
+        Value::Array(items) => Value::Array(items.iter().map(snake_case).collect()),
+        _ => value.clone(),
+    }
+}
+
+fn examples(schema: &str) -> Vec<Value> {
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../infrastructure/schemas").join(schema);
+    fs::read_dir(dir.join("examples"))
+        .unwrap()
+        .map(|entry| serde_json::from_str(&fs::read_to_string(entry.unwrap().path()).unwrap()).unwrap())
+        .collect()
+}
+
+fn accepts_aliases<T: Serialize + DeserializeOwned + PartialEq + std::fmt::Debug>(schema: &str) {
+    for canonical in examples(schema) {
+        let snake = snake_case(&canonical);
+        assert_ne!(snake, canonical);
+        let expected: T = serde_json::from_value(canonical).unwrap();
+        let parsed: T = serde_json::from_value(snake).unwrap();
+        assert_eq!(parsed, expected);
+        assert_eq!(serde_json::to_value(&parsed).unwrap(), serde_json::to_value(&expected).unwrap());
+    }
+}
+
+#[test]
+fn snake_case_input_parses_like_camel_case() {
+    accepts_aliases::<LabReport>("lab-report");
+    accepts_aliases::<ImagingReport>("imaging-report");
+    accepts_aliases::<MedicationRecord>("medication");
+    accepts_aliases::<Person>("health");
+    accepts_aliases::<FamilyHealthTree>("family-health");
+}
+
+#[test]
+fn output_stays_camel_case() {
+    let snake = snake_case(&examples("health").remove(0));
+    let person: Person = serde_json::from_value(snake).unwrap();
+    let json = serde_json::to_value(&person).unwrap();
+    assert!(json.get("birthDate").is_some() && json.get("birth_date").is_none());
+    assert_eq!(json["clinicalSummary"]["bloodType"], "O+");
+}
+
+#[test]
+fn borrowed_reports_accept_aliases() {
+    for canonical in examples("lab-report") {
+        let text = serde_json::to_string(&snake_case(&canonical)).unwrap();
+        let report: LabReportRef = serde_json::from_str(&text).unwrap();
+        assert_eq!(report.to_owned(), serde_json::from_value::<LabReport>(canonical).unwrap());
+    }
+}
+
+#[test]
+fn stream_validation_detects_snake_case_records() {
+    let person = serde_json::to_string(&snake_case(&examples("health").remove(0))).unwrap();
+    let mut issues = Vec::new();
+    let summary = wellally::validation::stream_validate(person.as_bytes(), |issue| issues.push(issue)).unwrap();
+    assert_eq!((summary.records, summary.invalid), (1, 0), "{:?}", issues);
+}
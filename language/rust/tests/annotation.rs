@@ -0,0 +1,66 @@
+//! Checks notes attached to reports, medications and family members.
+
+use serde_json::json;
+use wellally::{Annotation, FamilyHealthTree, LabReport, Reference, Resource, Validate};
+
+fn report() -> LabReport {
+    LabReport::from_json_value(&json!({
+        "id": "lab-1",
+        "patientId": "patient-1",
+        "issuedAt": "2024-12-03T08:40:00Z",
+        "results": [{
+            "code": {"coding": [{"system": "http://loinc.org", "code": "2093-3", "display": "Cholesterol"}]},
+            "value": {"value": 180, "unit": "mg/dL"}
+        }],
+        "notes": [
+            {
+                "authorReference": {"reference": "Practitioner/gp-1", "display": "Dr. Li"},
+                "time": "2024-12-03T10:00:00Z",
+                "text": "Within target; **recheck in 12 months**."
+            },
+            {"authorString": "Patient", "text": "Fasted for 10 hours."}
+        ]
+    }))
+    .unwrap()
+}
+
+#[test]
+fn notes_round_trip() {
+    let report = report();
+    assert!(report.is_valid(), "{:?}", report.validate());
+    let notes = report.notes.as_ref().unwrap();
+    assert_eq!(notes[0].author(), Some("Dr. Li"));
+    assert_eq!(notes[1].author(), Some("Patient"));
+    assert_eq!(Annotation::new("No author").author(), None);
+
+    let json = serde_json::to_value(&report).unwrap();
+    assert_eq!(json["notes"][0]["authorReference"]["reference"], "Practitioner/gp-1");
+    assert!(json["notes"][1].get("time").is_none());
+    assert_eq!(LabReport::from_json_value(&json).unwrap(), report);
+}
+
+#[test]
+fn invalid_notes_are_reported() {
+    let mut report = report();
+    let notes = report.notes.as_mut().unwrap();
+    notes[0].author_string = Some("Dr. Li".into());
+    notes[1].text = " ".into();
+    let author = Reference { reference: "gp-1".into(), display: None };
+    notes.push(Annotation { author_reference: Some(author), ..Annotation::new("x") });
+    let paths: Vec<_> = report.validate().into_iter().map(|issue| issue.path).collect();
+    assert_eq!(paths, ["notes[0]", "notes[1].text", "notes[2].authorReference.reference"]);
+}
+
+#[test]
+fn family_members_carry_notes() {
+    let tree = FamilyHealthTree::from_json_value(&json!({
+        "probandId": "p-1",
+        "members": [
+            {"id": "p-1", "relationToProband": "self"},
+            {"id": "m-1", "relationToProband": "mother", "notes": [{"text": ""}]}
+        ]
+    }))
+    .unwrap();
+    let paths: Vec<_> = tree.validate().into_iter().map(|issue| issue.path).collect();
+    assert_eq!(paths, ["members[1].notes[0].text"]);
+}
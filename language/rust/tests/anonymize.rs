@@ -0,0 +1,62 @@
+//! Checks pseudonymization of resources.
+
+use serde_json::{json, Value};
+use wellally::anonymize::{anonymize, Anonymizer, ANONYMOUS};
+use wellally::io::AnyResource;
+
+fn resource(json: Value) -> AnyResource {
+    serde_json::from_value(json).unwrap()
+}
+
+fn person() -> AnyResource {
+    resource(json!({
+        "id": "p-1",
+        "resourceType": "Person",
+        "birthDate": "1980-04-12",
+        "gender": "female",
+        "name": [{"family": "Doe", "given": ["Jane"]}],
+        "identifier": [{"system": "urn:oid:2.16.840.1.113883.4.1", "value": "123-45-6789"}],
+        "address": [{"city": "Springfield"}]
+    }))
+}
+
+fn referral() -> AnyResource {
+    resource(json!({
+        "id": "ref-1",
+        "patientId": "p-1",
+        "status": "active",
+        "authoredOn": "2024-11-02T09:30:00Z",
+        "requester": {"organization": {"name": "Clinic A"}},
+        "supportingInfo": [{"reference": "Practitioner/dr-9", "display": "Dr. Who"}]
+    }))
+}
+
+#[test]
+fn strips_direct_identifiers_of_a_person() {
+    let anonymizer = Anonymizer::new("secret");
+    let shared = serde_json::to_value(anonymizer.anonymize(&person()).unwrap()).unwrap();
+    assert_eq!(shared["id"], json!(anonymizer.pseudonym("p-1")));
+    assert_eq!(shared["name"], json!([{"family": ANONYMOUS, "given": []}]));
+    assert_eq!(shared["birthDate"], "1980");
+    assert_eq!(shared["gender"], "female");
+    assert!(shared.get("identifier").is_none());
+    assert!(shared.get("address").is_none());
+}
+
+#[test]
+fn keeps_records_of_a_patient_linked() {
+    let resources = anonymize(&[person(), referral()], "secret").unwrap();
+    assert_eq!(resources[1].patient_id(), resources[0].id());
+    let referral = serde_json::to_value(&resources[1]).unwrap();
+    let pseudonym = Anonymizer::new("secret").pseudonym("dr-9");
+    assert_eq!(referral["supportingInfo"][0]["reference"], format!("Practitioner/{}", pseudonym));
+}
+
+#[test]
+fn pseudonyms_depend_on_the_salt() {
+    let (a, b) = (Anonymizer::new("a"), Anonymizer::new("b"));
+    assert_eq!(a.pseudonym("p-1"), Anonymizer::new("a").pseudonym("p-1"));
+    assert_ne!(a.pseudonym("p-1"), b.pseudonym("p-1"));
+    assert_ne!(a.pseudonym("p-1"), a.pseudonym("p-2"));
+    assert_eq!(a.pseudonym("p-1").len(), 16);
+}
@@ -0,0 +1,109 @@
+//! Checks inline attachment content, hashing and size limits.
+
+use std::cell::Cell;
+
+use serde_json::json;
+use wellally::{Attachment, AttachmentFetcher, AttachmentLimits, ImagingReport, InlineError, Resource, Validate};
+
+struct Fixed {
+    content: Vec<u8>,
+    calls: Cell<usize>,
+}
+
+impl AttachmentFetcher for Fixed {
+    type Error = String;
+
+    fn fetch(&self, url: &str, _max_bytes: u64) -> Result<Vec<u8>, String> {
+        self.calls.set(self.calls.get() + 1);
+        if url.starts_with("https://") {
+            Ok(self.content.clone())
+        } else {
+            Err(format!("unsupported url {url}"))
+        }
+    }
+}
+
+fn fetcher(content: &[u8]) -> Fixed {
+    Fixed { content: content.to_vec(), calls: Cell::new(0) }
+}
+
+#[test]
+fn inline_fills_size_and_hash() {
+    let attachment = Attachment::inline("text/plain", b"hello world");
+    assert_eq!(attachment.data.as_deref(), Some("aGVsbG8gd29ybGQ="));
+    assert_eq!(attachment.hash.as_deref(), Some("Kq5sNclPz7QV2+lfQIuc6R7oRu0="));
+    assert_eq!(attachment.size, Some(11));
+    assert_eq!(attachment.content().unwrap().as_deref(), Some(&b"hello world"[..]));
+    assert_eq!(attachment.verify_hash(), Some(true));
+    assert!(AttachmentLimits::default().check(&attachment).is_empty());
+
+    let url_only = Attachment::from_url("https://example.org/a.pdf", "application/pdf");
+    assert_eq!(url_only.content().unwrap(), None);
+    assert_eq!(url_only.verify_hash(), None);
+}
+
+#[test]
+fn check_reports_bad_content() {
+    let mut attachment = Attachment::inline("text/plain", b"hello world");
+    attachment.size = Some(12);
+    attachment.hash = Some("AAAAAAAAAAAAAAAAAAAAAAAAAAA=".into());
+    let paths: Vec<_> = AttachmentLimits::default().check(&attachment).into_iter().map(|issue| issue.path).collect();
+    assert_eq!(paths, ["size", "hash"]);
+
+    assert_eq!(AttachmentLimits::new(4).check(&Attachment::inline("text/plain", b"hello"))[0].path, "data");
+
+    let garbled = Attachment { data: Some("not base64!".into()), ..Attachment::default() };
+    assert!(garbled.content().is_err());
+    assert_eq!(AttachmentLimits::default().check(&garbled)[0].message, "must be base64 encoded");
+
+    let short_hash = Attachment { hash: Some("AAAA".into()), ..Attachment::default() };
+    assert_eq!(AttachmentLimits::default().check(&short_hash)[0].message, "must be a base64 SHA-1 digest");
+}
+
+#[test]
+fn inline_with_fetches_within_limits() {
+    let source = fetcher(b"%PDF-1.7");
+    let mut attachment = Attachment::from_url("https://example.org/a.pdf", "application/pdf");
+    attachment.inline_with(&source, &AttachmentLimits::default()).unwrap();
+    assert_eq!(attachment.size, Some(8));
+    assert_eq!(attachment.verify_hash(), Some(true));
+    assert_eq!(attachment.url.as_deref(), Some("https://example.org/a.pdf"));
+
+    let mut declared = Attachment { size: Some(1_000), ..Attachment::from_url("https://example.org/b.pdf", "application/pdf") };
+    let source = fetcher(b"%PDF-1.7");
+    assert_eq!(declared.inline_with(&source, &AttachmentLimits::new(100)), Err(InlineError::TooLarge { size: 1_000, limit: 100 }));
+    assert_eq!(source.calls.get(), 0);
+
+    let mut fetched = Attachment::from_url("https://example.org/c.pdf", "application/pdf");
+    assert_eq!(fetched.inline_with(&fetcher(b"%PDF-1.7"), &AttachmentLimits::new(4)), Err(InlineError::TooLarge { size: 8, limit: 4 }));
+    assert_eq!(fetched.data, None);
+
+    let mut tampered = Attachment { hash: Some("Kq5sNclPz7QV2+lfQIuc6R7oRu0=".into()), ..Attachment::from_url("https://example.org/d", "text/plain") };
+    assert_eq!(tampered.inline_with(&fetcher(b"changed"), &AttachmentLimits::default()), Err(InlineError::HashMismatch));
+
+    let mut local = Attachment::from_url("file:///tmp/a.pdf", "application/pdf");
+    assert_eq!(
+        local.inline_with(&fetcher(b""), &AttachmentLimits::default()),
+        Err(InlineError::Fetch("unsupported url file:///tmp/a.pdf".into()))
+    );
+    assert_eq!(Attachment::default().inline_with(&fetcher(b""), &AttachmentLimits::default()), Err(InlineError::NoUrl));
+}
+
+#[test]
+fn report_validation_checks_attachments() {
+    let report = ImagingReport::from_json_value(&json!({
+        "id": "img-1",
+        "patientId": "p-1",
+        "modality": {"system": "http://dicom.nema.org/resources/ontology/DCM", "code": "CT"},
+        "bodySite": {"system": "http://snomed.info/sct", "code": "51185008"},
+        "reportedAt": "2024-11-02T09:30:00Z",
+        "attachments": [
+            {"url": "https://example.org/a.pdf", "type": "application/pdf", "title": "Report", "creation": "2024-11-02T09:00:00Z"},
+            {"type": "text/plain", "data": "aGVsbG8gd29ybGQ=", "size": 3}
+        ]
+    }))
+    .unwrap();
+    let issues = report.validate();
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues[0].path, "attachments[1].size");
+}
@@ -0,0 +1,107 @@
+//! Checks audiograms and hearing screening.
+
+use serde_json::json;
+use wellally::audiology::{
+    self, AudiogramReport, AudiometryPurpose, Conduction, Ear, EarResult, HearingGrade, Threshold, TympanogramType,
+};
+use wellally::{Resource, Validate};
+
+mod common;
+
+const EXAMPLE: &str = include_str!("../../../infrastructure/schemas/audiology/examples/audiology.occupational.json");
+
+fn audiogram(id: &str, left: &[(u32, f64)], right: &[(u32, f64)]) -> AudiogramReport {
+    let mut report = AudiogramReport::new(id, "p-1", "2024-10-08".parse().unwrap());
+    for (ear, levels) in [(Ear::Left, left), (Ear::Right, right)] {
+        let mut result = EarResult::new(ear);
+        result.thresholds = levels.iter().map(|(frequency, level)| Threshold::air(*frequency, *level)).collect();
+        report.ears.push(result);
+    }
+    report
+}
+
+#[test]
+fn published_example_round_trips() {
+    let (report, written) = common::round_trip::<AudiogramReport>(EXAMPLE);
+    assert_eq!(report.purpose, Some(AudiometryPurpose::OccupationalAnnual));
+    let left = report.ear(Ear::Left).unwrap();
+    assert_eq!(left.threshold(4000, Conduction::Bone).unwrap().level_db, 40.0);
+    assert!(left.threshold(4000, Conduction::Bone).unwrap().masked);
+    assert_eq!(left.tympanometry, Some(TympanogramType::A));
+    assert_eq!(written["ears"][0]["thresholds"][0].get("conduction"), None);
+    assert_eq!(written["ears"][0]["thresholds"][7]["conduction"], "bone");
+}
+
+#[test]
+fn grades_each_ear_from_the_pure_tone_average() {
+    let report = AudiogramReport::from_json(EXAMPLE).unwrap();
+    assert_eq!(report.ear(Ear::Left).unwrap().pure_tone_average(), Some(20.0));
+    assert_eq!(report.grade(Ear::Left), Some(HearingGrade::Mild));
+    assert_eq!(report.grade(Ear::Right), Some(HearingGrade::Normal));
+    assert_eq!(report.ear(Ear::Left).unwrap().air_bone_gap(4000), Some(5.0));
+    assert_eq!(report.ear(Ear::Right).unwrap().air_bone_gap(4000), None);
+
+    let partial = audiogram("aud-2", &[(1000, 40.0)], &[]);
+    assert_eq!(partial.grade(Ear::Left), None);
+    assert_eq!(HearingGrade::who(34.9), HearingGrade::Mild);
+    assert_eq!(HearingGrade::who(95.0), HearingGrade::Complete);
+}
+
+#[test]
+fn screens_at_a_fixed_level() {
+    let child = audiogram("aud-3", &[(1000, 15.0), (2000, 20.0), (4000, 20.0)], &[(1000, 10.0), (2000, 15.0)]);
+    assert!(child.passes_screening(20.0));
+    assert!(!child.passes_screening(15.0));
+
+    let mut unheard = child.clone();
+    unheard.ears[1].thresholds[0].no_response = true;
+    assert!(!unheard.passes_screening(20.0));
+    assert!(!AudiogramReport::new("aud-4", "p-1", "2024-10-08".parse().unwrap()).passes_screening(20.0));
+}
+
+#[test]
+fn finds_standard_threshold_shifts_against_the_baseline() {
+    let annual = AudiogramReport::from_json(EXAMPLE).unwrap();
+    let baseline = audiogram(
+        "aud-0",
+        &[(2000, 5.0), (3000, 10.0), (4000, 15.0)],
+        &[(2000, 10.0), (3000, 15.0), (4000, 25.0)],
+    );
+    assert_eq!(audiology::standard_threshold_shift(&baseline, &annual), [Ear::Left]);
+    assert!(audiology::standard_threshold_shift(&annual, &annual).is_empty());
+
+    let incomplete = audiogram("aud-1", &[(2000, 5.0)], &[]);
+    assert!(audiology::standard_threshold_shift(&incomplete, &annual).is_empty());
+}
+
+#[test]
+fn validation_reports_implausible_audiograms() {
+    let report: AudiogramReport = serde_json::from_value(json!({
+        "id": "aud-1", "patientId": "p-1", "testedAt": "2024-10-08",
+        "ears": [
+            {"ear": "left", "thresholds": [
+                {"frequencyHz": 1000, "levelDb": 20},
+                {"frequencyHz": 1000, "levelDb": 25},
+                {"frequencyHz": 1000, "levelDb": 15, "conduction": "bone"},
+                {"frequencyHz": 50, "levelDb": 150}
+            ]},
+            {"ear": "left", "speech": {"scorePercent": 104}}
+        ]
+    }))
+    .unwrap();
+    let paths: Vec<_> = report.validate().into_iter().map(|issue| issue.path).collect();
+    assert_eq!(
+        paths,
+        [
+            "ears[0].thresholds[1].frequencyHz",
+            "ears[0].thresholds[3].frequencyHz",
+            "ears[0].thresholds[3].levelDb",
+            "ears[1].ear",
+            "ears[1].speech.scorePercent",
+        ]
+    );
+
+    let empty = AudiogramReport::new("aud-2", "p-1", "2024-10-08".parse().unwrap());
+    let paths: Vec<_> = empty.validate().into_iter().map(|issue| issue.path).collect();
+    assert_eq!(paths, ["ears"]);
+}
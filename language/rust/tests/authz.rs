@@ -0,0 +1,102 @@
+//! Tests for SMART scope parsing and enforcement.
+
+use serde_json::json;
+use wellally::authz::{Action, Authorization, Context, Scope};
+use wellally::{LabReport, Resource};
+
+fn report(patient_id: &str) -> LabReport {
+    LabReport::from_json_value(&json!({
+        "id": "lab-1",
+        "patientId": patient_id,
+        "issuedAt": "2024-11-02T09:30:00Z",
+        "results": []
+    }))
+    .unwrap()
+}
+
+fn scope(source: &str) -> Scope {
+    source.parse().unwrap()
+}
+
+#[test]
+fn parses_v1_and_v2_actions() {
+    let read = scope("patient/LabReport.read");
+    assert_eq!(read.context(), Context::Patient);
+    assert_eq!(read.resource_type(), Some("LabReport"));
+    assert!(read.allows(Action::Read) && read.allows(Action::Search));
+    assert!(!read.allows(Action::Create));
+
+    let write = scope("user/*.write");
+    assert_eq!(write.resource_type(), None);
+    assert!([Action::Create, Action::Update, Action::Delete].iter().all(|action| write.allows(*action)));
+    assert!(!write.allows(Action::Read));
+
+    assert!(Action::ALL.iter().all(|action| scope("system/Task.*").allows(*action)));
+    assert!(Action::ALL.iter().all(|action| scope("system/Task.cruds").allows(*action)));
+    let updates = scope("user/Task.ru");
+    assert!(updates.allows(Action::Update) && !updates.allows(Action::Delete));
+}
+
+#[test]
+fn rejects_invalid_scopes() {
+    for source in [
+        "LabReport.read",
+        "practitioner/LabReport.read",
+        "patient/LabReport",
+        "patient/.read",
+        "patient/LabReport.sr",
+        "patient/LabReport.rr",
+        "patient/LabReport.x",
+        "patient/LabReport.rs?category=laboratory",
+    ] {
+        let err = Scope::parse(source).unwrap_err();
+        assert_eq!(err.resource_type(), "Scope", "{}", source);
+        assert_eq!(err.value(), Some(&json!(source)));
+    }
+}
+
+#[test]
+fn round_trips_as_text() {
+    let scope = scope("patient/MedicationRecord.rs");
+    assert_eq!(scope.to_string(), "patient/MedicationRecord.rs");
+    assert_eq!(serde_json::to_value(&scope).unwrap(), json!("patient/MedicationRecord.rs"));
+    assert_eq!(serde_json::from_value::<Scope>(json!("patient/MedicationRecord.rs")).unwrap(), scope);
+    assert!(serde_json::from_value::<Scope>(json!("patient/MedicationRecord")).is_err());
+}
+
+#[test]
+fn patient_scopes_need_a_patient_context() {
+    let scope = scope("patient/LabReport.read");
+    assert!(scope.permits("LabReport", Action::Read, Some("p-1")));
+    assert!(!scope.permits("LabReport", Action::Read, None));
+    assert!(!scope.permits("MedicationRecord", Action::Read, Some("p-1")));
+
+    let user = self::scope("user/LabReport.read");
+    assert!(user.permits("LabReport", Action::Read, None));
+}
+
+#[test]
+fn authorization_limits_patient_scopes_to_the_launch_patient() {
+    let token = Authorization::parse("openid fhirUser launch/patient offline_access patient/LabReport.cruds")
+        .unwrap()
+        .with_patient("p-1");
+    assert_eq!(token.scopes().len(), 1);
+    assert_eq!(token.patient(), Some("p-1"));
+    assert!(token.permits_resource(&report("p-1"), Action::Update));
+    assert!(!token.permits_resource(&report("p-2"), Action::Read));
+
+    let without_launch = Authorization::parse("patient/LabReport.read").unwrap();
+    assert!(!without_launch.permits_resource(&report("p-1"), Action::Read));
+}
+
+#[test]
+fn authorization_combines_scopes() {
+    let token = Authorization::parse("patient/*.read user/LabReport.write").unwrap().with_patient("p-1");
+    assert!(token.permits("MedicationRecord", Action::Search, "p-1"));
+    assert!(!token.permits("MedicationRecord", Action::Create, "p-1"));
+    assert!(token.permits(LabReport::RESOURCE_TYPE, Action::Create, "p-2"));
+    assert!(!token.permits(LabReport::RESOURCE_TYPE, Action::Read, "p-2"));
+
+    assert!(Authorization::parse("openid patient/LabReport.bogus").is_err());
+    assert!(!Authorization::default().permits("LabReport", Action::Read, "p-1"));
+}
@@ -0,0 +1,101 @@
+//! Checks birth records.
+
+use serde_json::json;
+use wellally::birth::{self, BirthRecord, BirthTerm, BirthWeightCategory, DeliveryMethod, GestationalAge};
+use wellally::{Quantity, Resource, Validate};
+
+mod common;
+
+const EXAMPLE: &str = include_str!("../../../infrastructure/schemas/birth/examples/birth.twin.json");
+
+fn born(child: &str, mother: &str, at: &str, weeks: u32, days: u32) -> BirthRecord {
+    let id = format!("birth-{}", child);
+    BirthRecord::new(id, child, mother, at.parse().unwrap(), GestationalAge::new(weeks, days))
+}
+
+#[test]
+fn published_example_round_trips() {
+    let (record, written) = common::round_trip::<BirthRecord>(EXAMPLE);
+    assert_eq!(record.patient_id, "person-101");
+    assert_eq!(record.mother_id, "person-001");
+    assert_eq!(record.gestational_age, GestationalAge::new(36, 2));
+    assert!(record.delivery_method.unwrap().is_cesarean());
+    assert_eq!(record.apgar[0].components.unwrap().total(), 6);
+    assert_eq!(written["gestationalAge"], json!({"weeks": 36, "days": 2}));
+}
+
+#[test]
+fn classifies_term_weight_and_apgar() {
+    let record = BirthRecord::from_json(EXAMPLE).unwrap();
+    assert_eq!(record.term(), BirthTerm::ModerateToLatePreterm);
+    assert!(record.is_preterm());
+    assert_eq!(record.weight_grams(), Some(2350.0));
+    assert_eq!(record.weight_category(), Some(BirthWeightCategory::Low));
+    assert_eq!(record.apgar_at(1), Some(6));
+    assert_eq!(record.apgar_at(5), Some(8));
+    assert!(!record.is_low_apgar());
+
+    assert_eq!(BirthTerm::of(GestationalAge::new(27, 6)), BirthTerm::ExtremelyPreterm);
+    assert_eq!(BirthTerm::of(GestationalAge::new(37, 0)), BirthTerm::EarlyTerm);
+    assert_eq!(BirthTerm::of(GestationalAge::new(40, 6)), BirthTerm::FullTerm);
+    assert_eq!(BirthTerm::of(GestationalAge::new(42, 0)), BirthTerm::PostTerm);
+    assert!(!BirthTerm::EarlyTerm.is_preterm());
+    assert_eq!(BirthWeightCategory::who(4000.0), BirthWeightCategory::Macrosomia);
+    assert!(!DeliveryMethod::Forceps.is_cesarean());
+
+    let mut heavy = born("baby-2", "mum-2", "2024-10-12", 40, 1);
+    heavy.birth_weight = Some(Quantity::new(4.2, "kg"));
+    assert_eq!(heavy.weight_category(), Some(BirthWeightCategory::Macrosomia));
+    heavy.birth_weight = Some(Quantity::new(52.0, "cm"));
+    assert_eq!(heavy.weight_category(), None);
+}
+
+#[test]
+fn lists_births_to_a_mother_in_order() {
+    let first_twin = BirthRecord::from_json(EXAMPLE).unwrap();
+    let mut second_twin = first_twin.clone();
+    second_twin.id = "birth-2024-10-12-002".into();
+    second_twin.patient_id = "person-102".into();
+    second_twin.birth_order = Some(2);
+    let mut older = born("person-100", "person-001", "2021-03-02", 39, 4);
+    older.delivery_method = Some(DeliveryMethod::SpontaneousVaginal);
+    let other = born("person-200", "person-002", "2022-01-01", 38, 0);
+
+    let records = [second_twin, other, first_twin, older];
+    let births = birth::births_to(&records, "person-001");
+    let ids: Vec<_> = births.iter().map(|record| record.patient_id.as_str()).collect();
+    assert_eq!(ids, ["person-100", "person-101", "person-102"]);
+}
+
+#[test]
+fn validation_reports_implausible_births() {
+    let record: BirthRecord = serde_json::from_value(json!({
+        "id": "birth-1", "patientId": "p-1", "motherId": "p-1", "bornAt": "2024-10-12",
+        "gestationalAge": {"weeks": 50, "days": 7},
+        "birthOrder": 0,
+        "birthWeight": {"value": 46, "unit": "cm"},
+        "apgar": [
+            {
+                "minute": 1, "score": 7,
+                "components": {"appearance": 3, "pulse": 2, "grimace": 1, "activity": 1, "respiration": 1}
+            },
+            {"minute": 1, "score": 11}
+        ]
+    }))
+    .unwrap();
+    let paths: Vec<_> = record.validate().into_iter().map(|issue| issue.path).collect();
+    assert_eq!(
+        paths,
+        [
+            "motherId",
+            "gestationalAge.weeks",
+            "gestationalAge.days",
+            "birthOrder",
+            "birthWeight.unit",
+            "apgar[0].components.appearance",
+            "apgar[0].components",
+            "apgar[1].minute",
+            "apgar[1].score",
+        ]
+    );
+}
@@ -0,0 +1,72 @@
+//! Checks body sites with laterality and qualifiers, and reading the legacy bare-Coding shape.
+
+use serde_json::json;
+use wellally::catalog::{coding, SNOMED_CT};
+use wellally::rehab::RehabSession;
+use wellally::{BodySite, ImagingReport, Laterality, Resource, Validate};
+
+const REHAB: &str = include_str!("../../../infrastructure/schemas/rehab/examples/rehab.knee-physiotherapy.json");
+
+fn knee_mri(id: &str, body_site: serde_json::Value, reported_at: &str) -> ImagingReport {
+    ImagingReport::from_json_value(&json!({
+        "id": id, "patientId": "p-1",
+        "modality": {"system": "http://dicom.nema.org/resources/ontology/DCM", "code": "MR"},
+        "bodySite": body_site,
+        "reportedAt": reported_at
+    }))
+    .unwrap()
+}
+
+#[test]
+fn reads_the_legacy_coding_shape() {
+    let legacy = json!({"system": SNOMED_CT, "code": "72696002", "display": "Knee region"});
+    let site: BodySite = serde_json::from_value(legacy.clone()).unwrap();
+    assert_eq!(site, BodySite::new(coding(SNOMED_CT, "72696002", "Knee region")));
+    assert_eq!(serde_json::to_value(&site).unwrap(), json!({ "site": legacy }));
+
+    let report = knee_mri("img-1", legacy, "2024-05-01T09:00:00Z");
+    assert_eq!(report.body_site.site.code, "72696002");
+    assert_eq!(report.body_site.laterality, None);
+    assert!(report.is_valid());
+}
+
+#[test]
+fn laterality_and_qualifiers_round_trip() {
+    let json = json!({
+        "site": {"system": SNOMED_CT, "code": "71341001", "display": "Femur"},
+        "laterality": "left",
+        "qualifiers": [{"system": SNOMED_CT, "code": "46053002", "display": "distal"}]
+    });
+    let site: BodySite = serde_json::from_value(json.clone()).unwrap();
+    assert_eq!(site.laterality, Some(Laterality::Left));
+    assert_eq!(site.label(), "left distal Femur");
+    assert_eq!(serde_json::to_value(&site).unwrap(), json);
+    assert_eq!(Laterality::Bilateral.snomed_code(), "51440002");
+
+    let session = RehabSession::from_json(REHAB).unwrap();
+    let knee = session.body_site.as_ref().unwrap();
+    assert_eq!((knee.site.code.as_str(), knee.laterality), ("72696002", Some(Laterality::Left)));
+}
+
+#[test]
+fn prior_lookup_keeps_sides_apart() {
+    let knee = |laterality: &str| json!({"site": {"system": SNOMED_CT, "code": "72696002"}, "laterality": laterality});
+    let left = knee_mri("img-left", knee("left"), "2024-01-10T09:00:00Z");
+    let right = knee_mri("img-right", knee("right"), "2024-03-10T09:00:00Z");
+    let follow_up = knee_mri("img-left-2", knee("left"), "2024-06-10T09:00:00Z");
+    let reports = [left.clone(), right.clone(), follow_up.clone()];
+    assert!(!follow_up.is_same_exam(&right));
+    assert_eq!(follow_up.prior(&reports), Some(&left));
+    assert_eq!(follow_up.narrative_fields()["bodySite"], ["left 72696002"]);
+}
+
+#[test]
+fn validation_reports_bad_sites() {
+    let site = json!({
+        "site": {"system": SNOMED_CT, "code": ""},
+        "qualifiers": [{"system": SNOMED_CT, "code": "24028007", "display": "Right"}, {"system": "", "code": "40415009"}]
+    });
+    let report = knee_mri("img-1", site, "2024-05-01T09:00:00Z");
+    let paths: Vec<_> = report.validate().into_iter().map(|issue| issue.path).collect();
+    assert_eq!(paths, ["bodySite.site.code", "bodySite.qualifiers[0].code", "bodySite.qualifiers[1].system"]);
+}
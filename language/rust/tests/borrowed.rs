@@ -0,0 +1,69 @@
+//! Tests for the borrowed lab report mirrors.
+
+use std::borrow::Cow;
+use std::fs;
+use std::path::PathBuf;
+
+use wellally::borrowed::{ndjson, LabReportRef, LabValueRef};
+use wellally::LabReport;
+
+fn examples() -> Vec<(String, String)> {
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../infrastructure/schemas/lab-report/examples");
+    let mut examples: Vec<_> = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .map(|path| (path.file_name().unwrap().to_string_lossy().into_owned(), fs::read_to_string(&path).unwrap()))
+        .collect();
+    examples.sort();
+    examples
+}
+
+#[test]
+fn to_owned_equals_the_owned_parse() {
+    for (name, json) in examples() {
+        let borrowed: LabReportRef = serde_json::from_str(&json).unwrap();
+        let owned: LabReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(borrowed.to_owned(), owned, "{}", name);
+        assert_eq!(serde_json::to_value(&borrowed).unwrap(), serde_json::to_value(&owned).unwrap(), "{}", name);
+    }
+}
+
+#[test]
+fn strings_borrow_unless_escaped() {
+    let json = r#"{"id":"lab-1","patientId":"p\u002d1","issuedAt":"2024-11-02T09:30:00Z",
+        "results":[{"code":{"coding":[{"system":"http://loinc.org","code":"5196-1"}]},"value":"negative"}]}"#;
+    let report: LabReportRef = serde_json::from_str(json).unwrap();
+    assert!(matches!(report.id, Cow::Borrowed("lab-1")));
+    assert!(matches!(report.patient_id, Cow::Owned(ref id) if id == "p-1"));
+    assert!(matches!(report.results[0].code.coding[0].system, Cow::Borrowed(_)));
+    assert!(matches!(&report.results[0].value, LabValueRef::String(Cow::Borrowed("negative"))));
+    assert_eq!(report.to_owned().patient_id, "p-1");
+}
+
+#[test]
+fn quantity_codes_borrow() {
+    let json = r#"{"id":"lab-1","patientId":"p-1","issuedAt":"2024-11-02T09:30:00Z",
+        "results":[{"code":{"coding":[{"system":"http://loinc.org","code":"2345-7"}]},
+        "value":{"value":97,"unit":"mg/dL","code":"mg/dL","system":"http://unitsofmeasure.org"}}]}"#;
+    let report: LabReportRef = serde_json::from_str(json).unwrap();
+    let LabValueRef::Quantity(quantity) = &report.results[0].value else { panic!("{:?}", report.results[0].value) };
+    assert!(matches!(quantity.code, Some(Cow::Borrowed("mg/dL"))));
+    assert!(matches!(quantity.system, Some(Cow::Borrowed("http://unitsofmeasure.org"))));
+}
+
+#[test]
+fn ndjson_reports_errors_per_line() {
+    let input = concat!(
+        r#"{"id":"lab-1","patientId":"p-1","issuedAt":"2024-11-02T09:30:00Z","results":[]}"#,
+        "\n\n",
+        r#"{"id":"lab-2","patientId":"p-1"}"#,
+        "\n",
+        r#"{"id":"lab-3","patientId":"p-2","issuedAt":"2024-11-03","results":[]}"#,
+        "\n",
+    );
+    let parsed: Vec<Result<LabReportRef, _>> = ndjson(input).collect();
+    assert_eq!(parsed.len(), 3);
+    assert_eq!(parsed[0].as_ref().unwrap().id, "lab-1");
+    assert!(parsed[1].as_ref().unwrap_err().to_string().contains("issuedAt"));
+    assert_eq!(parsed[2].as_ref().unwrap().to_owned().patient_id, "p-2");
+}
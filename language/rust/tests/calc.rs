@@ -0,0 +1,184 @@
+//! Checks the body measurement calculators against reference values.
+
+use wellally::calc::{BmiCategory, BodyMeasurements, BsaFormula, PediatricBmiCategory};
+use wellally::catalog::{concept, LOINC};
+use wellally::{Interpretation, LabResult, LabValue, Quantity, Sex, Validate};
+
+fn measurement(code: &str, value: f64, unit: &str) -> LabResult {
+    LabResult {
+        code: concept(LOINC, code, "measurement"),
+        value: LabValue::Quantity(Quantity::new(value, unit)),
+        reference_range: None,
+        interpretation: None,
+        method: None,
+        effective: None,
+    }
+}
+
+#[test]
+fn measurements_from_coded_results() {
+    let results = [measurement("8302-2", 70.0, "[in_i]"), measurement("29463-7", 220.0, "[lb_av]")];
+    let body = BodyMeasurements::from_results(&results).unwrap();
+    assert!((body.height_cm - 177.8).abs() < 1e-9);
+    assert!((body.weight_kg - 99.79).abs() < 0.01);
+
+    let bmi = body.bmi_result();
+    assert_eq!(bmi.value, LabValue::Quantity(Quantity::new(31.6, "kg/m2")));
+    assert_eq!(bmi.interpretation, Some(Interpretation::H));
+    assert_eq!(body.bmi_category(), BmiCategory::ObeseClass1);
+}
+
+#[test]
+fn unsupported_units_are_located() {
+    let results = [measurement("8302-2", 1.8, "yd"), measurement("29463-7", 80.0, "kg")];
+    let err = BodyMeasurements::from_results(&results).unwrap_err();
+    assert_eq!(err.path(), "results[0].value.unit");
+    assert_eq!(err.value(), Some(&"yd".into()));
+}
+
+#[test]
+fn body_surface_area_formulas() {
+    let body = BodyMeasurements { weight_kg: 70.0, height_cm: 170.0 };
+    assert!((body.bsa(BsaFormula::DuBois) - 1.8097).abs() < 1e-3);
+    assert!((body.bsa(BsaFormula::Mosteller) - 1.8181).abs() < 1e-3);
+    assert_eq!(body.bsa_result(BsaFormula::DuBois).code.text.as_deref(), Some("Body surface area (Du Bois)"));
+}
+
+#[test]
+fn ideal_and_adjusted_body_weight() {
+    // 6 ft tall: 12 inches over 5 ft
+    let body = BodyMeasurements { weight_kg: 120.0, height_cm: 182.88 };
+    assert!((body.ideal_body_weight(Sex::Male).unwrap() - 77.6).abs() < 1e-9);
+    assert!((body.ideal_body_weight(Sex::Female).unwrap() - 73.1).abs() < 1e-9);
+    assert!((body.adjusted_body_weight(Sex::Male).unwrap() - 94.56).abs() < 1e-9);
+    assert_eq!(body.ideal_body_weight(Sex::Unknown), None);
+
+    let results = [
+        body.bmi_result(),
+        body.bsa_result(BsaFormula::Mosteller),
+        body.ideal_body_weight_result(Sex::Male).unwrap(),
+        body.adjusted_body_weight_result(Sex::Male).unwrap(),
+    ];
+    let report = wellally::LabReport {
+        id: "calc-1".to_string(),
+        patient_id: "p-1".to_string(),
+        issued_at: chrono::Utc::now().into(),
+        results: results.to_vec(),
+        facility: None,
+        panel: None,
+        specimen: None,
+        notes: None,
+        schema_version: None,
+        security_labels: None,
+        extensions: None,
+        #[cfg(feature = "preserve-unknown")]
+        extra: Default::default(),
+    };
+    assert_eq!(report.validate(), vec![]);
+}
+
+#[test]
+fn pediatric_categories() {
+    assert_eq!(PediatricBmiCategory::cdc(4.9), PediatricBmiCategory::Underweight);
+    assert_eq!(PediatricBmiCategory::cdc(85.0), PediatricBmiCategory::Overweight);
+    assert_eq!(PediatricBmiCategory::cdc(97.0), PediatricBmiCategory::Obese);
+}
+
+mod obstetrics {
+    use chrono::NaiveDate;
+    use wellally::calc::obstetrics::{
+        DatingBasis, GestationalAge, PregnancyDating, Trimester, UltrasoundDating,
+    };
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn due_date_from_lmp() {
+        let dating = PregnancyDating::from_lmp(date(2024, 1, 1), None).unwrap();
+        assert_eq!(dating.estimated_due_date, date(2024, 10, 7));
+        assert_eq!(
+            PregnancyDating::from_lmp(date(2024, 1, 1), Some(32)).unwrap().estimated_due_date,
+            date(2024, 10, 11)
+        );
+        assert_eq!(dating.gestational_age(date(2024, 3, 28)), Some(GestationalAge::new(12, 3)));
+        assert_eq!(dating.gestational_age(date(2023, 12, 31)), None);
+        assert_eq!(dating.trimester(date(2024, 4, 8)), Some(Trimester::Second));
+        assert_eq!(dating.trimester(date(2024, 7, 15)), Some(Trimester::Third));
+        assert_eq!(GestationalAge::new(12, 3).to_string(), "12w3d");
+    }
+
+    #[test]
+    fn ultrasound_redating_follows_thresholds() {
+        let lmp = date(2024, 1, 1);
+        // Scan at 8w0d by LMP measuring 7w2d: 5 days apart keeps the LMP date
+        let close = UltrasoundDating { date: date(2024, 2, 26), gestational_age: GestationalAge::new(7, 2) };
+        let dating = PregnancyDating::estimate(Some(lmp), None, Some(close)).unwrap();
+        assert_eq!(dating.basis, DatingBasis::LastMenstrualPeriod);
+        // 6 days apart redates to the ultrasound
+        let far = UltrasoundDating { gestational_age: GestationalAge::new(7, 1), ..close };
+        let dating = PregnancyDating::estimate(Some(lmp), None, Some(far)).unwrap();
+        assert_eq!(dating.basis, DatingBasis::Ultrasound);
+        assert_eq!(dating.estimated_due_date, date(2024, 10, 13));
+        // The same 6 days at 20 weeks is within the 10-day threshold
+        let late = UltrasoundDating { date: date(2024, 5, 20), gestational_age: GestationalAge::new(19, 1) };
+        assert_eq!(PregnancyDating::estimate(Some(lmp), None, Some(late)).unwrap().basis, DatingBasis::LastMenstrualPeriod);
+        assert_eq!(PregnancyDating::estimate(None, None, None), None);
+    }
+
+    #[test]
+    fn dates_past_the_calendar_are_rejected() {
+        assert_eq!(PregnancyDating::from_lmp(NaiveDate::MAX, None), None);
+        assert_eq!(PregnancyDating::from_lmp(date(2024, 1, 1), Some(u32::MAX)), None);
+        let absurd = UltrasoundDating { date: NaiveDate::MIN, gestational_age: GestationalAge::new(41, 0) };
+        assert_eq!(PregnancyDating::from_ultrasound(absurd), None);
+        // An unusable scan leaves the LMP dating in place
+        let dating = PregnancyDating::estimate(Some(date(2024, 1, 1)), None, Some(absurd)).unwrap();
+        assert_eq!(dating.basis, DatingBasis::LastMenstrualPeriod);
+
+        let dating = PregnancyDating { estimated_due_date: NaiveDate::MIN, basis: DatingBasis::Ultrasound };
+        assert_eq!(dating.pregnancy_start(), None);
+        assert_eq!(dating.gestational_age(date(2024, 1, 1)), None);
+    }
+}
+
+mod kidney {
+    use serde_json::json;
+    use wellally::calc::kidney::{KidneyFunction, EGFR_UNIT};
+    use wellally::{LabReport, LabValue, Quantity, Resource, Sex};
+
+    #[test]
+    fn ckd_epi_2021() {
+        let male = KidneyFunction::new(&Quantity::new(1.2, "mg/dL"), 60.0, Sex::Male).unwrap();
+        assert_eq!(male.egfr().map(f64::round), Some(69.0));
+        let female = KidneyFunction::new(&Quantity::new(0.6, "mg/dL"), 60.0, Sex::Female).unwrap();
+        assert_eq!(female.egfr().map(f64::round), Some(103.0));
+        let molar = KidneyFunction::new(&Quantity::new(0.1061, "mmol/L"), 60.0, Sex::Male).unwrap();
+        assert!((molar.creatinine_mg_dl - 1.2).abs() < 0.001);
+
+        assert_eq!(KidneyFunction { age_years: 16.0, ..male }.egfr(), None);
+        assert_eq!(KidneyFunction { sex: Sex::Unknown, ..male }.egfr(), None);
+        assert!(KidneyFunction::new(&Quantity::new(1.2, "g/L"), 60.0, Sex::Male).is_err());
+    }
+
+    #[test]
+    fn egfr_result_from_creatinine_result() {
+        let report = LabReport::from_json_value(&json!({
+            "id": "lab-1", "patientId": "p-1", "issuedAt": "2024-10-01",
+            "results": [{
+                "code": {"coding": [{"system": "http://loinc.org", "code": "14682-9"}]},
+                "value": {"value": 106, "unit": "umol/L"}
+            }]
+        }))
+        .unwrap();
+        let kidney = KidneyFunction::from_results(&report.results, 60.0, Sex::Male).unwrap();
+        let result = kidney.egfr_result().unwrap();
+        assert_eq!(result.code.coding[0].code, "98979-8");
+        let LabValue::Quantity(egfr) = result.value else { panic!("{:?}", result.value) };
+        assert_eq!((egfr.value, egfr.unit.as_str()), (69.0, EGFR_UNIT));
+
+        let err = KidneyFunction::from_results(&[], 60.0, Sex::Male).unwrap_err();
+        assert!(err.to_string().contains("serum creatinine"), "{}", err);
+    }
+}
@@ -0,0 +1,107 @@
+//! Checks the CGM series encoding and its time-in-range aggregates.
+
+use wellally::cgm::{GlucoseSeries, MG_DL_PER_MMOL_L};
+use wellally::{FlexibleDateTime, Resource, Validate};
+
+fn start() -> FlexibleDateTime {
+    "2024-10-01T00:00:00Z".parse().unwrap()
+}
+
+fn series(samples: &[Option<f64>]) -> GlucoseSeries {
+    GlucoseSeries::from_samples("cgm-1", "p-1", "mg/dL", start(), 300, samples)
+}
+
+#[test]
+fn run_length_encoding_round_trips() {
+    let samples = [Some(100.0), Some(100.0), Some(100.0), None, None, Some(120.0), Some(100.0)];
+    let series = series(&samples);
+    assert_eq!(series.values, vec![Some(100.0), None, Some(120.0), Some(100.0)]);
+    assert_eq!(series.counts, Some(vec![3, 2, 1, 1]));
+    assert_eq!(series.len(), 7);
+    assert_eq!(series.samples(), samples);
+
+    let distinct = self::series(&[Some(100.0), Some(101.0)]);
+    assert_eq!(distinct.counts, None);
+    assert_eq!(distinct.runs().collect::<Vec<_>>(), vec![(Some(100.0), 1), (Some(101.0), 1)]);
+}
+
+#[test]
+fn readings_are_timed_from_the_start() {
+    let series = series(&[Some(100.0), None, Some(110.0)]);
+    let readings = series.readings().unwrap();
+    assert_eq!(readings.len(), 2);
+    assert_eq!(readings[1].0, "2024-10-01T00:10:00Z".parse::<chrono::DateTime<chrono::Utc>>().unwrap());
+    assert_eq!(series.end(), Some(readings[1].0));
+}
+
+#[test]
+fn times_past_the_calendar_are_none() {
+    let mut series = series(&[Some(100.0), Some(110.0)]);
+    series.interval_seconds = u32::MAX;
+    series.counts = Some(vec![u32::MAX, u32::MAX]);
+    assert_eq!(series.end(), None);
+    series.counts = Some(vec![100_000, 1]);
+    assert_eq!(series.readings(), None);
+    series.counts = Some(vec![1, 1]);
+    assert_eq!(series.readings().unwrap().len(), 2);
+}
+
+#[test]
+fn time_in_range_uses_the_consensus_bins() {
+    let samples = [50.0, 60.0, 70.0, 180.0, 181.0, 250.0, 251.0, 120.0, 130.0, 140.0].map(Some);
+    let tir = series(&samples).time_in_range().unwrap();
+    assert_eq!(tir.very_low, 10.0);
+    assert_eq!(tir.low, 10.0);
+    assert_eq!(tir.in_range, 50.0);
+    assert_eq!(tir.high, 20.0);
+    assert_eq!(tir.very_high, 10.0);
+    assert_eq!(series(&[None, None]).time_in_range(), None);
+}
+
+#[test]
+fn aggregates_weight_runs_and_convert_mmol() {
+    let mg = series(&[Some(126.0), Some(126.0), Some(162.0)]);
+    assert_eq!(mg.mean(), Some(138.0));
+    assert!((mg.gmi().unwrap() - 6.61).abs() < 0.01);
+
+    let samples = [Some(7.0), Some(7.0), Some(9.0)];
+    let mmol = GlucoseSeries::from_samples("cgm-2", "p-1", "mmol/L", start(), 300, &samples);
+    let expected = (7.0 + 7.0 + 9.0) / 3.0 * MG_DL_PER_MMOL_L;
+    assert!((mmol.mean().unwrap() - expected).abs() < 1e-9);
+
+    let unknown = GlucoseSeries { unit: "mg".into(), ..mg.clone() };
+    assert_eq!(unknown.mean(), None);
+}
+
+#[test]
+fn variability_is_the_sample_deviation() {
+    let series = series(&[Some(100.0), Some(100.0), Some(140.0), Some(140.0)]);
+    let variability = series.variability().unwrap();
+    assert!((variability.standard_deviation - 23.094).abs() < 0.001);
+    assert!((variability.coefficient_of_variation - 19.245).abs() < 0.001);
+    assert_eq!(self::series(&[Some(100.0)]).variability(), None);
+}
+
+#[test]
+fn downsampling_averages_buckets() {
+    let samples = [Some(100.0), Some(110.0), Some(120.0), None, None, None, Some(90.0)];
+    let hourly = series(&samples).downsample(900);
+    assert_eq!(hourly.interval_seconds, 900);
+    assert_eq!(hourly.samples(), vec![Some(110.0), None, Some(90.0)]);
+    assert_eq!(hourly.start, start());
+    assert_eq!(series(&samples).downsample(60).interval_seconds, 300);
+}
+
+#[test]
+fn json_round_trip_and_validation() {
+    let json = r#"{"id":"cgm-1","patientId":"p-1","unit":"mg/dL","start":"2024-10-01T00:00:00Z",
+        "intervalSeconds":300,"values":[110,null,95],"counts":[4,2,1]}"#;
+    let series = GlucoseSeries::from_json(json).unwrap();
+    assert_eq!(series.len(), 7);
+    assert!(series.validate().is_empty());
+    assert_eq!(GlucoseSeries::from_json(&serde_json::to_string(&series).unwrap()).unwrap(), series);
+
+    let broken = GlucoseSeries { unit: "mg".into(), interval_seconds: 0, counts: Some(vec![4, 0]), ..series };
+    let paths: Vec<_> = broken.validate().into_iter().map(|issue| issue.path).collect();
+    assert_eq!(paths, ["unit", "intervalSeconds", "counts", "counts[1]"]);
+}
@@ -0,0 +1,108 @@
+//! Checks deriving a clinical summary from a patient's chart, and the sources it trusts.
+
+use wellally::chart::{PatientChart, SummaryOptions, SummarySource};
+use wellally::io::{AnyResource, Bundle};
+use wellally::{
+    AllergyIntolerance, ClinicalStatus, ClinicalSummary, Condition, FlexibleDateTime, LabReport, MedicationRecord,
+    Person, Resource, Validate, VerificationStatus,
+};
+
+const CONDITION: &str = include_str!("../../../infrastructure/schemas/condition/examples/condition.hypertension.json");
+const ALLERGY: &str =
+    include_str!("../../../infrastructure/schemas/allergy-intolerance/examples/allergy-intolerance.penicillin.json");
+const LISINOPRIL: &str = include_str!("../../../infrastructure/schemas/medication/examples/medication.lisinopril.json");
+const AMOXICILLIN: &str =
+    include_str!("../../../infrastructure/schemas/medication/examples/medication.amoxicillin.json");
+const METFORMIN: &str = include_str!("../../../infrastructure/schemas/medication/examples/medication.metformin.json");
+const VITALS: &str = include_str!("../../../infrastructure/schemas/lab-report/examples/lab-report.vital-signs.json");
+const LIPIDS: &str = include_str!("../../../infrastructure/schemas/lab-report/examples/lab-report.lipid-panel.json");
+
+fn at(text: &str) -> FlexibleDateTime {
+    text.parse().unwrap()
+}
+
+fn chart() -> PatientChart {
+    let mut chart = PatientChart::new("person-001");
+    chart.conditions.push(Condition::from_json(CONDITION).unwrap());
+    chart.allergies.push(AllergyIntolerance::from_json(ALLERGY).unwrap());
+    for medication in [LISINOPRIL, AMOXICILLIN, METFORMIN] {
+        chart.medications.push(MedicationRecord::from_json(medication).unwrap());
+    }
+    chart.lab_reports.push(LabReport::from_json(VITALS).unwrap());
+    chart.lab_reports.push(LabReport::from_json(LIPIDS).unwrap());
+    chart
+}
+
+#[test]
+fn summary_lists_active_records_and_latest_vitals() {
+    let options = SummaryOptions::new(at("2024-12-10T10:00:00Z"));
+    let summary = ClinicalSummary::derive_with(&chart(), &options);
+    assert_eq!(summary.conditions.as_ref().unwrap()[0].coding[0].code, "38341003");
+    assert_eq!(summary.allergies.as_ref().unwrap()[0].coding[0].code, "7980");
+    assert_eq!(summary.current_medication_count, Some(2));
+    assert_eq!(summary.derived_at, Some(options.at));
+
+    let vitals = summary.latest_vitals.as_ref().unwrap();
+    assert_eq!(vitals.report_id, "lab-2024-vitals-001");
+    assert_eq!((vitals.systolic_blood_pressure, vitals.pulse), (Some(142.0), Some(76.0)));
+    assert_eq!(vitals.vitals().respiratory_rate, Some(16.0));
+
+    let written = serde_json::to_value(&summary).unwrap();
+    assert_eq!(written["currentMedicationCount"], 2);
+    assert_eq!(written["latestVitals"]["takenAt"], "2024-11-20T09:15:00+08:00");
+    assert_eq!(written["derivedAt"], "2024-12-10T10:00:00Z");
+    assert_eq!(serde_json::from_value::<ClinicalSummary>(written).unwrap(), summary);
+}
+
+#[test]
+fn summary_skips_inactive_records_and_later_reports() {
+    let mut chart = chart();
+    chart.conditions[0].clinical_status = ClinicalStatus::Resolved;
+    chart.allergies[0].verification_status = Some(VerificationStatus::Refuted);
+    let summary = ClinicalSummary::derive_with(&chart, &SummaryOptions::new(at("2024-09-12T10:00:00Z")));
+    assert_eq!(summary.conditions, Some(vec![]));
+    assert_eq!(summary.allergies, Some(vec![]));
+    assert_eq!(summary.current_medication_count, Some(2));
+    assert_eq!(summary.latest_vitals, None);
+}
+
+#[test]
+fn options_choose_sections_and_trusted_sources() {
+    let mut chart = chart();
+    chart.allergies[0].verification_status = Some(VerificationStatus::Unconfirmed);
+    let mut options = SummaryOptions::new(at("2024-12-10T10:00:00Z"));
+    options.confirmed_only = true;
+    options.trusted_facilities = Some(vec!["lab-01".into()]);
+    let summary = ClinicalSummary::derive_with(&chart, &options);
+    assert_eq!(summary.conditions.unwrap().len(), 1);
+    assert_eq!(summary.allergies, Some(vec![]));
+    assert_eq!(summary.latest_vitals, None);
+
+    options.trusted_facilities = Some(vec!["社区卫生服务中心".into()]);
+    options.sources = vec![SummarySource::Vitals];
+    let summary = ClinicalSummary::derive_with(&chart, &options);
+    assert_eq!((summary.conditions, summary.current_medication_count), (None, None));
+    assert!(summary.latest_vitals.is_some());
+}
+
+#[test]
+fn chart_from_bundle_keeps_the_patients_records() {
+    let mut other = Condition::from_json(CONDITION).unwrap();
+    other.patient_id = "person-002".into();
+    let bundle = Bundle::new(vec![
+        AnyResource::Condition(Condition::from_json(CONDITION).unwrap()),
+        AnyResource::Condition(other),
+        AnyResource::LabReport(LabReport::from_json(VITALS).unwrap()),
+    ]);
+    let chart = PatientChart::from_bundle(&bundle, "person-001");
+    assert_eq!((chart.conditions.len(), chart.lab_reports.len(), chart.medications.len()), (1, 1, 0));
+
+    let summary = ClinicalSummary::derive(&chart);
+    assert!(summary.derived_at.is_some());
+    let mut person = Person::from_json_value(&serde_json::json!({
+        "id": "person-001", "name": [{"family": "Wang", "given": ["Min"]}], "birthDate": "1960-05-02"
+    }))
+    .unwrap();
+    person.clinical_summary = Some(summary);
+    assert!(person.validate().is_empty(), "{:?}", person.validate());
+}
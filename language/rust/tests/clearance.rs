@@ -0,0 +1,97 @@
+//! Checks clearance documents.
+
+use serde_json::json;
+use wellally::catalog::{concept, LOINC};
+use wellally::clearance::{ClearanceDocument, ClearanceOutcome, ClearanceType, RequiredEvidence, Restriction};
+use wellally::{LabReport, Reference, Resource, Validate};
+
+mod common;
+
+const EXAMPLE: &str =
+    include_str!("../../../infrastructure/schemas/clearance/examples/clearance.return-to-work.json");
+
+fn clinician() -> Reference {
+    Reference { reference: "Practitioner/dr-li".into(), display: None }
+}
+
+fn sports_physical() -> ClearanceDocument {
+    ClearanceDocument::new("clr-1", "p-1", ClearanceType::SportsPhysical, "2024-08-01".parse().unwrap(), clinician())
+}
+
+fn lab(id: &str, patient_id: &str, issued_at: &str, code: &str) -> LabReport {
+    serde_json::from_value(json!({
+        "id": id, "patientId": patient_id, "issuedAt": issued_at,
+        "results": [{"code": {"coding": [{"system": LOINC, "code": code}]}, "value": "Negative"}]
+    }))
+    .unwrap()
+}
+
+#[test]
+fn published_example_round_trips() {
+    let (form, written) = common::round_trip::<ClearanceDocument>(EXAMPLE);
+    assert_eq!(form.clearance_type, ClearanceType::ReturnToWork);
+    assert_eq!(form.outcome, ClearanceOutcome::ClearedWithRestrictions);
+    assert_eq!(form.restrictions.len(), 2);
+    assert!(form.missing_evidence().is_empty());
+    assert_eq!(written["type"], "return-to-work");
+    assert_eq!(written["restrictions"][0]["until"], "2024-09-20");
+}
+
+#[test]
+fn is_valid_within_its_period_and_lifts_restrictions() {
+    let form = ClearanceDocument::from_json(EXAMPLE).unwrap();
+    assert!(!form.is_valid_on("2024-08-20".parse().unwrap()));
+    assert!(form.is_valid_on("2024-08-21".parse().unwrap()));
+    assert!(!form.is_valid_on("2025-08-21".parse().unwrap()));
+    assert_eq!(form.restrictions_on("2024-09-20".parse().unwrap()).len(), 2);
+    let later = form.restrictions_on("2024-09-21".parse().unwrap());
+    assert_eq!(later.iter().map(|r| r.description.as_str()).collect::<Vec<_>>(), ["不得登高作业"]);
+
+    let mut deferred = form.clone();
+    deferred.outcome = ClearanceOutcome::Deferred;
+    assert!(!deferred.is_valid_on("2024-09-01".parse().unwrap()));
+    assert!(sports_physical().is_valid_on("2030-01-01".parse().unwrap()));
+}
+
+#[test]
+fn links_the_latest_matching_lab_report() {
+    let mut form = sports_physical();
+    form.required_evidence.push(RequiredEvidence::new(concept(LOINC, "71773-6", "TB IGRA")));
+    form.required_evidence.push(RequiredEvidence::new(concept(LOINC, "5196-1", "HBsAg")));
+    let reports = [
+        lab("lab-old", "p-1", "2023-07-01T08:00:00Z", "71773-6"),
+        lab("lab-new", "p-1", "2024-07-20T08:00:00Z", "71773-6"),
+        lab("lab-other", "p-2", "2024-07-25T08:00:00Z", "71773-6"),
+        lab("lab-lipids", "p-1", "2024-07-25T08:00:00Z", "2093-3"),
+    ];
+    assert_eq!(form.link_lab_evidence(&reports), 1);
+    assert_eq!(form.required_evidence[0].evidence.as_ref().unwrap().reference, "LabReport/lab-new");
+    let missing = form.missing_evidence();
+    assert_eq!(missing.len(), 1);
+    assert_eq!(missing[0].code.coding[0].code, "5196-1");
+
+    let linked = form.required_evidence[0].evidence.clone();
+    assert_eq!(form.link_lab_evidence(&[lab("lab-newest", "p-1", "2024-07-30T08:00:00Z", "71773-6")]), 0);
+    assert_eq!(form.required_evidence[0].evidence, linked);
+}
+
+#[test]
+fn validation_reports_inconsistent_clearances() {
+    let mut form = sports_physical();
+    form.valid_period.end = Some("2024-07-01".parse().unwrap());
+    form.restrictions.push(Restriction::new(" "));
+    form.required_evidence.push(RequiredEvidence {
+        code: concept(LOINC, "71773-6", "TB IGRA"),
+        evidence: Some(Reference { reference: "lab-1".into(), display: None }),
+    });
+    let paths: Vec<_> = form.validate().into_iter().map(|issue| issue.path).collect();
+    assert_eq!(
+        paths,
+        ["validPeriod.end", "outcome", "restrictions[0].description", "requiredEvidence[0].evidence.reference"]
+    );
+
+    let mut restricted = sports_physical();
+    restricted.outcome = ClearanceOutcome::ClearedWithRestrictions;
+    let paths: Vec<_> = restricted.validate().into_iter().map(|issue| issue.path).collect();
+    assert_eq!(paths, ["restrictions"]);
+}
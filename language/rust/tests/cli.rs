@@ -0,0 +1,76 @@
+//! Runs the command-line companion end to end.
+
+#![cfg(feature = "cli")]
+
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+fn wellally(args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_wellally"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    // Commands rejecting their arguments exit without reading their input.
+    let _ = child.stdin.take().unwrap().write_all(stdin.as_bytes());
+    child.wait_with_output().unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+#[test]
+fn synthesized_cohorts_validate() {
+    let cohort = wellally(&["synth", "--patients", "3", "--seed", "1"], "");
+    assert!(cohort.status.success());
+    let cohort = stdout(&cohort);
+    assert!(cohort.lines().count() > 3);
+
+    let validated = wellally(&["validate", "-"], &cohort);
+    assert!(validated.status.success(), "{}", stdout(&validated));
+    assert!(String::from_utf8_lossy(&validated.stderr).contains("0 with issues"));
+}
+
+#[test]
+fn validate_reports_issues_and_fails() {
+    let report = r#"{"id":"lab-1","patientId":"p-1","issuedAt":"2024-11-02","results":[]}"#;
+    let validated = wellally(&["validate", "-"], report);
+    assert!(!validated.status.success());
+    assert_eq!(stdout(&validated), "-: LabReport/lab-1: results: must contain at least one item\n");
+}
+
+#[test]
+fn converts_and_anonymizes() {
+    let person = r#"{"id":"p-1","resourceType":"Person","birthDate":"1980-04-12",
+        "name":[{"family":"Doe","given":[]}]}"#;
+    let bundle = wellally(&["convert", "--to", "bundle"], person);
+    let bundle: serde_json::Value = serde_json::from_slice(&bundle.stdout).unwrap();
+    assert_eq!(bundle["resourceType"], "Bundle");
+    assert_eq!(bundle["entry"][0]["resource"]["id"], "p-1");
+
+    let anonymized = stdout(&wellally(&["anonymize", "--salt", "s"], person));
+    assert!(!anonymized.contains("Doe") && !anonymized.contains("p-1"));
+    assert!(anonymized.contains(r#""birthDate":"1980""#));
+
+    assert!(!wellally(&["convert", "--to", "xml"], person).status.success());
+}
+
+#[test]
+fn anonymize_withholds_categories() {
+    let records = concat!(
+        r#"{"id":"c-1","patientId":"p-1","clinicalStatus":"active","#,
+        r#""code":{"coding":[{"system":"http://hl7.org/fhir/sid/icd-10","code":"F32.1"}]}}"#,
+        "\n",
+        r#"{"id":"c-2","patientId":"p-1","clinicalStatus":"active","#,
+        r#""code":{"coding":[{"system":"http://hl7.org/fhir/sid/icd-10","code":"I10"}]}}"#,
+        "\n"
+    );
+    let output = wellally(&["anonymize", "--salt", "s", "--withhold", "BH"], records);
+    assert!(output.status.success());
+    let anonymized = stdout(&output);
+    assert_eq!(anonymized.lines().count(), 1);
+    assert!(anonymized.contains("I10"));
+}
@@ -0,0 +1,152 @@
+//! Runs the API client against a local stub server.
+
+#![cfg(feature = "client")]
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use serde_json::json;
+use wellally::client::{Client, ClientCredentials, ClientError};
+use wellally::{LabReport, MedicationRecord};
+
+/// Serves `responses` (status line suffix, headers, body) one connection
+/// each and records the requests as `METHOD path` plus headers and body.
+fn serve(responses: Vec<(&'static str, &'static str, String)>) -> (String, Arc<Mutex<Vec<String>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let base = format!("http://{}/v1", listener.local_addr().unwrap());
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let recorded = Arc::clone(&requests);
+    thread::spawn(move || {
+        for (status, headers, body) in responses {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request = String::new();
+            let mut length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if let Some(value) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                    length = value.trim().parse().unwrap();
+                }
+                request.push_str(&line);
+                if line == "\r\n" {
+                    break;
+                }
+            }
+            let mut content = vec![0; length];
+            reader.read_exact(&mut content).unwrap();
+            request.push_str(&String::from_utf8(content).unwrap());
+            recorded.lock().unwrap().push(request);
+            let response = format!(
+                "HTTP/1.1 {}\r\n{}Content-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                headers,
+                body.len(),
+                body
+            );
+            stream.write_all(response.as_bytes()).unwrap();
+        }
+    });
+    (base, requests)
+}
+
+fn ok(body: serde_json::Value) -> (&'static str, &'static str, String) {
+    ("200 OK", "", body.to_string())
+}
+
+fn report() -> serde_json::Value {
+    json!({
+        "id": "lab-1",
+        "patientId": "p-1",
+        "issuedAt": "2024-11-02T09:30:00Z",
+        "results": [{"code": {"coding": [{"system": "http://loinc.org", "code": "2345-7"}]}, "value": "normal"}]
+    })
+}
+
+fn medication(id: &str) -> serde_json::Value {
+    json!({
+        "id": id,
+        "patientId": "p-1",
+        "medication": {"system": "http://www.nlm.nih.gov/research/umls/rxnorm", "code": "308182"},
+        "dosage": {"value": 500, "unit": "mg"},
+        "route": {"system": "http://snomed.info/sct", "code": "26643006"},
+        "startDate": "2024-11-01"
+    })
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn uploads_and_reads_resources_with_a_bearer_token() {
+    let (base, requests) = serve(vec![ok(report()), ("404 Not Found", "", "{}".to_string())]);
+    let client = Client::new(&base).unwrap().with_token("t-123");
+
+    let report: LabReport = serde_json::from_value(report()).unwrap();
+    assert_eq!(client.upload_lab_report(&report).await.unwrap(), report);
+    assert!(client.get_person("p-404").await.unwrap().is_none());
+
+    let requests = requests.lock().unwrap();
+    assert!(requests[0].starts_with("PUT /v1/LabReport/lab-1 "));
+    assert!(requests[0].to_ascii_lowercase().contains("authorization: bearer t-123"));
+    assert!(requests[0].ends_with(&serde_json::to_string(&report).unwrap()));
+    assert!(requests[1].starts_with("GET /v1/Person/p-404 "));
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn follows_pages() {
+    let (base, requests) = serve(vec![
+        ok(json!({"items": [medication("m-1"), medication("m-2")], "next": "c-2"})),
+        ok(json!({"items": [medication("m-3")]})),
+    ]);
+    let client = Client::new(&base).unwrap().with_page_size(2);
+
+    let medications: Vec<MedicationRecord> = client.list_medications("p-1").await.unwrap();
+    let ids: Vec<_> = medications.iter().map(|medication| medication.id.as_str()).collect();
+    assert_eq!(ids, ["m-1", "m-2", "m-3"]);
+
+    let requests = requests.lock().unwrap();
+    assert!(requests[0].starts_with("GET /v1/MedicationRecord?patientId=p-1&limit=2 "));
+    assert!(requests[1].starts_with("GET /v1/MedicationRecord?patientId=p-1&limit=2&cursor=c-2 "));
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn retries_transient_failures() {
+    let (base, requests) = serve(vec![
+        ("503 Service Unavailable", "", "{}".to_string()),
+        ("429 Too Many Requests", "Retry-After: 0\r\n", "{}".to_string()),
+        ok(json!({"id": "p-1", "name": [{"family": "Doe", "given": []}], "birthDate": "1980"})),
+    ]);
+    let client = Client::new(&base).unwrap().with_retries(2, Duration::from_millis(1));
+    assert_eq!(client.get_person("p-1").await.unwrap().unwrap().id, "p-1");
+    assert_eq!(requests.lock().unwrap().len(), 3);
+
+    let (base, _) = serve(vec![("500 Internal Server Error", "", "boom".to_string())]);
+    let client = Client::new(&base).unwrap().with_retries(0, Duration::from_millis(1));
+    let err = client.get_person("p-1").await.unwrap_err();
+    assert!(matches!(err, ClientError::Status { status: 500, ref body } if body == "boom"));
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn rejects_invalid_uploads_before_sending() {
+    let client = Client::new("http://127.0.0.1:9/").unwrap();
+    let mut report: LabReport = serde_json::from_value(report()).unwrap();
+    report.results.clear();
+    assert!(matches!(client.upload_lab_report(&report).await, Err(ClientError::Data(_))));
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn fetches_and_caches_client_credentials_tokens() {
+    let (token_base, token_requests) = serve(vec![ok(json!({"access_token": "oauth-1", "expires_in": 3600}))]);
+    let (base, requests) = serve(vec![ok(medication("m-1")), ok(medication("m-2"))]);
+    let credentials = ClientCredentials::new(format!("{}/token", token_base), "app", "secret").with_scope("labs.write");
+    let client = Client::new(&base).unwrap().with_token_provider(credentials);
+
+    client.get::<MedicationRecord>("m-1").await.unwrap();
+    client.get::<MedicationRecord>("m-2").await.unwrap();
+
+    let token_requests = token_requests.lock().unwrap();
+    assert_eq!(token_requests.len(), 1);
+    assert!(token_requests[0].ends_with("grant_type=client_credentials&scope=labs.write"));
+    assert!(requests.lock().unwrap().iter().all(|request| request.to_ascii_lowercase().contains("bearer oauth-1")));
+}
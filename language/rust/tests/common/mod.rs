@@ -0,0 +1,16 @@
+//! Helpers shared by the resource test files.
+
+use std::fmt::Debug;
+
+use serde_json::Value;
+use wellally::{Resource, Validate};
+
+/// Parses a published example, checks that it validates and reads back from
+/// its own JSON, and returns the resource with that JSON for further checks.
+pub fn round_trip<R: Resource + Validate + PartialEq + Debug>(example: &str) -> (R, Value) {
+    let resource = R::from_json(example).unwrap();
+    assert!(resource.validate().is_empty(), "{:?}", resource.validate());
+    let written = serde_json::to_value(&resource).unwrap();
+    assert_eq!(R::from_json_value(&written).unwrap(), resource);
+    (resource, written)
+}
@@ -0,0 +1,59 @@
+//! Checks message records and thread reconstruction.
+
+use serde_json::json;
+use wellally::{message_threads, CommunicationRecord, MessagePayload, Resource, Validate};
+
+fn message(id: &str, sent: &str, reply_to: Option<&str>) -> CommunicationRecord {
+    let mut value = json!({
+        "id": id,
+        "patientId": "patient-1",
+        "status": "completed",
+        "sender": {"reference": "Person/patient-1"},
+        "recipient": [{"reference": "Practitioner/dr-li", "display": "Dr. Li"}],
+        "sent": sent,
+        "topic": {"coding": [{"system": "https://www.wellally.tech/fhir/CodeSystem/message-topic", "code": "lab-result"}]},
+        "payload": [
+            {"contentString": format!("Message {}", id)},
+            {"contentReference": {"reference": "LabReport/lab-1"}}
+        ]
+    });
+    if let Some(parent) = reply_to {
+        value["inResponseTo"] = json!({"reference": format!("CommunicationRecord/{}", parent)});
+    }
+    CommunicationRecord::from_json_value(&value).unwrap()
+}
+
+#[test]
+fn round_trips_and_validates() {
+    let message = message("msg-1", "2024-11-02T09:30:00Z", None);
+    assert!(message.is_valid(), "{:?}", message.validate());
+    assert_eq!(message.text(), "Message msg-1");
+    assert!(matches!(message.payload[1], MessagePayload::Reference(_)));
+    let json = serde_json::to_value(&message).unwrap();
+    assert_eq!(json["payload"][0]["contentString"], "Message msg-1");
+    assert_eq!(CommunicationRecord::from_json_value(&json).unwrap(), message);
+}
+
+#[test]
+fn rebuilds_threads_in_sent_order() {
+    let records = [
+        message("b-2", "2024-11-03T10:00:00Z", Some("b-1")),
+        message("a-1", "2024-11-01T08:00:00Z", None),
+        message("a-3", "2024-11-01T12:00:00Z", Some("a-2")),
+        message("a-2", "2024-11-01T09:00:00Z", Some("a-1")),
+        message("b-1", "2024-11-03T09:00:00Z", Some("missing")),
+    ];
+    let ids: Vec<Vec<&str>> =
+        message_threads(&records).iter().map(|thread| thread.iter().map(|m| m.id.as_str()).collect()).collect();
+    assert_eq!(ids, [vec!["a-1", "a-2", "a-3"], vec!["b-1", "b-2"]]);
+}
+
+#[test]
+fn replies_must_reference_messages_and_follow_sending() {
+    let mut message = message("msg-2", "2024-11-02T09:30:00Z", None);
+    message.in_response_to = serde_json::from_value(json!({"reference": "Task/task-1"})).unwrap();
+    message.received = Some("2024-11-02T09:00:00Z".parse().unwrap());
+    message.recipient.clear();
+    let paths: Vec<_> = message.validate().into_iter().map(|issue| issue.path).collect();
+    assert_eq!(paths, ["recipient", "inResponseTo.reference", "received"]);
+}
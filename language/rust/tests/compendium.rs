@@ -0,0 +1,87 @@
+//! Checks lab compendiums.
+
+use serde_json::json;
+use wellally::catalog::{coding, LOINC, SNOMED_CT};
+use wellally::compendium::{Compendium, SpecimenRequirement, TestDefinition};
+use wellally::{LabReport, Quantity, Resource, Validate};
+
+mod common;
+
+const EXAMPLE: &str = include_str!("../../../infrastructure/schemas/compendium/examples/compendium.district-lab.json");
+
+fn report(codes: &[(&str, &str)]) -> LabReport {
+    let results: Vec<_> = codes
+        .iter()
+        .map(|(system, code)| json!({"code": {"coding": [{"system": system, "code": code}]}, "value": "1"}))
+        .collect();
+    serde_json::from_value(json!({"id": "lab-1", "patientId": "p-1", "issuedAt": "2024-08-01", "results": results}))
+        .unwrap()
+}
+
+#[test]
+fn published_example_round_trips() {
+    let (compendium, written) = common::round_trip::<Compendium>(EXAMPLE);
+    assert_eq!(compendium.tests.len(), 3);
+    assert_eq!(compendium.patient_id(), "");
+    assert_eq!(written["tests"][0]["specimens"][0]["minimumVolume"]["unit"], "mL");
+    assert_eq!(written["tests"][2]["referenceLab"]["reference"], "Organization/ref-lab-1");
+}
+
+#[test]
+fn looks_tests_up_by_code_and_name() {
+    let compendium = Compendium::from_json(EXAMPLE).unwrap();
+    assert_eq!(compendium.by_code("4548-4").unwrap().name, "糖化血红蛋白");
+    assert!(compendium.by_code("2093-3").is_none(), "components are not orderable");
+    assert_eq!(compendium.by_name(" igra ").unwrap().code.code, "71773-6");
+    assert_eq!(compendium.by_name("血脂四项").unwrap().components.len(), 4);
+    assert!(compendium.by_name("lipid").is_none());
+
+    let found: Vec<_> = compendium.search("LIPID").iter().map(|test| test.code.code.as_str()).collect();
+    assert_eq!(found, ["57698-3"]);
+    assert_eq!(compendium.search("a1").len(), 1);
+    assert!(compendium.by_code("71773-6").unwrap().is_send_out());
+}
+
+#[test]
+fn finds_results_the_catalog_does_not_cover() {
+    let compendium = Compendium::from_json(EXAMPLE).unwrap();
+    assert!(compendium.covers(&coding(LOINC, "2085-9", "HDL")));
+    assert!(!compendium.covers(&coding(SNOMED_CT, "2085-9", "HDL")));
+
+    let report = report(&[(LOINC, "4548-4"), (LOINC, "2093-3"), (LOINC, "2160-0"), ("urn:lab-east", "GLU")]);
+    let uncatalogued = compendium.uncatalogued(&report);
+    let codes: Vec<_> = uncatalogued.iter().map(|result| result.code.coding[0].code.as_str()).collect();
+    assert_eq!(codes, ["2160-0", "GLU"]);
+}
+
+#[test]
+fn validation_reports_bad_definitions() {
+    let mut compendium = Compendium::new("c-1", "Lab");
+    let mut hba1c = TestDefinition::new(coding(LOINC, "4548-4", "HbA1c"), "HbA1c");
+    hba1c.specimens.push(SpecimenRequirement {
+        specimen_type: coding("http://terminology.hl7.org/CodeSystem/v2-0487", "BLDV", "Blood venous"),
+        container: None,
+        minimum_volume: Some(Quantity::new(2.0, "mg")),
+        handling: None,
+    });
+    hba1c.turnaround = Some(Quantity::new(24.0, "mL"));
+    compendium.tests.push(hba1c.clone());
+    compendium.tests.push(hba1c);
+    compendium.tests.push(TestDefinition::new(coding(SNOMED_CT, "43396009", "HbA1c measurement"), " "));
+    let paths: Vec<_> = compendium.validate().into_iter().map(|issue| issue.path).collect();
+    assert_eq!(
+        paths,
+        [
+            "tests[0].specimens[0].minimumVolume.unit",
+            "tests[0].turnaround.unit",
+            "tests[1].code",
+            "tests[1].specimens[0].minimumVolume.unit",
+            "tests[1].turnaround.unit",
+            "tests[2].code.system",
+            "tests[2].name",
+        ]
+    );
+
+    let paths: Vec<_> = Compendium::new("c-2", "Lab").validate().into_iter().map(|issue| issue.path).collect();
+    assert_eq!(paths, ["tests"]);
+}
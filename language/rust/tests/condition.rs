@@ -0,0 +1,66 @@
+//! Checks the Condition model: parsing, status helpers and validation.
+
+use serde_json::json;
+use wellally::catalog::{concept, SNOMED_CT};
+use wellally::{ClinicalStatus, CodeableConcept, Condition, Resource, Validate, VerificationStatus};
+
+fn hypertension() -> Condition {
+    Condition::from_json_value(&json!({
+        "id": "cond-1", "patientId": "p-1", "clinicalStatus": "active", "verificationStatus": "confirmed",
+        "category": "problem-list-item",
+        "code": {"coding": [{"system": "http://snomed.info/sct", "code": "38341003", "display": "Hypertensive disorder"}]},
+        "onset": "2019-03", "recordedDate": "2019-04-02T09:15:00+08:00"
+    }))
+    .unwrap()
+}
+
+#[test]
+fn round_trips_and_validates() {
+    let condition = hypertension();
+    assert!(condition.is_valid(), "{:?}", condition.validate());
+    assert_eq!(condition.verification_status, Some(VerificationStatus::Confirmed));
+    let json = serde_json::to_value(&condition).unwrap();
+    assert_eq!(json["clinicalStatus"], "active");
+    assert_eq!(json["onset"], "2019-03");
+    assert_eq!(Condition::from_json_value(&json).unwrap(), condition);
+}
+
+#[test]
+fn refuted_and_resolved_conditions_are_not_active() {
+    let mut condition = hypertension();
+    assert!(condition.is_active());
+    condition.verification_status = Some(VerificationStatus::Refuted);
+    assert!(!condition.is_active());
+    condition.verification_status = None;
+    condition.clinical_status = ClinicalStatus::Resolved;
+    assert!(!condition.is_active());
+    condition.clinical_status = ClinicalStatus::Relapse;
+    assert!(condition.is_active());
+}
+
+#[test]
+fn matches_by_shared_coding_or_text() {
+    let a = hypertension();
+    let mut b = Condition::new("cond-2", "p-1", concept(SNOMED_CT, "38341003", "Hypertension"));
+    assert!(a.same_code(&b));
+    b.code = concept(SNOMED_CT, "44054006", "Diabetes mellitus type 2");
+    assert!(!a.same_code(&b));
+
+    let text_only = Condition::new("cond-3", "p-1", CodeableConcept { coding: Vec::new(), text: Some("Back pain".into()) });
+    let mut other = text_only.clone();
+    other.code.text = Some(" back pain ".into());
+    assert!(text_only.same_code(&other));
+    assert!(!text_only.same_code(&a));
+}
+
+#[test]
+fn abatement_needs_a_closed_status_after_onset() {
+    let mut condition = hypertension();
+    condition.abatement = Some("2018".parse().unwrap());
+    let messages: Vec<_> = condition.validate().into_iter().map(|issue| issue.message).collect();
+    assert_eq!(messages, ["requires an inactive, remission or resolved clinicalStatus", "must not be before onset"]);
+
+    condition.clinical_status = ClinicalStatus::Resolved;
+    condition.abatement = Some("2024-06-01".parse().unwrap());
+    assert!(condition.is_valid(), "{:?}", condition.validate());
+}
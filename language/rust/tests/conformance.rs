@@ -0,0 +1,66 @@
+//! Runs the conformance suite against the published schema examples.
+
+use std::path::PathBuf;
+
+use wellally::conformance::{self, ConformanceError};
+
+fn schemas_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../infrastructure/schemas")
+}
+
+#[test]
+fn published_examples_round_trip() {
+    let report = conformance::assert_dir(schemas_dir());
+    for resource_type in [
+        "Person",
+        "LabReport",
+        "ImagingReport",
+        "MedicationRecord",
+        "FamilyHealthTree",
+        "Questionnaire",
+        "QuestionnaireResponse",
+        "Referral",
+        "Task",
+        "CommunicationRecord",
+        "Location",
+        "Condition",
+        "AllergyIntolerance",
+        "Flag",
+        "GlucoseSeries",
+        "EcgRecord",
+        "ConsentRecord",
+        "DialysisSession",
+        "RehabSession",
+        "WoundAssessment",
+        "InfusionRecord",
+        "PathologyReport",
+        "AudiogramReport",
+        "BirthRecord",
+        "ClearanceDocument",
+        "TravelHealthRecord",
+        "Compendium",
+        "DispenseRecord",
+        "ImmunizationRecord",
+        "RelatedPerson",
+    ] {
+        assert!(
+            report.passed.iter().any(|(_, passed)| *passed == resource_type),
+            "no {} fixture checked",
+            resource_type
+        );
+    }
+}
+
+#[test]
+fn unknown_fields_are_reported() {
+    let json = r#"{"id":"med-1","patientId":"p-1","medication":{"system":"s","code":"c"},
+        "dosage":{"value":5,"unit":"mg"},"route":{"system":"s","code":"c"},
+        "startDate":"2024-01-01","colour":"blue"}"#;
+    match conformance::check::<wellally::MedicationRecord>(json) {
+        Err(ConformanceError::Mismatch { pointer, actual, .. }) => {
+            assert_eq!(pointer, "/colour");
+            assert!(actual.is_empty());
+        }
+        other => panic!("expected mismatch, got {:?}", other),
+    }
+}
@@ -0,0 +1,176 @@
+//! Checks consent records and consent-aware bundle filtering.
+
+use chrono::NaiveDate;
+use serde_json::json;
+use wellally::catalog::{ACT_CODE, LOINC};
+use wellally::consent::{self, AccessOutcome, ConsentFilter, ConsentProvision, BEHAVIORAL_HEALTH, HIV};
+use wellally::io::{AnyResource, Bundle};
+use wellally::{Coding, ConsentDecision, ConsentRecord, ConsentStatus, Period, Purpose, Reference, Resource, Validate};
+
+mod common;
+
+const EXAMPLE: &str =
+    include_str!("../../../infrastructure/schemas/consent/examples/consent.research-no-mental-health.json");
+const RESEARCHER: &str = "Organization/research-1";
+
+fn day() -> NaiveDate {
+    "2024-06-01".parse().unwrap()
+}
+
+fn consent() -> ConsentRecord {
+    ConsentRecord::from_json(EXAMPLE).unwrap()
+}
+
+fn lab_report(patient_id: &str) -> AnyResource {
+    let result = |code: &str, display: &str| {
+        json!({
+            "code": {"coding": [{"system": "http://loinc.org", "code": code, "display": display}]},
+            "value": {"value": 1, "unit": "{index}"}
+        })
+    };
+    let report = json!({
+        "id": format!("lab-{}", patient_id), "patientId": patient_id, "issuedAt": "2024-05-20T08:40:00Z",
+        "results": [result("2093-3", "Cholesterol"), result("75622-1", "HIV 1 and 2 tests - Meaning")]
+    });
+    AnyResource::from_typed_json_value("LabReport", &report).unwrap()
+}
+
+fn depression(patient_id: &str) -> AnyResource {
+    let condition = json!({
+        "id": format!("cond-{}", patient_id), "patientId": patient_id, "clinicalStatus": "active",
+        "code": {"coding": [{"system": "http://snomed.info/sct", "code": "35489007", "display": "Depressive disorder"}]}
+    });
+    AnyResource::from_typed_json_value("Condition", &condition).unwrap()
+}
+
+fn hypertension(patient_id: &str) -> AnyResource {
+    let condition = json!({
+        "id": format!("htn-{}", patient_id), "patientId": patient_id, "clinicalStatus": "active",
+        "code": {"coding": [{"system": "http://snomed.info/sct", "code": "38341003", "display": "Hypertension"}]}
+    });
+    AnyResource::from_typed_json_value("Condition", &condition).unwrap()
+}
+
+fn ids(bundle: &Bundle) -> Vec<&str> {
+    bundle.resources.iter().map(AnyResource::id).collect()
+}
+
+#[test]
+fn published_example_round_trips() {
+    let (consent, _) = common::round_trip::<ConsentRecord>(EXAMPLE);
+    assert_eq!(consent.status, ConsentStatus::Active);
+    assert!(consent.is_in_force(day()));
+    assert!(!consent.is_in_force("2030-01-01".parse().unwrap()));
+}
+
+#[test]
+fn validates_periods_and_provisions() {
+    let mut consent = consent();
+    let provisions = consent.provisions.as_mut().unwrap();
+    provisions[0].purposes = Some(Vec::new());
+    provisions[1].period = Some(Period { start: Some(day()), end: "2024-01-01".parse().ok() });
+    let paths: Vec<_> = consent.validate().into_iter().map(|issue| issue.path).collect();
+    assert_eq!(paths, ["provisions[0].purposes", "provisions[1].period.end"]);
+}
+
+#[test]
+fn research_excludes_mental_health_and_redacts_hiv_results() {
+    let bundle = Bundle::new(vec![lab_report("patient-123"), depression("patient-123"), hypertension("patient-123")]);
+    let consents = [consent()];
+    let filtered = ConsentFilter::new(&consents).with_date(day()).apply(&bundle, Purpose::Research, RESEARCHER);
+
+    assert_eq!(ids(&filtered.bundle), ["lab-patient-123", "htn-patient-123"]);
+    let AnyResource::LabReport(report) = &filtered.bundle.resources[0] else { panic!("not a lab report") };
+    assert_eq!(report.results.len(), 1);
+    assert_eq!(report.results[0].code.coding[0].code, "2093-3");
+
+    let [lab, condition, shared] = filtered.decisions.as_slice() else { panic!("{:?}", filtered.decisions) };
+    assert_eq!(lab.outcome, AccessOutcome::Redacted { paths: vec!["results[1]".into()] });
+    assert_eq!(lab.categories, [Coding { system: ACT_CODE.into(), code: HIV.into(), display: None }]);
+    assert_eq!(lab.consents, ["consent-1"]);
+    assert_eq!(condition.outcome, AccessOutcome::Excluded);
+    assert_eq!(condition.categories[0].code, BEHAVIORAL_HEALTH);
+    assert_eq!(shared.outcome, AccessOutcome::Permitted);
+    assert!(shared.categories.is_empty());
+}
+
+#[test]
+fn treatment_shares_everything_the_base_decision_permits() {
+    let bundle = Bundle::new(vec![lab_report("patient-123"), depression("patient-123")]);
+    let consents = [consent()];
+    let filter = ConsentFilter::new(&consents).with_date(day());
+    let filtered = filter.apply(&bundle, Purpose::Treatment, "Practitioner/dr-li");
+    assert_eq!(filtered.bundle, bundle);
+    assert!(filtered.decisions.iter().all(|decision| decision.outcome == AccessOutcome::Permitted));
+}
+
+#[test]
+fn patients_without_consent_follow_the_default() {
+    let bundle = Bundle::new(vec![hypertension("patient-123"), hypertension("p-2")]);
+    let consents = [consent()];
+    let filtered = ConsentFilter::new(&consents).with_date(day()).apply(&bundle, Purpose::Research, RESEARCHER);
+    assert_eq!(ids(&filtered.bundle), ["htn-patient-123"]);
+    assert!(filtered.decisions[1].consents.is_empty());
+
+    let filtered = ConsentFilter::new(&consents)
+        .with_date(day())
+        .with_default(ConsentDecision::Permit)
+        .apply(&bundle, Purpose::Research, RESEARCHER);
+    assert_eq!(filtered.bundle, bundle);
+}
+
+#[test]
+fn provisions_match_actor_resource_type_and_period() {
+    let mut consent = ConsentRecord::new("consent-2", "p-1", ConsentDecision::Deny);
+    consent.provisions = Some(vec![ConsentProvision {
+        decision: ConsentDecision::Permit,
+        purposes: None,
+        actors: Some(vec![Reference { reference: RESEARCHER.into(), display: None }]),
+        categories: None,
+        resource_types: Some(vec!["Condition".into()]),
+        period: Some(Period { start: None, end: "2024-12-31".parse().ok() }),
+    }]);
+    let bundle = Bundle::new(vec![hypertension("p-1"), lab_report("p-1")]);
+    let consents = [consent];
+
+    let filter = ConsentFilter::new(&consents).with_date(day());
+    assert_eq!(ids(&filter.apply(&bundle, Purpose::Research, RESEARCHER).bundle), ["htn-p-1"]);
+    let filtered = filter.apply(&bundle, Purpose::Research, "Organization/other");
+    assert!(filtered.bundle.resources.is_empty());
+    let later = "2025-01-01".parse().unwrap();
+    let filtered = ConsentFilter::new(&consents).with_date(later).apply(&bundle, Purpose::Research, RESEARCHER);
+    assert!(filtered.bundle.resources.is_empty());
+}
+
+#[test]
+fn revoked_consents_and_custom_classifiers() {
+    let mut revoked = consent();
+    revoked.status = ConsentStatus::Inactive;
+    let bundle = Bundle::new(vec![hypertension("patient-123")]);
+    assert!(consent::filter(&bundle, &[revoked], Purpose::Treatment, "Practitioner/dr-li").bundle.resources.is_empty());
+
+    let consents = [consent()];
+    let hypertension_is_sensitive = |coding: &Coding| {
+        let category = Coding { system: ACT_CODE.into(), code: BEHAVIORAL_HEALTH.into(), display: None };
+        (coding.code == "38341003").then_some(category)
+    };
+    let filtered = ConsentFilter::new(&consents)
+        .with_date(day())
+        .with_classifier(hypertension_is_sensitive)
+        .apply(&bundle, Purpose::Research, RESEARCHER);
+    assert_eq!(filtered.decisions[0].outcome, AccessOutcome::Excluded);
+    assert_eq!(consent::sensitivity(&Coding { system: LOINC.into(), code: "2093-3".into(), display: None }), None);
+}
+
+#[test]
+fn decision_log_serializes_with_the_outcome() {
+    let bundle = Bundle::new(vec![lab_report("patient-123")]);
+    let consents = [consent()];
+    let filtered = ConsentFilter::new(&consents).with_date(day()).apply(&bundle, Purpose::Research, RESEARCHER);
+    let log = serde_json::to_value(&filtered.decisions[0]).unwrap();
+    assert_eq!(log["outcome"], "redacted");
+    assert_eq!(log["paths"], json!(["results[1]"]));
+    assert_eq!(log["resourceType"], "LabReport");
+    assert_eq!(log["consents"], json!(["consent-1"]));
+    assert_eq!(ConsentRecord::RESOURCE_TYPE, "ConsentRecord");
+}
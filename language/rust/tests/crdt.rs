@@ -0,0 +1,106 @@
+//! Checks that replicas of a record edited on several devices merge to the same record.
+
+use serde_json::json;
+use wellally::crdt::{LwwRegister, Mergeable, OrSet, Stamp};
+use wellally::{Annotation, MedicationRecord, Resource};
+
+fn stamp(minute: u32, device: &str) -> Stamp {
+    Stamp::new(format!("2024-11-02T09:{:02}:00Z", minute).parse().unwrap(), device)
+}
+
+fn medication() -> MedicationRecord {
+    MedicationRecord::from_json_value(&json!({
+        "id": "med-1",
+        "patientId": "p-1",
+        "medication": {"system": "http://www.nlm.nih.gov/research/umls/rxnorm", "code": "308182"},
+        "dosage": {"value": 500, "unit": "mg"},
+        "route": {"system": "http://snomed.info/sct", "code": "26643006"},
+        "startDate": "2024-11-01",
+        "notes": [{"text": "Take with food"}]
+    }))
+    .unwrap()
+}
+
+fn with_notes(record: &MedicationRecord, notes: &[&str]) -> MedicationRecord {
+    MedicationRecord { notes: Some(notes.iter().map(|text| Annotation::new(*text)).collect()), ..record.clone() }
+}
+
+fn texts(record: &MedicationRecord) -> Vec<String> {
+    record.notes.iter().flatten().map(|note| note.text.clone()).collect()
+}
+
+#[test]
+fn registers_keep_the_latest_edit() {
+    let mut register = LwwRegister::new("a", stamp(0, "phone"));
+    register.set("b", stamp(5, "phone"));
+    register.set("stale", stamp(3, "tablet"));
+    assert_eq!(register.value, "b");
+
+    let tie = LwwRegister::new("c", stamp(5, "watch"));
+    let mut merged = register.clone();
+    merged.merge(&tie);
+    let mut other = tie.clone();
+    other.merge(&register);
+    assert_eq!(merged, other);
+    assert_eq!(merged.value, "c");
+}
+
+#[test]
+fn observed_remove_sets_keep_concurrent_additions() {
+    let mut phone = OrSet::new();
+    phone.add("walk", &stamp(0, "phone"));
+    let mut tablet = phone.clone();
+
+    assert!(phone.remove(&"walk"));
+    tablet.add("walk", &stamp(2, "tablet"));
+    tablet.add("swim", &stamp(2, "tablet"));
+
+    let mut merged = phone.clone();
+    merged.merge(&tablet);
+    tablet.merge(&phone);
+    assert_eq!(merged, tablet);
+    assert_eq!(merged.values(), [&"walk", &"swim"]);
+}
+
+#[test]
+fn concurrent_offline_edits_merge_in_any_order() {
+    let record = medication();
+    let base = Mergeable::new(&record, &stamp(0, "server"), &["notes"]).unwrap();
+
+    let mut phone = base.clone();
+    let notes = ["Take with food", "Missed evening dose"];
+    let edited = MedicationRecord { frequency: Some("BID".into()), ..with_notes(&record, &notes) };
+    phone.update(&edited, &stamp(10, "phone")).unwrap();
+
+    let mut tablet = base.clone();
+    let edited = MedicationRecord { frequency: Some("TID".into()), ..with_notes(&record, &["Nausea after dose"]) };
+    tablet.update(&edited, &stamp(5, "tablet")).unwrap();
+
+    let mut one = phone.clone();
+    one.merge(&tablet);
+    let mut two = tablet.clone();
+    two.merge(&phone);
+    assert_eq!(one, two);
+
+    let merged = one.value().unwrap();
+    assert_eq!(merged.frequency.as_deref(), Some("BID"));
+    assert_eq!(merged.id, "med-1");
+    assert_eq!(texts(&merged), ["Nausea after dose", "Missed evening dose"]);
+    assert_eq!(one.stamp("frequency"), Some(&stamp(10, "phone")));
+    assert_eq!(one.stamp("dosage"), Some(&stamp(0, "server")));
+}
+
+#[test]
+fn cleared_fields_stay_cleared_and_replicas_serialize() {
+    let record = medication();
+    let mut replica = Mergeable::new(&record, &stamp(0, "server"), &["notes"]).unwrap();
+    replica.update(&MedicationRecord { notes: None, ..record.clone() }, &stamp(1, "phone")).unwrap();
+    assert_eq!(replica.value().unwrap().notes, None);
+
+    let json = serde_json::to_string(&replica).unwrap();
+    let back: Mergeable<MedicationRecord> = serde_json::from_str(&json).unwrap();
+    assert_eq!(back, replica);
+
+    let err = Mergeable::new(&"text", &stamp(0, "server"), &[]).unwrap_err();
+    assert!(err.to_string().contains("must serialize to an object"), "{}", err);
+}
@@ -0,0 +1,68 @@
+//! Checks data source metadata and the filters that keep manual or flagged data out of analytics.
+
+use serde_json::json;
+use wellally::cgm::GlucoseSeries;
+use wellally::vitals::BloodPressure;
+use wellally::{
+    exclude_manual, exclude_unreliable, CollectionMethod, DataSource, HasDataSource, QualityFlag, Reliability, Resource,
+};
+
+fn sourced(id: &str, source: Option<DataSource>) -> GlucoseSeries {
+    let start = "2024-10-01T00:00:00Z".parse().unwrap();
+    GlucoseSeries { source, ..GlucoseSeries::from_samples(id, "p-1", "mg/dL", start, 300, &[Some(110.0)]) }
+}
+
+fn method(method: CollectionMethod) -> Option<DataSource> {
+    Some(DataSource { collection_method: Some(method), ..Default::default() })
+}
+
+#[test]
+fn excludes_manual_entries_only() {
+    let records = [
+        sourced("sensor", method(CollectionMethod::Automatic)),
+        sourced("typed", method(CollectionMethod::Manual)),
+        sourced("unknown", None),
+    ];
+    let kept: Vec<&str> = exclude_manual(&records).into_iter().map(|record| record.id.as_str()).collect();
+    assert_eq!(kept, ["sensor", "unknown"]);
+    assert!(records[1].is_manual());
+}
+
+#[test]
+fn excludes_unreliable_and_flagged_data() {
+    let rated = |reliability| Some(DataSource { reliability: Some(reliability), ..Default::default() });
+    let flagged = Some(DataSource { quality_flags: Some(vec![QualityFlag::MotionArtifact]), ..Default::default() });
+    let records = [
+        sourced("high", rated(Reliability::High)),
+        sourced("medium", rated(Reliability::Medium)),
+        sourced("low", rated(Reliability::Low)),
+        sourced("flagged", flagged),
+        sourced("unrated", Some(DataSource::default())),
+    ];
+    let kept: Vec<&str> = exclude_unreliable(&records, Reliability::Medium).into_iter().map(|record| record.id.as_str()).collect();
+    assert_eq!(kept, ["high", "medium", "unrated"]);
+}
+
+#[test]
+fn blood_pressure_readings_carry_their_source() {
+    let cuff = BloodPressure { source: method(CollectionMethod::Automatic), ..BloodPressure::mm_hg(122.0, 78.0) };
+    let diary = BloodPressure { source: method(CollectionMethod::Manual), ..BloodPressure::mm_hg(150.0, 95.0) };
+    let readings = [cuff, diary];
+    assert_eq!(exclude_manual(&readings), [&readings[0]]);
+}
+
+#[test]
+fn source_round_trips_in_json() {
+    let series = GlucoseSeries::from_json_value(&json!({
+        "id": "cgm-1", "patientId": "p-1", "unit": "mg/dL", "start": "2024-10-01T00:00:00Z",
+        "intervalSeconds": 300, "values": [110],
+        "source": {"deviceId": "SN-1", "app": "LibreLink", "collectionMethod": "automatic",
+                   "reliability": "high", "qualityFlags": ["warm-up"]}
+    }))
+    .unwrap();
+    let source = series.data_source().unwrap();
+    assert_eq!(source.device_id.as_deref(), Some("SN-1"));
+    assert_eq!(source.quality_flags, Some(vec![QualityFlag::WarmUp]));
+    assert!(source.is_flagged() && !source.is_manual());
+    assert_eq!(serde_json::to_value(&series).unwrap()["source"]["collectionMethod"], "automatic");
+}
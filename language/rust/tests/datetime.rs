@@ -0,0 +1,86 @@
+//! Tests for partial and lenient dates and date-times.
+
+use chrono::{NaiveDate, TimeZone, Utc};
+use serde_json::json;
+use wellally::{DatePrecision, FlexibleDate, FlexibleDateTime, LabReport, Person};
+
+fn date(value: &str) -> FlexibleDate {
+    value.parse().unwrap()
+}
+
+fn date_time(value: &str) -> FlexibleDateTime {
+    value.parse().unwrap()
+}
+
+#[test]
+fn partial_dates_keep_their_precision() {
+    let year = date("2023");
+    assert_eq!((year.precision(), year.date()), (DatePrecision::Year, NaiveDate::from_ymd_opt(2023, 1, 1).unwrap()));
+    assert_eq!(year.last_date(), NaiveDate::from_ymd_opt(2023, 12, 31).unwrap());
+    assert_eq!(year.to_string(), "2023");
+
+    let month = date("2024-02");
+    assert_eq!(month.precision(), DatePrecision::Month);
+    assert_eq!(month.last_date(), NaiveDate::from_ymd_opt(2024, 2, 29).unwrap());
+    assert_eq!(month, FlexibleDate::from_year_month(2024, 2).unwrap());
+    assert_eq!(serde_json::to_value(month).unwrap(), json!("2024-02"));
+}
+
+#[test]
+fn lenient_separators_serialize_canonically() {
+    for input in ["2023/05/12", "2023.05.12", "20230512", " 2023-05-12 "] {
+        assert_eq!(date(input).to_string(), "2023-05-12", "{}", input);
+    }
+    assert_eq!(date("2023/5").to_string(), "2023-05");
+    for input in ["23-05-12", "2023-13", "2023-02-30", "2023-05-12-01", "May 2023"] {
+        assert!(input.parse::<FlexibleDate>().is_err(), "{}", input);
+    }
+}
+
+#[test]
+fn date_times_without_timezone_stay_local() {
+    let local = date_time("2023-05-12 08:30");
+    assert_eq!(local.offset(), None);
+    assert_eq!(local.to_string(), "2023-05-12T08:30:00");
+    assert_eq!(local.to_utc(), Utc.with_ymd_and_hms(2023, 5, 12, 8, 30, 0).unwrap());
+    assert_eq!(local.in_utc(), local);
+
+    let partial = date_time("2023-05");
+    assert_eq!((partial.precision(), partial.to_string()), (DatePrecision::Month, "2023-05".into()));
+    assert!("2023-05T08:30".parse::<FlexibleDateTime>().is_err());
+}
+
+#[test]
+fn offsets_are_kept_in_every_notation() {
+    for input in ["2023-05-12T08:30:00+08:00", "2023-05-12T08:30:00+0800", "2023-05-12t08:30:00+08"] {
+        let value = date_time(input);
+        assert_eq!(value.to_string(), "2023-05-12T08:30:00+08:00", "{}", input);
+        assert_eq!(value, Utc.with_ymd_and_hms(2023, 5, 12, 0, 30, 0).unwrap());
+    }
+    assert_eq!(date_time("2023-05-12T08:30:00.250z").to_string(), "2023-05-12T08:30:00.250Z");
+    assert!("2023-05-12T08:30:00+8".parse::<FlexibleDateTime>().is_err());
+}
+
+#[test]
+fn non_ascii_offsets_are_rejected() {
+    for input in ["2023-05-12T08:30+1é1", "2023-05-12T08:30-é", "2023-05-12T08:30+08:é"] {
+        let err = input.parse::<FlexibleDateTime>().unwrap_err();
+        assert!(err.to_string().contains("invalid UTC offset"), "{}: {}", input, err);
+    }
+    let json = json!({"id": "lab-1", "patientId": "p-1", "issuedAt": "2023-05-12T08:30+1é1", "results": []});
+    assert!(serde_json::from_value::<LabReport>(json).is_err());
+}
+
+#[test]
+fn resources_accept_partial_dates() {
+    let person: Person = serde_json::from_value(json!({"id": "p-1", "name": [], "birthDate": "1960"})).unwrap();
+    assert_eq!(person.birth_date.precision(), DatePrecision::Year);
+    assert_eq!(serde_json::to_value(&person).unwrap()["birthDate"], "1960");
+
+    let json = json!({"id": "lab-1", "patientId": "p-1", "issuedAt": "2024/11/02 09:30", "results": []});
+    let report: LabReport = serde_json::from_value(json).unwrap();
+    assert_eq!(serde_json::to_value(&report).unwrap()["issuedAt"], "2024-11-02T09:30:00");
+
+    let err = serde_json::from_value::<Person>(json!({"id": "p-1", "name": [], "birthDate": "sometime"})).unwrap_err();
+    assert!(err.to_string().contains("invalid date"), "{}", err);
+}
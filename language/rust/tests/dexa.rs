@@ -0,0 +1,108 @@
+//! Checks bone densitometry measurements and WHO classification.
+
+use wellally::imaging_report::dexa::{DexaReport, DexaSite, FraxInputs, SiteDensity, WhoClassification};
+use wellally::imaging_report::ImagingMeasurements;
+use wellally::{Gender, ImagingReport, ModalityCode, Quantity, Resource, Validate};
+
+mod common;
+
+const EXAMPLE: &str = include_str!("../../../infrastructure/schemas/imaging-report/examples/imaging-report.dexa.json");
+
+fn dexa(report: &mut ImagingReport) -> &mut DexaReport {
+    match report.measurements.as_mut().unwrap() {
+        ImagingMeasurements::Dexa(dexa) => dexa,
+        other => panic!("not a DEXA: {other:?}"),
+    }
+}
+
+fn density(site: DexaSite, t_score: f64) -> SiteDensity {
+    let mut density = SiteDensity::new(site, Quantity::new(0.8, "g/cm2"));
+    density.t_score = Some(t_score);
+    density
+}
+
+#[test]
+fn published_example_round_trips() {
+    let (mut report, written) = common::round_trip::<ImagingReport>(EXAMPLE);
+    assert_eq!(report.measurements.as_ref().unwrap().modality(), ModalityCode::BMD);
+    assert_eq!(written["measurements"]["type"], "dexa");
+    assert_eq!(written["measurements"]["sites"][0]["tScore"], -2.7);
+    assert_eq!(written["measurements"]["frax"]["parentFracturedHip"], true);
+
+    let dexa = dexa(&mut report);
+    assert_eq!(dexa.lowest_t_score(), Some(-2.7));
+    assert_eq!(dexa.who_classification(), Some(WhoClassification::Osteoporosis));
+    assert!(!dexa.below_expected_for_age());
+    assert_eq!(dexa.frax_bmd(), Some(&Quantity::new(0.701, "g/cm2")));
+    let frax = dexa.frax.as_ref().unwrap();
+    assert_eq!(frax.risk_factor_count(), 1);
+    assert!((frax.bmi().unwrap() - 52.0 / (1.56 * 1.56)).abs() < 1e-9);
+}
+
+#[test]
+fn classifies_the_lowest_diagnostic_t_score() {
+    assert_eq!(WhoClassification::of(-0.4), WhoClassification::Normal);
+    assert_eq!(WhoClassification::of(-1.0), WhoClassification::Normal);
+    assert_eq!(WhoClassification::of(-1.1), WhoClassification::Osteopenia);
+    assert_eq!(WhoClassification::of(-2.5), WhoClassification::Osteoporosis);
+
+    let mut dexa = DexaReport { sites: vec![density(DexaSite::TotalBody, -3.0)], frax: None };
+    assert_eq!(dexa.who_classification(), None, "total body is not diagnostic");
+    dexa.sites.push(density(DexaSite::TotalHip, -0.5));
+    dexa.sites.push(density(DexaSite::Radius33, -1.4));
+    assert_eq!(dexa.who_classification(), Some(WhoClassification::Osteopenia));
+
+    dexa.sites[1].z_score = Some(-2.0);
+    assert!(dexa.below_expected_for_age());
+    assert_eq!(DexaReport::default().lowest_t_score(), None);
+}
+
+#[test]
+fn frax_inputs_count_risk_factors() {
+    let mut frax = FraxInputs::new(70, Gender::Male);
+    assert_eq!((frax.risk_factor_count(), frax.bmi()), (0, None));
+    frax.glucocorticoids = true;
+    frax.current_smoking = true;
+    frax.weight = Some(Quantity::new(154.0, "[lb_av]"));
+    frax.height = Some(Quantity::new(70.0, "[in_i]"));
+    assert_eq!(frax.risk_factor_count(), 2);
+    assert!((frax.bmi().unwrap() - 22.1).abs() < 0.05);
+}
+
+#[test]
+fn validation_reports_bad_dexa_measurements() {
+    let mut report = ImagingReport::from_json(EXAMPLE).unwrap();
+    let dexa = dexa(&mut report);
+    dexa.sites[0].bmd = Quantity::new(0.812, "g/cm3");
+    dexa.sites[1].t_score = Some(f64::NAN);
+    dexa.sites.push(density(DexaSite::FemoralNeck, -1.0));
+    let frax = dexa.frax.as_mut().unwrap();
+    frax.age = 35;
+    frax.height = Some(Quantity::new(156.0, "kg"));
+    report.modality.code = ModalityCode::DX;
+    let paths: Vec<_> = report.validate().into_iter().map(|issue| issue.path).collect();
+    assert_eq!(
+        paths,
+        [
+            "measurements.type",
+            "measurements.sites[0].bmd.unit",
+            "measurements.sites[1].tScore",
+            "measurements.sites[3].site",
+            "measurements.frax.age",
+            "measurements.frax.height.unit",
+        ]
+    );
+}
+
+#[test]
+fn frax_inputs_accept_snake_case_risk_factors() {
+    let frax: FraxInputs = serde_json::from_str(
+        r#"{"age":72,"sex":"female","previous_fracture":true,"parent_fractured_hip":true,"alcohol_three_or_more_units":true}"#,
+    )
+    .unwrap();
+    assert!(frax.previous_fracture && frax.parent_fractured_hip && frax.alcohol_three_or_more_units);
+    assert_eq!(frax.risk_factor_count(), 3);
+    let written = serde_json::to_value(&frax).unwrap();
+    assert_eq!(written["parentFracturedHip"], true);
+    assert!(written.get("parent_fractured_hip").is_none());
+}
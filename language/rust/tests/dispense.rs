@@ -0,0 +1,86 @@
+//! Checks dispense records and refill projections.
+
+use chrono::{Days, NaiveDate};
+use wellally::dispense::{self, DispenseRecord};
+use wellally::{Quantity, Reference, Resource, Validate};
+
+mod common;
+
+const EXAMPLE: &str = include_str!("../../../infrastructure/schemas/dispense/examples/dispense.refill.json");
+
+fn prescription(id: &str) -> Reference {
+    Reference { reference: format!("MedicationRecord/{id}"), display: None }
+}
+
+fn fill(id: &str, on: &str, fill_number: u32, refills_remaining: u32) -> DispenseRecord {
+    let mut fill =
+        DispenseRecord::new(id, "p-1", prescription("med-1"), on.parse().unwrap(), Quantity::new(30.0, "{tbl}"), 30);
+    fill.fill_number = fill_number;
+    fill.refills_remaining = refills_remaining;
+    fill
+}
+
+#[test]
+fn published_example_round_trips() {
+    let (record, written) = common::round_trip::<DispenseRecord>(EXAMPLE);
+    assert_eq!(record.fill_number, 2);
+    assert_eq!(record.prescription.target(), Some(("MedicationRecord", "med-2024-03-01-atorvastatin")));
+    assert_eq!(written["daysSupply"], 30);
+    assert_eq!(written["refillsRemaining"], 3);
+}
+
+#[test]
+fn projects_refill_due_dates() {
+    let record = DispenseRecord::from_json(EXAMPLE).unwrap();
+    assert_eq!(record.supply_end().unwrap().to_string(), "2024-05-01");
+    let due: Vec<_> = record.refill_due_dates().iter().map(|date| date.to_string()).collect();
+    assert_eq!(due, ["2024-05-02", "2024-06-01", "2024-07-01"]);
+    assert_eq!(record.next_refill_due().unwrap().to_string(), "2024-05-02");
+    assert!(!record.is_final_fill());
+
+    let last = fill("rx-4", "2024-07-01", 4, 0);
+    assert!(last.is_final_fill());
+    assert!(last.refill_due_dates().is_empty());
+    assert_eq!(last.next_refill_due(), None);
+}
+
+#[test]
+fn orders_the_fills_of_a_prescription() {
+    let mut other = fill("rx-x", "2024-03-05", 1, 0);
+    other.prescription = prescription("med-2");
+    let records =
+        [fill("rx-3", "2024-05-01", 3, 1), other, fill("rx-1", "2024-03-01", 1, 3), fill("rx-2", "2024-04-01", 2, 2)];
+    let fills = dispense::fills_of(&records, &prescription("med-1"));
+    assert_eq!(fills.iter().map(|fill| fill.id.as_str()).collect::<Vec<_>>(), ["rx-1", "rx-2", "rx-3"]);
+}
+
+#[test]
+fn validation_reports_impossible_fills() {
+    let mut record = fill("rx-1", "2024-03-01", 0, 0);
+    record.days_supply = 0;
+    record.quantity.value = 0.0;
+    record.prescription = Reference { reference: "DispenseRecord/rx-0".into(), display: None };
+    let issues = record.validate();
+    let paths: Vec<_> = issues.iter().map(|issue| issue.path.as_str()).collect();
+    assert_eq!(paths, ["prescription.reference", "quantity.value", "daysSupply", "fillNumber"]);
+    assert_eq!(issues[0].message, "must reference a MedicationRecord");
+
+    let mut record = fill("rx-1", "2024-03-01", 1, 100);
+    record.days_supply = 4_000_000_000;
+    let paths: Vec<_> = record.validate().into_iter().map(|issue| issue.path).collect();
+    assert_eq!(paths, ["daysSupply", "refillsRemaining"]);
+}
+
+#[test]
+fn projections_stop_at_the_end_of_the_calendar() {
+    let mut record = fill("rx-1", "2024-03-01", 1, 3);
+    record.days_supply = 4_000_000_000;
+    assert_eq!(record.supply_end(), None);
+    assert!(record.refill_due_dates().is_empty());
+    assert_eq!(record.next_refill_due(), None);
+
+    record.dispensed_on = NaiveDate::MAX - Days::new(45);
+    record.days_supply = 30;
+    assert_eq!(record.supply_end(), Some(NaiveDate::MAX - Days::new(16)));
+    assert_eq!(record.refill_due_dates(), [NaiveDate::MAX - Days::new(15)]);
+}
@@ -0,0 +1,72 @@
+//! Checks the ECG record: waveform scaling, interval corrections and validation.
+
+use wellally::catalog::{concept, SNOMED_CT};
+use wellally::ecg::{EcgIntervals, EcgLead, EcgLeadName, EcgRecord, ATRIAL_FIBRILLATION, SINUS_RHYTHM};
+use wellally::{Attachment, Resource, Validate};
+
+fn watch() -> EcgRecord {
+    let mut record = EcgRecord::new("ecg-1", "p-1", "2024-10-01T08:15:00Z".parse().unwrap(), 500.0);
+    record.leads.push(EcgLead { factor: Some(2.5), ..EcgLead::microvolts(EcgLeadName::I, vec![0, 400, -40, 4]) });
+    record
+}
+
+#[test]
+fn scales_samples_to_millivolts() {
+    let record = watch();
+    let lead = record.lead(EcgLeadName::I).unwrap();
+    assert_eq!(lead.millivolts(), Some(vec![0.0, 1.0, -0.1, 0.01]));
+    assert_eq!(record.lead(EcgLeadName::V1), None);
+    assert_eq!(record.duration_seconds(), Some(4.0 / 500.0));
+
+    let referenced = EcgLead { lead: EcgLeadName::II, data: None, factor: None, attachment: Some(Attachment::default()) };
+    assert_eq!(referenced.millivolts(), None);
+}
+
+#[test]
+fn corrects_the_qt_interval() {
+    let intervals = EcgIntervals { heart_rate: Some(60.0), qt_interval: Some(400.0), ..Default::default() };
+    assert_eq!(intervals.rr_seconds(), Some(1.0));
+    assert_eq!(intervals.qtc_bazett(), Some(400.0));
+    assert_eq!(intervals.qtc_fridericia(), Some(400.0));
+
+    let fast = EcgIntervals { heart_rate: Some(120.0), ..intervals };
+    assert!((fast.qtc_bazett().unwrap() - 565.69).abs() < 0.01);
+    assert!((fast.qtc_fridericia().unwrap() - 503.97).abs() < 0.01);
+    assert_eq!(EcgIntervals { heart_rate: None, ..fast }.qtc_bazett(), None);
+}
+
+#[test]
+fn finds_the_rhythm() {
+    let mut record = watch();
+    assert!(!record.has_finding(SINUS_RHYTHM));
+    record.interpretation = Some(vec![concept(SNOMED_CT, ATRIAL_FIBRILLATION, "Atrial fibrillation")]);
+    assert!(record.has_finding(ATRIAL_FIBRILLATION));
+}
+
+#[test]
+fn twelve_lead_json_round_trips() {
+    let json = r#"{"id":"ecg-2","patientId":"p-1","recordedAt":"2024-10-02T10:00:00+08:00","samplingRate":500,
+        "leads":[{"lead":"II","data":[1,2,3]},{"lead":"aVR","attachment":{"url":"https://pacs.example/ecg-2.xml"}},
+                 {"lead":"V6","data":[3,2,1],"factor":4.88}],
+        "intervals":{"heartRate":64,"prInterval":168,"qrsDuration":88,"qtInterval":392,"qtcInterval":398}}"#;
+    let record = EcgRecord::from_json(json).unwrap();
+    assert_eq!(record.leads[1].lead, EcgLeadName::AVR);
+    assert_eq!(record.intervals.as_ref().unwrap().qrs_duration, Some(88.0));
+    assert!(record.validate().is_empty(), "{:?}", record.validate());
+    let written = serde_json::to_value(&record).unwrap();
+    assert_eq!(written["leads"][1]["lead"], "aVR");
+    assert_eq!(EcgRecord::from_json_value(&written).unwrap(), record);
+}
+
+#[test]
+fn validation_reports_unusable_leads_and_intervals() {
+    let mut record = watch();
+    record.sampling_rate = 0.0;
+    record.leads.push(EcgLead { lead: EcgLeadName::I, data: None, factor: Some(-1.0), attachment: None });
+    record.intervals = Some(EcgIntervals { qt_interval: Some(-5.0), ..Default::default() });
+    let paths: Vec<_> = record.validate().into_iter().map(|issue| issue.path).collect();
+    assert_eq!(paths, ["samplingRate", "leads[1].lead", "leads[1]", "leads[1].factor", "intervals.qtInterval"]);
+
+    let empty = EcgRecord::new("ecg-3", "p-1", "2024-10-01".parse().unwrap(), 250.0);
+    assert_eq!(empty.validate()[0].path, "leads");
+}
@@ -0,0 +1,80 @@
+//! Checks structured echocardiography measurements.
+
+use wellally::imaging_report::echo::{
+    EchoReport, EjectionFractionCategory, SegmentWallMotion, Valve, ValveAssessment, ValveSeverity, WallMotion,
+};
+use wellally::imaging_report::ImagingMeasurements;
+use wellally::{ImagingReport, ModalityCode, Quantity, Resource, Validate};
+
+mod common;
+
+const EXAMPLE: &str = include_str!("../../../infrastructure/schemas/imaging-report/examples/imaging-report.echo.json");
+
+fn echo(report: &mut ImagingReport) -> &mut EchoReport {
+    match report.measurements.as_mut().unwrap() {
+        ImagingMeasurements::Echocardiography(echo) => echo,
+        other => panic!("not an echo: {other:?}"),
+    }
+}
+
+#[test]
+fn published_example_round_trips() {
+    let (mut report, written) = common::round_trip::<ImagingReport>(EXAMPLE);
+    assert_eq!(report.measurements.as_ref().unwrap().modality(), ModalityCode::US);
+    assert_eq!(written["measurements"]["type"], "echocardiography");
+    assert_eq!(written["measurements"]["valves"][0]["regurgitation"], "moderate");
+    assert_eq!(written["measurements"]["wallMotion"][16]["motion"], "akinetic");
+
+    let echo = echo(&mut report);
+    assert_eq!(echo.ejection_fraction_category(), Some(EjectionFractionCategory::Reduced));
+    assert_eq!(echo.chambers.as_ref().unwrap().measured().len(), 6);
+    assert_eq!(echo.valve(Valve::Aortic).unwrap().worst(), Some(ValveSeverity::Mild));
+    assert!(echo.valve(Valve::Pulmonic).is_none());
+    let significant: Vec<_> = echo.significant_valve_disease().iter().map(|valve| valve.valve).collect();
+    assert_eq!(significant, [Valve::Mitral]);
+    assert_eq!(echo.wall_motion_score_index(), Some(23.0 / 17.0));
+}
+
+#[test]
+fn ejection_fraction_categories_follow_the_universal_definition() {
+    use EjectionFractionCategory::*;
+    let percents = [25.0, 40.0, 40.5, 49.9, 50.0, 65.0];
+    let categories: Vec<_> = percents.into_iter().map(EjectionFractionCategory::of).collect();
+    assert_eq!(categories, [Reduced, Reduced, MildlyReduced, MildlyReduced, Preserved, Preserved]);
+    assert_eq!(EchoReport::default().ejection_fraction_category(), None);
+    assert_eq!(EchoReport::default().wall_motion_score_index(), None);
+}
+
+#[test]
+fn valve_grades_order_from_none_to_severe() {
+    let mut stenotic = ValveAssessment::new(Valve::Aortic);
+    assert_eq!(stenotic.worst(), None);
+    stenotic.stenosis = Some(ValveSeverity::Severe);
+    stenotic.regurgitation = Some(ValveSeverity::Trace);
+    assert_eq!(stenotic.worst(), Some(ValveSeverity::Severe));
+    assert!(ValveSeverity::None < ValveSeverity::Trace && ValveSeverity::Moderate < ValveSeverity::Severe);
+    let echo = EchoReport { valves: vec![stenotic, ValveAssessment::new(Valve::Mitral)], ..EchoReport::default() };
+    assert_eq!(echo.significant_valve_disease().len(), 1);
+}
+
+#[test]
+fn validation_reports_bad_echo_measurements() {
+    let mut report = ImagingReport::from_json(EXAMPLE).unwrap();
+    let echo = echo(&mut report);
+    echo.lv_ejection_fraction = Some(138.0);
+    echo.chambers.as_mut().unwrap().ivsd = Some(Quantity::new(9.0, "mL"));
+    echo.valves.push(ValveAssessment::new(Valve::Mitral));
+    echo.wall_motion.push(SegmentWallMotion::new(18, WallMotion::Normal));
+    echo.wall_motion.push(SegmentWallMotion::new(17, WallMotion::Normal));
+    let paths: Vec<_> = report.validate().into_iter().map(|issue| issue.path).collect();
+    assert_eq!(
+        paths,
+        [
+            "measurements.lvEjectionFraction",
+            "measurements.chambers.ivsd.unit",
+            "measurements.valves[3].valve",
+            "measurements.wallMotion[17].segment",
+            "measurements.wallMotion[18].segment",
+        ]
+    );
+}
@@ -0,0 +1,89 @@
+//! Checks effective instants and periods on results and the time series built on them.
+
+use chrono::NaiveDate;
+use wellally::catalog::{concept, LOINC};
+use wellally::vitals::BloodPressure;
+use wellally::{time_series, Effective, FlexibleDateTime, LabReport, LabResult, LabValue, Period, Quantity, Specimen};
+
+const STEPS: &str = "41950-7";
+
+fn date(text: &str) -> NaiveDate {
+    text.parse().unwrap()
+}
+
+fn week(start: &str, end: &str) -> Effective {
+    Effective::Period(Period { start: Some(date(start)), end: Some(date(end)) })
+}
+
+fn steps(value: f64, effective: Option<Effective>) -> LabResult {
+    LabResult {
+        code: concept(LOINC, STEPS, "Number of steps in 24 hour Measured"),
+        value: LabValue::Quantity(Quantity::new(value, "/d")),
+        reference_range: None,
+        interpretation: None,
+        method: None,
+        effective,
+    }
+}
+
+fn report(id: &str, issued_at: &str, results: Vec<LabResult>) -> LabReport {
+    serde_json::from_value(serde_json::json!({ "id": id, "patientId": "p-1", "issuedAt": issued_at, "results": [] }))
+        .map(|report: LabReport| LabReport { results, ..report })
+        .unwrap()
+}
+
+#[test]
+fn serializes_instants_as_strings_and_periods_as_objects() {
+    let instant: Effective = serde_json::from_str(r#""2024-10-01T08:00:00+08:00""#).unwrap();
+    assert!(matches!(instant, Effective::Instant(_)));
+    assert_eq!(instant.first_date(), Some(date("2024-10-01")));
+
+    let json = r#"{"start":"2024-09-23","end":"2024-09-29"}"#;
+    let period: Effective = serde_json::from_str(json).unwrap();
+    assert_eq!(period, week("2024-09-23", "2024-09-29"));
+    assert_eq!(serde_json::to_string(&period).unwrap(), json);
+
+    let result: LabResult = serde_json::from_value(serde_json::json!({
+        "code": { "coding": [{ "system": LOINC, "code": STEPS }] },
+        "value": { "value": 8400, "unit": "/d" },
+        "effective": { "start": "2024-09-23", "end": "2024-09-29" }
+    }))
+    .unwrap();
+    assert_eq!(result.effective, Some(period));
+    assert!(!serde_json::to_string(&steps(1.0, None)).unwrap().contains("effective"));
+}
+
+#[test]
+fn overlaps_with_open_ends() {
+    let period = week("2024-09-23", "2024-09-29");
+    assert!(period.overlaps(&Period { start: Some(date("2024-09-29")), end: None }));
+    assert!(!period.overlaps(&Period { start: Some(date("2024-09-30")), end: None }));
+    assert!(period.overlaps(&Period { start: None, end: Some(date("2024-09-23")) }));
+    assert!(!period.overlaps(&Period { start: None, end: Some(date("2024-09-22")) }));
+    assert!(Effective::Period(Period { start: None, end: None }).overlaps(&Period { start: None, end: None }));
+}
+
+#[test]
+fn time_series_orders_by_effective_time_with_fallbacks() {
+    let weekly = report("weekly", "2024-10-01T09:00:00Z", vec![steps(8400.0, Some(week("2024-09-23", "2024-09-29")))]);
+    let issued = report("issued", "2024-09-20T09:00:00Z", vec![steps(7000.0, None)]);
+    let mut collected = report("collected", "2024-10-05T09:00:00Z", vec![steps(9100.0, None)]);
+    collected.specimen =
+        Some(Specimen { specimen_type: None, collected_at: Some("2024-09-25T07:00:00Z".parse::<FlexibleDateTime>().unwrap()) });
+
+    let reports = [weekly, issued, collected];
+    let series = time_series(&reports, STEPS);
+    let ids: Vec<&str> = series.iter().map(|point| point.report_id).collect();
+    assert_eq!(ids, ["issued", "weekly", "collected"]);
+    assert_eq!(series[1].effective, week("2024-09-23", "2024-09-29"));
+    assert_eq!(series[2].effective.first_date(), Some(date("2024-09-25")));
+    assert!(time_series(&reports, "8480-6").is_empty());
+}
+
+#[test]
+fn blood_pressure_keeps_its_effective_time() {
+    let averaged = BloodPressure { effective: Some(week("2024-09-23", "2024-09-29")), ..BloodPressure::mm_hg(131.0, 83.0) };
+    let results = averaged.to_results();
+    assert!(results.iter().all(|result| result.effective == averaged.effective));
+    assert_eq!(BloodPressure::from_results(&results), Some(averaged));
+}
@@ -0,0 +1,66 @@
+//! Checks that errors name the resource type, field path and offending value.
+
+use serde_json::json;
+use wellally::wire;
+use wellally::{Gender, Interpretation, LabReport, Person, Resource, WellAllyError};
+
+const REPORT: &str = r#"{"id":"lab-1","patientId":"p-1","issuedAt":"2024-11-02T09:30:00Z","results":[
+    {"code":{"coding":[{"system":"http://loinc.org","code":"2339-0"}]},"value":{"value":95,"unit":"mg/dL"}},
+    {"code":{"coding":[{"system":"http://loinc.org","code":"2345-7"}]},"value":{"value":"high","unit":"mg/dL"}}]}"#;
+
+#[test]
+fn parse_errors_carry_path_and_value() {
+    let err = LabReport::from_json(REPORT).unwrap_err();
+    assert_eq!(err.resource_type(), "LabReport");
+    assert_eq!(err.path(), "results[1].value");
+    assert_eq!(err.value(), Some(&json!({"value": "high", "unit": "mg/dL"})));
+    assert!(err.to_string().starts_with("LabReport at results[1].value: "), "{}", err);
+}
+
+#[test]
+fn missing_fields_are_named() {
+    let err = Person::from_json_value(&json!({"id": "p-1", "name": [{"family": "Li", "given": ["Wei"]}]})).unwrap_err();
+    assert_eq!(err.path(), "birthDate");
+    assert_eq!(err.value(), None);
+}
+
+#[test]
+fn syntax_errors_have_no_path() {
+    let err = LabReport::from_json(r#"{"id":"lab-1",}"#).unwrap_err();
+    assert!(matches!(err, WellAllyError::Parse { .. }));
+    assert_eq!(err.path(), "");
+    assert!(err.message().contains("line 1"), "{}", err);
+}
+
+#[test]
+fn validation_errors_carry_offending_value() {
+    let mut report = LabReport::from_json(&REPORT.replace(r#""high""#, "140")).unwrap();
+    report.patient_id = " ".to_string();
+    let errors = report.validation_errors();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].path(), "patientId");
+    assert_eq!(errors[0].value(), Some(&json!(" ")));
+    assert_eq!(errors[0].to_string(), r#"LabReport at patientId: must not be empty (got " ")"#);
+}
+
+#[test]
+fn unknown_codes_are_terminology_errors() {
+    assert_eq!("H".parse::<Interpretation>(), Ok(Interpretation::H));
+    assert_eq!("female".parse::<Gender>(), Ok(Gender::Female));
+    match "XX".parse::<Interpretation>() {
+        Err(WellAllyError::Terminology { resource_type, system, value, .. }) => {
+            assert_eq!(resource_type, "Interpretation");
+            assert_eq!(system, "http://terminology.hl7.org/CodeSystem/v3-ObservationInterpretation");
+            assert_eq!(value.as_deref(), Some(&json!("XX")));
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn wire_errors_use_canonical_paths() {
+    let err = wire::from_wire::<LabReport>(&json!({"i": "lab-1", "p": "p-1", "ia": "2024-11-02", "r": [{"c": 1}]}))
+        .unwrap_err();
+    assert_eq!(err.resource_type(), "LabReport");
+    assert_eq!(err.path(), "results[0].code");
+}
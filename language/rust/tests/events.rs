@@ -0,0 +1,93 @@
+//! Tests for the record-change event envelope.
+
+use std::path::PathBuf;
+
+use chrono::{TimeZone, Utc};
+use serde_json::{json, Value};
+use wellally::events::{EventType, RecordEvent, EVENT_VERSION, SCHEMA_ID};
+use wellally::io::AnyResource;
+
+fn task() -> AnyResource {
+    serde_json::from_value(json!({
+        "id": "task-1",
+        "patientId": "p-1",
+        "status": "requested",
+        "description": "Repeat CBC"
+    }))
+    .unwrap()
+}
+
+fn occurred_at() -> chrono::DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 11, 2, 9, 30, 0).unwrap()
+}
+
+#[test]
+fn serializes_canonically() {
+    let event = RecordEvent::updated(task()).with_occurred_at(occurred_at()).with_trace_id("trace-1");
+    assert_eq!(
+        event.to_canonical_json().unwrap(),
+        concat!(
+            r#"{"eventType":"updated","eventVersion":"1.0.0","id":"task-1","occurredAt":"2024-11-02T09:30:00Z","#,
+            r#""resource":{"description":"Repeat CBC","id":"task-1","patientId":"p-1","resourceType":"Task","status":"requested"},"#,
+            r#""resourceType":"Task","traceId":"trace-1"}"#
+        )
+    );
+    assert_eq!(RecordEvent::from_json(&event.to_canonical_json().unwrap()).unwrap(), event);
+}
+
+#[test]
+fn deletes_carry_no_resource() {
+    let event = RecordEvent::deleted("LabReport", "lab-1").with_occurred_at(occurred_at());
+    assert_eq!(event.key(), "LabReport/lab-1");
+    let json = event.to_canonical_json().unwrap();
+    assert!(!json.contains("resource\""));
+    let received = RecordEvent::from_json(&json).unwrap();
+    assert_eq!(received.event_type, EventType::Deleted);
+    assert!(received.resource.is_none());
+}
+
+#[test]
+fn accepts_newer_minor_versions_with_unknown_fields() {
+    let json = json!({
+        "eventVersion": "1.3.0",
+        "eventType": "created",
+        "resourceType": "Task",
+        "id": "task-1",
+        "resource": serde_json::to_value(task()).unwrap(),
+        "occurredAt": "2024-11-02T09:30:00Z",
+        "source": "ehr-a"
+    });
+    let event = RecordEvent::from_json(&json.to_string()).unwrap();
+    assert_eq!(event.resource, Some(task()));
+}
+
+#[test]
+fn rejects_incompatible_or_inconsistent_events() {
+    let event = serde_json::to_value(RecordEvent::created(task())).unwrap();
+    let broken = |field: &str, value: Value| {
+        let mut event = event.clone();
+        event[field] = value;
+        RecordEvent::from_json(&event.to_string()).unwrap_err()
+    };
+    assert_eq!(broken("eventVersion", json!("2.0.0")).path(), "eventVersion");
+    assert_eq!(broken("id", json!("task-2")).path(), "id");
+    assert_eq!(broken("resourceType", json!("Flag")).path(), "resource.status");
+    assert_eq!(broken("resource", Value::Null).path(), "resource");
+    assert_eq!(broken("resource", json!({"id": "task-1"})).path(), "resource.patientId");
+}
+
+#[test]
+fn published_schema_matches_the_envelope() {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("../../infrastructure/schemas/record-event/schema/record-event.schema.json");
+    let schema: Value = serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+    assert_eq!(schema["$id"], SCHEMA_ID);
+    assert!(SCHEMA_ID.ends_with(&EVENT_VERSION.to_string()));
+
+    let event = serde_json::to_value(RecordEvent::created(task()).with_trace_id("trace-1")).unwrap();
+    let mut fields: Vec<_> = event.as_object().unwrap().keys().collect();
+    let mut properties: Vec<_> = schema["properties"].as_object().unwrap().keys().collect();
+    fields.sort();
+    properties.sort();
+    assert_eq!(fields, properties);
+}
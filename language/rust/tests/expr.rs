@@ -0,0 +1,109 @@
+//! Checks FHIRPath-like expression parsing and evaluation.
+
+use serde_json::{json, Value};
+use wellally::expr::{evaluate, Expression};
+use wellally::{LabReport, WellAllyError};
+
+fn report() -> LabReport {
+    serde_json::from_value(json!({
+        "id": "lab-1",
+        "patientId": "p-1",
+        "issuedAt": "2024-11-02T09:30:00Z",
+        "results": [
+            {
+                "code": {"coding": [{"system": "http://loinc.org", "code": "2345-7"}], "text": "Glucose"},
+                "value": {"value": 97, "unit": "mg/dL"},
+                "interpretation": "N"
+            },
+            {
+                "code": {"coding": [{"system": "http://loinc.org", "code": "4548-4"}]},
+                "value": {"value": 7.2, "unit": "%"},
+                "interpretation": "H"
+            }
+        ]
+    }))
+    .unwrap()
+}
+
+fn eval(expression: &str) -> Vec<Value> {
+    evaluate(expression, &report()).unwrap()
+}
+
+#[test]
+fn paths_flatten_arrays_and_skip_absent_fields() {
+    assert_eq!(eval("id"), [json!("lab-1")]);
+    assert_eq!(eval("results.code.coding.code"), [json!("2345-7"), json!("4548-4")]);
+    assert_eq!(eval("results.code.text"), [json!("Glucose")]);
+    assert!(eval("performer.display").is_empty());
+    assert_eq!(eval("results[1].interpretation"), [json!("H")]);
+    assert!(eval("results[5]").is_empty());
+}
+
+#[test]
+fn where_filters_by_criteria() {
+    assert_eq!(eval("results.where(code.coding.code = '4548-4').value.value"), [json!(7.2)]);
+    assert_eq!(eval("results.where(interpretation != 'N').count()"), [json!(1)]);
+    assert_eq!(eval("results.where(value.value > 5 and value.unit = '%').interpretation"), [json!("H")]);
+    assert_eq!(eval("results.code.coding.code.where($this = '2345-7')"), [json!("2345-7")]);
+}
+
+#[test]
+fn functions_and_comparisons() {
+    assert_eq!(eval("results.exists()"), [json!(true)]);
+    assert_eq!(eval("notes.empty()"), [json!(true)]);
+    assert_eq!(eval("results.all(value.unit = 'mg/dL')"), [json!(false)]);
+    assert_eq!(eval("results.exists(interpretation = 'H')"), [json!(true)]);
+    assert_eq!(eval("results.select(code.coding.code).last()"), [json!("4548-4")]);
+    assert_eq!(eval("results.first().value.value = 97.0"), [json!(true)]);
+    assert_eq!(eval("issuedAt >= '2024-01-01'"), [json!(true)]);
+    assert_eq!(eval("(results.count() > 5 or id = 'lab-1') and notes.exists().not()"), [json!(true)]);
+    assert_eq!(eval("results.value.value < -1"), [json!(false)]);
+}
+
+#[test]
+fn matches_uses_truthiness() {
+    let report = report();
+    assert!(Expression::parse("results.where(interpretation = 'H')").unwrap().matches(&report).unwrap());
+    assert!(!Expression::parse("results.where(interpretation = 'L')").unwrap().matches(&report).unwrap());
+    assert!(!Expression::parse("results.exists(value.value > 100)").unwrap().matches(&report).unwrap());
+}
+
+#[test]
+fn syntax_errors_name_the_offset() {
+    for (source, offset) in [
+        ("results.where()", "offset 8"),
+        ("results.frobnicate()", "offset 8"),
+        ("id = 'lab-1", "offset 5"),
+        ("results[x]", "offset 8"),
+        ("id ==", "offset 4"),
+        ("id id", "offset 3"),
+    ] {
+        let err = Expression::parse(source).unwrap_err();
+        assert!(matches!(err, WellAllyError::Parse { .. }), "{}", source);
+        assert!(err.to_string().contains(offset), "{}: {}", source, err);
+    }
+}
+
+#[test]
+fn round_trips_as_its_source() {
+    let expression: Expression = "results.where(interpretation = 'H').count()".parse().unwrap();
+    let json = serde_json::to_value(&expression).unwrap();
+    assert_eq!(json, json!("results.where(interpretation = 'H').count()"));
+    assert_eq!(serde_json::from_value::<Expression>(json).unwrap(), expression);
+    assert!(serde_json::from_value::<Expression>(json!("results.where(")).is_err());
+}
+
+#[test]
+fn backticks_delimit_field_names() {
+    let row = json!({"Test Name": "Glucose", "Result": "97"});
+    let expression = Expression::parse("`Test Name` = 'Glucose' and `Result`.exists()").unwrap();
+    assert!(expression.matches_value(&row));
+    assert!(Expression::parse("`Test Name").is_err());
+}
+
+#[test]
+fn field_names_may_be_non_ascii() {
+    let row = json!({"名称": "x", "naïve": {"x": 1}});
+    assert!(Expression::parse("名称 = 'x'").unwrap().matches_value(&row));
+    assert_eq!(Expression::parse("naïve.x").unwrap().evaluate_value(&row), [json!(1)]);
+}
@@ -0,0 +1,77 @@
+//! Checks that extensions survive serialization and are reachable by URL.
+
+use serde_json::json;
+use wellally::borrowed::LabReportRef;
+use wellally::{Coding, Extensible, Extension, ExtensionValue, LabReport, Resource, Validate};
+
+const WARD: &str = "https://example.org/fhir/ward";
+const FASTING: &str = "https://example.org/fhir/fasting-hours";
+const SITE: &str = "https://example.org/fhir/site";
+
+fn report() -> serde_json::Value {
+    json!({
+        "id": "lab-1",
+        "patientId": "patient-1",
+        "issuedAt": "2024-11-02T09:30:00Z",
+        "results": [{
+            "code": {"coding": [{"system": "http://loinc.org", "code": "2345-7", "display": "Glucose"}]},
+            "value": {"value": 5.4, "unit": "mmol/L"}
+        }],
+        "extensions": [
+            {"url": WARD, "valueString": "4B"},
+            {"url": FASTING, "valueInteger": 10},
+            {"url": SITE, "valueCoding": {"system": "https://example.org/sites", "code": "north"}},
+            {"url": WARD, "valueString": "5A"}
+        ]
+    })
+}
+
+#[test]
+fn extensions_round_trip_losslessly() {
+    let json = report();
+    let report = LabReport::from_json_value(&json).unwrap();
+    assert!(report.is_valid(), "{:?}", report.validate());
+    assert_eq!(serde_json::to_value(&report).unwrap(), json);
+
+    let text = json.to_string();
+    let borrowed: LabReportRef = serde_json::from_str(&text).unwrap();
+    assert_eq!(borrowed.to_owned(), report);
+}
+
+#[test]
+fn typed_access_by_url() {
+    let mut report = LabReport::from_json_value(&report()).unwrap();
+    assert_eq!(report.extension(WARD).and_then(ExtensionValue::as_str), Some("4B"));
+    assert_eq!(report.extension_values(WARD).len(), 2);
+    assert_eq!(report.extension(FASTING).and_then(ExtensionValue::as_decimal), Some(10.0));
+    assert_eq!(report.extension(SITE).and_then(ExtensionValue::as_coding).map(|c| c.code.as_str()), Some("north"));
+    assert_eq!(report.extension(FASTING).and_then(ExtensionValue::as_bool), None);
+
+    report.set_extension(WARD, "7C");
+    assert_eq!(report.extension_values(WARD), [&ExtensionValue::String("7C".into())]);
+    assert_eq!(report.all_extensions()[0].url, WARD);
+
+    report.add_extension(FASTING, 12i64);
+    assert_eq!(report.remove_extension(FASTING).len(), 2);
+    report.remove_extension(WARD);
+    report.remove_extension(SITE);
+    assert!(report.extensions.is_none());
+    assert!(serde_json::to_value(&report).unwrap().get("extensions").is_none());
+}
+
+#[test]
+fn malformed_extensions_are_reported() {
+    let mut report = LabReport::from_json_value(&report()).unwrap();
+    report.extensions = Some(vec![
+        Extension::new("ward", "4B"),
+        Extension::new(SITE, Coding { system: "".into(), code: "north".into(), display: None }),
+    ]);
+    let paths: Vec<_> = report.validate().into_iter().map(|issue| issue.path).collect();
+    assert_eq!(paths, ["extensions[0].url", "extensions[1].valueCoding.system"]);
+
+    assert!(LabReport::from_json_value(&json!({
+        "id": "lab-1", "patientId": "p-1", "issuedAt": "2024-11-02", "results": [],
+        "extensions": [{"url": WARD}]
+    }))
+    .is_err());
+}
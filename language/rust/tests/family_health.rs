@@ -0,0 +1,198 @@
+//! Checks family health tree statistics, onset ages, anonymized sharing, re-rooting and hydration from Person records.
+
+use serde_json::json;
+use wellally::catalog::{coding, SNOMED_CT};
+use wellally::{ConditionOnset, FamilyHealthTree, Person, RelationToProband, Resource, Validate};
+
+const EXAMPLE: &str =
+    include_str!("../../../infrastructure/schemas/family-health/examples/family-tree.three-generation.json");
+const MINIMAL: &str = include_str!("../../../infrastructure/schemas/family-health/examples/family-tree.min.json");
+
+#[test]
+fn stats_count_relations_conditions_and_generations() {
+    let tree = FamilyHealthTree::from_json(EXAMPLE).unwrap();
+    assert!(tree.validate().is_empty(), "{:?}", tree.validate());
+    let stats = tree.stats();
+    assert_eq!(stats.members, 6);
+    assert_eq!(stats.by_relation[&RelationToProband::Mother], 1);
+    assert_eq!(stats.by_relation[&RelationToProband::Grandparent], 1);
+    assert!(!stats.by_relation.contains_key(&RelationToProband::Child));
+    assert_eq!(stats.generations, 3);
+
+    let codes: Vec<_> = stats.conditions.iter().map(|c| (c.condition.code.as_str(), c.affected)).collect();
+    assert_eq!(codes, [("254837009", 3), ("44054006", 1), ("59621000", 1)]);
+    assert_eq!(stats.conditions[0].mean_onset_age, Some(43.5));
+    assert_eq!(stats.conditions[2].mean_onset_age, None);
+    assert_eq!(stats.mean_onset_age, Some(139.0 / 3.0));
+
+    let written = serde_json::to_value(&stats).unwrap();
+    assert_eq!(written["byRelation"]["grandparent"], 1);
+    assert_eq!(written["conditions"][0]["meanOnsetAge"], 43.5);
+}
+
+#[test]
+fn stats_of_a_tree_without_onsets() {
+    let mut tree = FamilyHealthTree::from_json(MINIMAL).unwrap();
+    let stats = tree.stats();
+    assert_eq!((stats.members, stats.generations, stats.mean_onset_age), (4, 2, None));
+    assert_eq!(stats.conditions.len(), 2);
+
+    tree.members.retain(|member| member.relation_to_proband == RelationToProband::Other);
+    assert_eq!(tree.stats().generations, 0);
+}
+
+#[test]
+fn validation_reports_bad_onsets() {
+    let mut tree = FamilyHealthTree::from_json(MINIMAL).unwrap();
+    tree.members[1].condition_onsets = vec![
+        ConditionOnset { condition: coding(SNOMED_CT, "59621000", "Hypertension"), age_at_onset: 150 },
+        ConditionOnset { condition: coding(SNOMED_CT, "44054006", "Diabetes mellitus type 2"), age_at_onset: 40 },
+    ];
+    let paths: Vec<_> = tree.validate().into_iter().map(|issue| issue.path).collect();
+    assert_eq!(paths, ["members[1].conditionOnsets[0].ageAtOnset", "members[1].conditionOnsets[1].condition"]);
+}
+
+#[test]
+fn anonymized_copy_keeps_only_relations_sex_and_conditions() {
+    let tree = FamilyHealthTree::from_json(EXAMPLE).unwrap();
+    let shared = tree.anonymized();
+    assert_eq!(shared.proband_id, "member-1");
+    let ids: Vec<_> = shared.members.iter().map(|member| member.id.as_str()).collect();
+    assert_eq!(ids, ["member-1", "member-2", "member-3", "member-4", "member-5", "member-6"]);
+    let years: Vec<_> = shared.members.iter().map(|member| member.birth_year.unwrap()).collect();
+    assert_eq!(years, [1980, 1960, 1950, 1960, 1930, 1990]);
+    let grandmother = &shared.members[4];
+    assert_eq!(grandmother.relation_to_proband, RelationToProband::Grandparent);
+    assert_eq!((grandmother.deceased, grandmother.notes.as_ref()), (None, None));
+    assert_eq!(grandmother.conditions.as_ref().unwrap()[0].coding[0].code, "254837009");
+    assert_eq!(grandmother.conditions.as_ref().unwrap()[0].text, None);
+    assert!(shared.members.iter().all(|member| member.condition_onsets.is_empty()));
+    assert!(shared.validate().is_empty(), "{:?}", shared.validate());
+
+    let written = serde_json::to_string(&shared).unwrap();
+    assert!(!written.contains("person-002"));
+    assert_eq!(shared.stats().by_relation, tree.stats().by_relation);
+}
+
+#[test]
+fn anonymized_copy_keeps_uncoded_condition_text() {
+    let mut tree = FamilyHealthTree::from_json(MINIMAL).unwrap();
+    tree.proband_id = "someone-else".into();
+    let concept = &mut tree.members[1].conditions.as_mut().unwrap()[0];
+    concept.coding.clear();
+    let shared = tree.anonymized();
+    assert_eq!(shared.proband_id, "proband");
+    assert_eq!(shared.members[1].conditions.as_ref().unwrap()[0].text.as_deref(), Some("高血压"));
+}
+
+fn relations(tree: &FamilyHealthTree) -> Vec<(&str, RelationToProband)> {
+    tree.members.iter().map(|member| (member.id.as_str(), member.relation_to_proband)).collect()
+}
+
+#[test]
+fn rebase_recomputes_relations_from_parent_links() {
+    use RelationToProband::*;
+    let tree = FamilyHealthTree::from_json(EXAMPLE).unwrap();
+    assert_eq!(tree.rebase("person-002").unwrap(), tree);
+
+    let brother = tree.rebase("person-002-brother").unwrap();
+    assert_eq!(brother.proband_id, "person-002-brother");
+    assert_eq!(
+        relations(&brother),
+        [
+            ("person-002", Sibling),
+            ("person-002-mother", Mother),
+            ("person-002-father", Father),
+            ("person-002-aunt", Aunt),
+            ("person-002-grandmother", Grandparent),
+            ("person-002-brother", Self_),
+        ]
+    );
+    assert_eq!(brother.members[1].conditions, tree.members[1].conditions);
+
+    let mother = tree.rebase("person-002-mother").unwrap();
+    assert_eq!(
+        relations(&mother),
+        [
+            ("person-002", Child),
+            ("person-002-mother", Self_),
+            ("person-002-father", Other),
+            ("person-002-aunt", Sibling),
+            ("person-002-grandmother", Mother),
+            ("person-002-brother", Child),
+        ]
+    );
+    let grandmother = tree.rebase("person-002-grandmother").unwrap();
+    assert_eq!(relations(&grandmother)[0], ("person-002", Grandchild));
+    let aunt = tree.rebase("person-002-aunt").unwrap();
+    assert_eq!(relations(&aunt)[0], ("person-002", Other));
+    assert_eq!(tree.rebase("nobody"), None);
+}
+
+#[test]
+fn rebase_without_links_treats_siblings_as_full_siblings() {
+    use RelationToProband::*;
+    let tree = FamilyHealthTree::from_json(MINIMAL).unwrap();
+    let sister = tree.rebase("person-sis").unwrap();
+    assert_eq!(
+        relations(&sister),
+        [("person-001", Sibling), ("person-mother", Mother), ("person-father", Father), ("person-sis", Self_)]
+    );
+    let mother = tree.rebase("person-mother").unwrap();
+    assert_eq!(
+        relations(&mother),
+        [("person-001", Other), ("person-mother", Self_), ("person-father", Other), ("person-sis", Other)]
+    );
+}
+
+#[test]
+fn cousins_and_parent_link_validation() {
+    let mut tree = FamilyHealthTree::from_json(EXAMPLE).unwrap();
+    let mut cousin = tree.members[5].clone();
+    cousin.id = "person-002-cousin".into();
+    cousin.mother_id = Some("person-002-aunt".into());
+    cousin.father_id = None;
+    tree.members.push(cousin);
+    assert_eq!(tree.rebase("person-002-brother").unwrap().members[6].relation_to_proband, RelationToProband::Cousin);
+    assert_eq!(tree.anonymized().members[6].mother_id.as_deref(), Some("member-4"));
+
+    tree.members[6].father_id = Some("person-002-cousin".into());
+    tree.members[0].mother_id = Some("unknown".into());
+    let paths: Vec<_> = tree.validate().into_iter().map(|issue| issue.path).collect();
+    assert_eq!(paths, ["members[0].motherId", "members[6].fatherId"]);
+}
+
+fn person(id: &str, conditions: serde_json::Value) -> Person {
+    Person::from_json_value(&json!({
+        "id": id, "name": [{"family": "Wang", "given": ["Min"]}], "birthDate": "1960-05-02",
+        "clinicalSummary": {"conditions": conditions}
+    }))
+    .unwrap()
+}
+
+#[test]
+fn hydrate_pulls_conditions_from_linked_persons() {
+    let mut tree = FamilyHealthTree::from_json(EXAMPLE).unwrap();
+    assert_eq!(tree.members[1].person_id.as_deref(), Some("person-003"));
+    let persons = [
+        person("person-003", json!([
+            {"coding": [{"system": SNOMED_CT, "code": "254837009", "display": "Malignant neoplasm of breast"}]},
+            {"coding": [{"system": SNOMED_CT, "code": "59621000", "display": "Hypertension"}]},
+            {"coding": [], "text": "Migraine"}
+        ])),
+        person("person-004", json!([{"coding": [{"system": SNOMED_CT, "code": "73211009"}]}])),
+    ];
+    assert_eq!(tree.hydrate(&persons), 2);
+    let texts: Vec<_> = tree.members[1].conditions.iter().flatten().map(|c| c.text.as_deref().unwrap_or("")).collect();
+    assert_eq!(texts, ["乳腺癌", "", "Migraine"]);
+    assert_eq!(tree.hydrate(&persons), 0);
+    assert_eq!(tree.stats().conditions.iter().find(|c| c.condition.code == "59621000").unwrap().affected, 2);
+
+    tree.members[0].person_id = Some("person-004".into());
+    assert_eq!(tree.hydrate(&persons), 1);
+    assert_eq!(tree.members[0].conditions.as_ref().unwrap()[0].coding[0].code, "73211009");
+    assert_eq!(tree.anonymized().members[0].person_id, None);
+
+    tree.members[0].person_id = Some(" ".into());
+    assert_eq!(tree.validate()[0].path, "members[0].personId");
+}
@@ -0,0 +1,109 @@
+//! Checks flags and the alerts raised from safety checks and lab reports.
+
+use serde_json::json;
+use wellally::catalog::{coding, concept, DRUGS, RXNORM};
+use wellally::io::{parse_auto, AnyResource};
+use wellally::safety::renal::{RenalRule, RenalRules};
+use wellally::safety::{allergy_check, DrugClassMap};
+use wellally::{
+    AllergyIntolerance, Criticality, Flag, FlagCategory, FlagSeverity, FlagStatus, LabReport, Resource, Validate, FLAG_SYSTEM,
+};
+
+mod common;
+
+const EXAMPLE: &str = include_str!("../../../infrastructure/schemas/flag/examples/flag.allergy-conflict.json");
+
+fn amoxicillin() -> wellally::MedicationRecord {
+    let drug = DRUGS.iter().find(|drug| drug.code == "308182").unwrap();
+    drug.record("med-1".into(), "p-1".into(), "2024-10-15".parse().unwrap(), 7)
+}
+
+#[test]
+fn published_example_round_trips() {
+    let (flag, _) = common::round_trip::<Flag>(EXAMPLE);
+    assert!(flag.is_active());
+    assert_eq!(flag.message(), Some("Prescription conflicts with recorded allergy to penicillin G"));
+}
+
+#[test]
+fn validates_period_and_references() {
+    let mut flag = Flag::from_json_value(&json!({
+        "id": "flag-1", "patientId": "p-1", "status": "inactive", "category": "safety",
+        "code": {"coding": [{"system": "http://snomed.info/sct", "code": "129839007", "display": "At risk for falls"}]},
+        "period": {"start": "2024-10-15", "end": "2024-10-01"}, "focus": {"reference": "not a reference"}
+    }))
+    .unwrap();
+    assert_eq!(flag.status, FlagStatus::Inactive);
+    assert_eq!(flag.message(), Some("At risk for falls"));
+    let paths: Vec<_> = flag.validate().into_iter().map(|issue| issue.path).collect();
+    assert!(paths.contains(&"period.end".to_string()), "{:?}", paths);
+    assert!(paths.iter().any(|path| path.starts_with("focus")), "{:?}", paths);
+    flag.period = None;
+    flag.focus = None;
+    assert!(flag.is_valid());
+}
+
+#[test]
+fn allergy_alerts_become_drug_flags() {
+    let mut allergy = AllergyIntolerance::new("al-1", "p-1", concept(RXNORM, "723", "amoxicillin"));
+    allergy.criticality = Some(Criticality::High);
+    let alert = allergy_check(&[allergy], &amoxicillin(), &DrugClassMap::new()).remove(0);
+    let flag = alert.to_flag("flag-1");
+    assert_eq!(flag.patient_id, "p-1");
+    assert_eq!(flag.category, Some(FlagCategory::Drug));
+    assert_eq!(flag.severity, Some(FlagSeverity::Critical));
+    assert_eq!(flag.code.coding[0].system, FLAG_SYSTEM);
+    assert_eq!(flag.code.coding[0].code, "allergy-conflict");
+    assert_eq!(flag.focus.unwrap().reference, "MedicationRecord/med-1");
+    assert_eq!(flag.code.text.as_deref(), Some("Prescription conflicts with recorded allergy to amoxicillin"));
+}
+
+#[test]
+fn renal_advisories_become_drug_flags() {
+    let report = LabReport::from_json_value(&json!({
+        "id": "lab-1", "patientId": "p-1", "issuedAt": "2024-10-01",
+        "results": [{"code": {"coding": [{"system": "http://loinc.org", "code": "98979-8"}]}, "value": {"value": 25, "unit": "mL/min/{1.73_m2}"}}]
+    }))
+    .unwrap();
+    let mut rules = RenalRules::new();
+    rules.register(coding(RXNORM, "723", "amoxicillin"), RenalRule::reduce(30.0, "Give every 12 hours"));
+    let advisory = rules.check(&[report], &[amoxicillin()], "2024-10-16".parse().unwrap()).remove(0);
+    let flag = advisory.to_flag("flag-2");
+    assert_eq!(flag.severity, Some(FlagSeverity::Warning));
+    assert_eq!(flag.code.coding[0].code, "renal-dose-adjustment");
+    assert_eq!(flag.message(), Some("eGFR 25 is below 30: Give every 12 hours"));
+    assert!(flag.is_valid(), "{:?}", flag.validate());
+}
+
+#[test]
+fn critical_lab_values_become_lab_flags() {
+    let report = LabReport::from_json_value(&json!({
+        "id": "lab-9", "patientId": "p-1", "issuedAt": "2024-10-01T08:00:00Z",
+        "results": [
+            {"code": {"coding": [{"system": "http://loinc.org", "code": "2823-3", "display": "Potassium"}]},
+             "value": {"value": 6.9, "unit": "mmol/L"}, "interpretation": "HH"},
+            {"code": {"coding": [{"system": "http://loinc.org", "code": "2951-2", "display": "Sodium"}]},
+             "value": {"value": 139, "unit": "mmol/L"}, "interpretation": "N"}
+        ]
+    }))
+    .unwrap();
+    let flags = report.critical_flags();
+    assert_eq!(flags.len(), 1);
+    assert_eq!(flags[0].id, "lab-9-critical-0");
+    assert_eq!(flags[0].category, Some(FlagCategory::Lab));
+    assert_eq!(flags[0].severity, Some(FlagSeverity::Critical));
+    assert_eq!(flags[0].focus.as_ref().unwrap().reference, "LabReport/lab-9");
+    assert_eq!(flags[0].message(), Some("Critical Potassium result"));
+}
+
+#[test]
+fn flags_without_focus_are_read_by_their_resource_type() {
+    let bare = json!({
+        "id": "flag-2", "patientId": "p-1", "status": "active",
+        "code": {"coding": [{"system": "http://snomed.info/sct", "code": "129839007", "display": "At risk for falls"}]}
+    });
+    assert_eq!(parse_auto(bare.to_string().as_bytes(), None).unwrap_err().message(), "cannot determine resource type");
+    let flag = AnyResource::from(Flag::from_json_value(&bare).unwrap());
+    let parsed = parse_auto(&serde_json::to_vec(&flag).unwrap(), None).unwrap();
+    assert_eq!(parsed.resources, [flag]);
+}
@@ -0,0 +1,88 @@
+//! Checks formulary lookups and substitution suggestions.
+
+use serde_json::json;
+use wellally::catalog::{coding, DRUGS, RXNORM};
+use wellally::formulary::{self, FormularyEntry};
+use wellally::{Coding, MedicationRecord};
+
+fn drug(code: &str) -> Coding {
+    coding(RXNORM, code, code)
+}
+
+fn prescribed(code: &str) -> MedicationRecord {
+    let mut med = DRUGS[0].record("med-1".into(), "p-1".into(), "2024-03-01".parse().unwrap(), 30);
+    med.medication = drug(code);
+    med
+}
+
+fn entry(code: &str, tier: u8, prior_authorization: bool, alternatives: &[&str]) -> FormularyEntry {
+    let mut entry = FormularyEntry::new(drug(code), tier);
+    entry.prior_authorization = prior_authorization;
+    entry.alternatives = alternatives.iter().map(|code| drug(code)).collect();
+    entry
+}
+
+#[test]
+fn entries_use_camel_case_and_skip_defaults() {
+    let mut entry = entry("brand", 3, true, &["generic"]);
+    let written = serde_json::to_value(&entry).unwrap();
+    assert_eq!(written["priorAuthorization"], true);
+    assert_eq!(written["tier"], 3);
+    assert_eq!(serde_json::from_value::<FormularyEntry>(written).unwrap(), entry);
+
+    entry.prior_authorization = false;
+    entry.alternatives.clear();
+    let written = serde_json::to_value(&entry).unwrap();
+    assert_eq!(written, json!({"drug": {"system": RXNORM, "code": "brand", "display": "brand"}, "tier": 3}));
+    assert!(!FormularyEntry::excluded(drug("brand")).is_covered());
+}
+
+#[test]
+fn entries_accept_snake_case_input() {
+    let entry: FormularyEntry = serde_json::from_value(json!({
+        "drug": {"system": RXNORM, "code": "brand"}, "tier": 3, "prior_authorization": true
+    }))
+    .unwrap();
+    assert!(entry.prior_authorization);
+    let written = serde_json::to_value(&entry).unwrap();
+    assert_eq!(written["priorAuthorization"], true);
+    assert_eq!(serde_json::from_value::<FormularyEntry>(written).unwrap(), entry);
+}
+
+#[test]
+fn ranks_entries_by_coverage_tier_and_prior_authorization() {
+    let tier_2 = entry("a", 2, false, &[]);
+    let tier_2_pa = entry("b", 2, true, &[]);
+    let tier_3 = entry("c", 3, false, &[]);
+    let excluded = FormularyEntry::excluded(drug("d"));
+    assert!(tier_2.is_better_than(&tier_3));
+    assert!(tier_2.is_better_than(&tier_2_pa));
+    assert!(!tier_2_pa.is_better_than(&tier_2));
+    assert!(!tier_2.is_better_than(&tier_2));
+    assert!(tier_3.is_better_than(&excluded));
+    assert!(!excluded.is_better_than(&tier_3));
+}
+
+#[test]
+fn suggests_covered_alternatives_on_better_terms() {
+    let list = [
+        entry("brand", 3, true, &["pa-generic", "same-tier", "generic", "unlisted", "pricier"]),
+        entry("pa-generic", 1, true, &[]),
+        entry("same-tier", 3, false, &[]),
+        entry("generic", 1, false, &[]),
+        entry("pricier", 4, false, &[]),
+    ];
+    let suggested = formulary::suggest_alternatives(&prescribed("brand"), &list);
+    let codes: Vec<_> = suggested.iter().map(|entry| entry.drug.code.as_str()).collect();
+    assert_eq!(codes, ["generic", "pa-generic", "same-tier"]);
+
+    assert!(formulary::suggest_alternatives(&prescribed("generic"), &list).is_empty());
+    assert!(formulary::suggest_alternatives(&prescribed("not-listed"), &list).is_empty());
+
+    let mut excluded = FormularyEntry::excluded(drug("brand"));
+    excluded.alternatives = vec![drug("pricier")];
+    let uncovered = [excluded, entry("pricier", 4, false, &[])];
+    let suggested = formulary::suggest_alternatives(&prescribed("brand"), &uncovered);
+    assert_eq!(suggested[0].drug.code, "pricier");
+    assert_eq!(formulary::entry_for(&list, &coding("urn:other", "brand", "")), None);
+}
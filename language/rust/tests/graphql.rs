@@ -0,0 +1,86 @@
+//! Tests for the async-graphql object and input types.
+
+#![cfg(feature = "async-graphql")]
+
+use async_graphql::{EmptySubscription, Object, Request, Schema, Variables};
+use serde_json::json;
+use wellally::{LabReport, Person};
+
+fn report() -> LabReport {
+    serde_json::from_value(json!({
+        "id": "lab-1",
+        "patientId": "p-1",
+        "issuedAt": "2024-11-02T09:30:00Z",
+        "specimen": {"type": {"system": "http://snomed.info/sct", "code": "119297000"}},
+        "results": [
+            {"code": {"coding": [{"system": "http://loinc.org", "code": "2345-7"}]},
+             "value": {"value": 5.4, "unit": "mmol/L"}, "interpretation": "N"},
+            {"code": {"coding": [{"system": "http://loinc.org", "code": "5196-1"}]}, "value": "negative"}
+        ]
+    }))
+    .unwrap()
+}
+
+struct Query;
+
+#[Object]
+impl Query {
+    async fn report(&self) -> LabReport {
+        report()
+    }
+
+    async fn person(&self) -> Person {
+        let person = json!({"id": "p-1", "name": [{"family": "Wang", "given": ["Min"]}], "birthDate": "1960"});
+        serde_json::from_value(person).unwrap()
+    }
+}
+
+struct Mutation;
+
+#[Object]
+impl Mutation {
+    async fn save_report(&self, report: LabReport) -> LabReport {
+        report
+    }
+}
+
+fn schema() -> Schema<Query, Mutation, EmptySubscription> {
+    Schema::new(Query, Mutation, EmptySubscription)
+}
+
+#[test]
+fn emits_object_and_input_types() {
+    let sdl = schema().sdl();
+    for type_name in ["type LabReport", "input LabReportInput", "type Person", "input CodingInput"] {
+        assert!(sdl.contains(type_name), "missing {}", type_name);
+    }
+    assert!(sdl.contains("enum Interpretation"));
+    assert!(sdl.contains("scalar LabValue"));
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn resolves_fields_under_their_json_names() {
+    let query = "{ report { patientId specimen { type { code } } results { interpretation value } } \
+                 person { birthDate } }";
+    let response = schema().execute(query).await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    let data = response.data.into_json().unwrap();
+    assert_eq!(data["report"]["patientId"], "p-1");
+    assert_eq!(data["report"]["specimen"]["type"]["code"], "119297000");
+    assert_eq!(data["report"]["results"][0]["interpretation"], "N");
+    assert_eq!(data["report"]["results"][0]["value"], json!({"value": 5.4, "unit": "mmol/L"}));
+    assert_eq!(data["report"]["results"][1]["value"], "negative");
+    assert_eq!(data["person"]["birthDate"], "1960");
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn accepts_resources_as_input() {
+    let input = serde_json::to_value(report()).unwrap();
+    let mutation = "mutation($report: LabReportInput!) { saveReport(report: $report) { id results { value } } }";
+    let request = Request::new(mutation).variables(Variables::from_json(json!({ "report": input })));
+    let response = schema().execute(request).await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    let data = response.data.into_json().unwrap();
+    assert_eq!(data["saveReport"]["id"], "lab-1");
+    assert_eq!(data["saveReport"]["results"][1]["value"], "negative");
+}
@@ -0,0 +1,23 @@
+//! Checks the coverage of the display-text catalog.
+
+use wellally::catalog::PANELS;
+use wellally::i18n::{unit_label, Locale, LocalizedDisplay};
+use wellally::{Gender, Interpretation, ModalityCode, RelationToProband};
+
+#[test]
+fn catalog_units_have_labels() {
+    for analyte in PANELS.iter().flat_map(|panel| panel.analytes) {
+        for &locale in Locale::ALL {
+            assert!(unit_label(analyte.unit, locale).is_some(), "no {:?} label for {}", locale, analyte.unit);
+        }
+    }
+}
+
+#[test]
+fn enums_are_translated() {
+    assert_eq!(Gender::Female.localized_display(Locale::En), "Female");
+    assert_eq!(Gender::Female.localized_display(Locale::Zh), "女");
+    assert_eq!(ModalityCode::MR.localized_display(Locale::Zh), "磁共振成像");
+    assert_eq!(RelationToProband::Self_.localized_display(Locale::from_tag("zh_Hans_CN")), "本人");
+    assert_eq!(Interpretation::L.localized_display(Locale::from_tag("fr-FR")), "Low");
+}
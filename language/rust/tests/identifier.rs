@@ -0,0 +1,103 @@
+//! Checks identifier lookups, uniqueness and marital status codes on Person.
+
+use serde_json::json;
+use wellally::i18n::{Locale, LocalizedDisplay};
+use wellally::{detect_identifier_collisions, HasIdentifiers, Identifier, IdentifierCollision, IdentifierType, MaritalStatus, Person};
+
+fn person() -> Person {
+    serde_json::from_value(json!({
+        "id": "person-1",
+        "name": [{"family": "Zhang", "given": ["San"]}],
+        "birthDate": "1990-05-12",
+        "identifier": [
+            {"system": "urn:oid:2.16.156", "value": "110101199005120011",
+             "type": {"coding": [{"system": "http://terminology.hl7.org/CodeSystem/v2-0203", "code": "NI"}]}},
+            {"system": "http://hospital.example.org/mrn", "value": "A123456",
+             "type": {"coding": [{"system": "http://terminology.hl7.org/CodeSystem/v2-0203", "code": "MR"}]}}
+        ],
+        "maritalStatus": {"coding": [{"system": "http://terminology.hl7.org/CodeSystem/v3-MaritalStatus", "code": "M"}]}
+    }))
+    .unwrap()
+}
+
+#[test]
+fn finds_identifiers_by_type() {
+    let person = person();
+    assert_eq!(person.mrn().unwrap().value, "A123456");
+    assert_eq!(person.national_id().unwrap().value, "110101199005120011");
+    assert!(person.identifier_of(IdentifierType::Passport).is_none());
+    assert!(Person::default().mrn().is_none());
+
+    let untyped = Identifier { system: "http://hospital.example.org/mrn".into(), value: "A1".into(), r#type: None, period: None };
+    assert_eq!(untyped.identifier_type(), None);
+}
+
+#[test]
+fn typed_identifier_round_trips() {
+    let passport = Identifier::typed(IdentifierType::Passport, "urn:passport", "E12345678");
+    assert_eq!(passport.identifier_type(), Some(IdentifierType::Passport));
+    let json = serde_json::to_value(&passport).unwrap();
+    assert_eq!(json["type"]["coding"][0]["code"], "PPN");
+    assert_eq!(json["type"]["coding"][0]["system"], "http://terminology.hl7.org/CodeSystem/v2-0203");
+
+    for kind in IdentifierType::ALL {
+        assert_eq!(IdentifierType::from_code(kind.code()), Some(kind));
+    }
+    assert_eq!(IdentifierType::from_code("dl"), Some(IdentifierType::DriversLicense));
+    assert_eq!(IdentifierType::from_code("XX"), None);
+}
+
+#[test]
+fn marital_status_codes() {
+    assert_eq!(person().marital_status_code(), Some(MaritalStatus::Married));
+    for status in MaritalStatus::ALL {
+        assert_eq!(MaritalStatus::from_code(status.code()), Some(status));
+        let person = Person { marital_status: Some(status.concept()), ..Person::default() };
+        assert_eq!(person.marital_status_code(), Some(status));
+    }
+    let local = Person {
+        marital_status: Some(serde_json::from_value(json!({"coding": [{"system": "urn:local", "code": "M"}]})).unwrap()),
+        ..Person::default()
+    };
+    assert_eq!(local.marital_status_code(), None);
+    assert_eq!(serde_json::to_value(MaritalStatus::NeverMarried).unwrap(), "S");
+    assert_eq!(MaritalStatus::Widowed.localized_display(Locale::Zh), "丧偶");
+    assert_eq!(IdentifierType::MedicalRecordNumber.localized_display(Locale::En), "Medical record number");
+}
+
+fn with_mrn(id: &str, mrn: &str) -> Person {
+    let mut person = Person { id: id.into(), ..Person::default() };
+    person.add_identifier_unique(Identifier::typed(IdentifierType::MedicalRecordNumber, "http://hospital.example.org/mrn", mrn));
+    person
+}
+
+#[test]
+fn find_and_add_unique() {
+    let mut person = person();
+    assert_eq!(person.find_identifier("http://hospital.example.org/mrn").unwrap().value, "A123456");
+    assert!(person.find_identifier("urn:other").is_none());
+
+    let duplicate = Identifier { system: "http://hospital.example.org/mrn".into(), value: "A123456".into(), r#type: None, period: None };
+    assert!(!person.add_identifier_unique(duplicate));
+    assert_eq!(person.identifiers().len(), 2);
+
+    let mut empty = Person::default();
+    assert!(empty.identifiers().is_empty());
+    assert!(empty.add_identifier_unique(Identifier::typed(IdentifierType::Passport, "urn:passport", "E1")));
+    assert_eq!(empty.identifiers().len(), 1);
+}
+
+#[test]
+fn detects_collisions_between_different_records() {
+    let people = [with_mrn("p-1", "A1"), with_mrn("p-2", "A2"), with_mrn("p-3", "A1"), with_mrn("p-1", "A1")];
+    assert_eq!(
+        detect_identifier_collisions(&people),
+        vec![IdentifierCollision {
+            system: "http://hospital.example.org/mrn".into(),
+            value: "A1".into(),
+            ids: vec!["p-1".into(), "p-3".into()],
+        }]
+    );
+    assert!(detect_identifier_collisions(&people[..2]).is_empty());
+    assert!(detect_identifier_collisions(&[with_mrn("p-1", "A1"), with_mrn("p-1", "A1")]).is_empty());
+}
@@ -0,0 +1,177 @@
+//! Checks imaging report order linkage, appropriateness metadata, structured measurements and prior comparison.
+
+use wellally::catalog::{coding, SNOMED_CT};
+use wellally::imaging_report::measurements::{
+    BiometryMeasurement, FetalBiometry, OrganDimensions, UltrasoundMeasurements,
+};
+use wellally::imaging_report::ImagingMeasurements;
+use wellally::{CodedChange, ImagingReport, ModalityCode, Quantity, Reference, Resource, Validate};
+
+mod common;
+
+const EXAMPLE: &str =
+    include_str!("../../../infrastructure/schemas/imaging-report/examples/imaging-report.ct-chest.json");
+const FOLLOW_UP: &str =
+    include_str!("../../../infrastructure/schemas/imaging-report/examples/imaging-report.ct-chest-followup.json");
+const KIDNEY: &str =
+    include_str!("../../../infrastructure/schemas/imaging-report/examples/imaging-report.ct-kidney.json");
+const OBSTETRIC: &str =
+    include_str!("../../../infrastructure/schemas/imaging-report/examples/imaging-report.us-obstetric.json");
+const ABDOMEN: &str =
+    include_str!("../../../infrastructure/schemas/imaging-report/examples/imaging-report.us-abdomen.json");
+
+fn ultrasound(report: &ImagingReport) -> &UltrasoundMeasurements {
+    match report.measurements.as_ref().unwrap() {
+        ImagingMeasurements::Ultrasound(ultrasound) => ultrasound,
+        other => panic!("not ultrasound: {other:?}"),
+    }
+}
+
+#[test]
+fn published_example_carries_order_and_indications() {
+    let (report, written) = common::round_trip::<ImagingReport>(EXAMPLE);
+    let order = report.based_on.as_ref().unwrap();
+    assert_eq!(order.target(), Some(("ServiceRequest", "order-2024-10-15-007")));
+    assert_eq!(report.reason_code.as_ref().unwrap()[0].coding[0].code, "77176002");
+    assert!(report.clinical_history.as_deref().unwrap().contains("35 包年"));
+    assert_eq!(report.protocol.as_ref().unwrap().coding[0].code, "RPID16");
+    assert_eq!(written["basedOn"]["reference"], "ServiceRequest/order-2024-10-15-007");
+    assert_eq!(written["clinicalHistory"], report.clinical_history.as_deref().unwrap());
+}
+
+#[test]
+fn validation_reports_bad_appropriateness_metadata() {
+    let mut report = ImagingReport::from_json(EXAMPLE).unwrap();
+    report.based_on = Some(Reference { reference: "Referral/ref-1".into(), display: None });
+    report.reason_code.as_mut().unwrap()[0].coding.clear();
+    report.clinical_history = Some(" ".into());
+    report.protocol.as_mut().unwrap().coding[0].code.clear();
+    let paths: Vec<_> = report.validate().into_iter().map(|issue| issue.path).collect();
+    assert_eq!(paths, ["basedOn.reference", "reasonCode[0].coding", "clinicalHistory", "protocol.coding[0].code"]);
+
+    report.based_on = Some(Reference { reference: "order-1".into(), display: None });
+    let paths: Vec<_> = report.validate().into_iter().map(|issue| issue.path).collect();
+    assert_eq!(paths[0], "basedOn.reference");
+}
+
+#[test]
+fn ultrasound_examples_carry_typed_measurements() {
+    let (obstetric, written) = common::round_trip::<ImagingReport>(OBSTETRIC);
+    assert_eq!(obstetric.measurements.as_ref().unwrap().modality(), ModalityCode::US);
+    let biometry = ultrasound(&obstetric).fetal_biometry.as_ref().unwrap();
+    let names: Vec<_> = biometry.lengths().into_iter().map(|(name, _)| name).collect();
+    assert_eq!(names, ["bpd", "hc", "ac", "fl"]);
+    assert_eq!(biometry.ac.as_ref().unwrap().percentile, Some(6.0));
+    assert!(biometry.is_small_for_gestational_age());
+    assert_eq!(written["measurements"]["type"], "ultrasound");
+    assert_eq!(written["measurements"]["fetalBiometry"]["estimatedFetalWeight"]["value"]["unit"], "g");
+
+    let (abdomen, _) = common::round_trip::<ImagingReport>(ABDOMEN);
+    let organs = &ultrasound(&abdomen).organs;
+    assert_eq!(organs.len(), 3);
+    assert!(organs[0].estimated_volume().is_none());
+    let kidney = organs[2].estimated_volume().unwrap();
+    assert_eq!(kidney.unit, "mL");
+    assert!((kidney.value - 10.8 * 5.2 * 4.5 * std::f64::consts::PI / 6.0).abs() < 1e-9);
+}
+
+#[test]
+fn fetal_growth_screen_uses_weight_and_abdomen() {
+    let mut biometry = FetalBiometry::default();
+    assert!(!biometry.is_small_for_gestational_age());
+    biometry.hc = Some(BiometryMeasurement::new(Quantity::new(250.0, "mm"), Some(3.0)));
+    biometry.ac = Some(BiometryMeasurement::new(Quantity::new(262.0, "mm"), None));
+    assert!(!biometry.is_small_for_gestational_age(), "head centile does not count");
+    biometry.estimated_fetal_weight = Some(BiometryMeasurement::new(Quantity::new(1.6, "kg"), Some(9.9)));
+    assert!(biometry.is_small_for_gestational_age());
+
+    let mut bladder = OrganDimensions::new(coding(SNOMED_CT, "89837001", "Urinary bladder structure"));
+    bladder.volume = Some(Quantity::new(350.0, "mL"));
+    bladder.length = Some(Quantity::new(1.0, "cm"));
+    assert_eq!(bladder.estimated_volume(), Some(Quantity::new(350.0, "mL")), "reported volume wins");
+}
+
+#[test]
+fn validation_reports_bad_measurements() {
+    let mut report = ImagingReport::from_json(OBSTETRIC).unwrap();
+    let Some(ImagingMeasurements::Ultrasound(ultrasound)) = report.measurements.as_mut() else {
+        unreachable!("obstetric example carries ultrasound measurements");
+    };
+    let biometry = ultrasound.fetal_biometry.as_mut().unwrap();
+    biometry.gestational_age = Some(Quantity::new(32.0, "mm"));
+    biometry.bpd.as_mut().unwrap().value.unit = "g".into();
+    biometry.fl.as_mut().unwrap().percentile = Some(120.0);
+    biometry.estimated_fetal_weight.as_mut().unwrap().value.unit = "mm".into();
+    let mut spleen = OrganDimensions::new(coding(SNOMED_CT, "", "Spleen"));
+    spleen.width = Some(Quantity::new(4.0, "mL"));
+    spleen.volume = Some(Quantity::new(200.0, "cm"));
+    ultrasound.organs.push(spleen);
+    report.modality.code = ModalityCode::CT;
+    let paths: Vec<_> = report.validate().into_iter().map(|issue| issue.path).collect();
+    assert_eq!(
+        paths,
+        [
+            "measurements.type",
+            "measurements.fetalBiometry.gestationalAge.unit",
+            "measurements.fetalBiometry.bpd.value.unit",
+            "measurements.fetalBiometry.fl.percentile",
+            "measurements.fetalBiometry.estimatedFetalWeight.value.unit",
+            "measurements.organs[0].organ.code",
+            "measurements.organs[0].width.unit",
+            "measurements.organs[0].volume.unit",
+        ]
+    );
+}
+
+#[test]
+fn follow_up_finds_the_prior_of_the_same_exam() {
+    let baseline = ImagingReport::from_json(EXAMPLE).unwrap();
+    let kidney = ImagingReport::from_json(KIDNEY).unwrap();
+    let mut follow_up = ImagingReport::from_json(FOLLOW_UP).unwrap();
+    assert_eq!(follow_up.change_from_prior, Some(CodedChange::Stable));
+    assert!(follow_up.validate().is_empty(), "{:?}", follow_up.validate());
+
+    let mut other_patient = baseline.clone();
+    other_patient.id = "img-other".into();
+    other_patient.patient_id = "person-002".into();
+    let mut earlier = baseline.clone();
+    earlier.id = "img-2023-ct-01".into();
+    earlier.study_instance_uid = Some("1.2.3".into());
+    earlier.reported_at = "2023-10-10T09:00:00+08:00".parse().unwrap();
+    let reports = [earlier.clone(), kidney, other_patient, baseline.clone(), follow_up.clone()];
+
+    assert!(follow_up.is_same_exam(&baseline));
+    assert_eq!(follow_up.prior(&reports), Some(&baseline));
+    let priors: Vec<_> = follow_up.priors(&reports).into_iter().map(|report| report.id.as_str()).collect();
+    assert_eq!(priors, ["img-2024-ct-01", "img-2023-ct-01"]);
+    assert_eq!(baseline.prior(&reports), Some(&earlier));
+    assert_eq!(earlier.prior(&reports), None);
+    assert_eq!(follow_up.compared_reports(&reports), [&baseline]);
+
+    follow_up.comparison_study_uids.clear();
+    assert_eq!(follow_up.link_prior(&reports), Some(&baseline));
+    assert_eq!(follow_up.link_prior(&reports), Some(&baseline));
+    assert_eq!(follow_up.comparison_study_uids, [baseline.study_instance_uid.clone().unwrap()]);
+}
+
+#[test]
+fn validation_reports_bad_comparison_study_uids() {
+    let mut report = ImagingReport::from_json(FOLLOW_UP).unwrap();
+    let own = report.study_instance_uid.clone().unwrap();
+    let prior = report.comparison_study_uids[0].clone();
+    report.comparison_study_uids = vec!["1.02.3".into(), own, prior.clone(), prior, "".into()];
+    let issues: Vec<_> = report.validate().into_iter().map(|issue| (issue.path, issue.message)).collect();
+    assert_eq!(
+        issues,
+        [
+            ("comparisonStudyUids[0]".to_string(), "must be a DICOM UID".to_string()),
+            ("comparisonStudyUids[1]".to_string(), "must not be the report's own study".to_string()),
+            ("comparisonStudyUids[3]".to_string(), "must be unique".to_string()),
+            ("comparisonStudyUids[4]".to_string(), "must be a DICOM UID".to_string()),
+        ]
+    );
+    let written = serde_json::to_value(&report).unwrap();
+    assert_eq!(written["changeFromPrior"], "stable");
+    report.comparison_study_uids.clear();
+    assert!(serde_json::to_value(&report).unwrap().get("comparisonStudyUids").is_none());
+}
@@ -0,0 +1,107 @@
+//! Checks immunization records and lot recall matching.
+
+use serde_json::json;
+use wellally::catalog::coding;
+use wellally::immunization::{self, ImmunizationRecord, VaccineRecall, CVX, MVX};
+use wellally::{Period, Quantity, Validate};
+
+mod common;
+
+const EXAMPLE: &str =
+    include_str!("../../../infrastructure/schemas/immunization/examples/immunization.influenza.json");
+
+fn dose(id: &str, patient: &str, cvx: &str, on: &str, lot: &str) -> ImmunizationRecord {
+    let mut dose = ImmunizationRecord::new(id, patient, coding(CVX, cvx, "vaccine"), on.parse().unwrap());
+    dose.lot_number = Some(lot.into());
+    dose
+}
+
+#[test]
+fn published_example_round_trips() {
+    let (record, written) = common::round_trip::<ImmunizationRecord>(EXAMPLE);
+    assert_eq!(record.lot_number.as_deref(), Some("FL-24A118"));
+    assert_eq!(record.manufacturer.as_ref().unwrap().system, MVX);
+    assert!(!record.is_expired_dose());
+    assert_eq!(written["administeredOn"], "2024-10-03");
+    assert_eq!(written["doseQuantity"]["unit"], "mL");
+}
+
+#[test]
+fn matches_recalled_lots_ignoring_formatting() {
+    let records = [
+        dose("imm-1", "p-1", "150", "2024-10-03", "fl-24a118"),
+        dose("imm-2", "p-2", "150", "2024-10-04", "FL 24A118"),
+        dose("imm-3", "p-3", "150", "2024-10-04", "FL-24A119"),
+        ImmunizationRecord::new("imm-4", "p-4", coding(CVX, "150", "vaccine"), "2024-10-04".parse().unwrap()),
+    ];
+    let recalls = [VaccineRecall::new("recall-1", vec!["FL24A118".into()])];
+    let hits = immunization::recall_check(&records, &recalls);
+    let ids: Vec<_> = hits.iter().map(|hit| hit.record.id.as_str()).collect();
+    assert_eq!(ids, ["imm-1", "imm-2"]);
+    assert!(hits.iter().all(|hit| hit.recall.id == "recall-1"));
+}
+
+#[test]
+fn recall_narrows_by_vaccine_manufacturer_and_window() {
+    let mut flu = dose("imm-1", "p-1", "150", "2024-10-03", "L1");
+    flu.manufacturer = Some(coding(MVX, "SKB", "GlaxoSmithKline"));
+    let hepb = dose("imm-2", "p-1", "08", "2024-10-03", "L1");
+    let late = dose("imm-3", "p-2", "150", "2024-12-01", "L1");
+    let mut other_maker = dose("imm-4", "p-3", "150", "2024-10-05", "L1");
+    other_maker.manufacturer = Some(coding(MVX, "PMC", "sanofi pasteur"));
+    let records = [flu, hepb, late, other_maker];
+
+    let recall: VaccineRecall = serde_json::from_value(json!({
+        "id": "recall-2",
+        "lotNumbers": ["L1"],
+        "vaccine": {"system": CVX, "code": "150"},
+        "administered": {"start": "2024-09-01", "end": "2024-10-31"},
+        "reason": "Loss of potency"
+    }))
+    .unwrap();
+    let ids = |recalls: &[VaccineRecall]| -> Vec<String> {
+        immunization::recall_check(&records, recalls).iter().map(|hit| hit.record.id.clone()).collect()
+    };
+    assert_eq!(ids(std::slice::from_ref(&recall)), ["imm-1", "imm-4"]);
+
+    let mut by_maker = recall.clone();
+    by_maker.manufacturer = Some(coding(MVX, "SKB", "GlaxoSmithKline"));
+    assert_eq!(ids(&[by_maker]), ["imm-1"]);
+
+    let open = VaccineRecall { administered: Some(Period { start: None, end: None }), ..recall };
+    assert_eq!(ids(&[open]), ["imm-1", "imm-3", "imm-4"]);
+}
+
+#[test]
+fn recalls_accept_snake_case_input() {
+    let recall: VaccineRecall = serde_json::from_value(json!({"id": "recall-3", "lot_numbers": ["L1", "L2"]})).unwrap();
+    assert_eq!(recall, VaccineRecall::new("recall-3", vec!["L1".into(), "L2".into()]));
+    let written = serde_json::to_value(&recall).unwrap();
+    assert_eq!(written, json!({"id": "recall-3", "lotNumbers": ["L1", "L2"]}));
+}
+
+#[test]
+fn lists_each_affected_patient_once() {
+    let records = [
+        dose("imm-1", "p-1", "150", "2024-10-03", "A1"),
+        dose("imm-2", "p-2", "150", "2024-10-03", "B2"),
+        dose("imm-3", "p-1", "150", "2024-11-03", "B2"),
+    ];
+    let recalls =
+        [VaccineRecall::new("r-1", vec!["A1".into(), "B2".into()]), VaccineRecall::new("r-2", vec!["B2".into()])];
+    let hits = immunization::recall_check(&records, &recalls);
+    assert_eq!(hits.len(), 5);
+    assert_eq!(immunization::affected_patients(&hits), ["p-1", "p-2"]);
+    assert!(immunization::recall_check(&records, &[]).is_empty());
+}
+
+#[test]
+fn validation_reports_bad_doses() {
+    let mut record = dose("imm-1", "p-1", "150", "2024-10-03", " ");
+    record.dose_number = Some(0);
+    record.expiration_date = Some("2024-09-30".parse().unwrap());
+    record.dose_quantity = Some(Quantity::new(0.0, "mL"));
+    assert!(record.is_expired_dose());
+    let paths: Vec<_> = record.validate().into_iter().map(|issue| issue.path).collect();
+    assert_eq!(paths, ["doseNumber", "lotNumber", "administeredOn", "doseQuantity.value"]);
+}
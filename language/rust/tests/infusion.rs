@@ -0,0 +1,96 @@
+//! Checks infusion records.
+
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use wellally::catalog::{concept, RXNORM, SNOMED_CT};
+use wellally::infusion::{self, InfusionRecord, Interruption, LineType, Titration};
+use wellally::{Coding, Quantity, Resource, Route, Validate};
+
+mod common;
+
+const EXAMPLE: &str = include_str!("../../../infrastructure/schemas/infusion/examples/infusion.norepinephrine.json");
+
+fn at(text: &str) -> DateTime<Utc> {
+    text.parse().unwrap()
+}
+
+fn saline(rate: Quantity) -> InfusionRecord {
+    let medication = Coding { system: RXNORM.into(), code: "313002".into(), display: None };
+    InfusionRecord::new("inf-1", "p-1", medication, Route::intravenous(), "2024-10-03T08:00:00Z".parse().unwrap(), rate)
+}
+
+#[test]
+fn published_example_round_trips() {
+    let (record, written) = common::round_trip::<InfusionRecord>(EXAMPLE);
+    assert_eq!(record.line.as_ref().unwrap().line_type, LineType::CentralVenous);
+    assert_eq!(record.titrations.len(), 2);
+    assert_eq!(written["line"]["type"], "central-venous");
+}
+
+#[test]
+fn follows_titrations_and_interruptions() {
+    let record = InfusionRecord::from_json(EXAMPLE).unwrap();
+    let rate = |text| record.rate_at(at(text)).map(|rate| rate.value);
+    assert_eq!(rate("2024-10-02T23:59:59Z"), None);
+    assert_eq!(rate("2024-10-03T00:30:00Z"), Some(15.0));
+    assert_eq!(rate("2024-10-03T01:00:00Z"), Some(20.0));
+    assert_eq!(rate("2024-10-03T02:45:00Z"), None);
+    assert!(record.is_interrupted_at(at("2024-10-03T02:45:00Z")));
+    assert_eq!(rate("2024-10-03T03:00:00Z"), Some(20.0));
+    assert_eq!(rate("2024-10-03T05:00:00Z"), Some(10.0));
+    assert_eq!(rate("2024-10-03T06:00:00Z"), None);
+
+    assert_eq!(record.running_minutes(), Some(330));
+    assert!((record.volume_infused_ml().unwrap() - 85.0).abs() < 1e-9);
+    assert_eq!(record.is_complete(), Some(false));
+}
+
+#[test]
+fn volume_needs_an_end_and_a_volume_rate() {
+    let mut running = saline(Quantity::new(0.5, "L/h"));
+    running.total_volume = Some(Quantity::new(1.0, "L"));
+    assert_eq!(running.volume_infused_ml(), None);
+    running.end = Some("2024-10-03T10:00:00Z".parse().unwrap());
+    assert!((running.volume_infused_ml().unwrap() - 1000.0).abs() < 1e-9);
+    assert_eq!(running.is_complete(), Some(true));
+
+    let mut dosed = saline(Quantity::new(0.1, "ug/kg/min"));
+    dosed.end = Some("2024-10-03T10:00:00Z".parse().unwrap());
+    assert_eq!(dosed.running_minutes(), Some(120));
+    assert_eq!(dosed.volume_infused_ml(), None);
+
+    assert!(infusion::is_rate("mL/h") && infusion::is_rate("ug/kg/min") && infusion::is_rate("[iU]/h"));
+    assert!(!infusion::is_rate("mL") && !infusion::is_rate("mg/kg"));
+}
+
+#[test]
+fn validation_reports_inconsistent_records() {
+    let mut record = saline(Quantity::new(100.0, "mL"));
+    record.end = Some("2024-10-03T12:00:00Z".parse().unwrap());
+    record.total_volume = Some(Quantity::new(1.0, "g"));
+    record.titrations.push(Titration::new("2024-10-03T13:00:00Z".parse().unwrap(), Quantity::new(-5.0, "mL/h")));
+    record.interruptions.push(Interruption {
+        start: "2024-10-03T09:00:00Z".parse().unwrap(),
+        end: Some("2024-10-03T08:30:00Z".parse().unwrap()),
+        reason: concept(SNOMED_CT, "397797005", "Occlusion of catheter"),
+    });
+    let paths: Vec<_> = record.validate().into_iter().map(|issue| issue.path).collect();
+    assert_eq!(paths, [
+        "rate.unit",
+        "totalVolume.unit",
+        "titrations[0].at",
+        "titrations[0].rate.value",
+        "interruptions[0].end"
+    ]);
+
+    let running: InfusionRecord = serde_json::from_value(json!({
+        "id": "inf-2", "patientId": "p-1",
+        "medication": {"system": RXNORM, "code": "313002"},
+        "route": {"system": SNOMED_CT, "code": "47625008"},
+        "start": "2024-10-03T08:00:00Z", "rate": {"value": 125, "unit": "mL/h"},
+        "interruptions": [{"start": "2024-10-03T07:00:00Z", "reason": {"coding": []}}]
+    }))
+    .unwrap();
+    let paths: Vec<_> = running.validate().into_iter().map(|issue| issue.path).collect();
+    assert_eq!(paths, ["interruptions[0].start", "interruptions[0].reason.coding"]);
+}
@@ -0,0 +1,68 @@
+//! Tests for the interned lab result mirrors.
+
+#![cfg(feature = "intern")]
+
+use std::fs;
+use std::path::PathBuf;
+
+use wellally::intern::{InternedLabResult, Interner, Symbol};
+use wellally::LabReport;
+
+fn results() -> Vec<wellally::LabResult> {
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../infrastructure/schemas/lab-report/examples");
+    fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| fs::read_to_string(entry.unwrap().path()).unwrap())
+        .flat_map(|json| serde_json::from_str::<LabReport>(&json).unwrap().results)
+        .collect()
+}
+
+#[test]
+fn interner_shares_equal_strings() {
+    let interner = Interner::new();
+    assert!(interner.is_empty());
+    let first = interner.intern("http://loinc.org");
+    let second = interner.intern("http://loinc.org");
+    interner.intern("mmol/L");
+    assert_eq!(interner.len(), 2);
+    assert_eq!(first, second);
+    assert!(std::ptr::eq(first.as_str(), second.as_str()));
+    assert_eq!(&*first, "http://loinc.org");
+}
+
+#[test]
+fn deserialized_symbols_come_from_the_global_interner() {
+    let parsed: Vec<Symbol> = serde_json::from_str(r#"["mg/dL","mg/dL"]"#).unwrap();
+    let created = Symbol::new("mg/dL");
+    assert!(std::ptr::eq(parsed[0].as_str(), parsed[1].as_str()));
+    assert!(std::ptr::eq(parsed[0].as_str(), created.as_str()));
+    assert_eq!(serde_json::to_string(&created).unwrap(), r#""mg/dL""#);
+}
+
+#[test]
+fn interned_results_round_trip() {
+    for result in results() {
+        let interned = InternedLabResult::from(&result);
+        assert_eq!(interned.to_owned(), result);
+        let json = serde_json::to_value(&interned).unwrap();
+        assert_eq!(json, serde_json::to_value(&result).unwrap());
+        assert_eq!(serde_json::from_value::<InternedLabResult>(json).unwrap(), interned);
+    }
+}
+
+#[test]
+fn interned_results_accept_snake_case_aliases() {
+    for result in results().into_iter().filter(|result| result.reference_range.is_some()) {
+        let mut json = serde_json::to_value(&result).unwrap();
+        let object = json.as_object_mut().unwrap();
+        let mut ranges = object.remove("referenceRange").unwrap();
+        for range in ranges.as_array_mut().unwrap().iter_mut().filter_map(|range| range.as_object_mut()) {
+            if let Some(applies_to) = range.remove("appliesTo") {
+                range.insert("applies_to".into(), applies_to);
+            }
+        }
+        object.insert("reference_range".into(), ranges);
+        let interned: InternedLabResult = serde_json::from_value(json).unwrap();
+        assert_eq!(interned.to_owned(), result);
+    }
+}
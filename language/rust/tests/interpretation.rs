@@ -0,0 +1,42 @@
+//! Checks the HL7 v3 interpretation codes.
+
+use serde_json::json;
+use wellally::i18n::{Locale, LocalizedDisplay};
+use wellally::wire::{decode, encode};
+use wellally::{Interpretation, LabReport, Resource};
+
+#[test]
+fn full_table_deserializes() {
+    let codes = json!(["N", "L", "H", "A", "HH", "LL", "AA", "<", ">", "S", "R", "I", "SYN-R", "POS", "NEG", "IND"]);
+    let parsed: Vec<Interpretation> = serde_json::from_value(codes.clone()).unwrap();
+    assert_eq!(parsed[4], Interpretation::HH);
+    assert_eq!(parsed[7], Interpretation::OffScaleLow);
+    assert_eq!(parsed[12], Interpretation::SynR);
+    assert_eq!(serde_json::to_value(&parsed).unwrap(), codes);
+    assert_eq!("SYN-S".parse::<Interpretation>().unwrap(), Interpretation::SynS);
+    assert!("XYZ".parse::<Interpretation>().is_err());
+
+    let report = LabReport::from_json_value(&json!({
+        "id": "lab-1", "patientId": "p-1", "issuedAt": "2024-11-02",
+        "results": [{"code": {"coding": []}, "value": {"value": 2.1, "unit": "mmol/L"}, "interpretation": "LL"}]
+    }))
+    .unwrap();
+    assert!(report.results[0].interpretation.unwrap().is_critical());
+}
+
+#[test]
+fn flags_and_labels() {
+    assert!(Interpretation::OffScaleHigh.is_abnormal());
+    assert!(!Interpretation::POS.is_abnormal());
+    assert!(!Interpretation::H.is_critical());
+    assert_eq!(Interpretation::HH.localized_display(Locale::En), "Critically high");
+    assert_eq!(Interpretation::R.localized_display(Locale::Zh), "耐药");
+}
+
+#[test]
+fn wire_codes_keep_the_original_four() {
+    let result = json!({"interpretation": "A"});
+    assert_eq!(encode(&result), json!({"in": 3}));
+    let critical = json!({"interpretation": "HH"});
+    assert_eq!(decode(&encode(&critical)), critical);
+}
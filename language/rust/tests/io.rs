@@ -0,0 +1,215 @@
+//! Checks format detection and dispatch of mixed payloads.
+
+use serde_json::json;
+use wellally::io::{parse_auto, AnyResource, Bundle, Format};
+use wellally::wire::to_wire;
+use wellally::{LabReport, Resource, Validate};
+
+fn report() -> serde_json::Value {
+    json!({
+        "id": "lab-1",
+        "patientId": "p-1",
+        "issuedAt": "2024-11-02T09:30:00Z",
+        "results": [{
+            "code": {"coding": [{"system": "http://loinc.org", "code": "2345-7", "display": "Glucose"}]},
+            "value": {"value": 5.4, "unit": "mmol/L"}
+        }]
+    })
+}
+
+fn task() -> serde_json::Value {
+    json!({"id": "task-1", "patientId": "p-1", "status": "requested", "description": "Repeat CBC"})
+}
+
+#[test]
+fn containers_are_detected() {
+    let single = parse_auto(report().to_string().as_bytes(), None).unwrap();
+    assert_eq!(single.format, Format::Json);
+    assert!(!single.wire);
+    assert_eq!(single.resources, [AnyResource::LabReport(LabReport::from_json_value(&report()).unwrap())]);
+
+    let array = parse_auto(json!([report(), task()]).to_string().as_bytes(), Some("application/json")).unwrap();
+    assert_eq!(array.format, Format::JsonArray);
+    let types: Vec<_> = array.resources.iter().map(AnyResource::resource_type).collect();
+    assert_eq!(types, ["LabReport", "Task"]);
+
+    let bundle = json!({"resourceType": "Bundle", "entry": [{"resource": task()}, {"resource": report()}]});
+    let bundle = parse_auto(format!("\u{feff}\n{}", bundle).as_bytes(), None).unwrap();
+    assert_eq!(bundle.format, Format::Bundle);
+    assert_eq!(bundle.resources[1].id(), "lab-1");
+    assert!(bundle.resources.iter().all(|resource| resource.is_valid()));
+
+    let ndjson = format!("{}\n{}\n", task(), report());
+    let parsed = parse_auto(ndjson.as_bytes(), None).unwrap();
+    assert_eq!(parsed.format, Format::Ndjson);
+    assert_eq!(parsed.resources[0].resource_type(), "Task");
+    let one_line = parse_auto(task().to_string().as_bytes(), Some("application/fhir+ndjson; charset=utf-8")).unwrap();
+    assert_eq!(one_line.format, Format::Ndjson);
+}
+
+#[test]
+fn wire_records_are_decoded() {
+    let report = LabReport::from_json_value(&report()).unwrap();
+    let payload = json!([to_wire(&report).unwrap()]);
+    let parsed = parse_auto(payload.to_string().as_bytes(), None).unwrap();
+    assert!(parsed.wire);
+    assert_eq!(parsed.resources, [AnyResource::from(report.clone())]);
+    let mut expected = serde_json::to_value(&report).unwrap();
+    expected["resourceType"] = "LabReport".into();
+    assert_eq!(serde_json::to_value(&parsed.resources[0]).unwrap(), expected);
+}
+
+#[test]
+fn errors_locate_the_record() {
+    let mut broken = report();
+    broken["results"][0]["interpretation"] = json!("X");
+    let err = parse_auto(json!([task(), broken]).to_string().as_bytes(), None).unwrap_err();
+    assert_eq!(err.path(), "[1].results[0].interpretation");
+
+    let bundle = json!({"entry": [{"resource": {"id": "x"}}]});
+    let err = parse_auto(bundle.to_string().as_bytes(), None).unwrap_err();
+    assert_eq!(err.path(), "entry[0].resource");
+    assert_eq!(err.message(), "cannot determine resource type");
+}
+
+#[test]
+fn unsupported_formats_are_rejected() {
+    let cda = br#"<?xml version="1.0"?><ClinicalDocument xmlns="urn:hl7-org:v3"/>"#;
+    assert_eq!(parse_auto(cda, None).unwrap_err().message(), "XML input is not supported");
+    let hl7 = b"MSH|^~\\&|LAB|NORTH|||202411020930||ORU^R01|1|P|2.5\r";
+    assert_eq!(parse_auto(hl7, None).unwrap_err().message(), "HL7 v2 input is not supported");
+    assert_eq!(parse_auto(&[0xa2, 0x62, 0x69, 0x64], None).unwrap_err().message(), "CBOR input is not supported");
+    let err = parse_auto(report().to_string().as_bytes(), Some("application/cbor")).unwrap_err();
+    assert_eq!(err.message(), "CBOR input is not supported");
+    assert!(parse_auto(b"{\"id\":", None).is_err());
+}
+
+fn bundle() -> Bundle {
+    let lab = |id: &str, patient: &str| {
+        AnyResource::from_json_value(&json!({"id": id, "patientId": patient, "issuedAt": "2024-11-02T09:30:00Z", "results": []}))
+            .unwrap()
+    };
+    let questionnaire = json!({"resourceType": "Questionnaire", "id": "phq-2", "status": "active", "item": []});
+    Bundle::new(vec![
+        lab("lab-1", "p-1"),
+        lab("lab-2", "p-2"),
+        AnyResource::from_json_value(&task()).unwrap(),
+        AnyResource::from_json_value(&questionnaire).unwrap(),
+    ])
+}
+
+#[test]
+fn bundles_partition_by_patient() {
+    let partitions = bundle().partition_by_patient();
+    let ids = |patient: &str| partitions[patient].resources.iter().map(AnyResource::id).collect::<Vec<_>>();
+    assert_eq!(partitions.len(), 3);
+    assert_eq!(ids("p-1"), ["lab-1", "task-1"]);
+    assert_eq!(ids("p-2"), ["lab-2"]);
+    assert_eq!(ids(""), ["phq-2"]);
+}
+
+#[test]
+fn bundles_round_trip_through_parse_auto() {
+    let bundle = bundle();
+    let json = serde_json::to_value(&bundle).unwrap();
+    assert_eq!(json["resourceType"], "Bundle");
+    assert_eq!(json["type"], "collection");
+    assert_eq!(json["entry"][0]["resource"]["id"], "lab-1");
+
+    let parsed = parse_auto(json.to_string().as_bytes(), None).unwrap();
+    assert_eq!(parsed.format, Format::Bundle);
+    assert_eq!(Bundle::from(parsed), bundle);
+}
+
+#[test]
+fn resources_serialize_with_their_type() {
+    let location = AnyResource::from_typed_json_value("Location", &json!({"id": "loc-1", "name": "Clinic"})).unwrap();
+    let allergy = AnyResource::from_typed_json_value(
+        "AllergyIntolerance",
+        &json!({
+            "id": "allergy-1", "patientId": "p-1", "clinicalStatus": "active",
+            "code": {"coding": [{"system": "http://snomed.info/sct", "code": "256349002", "display": "Peanut"}]}
+        }),
+    )
+    .unwrap();
+    let person = AnyResource::from_json_value(&json!({"id": "p-1", "name": [], "birthDate": "1960"})).unwrap();
+    for resource in [location, allergy, person] {
+        let json = serde_json::to_value(&resource).unwrap();
+        assert_eq!(json["resourceType"], resource.resource_type());
+        assert_eq!(serde_json::to_string(&resource).unwrap().matches("resourceType").count(), 1);
+        assert_eq!(serde_json::from_value::<AnyResource>(json.clone()).unwrap(), resource);
+        assert_eq!(parse_auto(json.to_string().as_bytes(), None).unwrap().resources, [resource]);
+    }
+}
+
+#[test]
+fn bundles_of_every_resource_type_round_trip() {
+    let examples = [
+        include_str!("../../../infrastructure/schemas/allergy-intolerance/examples/allergy-intolerance.penicillin.json"),
+        include_str!("../../../infrastructure/schemas/audiology/examples/audiology.occupational.json"),
+        include_str!("../../../infrastructure/schemas/birth/examples/birth.twin.json"),
+        include_str!("../../../infrastructure/schemas/clearance/examples/clearance.return-to-work.json"),
+        include_str!("../../../infrastructure/schemas/communication/examples/communication.result-question.json"),
+        include_str!("../../../infrastructure/schemas/compendium/examples/compendium.district-lab.json"),
+        include_str!("../../../infrastructure/schemas/condition/examples/condition.hypertension.json"),
+        include_str!("../../../infrastructure/schemas/consent/examples/consent.research-no-mental-health.json"),
+        include_str!("../../../infrastructure/schemas/dialysis/examples/dialysis.hemodialysis.json"),
+        include_str!("../../../infrastructure/schemas/dispense/examples/dispense.refill.json"),
+        include_str!("../../../infrastructure/schemas/ecg/examples/ecg.smartwatch.json"),
+        include_str!("../../../infrastructure/schemas/family-health/examples/family-tree.min.json"),
+        include_str!("../../../infrastructure/schemas/flag/examples/flag.allergy-conflict.json"),
+        include_str!("../../../infrastructure/schemas/glucose-series/examples/glucose-series.overnight.json"),
+        include_str!("../../../infrastructure/schemas/health/examples/person.min.json"),
+        include_str!("../../../infrastructure/schemas/imaging-report/examples/imaging-report.ct-chest.json"),
+        include_str!("../../../infrastructure/schemas/immunization/examples/immunization.influenza.json"),
+        include_str!("../../../infrastructure/schemas/infusion/examples/infusion.norepinephrine.json"),
+        include_str!("../../../infrastructure/schemas/lab-report/examples/lab-report.ana-pattern.json"),
+        include_str!("../../../infrastructure/schemas/location/examples/location.cardiology-clinic.json"),
+        include_str!("../../../infrastructure/schemas/medication/examples/medication.amoxicillin.json"),
+        include_str!("../../../infrastructure/schemas/pathology/examples/pathology.colectomy.json"),
+        include_str!("../../../infrastructure/schemas/questionnaire-response/examples/questionnaire-response.phq-2.json"),
+        include_str!("../../../infrastructure/schemas/questionnaire/examples/questionnaire.phq-2.json"),
+        include_str!("../../../infrastructure/schemas/referral/examples/referral.cardiology.json"),
+        include_str!("../../../infrastructure/schemas/rehab/examples/rehab.knee-physiotherapy.json"),
+        include_str!("../../../infrastructure/schemas/related-person/examples/related-person.guardian.json"),
+        include_str!("../../../infrastructure/schemas/task/examples/task.repeat-cbc.json"),
+        include_str!("../../../infrastructure/schemas/travel/examples/travel.east-africa.json"),
+        include_str!("../../../infrastructure/schemas/wound/examples/wound.pressure-injury.json"),
+    ];
+    let bundle: Bundle = examples.iter().map(|example| serde_json::from_str::<AnyResource>(example).unwrap()).collect();
+    let mut types: Vec<_> = bundle.resources.iter().map(AnyResource::resource_type).collect();
+    types.sort_unstable();
+    let mut expected = AnyResource::RESOURCE_TYPES.to_vec();
+    expected.sort_unstable();
+    assert_eq!(types, expected);
+
+    let json = serde_json::to_value(&bundle).unwrap();
+    for (entry, resource) in json["entry"].as_array().unwrap().iter().zip(&bundle.resources) {
+        assert_eq!(entry["resource"]["resourceType"], resource.resource_type());
+    }
+    assert_eq!(Bundle::from(parse_auto(json.to_string().as_bytes(), None).unwrap()), bundle);
+
+    let location = AnyResource::from_typed_json_value("Location", &json!({"id": "loc-1", "name": "Clinic"})).unwrap();
+    let single = Bundle::new(vec![location]);
+    assert_eq!(Bundle::from(parse_auto(&serde_json::to_vec(&single).unwrap(), None).unwrap()), single);
+}
+
+#[test]
+fn bundles_split_by_entries_and_bytes() {
+    let bundle = bundle();
+    let chunks = bundle.split(Some(3), None).unwrap();
+    assert_eq!(chunks.iter().map(Bundle::len).collect::<Vec<_>>(), [3, 1]);
+    assert_eq!(chunks.into_iter().flat_map(|chunk| chunk.resources).collect::<Bundle>(), bundle);
+
+    let limit = serde_json::to_vec(&Bundle::new(bundle.resources[..2].to_vec())).unwrap().len();
+    let chunks = bundle.split(None, Some(limit)).unwrap();
+    assert_eq!(chunks[0].len(), 2);
+    for chunk in &chunks {
+        assert!(serde_json::to_vec(chunk).unwrap().len() <= limit);
+    }
+    assert_eq!(chunks.iter().map(Bundle::len).sum::<usize>(), 4);
+
+    let err = bundle.split(None, Some(100)).unwrap_err();
+    assert_eq!(err.path(), "entry[0]");
+    assert!(Bundle::default().split(Some(10), Some(1000)).unwrap().is_empty());
+}
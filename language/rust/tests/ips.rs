@@ -0,0 +1,114 @@
+//! Checks International Patient Summary generation and, with the `fhir` feature, the FHIR document bundle.
+
+use wellally::chart::PatientChart;
+use wellally::io::AnyResource;
+use wellally::ips::{self, IpsComposition, ALLERGIES_SECTION, IMMUNIZATIONS_SECTION, MEDICATIONS_SECTION};
+use wellally::ips::{PROBLEMS_SECTION, RESULTS_SECTION};
+use wellally::{AllergyIntolerance, Condition, ImmunizationRecord, LabReport, MedicationRecord, Person, Resource};
+
+const PERSON: &str = include_str!("../../../infrastructure/schemas/health/examples/person.min.json");
+const CONDITION: &str = include_str!("../../../infrastructure/schemas/condition/examples/condition.hypertension.json");
+const ALLERGY: &str =
+    include_str!("../../../infrastructure/schemas/allergy-intolerance/examples/allergy-intolerance.penicillin.json");
+const LISINOPRIL: &str = include_str!("../../../infrastructure/schemas/medication/examples/medication.lisinopril.json");
+const AMOXICILLIN: &str =
+    include_str!("../../../infrastructure/schemas/medication/examples/medication.amoxicillin.json");
+const VITALS: &str = include_str!("../../../infrastructure/schemas/lab-report/examples/lab-report.vital-signs.json");
+const LIPIDS: &str = include_str!("../../../infrastructure/schemas/lab-report/examples/lab-report.lipid-panel.json");
+const INFLUENZA: &str =
+    include_str!("../../../infrastructure/schemas/immunization/examples/immunization.influenza.json");
+
+fn chart() -> PatientChart {
+    let mut chart = PatientChart::new("person-001");
+    chart.conditions.push(Condition::from_json(CONDITION).unwrap());
+    chart.allergies.push(AllergyIntolerance::from_json(ALLERGY).unwrap());
+    chart.medications.push(MedicationRecord::from_json(LISINOPRIL).unwrap());
+    chart.medications.push(MedicationRecord::from_json(AMOXICILLIN).unwrap());
+    chart.lab_reports.push(LabReport::from_json(VITALS).unwrap());
+    chart.lab_reports.push(LabReport::from_json(LIPIDS).unwrap());
+    chart.immunizations.push(ImmunizationRecord::from_json(INFLUENZA).unwrap());
+    chart
+}
+
+fn document() -> IpsComposition {
+    let person = Person::from_json(PERSON).unwrap();
+    ips::generate_at(&person, &chart(), "2024-12-10T10:00:00Z".parse().unwrap())
+}
+
+fn ids(document: &IpsComposition, code: &str) -> Vec<String> {
+    document.section(code).unwrap().entries.iter().map(|entry| entry.id().to_string()).collect()
+}
+
+#[test]
+fn sections_list_current_records() {
+    let document = document();
+    assert_eq!(document.r#type.code, ips::PATIENT_SUMMARY);
+    let codes: Vec<_> = document.sections.iter().map(|section| section.code.code.as_str()).collect();
+    let expected = [PROBLEMS_SECTION, MEDICATIONS_SECTION, ALLERGIES_SECTION, RESULTS_SECTION, IMMUNIZATIONS_SECTION];
+    assert_eq!(codes, expected);
+    assert_eq!(ids(&document, PROBLEMS_SECTION), ["cond-htn-01"]);
+    assert_eq!(ids(&document, MEDICATIONS_SECTION), ["med-2024-003"]);
+    assert_eq!(ids(&document, ALLERGIES_SECTION), ["allergy-pcn-01"]);
+    assert_eq!(ids(&document, RESULTS_SECTION), ["lab-2024-002", "lab-2024-vitals-001"]);
+    assert_eq!(ids(&document, IMMUNIZATIONS_SECTION), ["imm-2024-10-03-001"]);
+    assert!(document.sections.iter().all(|section| section.empty_reason.is_none()));
+}
+
+#[test]
+fn empty_required_sections_give_a_reason() {
+    let person = Person::from_json(PERSON).unwrap();
+    let mut chart = chart();
+    chart.allergies.clear();
+    let mut repeat = LabReport::from_json(LIPIDS).unwrap();
+    repeat.id = "lab-2024-001".into();
+    repeat.issued_at = "2024-06-03T08:40:00Z".parse().unwrap();
+    chart.lab_reports.push(repeat);
+
+    let document = ips::generate_at(&person, &chart, "2024-09-01T00:00:00Z".parse().unwrap());
+    let allergies = document.section(ALLERGIES_SECTION).unwrap();
+    assert!(allergies.entries.is_empty());
+    assert_eq!(allergies.empty_reason.as_ref().unwrap().code, "unavailable");
+    assert!(ids(&document, MEDICATIONS_SECTION).is_empty());
+    assert_eq!(ids(&document, RESULTS_SECTION), ["lab-2024-001"]);
+    assert!(document.section(IMMUNIZATIONS_SECTION).is_none());
+    assert_eq!(document.sections.len(), 4);
+}
+
+#[test]
+fn composition_round_trips_as_json() {
+    let document = document();
+    let written = serde_json::to_value(&document).unwrap();
+    assert_eq!(written["type"]["code"], "60591-5");
+    assert_eq!(written["patient"]["id"], "person-001");
+    assert_eq!(written["sections"][1]["entries"][0]["medication"]["code"], "314076");
+    assert!(written["sections"][0].get("emptyReason").is_none());
+    let read: IpsComposition = serde_json::from_value(written).unwrap();
+    assert_eq!(read, document);
+    assert!(matches!(read.sections[4].entries[0], AnyResource::ImmunizationRecord(_)));
+}
+
+#[cfg(feature = "fhir")]
+#[test]
+fn fhir_document_bundle() {
+    let bundle = document().to_fhir();
+    assert_eq!(bundle["type"], "document");
+    let entries = bundle["entry"].as_array().unwrap();
+    let types: Vec<_> = entries.iter().map(|entry| entry["resource"]["resourceType"].as_str().unwrap()).collect();
+    let leading = ["Composition", "Patient", "Condition", "MedicationStatement", "AllergyIntolerance", "Observation"];
+    assert_eq!(&types[..6], leading);
+    assert_eq!(types.last(), Some(&"Immunization"));
+    assert_eq!(types.iter().filter(|t| **t == "Observation").count(), 9);
+
+    let composition = &entries[0]["resource"];
+    assert_eq!(composition["subject"]["reference"], "Patient/person-001");
+    assert_eq!(composition["section"][0]["entry"][0]["reference"], "Condition/cond-htn-01");
+    assert!(composition["section"][0]["text"]["div"].as_str().unwrap().contains("<li>"));
+    assert_eq!(entries[2]["fullUrl"], "https://www.wellally.tech/fhir/Condition/cond-htn-01");
+    assert_eq!(entries[2]["resource"]["clinicalStatus"]["coding"][0]["code"], "active");
+
+    let vitals = entries.iter().find(|entry| entry["resource"]["id"] == "lab-2024-vitals-001-2").unwrap();
+    assert_eq!(vitals["resource"]["valueQuantity"]["system"], "http://unitsofmeasure.org");
+    assert_eq!(vitals["resource"]["interpretation"][0]["coding"][0]["code"], "H");
+    let dose = &entries.last().unwrap()["resource"];
+    assert_eq!((&dose["lotNumber"], &dose["occurrenceDateTime"]), (&"FL-24A118".into(), &"2024-10-03".into()));
+}
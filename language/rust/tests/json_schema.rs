@@ -0,0 +1,207 @@
+//! Keeps the schemas generated from the Rust models in sync with the
+//! published JSON Schemas under `infrastructure/schemas`.
+
+#![cfg(feature = "schemars")]
+
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+
+use serde_json::Value;
+use wellally::resource::json_schema;
+use wellally::{
+    AllergyIntolerance, AudiogramReport, BirthRecord, ClearanceDocument, CommunicationRecord, Compendium, Condition,
+    ConsentRecord, DialysisSession, DispenseRecord, EcgRecord, FamilyHealthTree, Flag, GlucoseSeries, ImagingReport,
+    ImmunizationRecord, InfusionRecord, LabReport, Location, MedicationRecord, PathologyReport, Person, Questionnaire,
+    QuestionnaireResponse, Referral, RehabSession, RelatedPerson, Resource, Task, TravelHealthRecord, WoundAssessment,
+};
+
+fn published(module: &str) -> Value {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("../../infrastructure/schemas")
+        .join(module)
+        .join("schema")
+        .join(format!("{}.schema.json", module));
+    let text = std::fs::read_to_string(&path)
+        .unwrap_or_else(|err| panic!("cannot read {}: {}", path.display(), err));
+    serde_json::from_str(&text).unwrap()
+}
+
+fn keys(schema: &Value, field: &str) -> BTreeSet<String> {
+    match &schema[field] {
+        Value::Object(map) => map.keys().cloned().collect(),
+        Value::Array(items) => items.iter().filter_map(|v| v.as_str().map(String::from)).collect(),
+        _ => BTreeSet::new(),
+    }
+}
+
+fn assert_in_sync<R: Resource + schemars::JsonSchema>(module: &str) {
+    let published = published(module);
+    let generated = serde_json::to_value(json_schema::<R>()).unwrap();
+
+    assert_eq!(published["$id"], R::SCHEMA_ID, "{}: $id", module);
+    assert_eq!(generated["$id"], R::SCHEMA_ID, "{}: generated $id", module);
+    assert_eq!(
+        keys(&generated, "properties"),
+        keys(&published, "properties"),
+        "{}: properties",
+        module
+    );
+    assert_eq!(
+        keys(&generated, "required"),
+        keys(&published, "required"),
+        "{}: required",
+        module
+    );
+}
+
+#[test]
+fn person_schema_in_sync() {
+    assert_in_sync::<Person>("health");
+}
+
+#[test]
+fn lab_report_schema_in_sync() {
+    assert_in_sync::<LabReport>("lab-report");
+
+    let published = published("lab-report");
+    let generated = serde_json::to_value(json_schema::<LabReport>()).unwrap();
+    let result = &generated["$defs"]["LabResult"];
+    assert_eq!(keys(result, "properties"), keys(&published["$defs"]["LabResult"], "properties"));
+    assert_eq!(keys(result, "required"), keys(&published["$defs"]["LabResult"], "required"));
+}
+
+#[test]
+fn imaging_report_schema_in_sync() {
+    assert_in_sync::<ImagingReport>("imaging-report");
+}
+
+#[test]
+fn medication_schema_in_sync() {
+    assert_in_sync::<MedicationRecord>("medication");
+}
+
+#[test]
+fn family_health_schema_in_sync() {
+    assert_in_sync::<FamilyHealthTree>("family-health");
+}
+
+#[test]
+fn questionnaire_schema_in_sync() {
+    assert_in_sync::<Questionnaire>("questionnaire");
+    assert_in_sync::<QuestionnaireResponse>("questionnaire-response");
+}
+
+#[test]
+fn referral_schema_in_sync() {
+    assert_in_sync::<Referral>("referral");
+}
+
+#[test]
+fn task_schema_in_sync() {
+    assert_in_sync::<Task>("task");
+}
+
+#[test]
+fn communication_schema_in_sync() {
+    assert_in_sync::<CommunicationRecord>("communication");
+}
+
+#[test]
+fn location_schema_in_sync() {
+    assert_in_sync::<Location>("location");
+}
+
+#[test]
+fn condition_schema_in_sync() {
+    assert_in_sync::<Condition>("condition");
+}
+
+#[test]
+fn allergy_intolerance_schema_in_sync() {
+    assert_in_sync::<AllergyIntolerance>("allergy-intolerance");
+}
+
+#[test]
+fn flag_schema_in_sync() {
+    assert_in_sync::<Flag>("flag");
+}
+
+#[test]
+fn glucose_series_schema_in_sync() {
+    assert_in_sync::<GlucoseSeries>("glucose-series");
+}
+
+#[test]
+fn ecg_schema_in_sync() {
+    assert_in_sync::<EcgRecord>("ecg");
+}
+
+#[test]
+fn consent_schema_in_sync() {
+    assert_in_sync::<ConsentRecord>("consent");
+}
+
+#[test]
+fn dialysis_schema_in_sync() {
+    assert_in_sync::<DialysisSession>("dialysis");
+}
+
+#[test]
+fn rehab_schema_in_sync() {
+    assert_in_sync::<RehabSession>("rehab");
+}
+
+#[test]
+fn wound_schema_in_sync() {
+    assert_in_sync::<WoundAssessment>("wound");
+}
+
+#[test]
+fn infusion_schema_in_sync() {
+    assert_in_sync::<InfusionRecord>("infusion");
+}
+
+#[test]
+fn pathology_schema_in_sync() {
+    assert_in_sync::<PathologyReport>("pathology");
+}
+
+#[test]
+fn audiology_schema_in_sync() {
+    assert_in_sync::<AudiogramReport>("audiology");
+}
+
+#[test]
+fn birth_schema_in_sync() {
+    assert_in_sync::<BirthRecord>("birth");
+}
+
+#[test]
+fn clearance_schema_in_sync() {
+    assert_in_sync::<ClearanceDocument>("clearance");
+}
+
+#[test]
+fn travel_schema_in_sync() {
+    assert_in_sync::<TravelHealthRecord>("travel");
+}
+
+#[test]
+fn compendium_schema_in_sync() {
+    assert_in_sync::<Compendium>("compendium");
+}
+
+#[test]
+fn dispense_schema_in_sync() {
+    assert_in_sync::<DispenseRecord>("dispense");
+}
+
+#[test]
+fn immunization_schema_in_sync() {
+    assert_in_sync::<ImmunizationRecord>("immunization");
+}
+
+#[test]
+fn related_person_schema_in_sync() {
+    assert_in_sync::<RelatedPerson>("related-person");
+}
@@ -0,0 +1,101 @@
+//! Checks detection and merging of duplicate lab reports from different sources.
+
+use chrono::Duration;
+use wellally::lab_report::merge::{dedupe, ReportMerger, MERGED_FROM};
+use wellally::{Extensible, ExtensionValue, LabReport};
+
+const GLUCOSE: &str = "2345-7";
+const POTASSIUM: &str = "2823-3";
+const CREATININE: &str = "2160-0";
+
+fn result(code: &str, value: f64, unit: &str) -> serde_json::Value {
+    serde_json::json!({
+        "code": { "coding": [{ "system": "http://loinc.org", "code": code }] },
+        "value": { "value": value, "unit": unit }
+    })
+}
+
+fn report(id: &str, patient: &str, collected_at: &str, results: Vec<serde_json::Value>) -> LabReport {
+    serde_json::from_value(serde_json::json!({
+        "id": id,
+        "patientId": patient,
+        "issuedAt": "2024-11-02T12:00:00Z",
+        "specimen": { "collectedAt": collected_at },
+        "results": results
+    }))
+    .unwrap()
+}
+
+#[test]
+fn merges_a_panel_from_the_portal_and_the_ehr_export() {
+    let portal = report(
+        "portal-1",
+        "p-1",
+        "2024-11-02T07:15:00Z",
+        vec![result(GLUCOSE, 97.0, "mg/dL"), result(POTASSIUM, 4.1, "mmol/L")],
+    );
+    let mut ehr = report(
+        "ehr-7",
+        "p-1",
+        "2024-11-02T15:18:00+08:00",
+        vec![result(GLUCOSE, 0.98, "g/L"), result(CREATININE, 0.9, "mg/dL")],
+    );
+    ehr.facility = serde_json::from_value(serde_json::json!({ "name": "City Hospital" })).unwrap();
+
+    let merged = dedupe(&[portal, ehr]);
+    assert_eq!(merged.len(), 1);
+    let merged = &merged[0];
+    assert!(merged.is_merged());
+    assert_eq!(merged.report.id, "portal-1");
+    assert_eq!(merged.sources, ["portal-1", "ehr-7"]);
+
+    let codes: Vec<&str> = merged.report.results.iter().map(|result| result.code.coding[0].code.as_str()).collect();
+    assert_eq!(codes, [GLUCOSE, POTASSIUM, CREATININE]);
+    assert_eq!(merged.result_sources, ["portal-1", "portal-1", "ehr-7"]);
+    assert_eq!(merged.report.facility.as_ref().and_then(|facility| facility.name.as_deref()), Some("City Hospital"));
+
+    let provenance: Vec<&str> = merged
+        .report
+        .extension_values(MERGED_FROM)
+        .into_iter()
+        .filter_map(|value| match value {
+            ExtensionValue::Reference(reference) => Some(reference.reference.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(provenance, ["LabReport/portal-1", "LabReport/ehr-7"]);
+}
+
+#[test]
+fn keeps_reports_that_differ_apart() {
+    let base = report("a", "p-1", "2024-11-02T07:15:00Z", vec![result(GLUCOSE, 97.0, "mg/dL")]);
+    let other_patient = report("b", "p-2", "2024-11-02T07:15:00Z", vec![result(GLUCOSE, 97.0, "mg/dL")]);
+    let next_morning = report("c", "p-1", "2024-11-03T07:15:00Z", vec![result(GLUCOSE, 97.0, "mg/dL")]);
+    let different_value = report("d", "p-1", "2024-11-02T07:15:00Z", vec![result(GLUCOSE, 110.0, "mg/dL")]);
+    let no_shared_analyte = report("e", "p-1", "2024-11-02T07:15:00Z", vec![result(POTASSIUM, 4.1, "mmol/L")]);
+
+    let merged = dedupe(&[base, other_patient, next_morning, different_value, no_shared_analyte]);
+    assert_eq!(merged.len(), 5);
+    assert!(merged.iter().all(|merged| !merged.is_merged() && merged.report.extensions.is_none()));
+}
+
+#[test]
+fn window_and_tolerance_are_configurable() {
+    let a = report("a", "p-1", "2024-11-02T07:15:00Z", vec![result(GLUCOSE, 97.0, "mg/dL")]);
+    let b = report("b", "p-1", "2024-11-02T07:45:00Z", vec![result(GLUCOSE, 101.0, "mg/dL")]);
+    assert!(!ReportMerger::default().is_duplicate(&a, &b));
+
+    let lenient = ReportMerger::default().with_collection_window(Duration::hours(1)).with_tolerance(0.05);
+    assert!(lenient.is_duplicate(&a, &b));
+    assert_eq!(lenient.dedupe(&[a, b]).len(), 1);
+}
+
+#[test]
+fn compares_values_by_their_ucum_code() {
+    let portal = report("a", "p-1", "2024-11-02T07:15:00Z", vec![result(GLUCOSE, 97.0, "mg/dL")]);
+    let mut labelled = result(GLUCOSE, 0.97, "grams per litre");
+    labelled["value"]["code"] = "g/L".into();
+    labelled["value"]["system"] = "http://unitsofmeasure.org".into();
+    let ehr = report("b", "p-1", "2024-11-02T07:15:00Z", vec![labelled]);
+    assert!(ReportMerger::default().is_duplicate(&portal, &ehr));
+}
@@ -0,0 +1,116 @@
+//! Checks mapping of extracted lab table rows onto coded results.
+
+use wellally::lab_report::import::{tabular, LoincDictionary, TabularImporter, TabularRow};
+use wellally::{Comparator, Interpretation, LabReport, LabValue, Quantity, Validate};
+
+#[test]
+fn codes_names_aliases_and_chinese() {
+    let rows = [
+        TabularRow::new("Hemoglobin", "13.2", "g/dL", "12.0-17.5", ""),
+        TabularRow::new("ALT (SGPT)", "62", "U/L", "7 - 56", "H"),
+        TabularRow::new("Glycated Hemoglobin (HbA1c)", "6.1", "%", "4.0~5.6", "↑"),
+        TabularRow::new("血红蛋白", "135", "g/L", "130–175", ""),
+        TabularRow::new("空腹血糖", "5.1", "mmol/L", "3.9-6.1", "正常"),
+        TabularRow::new("Vitamin K", "0.5", "ng/mL", "", ""),
+    ];
+    let imported = tabular(&rows);
+    let codes: Vec<_> = imported.iter().map(|row| row.result.code.coding.first().map(|coding| coding.code.as_str())).collect();
+    assert_eq!(codes, [Some("718-7"), Some("1742-6"), Some("4548-4"), Some("718-7"), Some("2345-7"), None]);
+    assert_eq!(imported[0].confidence, 1.0);
+    assert!(imported[1].confidence > 0.75 && imported[1].confidence < 1.0);
+    assert!(!imported[5].is_coded());
+    assert_eq!(imported[5].confidence, 0.0);
+    assert_eq!(imported[5].result.code.text.as_deref(), Some("Vitamin K"));
+    assert_eq!(imported[1].result.code.text.as_deref(), Some("ALT (SGPT)"));
+}
+
+#[test]
+fn values_ranges_and_flags() {
+    let rows = [
+        TabularRow::new("Glucose", "105", "mg/dL", "70-99", "H"),
+        TabularRow::new("TSH", "<0.01", "mIU/L", "0.4 to 4.0", "L"),
+        TabularRow::new("Total cholesterol", "182 mg/dL", "", "<200", ""),
+        TabularRow::new("HDL", "52", "mg/dL", ">=40", "N"),
+        TabularRow::new("Creatinine", "0.9", "mg/dL", "see comment", "*"),
+    ];
+    let imported = tabular(&rows);
+    let glucose = &imported[0].result;
+    assert_eq!(glucose.value, LabValue::Quantity(Quantity::new(105.0, "mg/dL")));
+    let range = &glucose.reference_range.as_ref().unwrap()[0];
+    assert_eq!(range.low.as_ref().unwrap().value, 70.0);
+    assert_eq!(range.high.as_ref().unwrap().unit, "mg/dL");
+    assert_eq!(glucose.interpretation, Some(Interpretation::H));
+
+    assert_eq!(
+        imported[1].result.value,
+        LabValue::Comparator { comparator: Comparator::LessThan, value: Quantity::new(0.01, "mIU/L") }
+    );
+    assert_eq!(imported[1].result.reference_range.as_ref().unwrap()[0].high.as_ref().unwrap().value, 4.0);
+
+    assert_eq!(imported[2].result.value, LabValue::Quantity(Quantity::new(182.0, "mg/dL")));
+    let cholesterol = &imported[2].result.reference_range.as_ref().unwrap()[0];
+    assert!(cholesterol.low.is_none());
+    assert_eq!(cholesterol.high.as_ref().unwrap().value, 200.0);
+
+    let hdl = &imported[3].result.reference_range.as_ref().unwrap()[0];
+    assert_eq!(hdl.low.as_ref().unwrap().value, 40.0);
+    assert!(hdl.high.is_none());
+    assert_eq!(imported[3].result.interpretation, Some(Interpretation::N));
+
+    let creatinine = &imported[4].result;
+    assert_eq!(creatinine.reference_range.as_ref().unwrap()[0].text.as_deref(), Some("see comment"));
+    assert_eq!(creatinine.interpretation, Some(Interpretation::A));
+}
+
+#[test]
+fn qualitative_and_ratio_values() {
+    let imported = tabular(&[
+        TabularRow::new("ANA titer", "1:160", "", "", "POS"),
+        TabularRow::new("HBsAg", "Negative", "", "", "阴性"),
+    ]);
+    assert_eq!(imported[0].result.value, LabValue::Ratio { numerator: 1.0, denominator: 160.0 });
+    assert_eq!(imported[0].result.interpretation, Some(Interpretation::POS));
+    assert_eq!(imported[1].result.value, LabValue::String("Negative".into()));
+    assert_eq!(imported[1].result.interpretation, Some(Interpretation::NEG));
+}
+
+#[test]
+fn custom_dictionary_and_threshold() {
+    let mut dictionary = LoincDictionary::new();
+    dictionary.insert("25-Hydroxyvitamin D", "1989-3", "Vitamin D+Metabolites [Mass/volume] in Serum or Plasma");
+    let importer = TabularImporter::new(dictionary.clone());
+    let imported = importer.import(&[TabularRow::new("25 Hydroxy Vit D", "32", "ng/mL", "30-100", "")]);
+    assert_eq!(imported[0].result.code.coding[0].code, "1989-3");
+    assert!(imported[0].confidence < 1.0);
+
+    let strict = TabularImporter::new(dictionary).with_min_confidence(0.9);
+    assert!(!strict.import(&[TabularRow::new("25 Hydroxy Vit D", "32", "ng/mL", "", "")])[0].is_coded());
+    assert!(LoincDictionary::default().lookup("  ").is_none());
+}
+
+#[test]
+fn imported_results_form_a_valid_report() {
+    let results = tabular(&[
+        TabularRow::new("WBC", "6.2", "10*3/uL", "4.0-11.0", ""),
+        TabularRow::new("PLT", "250", "10*3/uL", "150-400", ""),
+    ])
+    .into_iter()
+    .map(|row| row.result)
+    .collect();
+    let report = LabReport {
+        id: "lab-1".into(),
+        patient_id: "p-1".into(),
+        issued_at: "2024-11-02T09:30:00Z".parse().unwrap(),
+        results,
+        facility: None,
+        panel: None,
+        specimen: None,
+        notes: None,
+        schema_version: None,
+        security_labels: None,
+        extensions: None,
+        #[cfg(feature = "preserve-unknown")]
+        extra: Default::default(),
+    };
+    assert_eq!(report.validate(), []);
+}
@@ -0,0 +1,59 @@
+//! Checks ratio and limited-quantity lab values.
+
+use serde_json::json;
+use wellally::{Comparator, LabReport, LabValue, Quantity, Resource, Validate};
+
+fn quantity(value: f64, unit: &str) -> Quantity {
+    Quantity::new(value, unit)
+}
+
+#[test]
+fn string_forms_are_parsed() {
+    assert_eq!(LabValue::parse("1:128"), LabValue::Ratio { numerator: 1.0, denominator: 128.0 });
+    assert_eq!(
+        LabValue::parse("<5"),
+        LabValue::Comparator { comparator: Comparator::LessThan, value: quantity(5.0, "1") }
+    );
+    assert_eq!(
+        LabValue::parse(" >= 1000 U/L"),
+        LabValue::Comparator { comparator: Comparator::GreaterOrEqual, value: quantity(1000.0, "U/L") }
+    );
+    assert_eq!(
+        LabValue::parse("≤0.1 ng/mL"),
+        LabValue::Comparator { comparator: Comparator::LessOrEqual, value: quantity(0.1, "ng/mL") }
+    );
+    assert_eq!(LabValue::parse("positive"), LabValue::String("positive".into()));
+    assert_eq!(LabValue::parse("<pending>"), LabValue::String("<pending>".into()));
+}
+
+#[test]
+fn numeric_logic_runs_on_all_forms() {
+    assert_eq!(LabValue::parse("1:4").numeric_value(), Some(0.25));
+    assert_eq!(LabValue::String(">1000".into()).numeric_value(), Some(1000.0));
+    assert_eq!(LabValue::Quantity(quantity(5.4, "mmol/L")).numeric_value(), Some(5.4));
+    assert_eq!(LabValue::String("trace".into()).numeric_value(), None);
+    assert_eq!(LabValue::Ratio { numerator: 1.0, denominator: 0.0 }.numeric_value(), None);
+}
+
+#[test]
+fn structured_forms_round_trip_and_validate() {
+    let json = json!({
+        "id": "lab-1",
+        "patientId": "p-1",
+        "issuedAt": "2024-11-02",
+        "results": [
+            {"code": {"coding": [{"system": "http://loinc.org", "code": "5048-4"}]}, "value": {"numerator": 1.0, "denominator": 320.0}},
+            {"code": {"coding": [{"system": "http://loinc.org", "code": "1988-5"}]},
+             "value": {"comparator": "<", "value": {"value": 0.5, "unit": "mg/L"}}}
+        ]
+    });
+    let mut report = LabReport::from_json_value(&json).unwrap();
+    assert_eq!(serde_json::to_value(&report).unwrap(), json);
+    assert!(matches!(report.results[1].value, LabValue::Comparator { comparator: Comparator::LessThan, .. }));
+    assert!(report.is_valid(), "{:?}", report.validate());
+
+    report.results[0].value = LabValue::Ratio { numerator: 1.0, denominator: 0.0 };
+    report.results[1].value = LabValue::Comparator { comparator: Comparator::GreaterThan, value: quantity(5.0, "") };
+    let paths: Vec<_> = report.validate().into_iter().map(|issue| issue.path).collect();
+    assert_eq!(paths, ["results[0].value.denominator", "results[1].value.value.unit"]);
+}
@@ -0,0 +1,36 @@
+//! Checks the deprecated root models still read the JSON they were written for.
+
+#![allow(deprecated)]
+
+use serde_json::json;
+use wellally::legacy::LabReport;
+use wellally::{HealthPerson, LabResultValue, SpecimenType};
+
+#[test]
+fn health_person_reads_camel_case_json() {
+    let person: HealthPerson = serde_json::from_value(json!({
+        "id": "person-1",
+        "name": [{"family": "Zhang", "given": ["San"]}],
+        "birthDate": "1990-05-12",
+        "clinicalSummary": {"bloodType": "A+"}
+    }))
+    .unwrap();
+    assert_eq!(person.resourceType, "Person");
+    assert_eq!(person.clinicalSummary.unwrap().bloodType.as_deref(), Some("A+"));
+}
+
+#[test]
+fn lab_report_keeps_untyped_values() {
+    let value = json!({
+        "id": "lab-1",
+        "patientId": "person-1",
+        "issuedAt": "2024-11-02T09:30:00Z",
+        "results": [{"code": {"coding": [], "text": "Culture"}, "value": "no growth"}],
+        "specimen": {"type": {"system": "http://snomed.info/sct", "code": "119297000"}}
+    });
+    let report: LabReport = serde_json::from_value(value.clone()).unwrap();
+    assert!(matches!(&report.results[0].value, LabResultValue::Text(text) if text == "no growth"));
+    let specimen: Option<&SpecimenType> = report.specimen.as_ref().and_then(|specimen| specimen.r#type.as_ref());
+    assert_eq!(specimen.unwrap().code, "119297000");
+    assert_eq!(serde_json::to_value(&report).unwrap(), value);
+}
@@ -0,0 +1,98 @@
+//! Checks locations: hierarchy, opening hours, distances and facility links.
+
+use chrono::NaiveDate;
+use serde_json::json;
+use wellally::io::{parse_auto, AnyResource};
+use wellally::location::nearest_locations;
+use wellally::{LabReport, Location, Position, Reference, Resource, Validate};
+
+fn locations() -> Vec<Location> {
+    let hours = json!([
+        {"daysOfWeek": ["mon", "tue", "wed", "thu", "fri"], "openingTime": "08:00:00", "closingTime": "18:00:00"},
+        {"daysOfWeek": ["sat"], "openingTime": "22:00:00", "closingTime": "02:00:00"}
+    ]);
+    [
+        json!({"id": "hosp-1", "name": "City Hospital", "status": "active",
+               "type": [{"coding": [{"system": "http://terminology.hl7.org/CodeSystem/v3-RoleCode", "code": "HOSP"}]}],
+               "position": {"latitude": 31.2304, "longitude": 121.4737}, "hoursOfOperation": hours}),
+        json!({"id": "ward-3", "name": "Cardiology Ward", "partOf": {"reference": "Location/hosp-1"}}),
+        json!({"id": "bed-12", "name": "Bed 12", "partOf": {"reference": "Location/ward-3"}}),
+        json!({"id": "clinic-2", "name": "Hangzhou Clinic", "position": {"latitude": 30.2741, "longitude": 120.1551}}),
+    ]
+    .iter()
+    .map(|value| Location::from_json_value(value).unwrap())
+    .collect()
+}
+
+#[test]
+fn round_trips_and_validates() {
+    for location in locations() {
+        assert!(location.is_valid(), "{:?}", location.validate());
+        let json = serde_json::to_value(&location).unwrap();
+        assert_eq!(Location::from_json_value(&json).unwrap(), location);
+    }
+}
+
+#[test]
+fn walks_the_hierarchy() {
+    let locations = locations();
+    let ids: Vec<_> = locations[2].ancestors(&locations).iter().map(|l| l.id.as_str()).collect();
+    assert_eq!(ids, ["ward-3", "hosp-1"]);
+    assert!(locations[0].ancestors(&locations).is_empty());
+}
+
+#[test]
+fn checks_opening_hours() {
+    let hospital = &locations()[0];
+    let at = |d: u32, h: u32| NaiveDate::from_ymd_opt(2024, 11, d).unwrap().and_hms_opt(h, 30, 0).unwrap();
+    assert_eq!(hospital.is_open_at(at(4, 9)), Some(true)); // Monday
+    assert_eq!(hospital.is_open_at(at(4, 19)), Some(false));
+    assert_eq!(hospital.is_open_at(at(9, 23)), Some(true)); // Saturday night
+    assert_eq!(hospital.is_open_at(at(10, 1)), Some(true)); // ... past midnight
+    assert_eq!(hospital.is_open_at(at(10, 9)), Some(false)); // Sunday
+    assert_eq!(locations()[3].is_open_at(at(4, 9)), None);
+}
+
+#[test]
+fn finds_the_nearest_location() {
+    let locations = locations();
+    let here = Position { latitude: 31.0, longitude: 121.0, altitude: None };
+    let nearest = nearest_locations(&locations, &here);
+    assert_eq!(nearest.len(), 2);
+    assert_eq!(nearest[0].0.id, "hosp-1");
+    assert!((nearest[1].1 - 118.0).abs() < 5.0, "{}", nearest[1].1);
+}
+
+#[test]
+fn facilities_reference_locations() {
+    let report = LabReport::from_json_value(&json!({
+        "id": "lab-1", "patientId": "patient-1", "issuedAt": "2024-11-02T09:30:00Z", "results": [],
+        "facility": {"id": "lab-central", "name": "Central Laboratory", "location": {"reference": "Location/hosp-1"}}
+    }))
+    .unwrap();
+    let location = report.facility.as_ref().and_then(|facility| facility.location.as_ref()).unwrap();
+    assert!(location.points_to(&locations()[0]));
+    assert_eq!(*location, Reference::to(&locations()[0]));
+
+    let json = serde_json::to_string(&report).unwrap();
+    let borrowed: wellally::borrowed::LabReportRef = serde_json::from_str(&json).unwrap();
+    assert_eq!(borrowed.to_owned(), report);
+}
+
+#[test]
+fn rejects_invalid_coordinates_and_self_parent() {
+    let mut location = locations()[1].clone();
+    location.position = Some(Position { latitude: 95.0, longitude: 0.0, altitude: None });
+    location.part_of = Some(Reference { reference: "Location/ward-3".into(), display: None });
+    let paths: Vec<_> = location.validate().into_iter().map(|issue| issue.path).collect();
+    assert_eq!(paths, ["position.latitude", "partOf"]);
+}
+
+#[test]
+fn minimal_locations_are_read_by_their_resource_type() {
+    let bare = json!({"id": "room-4", "name": "Room 4"});
+    assert_eq!(parse_auto(bare.to_string().as_bytes(), None).unwrap_err().message(), "cannot determine resource type");
+    let location = AnyResource::from(Location::from_json_value(&bare).unwrap());
+    let parsed = parse_auto(&serde_json::to_vec(&location).unwrap(), None).unwrap();
+    assert_eq!(parsed.resources, [location]);
+}
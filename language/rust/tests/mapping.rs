@@ -0,0 +1,149 @@
+//! Checks declarative mappings from partner JSON and CSV layouts.
+
+#![cfg(feature = "mapping")]
+
+use serde_json::json;
+use wellally::mapping::{FieldMapping, Mapping, Transform};
+use wellally::{LabReport, LabValue, Person, WellAllyError};
+
+const PATIENTS: &str = r#"
+name: partner-y-patients
+resourceType: Person
+records: rows
+fields:
+  - { target: id, source: MRN, transforms: [trim, lowercase] }
+  - { target: birthDate, source: "`Date of Birth`", transforms: [{ date: "%m/%d/%Y" }] }
+  - { target: gender, source: Sex, transforms: [{ lookup: { M: male, F: female, U: unknown } }] }
+  - target: name
+    array: true
+    fields:
+      - { target: family, source: Surname }
+      - { target: given, source: Forename, array: true }
+  - { target: language, default: [en] }
+"#;
+
+const LABS: &str = r#"
+name: partner-x-labs
+resourceType: LabReport
+records: orders
+fields:
+  - { target: id, source: orderNo }
+  - { target: patientId, source: patient.mrn }
+  - { target: issuedAt, source: reported, transforms: [{ date: "%Y-%m-%d %H:%M %z" }] }
+  - { target: specimen.collectedAt, source: drawn, transforms: [{ date: "%Y-%m-%d %H:%M" }] }
+  - target: results
+    source: tests
+    array: true
+    default: []
+    fields:
+      - target: code
+        source: code
+        transforms: [uppercase, { lookup: { GLU: "2345-7", K: "2823-3" } }, { concept: "http://loinc.org" }]
+      - target: value
+        fields:
+          - { target: value, source: result, transforms: [number] }
+          - { target: unit, source: unit }
+        transforms: [{ convert: mg/dL }]
+"#;
+
+fn orders() -> serde_json::Value {
+    json!({"orders": [
+        {
+            "orderNo": "A-17",
+            "patient": {"mrn": "p-1"},
+            "reported": "2024-11-02 09:30 +0800",
+            "drawn": "2024-11-02 07:15",
+            "tests": [{"code": "glu", "result": "0.97", "unit": "g/L"}]
+        },
+        {"orderNo": "A-18", "patient": {"mrn": "p-2"}, "reported": "2024-11-03 10:00 +0000",
+         "tests": [{"code": "K", "result": "0.16", "unit": "g/L"}]}
+    ]})
+}
+
+#[test]
+fn maps_json_records_onto_lab_reports() {
+    let mapping = Mapping::from_yaml(LABS).unwrap();
+    let reports: Vec<LabReport> = mapping.map(&orders()).unwrap();
+    assert_eq!(reports.len(), 2);
+    let report = &reports[0];
+    assert_eq!(report.id, "A-17");
+    assert_eq!(report.issued_at.to_string(), "2024-11-02T09:30:00+08:00");
+    assert!(report.specimen.as_ref().unwrap().collected_at.is_some());
+    assert_eq!(report.results[0].code.coding[0].system, "http://loinc.org");
+    let LabValue::Quantity(quantity) = &report.results[0].value else { panic!("expected a quantity") };
+    assert!((quantity.value - 97.0).abs() < 1e-9);
+    assert_eq!(quantity.unit, "mg/dL");
+    assert_eq!(reports[1].results[0].code.coding[0].code, "2823-3");
+    assert!(reports[1].specimen.is_none());
+}
+
+#[test]
+fn maps_csv_rows_onto_persons() {
+    let csv = "MRN,Surname,Forename,Date of Birth,Sex\n P-1 ,Doe,Jane,04/12/1980,F\np-2,Roe,Rick,11/30/1975,\n";
+    let persons: Vec<Person> = Mapping::from_yaml(PATIENTS).unwrap().map_csv(csv).unwrap();
+    assert_eq!(persons.len(), 2);
+    assert_eq!(persons[0].id, "p-1");
+    assert_eq!(persons[0].name[0].given, ["Jane"]);
+    assert_eq!(persons[0].birth_date.to_string(), "1980-04-12");
+    assert_eq!(serde_json::to_value(persons[0].gender).unwrap(), json!("female"));
+    assert!(persons[1].gender.is_none());
+    assert_eq!(persons[1].language, Some(vec!["en".to_string()]));
+}
+
+#[test]
+fn json_and_yaml_mappings_agree() {
+    let yaml = Mapping::from_yaml(PATIENTS).unwrap();
+    let json = Mapping::from_json(&serde_json::to_string(&yaml).unwrap()).unwrap();
+    assert_eq!(json, yaml);
+    assert_eq!(yaml.fields[2].transforms[0], Transform::Lookup(
+        [("F", "female"), ("M", "male"), ("U", "unknown")].into_iter().map(|(k, v)| (k.to_string(), json!(v))).collect()
+    ));
+}
+
+#[test]
+fn errors_name_the_target_field() {
+    let mut source = orders();
+    source["orders"][0]["tests"][0]["code"] = json!("XYZ");
+    let err = Mapping::from_yaml(LABS).unwrap().map::<LabReport>(&source).unwrap_err();
+    assert!(matches!(err, WellAllyError::Conversion { .. }));
+    assert_eq!(err.path(), "results[0].code");
+    assert_eq!(err.value(), Some(&json!("XYZ")));
+
+    let mut source = orders();
+    source["orders"][0]["reported"] = json!("yesterday");
+    assert_eq!(Mapping::from_yaml(LABS).unwrap().map::<LabReport>(&source).unwrap_err().path(), "issuedAt");
+
+    let mut source = orders();
+    source["orders"][1]["tests"] = json!([]);
+    let err = Mapping::from_yaml(LABS).unwrap().map::<LabReport>(&source).unwrap_err();
+    assert!(matches!(err, WellAllyError::Validation { .. }));
+    assert_eq!(err.path(), "results");
+}
+
+#[test]
+fn rejects_ambiguous_sources_and_the_wrong_resource_type() {
+    let mapping = Mapping {
+        name: "ambiguous".to_string(),
+        description: None,
+        resource_type: "LabReport".to_string(),
+        records: None,
+        fields: vec![FieldMapping {
+            target: "id".to_string(),
+            source: Some("orders.orderNo".parse().unwrap()),
+            ..FieldMapping::default()
+        }],
+    };
+    let err = mapping.map_value(&orders()).unwrap_err();
+    assert!(err.to_string().contains("selects 2 values"));
+    assert!(mapping.map::<Person>(&orders()).is_err());
+}
+
+#[test]
+fn invalid_mappings_and_records_are_reported() {
+    let err = Mapping::from_yaml("name: x\nresourceType: Person\nfields:\n  - { target: id, source: 'a.where(' }\n")
+        .unwrap_err();
+    assert_eq!(err.path(), "fields[0].source");
+
+    let err = Mapping::from_yaml(PATIENTS).unwrap().map_csv::<Person>("MRN,Surname\np-1,Doe\n").unwrap_err();
+    assert!(matches!(err, WellAllyError::Parse { .. } | WellAllyError::Validation { .. }));
+}
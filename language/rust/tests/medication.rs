@@ -0,0 +1,110 @@
+//! Checks the daily dose and supply calculations of medication records, and
+//! their as-needed and controlled substance fields.
+
+use serde_json::json;
+use wellally::{doses_per_day, DeaSchedule, MedicationRecord, Quantity, Resource, Validate};
+
+const OXYCODONE: &str =
+    include_str!("../../../infrastructure/schemas/medication/examples/medication.oxycodone-prn.json");
+
+fn record(frequency: Option<&str>) -> MedicationRecord {
+    MedicationRecord::from_json_value(&json!({
+        "id": "med-1",
+        "patientId": "p-1",
+        "medication": {"system": "http://www.nlm.nih.gov/research/umls/rxnorm", "code": "308182"},
+        "dosage": {"value": 500, "unit": "mg"},
+        "route": {"system": "http://snomed.info/sct", "code": "26643006"},
+        "startDate": "2024-11-01",
+        "frequency": frequency,
+        "durationDays": 7
+    }))
+    .unwrap()
+}
+
+fn mg(value: f64) -> Quantity {
+    Quantity::new(value, "mg")
+}
+
+#[test]
+fn frequencies_are_understood() {
+    assert_eq!(doses_per_day("QD"), Some(1.0));
+    assert_eq!(doses_per_day("b.i.d."), Some(2.0));
+    assert_eq!(doses_per_day("tid"), Some(3.0));
+    assert_eq!(doses_per_day("Q6H"), Some(4.0));
+    assert_eq!(doses_per_day("QOD"), Some(0.5));
+    assert_eq!(doses_per_day("PRN"), None);
+    assert_eq!(doses_per_day("Q0H"), None);
+}
+
+#[test]
+fn daily_dose_and_course_total() {
+    let med = record(Some("TID"));
+    assert_eq!(med.daily_dose(), Some(mg(1500.0)));
+    assert_eq!(med.total_quantity_needed(), Some(mg(10500.0)));
+
+    let mut by_end_date = record(Some("BID"));
+    by_end_date.duration_days = None;
+    by_end_date.end_date = Some("2024-11-10".parse().unwrap());
+    assert_eq!(by_end_date.treatment_days(), Some(10));
+    assert_eq!(by_end_date.total_quantity_needed(), Some(mg(10000.0)));
+
+    assert_eq!(record(Some("PRN")).daily_dose(), None);
+    assert_eq!(record(None).total_quantity_needed(), None);
+}
+
+#[test]
+fn days_supply_of_a_dispensed_quantity() {
+    let med = record(Some("TID"));
+    assert_eq!(med.days_supply(&mg(15000.0)), Some(10));
+    assert_eq!(med.days_supply(&mg(14999.0)), Some(9));
+    assert_eq!(med.days_supply(&Quantity::new(30.0, "{tbl}")), None);
+    assert_eq!(record(Some("PRN")).days_supply(&mg(15000.0)), None);
+}
+
+#[test]
+fn prn_and_controlled_fields_round_trip() {
+    let med = MedicationRecord::from_json(OXYCODONE).unwrap();
+    assert!(med.is_prn);
+    assert_eq!(med.max_dose_per_day, Some(mg(20.0)));
+    assert_eq!(med.dea_schedule, Some(DeaSchedule::ScheduleII));
+    assert!(med.is_controlled());
+    assert!(med.validate().is_empty(), "{:?}", med.validate());
+    let written = serde_json::to_value(&med).unwrap();
+    assert_eq!(written["deaSchedule"], "CII");
+    assert_eq!(written["prnReason"]["text"], "术后疼痛");
+    assert_eq!(MedicationRecord::from_json_value(&written).unwrap(), med);
+
+    let scheduled = serde_json::to_value(record(Some("TID"))).unwrap();
+    assert!(scheduled.get("isPrn").is_none());
+    assert!(!record(Some("TID")).is_controlled());
+}
+
+#[test]
+fn dea_schedules_limit_refills() {
+    assert_eq!(DeaSchedule::ScheduleII.max_refills(), Some(0));
+    assert_eq!(DeaSchedule::ScheduleIV.max_refills(), Some(5));
+    assert_eq!(DeaSchedule::ScheduleV.max_refills(), None);
+    assert!(DeaSchedule::ScheduleII < DeaSchedule::ScheduleV);
+}
+
+#[test]
+fn prn_records_need_a_max_daily_dose() {
+    let mut med = record(None);
+    med.is_prn = true;
+    let paths: Vec<_> = med.validate().into_iter().map(|issue| issue.path).collect();
+    assert_eq!(paths, ["maxDosePerDay"]);
+
+    med.max_dose_per_day = Some(Quantity::new(0.25, "g"));
+    let paths: Vec<_> = med.validate().into_iter().map(|issue| issue.path).collect();
+    assert_eq!(paths, ["maxDosePerDay"], "below one 500 mg dose");
+    med.max_dose_per_day = Some(Quantity::new(3.0, "g"));
+    assert!(med.validate().is_empty());
+    med.max_dose_per_day = Some(Quantity::new(3.0, "mL"));
+    let paths: Vec<_> = med.validate().into_iter().map(|issue| issue.path).collect();
+    assert_eq!(paths, ["maxDosePerDay.unit"]);
+
+    let mut scheduled = record(Some("TID"));
+    scheduled.prn_reason = Some(wellally::catalog::concept("http://snomed.info/sct", "22253000", "Pain"));
+    let paths: Vec<_> = scheduled.validate().into_iter().map(|issue| issue.path).collect();
+    assert_eq!(paths, ["isPrn"]);
+}
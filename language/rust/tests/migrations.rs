@@ -0,0 +1,113 @@
+//! Checks version detection and chaining of migration steps.
+
+use serde_json::{json, Value};
+use wellally::migrations::{self, detect_version, Migration, Migrator, SchemaVersion};
+use wellally::{
+    CommunicationRecord, FamilyHealthTree, ImagingReport, LabReport, Location, MedicationRecord, Person, Questionnaire,
+    QuestionnaireResponse, Referral, Resource, Task, Validate, WellAllyError,
+};
+
+const V0_2_0: SchemaVersion = SchemaVersion::new(0, 2, 0);
+const V0_3_0: SchemaVersion = SchemaVersion::new(0, 3, 0);
+
+/// 0.2.0 renames `issuedAt` to `issued`.
+fn rename_issued(document: &mut Value) -> Result<(), WellAllyError> {
+    if let Some(map) = document.as_object_mut() {
+        if let Some(issued) = map.remove("issuedAt") {
+            map.insert("issued".into(), issued);
+        }
+    }
+    Ok(())
+}
+
+fn restore_issued_at(document: &mut Value) -> Result<(), WellAllyError> {
+    if let Some(map) = document.as_object_mut() {
+        if let Some(issued) = map.remove("issued") {
+            map.insert("issuedAt".into(), issued);
+        }
+    }
+    Ok(())
+}
+
+/// 0.3.0 wraps `panel` in an array.
+fn wrap_panel(document: &mut Value) -> Result<(), WellAllyError> {
+    if let Some(panel) = document.get_mut("panel") {
+        *panel = Value::Array(vec![panel.take()]);
+    }
+    Ok(())
+}
+
+fn migrator() -> Migrator {
+    Migrator::new()
+        .with(Migration { from: SchemaVersion::V0_1_0, to: V0_2_0, apply: rename_issued })
+        .with(Migration { from: V0_2_0, to: SchemaVersion::V0_1_0, apply: restore_issued_at })
+        .with(Migration { from: V0_2_0, to: V0_3_0, apply: wrap_panel })
+}
+
+fn report() -> Value {
+    json!({"id": "lab-1", "patientId": "p-1", "issuedAt": "2024-11-02", "results": [], "panel": {"coding": [], "text": "CBC"}})
+}
+
+#[test]
+fn versions_are_detected() {
+    assert_eq!(detect_version(&report()).unwrap(), SchemaVersion::V0_1_0);
+    assert_eq!(detect_version(&json!({"schemaVersion": "v0.2.0"})).unwrap(), V0_2_0);
+    let err = detect_version(&json!({"schemaVersion": "0.2"})).unwrap_err();
+    assert_eq!(err.path(), "schemaVersion");
+    assert!(detect_version(&json!({"schemaVersion": 2})).is_err());
+    assert_eq!("1.10.3".parse::<SchemaVersion>().unwrap().to_string(), "1.10.3");
+    assert!(SchemaVersion::new(0, 10, 0) > V0_2_0);
+}
+
+#[test]
+fn steps_are_chained_in_both_directions() {
+    let migrator = migrator();
+    let upgraded = migrator.migrate(report(), SchemaVersion::V0_1_0, V0_3_0).unwrap();
+    assert_eq!(upgraded["issued"], "2024-11-02");
+    assert_eq!(upgraded["panel"], json!([{"coding": [], "text": "CBC"}]));
+    assert_eq!(upgraded["schemaVersion"], "0.3.0");
+
+    let halfway = migrator.migrate(report(), SchemaVersion::V0_1_0, V0_2_0).unwrap();
+    let back = migrator.migrate(halfway, V0_2_0, SchemaVersion::V0_1_0).unwrap();
+    assert_eq!(back["issuedAt"], "2024-11-02");
+    assert_eq!(LabReport::from_json_value(&back).unwrap().schema_version.as_deref(), Some("0.1.0"));
+
+    let err = migrator.migrate(report(), V0_3_0, SchemaVersion::V0_1_0).unwrap_err();
+    assert_eq!(err.message(), "no migration from 0.3.0 towards 0.1.0");
+}
+
+#[test]
+fn current_documents_are_left_alone() {
+    assert_eq!(migrations::upgrade(report()).unwrap(), report());
+    assert_eq!(migrations::migrate(report(), SchemaVersion::CURRENT, SchemaVersion::CURRENT).unwrap(), report());
+    assert!(migrations::migrate(report(), SchemaVersion::V0_1_0, V0_2_0).is_err());
+}
+
+#[test]
+fn newer_versions_fail_validation() {
+    let mut report = LabReport::from_json_value(&report()).unwrap();
+    report.schema_version = Some("0.2.0".into());
+    let issues = report.validate();
+    let issue = issues.iter().find(|issue| issue.path == "schemaVersion").unwrap();
+    assert_eq!(issue.message, "0.2.0 is newer than the supported 0.1.0");
+}
+
+#[test]
+fn schema_ids_carry_the_current_version() {
+    let suffix = format!("/v{}", SchemaVersion::CURRENT);
+    for id in [
+        Person::SCHEMA_ID,
+        LabReport::SCHEMA_ID,
+        ImagingReport::SCHEMA_ID,
+        MedicationRecord::SCHEMA_ID,
+        FamilyHealthTree::SCHEMA_ID,
+        Questionnaire::SCHEMA_ID,
+        QuestionnaireResponse::SCHEMA_ID,
+        Referral::SCHEMA_ID,
+        Task::SCHEMA_ID,
+        CommunicationRecord::SCHEMA_ID,
+        Location::SCHEMA_ID,
+    ] {
+        assert!(id.ends_with(&suffix), "{}", id);
+    }
+}
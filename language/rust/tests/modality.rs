@@ -0,0 +1,55 @@
+//! Checks the open set of DICOM modality codes.
+
+use serde_json::json;
+use wellally::i18n::{Locale, LocalizedDisplay};
+use wellally::{ImagingReport, ModalityCode, Resource, Validate};
+
+fn report(code: &str) -> serde_json::Value {
+    json!({
+        "id": "img-1",
+        "patientId": "p-1",
+        "modality": {"system": "http://dicom.nema.org/resources/ontology/DCM", "code": code},
+        "bodySite": {"site": {"system": "http://snomed.info/sct", "code": "76752008", "display": "Breast structure"}},
+        "reportedAt": "2024-11-02T09:30:00Z"
+    })
+}
+
+#[test]
+fn known_and_unknown_codes_round_trip() {
+    for (code, expected) in [("NM", ModalityCode::NM), ("MG", ModalityCode::MG), ("OT", ModalityCode::OT)] {
+        let json = report(code);
+        let parsed = ImagingReport::from_json_value(&json).unwrap();
+        assert_eq!(parsed.modality.code, expected);
+        assert_eq!(serde_json::to_value(&parsed).unwrap(), json);
+    }
+    let vendor = ImagingReport::from_json_value(&report("ZZ-VENDOR")).unwrap();
+    assert_eq!(vendor.modality.code, ModalityCode::Other("ZZ-VENDOR".into()));
+    assert_eq!(serde_json::to_value(&vendor).unwrap(), report("ZZ-VENDOR"));
+    assert!(vendor.is_valid());
+
+    let blank = ImagingReport::from_json_value(&report("")).unwrap();
+    assert_eq!(blank.validate()[0].path, "modality.code");
+}
+
+#[test]
+fn from_str_accepts_aliases() {
+    assert_eq!("mri".parse::<ModalityCode>().unwrap(), ModalityCode::MR);
+    assert_eq!("PET".parse::<ModalityCode>().unwrap(), ModalityCode::PT);
+    assert_eq!("X-ray".parse::<ModalityCode>().unwrap(), ModalityCode::XR);
+    assert_eq!(" dx ".parse::<ModalityCode>().unwrap(), ModalityCode::DX);
+    assert_eq!("Mammography".parse::<ModalityCode>().unwrap(), ModalityCode::MG);
+    assert_eq!("Thermo".parse::<ModalityCode>().unwrap(), ModalityCode::Other("Thermo".into()));
+    assert!(" ".parse::<ModalityCode>().is_err());
+    assert_eq!(ModalityCode::IVUS.to_string(), "IVUS");
+    assert!(ModalityCode::KNOWN.contains(&ModalityCode::XA));
+}
+
+#[test]
+fn every_code_has_labels() {
+    for code in ModalityCode::KNOWN {
+        assert!(!code.localized_display(Locale::En).is_empty());
+        assert!(!code.localized_display(Locale::Zh).is_empty());
+    }
+    assert_eq!(ModalityCode::NM.localized_display(Locale::Zh), "核医学");
+    assert_eq!(ModalityCode::Other("ZZ".into()).localized_display(Locale::En), "Other modality");
+}
@@ -0,0 +1,91 @@
+//! Checks narrative templates and the imaging report layout.
+
+use serde_json::json;
+use wellally::narrative::{NarrativeFields, Template};
+use wellally::{ImagingReport, Resource};
+
+fn report() -> ImagingReport {
+    ImagingReport::from_json_value(&json!({
+        "id": "img-1",
+        "patientId": "p-1",
+        "modality": {"system": "http://dicom.nema.org/resources/ontology/DCM", "code": "CT", "display": "CT"},
+        "bodySite": {"system": "http://snomed.info/sct", "code": "51185008", "display": "chest"},
+        "reportedAt": "2024-10-15T14:20:00Z",
+        "performer": {"name": "Dr. Li", "role": "Radiologist"},
+        "technique": "Low-dose non-contrast CT, 1.25 mm slices.",
+        "findings": ["Lungs are clear.", "No lymphadenopathy."],
+        "impression": "No acute findings.",
+        "radiationDose": {"ctdiVol_mGy": 3.2, "dlp_mGy_cm": 110.5}
+    }))
+    .unwrap()
+}
+
+#[test]
+fn default_layout_has_all_sections() {
+    assert_eq!(
+        report().narrative(),
+        "CT chest\n\
+         Reported: 2024-10-15T14:20:00Z by Dr. Li\n\
+         \n\
+         TECHNIQUE:\n\
+         Low-dose non-contrast CT, 1.25 mm slices.\n\
+         Radiation dose: CTDIvol 3.2 mGy, DLP 110.5 mGy·cm\n\
+         \n\
+         COMPARISON:\n\
+         None.\n\
+         \n\
+         FINDINGS:\n\
+         - Lungs are clear.\n\
+         - No lymphadenopathy.\n\
+         \n\
+         IMPRESSION:\n\
+         No acute findings.\n"
+    );
+}
+
+#[test]
+fn empty_fields_use_fallbacks() {
+    let mut bare = report();
+    bare.technique = None;
+    bare.findings = Some(vec!["  ".into()]);
+    bare.impression = None;
+    bare.radiation_dose = None;
+    bare.performer = None;
+    let text = bare.narrative();
+    assert!(text.starts_with("CT chest\nReported: 2024-10-15T14:20:00Z\n"));
+    assert!(text.contains("TECHNIQUE:\nCT of the chest.\n\nCOMPARISON:"));
+    assert!(text.contains("FINDINGS:\nNo findings recorded.\n"));
+    assert!(text.ends_with("IMPRESSION:\nPending.\n"));
+}
+
+#[test]
+fn clinical_history_and_indications() {
+    let mut ordered = report();
+    ordered.clinical_history = Some("58-year-old smoker, annual screening.".into());
+    ordered.reason_code = Some(vec![serde_json::from_value(json!({
+        "coding": [{"system": "http://snomed.info/sct", "code": "77176002", "display": "Smoker"}]
+    }))
+    .unwrap()]);
+    let text = ordered.narrative();
+    assert!(text.contains("by Dr. Li\n\nCLINICAL HISTORY:\n58-year-old smoker, annual screening.\n\nTECHNIQUE:\n"));
+    assert!(!report().narrative().contains("CLINICAL HISTORY"));
+    assert_eq!(ordered.narrative_fields()["reasons"], ["Smoker"]);
+}
+
+#[test]
+fn custom_templates_and_fields() {
+    let template: Template = "{{#findings}}[{{.}}]{{/findings}} {{missing}}|{{^missing}}none{{/missing}}".parse().unwrap();
+    assert_eq!(report().render_narrative(&template), "[Lungs are clear.][No lymphadenopathy.] |none");
+
+    let mut fields = NarrativeFields::new();
+    fields.insert("items".into(), vec!["a".into(), "b".into()]);
+    assert_eq!(Template::parse("{{items}}").unwrap().render(&fields), "a\nb");
+}
+
+#[test]
+fn malformed_templates_are_rejected() {
+    for source in ["{{#findings}}open", "{{/findings}}", "{{#a}}{{/b}}", "{{name", "{{}}"] {
+        let err = Template::parse(source).unwrap_err();
+        assert!(matches!(err, wellally::WellAllyError::Parse { .. }), "{source}");
+    }
+}
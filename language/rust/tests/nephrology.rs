@@ -0,0 +1,109 @@
+//! Checks dialysis sessions and CKD staging.
+
+use serde_json::json;
+use wellally::catalog::{concept, SNOMED_CT};
+use wellally::nephrology::{
+    AccessType, AlbuminuriaCategory, CkdRisk, CkdStage, DialysisAccess, DialysisModality, DialysisSession, GfrCategory,
+    ULTRAFILTRATION_RATE_LIMIT,
+};
+use wellally::{LabReport, Person, Quantity, Resource, Validate};
+
+mod common;
+
+const EXAMPLE: &str = include_str!("../../../infrastructure/schemas/dialysis/examples/dialysis.hemodialysis.json");
+
+#[test]
+fn published_example_round_trips() {
+    let (session, written) = common::round_trip::<DialysisSession>(EXAMPLE);
+    assert_eq!(session.modality, DialysisModality::Hemodialysis);
+    assert_eq!(session.access.as_ref().unwrap().access_type, AccessType::ArteriovenousFistula);
+    assert!(session.has_complications());
+    assert_eq!(written["access"]["type"], "arteriovenous-fistula");
+}
+
+#[test]
+fn derives_fluid_removal() {
+    let session = DialysisSession::from_json(EXAMPLE).unwrap();
+    assert_eq!(session.end().to_string(), "2024-10-02 11:30:00");
+    assert_eq!(session.ultrafiltration_ml(), Some(2400.0));
+    assert!((session.weight_loss_kg().unwrap() - 2.3).abs() < 1e-9);
+    let rate = session.ultrafiltration_rate().unwrap();
+    assert!((rate - 8.81).abs() < 0.01, "{}", rate);
+    assert!(rate < ULTRAFILTRATION_RATE_LIMIT);
+
+    let start = "2024-10-02T22:00:00Z".parse().unwrap();
+    let mut pd = DialysisSession::new("pd-1", "p-1", DialysisModality::Apd, start, 540);
+    pd.ultrafiltration_volume = Some(Quantity::new(1.5, "L"));
+    assert!(pd.modality.is_peritoneal());
+    assert!((pd.ultrafiltration_ml().unwrap() - 1500.0).abs() < 1e-9);
+    assert_eq!(pd.ultrafiltration_rate(), None);
+    assert!(!pd.has_complications());
+}
+
+#[test]
+fn validation_reports_inconsistent_sessions() {
+    let mut session = DialysisSession::new("pd-1", "p-1", DialysisModality::Capd, "2024-10-02".parse().unwrap(), 0);
+    session.access = Some(DialysisAccess { access_type: AccessType::CentralVenousCatheter, site: None });
+    session.ultrafiltration_volume = Some(Quantity::new(1.2, "kg"));
+    session.post_weight = Some(Quantity::new(60.0, "kg"));
+    session.kt_v = Some(-1.0);
+    session.complications = Some(vec![concept(SNOMED_CT, "45007003", "Low blood pressure")]);
+    let paths: Vec<_> = session.validate().into_iter().map(|issue| issue.path).collect();
+    assert_eq!(paths, ["durationMinutes", "access.type", "ultrafiltrationVolume.unit", "ktV"]);
+}
+
+#[test]
+fn kdigo_categories_and_risk() {
+    assert_eq!(GfrCategory::from_egfr(90.0), GfrCategory::G1);
+    assert_eq!(GfrCategory::from_egfr(59.9), GfrCategory::G3a);
+    assert_eq!(GfrCategory::from_egfr(14.0).display(), "Kidney failure");
+    assert_eq!(AlbuminuriaCategory::from_acr_mg_g(29.0), AlbuminuriaCategory::A1);
+    assert_eq!(AlbuminuriaCategory::from_acr_mg_g(300.0), AlbuminuriaCategory::A2);
+    assert_eq!(AlbuminuriaCategory::from_acr(&Quantity::new(40.0, "mg/mmol")), Some(AlbuminuriaCategory::A3));
+    assert_eq!(AlbuminuriaCategory::from_acr(&Quantity::new(40.0, "mg/L")), None);
+
+    let risk = |egfr, albuminuria| CkdStage::new(egfr, Some(albuminuria)).risk().unwrap();
+    assert_eq!(risk(95.0, AlbuminuriaCategory::A1), CkdRisk::Low);
+    assert_eq!(risk(70.0, AlbuminuriaCategory::A2), CkdRisk::ModeratelyIncreased);
+    assert_eq!(risk(50.0, AlbuminuriaCategory::A2), CkdRisk::High);
+    assert_eq!(risk(35.0, AlbuminuriaCategory::A1), CkdRisk::High);
+    assert_eq!(risk(20.0, AlbuminuriaCategory::A1), CkdRisk::VeryHigh);
+    assert_eq!(CkdStage::new(50.0, None).risk(), None);
+    assert_eq!(CkdStage::new(50.0, None).to_string(), "G3a");
+}
+
+fn person(gender: &str) -> Person {
+    Person::from_json_value(&json!({
+        "id": "p-1", "birthDate": "1964-03-15", "gender": gender, "name": [{"family": "Doe", "given": ["John"]}]
+    }))
+    .unwrap()
+}
+
+fn report(id: &str, issued: &str, code: &str, value: f64, unit: &str) -> LabReport {
+    LabReport::from_json_value(&json!({
+        "id": id, "patientId": "p-1", "issuedAt": issued,
+        "results": [{
+            "code": {"coding": [{"system": "http://loinc.org", "code": code}]},
+            "value": {"value": value, "unit": unit}
+        }]
+    }))
+    .unwrap()
+}
+
+#[test]
+fn stages_from_lab_reports() {
+    let reports = [
+        report("lab-1", "2023-01-10", "14682-9", 80.0, "umol/L"),
+        report("lab-2", "2024-10-01", "14682-9", 106.0, "umol/L"),
+        report("lab-3", "2024-10-01", "9318-7", 45.0, "mg/mmol"),
+        report("lab-4", "2024-11-01", "98979-8", 30.0, "mL/min/{1.73_m2}"),
+    ];
+    let stage = CkdStage::assess(&reports, &person("male")).unwrap();
+    assert_eq!(stage.egfr.round(), 69.0);
+    assert_eq!(stage.to_string(), "G2 A3");
+    assert_eq!(stage.risk(), Some(CkdRisk::High));
+
+    let stage = CkdStage::assess(&reports, &person("unknown")).unwrap();
+    assert_eq!((stage.egfr, stage.gfr), (30.0, GfrCategory::G3b));
+    assert_eq!(CkdStage::assess(&reports[..2], &person("unknown")), None);
+}
@@ -0,0 +1,126 @@
+//! Tests for the OMOP CDM export.
+
+#![cfg(feature = "omop")]
+
+use serde_json::{json, Value};
+use wellally::catalog::{LOINC, RXNORM, SNOMED_CT, UCUM};
+use wellally::io::AnyResource;
+use wellally::omop::{self, ConceptMap, OmopExporter, EHR, FEMALE, NO_MATCHING_CONCEPT};
+use wellally::Comparator;
+
+fn resource(json: Value) -> AnyResource {
+    serde_json::from_value(json).unwrap()
+}
+
+fn resources() -> Vec<AnyResource> {
+    vec![
+        resource(json!({
+            "id": "p-1", "resourceType": "Person", "birthDate": "1980-04", "gender": "female",
+            "name": [{"family": "Doe", "given": ["Jane"]}]
+        })),
+        AnyResource::from_typed_json_value("LabReport", &json!({
+            "id": "lab-1", "patientId": "p-1", "issuedAt": "2024-05-20T08:40:00+08:00",
+            "results": [
+                {
+                    "code": {"coding": [{"system": LOINC, "code": "2345-7", "display": "Glucose"}]},
+                    "value": {"value": 5.4, "unit": "mmol/L"},
+                    "referenceRange": [{
+                        "low": {"value": 3.9, "unit": "mmol/L"}, "high": {"value": 6.1, "unit": "mmol/L"}
+                    }]
+                },
+                {
+                    "code": {"coding": [{"system": LOINC, "code": "1988-5"}]},
+                    "value": {"comparator": "<", "value": {"value": 5, "unit": "mg/L"}},
+                    "effective": "2024-05-19T07:00:00+08:00"
+                },
+                {
+                    "code": {"coding": [{"system": LOINC, "code": "5778-6"}]},
+                    "value": {"coding": [{"system": SNOMED_CT, "code": "371244009", "display": "Yellow"}]}
+                }
+            ]
+        }))
+        .unwrap(),
+        resource(json!({
+            "id": "med-1", "patientId": "p-2",
+            "medication": {"system": RXNORM, "code": "860975", "display": "Metformin 500 MG Oral Tablet"},
+            "dosage": {"value": 500, "unit": "mg"}, "route": {"system": SNOMED_CT, "code": "26643006"},
+            "frequency": "BID", "startDate": "2024-01-01", "durationDays": 30
+        })),
+        resource(json!({
+            "id": "cond-1", "patientId": "p-1", "clinicalStatus": "resolved",
+            "onset": "2023-02-01", "abatement": "2023-03-15",
+            "code": {"coding": [{"system": SNOMED_CT, "code": "233604007", "display": "Pneumonia"}]}
+        })),
+        resource(json!({
+            "id": "cond-2", "patientId": "p-1", "clinicalStatus": "active", "verificationStatus": "refuted",
+            "onset": "2023-02-01", "code": {"coding": [{"system": SNOMED_CT, "code": "44054006"}]}
+        })),
+    ]
+}
+
+fn concepts() -> ConceptMap {
+    [
+        (LOINC, "2345-7", 3004501),
+        (LOINC, "1988-5", 3020460),
+        (UCUM, "mmol/L", 8753),
+        (RXNORM, "860975", 40163924),
+        (SNOMED_CT, "26643006", 4132161),
+        (SNOMED_CT, "233604007", 255848),
+    ]
+    .into_iter()
+    .collect()
+}
+
+#[test]
+fn maps_every_table() {
+    let concepts = concepts();
+    let tables = OmopExporter::new().with_concepts(|coding| concepts.get(coding)).export(&resources());
+    assert_eq!(tables.person_ids.get("p-1"), Some(&1));
+    assert_eq!(tables.person_ids.get("p-2"), Some(&2));
+
+    let [person] = tables.person.as_slice() else { panic!("{:?}", tables.person) };
+    assert_eq!((person.gender_concept_id, person.year_of_birth), (FEMALE, 1980));
+    assert_eq!((person.month_of_birth, person.day_of_birth), (Some(4), None));
+    assert_eq!(person.person_source_value, "p-1");
+
+    let [glucose, crp, color] = tables.measurement.as_slice() else { panic!("{:?}", tables.measurement) };
+    assert_eq!((glucose.measurement_id, glucose.measurement_concept_id), (1, 3004501));
+    assert_eq!(glucose.measurement_date.to_string(), "2024-05-20");
+    assert_eq!((glucose.value_as_number, glucose.unit_concept_id), (Some(5.4), Some(8753)));
+    assert_eq!((glucose.range_low, glucose.range_high), (Some(3.9), Some(6.1)));
+    assert_eq!(glucose.measurement_type_concept_id, EHR);
+    assert_eq!(crp.operator_concept_id, Some(omop::operator_concept(Comparator::LessThan)));
+    assert_eq!(crp.measurement_date.to_string(), "2024-05-19");
+    assert_eq!(crp.measurement_datetime, None);
+    assert_eq!(crp.unit_concept_id, Some(NO_MATCHING_CONCEPT));
+    assert_eq!(color.value_as_concept_id, Some(NO_MATCHING_CONCEPT));
+    assert_eq!(color.value_source_value.as_deref(), Some("371244009"));
+
+    let [drug] = tables.drug_exposure.as_slice() else { panic!("{:?}", tables.drug_exposure) };
+    assert_eq!((drug.person_id, drug.drug_concept_id, drug.route_concept_id), (2, 40163924, 4132161));
+    assert_eq!(drug.drug_exposure_end_date.to_string(), "2024-01-30");
+    assert_eq!((drug.days_supply, drug.sig.as_deref()), (Some(30), Some("BID")));
+
+    let [pneumonia] = tables.condition_occurrence.as_slice() else { panic!("{:?}", tables.condition_occurrence) };
+    assert_eq!((pneumonia.condition_concept_id, pneumonia.condition_source_value.as_str()), (255848, "233604007"));
+    assert_eq!(pneumonia.condition_end_date.map(|date| date.to_string()).as_deref(), Some("2023-03-15"));
+}
+
+#[test]
+fn unknown_codes_map_to_no_matching_concept() {
+    let tables = OmopExporter::new().export(&resources());
+    assert!(tables.measurement.iter().all(|row| row.measurement_concept_id == NO_MATCHING_CONCEPT));
+    assert_eq!(tables.drug_exposure[0].drug_concept_id, NO_MATCHING_CONCEPT);
+    assert_eq!(tables.drug_exposure[0].drug_source_value, "860975");
+}
+
+#[test]
+fn rows_serialize_with_cdm_column_names() {
+    let tables = OmopExporter::default().export(&resources());
+    let person = serde_json::to_value(&tables.person[0]).unwrap();
+    assert_eq!(person["gender_source_value"], "female");
+    assert_eq!(person["race_concept_id"], 0);
+    let measurement = serde_json::to_value(&tables.measurement[0]).unwrap();
+    assert_eq!(measurement["measurement_datetime"], "2024-05-20T08:40:00");
+    assert_eq!(measurement["unit_source_value"], "mmol/L");
+}
@@ -0,0 +1,230 @@
+//! Tests for the mCODE-aligned oncology structures.
+
+use chrono::NaiveDate;
+use serde_json::json;
+use wellally::catalog::{concept, LOINC, RXNORM, SNOMED_CT};
+use wellally::extension::Extensible;
+use wellally::oncology::{
+    self, CancerCondition, ChemotherapyRegimen, RadiotherapyCourse, RadiotherapyModality, RecistResponse, TnmStaging,
+    TumorAssessment, TumorMarker,
+};
+use wellally::{Coding, Condition, ImagingReport, LabReport, MedicationRecord, Quantity, Resource};
+
+fn date(text: &str) -> NaiveDate {
+    text.parse().unwrap()
+}
+
+fn colon_cancer() -> Condition {
+    Condition::new(
+        "cond-1",
+        "p-1",
+        concept("http://hl7.org/fhir/sid/icd-10-cm", "C18.7", "Malignant neoplasm of sigmoid colon"),
+    )
+}
+
+fn drug(id: &str, code: &str, start: &str) -> MedicationRecord {
+    serde_json::from_value(json!({
+        "id": id, "patientId": "p-1",
+        "medication": {"system": RXNORM, "code": code},
+        "dosage": {"value": 85, "unit": "mg/m2"}, "route": {"system": SNOMED_CT, "code": "47625008"},
+        "frequency": "ONCE", "startDate": start, "durationDays": 1
+    }))
+    .unwrap()
+}
+
+fn ct(id: &str, reported_at: &str) -> ImagingReport {
+    ImagingReport::from_json_value(&json!({
+        "id": id, "patientId": "p-1",
+        "modality": {"system": "http://dicom.nema.org/resources/ontology/DCM", "code": "CT"},
+        "bodySite": {"system": SNOMED_CT, "code": "818981001", "display": "Abdomen"},
+        "reportedAt": reported_at
+    }))
+    .unwrap()
+}
+
+#[test]
+fn parses_and_formats_tnm() {
+    let staging = TnmStaging::parse("ypT0 N1a M0").unwrap();
+    assert_eq!(staging.prefix, "yp");
+    assert_eq!(
+        (staging.primary_tumor.as_deref(), staging.regional_nodes.as_deref(), staging.distant_metastases.as_deref()),
+        (Some("T0"), Some("N1a"), Some("M0"))
+    );
+    assert!(staging.is_pathologic() && !staging.is_metastatic());
+    assert_eq!(staging.to_string(), "ypT0N1aM0");
+    assert_eq!(TnmStaging::parse("cTisNXM1c").unwrap().distant_metastases.as_deref(), Some("M1c"));
+    assert!(TnmStaging::parse("cTisNXM1c").unwrap().is_metastatic());
+
+    for invalid in ["", "stage II", "xT1N0", "T1T2", "T1NM0"] {
+        assert_eq!(TnmStaging::parse(invalid), None, "{}", invalid);
+    }
+}
+
+#[test]
+fn staging_round_trips_through_condition_extensions() {
+    assert!(oncology::is_cancer(&colon_cancer()));
+    let mut cancer = CancerCondition::new(colon_cancer());
+    cancer.histology = Some(concept("http://terminology.hl7.org/CodeSystem/icd-o-3", "8140/3", "Adenocarcinoma"));
+    cancer.staging = TnmStaging::parse("pT3 N1a M0");
+    cancer.stage_group = Some("IIIB".into());
+    assert_eq!(cancer.reference().reference, "Condition/cond-1");
+
+    let condition = cancer.clone().into_condition();
+    assert_eq!(condition.extension(oncology::TNM_STAGING).and_then(|value| value.as_str()), Some("pT3N1aM0"));
+    let json = serde_json::to_value(&condition).unwrap();
+    assert_eq!(Condition::from_json_value(&json).unwrap(), condition);
+    assert_eq!(CancerCondition::from_condition(condition), cancer);
+
+    let mut unstaged = colon_cancer();
+    unstaged.set_extension(oncology::TNM_STAGING, "stage III");
+    let read = CancerCondition::from_condition(unstaged);
+    assert_eq!(read.staging, None);
+    assert!(read.condition.extension(oncology::TNM_STAGING).is_some());
+}
+
+#[test]
+fn reads_tumor_marker_series() {
+    let report = |id: &str, issued: &str, value: f64| {
+        LabReport::from_json_value(&json!({
+            "id": id, "patientId": "p-1", "issuedAt": issued,
+            "results": [{
+                "code": {"coding": [{"system": LOINC, "code": "2039-6", "display": "CEA"}]},
+                "value": {"value": value, "unit": "ng/mL"}
+            }]
+        }))
+        .unwrap()
+    };
+    let reports = [report("lab-2", "2024-04-01T08:00:00Z", 3.0), report("lab-1", "2024-01-01T08:00:00Z", 12.0)];
+    assert_eq!(TumorMarker::of(&reports[0].results[0]), Some(TumorMarker::Cea));
+
+    let series = TumorMarker::Cea.series(&reports);
+    assert_eq!(series.iter().map(|point| point.report_id).collect::<Vec<_>>(), ["lab-1", "lab-2"]);
+    assert_eq!(TumorMarker::percent_change(&series), Some(-75.0));
+    assert!(TumorMarker::Psa.series(&reports).is_empty());
+}
+
+#[test]
+fn counts_chemotherapy_cycles() {
+    let mut regimen = ChemotherapyRegimen::new(
+        "FOLFOX",
+        14,
+        vec![drug("m-1", "32592", "2024-02-01"), drug("m-2", "4492", "2024-02-01"), drug("m-3", "32592", "2024-02-15")],
+    );
+    regimen.planned_cycles = Some(12);
+    regimen.condition = Some(CancerCondition::new(colon_cancer()).reference());
+    assert_eq!(regimen.start_date(), Some(date("2024-02-01")));
+    assert_eq!(regimen.cycle_on(date("2024-01-31")), None);
+    assert_eq!(regimen.cycle_on(date("2024-02-14")), Some(1));
+    assert_eq!(regimen.cycle_on(date("2024-02-15")), Some(2));
+    assert_eq!(regimen.cycle_on(date("2024-07-17")), Some(12));
+    assert_eq!(regimen.cycle_on(date("2024-07-18")), None);
+    assert_eq!(regimen.agents().iter().map(|agent| agent.code.as_str()).collect::<Vec<_>>(), ["32592", "4492"]);
+
+    let json = serde_json::to_value(&regimen).unwrap();
+    assert_eq!(json["cycleDays"], 14);
+    assert_eq!(serde_json::from_value::<ChemotherapyRegimen>(json).unwrap(), regimen);
+}
+
+#[test]
+fn computes_radiotherapy_doses() {
+    let course = RadiotherapyCourse {
+        modality: RadiotherapyModality::Photon,
+        body_site: Coding { system: SNOMED_CT.into(), code: "34402009".into(), display: None }.into(),
+        condition: None,
+        intent: None,
+        total_dose: Quantity::new(5000.0, "cGy"),
+        fractions: 25,
+        start_date: date("2024-03-01"),
+        end_date: None,
+    };
+    assert_eq!(course.dose_per_fraction().unwrap().value, 200.0);
+    let bed = course.biologically_effective_dose(10.0).unwrap();
+    assert!((bed - 60.0).abs() < 1e-9, "{}", bed);
+    assert!((course.eqd2(10.0).unwrap() - 50.0).abs() < 1e-9);
+
+    let short_course = RadiotherapyCourse { total_dose: Quantity::new(25.0, "Gy"), fractions: 5, ..course };
+    assert!((short_course.eqd2(10.0).unwrap() - 31.25).abs() < 1e-9);
+    assert_eq!(RadiotherapyCourse { fractions: 0, ..short_course }.biologically_effective_dose(10.0), None);
+}
+
+#[test]
+fn classifies_recist_response() {
+    let assessment = |id: &str, day: &str, lesions: &[f64], new_lesions: bool| {
+        TumorAssessment::from_imaging(&ct(id, &format!("{}T10:00:00Z", day)), lesions.to_vec(), new_lesions)
+    };
+    let baseline = assessment("img-1", "2024-01-10", &[30.0, 20.0], false);
+    let partial = assessment("img-2", "2024-03-10", &[18.0, 12.0], false);
+    assert_eq!(baseline.imaging_report.reference, "ImagingReport/img-1");
+    assert_eq!(oncology::recist_response(std::slice::from_ref(&baseline)), None);
+
+    let response = oncology::recist_response(&[baseline.clone(), partial.clone()]).unwrap();
+    assert_eq!(response.response, RecistResponse::PartialResponse);
+    assert_eq!((response.baseline.reference.as_str(), response.date), ("ImagingReport/img-1", date("2024-03-10")));
+    assert_eq!(oncology::describe(&response), "PR (-40% from baseline)");
+
+    let stable = assessment("img-3", "2024-05-10", &[20.0, 14.0], false);
+    let response = oncology::recist_response(&[baseline.clone(), partial.clone(), stable]).unwrap();
+    assert_eq!(response.response, RecistResponse::PartialResponse);
+
+    let grown = assessment("img-4", "2024-05-10", &[22.0, 14.0], false);
+    let response = oncology::recist_response(&[baseline.clone(), partial.clone(), grown]).unwrap();
+    assert_eq!(response.response, RecistResponse::ProgressiveDisease);
+
+    let new_lesion = assessment("img-5", "2024-05-10", &[10.0, 5.0], true);
+    let response = oncology::recist_response(&[baseline.clone(), new_lesion]).unwrap();
+    assert_eq!(response.response, RecistResponse::ProgressiveDisease);
+
+    let cleared = assessment("img-6", "2024-05-10", &[0.0, 0.0], false);
+    let response = oncology::recist_response(&[baseline.clone(), partial, cleared]).unwrap();
+    assert_eq!((response.response, response.imaging_report.reference.as_str()), (
+        RecistResponse::CompleteResponse,
+        "ImagingReport/img-6"
+    ));
+
+    let small = assessment("img-7", "2024-03-10", &[28.0, 20.0], false);
+    let response = oncology::recist_response(&[baseline, small]).unwrap();
+    assert_eq!(response.response, RecistResponse::StableDisease);
+}
+
+#[test]
+fn snake_case_input_round_trips() {
+    let baseline: TumorAssessment = serde_json::from_value(json!({
+        "imaging_report": {"reference": "ImagingReport/img-1"}, "date": "2024-01-10", "target_lesions_mm": [30.0, 20.0]
+    }))
+    .unwrap();
+    let current: TumorAssessment = serde_json::from_value(json!({
+        "imaging_report": {"reference": "ImagingReport/img-5"}, "date": "2024-05-10",
+        "target_lesions_mm": [10.0, 5.0], "new_lesions": true
+    }))
+    .unwrap();
+    assert!(current.new_lesions);
+    let json = serde_json::to_value(&current).unwrap();
+    assert_eq!((&json["newLesions"], &json["targetLesionsMm"][0]), (&json!(true), &json!(10.0)));
+    assert_eq!(serde_json::from_value::<TumorAssessment>(json).unwrap(), current);
+
+    let response = oncology::recist_response(&[baseline, current]).unwrap();
+    assert_eq!(response.response, RecistResponse::ProgressiveDisease);
+    let json = serde_json::to_value(&response).unwrap();
+    assert!(json.get("changeFromBaseline").is_some() && json.get("change_from_baseline").is_none());
+
+    let staging: TnmStaging = serde_json::from_value(json!({
+        "prefix": "c", "primary_tumor": "T2", "regional_nodes": "N1", "distant_metastases": "M0"
+    }))
+    .unwrap();
+    assert_eq!(staging, TnmStaging::parse("cT2N1M0").unwrap());
+    assert_eq!(serde_json::to_value(&staging).unwrap()["primaryTumor"], "T2");
+
+    let course: RadiotherapyCourse = serde_json::from_value(json!({
+        "modality": "photon", "body_site": {"system": SNOMED_CT, "code": "34402009"},
+        "total_dose": {"value": 50, "unit": "Gy"}, "fractions": 25, "start_date": "2024-03-01", "end_date": "2024-04-04"
+    }))
+    .unwrap();
+    assert_eq!(course.end_date, Some(date("2024-04-04")));
+    let json = serde_json::to_value(&course).unwrap();
+    assert_eq!(serde_json::from_value::<RadiotherapyCourse>(json).unwrap(), course);
+
+    let regimen: ChemotherapyRegimen =
+        serde_json::from_value(json!({"name": "FOLFOX", "cycle_days": 14, "planned_cycles": 12, "medications": []}))
+            .unwrap();
+    assert_eq!((regimen.cycle_days, regimen.planned_cycles), (14, Some(12)));
+}
@@ -0,0 +1,77 @@
+//! Checks the OpenAPI components document is self-contained.
+
+#![cfg(feature = "utoipa")]
+
+use std::collections::BTreeSet;
+
+use serde_json::{json, Value};
+use utoipa::OpenApi;
+use wellally::openapi::ComponentsDoc;
+
+fn refs(value: &Value, found: &mut BTreeSet<String>) {
+    match value {
+        Value::Object(object) => {
+            if let Some(Value::String(target)) = object.get("$ref") {
+                found.insert(target.clone());
+            }
+            object.values().for_each(|value| refs(value, found));
+        }
+        Value::Array(items) => items.iter().for_each(|value| refs(value, found)),
+        _ => {}
+    }
+}
+
+#[test]
+fn every_ref_resolves() {
+    let doc = serde_json::to_value(ComponentsDoc::openapi()).unwrap();
+    let schemas = doc["components"]["schemas"].as_object().unwrap();
+    let mut found = BTreeSet::new();
+    refs(&doc, &mut found);
+    assert!(!found.is_empty());
+
+    let dangling: Vec<_> = found
+        .iter()
+        .filter(|target| {
+            target.strip_prefix("#/components/schemas/").is_none_or(|name| !schemas.contains_key(name))
+        })
+        .collect();
+    assert!(dangling.is_empty(), "unresolved references: {:?}", dangling);
+}
+
+#[test]
+fn resources_are_components() {
+    let doc = ComponentsDoc::openapi();
+    let schemas = &doc.components.unwrap().schemas;
+    for name in ["Person", "LabReport", "ImagingReport", "EchoReport", "DexaReport", "RelatedPerson", "Extension"] {
+        assert!(schemas.contains_key(name), "missing {}", name);
+    }
+}
+
+#[test]
+fn lab_values_are_one_of_the_value_shapes() {
+    let doc = serde_json::to_value(ComponentsDoc::openapi()).unwrap();
+    let schemas = &doc["components"]["schemas"];
+    let shapes = schemas["LabValue"]["oneOf"].as_array().unwrap();
+    assert_eq!(shapes.len(), 5);
+    assert_eq!(shapes[0]["$ref"], "#/components/schemas/Quantity");
+    assert_eq!(shapes[1]["required"], json!(["numerator", "denominator"]));
+    assert_eq!(shapes[2]["properties"]["comparator"]["$ref"], "#/components/schemas/Comparator");
+    assert_eq!(shapes[4]["type"], "string");
+}
+
+#[test]
+fn enums_list_their_values() {
+    let doc = serde_json::to_value(ComponentsDoc::openapi()).unwrap();
+    let schemas = &doc["components"]["schemas"];
+    assert_eq!(schemas["Gender"]["enum"], json!(["male", "female", "other", "unknown"]));
+    let interpretations = schemas["Interpretation"]["enum"].as_array().unwrap();
+    assert!(["N", "H", "LL", "POS"].iter().all(|code| interpretations.contains(&json!(code))));
+}
+
+#[test]
+fn family_members_link_person_records() {
+    let doc = serde_json::to_value(ComponentsDoc::openapi()).unwrap();
+    let member = &doc["components"]["schemas"]["FamilyMember"];
+    assert_eq!(member["properties"]["personId"]["type"], json!(["string", "null"]));
+    assert!(doc["components"]["schemas"]["ConditionOnset"].is_object());
+}
@@ -0,0 +1,81 @@
+//! Checks anatomic pathology reports.
+
+use serde_json::json;
+use wellally::catalog::{concept, SNOMED_CT};
+use wellally::oncology::{CancerCondition, TnmStaging};
+use wellally::pathology::{PathologyReport, PathologySpecimen, ICD_O_3};
+use wellally::{Condition, LabValue, Resource, Validate};
+
+mod common;
+
+const EXAMPLE: &str = include_str!("../../../infrastructure/schemas/pathology/examples/pathology.colectomy.json");
+
+#[test]
+fn published_example_round_trips() {
+    let (report, written) = common::round_trip::<PathologyReport>(EXAMPLE);
+    assert_eq!(report.specimens.len(), 2);
+    assert!(report.specimen("A").unwrap().microscopic_findings.is_some());
+    assert_eq!(written["specimens"][1]["type"]["code"], "119378008");
+}
+
+#[test]
+fn reads_synoptic_elements_and_staging() {
+    let report = PathologyReport::from_json(EXAMPLE).unwrap();
+    match report.synoptic_value("21899-0") {
+        Some(LabValue::Quantity(nodes)) => assert_eq!(nodes.value, 2.0),
+        other => panic!("unexpected {:?}", other),
+    }
+    assert!(matches!(report.synoptic_value("44667-4"), Some(LabValue::String(_))));
+    assert_eq!(report.synoptic_value("00000-0"), None);
+    assert_eq!(report.tnm_staging(), TnmStaging::parse("pT3N1bM0"));
+    assert!(report.tnm_staging().unwrap().is_pathologic());
+    assert_eq!(report.histology().unwrap().coding[0].code, "8140/3");
+    assert!(report.is_malignant());
+
+    let mut benign = PathologyReport::new("path-2", "p-1", "2024-10-04".parse().unwrap(), vec![]);
+    benign.diagnoses.push(concept(ICD_O_3, "8211/0", "Tubular adenoma, NOS"));
+    assert!(!benign.is_malignant());
+}
+
+#[test]
+fn carries_findings_over_to_the_cancer_condition() {
+    let report = PathologyReport::from_json(EXAMPLE).unwrap();
+    let sigmoid = concept("http://hl7.org/fhir/sid/icd-10-cm", "C18.7", "Malignant neoplasm of sigmoid colon");
+    let mut cancer = CancerCondition::new(Condition::new("cond-1", "p-1", sigmoid));
+    cancer.staging = TnmStaging::parse("cT3 N1 M0");
+    report.apply_to(&mut cancer);
+    assert_eq!(cancer.histology.as_ref().unwrap().coding[0].code, "8140/3");
+    assert_eq!(cancer.staging.as_ref().unwrap().to_string(), "pT3N1bM0");
+    assert_eq!(cancer.stage_group.as_deref(), Some("IIIB"));
+
+    let specimens = vec![PathologySpecimen::new("A")];
+    let silent = PathologyReport::new("path-3", "p-1", "2024-10-04".parse().unwrap(), specimens);
+    silent.apply_to(&mut cancer);
+    assert_eq!(cancer.staging.as_ref().unwrap().to_string(), "pT3N1bM0");
+}
+
+#[test]
+fn validation_reports_inconsistent_reports() {
+    let report: PathologyReport = serde_json::from_value(json!({
+        "id": "path-1", "patientId": "p-1", "issuedAt": "2024-10-04",
+        "specimens": [{"id": "A"}, {"id": "A", "type": {"system": SNOMED_CT, "code": ""}}],
+        "synoptic": [
+            {
+                "code": {"system": "http://loinc.org", "code": "33728-7"},
+                "value": {"value": 4.5, "unit": "cm"}, "specimen": "C"
+            },
+            {"code": {"system": "http://loinc.org", "code": "44667-4"}, "value": " "}
+        ],
+        "tnm": "stage III"
+    }))
+    .unwrap();
+    let paths: Vec<_> = report.validate().into_iter().map(|issue| issue.path).collect();
+    assert_eq!(
+        paths,
+        ["specimens[1].id", "specimens[1].type.code", "synoptic[0].specimen", "synoptic[1].value", "tnm"]
+    );
+
+    let empty = PathologyReport::new("path-2", "p-1", "2024-10-04".parse().unwrap(), vec![]);
+    let paths: Vec<_> = empty.validate().into_iter().map(|issue| issue.path).collect();
+    assert_eq!(paths, ["specimens"]);
+}
@@ -0,0 +1,148 @@
+//! Tests for the PCORnet CDM export.
+
+#![cfg(feature = "pcornet")]
+
+use serde_json::{json, Value};
+use wellally::io::{AnyResource, Bundle};
+use wellally::pcornet::{Demographic, LabResultCm, PcornetTable, PcornetTables, Prescribing};
+
+fn resource(json: Value) -> AnyResource {
+    serde_json::from_value(json).unwrap()
+}
+
+fn bundle() -> Bundle {
+    Bundle::new(vec![
+        resource(json!({
+            "id": "p-1", "resourceType": "Person", "birthDate": "1975", "gender": "male", "language": ["en", "es"],
+            "name": [{"family": "Doe", "given": ["John"]}]
+        })),
+        AnyResource::from_typed_json_value("LabReport", &json!({
+            "id": "lab-1", "patientId": "p-1", "issuedAt": "2024-05-20T16:05:00+08:00",
+            "specimen": {"type": {"system": "http://terminology.hl7.org/CodeSystem/v2-0487", "code": "SER"},
+                         "collectedAt": "2024-05-20T07:30:00+08:00"},
+            "results": [
+                {
+                    "code": {"coding": [{"system": "http://loinc.org", "code": "2345-7", "display": "Glucose"}]},
+                    "value": {"value": 7.9, "unit": "mmol/L"}, "interpretation": "H",
+                    "referenceRange": [{
+                        "low": {"value": 3.9, "unit": "mmol/L"}, "high": {"value": 6.1, "unit": "mmol/L"}
+                    }]
+                },
+                {
+                    "code": {"coding": [{"system": "http://loinc.org", "code": "1988-5", "display": "CRP"}]},
+                    "value": {"comparator": "<", "value": {"value": 5, "unit": "mg/L"}},
+                    "referenceRange": [{"high": {"value": 10, "unit": "mg/L"}}]
+                },
+                {
+                    "code": {"coding": [{"system": "http://loinc.org", "code": "5196-1", "display": "HBsAg"}]},
+                    "value": {
+                        "coding": [{"system": "http://snomed.info/sct", "code": "260385009", "display": "Negative"}]
+                    },
+                    "interpretation": "NEG"
+                }
+            ]
+        }))
+        .unwrap(),
+        resource(json!({
+            "id": "med-1", "patientId": "p-1",
+            "medication": {
+                "system": "http://www.nlm.nih.gov/research/umls/rxnorm", "code": "860975", "display": "Metformin"
+            },
+            "dosage": {"value": 500, "unit": "mg"}, "route": {"system": "http://snomed.info/sct", "code": "26643006"},
+            "frequency": "BID", "startDate": "2024-01-01", "endDate": "2024-03-31"
+        })),
+    ])
+}
+
+fn csv<T: PcornetTable>(rows: &[T]) -> Vec<Vec<String>> {
+    let mut out = Vec::new();
+    wellally::pcornet::write_csv(rows, &mut out).unwrap();
+    let text = String::from_utf8(out).unwrap();
+    text.lines().map(|line| line.split(',').map(Into::into).collect()).collect()
+}
+
+fn column<'a>(rows: &'a [Vec<String>], row: usize, name: &str) -> &'a str {
+    let i = rows[0].iter().position(|header| header == name).unwrap_or_else(|| panic!("no column {}", name));
+    &rows[row][i]
+}
+
+#[test]
+fn demographic_rows() {
+    let tables = PcornetTables::from_bundles(&[bundle()]);
+    let rows = csv(&tables.demographic);
+    assert_eq!(rows[0], Demographic::COLUMNS);
+    assert_eq!(column(&rows, 1, "PATID"), "p-1");
+    assert_eq!(column(&rows, 1, "BIRTH_DATE"), "1975-01-01");
+    assert_eq!(column(&rows, 1, "SEX"), "M");
+    assert_eq!(column(&rows, 1, "RACE"), "NI");
+    assert_eq!(column(&rows, 1, "RAW_PAT_PREF_LANGUAGE_SPOKEN"), "en;es");
+}
+
+#[test]
+fn lab_result_rows() {
+    let tables = PcornetTables::from_bundles(&[bundle()]);
+    let rows = csv(&tables.lab_result_cm);
+    assert_eq!(rows[0], LabResultCm::COLUMNS);
+    assert_eq!(rows.len(), 4);
+
+    assert_eq!(column(&rows, 1, "LAB_RESULT_CM_ID"), "lab-1-1");
+    assert_eq!(column(&rows, 1, "LAB_LOINC"), "2345-7");
+    assert_eq!(column(&rows, 1, "SPECIMEN_SOURCE"), "SERUM");
+    assert_eq!((column(&rows, 1, "SPECIMEN_DATE"), column(&rows, 1, "SPECIMEN_TIME")), ("2024-05-20", "07:30"));
+    assert_eq!((column(&rows, 1, "RESULT_DATE"), column(&rows, 1, "RESULT_TIME")), ("2024-05-20", "16:05"));
+    assert_eq!((column(&rows, 1, "RESULT_NUM"), column(&rows, 1, "RESULT_MODIFIER")), ("7.9", "EQ"));
+    assert_eq!((column(&rows, 1, "NORM_MODIFIER_LOW"), column(&rows, 1, "NORM_MODIFIER_HIGH")), ("EQ", "EQ"));
+    assert_eq!(column(&rows, 1, "ABN_IND"), "AH");
+
+    assert_eq!((column(&rows, 2, "RESULT_MODIFIER"), column(&rows, 2, "RAW_RESULT")), ("LT", "<5"));
+    assert_eq!((column(&rows, 2, "NORM_RANGE_LOW"), column(&rows, 2, "NORM_MODIFIER_LOW")), ("", "NO"));
+    assert_eq!((column(&rows, 2, "NORM_RANGE_HIGH"), column(&rows, 2, "NORM_MODIFIER_HIGH")), ("10.0", "LE"));
+
+    assert_eq!((column(&rows, 3, "RESULT_QUAL"), column(&rows, 3, "RESULT_MODIFIER")), ("NEGATIVE", "TX"));
+    assert_eq!((column(&rows, 3, "RESULT_SNOMED"), column(&rows, 3, "RESULT_NUM")), ("260385009", ""));
+}
+
+#[test]
+fn prescribing_rows() {
+    let tables = PcornetTables::from_bundles(&[bundle()]);
+    let rows = csv(&tables.prescribing);
+    assert_eq!(rows[0], Prescribing::COLUMNS);
+    assert_eq!(column(&rows, 1, "RX_START_DATE"), "2024-01-01");
+    assert_eq!(column(&rows, 1, "RX_END_DATE"), "2024-03-31");
+    assert_eq!(column(&rows, 1, "RX_DAYS_SUPPLY"), "91");
+    assert_eq!((column(&rows, 1, "RX_FREQUENCY"), column(&rows, 1, "RX_PRN_FLAG")), ("02", "N"));
+    assert_eq!((column(&rows, 1, "RXNORM_CUI"), column(&rows, 1, "RX_ROUTE")), ("860975", "26643006"));
+    assert_eq!(column(&rows, 1, "RAW_RX_MED_NAME"), "Metformin");
+}
+
+#[test]
+fn columns_match_the_rows() {
+    let tables = PcornetTables::from_bundles(&[bundle()]);
+    let keys = |row: Value| row.as_object().unwrap().keys().cloned().collect::<Vec<_>>();
+    let sorted = |columns: &[&str]| {
+        let mut columns: Vec<_> = columns.iter().map(|column| column.to_string()).collect();
+        columns.sort();
+        columns
+    };
+    let mut demographic = keys(serde_json::to_value(&tables.demographic[0]).unwrap());
+    let mut lab = keys(serde_json::to_value(&tables.lab_result_cm[0]).unwrap());
+    let mut prescribing = keys(serde_json::to_value(&tables.prescribing[0]).unwrap());
+    demographic.sort();
+    lab.sort();
+    prescribing.sort();
+    assert_eq!(demographic, sorted(Demographic::COLUMNS));
+    assert_eq!(lab, sorted(LabResultCm::COLUMNS));
+    assert_eq!(prescribing, sorted(Prescribing::COLUMNS));
+}
+
+#[test]
+fn writes_every_table_with_headers() {
+    let dir = std::env::temp_dir().join(format!("wellally-pcornet-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    PcornetTables::default().write_dir(&dir).unwrap();
+    for table in [Demographic::TABLE, LabResultCm::TABLE, Prescribing::TABLE] {
+        let text = std::fs::read_to_string(dir.join(format!("{}.csv", table))).unwrap();
+        assert_eq!(text.lines().count(), 1, "{}", table);
+    }
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,99 @@
+//! Tests for the PostgreSQL mappings. The database tests are ignored by
+//! default; run them with `DATABASE_URL` set and `cargo test --features
+//! postgres -- --ignored`. They work inside a transaction that is rolled back.
+
+#![cfg(feature = "postgres")]
+
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use sqlx::postgres::{PgArgumentBuffer, PgPool, Postgres};
+use sqlx::{Encode, Type, TypeInfo};
+use wellally::postgres::{self, ResourceRow, MIGRATION};
+use wellally::{LabReport, Task};
+
+fn report(id: &str, patient_id: &str) -> LabReport {
+    serde_json::from_value(json!({
+        "id": id,
+        "patientId": patient_id,
+        "issuedAt": "2024-11-02T09:30:00Z",
+        "results": [{"code": {"coding": [{"system": "http://loinc.org", "code": "2345-7"}]},
+                     "value": {"value": 5.4, "unit": "mmol/L"}}]
+    }))
+    .unwrap()
+}
+
+#[test]
+fn resources_are_jsonb() {
+    assert_eq!(<LabReport as Type<Postgres>>::type_info().name(), "JSONB");
+    assert!(<Task as Type<Postgres>>::compatible(&<LabReport as Type<Postgres>>::type_info()));
+}
+
+#[test]
+fn jsonb_encoding_round_trips() {
+    let original = report("lab-1", "p-1");
+    let mut buf = PgArgumentBuffer::default();
+    assert!(!original.encode_by_ref(&mut buf).unwrap().is_null());
+
+    // Binary JSONB: a version byte, then the JSON text
+    assert_eq!(buf[0], 1);
+    let decoded: LabReport = serde_json::from_slice(&buf[1..]).unwrap();
+    assert_eq!(decoded, original);
+    assert_eq!(serde_json::from_slice::<serde_json::Value>(&buf[1..]).unwrap()["patientId"], "p-1");
+}
+
+async fn pool() -> Option<PgPool> {
+    let url = std::env::var("DATABASE_URL").ok()?;
+    let pool = PgPool::connect(&url).await.unwrap();
+    sqlx::raw_sql(MIGRATION).execute(&pool).await.unwrap();
+    Some(pool)
+}
+
+#[tokio::test(flavor = "current_thread")]
+#[ignore = "needs DATABASE_URL"]
+async fn saves_and_loads_resources() {
+    let Some(pool) = pool().await else { return };
+    let mut tx = pool.begin().await.unwrap();
+
+    let original = report("pg-test-lab-1", "pg-test-p-1");
+    let first = postgres::save(&mut *tx, &original).await.unwrap();
+    assert_eq!(postgres::load::<LabReport, _>(&mut *tx, "pg-test-lab-1").await.unwrap(), Some(original.clone()));
+    assert_eq!(postgres::load::<LabReport, _>(&mut *tx, "pg-test-missing").await.unwrap(), None);
+
+    let second = postgres::save(&mut *tx, &original).await.unwrap();
+    assert!(second > first);
+    postgres::save(&mut *tx, &report("pg-test-lab-2", "pg-test-p-2")).await.unwrap();
+    let loaded: Vec<LabReport> = postgres::load_by_patient(&mut *tx, "pg-test-p-1").await.unwrap();
+    assert_eq!(loaded, [original]);
+
+    tx.rollback().await.unwrap();
+}
+
+#[tokio::test(flavor = "current_thread")]
+#[ignore = "needs DATABASE_URL"]
+async fn reads_rows_updated_since() {
+    let Some(pool) = pool().await else { return };
+    let mut tx = pool.begin().await.unwrap();
+
+    let before: DateTime<Utc> = sqlx::query_scalar("SELECT clock_timestamp()").fetch_one(&mut *tx).await.unwrap();
+    let first = postgres::save(&mut *tx, &report("pg-test-lab-1", "pg-test-p-1")).await.unwrap();
+    postgres::save(&mut *tx, &report("pg-test-lab-2", "pg-test-p-1")).await.unwrap();
+
+    let rows: Vec<ResourceRow<LabReport>> = postgres::updated_since(&mut *tx, before, 10).await.unwrap();
+    let ids: Vec<_> = rows.iter().map(|row| row.id.as_str()).collect();
+    assert_eq!(ids, ["pg-test-lab-1", "pg-test-lab-2"]);
+    assert_eq!(rows[0].resource_type, "LabReport");
+    assert_eq!(rows[0].patient_id, "pg-test-p-1");
+    assert_eq!(rows[0].updated_at, first);
+    assert_eq!(rows[0].resource, report("pg-test-lab-1", "pg-test-p-1"));
+
+    let rest: Vec<ResourceRow<LabReport>> = postgres::updated_since(&mut *tx, first, 10).await.unwrap();
+    assert_eq!(rest.len(), 1);
+    let bodies: Vec<LabReport> = sqlx::query_as("SELECT body FROM resources WHERE id = $1")
+        .bind("pg-test-lab-2")
+        .fetch_all(&mut *tx)
+        .await
+        .unwrap();
+    assert_eq!(bodies, [rest[0].resource.clone()]);
+
+    tx.rollback().await.unwrap();
+}
@@ -0,0 +1,80 @@
+//! Checks problem list reconciliation and the active problem snapshot.
+
+use wellally::catalog::{concept, SNOMED_CT};
+use wellally::problem_list::{active_problems, update};
+use wellally::{Annotation, ClinicalStatus, ClinicalSummary, Condition, VerificationStatus};
+
+fn condition(id: &str, code: &str, display: &str) -> Condition {
+    Condition::new(id, "p-1", concept(SNOMED_CT, code, display))
+}
+
+fn hypertension(id: &str) -> Condition {
+    condition(id, "38341003", "Hypertensive disorder")
+}
+
+#[test]
+fn duplicates_merge_into_the_existing_entry() {
+    let mut existing = hypertension("c-1");
+    existing.onset = Some("2020-05".parse().unwrap());
+    existing.recorded_date = Some("2020-06-01".parse().unwrap());
+    let mut incoming = hypertension("c-7");
+    incoming.onset = Some("2019".parse().unwrap());
+    incoming.recorded_date = Some("2024-01-10".parse().unwrap());
+    incoming.verification_status = Some(VerificationStatus::Confirmed);
+    incoming.code.coding.push(concept("http://hl7.org/fhir/sid/icd-10", "I10", "Essential hypertension").coding.remove(0));
+    incoming.notes = Some(vec![Annotation { author_reference: None, author_string: None, time: None, text: "On amlodipine".into() }]);
+
+    let list = update(vec![existing], vec![incoming.clone(), incoming]);
+    assert_eq!(list.len(), 1);
+    let merged = &list[0];
+    assert_eq!(merged.id, "c-1");
+    assert_eq!(merged.onset.unwrap().to_string(), "2019");
+    assert_eq!(merged.recorded_date.unwrap().to_string(), "2024-01-10");
+    assert_eq!(merged.verification_status, Some(VerificationStatus::Confirmed));
+    assert_eq!(merged.code.coding.len(), 2);
+    assert_eq!(merged.notes.as_ref().map(Vec::len), Some(1));
+}
+
+#[test]
+fn resolved_reports_close_and_new_reports_reopen() {
+    let mut resolved = hypertension("c-2");
+    resolved.clinical_status = ClinicalStatus::Resolved;
+    resolved.abatement = Some("2023-08".parse().unwrap());
+
+    let list = update(vec![hypertension("c-1")], vec![resolved]);
+    assert_eq!(list[0].clinical_status, ClinicalStatus::Resolved);
+    assert_eq!(list[0].abatement.unwrap().to_string(), "2023-08");
+    assert!(active_problems(&list).is_empty());
+
+    let list = update(list, vec![hypertension("c-3")]);
+    assert_eq!(list[0].id, "c-1");
+    assert_eq!(list[0].clinical_status, ClinicalStatus::Recurrence);
+    assert_eq!(list[0].abatement, None);
+}
+
+#[test]
+fn entered_in_error_removes_the_entry() {
+    let mut mistake = hypertension("c-9");
+    mistake.verification_status = Some(VerificationStatus::EnteredInError);
+    let list = update(vec![hypertension("c-1"), condition("c-2", "44054006", "Diabetes mellitus type 2")], vec![mistake.clone()]);
+    assert_eq!(list.iter().map(|c| c.id.as_str()).collect::<Vec<_>>(), ["c-2"]);
+    assert_eq!(update(list, vec![mistake]).len(), 1);
+}
+
+#[test]
+fn other_patients_and_codes_are_appended_in_order() {
+    let mut someone_else = hypertension("c-5");
+    someone_else.patient_id = "p-2".into();
+    let list = update(vec![hypertension("c-1")], vec![condition("c-4", "195967001", "Asthma"), someone_else]);
+    assert_eq!(list.iter().map(|c| c.id.as_str()).collect::<Vec<_>>(), ["c-1", "c-4", "c-5"]);
+}
+
+#[test]
+fn active_problems_fill_the_clinical_summary() {
+    let mut refuted = condition("c-3", "195967001", "Asthma");
+    refuted.verification_status = Some(VerificationStatus::Refuted);
+    let list = vec![hypertension("c-1"), refuted, condition("c-2", "44054006", "Diabetes mellitus type 2")];
+    let summary = ClinicalSummary { conditions: Some(active_problems(&list)), ..Default::default() };
+    let codes: Vec<_> = summary.conditions.unwrap().iter().map(|c| c.coding[0].code.clone()).collect();
+    assert_eq!(codes, ["38341003", "44054006"]);
+}
@@ -0,0 +1,158 @@
+//! Checks data-quality profiles read from JSON and TOML.
+
+#![cfg(feature = "profiles")]
+
+use serde_json::json;
+use wellally::io::AnyResource;
+use wellally::profiles::{Pattern, Profile, Rule};
+use wellally::{LabReport, WellAllyError};
+
+fn report() -> LabReport {
+    serde_json::from_value(json!({
+        "id": "lab-1",
+        "patientId": "p-1",
+        "issuedAt": "2024-11-02T09:30:00Z",
+        "facility": {"name": "Clinic A"},
+        "results": [
+            {"code": {"coding": [{"system": "http://loinc.org", "code": "2345-7"}]}, "value": "normal"},
+            {"code": {"coding": [{"system": "urn:local", "code": "HBA1C"}]}, "value": "high"}
+        ]
+    }))
+    .unwrap()
+}
+
+fn rule(path: &str) -> Rule {
+    Rule { resource_type: "LabReport".to_string(), path: path.to_string(), ..Rule::default() }
+}
+
+fn profile(rules: Vec<Rule>) -> Profile {
+    Profile { name: "test".to_string(), description: None, rules }
+}
+
+#[test]
+fn reads_the_same_profile_from_json_and_toml() {
+    let from_json = Profile::from_json(
+        r#"{"name": "clinic-a", "rules": [
+            {"resourceType": "LabReport", "path": "results.code", "systems": ["http://loinc.org"]},
+            {"resourceType": "LabReport", "path": "id", "pattern": "^lab-[0-9]+$"}
+        ]}"#,
+    )
+    .unwrap();
+    let from_toml = Profile::from_toml(
+        r#"
+        name = "clinic-a"
+
+        [[rules]]
+        resourceType = "LabReport"
+        path = "results.code"
+        systems = ["http://loinc.org"]
+
+        [[rules]]
+        resourceType = "LabReport"
+        path = "id"
+        pattern = "^lab-[0-9]+$"
+        "#,
+    )
+    .unwrap();
+    assert_eq!(from_json, from_toml);
+    assert_eq!(from_json.rules[1].pattern.as_ref().unwrap().as_str(), "^lab-[0-9]+$");
+}
+
+#[test]
+fn rejects_unknown_fields_and_invalid_patterns() {
+    let unknown =
+        Profile::from_json(r#"{"name": "a", "rules": [{"resourceType": "LabReport", "path": "id", "requird": true}]}"#);
+    assert!(matches!(unknown, Err(WellAllyError::Parse { .. })));
+
+    let pattern = Profile::from_toml(
+        r#"
+        name = "a"
+        [[rules]]
+        resourceType = "LabReport"
+        path = "id"
+        pattern = "("
+        "#,
+    );
+    assert!(matches!(pattern, Err(WellAllyError::Parse { .. })));
+    assert!(Pattern::new("(").is_err());
+}
+
+#[test]
+fn checks_required_fields_in_every_parent() {
+    let required = Rule { required: true, ..rule("results.interpretation") };
+    let issues = profile(vec![required, Rule { required: true, ..rule("performer") }]).validate(&report());
+    let paths: Vec<_> = issues.iter().map(|issue| issue.path.as_str()).collect();
+    assert_eq!(paths, ["results[0].interpretation", "results[1].interpretation", "performer"]);
+    assert!(issues.iter().all(|issue| issue.message == "is required"));
+}
+
+#[test]
+fn checks_cardinality() {
+    let issues = profile(vec![Rule { min: Some(3), ..rule("results") }]).validate(&report());
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues[0].message, "must have at least 3 items");
+
+    let issues = profile(vec![Rule { max: Some(1), ..rule("results") }]).validate(&report());
+    assert_eq!(issues[0].path, "results");
+    assert_eq!(issues[0].message, "must have at most 1 items");
+
+    assert!(profile(vec![Rule { min: Some(1), max: Some(2), ..rule("results") }]).validate(&report()).is_empty());
+}
+
+#[test]
+fn checks_code_systems_of_codings_and_concepts() {
+    let systems = Some(vec!["http://loinc.org".to_string()]);
+    let concept = profile(vec![Rule { systems: systems.clone(), ..rule("results.code") }]).validate(&report());
+    let coding = profile(vec![Rule { systems, ..rule("results.code.coding") }]).validate(&report());
+    assert_eq!(concept, coding);
+    assert_eq!(concept.len(), 1);
+    assert_eq!(concept[0].path, "results[1].code.coding[0].system");
+    assert_eq!(concept[0].message, "code system 'urn:local' is not allowed");
+}
+
+#[test]
+fn checks_values_and_patterns() {
+    let values = Rule { values: Some(vec![json!("normal"), json!("low")]), ..rule("results.value") };
+    let issues = profile(vec![values]).validate(&report());
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues[0].path, "results[1].value");
+
+    let pattern = Rule {
+        pattern: Some(Pattern::new("^Clinic [A-Z]$").unwrap()),
+        message: Some("facility must be a clinic".to_string()),
+        ..rule("facility.name")
+    };
+    let mut other = report();
+    assert!(profile(vec![pattern.clone()]).validate(&other).is_empty());
+    other.facility.as_mut().unwrap().name = Some("St. Mary".to_string());
+    assert_eq!(profile(vec![pattern]).validate(&other)[0].message, "facility must be a clinic");
+}
+
+#[test]
+fn applies_only_the_rules_of_the_resource_type() {
+    let task: AnyResource =
+        serde_json::from_value(json!({"id": "task-1", "patientId": "p-1", "status": "requested", "description": "x"}))
+            .unwrap();
+    let rules = vec![Rule { required: true, ..rule("performer") }];
+    assert!(profile(rules.clone()).validate_any(&task).is_empty());
+    assert_eq!(profile(rules).validate_any(&AnyResource::from(report())).len(), 1);
+}
+
+#[test]
+fn checks_expressions_on_each_value() {
+    let profile = Profile::from_toml(
+        r#"
+        name = "clinic-a"
+
+        [[rules]]
+        resourceType = "LabReport"
+        path = "results"
+        expression = "code.coding.exists(system = 'http://loinc.org') and value = 'normal'"
+        "#,
+    )
+    .unwrap();
+    let issues = profile.validate(&report());
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues[0].path, "results[1]");
+    assert!(issues[0].message.starts_with("does not satisfy code.coding.exists"));
+}
@@ -0,0 +1,73 @@
+//! Checks the FHIR-aligned quantity fields: comparator, coded unit and system.
+
+use serde_json::json;
+use wellally::borrowed::QuantityRef;
+use wellally::catalog::UCUM;
+use wellally::{Comparator, LabReport, LabValue, Quantity, Resource, Validate};
+
+#[test]
+fn plain_quantities_keep_their_shape() {
+    let quantity: Quantity = serde_json::from_value(json!({"value": 5.4, "unit": "mmol/L"})).unwrap();
+    assert_eq!(quantity, Quantity::new(5.4, "mmol/L"));
+    assert_eq!(serde_json::to_value(&quantity).unwrap(), json!({"value": 5.4, "unit": "mmol/L"}));
+    assert_eq!(quantity.ucum_code(), "mmol/L");
+}
+
+#[test]
+fn fhir_quantities_round_trip() {
+    let json = json!({"value": 70.0, "unit": "in", "code": "[in_i]", "system": "http://unitsofmeasure.org"});
+    let height: Quantity = serde_json::from_value(json.clone()).unwrap();
+    assert_eq!(height.ucum_code(), "[in_i]");
+    assert_eq!(height.system.as_deref(), Some(UCUM));
+    assert_eq!(serde_json::to_value(&height).unwrap(), json);
+
+    let other = Quantity { code: Some("258672001".into()), system: Some("http://snomed.info/sct".into()), ..Quantity::new(2.0, "cm") };
+    assert_eq!(other.ucum_code(), "cm");
+}
+
+#[test]
+fn interval_values_carry_their_comparator() {
+    let report = LabReport::from_json_value(&json!({
+        "id": "lab-1", "patientId": "p-1", "issuedAt": "2024-10-01",
+        "results": [{"code": {"coding": [{"system": "http://loinc.org", "code": "2986-8"}]},
+                     "value": {"value": 0.5, "unit": "ng/mL", "comparator": "<"}}]
+    }))
+    .unwrap();
+    let LabValue::Quantity(value) = &report.results[0].value else { panic!("{:?}", report.results[0].value) };
+    assert_eq!(value.comparator, Some(Comparator::LessThan));
+    assert_eq!(report.results[0].value.numeric_value(), Some(0.5));
+    assert!(report.is_valid(), "{:?}", report.validate());
+    let json = serde_json::to_value(&report).unwrap();
+    assert_eq!(json["results"][0]["value"]["comparator"], "<");
+    assert_eq!(LabReport::from_json_value(&json).unwrap(), report);
+}
+
+#[test]
+fn validates_the_coded_unit() {
+    let quantity = |code: Option<&str>, system: Option<&str>| {
+        let value = Quantity { code: code.map(Into::into), system: system.map(Into::into), ..Quantity::new(1.0, "in") };
+        LabReport::from_json_value(&json!({
+            "id": "lab-1", "patientId": "p-1", "issuedAt": "2024-10-01",
+            "results": [{"code": {"coding": [{"system": "http://loinc.org", "code": "8302-2"}]}, "value": value}]
+        }))
+        .unwrap()
+        .validate()
+        .into_iter()
+        .map(|issue| issue.path)
+        .collect::<Vec<_>>()
+    };
+    assert_eq!(quantity(Some("[in_i]"), Some(UCUM)), Vec::<String>::new());
+    assert_eq!(quantity(Some("inch es"), None), ["results[0].value.code"]);
+    assert_eq!(quantity(None, Some(UCUM)), ["results[0].value.system"]);
+    assert!(quantity(Some("inch es"), Some("urn:local:units")).is_empty());
+}
+
+#[test]
+fn borrowed_and_interned_forms_keep_the_new_fields() {
+    let text = r#"{"value":0.5,"unit":"ng/mL","comparator":"<=","code":"ng/mL","system":"http://unitsofmeasure.org"}"#;
+    let owned: Quantity = serde_json::from_str(text).unwrap();
+    let borrowed: QuantityRef<'_> = serde_json::from_str(text).unwrap();
+    assert_eq!(borrowed.to_owned(), owned);
+    #[cfg(feature = "intern")]
+    assert_eq!(wellally::intern::InternedQuantity::from(&owned).to_owned(), owned);
+}
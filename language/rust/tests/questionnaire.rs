@@ -0,0 +1,106 @@
+//! Checks questionnaire enable-when logic, response validation and scoring.
+
+use serde_json::json;
+use wellally::{
+    AnswerValue, Questionnaire, QuestionnaireResponse, Resource, ScoringRule, SumOfOptionScores, Validate,
+};
+
+/// First two PHQ-9 items plus a follow-up enabled by a positive screen.
+fn phq() -> Questionnaire {
+    let options = json!([
+        {"value": {"valueCoding": {"system": "http://loinc.org", "code": "LA6568-5", "display": "Not at all"}}, "score": 0},
+        {"value": {"valueCoding": {"system": "http://loinc.org", "code": "LA6569-3", "display": "Several days"}}, "score": 1},
+        {"value": {"valueCoding": {"system": "http://loinc.org", "code": "LA6570-1", "display": "More than half the days"}}, "score": 2},
+        {"value": {"valueCoding": {"system": "http://loinc.org", "code": "LA6571-9", "display": "Nearly every day"}}, "score": 3}
+    ]);
+    Questionnaire::from_json_value(&json!({
+        "id": "phq-2",
+        "url": "https://www.wellally.tech/Questionnaire/phq-2",
+        "status": "active",
+        "item": [
+            {"linkId": "1", "text": "Little interest or pleasure in doing things", "type": "choice", "required": true, "answerOption": options},
+            {"linkId": "2", "text": "Feeling down, depressed, or hopeless", "type": "choice", "required": true, "answerOption": options},
+            {
+                "linkId": "3", "text": "Since when?", "type": "date", "required": true,
+                "enableWhen": [{"question": "1", "operator": "!=", "answer": {"valueCoding": {"system": "http://loinc.org", "code": "LA6568-5"}}}]
+            }
+        ]
+    }))
+    .unwrap()
+}
+
+fn response(first: &str, second: &str) -> QuestionnaireResponse {
+    let answer = |code: &str| json!([{"valueCoding": {"system": "http://loinc.org", "code": code}}]);
+    QuestionnaireResponse::from_json_value(&json!({
+        "id": "qr-1",
+        "patientId": "patient-1",
+        "questionnaire": "https://www.wellally.tech/Questionnaire/phq-2",
+        "status": "completed",
+        "authored": "2024-11-02T09:30:00Z",
+        "item": [
+            {"linkId": "1", "answer": answer(first)},
+            {"linkId": "2", "answer": answer(second)}
+        ]
+    }))
+    .unwrap()
+}
+
+#[test]
+fn round_trips_through_json() {
+    let questionnaire = phq();
+    assert!(questionnaire.is_valid(), "{:?}", questionnaire.validate());
+    let json = serde_json::to_value(&questionnaire).unwrap();
+    assert_eq!(json["item"][2]["enableWhen"][0]["operator"], "!=");
+    assert_eq!(Questionnaire::from_json_value(&json).unwrap(), questionnaire);
+
+    let response = response("LA6568-5", "LA6569-3");
+    assert!(response.is_valid());
+    assert_eq!(QuestionnaireResponse::from_json(&serde_json::to_string(&response).unwrap()).unwrap(), response);
+}
+
+#[test]
+fn enable_when_controls_required_questions() {
+    let questionnaire = phq();
+    let follow_up = questionnaire.find("3").unwrap();
+
+    let negative = response("LA6568-5", "LA6568-5");
+    assert!(!follow_up.is_enabled(&negative));
+    assert!(negative.validate_against(&questionnaire).is_empty());
+
+    let positive = response("LA6571-9", "LA6568-5");
+    assert!(follow_up.is_enabled(&positive));
+    let issues = positive.validate_against(&questionnaire);
+    assert_eq!(issues.len(), 1);
+    assert!(issues[0].message.contains("'3'"));
+}
+
+#[test]
+fn answers_are_checked_against_the_definition() {
+    let questionnaire = phq();
+    let mut response = response("LA6568-5", "not-an-option");
+    response.item[0].answer.as_mut().unwrap().push(AnswerValue::Integer(1));
+    response.item.push(serde_json::from_value(json!({"linkId": "99", "answer": [{"valueBoolean": true}]})).unwrap());
+
+    let paths: Vec<_> = response.validate_against(&questionnaire).into_iter().map(|issue| issue.path).collect();
+    assert_eq!(paths, ["item[0].answer", "item[0].answer[1]", "item[1].answer[0]", "item[2].linkId"]);
+}
+
+#[test]
+fn sums_option_scores() {
+    let questionnaire = phq();
+    assert_eq!(SumOfOptionScores.score(&questionnaire, &response("LA6570-1", "LA6571-9")), Some(5.0));
+
+    let mut unanswered = response("LA6570-1", "LA6571-9");
+    unanswered.item.pop();
+    assert_eq!(SumOfOptionScores.score(&questionnaire, &unanswered), None);
+}
+
+#[test]
+fn questionnaire_validation_reports_broken_references() {
+    let mut questionnaire = phq();
+    questionnaire.item[2].enable_when.as_mut().unwrap()[0].question = "7".into();
+    questionnaire.item[1].link_id = "1".into();
+    let issues = questionnaire.validate();
+    let paths: Vec<_> = issues.iter().map(|issue| issue.path.as_str()).collect();
+    assert_eq!(paths, ["item[2].enableWhen[0].question", "item"]);
+}
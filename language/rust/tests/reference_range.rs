@@ -0,0 +1,78 @@
+//! Checks reference range applicability and resolution.
+
+use serde_json::json;
+use wellally::borrowed::LabReportRef;
+use wellally::{Coding, LabReport, RangeContext, ReferenceRangeType, Resource, Validate};
+
+fn hemoglobin(ranges: serde_json::Value) -> LabReport {
+    LabReport::from_json_value(&json!({
+        "id": "lab-1",
+        "patientId": "p-1",
+        "issuedAt": "2024-11-02",
+        "results": [{
+            "code": {"coding": [{"system": "http://loinc.org", "code": "718-7"}]},
+            "value": {"value": 11.8, "unit": "g/dL"},
+            "referenceRange": ranges
+        }]
+    }))
+    .unwrap()
+}
+
+fn ranges() -> serde_json::Value {
+    json!([
+        {"low": {"value": 11.0, "unit": "g/dL"}, "high": {"value": 13.5, "unit": "g/dL"},
+         "age": {"low": {"value": 6, "unit": "mo"}, "high": {"value": 11, "unit": "a"}}},
+        {"low": {"value": 12.0, "unit": "g/dL"}, "high": {"value": 15.5, "unit": "g/dL"}},
+        {"low": {"value": 11.0, "unit": "g/dL"}, "high": {"value": 15.0, "unit": "g/dL"},
+         "appliesTo": [{"coding": [{"system": "http://snomed.info/sct", "code": "77386006"}]}]},
+        {"low": {"value": 7.0, "unit": "g/dL"}, "type": "critical"}
+    ])
+}
+
+fn pregnant() -> Coding {
+    Coding { system: "http://snomed.info/sct".into(), code: "77386006".into(), display: None }
+}
+
+#[test]
+fn most_specific_applicable_range_wins() {
+    let report = hemoglobin(ranges());
+    let result = &report.results[0];
+    let low_bound = |context: &RangeContext| {
+        result.applicable_range(ReferenceRangeType::Normal, context).and_then(|range| range.low.as_ref()).map(|q| q.value)
+    };
+    assert_eq!(low_bound(&RangeContext::default()), Some(12.0));
+    assert_eq!(low_bound(&RangeContext { age_years: Some(4.0), conditions: vec![] }), Some(11.0));
+    assert_eq!(low_bound(&RangeContext { age_years: Some(30.0), conditions: vec![] }), Some(12.0));
+    assert_eq!(low_bound(&RangeContext { age_years: Some(30.0), conditions: vec![pregnant()] }), Some(11.0));
+
+    let critical = result.applicable_range(ReferenceRangeType::Critical, &RangeContext::default()).unwrap();
+    assert_eq!(critical.low.as_ref().unwrap().value, 7.0);
+    assert!(result.applicable_range(ReferenceRangeType::Therapeutic, &RangeContext::default()).is_none());
+    assert!(report.is_valid(), "{:?}", report.validate());
+}
+
+#[test]
+fn single_range_objects_are_still_accepted() {
+    let single = json!({"low": {"value": 12.0, "unit": "g/dL"}, "high": {"value": 15.5, "unit": "g/dL"}});
+    let report = hemoglobin(single.clone());
+    assert_eq!(report.results[0].reference_range.as_ref().unwrap().len(), 1);
+    assert_eq!(serde_json::to_value(&report).unwrap()["results"][0]["referenceRange"], json!([single]));
+
+    let text = serde_json::to_string(&hemoglobin(ranges())).unwrap();
+    let borrowed: LabReportRef = serde_json::from_str(&text).unwrap();
+    assert_eq!(borrowed.to_owned(), hemoglobin(ranges()));
+
+    let err = LabReport::from_json_value(&json!({
+        "id": "lab-1", "patientId": "p-1", "issuedAt": "2024-11-02",
+        "results": [{"code": {"coding": []}, "value": "trace", "referenceRange": [{"low": {"value": "x"}}]}]
+    }))
+    .unwrap_err();
+    assert_eq!(err.path(), "results[0].referenceRange[0].low.value");
+}
+
+#[test]
+fn age_limits_need_time_units() {
+    let report = hemoglobin(json!([{"low": {"value": 12.0, "unit": "g/dL"}, "age": {"low": {"value": 18, "unit": "kg"}}}]));
+    let paths: Vec<_> = report.validate().into_iter().map(|issue| issue.path).collect();
+    assert_eq!(paths, ["results[0].referenceRange[0].age.low.unit"]);
+}
@@ -0,0 +1,65 @@
+//! Checks the referral model and its status lifecycle.
+
+use serde_json::json;
+use wellally::{LabReport, Reference, Referral, ReferralStatus, Resource, Validate, WellAllyError};
+
+fn referral() -> Referral {
+    Referral::from_json_value(&json!({
+        "id": "ref-1",
+        "patientId": "patient-1",
+        "status": "draft",
+        "priority": "urgent",
+        "authoredOn": "2024-11-02T09:30:00Z",
+        "requester": {"practitioner": {"id": "dr-li", "name": "Dr. Li", "role": "GP"}},
+        "serviceRequested": {"coding": [{"system": "http://snomed.info/sct", "code": "183519002", "display": "Referral to cardiology service"}]},
+        "reasonCode": [{"coding": [{"system": "http://snomed.info/sct", "code": "49436004", "display": "Atrial fibrillation"}]}],
+        "supportingInfo": [{"reference": "LabReport/lab-1"}]
+    }))
+    .unwrap()
+}
+
+#[test]
+fn round_trips_and_validates() {
+    let referral = referral();
+    assert!(referral.is_valid(), "{:?}", referral.validate());
+    let json = serde_json::to_value(&referral).unwrap();
+    assert_eq!(json["supportingInfo"][0]["reference"], "LabReport/lab-1");
+    assert_eq!(Referral::from_json_value(&json).unwrap(), referral);
+}
+
+#[test]
+fn follows_the_status_lifecycle() {
+    let mut referral = referral();
+    referral.recipient = serde_json::from_value(json!({"organization": {"id": "org-9", "name": "Heart Centre"}})).unwrap();
+    referral.set_status(ReferralStatus::Active).unwrap();
+    referral.set_status(ReferralStatus::Completed).unwrap();
+    assert!(referral.status.is_terminal());
+
+    let err = referral.set_status(ReferralStatus::Active).unwrap_err();
+    assert!(matches!(err, WellAllyError::Validation { .. }));
+    assert_eq!(err.path(), "status");
+    assert_eq!(err.to_string(), r#"Referral at status: cannot change status from "completed" to "active" (got "active")"#);
+    referral.set_status(ReferralStatus::EnteredInError).unwrap();
+}
+
+#[test]
+fn reports_incomplete_parties_and_bad_references() {
+    let mut referral = referral();
+    referral.status = ReferralStatus::Active;
+    referral.requester.practitioner = None;
+    referral.supporting_info = Some(vec![Reference { reference: "lab-1".into(), display: None }]);
+    let paths: Vec<_> = referral.validate().into_iter().map(|issue| issue.path).collect();
+    assert_eq!(paths, ["requester", "recipient", "supportingInfo[0].reference"]);
+}
+
+#[test]
+fn references_point_to_resources() {
+    let report: LabReport = serde_json::from_value(json!({
+        "id": "lab-1", "patientId": "patient-1", "issuedAt": "2024-11-02T09:30:00Z", "results": []
+    }))
+    .unwrap();
+    let reference = Reference::to(&report);
+    assert_eq!(reference.reference, "LabReport/lab-1");
+    assert!(reference.points_to(&report));
+    assert!(referral().supporting_info.unwrap()[0].points_to(&report));
+}
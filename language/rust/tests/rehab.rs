@@ -0,0 +1,83 @@
+//! Checks rehabilitation sessions.
+
+use serde_json::json;
+use wellally::catalog::{concept, LOINC, SNOMED_CT};
+use wellally::rehab::{self, Exercise, FunctionalScore, RehabSession};
+use wellally::{Coding, Quantity, Resource, Validate};
+
+mod common;
+
+const EXAMPLE: &str = include_str!("../../../infrastructure/schemas/rehab/examples/rehab.knee-physiotherapy.json");
+
+fn physiotherapy() -> Coding {
+    Coding { system: SNOMED_CT.into(), code: "91251008".into(), display: None }
+}
+
+fn lefs(score: f64) -> FunctionalScore {
+    FunctionalScore { instrument: concept(LOINC, "75566-0", "LEFS total score"), score, max_score: Some(80.0) }
+}
+
+#[test]
+fn published_example_round_trips() {
+    let (session, written) = common::round_trip::<RehabSession>(EXAMPLE);
+    assert_eq!(session.exercises.len(), 3);
+    assert_eq!(session.therapist.as_ref().unwrap().reference, "Practitioner/pt-wang");
+    assert_eq!(written["exercises"][2]["durationSeconds"], 600);
+}
+
+#[test]
+fn derives_pain_change_and_volume() {
+    let session = RehabSession::from_json(EXAMPLE).unwrap();
+    assert_eq!(session.pain_change(), Some(-2));
+    assert_eq!(session.volume_kg(), 72.0);
+    assert_eq!(session.score("75566-0").map(|score| score.score), Some(42.0));
+    assert_eq!(session.score("9999-9"), None);
+
+    let banded = Exercise::new("Clamshell").with_sets(2, 15).with_resistance(Quantity::new(1.0, "{band}"));
+    assert_eq!(banded.volume_kg(), None);
+    let mut fresh = RehabSession::new("rehab-2", "p-1", physiotherapy(), "2024-10-15T09:00:00Z".parse().unwrap());
+    fresh.exercises.push(Exercise::new("Leg press").with_sets(3, 10).with_resistance(Quantity::new(20000.0, "g")));
+    fresh.pain_before = Some(4);
+    assert_eq!(fresh.pain_change(), None);
+    assert_eq!(fresh.volume_kg(), 600.0);
+}
+
+#[test]
+fn follows_scores_across_sessions() {
+    let session = |id: &str, start: &str, score: Option<f64>| {
+        let mut session = RehabSession::new(id, "p-1", physiotherapy(), start.parse().unwrap());
+        session.functional_scores = score.map(|score| vec![lefs(score)]);
+        session
+    };
+    let sessions = [
+        session("rehab-3", "2024-11-05T09:00:00+08:00", Some(61.0)),
+        session("rehab-1", "2024-10-08T09:00:00+08:00", Some(42.0)),
+        session("rehab-2", "2024-10-22T09:00:00+08:00", None),
+    ];
+    let series = rehab::score_series(&sessions, "75566-0");
+    let points: Vec<_> = series.iter().map(|point| (point.session.id.as_str(), point.score.score)).collect();
+    assert_eq!(points, [("rehab-1", 42.0), ("rehab-3", 61.0)]);
+}
+
+#[test]
+fn validation_reports_out_of_range_values() {
+    let session: RehabSession = serde_json::from_value(json!({
+        "id": "rehab-1", "patientId": "p-1",
+        "therapyType": {"system": SNOMED_CT, "code": "91251008"},
+        "start": "2024-10-08", "durationMinutes": 0,
+        "exercises": [{"name": "", "sets": 0, "repetitions": 10, "resistance": {"value": 2, "unit": "kg"}}],
+        "painBefore": 11, "painAfter": 3,
+        "functionalScores": [{
+            "instrument": {"coding": [{"system": LOINC, "code": "75566-0"}]}, "score": 90, "maxScore": 80
+        }]
+    }))
+    .unwrap();
+    let paths: Vec<_> = session.validate().into_iter().map(|issue| issue.path).collect();
+    assert_eq!(paths, [
+        "durationMinutes",
+        "exercises[0].name",
+        "exercises[0].sets",
+        "painBefore",
+        "functionalScores[0].score"
+    ]);
+}
@@ -0,0 +1,93 @@
+//! Checks related persons: roles, the period they are in force and the permissions they hold on the record.
+
+use wellally::authz::{Action, Scope};
+use wellally::relationships::{self, RelatedPerson, RelationshipRole};
+use wellally::{Resource, Validate};
+
+mod common;
+
+const GUARDIAN: &str =
+    include_str!("../../../infrastructure/schemas/related-person/examples/related-person.guardian.json");
+const PROXY: &str =
+    include_str!("../../../infrastructure/schemas/related-person/examples/related-person.healthcare-proxy.json");
+
+fn date(text: &str) -> chrono::NaiveDate {
+    text.parse().unwrap()
+}
+
+#[test]
+fn published_examples_round_trip() {
+    let (mother, written) = common::round_trip::<RelatedPerson>(GUARDIAN);
+    assert_eq!(mother.person_id.as_deref(), Some("person-003"));
+    assert_eq!(written["relationship"], serde_json::json!(["parent", "guardian"]));
+    assert_eq!(written["permissions"][1], "patient/ImmunizationRecord.cu");
+
+    let (proxy, written) = common::round_trip::<RelatedPerson>(PROXY);
+    assert!(proxy.has_role(RelationshipRole::HealthcareProxy));
+    assert_eq!(written["period"]["start"], "2023-07-01");
+    assert!(written["period"].get("end").is_none());
+}
+
+#[test]
+fn guardian_permissions_apply_while_in_force() {
+    let mother = RelatedPerson::from_json(GUARDIAN).unwrap();
+    assert!(mother.validate().is_empty(), "{:?}", mother.validate());
+    assert!(mother.has_role(RelationshipRole::Guardian));
+    assert!(!mother.has_role(RelationshipRole::Spouse));
+
+    let today = date("2024-09-01");
+    assert!(mother.permits("LabReport", Action::Read, today));
+    assert!(mother.permits("ImmunizationRecord", Action::Create, today));
+    assert!(!mother.permits("ImmunizationRecord", Action::Delete, today));
+    assert!(mother.permits("Task", Action::Delete, today));
+    assert!(!mother.permits("LabReport", Action::Read, date("2034-03-12")));
+
+    let token = mother.authorization();
+    assert_eq!(token.patient(), Some("person-child-001"));
+    assert!(!token.permits("LabReport", Action::Read, "person-003"));
+}
+
+#[test]
+fn acting_for_lists_linked_patients_in_force() {
+    let mother = RelatedPerson::from_json(GUARDIAN).unwrap();
+    let mut proxy = RelatedPerson::from_json(PROXY).unwrap();
+    assert!(proxy.validate().is_empty(), "{:?}", proxy.validate());
+    assert_eq!(relationships::acting_for(&[mother.clone(), proxy.clone()], "person-003", date("2024-01-01")).len(), 1);
+
+    proxy.person_id = Some("person-003".into());
+    let links = [mother, proxy.clone()];
+    let patients: Vec<_> = relationships::acting_for(&links, "person-003", date("2024-01-01"))
+        .into_iter()
+        .map(|link| link.patient_id.as_str())
+        .collect();
+    assert_eq!(patients, ["person-child-001", "person-elder-001"]);
+    assert_eq!(relationships::acting_for(&links, "person-003", date("2023-01-01")).len(), 1);
+
+    proxy.permissions.clear();
+    assert!(relationships::acting_for(&[proxy], "person-003", date("2024-01-01")).is_empty());
+}
+
+#[test]
+fn permissions_round_trip_as_scope_strings() {
+    let mut contact = RelatedPerson::new("rp-9", "p-1", RelationshipRole::EmergencyContact);
+    contact.permissions = vec![Scope::parse("patient/Person.r").unwrap()];
+    let json = serde_json::to_string(&contact).unwrap();
+    assert!(json.contains(r#""relationship":["emergency-contact"]"#), "{}", json);
+    assert!(json.contains(r#""permissions":["patient/Person.r"]"#), "{}", json);
+    assert_eq!(RelatedPerson::from_json(&json).unwrap(), contact);
+
+    let invalid = json.replace("patient/Person.r", "patient/Person.x");
+    assert!(RelatedPerson::from_json(&invalid).is_err());
+}
+
+#[test]
+fn validation_reports_bad_links_and_scopes() {
+    let mut mother = RelatedPerson::from_json(GUARDIAN).unwrap();
+    mother.relationship.clear();
+    mother.person_id = Some("person-child-001".into());
+    mother.permissions.push(Scope::parse("user/*.read").unwrap());
+    let period = mother.period.as_mut().unwrap();
+    period.end = Some(date("2010-01-01"));
+    let paths: Vec<_> = mother.validate().into_iter().map(|issue| issue.path).collect();
+    assert_eq!(paths, ["relationship", "personId", "period.end", "permissions[3]"]);
+}
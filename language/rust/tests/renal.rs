@@ -0,0 +1,85 @@
+//! Checks renal dose-adjustment advisories from eGFR results.
+
+use serde_json::json;
+use wellally::catalog::{coding, DRUGS, RXNORM};
+use wellally::safety::renal::{latest_egfr, RenalAction, RenalRule, RenalRules};
+use wellally::{LabReport, MedicationRecord, Quantity, Resource};
+
+fn egfr(id: &str, patient: &str, issued_at: &str, value: f64) -> LabReport {
+    LabReport::from_json_value(&json!({
+        "id": id, "patientId": patient, "issuedAt": issued_at,
+        "results": [
+            {"code": {"coding": [{"system": "http://loinc.org", "code": "2160-0"}]}, "value": {"value": 1.9, "unit": "mg/dL"}},
+            {"code": {"coding": [{"system": "http://loinc.org", "code": "62238-1"}]}, "value": {"value": value, "unit": "mL/min/{1.73_m2}"}}
+        ]
+    }))
+    .unwrap()
+}
+
+fn metformin(patient: &str) -> MedicationRecord {
+    let drug = DRUGS.iter().find(|drug| drug.code == "860975").unwrap();
+    drug.record("med-metformin".into(), patient.into(), "2024-01-01".parse().unwrap(), 365)
+}
+
+fn rules() -> RenalRules {
+    let mut rules = RenalRules::new();
+    let metformin = coding(RXNORM, "6809", "metformin");
+    rules.register(
+        metformin.clone(),
+        RenalRule::reduce(45.0, "Maximum 1000 mg daily").with_max_daily_dose(Quantity::new(1000.0, "mg")),
+    );
+    rules.register(metformin, RenalRule::avoid(30.0, "Contraindicated"));
+    rules.register(coding(RXNORM, "29046", "lisinopril"), RenalRule::reduce(30.0, "Start at 2.5-5 mg"));
+    rules
+}
+
+#[test]
+fn uses_the_latest_egfr_before_the_day() {
+    let reports = [egfr("lab-1", "p-1", "2024-03-01", 52.0), egfr("lab-2", "p-1", "2024-09-01T08:00:00+08:00", 28.0)];
+    let latest = latest_egfr(&reports).unwrap();
+    assert_eq!((latest.value, latest.report_id.as_str()), (28.0, "lab-2"));
+
+    let advisories = rules().check(&reports, &[metformin("p-1")], "2024-10-01".parse().unwrap());
+    assert_eq!(advisories.len(), 1);
+    assert_eq!(advisories[0].action, RenalAction::Avoid);
+    assert_eq!(advisories[0].egfr_report_id, "lab-2");
+    assert_eq!(advisories[0].below_egfr, 30.0);
+
+    // In June only the March result was available.
+    assert!(rules().check(&reports, &[metformin("p-1")], "2024-06-01".parse().unwrap()).is_empty());
+}
+
+#[test]
+fn reduce_dose_rules_respect_the_daily_limit() {
+    let reports = [egfr("lab-1", "p-1", "2024-09-01", 40.0)];
+    let mut med = metformin("p-1");
+    assert!(rules().check(&reports, std::slice::from_ref(&med), "2024-10-01".parse().unwrap()).is_empty());
+
+    med.frequency = Some("TID".into());
+    med.dosage.value = 1000.0;
+    let advisories = rules().check(&reports, &[med], "2024-10-01".parse().unwrap());
+    assert_eq!(advisories[0].action, RenalAction::ReduceDose);
+    assert_eq!(advisories[0].advice, "Maximum 1000 mg daily");
+}
+
+#[test]
+fn skips_inactive_medications_and_patients_without_egfr() {
+    let reports = [egfr("lab-1", "p-1", "2024-09-01", 20.0)];
+    let mut finished = metformin("p-1");
+    finished.duration_days = Some(30);
+    let unknown = metformin("p-2");
+    assert!(rules().check(&reports, &[finished, unknown], "2024-10-01".parse().unwrap()).is_empty());
+    assert!(rules().check(&[], &[metformin("p-1")], "2024-10-01".parse().unwrap()).is_empty());
+}
+
+#[test]
+fn medication_activity_follows_start_and_duration() {
+    let mut med = metformin("p-1");
+    med.duration_days = Some(10);
+    assert!(!med.is_active_on("2023-12-31".parse().unwrap()));
+    assert!(med.is_active_on("2024-01-10".parse().unwrap()));
+    assert!(!med.is_active_on("2024-01-11".parse().unwrap()));
+    med.duration_days = None;
+    med.end_date = None;
+    assert!(med.is_active_on("2030-01-01".parse().unwrap()));
+}
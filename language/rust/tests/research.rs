@@ -0,0 +1,184 @@
+//! Checks cohort selection and de-identified research datasets.
+
+use serde_json::{json, Value};
+use wellally::io::{AnyResource, Bundle};
+use wellally::research::{CohortBuilder, ColumnType, Criterion};
+use wellally::security::{self, BEHAVIORAL_HEALTH};
+use wellally::{Coding, Comparator, Period, Quantity};
+
+const ICD_10: &str = "http://hl7.org/fhir/sid/icd-10";
+const RXNORM: &str = "http://www.nlm.nih.gov/research/umls/rxnorm";
+
+fn coding(system: &str, code: &str) -> Coding {
+    Coding { system: system.into(), code: code.into(), display: None }
+}
+
+fn year(year: i32) -> Period {
+    Period { start: format!("{}-01-01", year).parse().ok(), end: format!("{}-12-31", year).parse().ok() }
+}
+
+fn resource(json: Value) -> AnyResource {
+    serde_json::from_value(json).unwrap()
+}
+
+fn person(id: &str, birth_date: &str) -> AnyResource {
+    resource(json!({
+        "id": id, "resourceType": "Person", "birthDate": birth_date, "gender": "female",
+        "name": [{"family": "Doe", "given": ["Jane"]}]
+    }))
+}
+
+fn condition(patient_id: &str, code: &str, onset: &str, abatement: Option<&str>) -> AnyResource {
+    let mut condition = json!({
+        "id": format!("cond-{}-{}", patient_id, code), "patientId": patient_id, "clinicalStatus": "active",
+        "onset": onset, "code": {"coding": [{"system": ICD_10, "code": code, "display": "Diagnosis"}]}
+    });
+    if let Some(abatement) = abatement {
+        condition["clinicalStatus"] = json!("resolved");
+        condition["abatement"] = json!(abatement);
+    }
+    resource(condition)
+}
+
+fn lab(patient_id: &str, loinc: &str, value: f64, unit: &str, issued_at: &str) -> AnyResource {
+    AnyResource::from_typed_json_value("LabReport", &json!({
+        "id": format!("lab-{}-{}", patient_id, loinc), "patientId": patient_id, "issuedAt": issued_at,
+        "results": [{
+            "code": {"coding": [{"system": "http://loinc.org", "code": loinc}]},
+            "value": {"value": value, "unit": unit}
+        }]
+    }))
+    .unwrap()
+}
+
+fn medication(patient_id: &str, code: &str, start_date: &str, duration_days: u32) -> AnyResource {
+    resource(json!({
+        "id": format!("med-{}-{}", patient_id, code), "patientId": patient_id,
+        "medication": {"system": RXNORM, "code": code, "display": "Medication"},
+        "dosage": {"value": 500, "unit": "mg"}, "route": {"system": "http://snomed.info/sct", "code": "26643006"},
+        "startDate": start_date, "durationDays": duration_days
+    }))
+}
+
+/// p-1 qualifies; p-2 is controlled, p-3 has no diabetes and p-4 is on insulin.
+fn bundles() -> Vec<Bundle> {
+    let mut resources = Vec::new();
+    for (id, hba1c, insulin) in [("p-1", 8.1, false), ("p-2", 6.2, false), ("p-3", 8.4, false), ("p-4", 9.0, true)] {
+        resources.push(person(id, "1980-04-12"));
+        if id != "p-3" {
+            resources.push(condition(id, "E11.9", "2019-03-01", None));
+        }
+        resources.push(lab(id, "4548-4", hba1c, "%", "2023-05-10T08:00:00Z"));
+        resources.push(medication(id, "6809", "2023-01-15", 90));
+        if insulin {
+            resources.push(medication(id, "5856", "2023-06-01", 30));
+        }
+    }
+    let (first, second) = resources.split_at(8);
+    vec![Bundle::new(first.to_vec()), Bundle::new(second.to_vec())]
+}
+
+fn diabetes() -> Criterion {
+    Criterion::condition([coding(ICD_10, "E11.9"), coding(ICD_10, "E11.65")])
+}
+
+fn uncontrolled() -> Criterion {
+    Criterion::lab(coding("http://loinc.org", "4548-4"), Comparator::GreaterOrEqual, Quantity::new(7.5, "%"))
+}
+
+#[test]
+fn selects_patients_meeting_every_criterion() {
+    let dataset = CohortBuilder::new("salt")
+        .include(diabetes())
+        .include(uncontrolled())
+        .include(Criterion::age(Some(18), Some(75)))
+        .exclude(Criterion::medication([coding(RXNORM, "5856")]))
+        .with_index_date("2023-12-31".parse().unwrap())
+        .with_window(year(2023))
+        .build(&bundles());
+
+    let manifest = &dataset.manifest;
+    assert_eq!((manifest.screened, manifest.selected), (4, 1));
+    let attrition: Vec<_> = manifest.attrition.iter().map(|step| (step.step.as_str(), step.remaining)).collect();
+    assert_eq!(attrition, [("inclusion[0]", 3), ("inclusion[1]", 2), ("inclusion[2]", 2), ("exclusion[0]", 1)]);
+
+    let [patient] = dataset.patients.as_slice() else { panic!("{:?}", dataset.patients) };
+    assert_eq!(patient.person_id, wellally::anonymize::Anonymizer::new("salt").pseudonym("p-1"));
+    assert_eq!((patient.birth_year, patient.age_at_index), (Some(1980), Some(43)));
+    assert_eq!(patient.gender.as_deref(), Some("female"));
+    assert_eq!(dataset.measurements[0].value, Some(8.1));
+    assert_eq!(dataset.medications[0].end_date, "2023-04-14".parse().ok());
+    assert!(dataset.conditions.iter().all(|row| row.person_id == patient.person_id));
+}
+
+#[test]
+fn windows_bound_criteria_and_exported_rows() {
+    let resources = vec![
+        condition("p-1", "E11.9", "2015-01-01", Some("2016-06-30")),
+        condition("p-1", "I10", "2020-02-01", None),
+        lab("p-1", "4548-4", 8.0, "%", "2021-03-01T08:00:00Z"),
+    ];
+    let bundles = [Bundle::new(resources)];
+    let builder = CohortBuilder::new("salt").with_window(year(2023));
+    assert_eq!(builder.clone().include(diabetes()).build(&bundles).manifest.selected, 0);
+    assert_eq!(builder.clone().include(uncontrolled()).build(&bundles).manifest.selected, 0);
+
+    let dataset = builder.include(diabetes().within(year(2016))).build(&bundles);
+    assert_eq!(dataset.manifest.selected, 1);
+    let codes: Vec<_> = dataset.conditions.iter().map(|row| row.code.as_str()).collect();
+    assert_eq!(codes, ["I10"]);
+    assert!(dataset.measurements.is_empty());
+}
+
+#[test]
+fn lab_thresholds_compare_in_the_threshold_unit() {
+    let bundles = [Bundle::new(vec![lab("p-1", "718-7", 110.0, "g/L", "2023-05-10T08:00:00Z")])];
+    let hemoglobin = coding("http://loinc.org", "718-7");
+    let anemia = |comparator| Criterion::lab(hemoglobin.clone(), comparator, Quantity::new(12.0, "g/dL"));
+    let builder = CohortBuilder::new("salt");
+    assert_eq!(builder.clone().include(anemia(Comparator::LessThan)).build(&bundles).manifest.selected, 1);
+    assert_eq!(builder.clone().include(anemia(Comparator::GreaterOrEqual)).build(&bundles).manifest.selected, 0);
+    let seconds = Quantity::new(1.0, "s");
+    let incommensurable = Criterion::lab(hemoglobin.clone(), Comparator::LessThan, seconds);
+    assert_eq!(builder.include(incommensurable).build(&bundles).manifest.selected, 0);
+}
+
+#[test]
+fn withheld_records_neither_qualify_nor_export() {
+    let mut depression = condition("p-1", "F32.1", "2023-02-01", None);
+    security::tag(&mut depression);
+    let bundles = [Bundle::new(vec![person("p-1", "1980-04-12"), depression])];
+    let builder = CohortBuilder::new("salt").withholding([security::category(BEHAVIORAL_HEALTH)]);
+    let depressive = Criterion::condition([coding(ICD_10, "F32.1")]);
+    assert_eq!(builder.clone().include(depressive).build(&bundles).manifest.selected, 0);
+    let dataset = builder.build(&bundles);
+    assert_eq!(dataset.patients.len(), 1);
+    assert!(dataset.conditions.is_empty());
+}
+
+#[test]
+fn manifest_records_criteria_and_table_schemas() {
+    let dataset = CohortBuilder::new("salt")
+        .include(uncontrolled())
+        .exclude(Criterion::age(None, Some(17)))
+        .with_index_date("2023-12-31".parse().unwrap())
+        .build(&bundles());
+    let manifest = serde_json::to_value(&dataset.manifest).unwrap();
+    assert_eq!(manifest["indexDate"], "2023-12-31");
+    assert_eq!(manifest["inclusion"][0]["criterion"], "lab");
+    assert_eq!(manifest["inclusion"][0]["comparator"], ">=");
+    assert_eq!(manifest["exclusion"][0], json!({"criterion": "age", "max": 17}));
+    let criterion: Criterion = serde_json::from_value(manifest["inclusion"][0].clone()).unwrap();
+    assert_eq!(criterion, uncontrolled());
+
+    let tables: Vec<_> = dataset.manifest.tables.iter().map(|table| table.name).collect();
+    assert_eq!(tables, ["patients", "conditions", "measurements", "medications"]);
+    let measurements = &dataset.manifest.tables[2];
+    assert_eq!(measurements.columns[3].column_type, ColumnType::Double);
+    let row = serde_json::to_value(&dataset.measurements[0]).unwrap();
+    let mut columns: Vec<_> = row.as_object().unwrap().keys().cloned().collect();
+    let mut expected: Vec<_> = measurements.columns.iter().map(|column| column.name.to_string()).collect();
+    columns.sort();
+    expected.sort();
+    assert_eq!(columns, expected);
+}
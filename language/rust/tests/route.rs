@@ -0,0 +1,54 @@
+//! Checks route of administration codes and their SNOMED CT mapping.
+
+use wellally::i18n::{Locale, LocalizedDisplay};
+use wellally::{Route, RouteCode};
+
+#[test]
+fn constructors_use_snomed_codes() {
+    let oral = Route::oral();
+    assert_eq!(oral.system, "http://snomed.info/sct");
+    assert_eq!(oral.code, "26643006");
+    assert_eq!(oral.display.as_deref(), Some("Oral route"));
+    assert_eq!(oral.route_code(), RouteCode::Oral);
+
+    assert_eq!(Route::intravenous().code, "47625008");
+    assert_eq!(Route::inhalation().route_code(), RouteCode::Inhalation);
+    assert_eq!(Route::nasal().route_code().abbreviation(), "NAS");
+
+    let other = Route::new(RouteCode::Other("419993007".into()));
+    assert_eq!(other.code, "419993007");
+    assert_eq!(other.display, None);
+}
+
+#[test]
+fn every_known_route_maps_both_ways() {
+    assert_eq!(RouteCode::KNOWN.len(), 11);
+    for route in RouteCode::KNOWN {
+        let snomed = route.snomed_code().unwrap();
+        assert_eq!(&RouteCode::from_snomed(snomed), route);
+        assert_eq!(&RouteCode::from_abbreviation(route.abbreviation()), route);
+        assert_eq!(&Route::new(route.clone()).route_code(), route);
+    }
+    assert_eq!(RouteCode::from_snomed("PO"), RouteCode::Other("PO".into()));
+    assert_eq!(RouteCode::Other("X".into()).snomed_code(), None);
+}
+
+#[test]
+fn from_str_accepts_abbreviations_codes_and_names() {
+    assert_eq!("po".parse::<RouteCode>().unwrap(), RouteCode::Oral);
+    assert_eq!("Oral".parse::<RouteCode>().unwrap(), RouteCode::Oral);
+    assert_eq!("SQ".parse::<RouteCode>().unwrap(), RouteCode::Subcutaneous);
+    assert_eq!(" 78421000 ".parse::<RouteCode>().unwrap(), RouteCode::Intramuscular);
+    assert_eq!("Intrathecal".parse::<RouteCode>().unwrap(), RouteCode::Other("Intrathecal".into()));
+    assert!("  ".parse::<RouteCode>().is_err());
+
+    let legacy = Route { system: "http://snomed.info/sct".into(), code: "PO".into(), display: None };
+    assert_eq!(legacy.route_code(), RouteCode::Oral);
+    assert_eq!(RouteCode::Topical.to_string(), "TOP");
+}
+
+#[test]
+fn localized_labels() {
+    assert_eq!(RouteCode::Oral.localized_display(Locale::Zh), "口服");
+    assert_eq!(RouteCode::Other("IT".into()).localized_display(Locale::En), "Other route");
+}
@@ -0,0 +1,99 @@
+//! Checks prescription allergy cross-checks against drug class tables.
+
+use wellally::catalog::{coding, concept, DRUGS, RXNORM};
+use wellally::safety::{allergy_check, AllergyConflict, DrugClassMap};
+use wellally::{AllergyClinicalStatus, AllergyIntolerance, CodeableConcept, Criticality, MedicationRecord, Resource, Validate, VerificationStatus};
+
+mod common;
+
+const EXAMPLE: &str =
+    include_str!("../../../infrastructure/schemas/allergy-intolerance/examples/allergy-intolerance.penicillin.json");
+
+fn classes() -> DrugClassMap {
+    let mut classes = DrugClassMap::new();
+    classes.insert("penicillins", coding(RXNORM, "7980", "penicillin G"));
+    classes.insert("penicillins", coding(RXNORM, "723", "amoxicillin"));
+    classes.insert("cephalosporins", coding(RXNORM, "2231", "cephalexin"));
+    classes.cross_sensitive("penicillins", "cephalosporins");
+    classes
+}
+
+fn prescription(code: &str) -> MedicationRecord {
+    let drug = DRUGS.iter().find(|drug| drug.code == code).unwrap();
+    drug.record("med-1".into(), "p-1".into(), "2024-10-15".parse().unwrap(), 7)
+}
+
+fn amoxicillin() -> MedicationRecord {
+    prescription("308182")
+}
+
+#[test]
+fn published_example_round_trips() {
+    let (allergy, written) = common::round_trip::<AllergyIntolerance>(EXAMPLE);
+    assert_eq!(allergy.criticality, Some(Criticality::High));
+    assert_eq!(written["type"], "allergy");
+}
+
+#[test]
+fn validation_reports_incomplete_allergies() {
+    let mut allergy = AllergyIntolerance::from_json(EXAMPLE).unwrap();
+    allergy.patient_id.clear();
+    allergy.reactions.as_mut().unwrap()[0].manifestation.clear();
+    let paths: Vec<_> = allergy.validate().into_iter().map(|issue| issue.path).collect();
+    assert_eq!(paths, ["patientId", "reactions[0].manifestation"]);
+}
+
+#[test]
+fn flags_a_direct_ingredient_by_code_or_name() {
+    let by_name = AllergyIntolerance::new("al-1", "p-1", CodeableConcept { coding: Vec::new(), text: Some("Amoxicillin".into()) });
+    let alerts = allergy_check(&[by_name], &amoxicillin(), &DrugClassMap::new());
+    assert_eq!(alerts.len(), 1);
+    assert_eq!(alerts[0].conflict, AllergyConflict::Ingredient);
+    assert_eq!(alerts[0].substance, "Amoxicillin");
+    assert_eq!(alerts[0].medication_id, "med-1");
+
+    let by_code = AllergyIntolerance::new("al-2", "p-1", concept(RXNORM, "308182", "amoxicillin 500 MG Oral Capsule"));
+    assert_eq!(allergy_check(&[by_code], &amoxicillin(), &DrugClassMap::new())[0].conflict, AllergyConflict::Ingredient);
+}
+
+#[test]
+fn flags_the_same_class_and_cross_sensitive_classes() {
+    let mut penicillin = AllergyIntolerance::new("al-1", "p-1", concept(RXNORM, "7980", "penicillin G"));
+    penicillin.criticality = Some(Criticality::High);
+    let alerts = allergy_check(&[penicillin], &amoxicillin(), &classes());
+    assert_eq!(alerts[0].conflict, AllergyConflict::SameClass { class: "penicillins".into() });
+    assert_eq!(alerts[0].criticality, Some(Criticality::High));
+
+    let cephalexin = AllergyIntolerance::new("al-2", "p-1", concept(RXNORM, "2231", "cephalexin"));
+    let alerts = allergy_check(std::slice::from_ref(&cephalexin), &amoxicillin(), &classes());
+    assert_eq!(
+        alerts[0].conflict,
+        AllergyConflict::CrossSensitivity { allergy_class: "cephalosporins".into(), drug_class: "penicillins".into() }
+    );
+
+    let mut one_way = DrugClassMap::new();
+    one_way.insert("cephalosporins", coding(RXNORM, "2231", "cephalexin"));
+    one_way.insert("penicillins", coding(RXNORM, "723", "amoxicillin"));
+    assert!(allergy_check(&[cephalexin], &amoxicillin(), &one_way).is_empty());
+}
+
+#[test]
+fn ignores_unrelated_inactive_and_other_patients_allergies() {
+    let penicillin = || AllergyIntolerance::new("al-1", "p-1", concept(RXNORM, "7980", "penicillin G"));
+    let lisinopril = prescription("314076");
+    assert!(allergy_check(&[penicillin()], &lisinopril, &classes()).is_empty());
+
+    let mut resolved = penicillin();
+    resolved.clinical_status = Some(AllergyClinicalStatus::Resolved);
+    let mut refuted = penicillin();
+    refuted.verification_status = Some(VerificationStatus::Refuted);
+    let mut someone_else = penicillin();
+    someone_else.patient_id = "p-2".into();
+    assert!(allergy_check(&[resolved, refuted, someone_else], &amoxicillin(), &classes()).is_empty());
+}
+
+#[test]
+fn names_match_whole_words_only() {
+    let fragment = AllergyIntolerance::new("al-1", "p-1", CodeableConcept { coding: Vec::new(), text: Some("cillin".into()) });
+    assert!(allergy_check(&[fragment], &amoxicillin(), &DrugClassMap::new()).is_empty());
+}
@@ -0,0 +1,129 @@
+//! Checks security labels, their derivation from clinical codes and their
+//! use by consent filtering and anonymization.
+
+use serde_json::{json, Value};
+use wellally::anonymize::Anonymizer;
+use wellally::catalog::{ACT_CODE, CONFIDENTIALITY};
+use wellally::consent::{AccessOutcome, ConsentFilter};
+use wellally::io::{AnyResource, Bundle};
+use wellally::security::{self, Classifier, SecurityLabeled, BEHAVIORAL_HEALTH, HIV, RESTRICTED, SUBSTANCE_USE};
+use wellally::{Coding, Condition, ConsentDecision, ConsentRecord, MedicationRecord, Purpose, Resource, Validate};
+
+const ICD_10: &str = "http://hl7.org/fhir/sid/icd-10";
+
+fn condition(system: &str, code: &str) -> Condition {
+    Condition::from_json_value(&json!({
+        "id": "cond-1", "patientId": "p-1", "clinicalStatus": "active",
+        "code": {"coding": [{"system": system, "code": code}]}
+    }))
+    .unwrap()
+}
+
+fn lab_report(loinc: &str) -> AnyResource {
+    AnyResource::from_typed_json_value("LabReport", &json!({
+        "id": "lab-1", "patientId": "p-1", "issuedAt": "2024-05-20T08:40:00Z",
+        "results": [{
+            "code": {"coding": [{"system": "http://loinc.org", "code": loinc}]},
+            "value": {"value": 1, "unit": "{index}"}
+        }]
+    }))
+    .unwrap()
+}
+
+#[test]
+fn labels_round_trip() {
+    let labeled = |mut value: Value| {
+        value["securityLabels"] = json!([{"system": CONFIDENTIALITY, "code": "V", "display": "very restricted"}]);
+        AnyResource::from_json_value(&value).unwrap()
+    };
+    let resource = labeled(serde_json::to_value(condition(ICD_10, "I10")).unwrap());
+    assert!(resource.has_security_label(&security::confidentiality(security::VERY_RESTRICTED)));
+    let written = serde_json::to_value(&resource).unwrap();
+    assert_eq!(written["securityLabels"][0]["display"], "very restricted");
+
+    let medication: MedicationRecord = serde_json::from_value(json!({
+        "id": "med-1", "patientId": "p-1",
+        "medication": {"system": "http://www.nlm.nih.gov/research/umls/rxnorm", "code": "6448", "display": "Lithium"},
+        "dosage": {"value": 300, "unit": "mg"}, "route": {"system": "http://snomed.info/sct", "code": "26643006"},
+        "startDate": "2024-01-01", "security_labels": [{"system": ACT_CODE, "code": "BH"}]
+    }))
+    .unwrap();
+    assert_eq!(medication.all_security_labels(), [security::category(BEHAVIORAL_HEALTH)]);
+}
+
+#[test]
+fn classifier_derives_categories_from_codes() {
+    let depression = condition(ICD_10, "F32.1");
+    assert_eq!(
+        security::classify(&depression),
+        [security::category(BEHAVIORAL_HEALTH), security::confidentiality(RESTRICTED)]
+    );
+    assert_eq!(security::classify(&condition(ICD_10, "F10.2"))[0].code, SUBSTANCE_USE);
+    assert!(security::classify(&condition(ICD_10, "I10")).is_empty());
+    assert_eq!(security::classify(&lab_report("75622-1"))[0], security::category(HIV));
+
+    let custom = Classifier::new(|coding: &Coding| (coding.code == "I10").then(|| security::category("CARDIO")));
+    assert_eq!(custom.labels(&condition(ICD_10, "I10"))[0].code, "CARDIO");
+}
+
+#[test]
+fn tagging_adds_only_missing_labels() {
+    let mut depression = condition(ICD_10, "F32.1");
+    depression.add_security_label(security::confidentiality(security::VERY_RESTRICTED));
+    assert_eq!(security::tag(&mut depression), 1);
+    assert_eq!(
+        depression.all_security_labels(),
+        [security::confidentiality(security::VERY_RESTRICTED), security::category(BEHAVIORAL_HEALTH)]
+    );
+    assert_eq!(security::tag(&mut depression), 0);
+    let displayed = Coding { display: Some("behavioral health".into()), ..security::category(BEHAVIORAL_HEALTH) };
+    assert!(!depression.add_security_label(displayed));
+}
+
+#[test]
+fn validates_labels() {
+    let mut hypertension = condition(ICD_10, "I10");
+    hypertension.security_labels = Some(vec![
+        security::confidentiality("X"),
+        Coding { system: String::new(), code: "BH".into(), display: None },
+    ]);
+    let paths: Vec<_> = hypertension.validate().into_iter().map(|issue| issue.path).collect();
+    assert!(paths.contains(&"securityLabels[0].code".to_string()), "{:?}", paths);
+    assert!(paths.iter().any(|path| path.starts_with("securityLabels[1]")), "{:?}", paths);
+}
+
+#[test]
+fn consent_filtering_reads_explicit_labels() {
+    let mut hypertension = condition(ICD_10, "I10");
+    hypertension.add_security_label(security::category(HIV));
+    let bundle = Bundle::new(vec![hypertension.into()]);
+    let mut consent = ConsentRecord::new("consent-1", "p-1", ConsentDecision::Permit);
+    consent.provisions = Some(vec![wellally::ConsentProvision {
+        decision: ConsentDecision::Deny,
+        purposes: Some(vec![Purpose::Research]),
+        actors: None,
+        categories: Some(vec![security::category(HIV)]),
+        resource_types: None,
+        period: None,
+    }]);
+    let filtered = ConsentFilter::new(&[consent]).apply(&bundle, Purpose::Research, "Organization/research-1");
+    assert_eq!(filtered.decisions[0].outcome, AccessOutcome::Excluded);
+    assert_eq!(filtered.decisions[0].categories, [security::category(HIV)]);
+}
+
+#[test]
+fn anonymizer_withholds_sensitive_resources() {
+    let mut labeled = condition(ICD_10, "I10");
+    labeled.add_security_label(security::category(BEHAVIORAL_HEALTH));
+    let resources = [
+        AnyResource::from(condition(ICD_10, "F32.1")),
+        labeled.into(),
+        lab_report("75622-1"),
+        condition(ICD_10, "I10").into(),
+    ];
+    let anonymizer = Anonymizer::new("secret").withholding([security::category(BEHAVIORAL_HEALTH)]);
+    assert!(anonymizer.is_withheld(&resources[0]) && anonymizer.is_withheld(&resources[1]));
+    let shared = anonymizer.anonymize_all(&resources).unwrap();
+    assert_eq!(shared.iter().map(AnyResource::resource_type).collect::<Vec<_>>(), ["LabReport", "Condition"]);
+    assert_eq!(Anonymizer::new("secret").anonymize_all(&resources).unwrap().len(), 4);
+}
@@ -0,0 +1,112 @@
+//! Tests for the SQLite store and its change feed.
+
+#![cfg(feature = "sqlite")]
+
+use serde_json::json;
+use wellally::sqlite::{Change, SqliteStore, SyncCursor};
+use wellally::LabReport;
+
+fn report(id: &str, patient_id: &str) -> LabReport {
+    serde_json::from_value(json!({
+        "id": id,
+        "patientId": patient_id,
+        "issuedAt": "2024-11-02T09:30:00Z",
+        "results": [{"code": {"coding": [{"system": "http://loinc.org", "code": "2345-7"}]},
+                     "value": {"value": 5.4, "unit": "mmol/L"}}]
+    }))
+    .unwrap()
+}
+
+#[test]
+fn saved_resources_load_back() {
+    let store = SqliteStore::open_in_memory().unwrap();
+    let original = report("lab-1", "p-1");
+    store.save(&original).unwrap();
+    assert_eq!(store.load::<LabReport>("lab-1").unwrap(), Some(original));
+    assert_eq!(store.load::<LabReport>("lab-2").unwrap(), None);
+
+    let mut updated = report("lab-1", "p-1");
+    updated.panel = Some(wellally::catalog::concept(wellally::catalog::LOINC, "24323-8", "Metabolic panel"));
+    store.save(&updated).unwrap();
+    assert_eq!(store.load::<LabReport>("lab-1").unwrap(), Some(updated));
+}
+
+#[test]
+fn loads_by_patient() {
+    let store = SqliteStore::open_in_memory().unwrap();
+    for (id, patient_id) in [("lab-2", "p-1"), ("lab-1", "p-1"), ("lab-3", "p-2")] {
+        store.save(&report(id, patient_id)).unwrap();
+    }
+    store.delete::<LabReport>("lab-2").unwrap();
+
+    let ids = |patient_id| {
+        store.load_by_patient::<LabReport>(patient_id).unwrap().into_iter().map(|r| r.id).collect::<Vec<_>>()
+    };
+    assert_eq!(ids("p-1"), ["lab-1"]);
+    assert_eq!(ids("p-2"), ["lab-3"]);
+    assert!(ids("p-3").is_empty());
+}
+
+#[test]
+fn deletes_leave_tombstones_in_the_change_feed() {
+    let store = SqliteStore::open_in_memory().unwrap();
+    store.save(&report("lab-1", "p-1")).unwrap();
+    let cursor = store.changes_since::<LabReport>(SyncCursor::start(), 10).unwrap().cursor;
+
+    assert!(store.delete::<LabReport>("lab-1").unwrap());
+    assert!(!store.delete::<LabReport>("lab-1").unwrap());
+    assert_eq!(store.load::<LabReport>("lab-1").unwrap(), None);
+
+    let batch = store.changes_since::<LabReport>(cursor, 10).unwrap();
+    assert_eq!(batch.changes, [Change::Deleted("lab-1".into())]);
+    assert!(batch.cursor > cursor);
+}
+
+#[test]
+fn pages_through_changes_with_the_cursor() {
+    let store = SqliteStore::open_in_memory().unwrap();
+    for id in ["lab-1", "lab-2", "lab-3", "lab-4", "lab-5"] {
+        store.save(&report(id, "p-1")).unwrap();
+    }
+
+    let mut cursor = SyncCursor::start();
+    let mut pages = Vec::new();
+    loop {
+        let batch = store.changes_since::<LabReport>(cursor, 2).unwrap();
+        if batch.changes.is_empty() {
+            assert_eq!(batch.cursor, cursor);
+            break;
+        }
+        let ids: Vec<_> = batch
+            .changes
+            .iter()
+            .map(|change| match change {
+                Change::Saved(report) => report.id.clone(),
+                Change::Deleted(id) => id.clone(),
+            })
+            .collect();
+        pages.push(ids);
+        cursor = batch.cursor;
+    }
+    assert_eq!(pages, [vec!["lab-1", "lab-2"], vec!["lab-3", "lab-4"], vec!["lab-5"]]);
+}
+
+#[test]
+fn timestamps_increase_past_equal_updated_at() {
+    let store = SqliteStore::open_in_memory().unwrap();
+    store.save(&report("lab-1", "p-1")).unwrap();
+    store.save(&report("lab-2", "p-1")).unwrap();
+    // Both rows at the same instant, ahead of the clock
+    let future = chrono::Utc::now().timestamp_millis() + 60_000;
+    store.connection().execute("UPDATE resources SET updated_at = ?1", [future]).unwrap();
+
+    let saved = store.save(&report("lab-3", "p-1")).unwrap();
+    assert_eq!(saved.timestamp_millis(), future + 1);
+    let next = store.save(&report("lab-4", "p-1")).unwrap();
+    assert!(next > saved);
+
+    let cursor = SyncCursor { updated_at: chrono::DateTime::from_timestamp_millis(future).unwrap() };
+    let batch = store.changes_since::<LabReport>(cursor, 10).unwrap();
+    assert_eq!(batch.changes.len(), 2);
+    assert_eq!(batch.cursor.updated_at, next);
+}
@@ -0,0 +1,89 @@
+//! Property tests for the resource strategies.
+
+#![cfg(feature = "proptest")]
+
+use std::collections::BTreeSet;
+
+use proptest::prelude::*;
+use proptest::strategy::ValueTree;
+use proptest::test_runner::TestRunner;
+use wellally::io::AnyResource;
+use wellally::strategies::{self, lab_report_for, medication_record_for};
+use wellally::{FamilyHealthTree, ImagingReport, LabReport, MedicationRecord, Person, RelationToProband, Validate};
+
+fn round_trips<T>(value: &T) -> bool
+where
+    T: serde::Serialize + serde::de::DeserializeOwned + PartialEq,
+{
+    serde_json::from_value::<T>(serde_json::to_value(value).unwrap()).unwrap() == *value
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn resources_are_valid_and_round_trip(
+        person in any::<Person>(),
+        report in any::<LabReport>(),
+        imaging in any::<ImagingReport>(),
+        medication in any::<MedicationRecord>(),
+        tree in any::<FamilyHealthTree>(),
+    ) {
+        prop_assert!(person.validate().is_empty(), "{:?}", person.validate());
+        prop_assert!(report.validate().is_empty(), "{:?}", report.validate());
+        prop_assert!(imaging.validate().is_empty(), "{:?}", imaging.validate());
+        prop_assert!(medication.validate().is_empty(), "{:?}", medication.validate());
+        prop_assert!(tree.validate().is_empty(), "{:?}", tree.validate());
+        prop_assert!(round_trips(&person) && round_trips(&report) && round_trips(&imaging));
+        prop_assert!(round_trips(&medication) && round_trips(&tree));
+    }
+
+    #[test]
+    fn records_belong_to_the_patient(
+        report in lab_report_for("p-1".into()),
+        medication in medication_record_for("p-1".into()),
+    ) {
+        prop_assert_eq!(&report.patient_id, "p-1");
+        prop_assert!(!report.results.is_empty());
+        prop_assert_eq!(&medication.patient_id, "p-1");
+        prop_assert!(medication.last_date().is_none_or(|end| end >= medication.start_date));
+    }
+
+    #[test]
+    fn family_trees_are_rooted_at_the_proband(tree in strategies::family_health_tree_for("p-1".into(), 1980)) {
+        prop_assert_eq!(&tree.proband_id, "p-1");
+        prop_assert_eq!(tree.members[0].relation_to_proband, RelationToProband::Self_);
+        for member in &tree.members[1..] {
+            let year = member.birth_year.unwrap();
+            match member.relation_to_proband {
+                RelationToProband::Mother | RelationToProband::Father => prop_assert!(year < 1980),
+                RelationToProband::Child => prop_assert!(year > 1980),
+                _ => {}
+            }
+        }
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(1024))]
+
+    #[test]
+    fn any_resource_is_valid_and_round_trips(resource in any::<AnyResource>()) {
+        prop_assert!(resource.validate().is_empty(), "{} {:?}", resource.resource_type(), resource.validate());
+        let value = serde_json::to_value(&resource).unwrap();
+        prop_assert_eq!(AnyResource::from_typed_json_value(resource.resource_type(), &value).unwrap(), resource);
+    }
+}
+
+#[test]
+fn any_resource_covers_every_resource_type() {
+    let mut runner = TestRunner::deterministic();
+    let strategy = strategies::any_resource();
+    let mut seen = BTreeSet::new();
+    for _ in 0..2000 {
+        seen.insert(strategy.new_tree(&mut runner).unwrap().current().resource_type());
+    }
+    for resource_type in AnyResource::RESOURCE_TYPES {
+        assert!(seen.contains(resource_type), "no strategy generates {}", resource_type);
+    }
+}
@@ -0,0 +1,142 @@
+//! Tests for streaming validation of NDJSON, arrays and bundles.
+
+#![cfg(feature = "std")]
+
+use serde_json::{Map, Value};
+use wellally::validation::{stream_validate, StreamIssue, StreamSummary};
+
+const VALID_REPORT: &str = r#"{"id":"lab-1","patientId":"p-1","issuedAt":"2024-11-02T09:30:00Z",
+    "results":[{"code":{"coding":[{"system":"http://loinc.org","code":"5196-1"}]},"value":"negative"}]}"#;
+
+fn run(input: &str) -> (StreamSummary, Vec<StreamIssue>) {
+    let mut issues = Vec::new();
+    let summary = stream_validate(input.as_bytes(), |issue| issues.push(issue)).unwrap();
+    (summary, issues)
+}
+
+/// `example` with every camelCase key written in snake_case.
+fn snake_case(example: &str) -> String {
+    fn convert(value: Value) -> Value {
+        match value {
+            Value::Object(map) => Value::Object(
+                map.into_iter()
+                    .map(|(key, value)| {
+                        let mut snake = String::new();
+                        for c in key.chars() {
+                            if c.is_ascii_uppercase() {
+                                snake.push('_');
+                            }
+                            snake.push(c.to_ascii_lowercase());
+                        }
+                        (snake, convert(value))
+                    })
+                    .collect::<Map<_, _>>(),
+            ),
+            Value::Array(items) => Value::Array(items.into_iter().map(convert).collect()),
+            value => value,
+        }
+    }
+    convert(serde_json::from_str(example).unwrap()).to_string()
+}
+
+fn detects_snake_case(example: &str, resource_type: &str) {
+    let mut record: Value = serde_json::from_str(&snake_case(example)).unwrap();
+    record.as_object_mut().unwrap().remove("resource_type");
+    let (summary, issues) = run(&record.to_string());
+    assert_eq!((summary.records, summary.invalid), (1, 0), "{:?}", issues);
+    record["id"] = "".into();
+    let (summary, issues) = run(&record.to_string());
+    assert_eq!(summary.invalid, 1);
+    assert_eq!((issues[0].resource_type, issues[0].issue.path.as_str()), (Some(resource_type), "id"));
+}
+
+#[test]
+fn ndjson_issues_carry_record_type_and_id() {
+    let input = [
+        VALID_REPORT.replace('\n', ""),
+        r#"{"id":"lab-2","patientId":"","issuedAt":"2024-11-02T09:30:00Z","results":[]}"#.into(),
+        r#"{"id":"p-9","birthDate":"1960-02-30"}"#.into(),
+    ]
+    .join("\n");
+    let (summary, issues) = run(&input);
+    assert_eq!(summary.records, 3);
+    assert_eq!(summary.invalid, 2);
+    assert_eq!(summary.issues, issues.len());
+
+    let lab = issues.iter().find(|issue| issue.issue.path == "patientId").unwrap();
+    assert_eq!((lab.record, lab.resource_type, lab.id.as_deref()), (1, Some("LabReport"), Some("lab-2")));
+    let person = issues.iter().find(|issue| issue.record == 2).unwrap();
+    assert_eq!((person.resource_type, person.id.as_deref()), (Some("Person"), Some("p-9")));
+    assert_eq!(person.issue.path, "birthDate");
+}
+
+#[test]
+fn arrays_and_bundles_are_read_entry_by_entry() {
+    let array = format!("[{}, {{\"id\":\"x\"}}]", VALID_REPORT);
+    let (summary, issues) = run(&array);
+    assert_eq!((summary.records, summary.invalid), (2, 1));
+    assert_eq!((issues[0].record, issues[0].resource_type), (1, None));
+    assert_eq!(issues[0].issue.path, "");
+
+    let bundle = format!(r#"{{"resourceType":"Bundle","entry":[{{"resource":{}}},{{"resource":7}}]}}"#, VALID_REPORT);
+    let (summary, issues) = run(&bundle);
+    assert_eq!((summary.records, summary.invalid, summary.issues), (2, 1, 1));
+    assert_eq!(issues[0].record, 1);
+}
+
+#[test]
+fn wrong_shapes_report_the_field_and_malformed_json_stops() {
+    let input = r#"{"resourceType":"LabReport","id":"lab-3","patientId":"p-1","issuedAt":5,"results":[]}"#;
+    let (summary, issues) = run(input);
+    assert_eq!(summary.invalid, 1);
+    assert_eq!(issues[0].id.as_deref(), Some("lab-3"));
+    assert_eq!(issues[0].issue.path, "issuedAt");
+
+    let mut seen = 0;
+    let input = format!("{}\n{{\"id\":", VALID_REPORT.replace('\n', ""));
+    assert!(stream_validate(input.as_bytes(), |_| seen += 1).is_err());
+    assert_eq!(seen, 0);
+}
+
+#[test]
+fn detects_snake_case_dialysis_sessions() {
+    detects_snake_case(
+        include_str!("../../../infrastructure/schemas/dialysis/examples/dialysis.hemodialysis.json"),
+        "DialysisSession",
+    );
+}
+
+#[test]
+fn detects_snake_case_ecg_records() {
+    detects_snake_case(include_str!("../../../infrastructure/schemas/ecg/examples/ecg.smartwatch.json"), "EcgRecord");
+}
+
+#[test]
+fn detects_snake_case_glucose_series() {
+    detects_snake_case(
+        include_str!("../../../infrastructure/schemas/glucose-series/examples/glucose-series.overnight.json"),
+        "GlucoseSeries",
+    );
+}
+
+#[test]
+fn detects_snake_case_conditions() {
+    detects_snake_case(
+        include_str!("../../../infrastructure/schemas/condition/examples/condition.hypertension.json"),
+        "Condition",
+    );
+}
+
+#[test]
+fn allergies_without_reactions_are_not_conditions() {
+    let mut allergy: Value = serde_json::from_str(include_str!(
+        "../../../infrastructure/schemas/allergy-intolerance/examples/allergy-intolerance.penicillin.json"
+    ))
+    .unwrap();
+    let record = allergy.as_object_mut().unwrap();
+    record.remove("criticality");
+    record.remove("reactions");
+    detects_snake_case(&allergy.to_string(), "AllergyIntolerance");
+    allergy.as_object_mut().unwrap().remove("type");
+    detects_snake_case(&allergy.to_string(), "AllergyIntolerance");
+}
@@ -0,0 +1,129 @@
+//! Checks change sets, conflict detection and the stores they are applied to.
+
+use serde_json::json;
+use wellally::io::AnyResource;
+use wellally::sync::{apply_changeset, apply_changeset_with, ChangeSet, ConflictPolicy, MemoryStore, SyncStore};
+
+fn task(id: &str, description: &str) -> AnyResource {
+    serde_json::from_value(json!({"id": id, "patientId": "p-1", "status": "requested", "description": description})).unwrap()
+}
+
+fn upserts(resources: Vec<AnyResource>) -> ChangeSet {
+    ChangeSet { upserts: resources, ..ChangeSet::default() }
+}
+
+#[test]
+fn pulls_resume_from_the_cursor() {
+    let mut server = MemoryStore::new();
+    apply_changeset(&mut server, &upserts(vec![task("task-1", "Repeat CBC"), task("task-2", "Call back")])).unwrap();
+
+    let first = server.changes_since(None);
+    assert_eq!(first.upserts.len(), 2);
+    assert!(server.changes_since(first.cursor).is_empty());
+
+    server.remove("Task", "task-2").unwrap();
+    let second = server.changes_since(first.cursor);
+    assert!(second.upserts.is_empty());
+    assert_eq!(second.deletes, [("Task".to_string(), "task-2".to_string())]);
+    assert!(second.cursor > first.cursor);
+
+    let mut device = MemoryStore::new();
+    apply_changeset(&mut device, &first).unwrap();
+    let outcome = apply_changeset(&mut device, &second).unwrap();
+    assert_eq!(outcome.deleted, 1);
+    assert_eq!(device.resources().map(AnyResource::id).collect::<Vec<_>>(), ["task-1"]);
+}
+
+#[test]
+fn detects_conflicts_by_last_updated() {
+    let mut server = MemoryStore::new();
+    apply_changeset(&mut server, &upserts(vec![task("task-1", "Repeat CBC")])).unwrap();
+    let pulled = server.changes_since(None);
+
+    server.upsert(&task("task-1", "Repeat CBC fasting")).unwrap();
+    let push = ChangeSet {
+        upserts: vec![task("task-1", "Cancel"), task("task-3", "New")],
+        deletes: vec![("Task".into(), "task-1".into())],
+        cursor: pulled.cursor,
+    };
+    let outcome = apply_changeset(&mut server, &push).unwrap();
+    assert_eq!(outcome.upserted, 1);
+    assert_eq!(outcome.deleted, 0);
+    assert_eq!(outcome.conflicts.len(), 2);
+    assert_eq!(outcome.conflicts[0].id, "task-1");
+    assert_eq!(outcome.conflicts[0].incoming, Some(task("task-1", "Cancel")));
+    assert_eq!(outcome.conflicts[1].incoming, None);
+    assert_eq!(server.get("Task", "task-1"), Some(&task("task-1", "Repeat CBC fasting")));
+
+    let forced = ChangeSet { deletes: Vec::new(), ..push };
+    let outcome = apply_changeset_with(&mut server, &forced, ConflictPolicy::KeepIncoming).unwrap();
+    assert_eq!(outcome.upserted, 2);
+    assert_eq!(outcome.conflicts.len(), 2);
+    assert_eq!(server.get("Task", "task-1"), Some(&task("task-1", "Cancel")));
+}
+
+#[test]
+fn changes_without_a_cursor_conflict_with_stored_resources() {
+    let mut store = MemoryStore::new();
+    apply_changeset(&mut store, &upserts(vec![task("task-1", "Repeat CBC")])).unwrap();
+    let outcome = apply_changeset(&mut store, &upserts(vec![task("task-1", "Other")])).unwrap();
+    assert_eq!(outcome.conflicts.len(), 1);
+}
+
+#[test]
+fn change_sets_round_trip_through_json() {
+    let changes = ChangeSet {
+        upserts: vec![task("task-1", "Repeat CBC")],
+        deletes: vec![("LabReport".into(), "lab-1".into())],
+        cursor: Some("2024-11-02T09:30:00Z".parse().unwrap()),
+    };
+    let json = serde_json::to_value(&changes).unwrap();
+    assert_eq!(json["deletes"], json!([["LabReport", "lab-1"]]));
+    assert_eq!(serde_json::from_value::<ChangeSet>(json).unwrap(), changes);
+    assert_eq!(serde_json::from_value::<ChangeSet>(json!({})).unwrap(), ChangeSet::default());
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn sqlite_store_syncs_every_resource_type() {
+    use wellally::sqlite::{SqliteStore, SyncCursor};
+
+    let mut store = SqliteStore::open_in_memory().unwrap();
+    let report: AnyResource =
+        serde_json::from_value(json!({"id": "lab-1", "patientId": "p-1", "issuedAt": "2024-11-02T09:30:00Z", "results": []}))
+            .unwrap();
+    let outcome = apply_changeset(&mut store, &upserts(vec![task("task-1", "Repeat CBC"), report])).unwrap();
+    assert_eq!(outcome.upserted, 2);
+
+    let pulled = store.changeset_since(SyncCursor::start(), 10).unwrap();
+    assert_eq!(pulled.upserts.iter().map(AnyResource::resource_type).collect::<Vec<_>>(), ["Task", "LabReport"]);
+
+    let push = ChangeSet { deletes: vec![("LabReport".into(), "lab-1".into())], ..pulled.clone() };
+    let outcome = apply_changeset(&mut store, &ChangeSet { upserts: Vec::new(), ..push }).unwrap();
+    assert!(outcome.conflicts.is_empty());
+    assert_eq!(outcome.deleted, 1);
+
+    let cursor = SyncCursor { updated_at: pulled.cursor.unwrap() };
+    let after = store.changeset_since(cursor, 10).unwrap();
+    assert_eq!(after.deletes, [("LabReport".to_string(), "lab-1".to_string())]);
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn sqlite_changesets_carry_resources_without_distinguishing_fields() {
+    use wellally::sqlite::{SqliteStore, SyncCursor};
+    use wellally::{Flag, Location, Resource};
+
+    let store = SqliteStore::open_in_memory().unwrap();
+    let location = Location::from_json_value(&json!({"id": "loc-1", "name": "Clinic"})).unwrap();
+    let flag = Flag::from_json_value(&json!({
+        "id": "flag-1", "patientId": "p-1", "status": "active",
+        "code": {"coding": [{"system": "http://snomed.info/sct", "code": "59621000"}]}
+    }))
+    .unwrap();
+    store.save(&location).unwrap();
+    store.save(&flag).unwrap();
+
+    let pulled = store.changeset_since(SyncCursor::start(), 10).unwrap();
+    assert_eq!(pulled.upserts, [AnyResource::from(location), AnyResource::from(flag)]);
+}
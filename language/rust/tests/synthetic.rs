@@ -0,0 +1,66 @@
+//! Tests for the synthetic cohort generator.
+
+#![cfg(feature = "synthetic")]
+
+use wellally::synthetic::{self, Cohort, CohortConfig};
+use wellally::{RelationToProband, Validate};
+
+fn config(seed: u64) -> CohortConfig {
+    CohortConfig { patients: 8, seed, ..Default::default() }
+}
+
+#[test]
+fn equal_seeds_give_equal_cohorts() {
+    assert_eq!(synthetic::generate(config(7)), synthetic::generate(config(7)));
+    assert_ne!(synthetic::generate(config(7)), synthetic::generate(config(8)));
+    assert_eq!(Cohort::new(config(7)).count(), 8);
+}
+
+#[test]
+fn records_are_valid_and_belong_to_the_patient() {
+    for patient in synthetic::generate(config(1)) {
+        let id = &patient.person.id;
+        assert!(patient.person.validate().is_empty(), "{:?}", patient.person.validate());
+        for report in &patient.lab_reports {
+            assert_eq!(&report.patient_id, id);
+            assert!(report.validate().is_empty(), "{:?}", report.validate());
+        }
+        for medication in &patient.medications {
+            assert_eq!(&medication.patient_id, id);
+            assert!(medication.validate().is_empty(), "{:?}", medication.validate());
+        }
+        assert_eq!(&patient.family_tree.proband_id, id);
+        assert_eq!(patient.family_tree.members[0].relation_to_proband, RelationToProband::Self_);
+    }
+}
+
+#[test]
+fn lab_reports_are_chronological_within_the_period() {
+    let config = config(3);
+    for patient in synthetic::generate(config.clone()) {
+        assert!(!patient.lab_reports.is_empty());
+        let collected: Vec<_> = patient
+            .lab_reports
+            .iter()
+            .map(|report| report.specimen.as_ref().and_then(|specimen| specimen.collected_at).unwrap().to_utc())
+            .collect();
+        assert!(collected.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert!(collected[0].date_naive() >= config.start);
+        assert!(collected.last().unwrap().date_naive() <= config.end);
+        for report in &patient.lab_reports {
+            let collected_at = report.specimen.as_ref().and_then(|specimen| specimen.collected_at).unwrap();
+            assert!(report.issued_at.to_utc() > collected_at.to_utc());
+        }
+    }
+}
+
+#[test]
+fn treated_conditions_have_medications() {
+    let cohort = synthetic::generate(CohortConfig { patients: 40, ..Default::default() });
+    let with_conditions: Vec<_> =
+        cohort.iter().filter(|patient| patient.person.clinical_summary.is_some()).collect();
+    assert!(!with_conditions.is_empty());
+    assert!(with_conditions.iter().any(|patient| !patient.medications.is_empty()));
+    let mut healthy = cohort.iter().filter(|patient| patient.person.clinical_summary.is_none());
+    assert!(healthy.all(|patient| patient.medications.is_empty()));
+}
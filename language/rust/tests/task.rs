@@ -0,0 +1,54 @@
+//! Checks the task model, its lifecycle and due dates.
+
+use chrono::NaiveDate;
+use serde_json::json;
+use wellally::{FlexibleDate, Resource, Task, TaskStatus, Validate};
+
+fn task() -> Task {
+    Task::from_json_value(&json!({
+        "id": "task-1",
+        "patientId": "patient-1",
+        "status": "requested",
+        "priority": "routine",
+        "description": "Repeat CBC in 3 months",
+        "code": {"coding": [{"system": "http://loinc.org", "code": "58410-2", "display": "CBC panel"}]},
+        "authoredOn": "2024-11-02T09:30:00Z",
+        "dueDate": "2025-02",
+        "focus": {"reference": "LabReport/lab-1"},
+        "basedOn": [{"reference": "Referral/ref-1"}]
+    }))
+    .unwrap()
+}
+
+#[test]
+fn round_trips_and_validates() {
+    let task = task();
+    assert!(task.is_valid(), "{:?}", task.validate());
+    let json = serde_json::to_value(&task).unwrap();
+    assert_eq!(json["dueDate"], "2025-02");
+    assert_eq!(json["focus"]["reference"], "LabReport/lab-1");
+    assert_eq!(Task::from_json_value(&json).unwrap(), task);
+}
+
+#[test]
+fn partial_due_dates_are_due_at_the_end_of_the_period() {
+    let mut task = task();
+    assert!(!task.is_overdue(NaiveDate::from_ymd_opt(2025, 2, 28).unwrap()));
+    assert!(task.is_overdue(NaiveDate::from_ymd_opt(2025, 3, 1).unwrap()));
+
+    task.set_status(TaskStatus::InProgress).unwrap();
+    task.set_status(TaskStatus::Completed).unwrap();
+    assert!(!task.is_overdue(NaiveDate::from_ymd_opt(2025, 3, 1).unwrap()));
+    assert!(task.set_status(TaskStatus::InProgress).is_err());
+
+    assert_eq!(FlexibleDate::from_year(2024).unwrap().last_date(), NaiveDate::from_ymd_opt(2024, 12, 31).unwrap());
+}
+
+#[test]
+fn due_date_must_follow_authoring() {
+    let mut task = task();
+    task.due_date = Some("2024-10".parse().unwrap());
+    task.description = " ".into();
+    let paths: Vec<_> = task.validate().into_iter().map(|issue| issue.path).collect();
+    assert_eq!(paths, ["description", "dueDate"]);
+}
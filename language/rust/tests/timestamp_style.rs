@@ -0,0 +1,36 @@
+//! Tests for the process-wide timestamp serialization style.
+//!
+//! The style is global, so everything runs in one test of its own binary.
+
+use serde_json::json;
+use wellally::{set_timestamp_style, timestamp_style, FlexibleDateTime, LabReport, TimestampStyle};
+
+#[test]
+fn offsets_are_kept_unless_utc_is_requested() {
+    let report: LabReport = serde_json::from_value(json!({
+        "id": "lab-1",
+        "patientId": "p-1",
+        "issuedAt": "2024-11-02T09:30:00+08:00",
+        "specimen": {"collectedAt": "2024-11-02T07:15:00"},
+        "results": []
+    }))
+    .unwrap();
+    let partial: FlexibleDateTime = "2024-11".parse().unwrap();
+
+    assert_eq!(timestamp_style(), TimestampStyle::Original);
+    let original = serde_json::to_value(&report).unwrap();
+    assert_eq!(original["issuedAt"], "2024-11-02T09:30:00+08:00");
+    assert_eq!(original["specimen"]["collectedAt"], "2024-11-02T07:15:00");
+
+    set_timestamp_style(TimestampStyle::Utc);
+    assert_eq!(timestamp_style(), TimestampStyle::Utc);
+    let utc = serde_json::to_value(&report).unwrap();
+    assert_eq!(utc["issuedAt"], "2024-11-02T01:30:00Z");
+    assert_eq!(utc["specimen"]["collectedAt"], "2024-11-02T07:15:00");
+    assert_eq!(serde_json::to_value(partial).unwrap(), "2024-11");
+    assert_eq!(serde_json::from_value::<LabReport>(utc).unwrap().issued_at.to_utc(), report.issued_at.to_utc());
+
+    set_timestamp_style(TimestampStyle::Original);
+    assert_eq!(serde_json::to_value(&report).unwrap(), original);
+    assert_eq!(report.issued_at.offset().unwrap().local_minus_utc(), 8 * 3600);
+}
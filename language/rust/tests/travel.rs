@@ -0,0 +1,87 @@
+//! Checks travel health records.
+
+use wellally::travel::{Destination, TravelAdvisory, TravelHealthRecord, TravelVaccine, VaccineRequirement, CVX};
+use wellally::{Coding, Reference, Resource, Validate};
+
+mod common;
+
+const EXAMPLE: &str = include_str!("../../../infrastructure/schemas/travel/examples/travel.east-africa.json");
+
+fn cvx(code: &str) -> Coding {
+    Coding { system: CVX.into(), code: code.into(), display: None }
+}
+
+fn trip() -> TravelHealthRecord {
+    let mut trip = TravelHealthRecord::new("trip-1", "p-1", "2024-09-10".parse().unwrap());
+    trip.destinations.push(Destination::new("KE", "2024-10-05".parse().unwrap(), "2024-10-19".parse().unwrap()));
+    trip
+}
+
+#[test]
+fn published_example_round_trips() {
+    let (trip, written) = common::round_trip::<TravelHealthRecord>(EXAMPLE);
+    assert_eq!(trip.destinations.len(), 2);
+    assert_eq!(trip.vaccines[0].requirement, VaccineRequirement::Required);
+    assert_eq!(trip.malaria_prophylaxis[0].target(), Some(("MedicationRecord", "med-2024-09-10-atovaquone")));
+    assert!(trip.outstanding_vaccines().is_empty());
+    assert_eq!(written["destinations"][1]["country"], "TZ");
+    assert_eq!(written["vaccines"][2]["notGivenReason"]["coding"][0]["code"], "PATOBJ");
+}
+
+#[test]
+fn spans_the_destinations() {
+    let trip = TravelHealthRecord::from_json(EXAMPLE).unwrap();
+    assert_eq!(trip.departure_date(), Some("2024-10-05".parse().unwrap()));
+    assert_eq!(trip.return_date(), Some("2024-10-19".parse().unwrap()));
+    let country = |date: &str| trip.destination_on(date.parse().unwrap()).map(|d| d.country.as_str());
+    assert_eq!(country("2024-10-08"), Some("KE"));
+    assert_eq!(country("2024-10-12"), Some("TZ"));
+    assert_eq!(country("2024-10-20"), None);
+    assert_eq!(TravelHealthRecord::new("trip-2", "p-1", "2024-09-10".parse().unwrap()).departure_date(), None);
+
+    let advice: Vec<_> = trip.advisories_for("tz").iter().map(|a| a.issuer.as_deref()).collect();
+    assert_eq!(advice, [Some("WHO"), None]);
+    assert_eq!(trip.advisories_for("KE").len(), 1);
+}
+
+#[test]
+fn tracks_outstanding_vaccines() {
+    let mut trip = trip();
+    trip.vaccines.push(TravelVaccine::new(cvx("37"), VaccineRequirement::Required));
+    trip.vaccines.push(TravelVaccine::new(cvx("101"), VaccineRequirement::Recommended));
+    assert_eq!(trip.unmet_requirements().len(), 1);
+    assert_eq!(trip.outstanding_vaccines().len(), 2);
+
+    trip.vaccines[0].immunization = Some(Reference { reference: "ImmunizationRecord/imm-1".into(), display: None });
+    assert!(trip.vaccines[0].is_received());
+    assert!(trip.unmet_requirements().is_empty());
+    assert_eq!(trip.outstanding_vaccines()[0].vaccine.code, "101");
+}
+
+#[test]
+fn validation_reports_bad_destinations_and_references() {
+    let mut trip = trip();
+    trip.destinations.push(Destination::new("Kenya", "2024-10-19".parse().unwrap(), "2024-10-18".parse().unwrap()));
+    let mut vaccine = TravelVaccine::new(cvx("37"), VaccineRequirement::Required);
+    vaccine.immunization = Some(Reference { reference: "MedicationRecord/med-1".into(), display: None });
+    trip.vaccines.push(vaccine);
+    trip.malaria_prophylaxis.push(Reference { reference: "ImmunizationRecord/imm-1".into(), display: None });
+    trip.advisories.push(TravelAdvisory { text: " ".into(), country: None, topic: None, issuer: None });
+    let issues = trip.validate();
+    let paths: Vec<_> = issues.iter().map(|issue| issue.path.as_str()).collect();
+    assert_eq!(
+        paths,
+        [
+            "destinations[1].country",
+            "destinations[1].departure",
+            "vaccines[0].immunization.reference",
+            "malariaProphylaxis[0].reference",
+            "advisories[0].text",
+        ]
+    );
+    assert_eq!(issues[2].message, "must reference an ImmunizationRecord");
+
+    let empty = TravelHealthRecord::new("trip-2", "p-1", "2024-09-10".parse().unwrap());
+    let paths: Vec<_> = empty.validate().into_iter().map(|issue| issue.path).collect();
+    assert_eq!(paths, ["destinations"]);
+}
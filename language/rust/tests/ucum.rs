@@ -0,0 +1,120 @@
+//! Checks UCUM unit conversion and the arithmetic and ordering of quantities built on it.
+
+use std::cmp::Ordering;
+
+use wellally::{ucum, Comparator, Quantity, WellAllyError};
+
+fn close(a: f64, b: f64) -> bool {
+    (a - b).abs() <= 1e-9 * a.abs().max(b.abs())
+}
+
+#[test]
+fn converts_prefixed_and_derived_units() {
+    assert_eq!(ucum::convert(2.5, "kg", "g").unwrap(), 2500.0);
+    assert!(close(ucum::convert(180.0, "mg/dL", "g/L").unwrap(), 1.8));
+    assert!(close(ucum::convert(1.0, "[lb_av]", "kg").unwrap(), 0.453_592_37));
+    assert!(close(ucum::convert(1.0, "d", "h").unwrap(), 24.0));
+    assert!(close(ucum::convert(100.0, "mL/h", "L/d").unwrap(), 2.4));
+    assert!(close(ucum::convert(1.0, "kcal", "kJ").unwrap(), 4.184));
+    assert!(close(ucum::convert(4.5, "10*9/L", "10*3/uL").unwrap(), 4.5));
+    assert!(close(ucum::convert(1.0, "m2", "cm2").unwrap(), 10_000.0));
+    assert!(close(ucum::convert(60.0, "mL/min/{1.73_m2}", "mL/s").unwrap(), 1.0));
+    assert!(close(ucum::convert(50.0, "%", "1").unwrap(), 0.5));
+}
+
+#[test]
+fn identical_units_convert_outside_the_supported_subset() {
+    assert_eq!(ucum::convert(40.0, "[IU]", "[IU]").unwrap(), 40.0);
+    assert!(!ucum::commensurable("[IU]", "mg"));
+    assert!(!ucum::commensurable("Cel", "K"));
+}
+
+#[test]
+fn rejects_incompatible_dimensions() {
+    let err = ucum::convert(1.0, "mg", "mL").unwrap_err();
+    assert!(matches!(err, WellAllyError::Conversion { .. }));
+    assert_eq!(err.path(), "unit");
+    assert!(err.to_string().contains("incompatible dimensions"), "{}", err);
+
+    let err = ucum::convert(1.0, "mg", "furlong").unwrap_err();
+    assert!(err.to_string().contains("'furlong' is not a supported UCUM unit"), "{}", err);
+}
+
+#[test]
+fn rejects_exponents_out_of_range() {
+    for unit in ["m100.m100", "m200", "/m-128", "10*1000000000/L", "10*-400"] {
+        let err = ucum::convert(1.0, unit, "m").unwrap_err();
+        assert!(err.to_string().contains("is not a supported UCUM unit"), "{}", err);
+    }
+    assert!(close(ucum::convert(1.0, "m60.m60", "m120").unwrap(), 1.0));
+    assert_eq!(ucum::convert(1.0, "10*300", "10*299").unwrap(), 10.0);
+}
+
+#[test]
+fn sums_a_cumulative_dose_in_the_first_unit() {
+    let doses = [Quantity::new(0.5, "g"), Quantity::new(250.0, "mg"), Quantity::new(250_000.0, "ug")];
+    let total = doses.iter().try_fold(Quantity::new(0.0, "mg"), |sum, dose| sum + dose).unwrap();
+    assert_eq!(total.unit, "mg");
+    assert!(close(total.value, 1000.0));
+    assert!(total.equivalent(&Quantity::new(1.0, "g")));
+    assert_ne!(total, Quantity::new(1.0, "g"), "== keeps the unit");
+}
+
+#[test]
+fn computes_a_fluid_balance() {
+    let intake = (Quantity::new(1.2, "L") + Quantity::new(350.0, "mL")).unwrap();
+    let balance = (intake - Quantity::new(900.0, "mL")).unwrap();
+    assert_eq!(balance.unit, "L");
+    assert!(close(balance.value, 0.65));
+    assert!(balance > Quantity::new(0.0, "mL"));
+}
+
+#[test]
+fn arithmetic_fails_on_incompatible_units_and_limits() {
+    let err = (Quantity::new(1.0, "L") + Quantity::new(1.0, "kg")).unwrap_err();
+    assert!(err.to_string().contains("cannot convert kg to L"), "{}", err);
+
+    let limit = Quantity { comparator: Some(Comparator::LessThan), ..Quantity::new(5.0, "mg") };
+    let err = (Quantity::new(1.0, "mg") - limit).unwrap_err();
+    assert_eq!(err.path(), "comparator");
+}
+
+#[test]
+fn orders_commensurable_quantities() {
+    assert!(Quantity::new(2.0, "[lb_av]") < Quantity::new(1.0, "kg"));
+    assert_eq!(Quantity::new(120.0, "min").compare(&Quantity::new(2.0, "h")), Some(Ordering::Equal));
+    assert_eq!(Quantity::new(120.0, "min").partial_cmp(&Quantity::new(2.0, "h")), None);
+    assert_eq!(Quantity::new(1.0, "mg").partial_cmp(&Quantity::new(1.0, "mL")), None);
+    assert_ne!(Quantity::new(1.0, "mg"), Quantity::new(1.0, "mL"));
+}
+
+#[test]
+fn equality_compares_value_unit_and_code() {
+    let inches = Quantity { code: Some("[in_i]".into()), ..Quantity::new(70.0, "in") };
+    assert_eq!(inches, inches.clone());
+    assert_ne!(inches, Quantity::new(70.0, "in"));
+    assert_ne!(Quantity::new(1.0, "g"), Quantity::new(1000.0, "mg"));
+    assert!(Quantity::new(1.0, "g").equivalent(&Quantity::new(1000.0, "mg")));
+    assert!(!Quantity::new(1.0, "mg").equivalent(&Quantity::new(1.0, "mL")));
+}
+
+#[test]
+fn limits_only_equal_the_same_limit() {
+    let below = |value: f64, unit: &str| Quantity { comparator: Some(Comparator::LessThan), ..Quantity::new(value, unit) };
+    assert!(below(5.0, "mg").equivalent(&below(0.005, "g")));
+    assert!(!below(5.0, "mg").equivalent(&Quantity::new(5.0, "mg")));
+    assert_ne!(below(5.0, "mg"), Quantity::new(5.0, "mg"));
+    assert_eq!(below(5.0, "mg").partial_cmp(&below(6.0, "mg")), None);
+}
+
+#[test]
+fn converts_quantities_keeping_the_comparator() {
+    let height = Quantity { code: Some("[in_i]".into()), ..Quantity::new(70.0, "in") };
+    let converted = height.convert_to("cm").unwrap();
+    assert!(close(converted.value, 177.8));
+    assert_eq!(converted.unit, "cm");
+    assert_eq!(converted.code, None);
+
+    let limit = Quantity { comparator: Some(Comparator::GreaterThan), ..Quantity::new(10.0, "mmol/L") };
+    assert_eq!(limit.convert_to("umol/L").unwrap().comparator, Some(Comparator::GreaterThan));
+}
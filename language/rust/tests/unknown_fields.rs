@@ -0,0 +1,59 @@
+//! Checks that unknown properties survive read-modify-write cycles.
+
+#![cfg(feature = "preserve-unknown")]
+
+use serde_json::json;
+use wellally::conformance::{self, ConformanceError};
+use wellally::{MedicationRecord, Resource, Task, TaskStatus, UnknownFields};
+
+fn newer_task() -> serde_json::Value {
+    json!({
+        "id": "task-1",
+        "patientId": "patient-1",
+        "status": "requested",
+        "description": "Repeat CBC in 3 months",
+        "reminderPolicy": {"channel": "sms", "daysBefore": 3},
+        "assignedTeam": "follow-up"
+    })
+}
+
+#[test]
+fn unknown_properties_survive_modification() {
+    let mut task = Task::from_json_value(&newer_task()).unwrap();
+    assert_eq!(task.unknown_fields().unwrap()["assignedTeam"], "follow-up");
+
+    task.set_status(TaskStatus::Accepted).unwrap();
+    let json = serde_json::to_value(&task).unwrap();
+    assert_eq!(json["status"], "accepted");
+    assert_eq!(json["reminderPolicy"], newer_task()["reminderPolicy"]);
+    assert_eq!(json["assignedTeam"], "follow-up");
+
+    let taken = task.take_unknown_fields();
+    assert_eq!(taken.len(), 2);
+    assert!(serde_json::to_value(&task).unwrap().get("assignedTeam").is_none());
+}
+
+#[test]
+fn known_aliases_are_not_unknown() {
+    let task = Task::from_json_value(&json!({
+        "id": "task-1",
+        "patient_id": "patient-1",
+        "status": "requested",
+        "description": "Call back",
+        "due_date": "2025-01"
+    }))
+    .unwrap();
+    assert!(task.unknown_fields().unwrap().is_empty());
+    assert_eq!(task.patient_id, "patient-1");
+}
+
+#[test]
+fn conformance_still_reports_unknown_properties() {
+    let json = r#"{"id":"med-1","patientId":"p-1","medication":{"system":"s","code":"c"},
+        "dosage":{"value":5,"unit":"mg"},"route":{"system":"s","code":"c"},
+        "startDate":"2024-01-01","colour":"blue"}"#;
+    assert!(matches!(
+        conformance::check::<MedicationRecord>(json),
+        Err(ConformanceError::Mismatch { pointer, .. }) if pointer == "/colour"
+    ));
+}
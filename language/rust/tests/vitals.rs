@@ -0,0 +1,145 @@
+//! Checks NEWS2 scoring against the RCP chart.
+
+use wellally::catalog::{concept, LOINC};
+use wellally::vitals::scoring::{news2, AlertThresholds, Parameter, RiskLevel, SpO2Scale};
+use wellally::vitals::{Consciousness, Vitals};
+use wellally::{LabResult, LabValue, Quantity};
+
+fn normal() -> Vitals {
+    Vitals {
+        respiratory_rate: Some(16.0),
+        oxygen_saturation: Some(97.0),
+        supplemental_oxygen: Some(false),
+        systolic_blood_pressure: Some(120.0),
+        pulse: Some(72.0),
+        consciousness: Some(Consciousness::Alert),
+        temperature: Some(36.8),
+    }
+}
+
+#[test]
+fn normal_vitals_score_zero() {
+    let score = news2(&normal(), SpO2Scale::Scale1, &AlertThresholds::default());
+    assert_eq!(score.total, 0);
+    assert_eq!(score.risk, RiskLevel::Low);
+    assert!(score.is_complete());
+    assert_eq!(score.contributing().count(), 0);
+}
+
+#[test]
+fn deteriorating_patient() {
+    let vitals = Vitals {
+        respiratory_rate: Some(23.0),     // 2
+        oxygen_saturation: Some(93.0),    // 2
+        supplemental_oxygen: Some(true),  // 2
+        pulse: Some(112.0),               // 2
+        temperature: Some(38.4),          // 1
+        ..normal()
+    };
+    let score = news2(&vitals, SpO2Scale::Scale1, &AlertThresholds::default());
+    assert_eq!(score.total, 9);
+    assert_eq!(score.risk, RiskLevel::High);
+    assert_eq!(score.contributing().count(), 5);
+}
+
+#[test]
+fn single_red_score_is_low_medium() {
+    let vitals = Vitals { consciousness: Some(Consciousness::Confusion), ..normal() };
+    let score = news2(&vitals, SpO2Scale::Scale1, &AlertThresholds::default());
+    assert_eq!(score.total, 3);
+    assert_eq!(score.risk, RiskLevel::LowMedium);
+    let strict = AlertThresholds { medium: 3, ..AlertThresholds::default() };
+    assert_eq!(news2(&vitals, SpO2Scale::Scale1, &strict).risk, RiskLevel::Medium);
+}
+
+#[test]
+fn scale_two_scores_high_saturation_on_oxygen() {
+    let vitals = Vitals { oxygen_saturation: Some(97.0), supplemental_oxygen: Some(true), ..normal() };
+    let score = news2(&vitals, SpO2Scale::Scale2, &AlertThresholds::default());
+    let spo2 = score.components.iter().find(|c| c.parameter == Parameter::OxygenSaturation).unwrap();
+    assert_eq!(spo2.score, 3);
+    assert_eq!(score.total, 5);
+}
+
+#[test]
+fn vitals_from_coded_results() {
+    let result = |code: &str, value: f64, unit: &str| LabResult {
+        code: concept(LOINC, code, "vital sign"),
+        value: LabValue::Quantity(Quantity::new(value, unit)),
+        reference_range: None,
+        interpretation: None,
+        method: None,
+        effective: None,
+    };
+    let vitals = Vitals::from_results(&[result("9279-1", 18.0, "/min"), result("8310-5", 100.4, "[degF]")]);
+    assert_eq!(vitals.respiratory_rate, Some(18.0));
+    assert!((vitals.temperature.unwrap() - 38.0).abs() < 1e-9);
+    let score = news2(&vitals, SpO2Scale::Scale1, &AlertThresholds::default());
+    assert_eq!(score.missing.len(), 5);
+}
+
+#[test]
+fn blood_pressure_categories() {
+    use wellally::vitals::{AccAhaCategory, BloodPressure, Jnc7Category};
+
+    let cases = [
+        (118.0, 76.0, AccAhaCategory::Normal, Jnc7Category::Normal),
+        (124.0, 78.0, AccAhaCategory::Elevated, Jnc7Category::Prehypertension),
+        (118.0, 84.0, AccAhaCategory::Stage1, Jnc7Category::Prehypertension),
+        (142.0, 88.0, AccAhaCategory::Stage2, Jnc7Category::Stage1),
+        (150.0, 101.0, AccAhaCategory::Stage2, Jnc7Category::Stage2),
+        (185.0, 110.0, AccAhaCategory::HypertensiveCrisis, Jnc7Category::Stage2),
+        (160.0, 125.0, AccAhaCategory::HypertensiveCrisis, Jnc7Category::Stage2),
+    ];
+    for (systolic, diastolic, acc_aha, jnc7) in cases {
+        let reading = BloodPressure::mm_hg(systolic, diastolic);
+        assert_eq!(reading.acc_aha_category(), Some(acc_aha), "{}/{}", systolic, diastolic);
+        assert_eq!(reading.jnc7_category(), Some(jnc7), "{}/{}", systolic, diastolic);
+    }
+}
+
+#[test]
+fn blood_pressure_converts_units_and_refuses_others() {
+    use wellally::vitals::{AccAhaCategory, BloodPressure};
+    use wellally::Comparator;
+
+    let kpa = BloodPressure {
+        systolic: Quantity::new(18.0, "kPa"),
+        diastolic: Quantity::new(11.0, "kPa"),
+        ..BloodPressure::mm_hg(0.0, 0.0)
+    };
+    let (systolic, diastolic) = kpa.values_mm_hg().unwrap();
+    assert!((systolic - 135.0).abs() < 0.1 && (diastolic - 82.5).abs() < 0.1);
+    assert_eq!(kpa.acc_aha_category(), Some(AccAhaCategory::Stage1));
+
+    let unitless = BloodPressure { systolic: Quantity::new(120.0, "1"), ..BloodPressure::mm_hg(0.0, 80.0) };
+    assert_eq!(unitless.acc_aha_category(), None);
+    let limit = BloodPressure {
+        diastolic: Quantity { comparator: Some(Comparator::LessThan), ..Quantity::new(40.0, "mm[Hg]") },
+        ..BloodPressure::mm_hg(70.0, 0.0)
+    };
+    assert_eq!(limit.values_mm_hg(), None);
+}
+
+#[test]
+fn blood_pressure_round_trips_through_component_results() {
+    use wellally::catalog::SNOMED_CT;
+    use wellally::vitals::{BloodPressure, BodyPosition, CuffSite, DIASTOLIC_BLOOD_PRESSURE, SYSTOLIC_BLOOD_PRESSURE};
+
+    let reading = BloodPressure {
+        position: Some(BodyPosition::Sitting),
+        cuff_site: Some(CuffSite::LeftUpperArm),
+        method: Some(concept(SNOMED_CT, "37931006", "Auscultation")),
+        ..BloodPressure::mm_hg(128.0, 84.0)
+    };
+    let results = reading.to_results();
+    assert_eq!(results[0].code.coding[0].code, SYSTOLIC_BLOOD_PRESSURE);
+    assert_eq!(results[1].code.coding[0].code, DIASTOLIC_BLOOD_PRESSURE);
+    assert_eq!(results[1].value, LabValue::Quantity(Quantity::new(84.0, "mm[Hg]")));
+    assert_eq!(results[1].method, reading.method);
+
+    let read = BloodPressure::from_results(&results).unwrap();
+    assert_eq!(read, BloodPressure { position: None, cuff_site: None, ..reading });
+    assert_eq!(Vitals::from_results(&results).systolic_blood_pressure, Some(128.0));
+    assert_eq!(BloodPressure::from_results(&results[..1]), None);
+}
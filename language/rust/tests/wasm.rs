@@ -0,0 +1,69 @@
+//! Tests for the JavaScript bindings that do not cross into JS values, so
+//! they run on the host target.
+
+#![cfg(feature = "wasm")]
+
+use serde_json::{json, Value};
+use wellally::wasm::{JsLabReport, JsMedicationRecord};
+use wellally::{LabReport, MedicationRecord};
+
+fn report_json() -> Value {
+    json!({
+        "id": "lab-1",
+        "patientId": "p-1",
+        "issuedAt": "2024-11-02T09:30:00+08:00",
+        "results": [{"code": {"coding": [{"system": "http://loinc.org", "code": "2345-7"}]},
+                     "value": {"value": 5.4, "unit": "mmol/L"}, "interpretation": "N"}]
+    })
+}
+
+#[test]
+fn json_round_trips_through_the_class() {
+    let js = JsLabReport::from_json(&report_json().to_string()).unwrap();
+    assert_eq!(js.id(), "lab-1");
+    assert_eq!(js.patient_id(), "p-1");
+    assert!(js.is_valid());
+
+    let text = js.to_json().unwrap();
+    assert_eq!(serde_json::from_str::<Value>(&text).unwrap(), report_json());
+    let model: LabReport = js.into();
+    assert_eq!(model, serde_json::from_value(report_json()).unwrap());
+}
+
+#[test]
+fn setters_update_the_model() {
+    let mut js = JsLabReport::from_json(&report_json().to_string()).unwrap();
+    js.set_patient_id("p-2".into());
+    js.set_issued_at("2024-11").unwrap();
+    assert_eq!(js.issued_at(), "2024-11");
+    let model: LabReport = js.into();
+    assert_eq!(model.patient_id, "p-2");
+}
+
+#[test]
+fn validation_matches_the_rust_rules() {
+    let mut report: LabReport = serde_json::from_value(report_json()).unwrap();
+    report.results.clear();
+    assert!(!JsLabReport::from(report).is_valid());
+}
+
+#[test]
+fn medication_dates_and_optional_fields() {
+    let record: MedicationRecord = serde_json::from_value(json!({
+        "id": "med-1",
+        "patientId": "p-1",
+        "medication": {"system": "http://www.nlm.nih.gov/research/umls/rxnorm", "code": "197361"},
+        "dosage": {"value": 5, "unit": "mg"},
+        "route": {"system": "http://snomed.info/sct", "code": "26643006"},
+        "startDate": "2024-01-01"
+    }))
+    .unwrap();
+    let mut js = JsMedicationRecord::from(record);
+    assert_eq!(js.start_date(), "2024-01-01");
+    assert_eq!(js.end_date(), None);
+    js.set_end_date(Some("2024-03-31".into())).unwrap();
+    js.set_frequency(Some("once daily".into()));
+    let model: MedicationRecord = js.into();
+    assert_eq!(model.end_date, Some("2024-03-31".parse().unwrap()));
+    assert_eq!(model.frequency.as_deref(), Some("once daily"));
+}
@@ -0,0 +1,80 @@
+//! Tests for webhook signature verification.
+
+#![cfg(feature = "webhook")]
+
+use chrono::{Duration, TimeZone, Utc};
+use wellally::events::EventType;
+use wellally::webhook::{self, sign, Verifier, WebhookError};
+
+const SECRET: &[u8] = b"whsec-123";
+
+fn body() -> &'static [u8] {
+    concat!(
+        r#"{"eventType":"deleted","eventVersion":"1.0.0","id":"lab-1","#,
+        r#""occurredAt":"2024-11-02T09:30:00Z","resourceType":"LabReport"}"#
+    )
+    .as_bytes()
+}
+
+#[test]
+fn signs_timestamp_and_body() {
+    let signed_at = Utc.with_ymd_and_hms(2024, 11, 2, 9, 30, 0).unwrap();
+    let header = sign(b"{}", b"secret", signed_at);
+    // HMAC-SHA256 keyed with "secret" of "1730539800.{}"
+    assert_eq!(header, "t=1730539800,v1=fdd110f71c0044d4199839788f84e341f9eb4e480b8d4d65c4be231334eb157b");
+    assert!(Verifier::new(b"secret".to_vec()).verify_at(b"{}", &header, signed_at).is_ok());
+}
+
+#[test]
+fn verifies_and_parses_events() {
+    let header = sign(body(), SECRET, Utc::now());
+    webhook::verify(body(), &header, SECRET).unwrap();
+    let event = webhook::verify_event(body(), &header, SECRET).unwrap();
+    assert_eq!(event.event_type, EventType::Deleted);
+    assert_eq!(event.key(), "LabReport/lab-1");
+}
+
+#[test]
+fn rejects_tampered_payloads_and_wrong_secrets() {
+    let header = sign(body(), SECRET, Utc::now());
+    let tampered = String::from_utf8(body().to_vec()).unwrap().replace("lab-1", "lab-2");
+    assert!(matches!(webhook::verify(tampered.as_bytes(), &header, SECRET), Err(WebhookError::SignatureMismatch)));
+    assert!(matches!(webhook::verify(body(), &header, b"other"), Err(WebhookError::SignatureMismatch)));
+}
+
+#[test]
+fn rejects_stale_deliveries() {
+    let now = Utc::now();
+    let header = sign(body(), SECRET, now - Duration::minutes(10));
+    assert!(matches!(webhook::verify(body(), &header, SECRET), Err(WebhookError::Expired { .. })));
+
+    let verifier = Verifier::new(SECRET).with_tolerance(Duration::minutes(15));
+    verifier.verify_at(body(), &header, now).unwrap();
+    let future = sign(body(), SECRET, now + Duration::minutes(20));
+    assert!(matches!(verifier.verify_at(body(), &future, now), Err(WebhookError::Expired { .. })));
+}
+
+#[test]
+fn accepts_any_signature_during_rotation() {
+    let now = Utc::now();
+    let old = sign(body(), b"old-secret", now);
+    let new = sign(body(), SECRET, now);
+    let header = format!("{}, {}", old, new.split_once(',').unwrap().1);
+    webhook::verify(body(), &header, SECRET).unwrap();
+    webhook::verify(body(), &header, b"old-secret").unwrap();
+}
+
+#[test]
+fn rejects_malformed_headers() {
+    for header in ["", "v1=abcd", "t=1730539800", "t=soon,v1=abcd", "t=1730539800,v1=xyz"] {
+        let err = webhook::verify(body(), header, SECRET).unwrap_err();
+        assert!(matches!(err, WebhookError::MalformedHeader(_)), "{}: {}", header, err);
+    }
+}
+
+#[test]
+fn reports_invalid_events_after_verification() {
+    let payload = br#"{"eventType":"created","resourceType":"Task"}"#;
+    let header = sign(payload, SECRET, Utc::now());
+    assert!(matches!(webhook::verify_event(payload, &header, SECRET), Err(WebhookError::Event(_))));
+}
@@ -0,0 +1,62 @@
+//! Tests for the compact wire JSON profile.
+
+use std::fs;
+use std::path::PathBuf;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{json, Value};
+use wellally::wire::{decode, encode, from_wire, to_wire, Wire};
+use wellally::{FamilyHealthTree, ImagingReport, InfusionRecord, LabReport, MedicationRecord, Person};
+
+fn round_trips<T: Serialize + DeserializeOwned + PartialEq + std::fmt::Debug>(schema: &str) {
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../infrastructure/schemas").join(schema);
+    let dir = dir.join("examples");
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        let canonical: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(decode(&encode(&canonical)), canonical, "{}", path.display());
+
+        let model: T = serde_json::from_value(canonical).unwrap();
+        let wire = to_wire(&model).unwrap();
+        assert_eq!(from_wire::<T>(&wire).unwrap(), model, "{}", path.display());
+        let Wire(back): Wire<T> = serde_json::from_str(&serde_json::to_string(&Wire(&model)).unwrap()).unwrap();
+        assert_eq!(back, model, "{}", path.display());
+    }
+}
+
+#[test]
+fn published_examples_round_trip_losslessly() {
+    round_trips::<LabReport>("lab-report");
+    round_trips::<ImagingReport>("imaging-report");
+    round_trips::<MedicationRecord>("medication");
+    round_trips::<Person>("health");
+    round_trips::<FamilyHealthTree>("family-health");
+    round_trips::<InfusionRecord>("infusion");
+}
+
+#[test]
+fn enumerated_values_and_systems_become_codes() {
+    let canonical = json!({
+        "gender": "female",
+        "coding": [{"system": "http://loinc.org", "code": "2345-7"}],
+        "interpretation": "H",
+        "relationToProband": "mother"
+    });
+    assert_eq!(encode(&canonical), json!({"ge": 1, "cd": [{"s": 2, "c": "2345-7"}], "in": 2, "rl": 1}));
+}
+
+#[test]
+fn unknown_names_and_values_pass_through() {
+    let canonical = json!({"gender": "nonbinary", "system": "urn:local", "custom": {"code": "x"}});
+    let wire = encode(&canonical);
+    assert_eq!(wire, json!({"ge": "nonbinary", "s": "urn:local", "custom": {"c": "x"}}));
+    assert_eq!(decode(&wire), canonical);
+    assert_eq!(decode(&json!({"ge": 99})), json!({"gender": 99}));
+}
+
+#[test]
+fn errors_use_canonical_paths() {
+    let err = from_wire::<LabReport>(&json!({"i": "lab-1", "p": "p-1", "ia": 5, "r": []})).unwrap_err();
+    assert_eq!(err.path(), "issuedAt");
+}
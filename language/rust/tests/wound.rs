@@ -0,0 +1,78 @@
+//! Checks wound assessments.
+
+use serde_json::json;
+use wellally::catalog::SNOMED_CT;
+use wellally::wound::{
+    self, ExudateAmount, PressureInjuryStage, TissueProportion, TissueType, WoundAssessment, WoundDimensions,
+};
+use wellally::{Coding, Quantity, Resource, Validate};
+
+mod common;
+
+const EXAMPLE: &str = include_str!("../../../infrastructure/schemas/wound/examples/wound.pressure-injury.json");
+
+fn sacrum() -> Coding {
+    Coding { system: SNOMED_CT.into(), code: "54735007".into(), display: None }
+}
+
+fn measured(id: &str, wound_id: &str, at: &str, length_cm: f64, width_cm: f64) -> WoundAssessment {
+    let mut assessment = WoundAssessment::new(id, "p-1", wound_id, sacrum(), at.parse().unwrap());
+    assessment.dimensions = Some(WoundDimensions::new(Quantity::new(length_cm, "cm"), Quantity::new(width_cm, "cm")));
+    assessment
+}
+
+#[test]
+fn published_example_round_trips() {
+    let (assessment, written) = common::round_trip::<WoundAssessment>(EXAMPLE);
+    assert_eq!(assessment.stage, Some(PressureInjuryStage::Stage3));
+    assert_eq!(assessment.exudate.as_ref().unwrap().amount, ExudateAmount::Moderate);
+    assert_eq!(assessment.dressing_plan.as_ref().unwrap().dressings.len(), 2);
+    assert_eq!(written["stage"], "stage-3");
+    assert_eq!(written["exudate"]["type"], "serosanguineous");
+}
+
+#[test]
+fn derives_area_and_tissue() {
+    let assessment = WoundAssessment::from_json(EXAMPLE).unwrap();
+    assert!((assessment.area_cm2().unwrap() - 12.6).abs() < 1e-9);
+    let volume = assessment.dimensions.as_ref().unwrap().volume_cm3().unwrap();
+    assert!((volume - 6.3).abs() < 1e-9, "{}", volume);
+    assert_eq!(assessment.tissue_percent(TissueType::Granulation), 70.0);
+    assert_eq!(assessment.tissue_percent(TissueType::Eschar), 0.0);
+    assert!(assessment.has_devitalized_tissue());
+
+    let mut clean = measured("wa-2", "wound-1", "2024-10-01", 2.0, 1.0);
+    clean.tissue = vec![TissueProportion { tissue: TissueType::Granulation, percent: 100.0 }];
+    assert!(!clean.has_devitalized_tissue());
+    assert_eq!(clean.dimensions.as_ref().unwrap().volume_cm3(), None);
+}
+
+#[test]
+fn tracks_area_reduction_per_wound() {
+    let assessments = [
+        measured("wa-3", "wound-1", "2024-10-29T10:00:00+08:00", 3.0, 2.0),
+        measured("wa-1", "wound-1", "2024-10-01T10:00:00+08:00", 4.0, 4.0),
+        measured("wa-9", "wound-2", "2024-10-15T10:00:00+08:00", 1.0, 1.0),
+        WoundAssessment::new("wa-2", "p-1", "wound-1", sacrum(), "2024-10-15".parse().unwrap()),
+    ];
+    let series = wound::area_series(&assessments, "wound-1");
+    let points: Vec<_> = series.iter().map(|point| (point.assessment.id.as_str(), point.area_cm2)).collect();
+    assert_eq!(points, [("wa-1", 16.0), ("wa-3", 6.0)]);
+    assert_eq!(wound::percent_area_reduction(&assessments, "wound-1"), Some(62.5));
+    assert_eq!(wound::percent_area_reduction(&assessments, "wound-2"), None);
+}
+
+#[test]
+fn validation_reports_inconsistent_assessments() {
+    let assessment: WoundAssessment = serde_json::from_value(json!({
+        "id": "wa-1", "patientId": "p-1", "woundId": " ",
+        "location": {"system": SNOMED_CT, "code": "54735007"},
+        "assessedAt": "2024-10-09",
+        "dimensions": {"length": {"value": 4, "unit": "cm"}, "width": {"value": 3, "unit": "mL"}},
+        "tissue": [{"tissue": "granulation", "percent": 80}, {"tissue": "slough", "percent": 40}],
+        "dressingPlan": {"changeEveryDays": 0}
+    }))
+    .unwrap();
+    let paths: Vec<_> = assessment.validate().into_iter().map(|issue| issue.path).collect();
+    assert_eq!(paths, ["woundId", "dimensions.width.unit", "tissue", "dressingPlan.changeEveryDays"]);
+}